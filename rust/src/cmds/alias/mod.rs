@@ -0,0 +1,170 @@
+use colored::Colorize;
+use serde::Serialize;
+
+/// 已弃用的命令名：旧路径 → 新路径
+#[derive(Debug, Clone, Copy)]
+pub struct CommandAlias {
+    pub old: &'static [&'static str],
+    pub new: &'static [&'static str],
+    /// 开始弃用的版本
+    pub since: &'static str,
+}
+
+/// 已弃用的选项；command 为空表示所有命令
+#[derive(Debug, Clone, Copy)]
+pub struct FlagAlias {
+    pub command: &'static [&'static str],
+    pub old: &'static str,
+    pub new: &'static str,
+    pub since: &'static str,
+}
+
+/// 旧版 rmmbox 与早期 Rust CLI 的命令名
+pub const COMMAND_ALIASES: &[CommandAlias] = &[
+    CommandAlias { old: &["install"], new: &["device", "install"], since: "0.3.0" },
+    CommandAlias { old: &["snapshot"], new: &["device", "snapshot"], since: "0.3.0" },
+    CommandAlias { old: &["rollback"], new: &["device", "rollback"], since: "0.3.0" },
+    CommandAlias { old: &["devices"], new: &["device", "list"], since: "0.3.0" },
+    CommandAlias { old: &["tree"], new: &["deps", "tree"], since: "0.3.0" },
+    CommandAlias { old: &["graph"], new: &["workspace", "graph"], since: "0.3.0" },
+    CommandAlias { old: &["template", "add"], new: &["template", "install"], since: "0.3.0" },
+];
+
+/// 改名的选项
+pub const FLAG_ALIASES: &[FlagAlias] = &[
+    FlagAlias { command: &[], old: "--path", new: "--project-path", since: "0.3.0" },
+    FlagAlias { command: &["build"], old: "--no-fix", new: "--no-auto-fix", since: "0.3.0" },
+];
+
+/// 一次弃用用法
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Deprecation {
+    /// command / flag
+    pub kind: &'static str,
+    pub old: String,
+    pub new: String,
+    pub since: &'static str,
+}
+
+impl Deprecation {
+    pub fn message(&self) -> String {
+        let kind = if self.kind == "flag" { "选项" } else { "命令" };
+        format!("{} '{}' 自 {} 起已弃用，请改用 '{}'", kind, self.old, self.since, self.new)
+    }
+
+    /// CI 中输出 GitHub Actions 注解，便于在运行摘要中看到需要迁移的脚本
+    pub fn print(&self) {
+        if std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true") {
+            eprintln!("::warning title=rmm deprecated {}::{}", self.kind, self.message());
+        } else {
+            eprintln!("{} {}", "[deprecated]".yellow().bold(), self.message());
+        }
+    }
+}
+
+/// 子命令在参数中的起始位置（跳过前置的全局选项）
+fn command_start(args: &[String]) -> usize {
+    args.iter().position(|arg| !arg.starts_with('-')).unwrap_or(args.len())
+}
+
+/// 将旧命令名与旧选项改写为当前写法（args 不含程序名），返回改写后的参数与弃用记录
+pub fn rewrite_args(args: &[String]) -> (Vec<String>, Vec<Deprecation>) {
+    let mut args = args.to_vec();
+    let mut deprecations = Vec::new();
+    let start = command_start(&args);
+
+    let matched = COMMAND_ALIASES.iter()
+        .filter(|alias| args.len() >= start + alias.old.len() && args[start..start + alias.old.len()].iter().zip(alias.old).all(|(a, b)| a == b))
+        .max_by_key(|alias| alias.old.len());
+    if let Some(alias) = matched {
+        args.splice(start..start + alias.old.len(), alias.new.iter().map(|s| s.to_string()));
+        deprecations.push(Deprecation {
+            kind: "command",
+            old: format!("rmm {}", alias.old.join(" ")),
+            new: format!("rmm {}", alias.new.join(" ")),
+            since: alias.since,
+        });
+    }
+
+    let end = args.iter().position(|arg| arg == "--").unwrap_or(args.len());
+    for index in start..end {
+        for alias in FLAG_ALIASES {
+            let applies = args.len() >= start + alias.command.len()
+                && args[start..start + alias.command.len()].iter().zip(alias.command).all(|(a, b)| a == b);
+            if !applies {
+                continue;
+            }
+            let rewritten = if args[index] == alias.old {
+                alias.new.to_string()
+            } else if let Some(value) = args[index].strip_prefix(alias.old).and_then(|rest| rest.strip_prefix('=')) {
+                format!("{}={}", alias.new, value)
+            } else {
+                continue;
+            };
+            args[index] = rewritten;
+            deprecations.push(Deprecation { kind: "flag", old: alias.old.to_string(), new: alias.new.to_string(), since: alias.since });
+        }
+    }
+    (args, deprecations)
+}
+
+/// `rmm help --deprecated`
+pub fn is_deprecated_help(args: &[String]) -> bool {
+    let start = command_start(args);
+    args.get(start).is_some_and(|arg| arg == "help") && args[start..].iter().any(|arg| arg == "--deprecated")
+}
+
+/// 打印全部弃用映射
+pub fn print_deprecated() {
+    println!("{} 已弃用的命令（仍可使用，会打印警告）:", "[deprecated]".yellow().bold());
+    for alias in COMMAND_ALIASES {
+        println!("    {:<24} → {:<28} {}", format!("rmm {}", alias.old.join(" ")), format!("rmm {}", alias.new.join(" ")).green(), format!("自 {}", alias.since).bright_black());
+    }
+    println!("{} 已弃用的选项:", "[deprecated]".yellow().bold());
+    for alias in FLAG_ALIASES {
+        let scope = if alias.command.is_empty() { "所有命令".to_string() } else { format!("rmm {}", alias.command.join(" ")) };
+        println!("    {:<24} → {:<28} {}", alias.old, alias.new.green(), format!("{}，自 {}", scope, alias.since).bright_black());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_rewrite_commands_and_flags() {
+        let (rewritten, deprecations) = rewrite_args(&args("--trace-config install demo.zip --path ./demo"));
+        assert_eq!(rewritten, args("--trace-config device install demo.zip --project-path ./demo"));
+        assert_eq!(deprecations.len(), 2);
+        assert_eq!(deprecations[0].old, "rmm install");
+        assert_eq!(deprecations[0].new, "rmm device install");
+        assert_eq!(deprecations[1].kind, "flag");
+
+        let (rewritten, _) = rewrite_args(&args("build --no-fix --path=./demo"));
+        assert_eq!(rewritten, args("build --no-auto-fix --project-path=./demo"));
+
+        // 只对匹配的命令生效，-- 之后的参数原样传递
+        let (rewritten, deprecations) = rewrite_args(&args("run --no-fix -- --path x"));
+        assert_eq!(rewritten, args("run --no-fix -- --path x"));
+        assert!(deprecations.is_empty());
+
+        let (rewritten, deprecations) = rewrite_args(&args("template add https://example.com/t.git"));
+        assert_eq!(rewritten, args("template install https://example.com/t.git"));
+        assert_eq!(deprecations[0].message(), "命令 'rmm template add' 自 0.3.0 起已弃用，请改用 'rmm template install'");
+
+        let (rewritten, deprecations) = rewrite_args(&args("device install"));
+        assert_eq!(rewritten, args("device install"));
+        assert!(deprecations.is_empty());
+    }
+
+    #[test]
+    fn test_deprecated_help_detection() {
+        assert!(is_deprecated_help(&args("help --deprecated")));
+        assert!(!is_deprecated_help(&args("help build")));
+        assert!(!is_deprecated_help(&args("build --deprecated")));
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+
+use crate::core::action::ACTION_SCRIPT;
+
+/// 目录条目的权限
+const DIR_MODE: u32 = 0o755;
+/// 无法读取源文件权限时（非 Unix）普通文件与脚本的默认权限
+const FILE_MODE: u32 = 0o644;
+const SCRIPT_MODE: u32 = 0o755;
+
+/// zip 条目权限规则：[build.permissions] 中的 glob → 权限，最长（最具体）的模式优先
+pub struct PermissionRules {
+    rules: Vec<(glob::Pattern, u32)>,
+}
+
+impl PermissionRules {
+    pub fn new(permissions: &BTreeMap<String, u32>) -> Result<Self> {
+        let mut rules = permissions.iter()
+            .map(|(pattern, mode)| {
+                if *mode > 0o7777 {
+                    anyhow::bail!("[build.permissions] 中 {} 的权限无效: {:o}", pattern, mode);
+                }
+                let glob = glob::Pattern::new(pattern)
+                    .with_context(|| format!("[build.permissions] 中的模式无效: {}", pattern))?;
+                Ok((glob, *mode))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        rules.sort_by_key(|(glob, _)| std::cmp::Reverse(glob.as_str().len()));
+        Ok(Self { rules })
+    }
+
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// 文件条目的权限：配置规则 > 源文件权限 > 按文件类型的默认值
+    fn file_mode(&self, entry_name: &str, metadata: &fs::Metadata) -> u32 {
+        if let Some((_, mode)) = self.rules.iter().find(|(glob, _)| glob.matches(entry_name)) {
+            return *mode;
+        }
+        let mode = source_mode(metadata).unwrap_or(if entry_name.ends_with(".sh") { SCRIPT_MODE } else { FILE_MODE });
+        // action.sh 由管理器直接执行，始终可执行
+        if entry_name == ACTION_SCRIPT { mode | 0o111 } else { mode }
+    }
+}
+
+#[cfg(unix)]
+fn source_mode(metadata: &fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn source_mode(_metadata: &fs::Metadata) -> Option<u32> {
+    None
+}
+
+/// 将目录打包为 zip，保留文件权限，符号链接存为链接条目
+pub fn create_zip_archive(source_dir: &Path, output_path: &Path, permissions: &PermissionRules) -> Result<()> {
+    let file = fs::File::create(output_path)?;
+    let mut zip = zip::ZipWriter::new(file);
+    add_directory_to_zip(&mut zip, source_dir, source_dir, permissions)?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// 添加目录到 ZIP
+fn add_directory_to_zip<W: Write + Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    dir: &Path,
+    base_dir: &Path,
+    permissions: &PermissionRules,
+) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let relative_path = path.strip_prefix(base_dir)?;
+        // zip 条目名只能是 UTF-8，非 UTF-8 文件名直接报错，避免写入被替换字符破坏的条目
+        let entry_name = relative_path.to_str()
+            .ok_or_else(|| anyhow::anyhow!("文件名不是有效的 UTF-8，无法写入 zip: {:?}，请重命名该文件", relative_path))?
+            .replace('\\', "/");
+        // 不跟随符号链接，链接本身作为条目写入
+        let metadata = fs::symlink_metadata(&path)?;
+
+        if metadata.file_type().is_symlink() {
+            let target = fs::read_link(&path)?;
+            let target = target.to_str()
+                .ok_or_else(|| anyhow::anyhow!("符号链接目标不是有效的 UTF-8: {}", path.display()))?
+                .replace('\\', "/");
+            zip.add_symlink(entry_name, target, SimpleFileOptions::default())?;
+        } else if metadata.is_dir() {
+            zip.add_directory(format!("{}/", entry_name), SimpleFileOptions::default().unix_permissions(DIR_MODE))?;
+            add_directory_to_zip(zip, &path, base_dir, permissions)?;
+        } else {
+            let options = SimpleFileOptions::default().unix_permissions(permissions.file_mode(&entry_name, &metadata));
+            zip.start_file(entry_name, options)?;
+            zip.write_all(&fs::read(&path)?)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_zip_keeps_modes_and_symlinks() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("build");
+        fs::create_dir_all(source.join("system/bin")).unwrap();
+        fs::create_dir_all(source.join("system/etc")).unwrap();
+        fs::write(source.join("module.prop"), "id=demo\n").unwrap();
+        fs::write(source.join("service.sh"), "#!/system/bin/sh\n").unwrap();
+        fs::write(source.join("system/bin/tool"), "bin").unwrap();
+        fs::write(source.join("system/etc/tool.conf"), "conf").unwrap();
+        fs::write(source.join("system/etc/secret.conf"), "conf").unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(source.join("service.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+            fs::set_permissions(source.join("module.prop"), fs::Permissions::from_mode(0o644)).unwrap();
+            std::os::unix::fs::symlink("tool", source.join("system/bin/tool-alias")).unwrap();
+            std::os::unix::fs::symlink("/data/adb/demo/config", source.join("system/etc/demo.conf")).unwrap();
+        }
+
+        let rules = PermissionRules::new(&BTreeMap::from([
+            ("system/bin/*".to_string(), 0o755),
+            ("system/etc/secret.conf".to_string(), 0o600),
+            ("system/*".to_string(), 0o644),
+        ])).unwrap();
+        let zip_path = temp.path().join("demo.zip");
+        create_zip_archive(&source, &zip_path, &rules).unwrap();
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+        let mut mode = |name: &str| archive.by_name(name).unwrap().unix_mode().unwrap() & 0o7777;
+        assert_eq!(mode("service.sh"), 0o755);
+        assert_eq!(mode("module.prop"), 0o644);
+        assert_eq!(mode("system/bin/tool"), 0o755);
+        assert_eq!(mode("system/etc/tool.conf"), 0o644);
+        assert_eq!(mode("system/etc/secret.conf"), 0o600);
+        assert_eq!(mode("system/bin/"), 0o755);
+
+        #[cfg(unix)]
+        {
+            let mut link = archive.by_name("system/bin/tool-alias").unwrap();
+            assert!(link.is_symlink());
+            let mut target = String::new();
+            link.read_to_string(&mut target).unwrap();
+            assert_eq!(target, "tool");
+            drop(link);
+            assert!(archive.by_name("system/etc/demo.conf").unwrap().is_symlink());
+
+            // 解压后权限与链接保持不变
+            let extracted = temp.path().join("extracted");
+            archive.extract(&extracted).unwrap();
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(extracted.join("service.sh")).unwrap().permissions().mode() & 0o777, 0o755);
+            assert_eq!(fs::read_link(extracted.join("system/bin/tool-alias")).unwrap(), Path::new("tool"));
+        }
+
+        assert!(PermissionRules::new(&BTreeMap::from([("x".to_string(), 0o17777)])).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_checksum_manifest_covers_symlinks() {
+        use crate::core::checksums::{write_checksum_manifest, ChecksumManifest};
+        use crate::core::hashing::HashAlgorithm;
+
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("build");
+        fs::create_dir_all(source.join("system/bin")).unwrap();
+        fs::write(source.join("module.prop"), "id=demo\n").unwrap();
+        fs::write(source.join("system/bin/tool"), "bin").unwrap();
+        std::os::unix::fs::symlink("tool", source.join("system/bin/tool-alias")).unwrap();
+        // 指向设备上路径的链接在本机不存在
+        std::os::unix::fs::symlink("/data/adb/demo/config", source.join("system/bin/demo.conf")).unwrap();
+
+        let (manifest, _) = write_checksum_manifest(&source, HashAlgorithm::Blake3).unwrap();
+        assert_eq!(manifest.files["system/bin/tool-alias"], HashAlgorithm::Blake3.hash_bytes(b"tool"));
+        assert!(manifest.files.contains_key("system/bin/demo.conf"));
+        assert!(manifest.verify(&source).unwrap().is_empty());
+
+        // 随包清单与 zip 中的符号链接条目一致
+        let zip_path = temp.path().join("demo.zip");
+        create_zip_archive(&source, &zip_path, &PermissionRules::new(&BTreeMap::new()).unwrap()).unwrap();
+        let packed = ChecksumManifest::from_zip(&zip_path).unwrap().unwrap();
+        assert!(packed.verify_zip(&zip_path).unwrap().is_empty());
+
+        // 链接目标改变时校验失败
+        fs::remove_file(source.join("system/bin/tool-alias")).unwrap();
+        std::os::unix::fs::symlink("other", source.join("system/bin/tool-alias")).unwrap();
+        assert_eq!(manifest.verify(&source).unwrap(), vec!["哈希不一致: system/bin/tool-alias"]);
+    }
+}
@@ -0,0 +1,314 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::core::encoding::LineEndingRules;
+use crate::core::exclude_presets::build_excludes;
+use crate::core::process::{check_cancelled, TempGuard};
+use crate::core::rmm_core::BuildConfig;
+use crate::core::storage::RmmStorage;
+
+use super::archive::{create_zip_archive, PermissionRules};
+use super::files::{ExclusionSet, ProjectFiles};
+
+/// 基准测试阶段（按执行顺序）
+pub const PHASES: [&str; 4] = ["scan", "exclude", "copy", "zip"];
+/// 基准报告格式版本
+const REPORT_FORMAT: u32 = 1;
+
+/// rmm bench 选项
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// 合成项目的文件数
+    pub files: usize,
+    /// 合成项目的总大小（MB）
+    pub size_mb: u64,
+    /// 重复次数，取中位数
+    pub iterations: usize,
+    /// 基线文件（默认 RMM_ROOT/bench/baseline.json）
+    pub baseline: Option<PathBuf>,
+    /// 将本次结果保存为基线
+    pub save_baseline: bool,
+    /// 任一阶段比基线慢超过该百分比时失败
+    pub max_regression: Option<f64>,
+    pub json: bool,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            files: 2000,
+            size_mb: 50,
+            iterations: 5,
+            baseline: None,
+            save_baseline: false,
+            max_regression: None,
+            json: false,
+        }
+    }
+}
+
+/// 一次基准测试的结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchReport {
+    pub format: u32,
+    pub rmm_version: String,
+    pub files: usize,
+    pub size_mb: u64,
+    pub iterations: usize,
+    /// 各阶段耗时中位数（毫秒）
+    pub phases: BTreeMap<String, f64>,
+}
+
+impl BenchReport {
+    pub fn total_ms(&self) -> f64 {
+        self.phases.values().sum()
+    }
+}
+
+/// 与基线对比的单个阶段
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseComparison {
+    pub phase: String,
+    pub baseline_ms: f64,
+    pub current_ms: f64,
+    /// 相对基线的变化百分比，正数表示变慢
+    pub change_percent: f64,
+}
+
+/// 简单的确定性伪随机数（xorshift），保证每次生成相同的合成项目
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// 生成合成项目：二进制文件、shell 脚本、会被排除的日志和 node_modules
+pub fn generate_project(root: &Path, files: usize, size_mb: u64) -> Result<()> {
+    fs::create_dir_all(root.join(".rmmp"))?;
+    fs::write(root.join(".rmmp/Rmake.toml"), "[build]\ninclude = []\nexclude = []\nprebuild = []\nbuild = []\npostbuild = []\n")?;
+    fs::write(root.join("module.prop"), "id=bench\nname=Bench\nversion=v1.0\nversionCode=1\nauthor=rmm\ndescription=bench\n")?;
+
+    let files = files.max(1);
+    let per_file = ((size_mb * 1024 * 1024) / files as u64).max(1) as usize;
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+    for i in 0..files {
+        let dir = match i % 10 {
+            0 => root.join(format!("node_modules/pkg{}", i / 100)),
+            1 => root.join("logs"),
+            2 | 3 => root.join(format!("system/bin/d{}", i / 200)),
+            _ => root.join(format!("system/lib64/d{}", i / 200)),
+        };
+        fs::create_dir_all(&dir)?;
+        let (name, content) = match i % 10 {
+            0 => (format!("m{}.js", i), text_content(&mut rng, per_file)),
+            1 => (format!("r{}.log", i), text_content(&mut rng, per_file)),
+            2 => (format!("s{}.sh", i), text_content(&mut rng, per_file)),
+            _ => (format!("f{}.bin", i), binary_content(&mut rng, per_file)),
+        };
+        fs::write(dir.join(name), content)?;
+    }
+    Ok(())
+}
+
+/// 半可压缩的二进制内容
+fn binary_content(rng: &mut XorShift, len: usize) -> Vec<u8> {
+    (0..len).map(|i| if i % 4 == 0 { (rng.next() & 0xff) as u8 } else { (i % 7) as u8 }).collect()
+}
+
+/// 仅含 LF 换行的文本内容
+fn text_content(rng: &mut XorShift, len: usize) -> Vec<u8> {
+    let mut content = Vec::with_capacity(len);
+    while content.len() < len {
+        content.extend_from_slice(format!("echo line {}\n", rng.next() % 10_000).as_bytes());
+    }
+    content.truncate(len);
+    content
+}
+
+fn median_ms(samples: &mut [Duration]) -> f64 {
+    samples.sort();
+    samples.get(samples.len() / 2).map(|d| d.as_secs_f64() * 1000.0).unwrap_or_default()
+}
+
+/// 对合成项目执行 扫描 → 排除 → 复制 → 压缩，返回各阶段耗时中位数
+pub fn run_phases(project: &Path, work_dir: &Path, iterations: usize) -> Result<BTreeMap<String, f64>> {
+    let build = BuildConfig {
+        exclude: vec!["*.log".to_string(), ".git".to_string()],
+        exclude_presets: vec!["node".to_string()],
+        ..Default::default()
+    };
+    let exclusions = ExclusionSet::new(&build_excludes(&build)?)?;
+    let mut samples: Vec<Vec<Duration>> = vec![Vec::new(); PHASES.len()];
+
+    for _ in 0..iterations.max(1) {
+        check_cancelled()?;
+        let build_dir = work_dir.join("build");
+        let zip_path = work_dir.join("bench.zip");
+        if build_dir.exists() {
+            fs::remove_dir_all(&build_dir)?;
+        }
+        fs::create_dir_all(&build_dir)?;
+
+        let start = Instant::now();
+        let files = ProjectFiles::scan(project)?;
+        samples[0].push(start.elapsed());
+
+        let start = Instant::now();
+        let (entries, _) = exclusions.filter(files.module_entries());
+        samples[1].push(start.elapsed());
+
+        let start = Instant::now();
+        files.copy_to(&entries, &build_dir, &LineEndingRules::empty())?;
+        samples[2].push(start.elapsed());
+
+        let start = Instant::now();
+        create_zip_archive(&build_dir, &zip_path, &PermissionRules::empty())?;
+        samples[3].push(start.elapsed());
+    }
+
+    Ok(PHASES.iter()
+        .zip(samples.iter_mut())
+        .map(|(phase, samples)| (phase.to_string(), median_ms(samples)))
+        .collect())
+}
+
+/// 对比基线，返回两边都有的阶段
+pub fn compare(baseline: &BenchReport, current: &BenchReport) -> Vec<PhaseComparison> {
+    PHASES.iter()
+        .filter_map(|phase| {
+            let baseline_ms = *baseline.phases.get(*phase)?;
+            let current_ms = *current.phases.get(*phase)?;
+            let change_percent = if baseline_ms > 0.0 {
+                (current_ms - baseline_ms) / baseline_ms * 100.0
+            } else {
+                0.0
+            };
+            Some(PhaseComparison { phase: phase.to_string(), baseline_ms, current_ms, change_percent })
+        })
+        .collect()
+}
+
+fn default_baseline_path() -> PathBuf {
+    RmmStorage::resolve().root().join("bench").join("baseline.json")
+}
+
+/// rmm bench：生成合成项目并测量打包热路径
+pub fn run_bench(options: &BenchOptions) -> Result<BenchReport> {
+    let work_dir = std::env::temp_dir().join(format!("rmm-bench-{}", std::process::id()));
+    let guard = TempGuard::new(&work_dir);
+    let project = guard.path().join("project");
+
+    if !options.json {
+        println!("{} 生成合成项目: {} 个文件, {} MB", "[bench]".magenta().bold(), options.files, options.size_mb);
+    }
+    generate_project(&project, options.files, options.size_mb)?;
+    let phases = run_phases(&project, guard.path(), options.iterations)?;
+    drop(guard);
+
+    let report = BenchReport {
+        format: REPORT_FORMAT,
+        rmm_version: env!("CARGO_PKG_VERSION").to_string(),
+        files: options.files,
+        size_mb: options.size_mb,
+        iterations: options.iterations.max(1),
+        phases,
+    };
+
+    let baseline_path = options.baseline.clone().unwrap_or_else(default_baseline_path);
+    let baseline: Option<BenchReport> = fs::read_to_string(&baseline_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let comparisons = baseline.as_ref()
+        .filter(|b| b.files == report.files && b.size_mb == report.size_mb)
+        .map(|b| compare(b, &report))
+        .unwrap_or_default();
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report, &comparisons);
+        if baseline.is_some() && comparisons.is_empty() {
+            println!("  {} 基线的项目规模不同，未进行对比", "[i]".cyan());
+        }
+    }
+
+    if options.save_baseline {
+        if let Some(parent) = baseline_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&baseline_path, serde_json::to_string_pretty(&report)? + "\n")?;
+        eprintln!("{} 已保存基线: {}", "[+]".green().bold(), baseline_path.display());
+    }
+
+    if let Some(limit) = options.max_regression {
+        let regressed: Vec<String> = comparisons.iter()
+            .filter(|c| c.change_percent > limit)
+            .map(|c| format!("{} (+{:.1}%)", c.phase, c.change_percent))
+            .collect();
+        if !regressed.is_empty() {
+            anyhow::bail!("性能回退超过 {}%: {}", limit, regressed.join(", "));
+        }
+    }
+    Ok(report)
+}
+
+fn print_report(report: &BenchReport, comparisons: &[PhaseComparison]) {
+    println!("{} 各阶段耗时（{} 次中位数）", "[bench]".magenta().bold(), report.iterations);
+    for phase in PHASES {
+        let Some(ms) = report.phases.get(phase) else { continue };
+        let delta = comparisons.iter()
+            .find(|c| c.phase == phase)
+            .map(|c| {
+                let text = format!("{:+.1}% (基线 {:.1} ms)", c.change_percent, c.baseline_ms);
+                if c.change_percent > 5.0 { text.red() } else if c.change_percent < -5.0 { text.green() } else { text.bright_black() }
+            })
+            .unwrap_or_default();
+        println!("  {:<8} {:>10.1} ms  {}", phase, ms, delta);
+    }
+    println!("  {:<8} {:>10.1} ms", "total", report.total_ms());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_bench_phases_and_compare() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        generate_project(&project, 40, 1).unwrap();
+        assert!(project.join("node_modules").exists());
+
+        let phases = run_phases(&project, temp_dir.path(), 2).unwrap();
+        assert_eq!(phases.len(), PHASES.len());
+        assert!(temp_dir.path().join("bench.zip").exists());
+        assert!(!temp_dir.path().join("build/node_modules").exists());
+        assert!(!temp_dir.path().join("build/logs/r1.log").exists());
+        assert!(temp_dir.path().join("build/module.prop").exists());
+
+        let report = |scan: f64| BenchReport {
+            format: REPORT_FORMAT,
+            rmm_version: "0".to_string(),
+            files: 40,
+            size_mb: 1,
+            iterations: 1,
+            phases: BTreeMap::from([("scan".to_string(), scan), ("zip".to_string(), 10.0)]),
+        };
+        let comparisons = compare(&report(100.0), &report(150.0));
+        assert_eq!(comparisons.len(), 2);
+        assert_eq!(comparisons[0].phase, "scan");
+        assert!((comparisons[0].change_percent - 50.0).abs() < 1e-9);
+        assert_eq!(comparisons[1].change_percent, 0.0);
+    }
+}
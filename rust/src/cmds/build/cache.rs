@@ -0,0 +1,370 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::core::hashing::{hash_tree, sha256_bytes, HashAlgorithm};
+use crate::core::remote_cache::RemoteCache;
+use crate::core::rmm_core::RmakeConfig;
+use crate::core::shell_script::{ScriptKind, ScriptMatcher};
+
+/// 构建缓存文件名（位于 .rmmp 下）
+const CACHE_FILE: &str = "build-cache.json";
+
+/// 构建阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildPhase {
+    Copy,
+    Shellcheck,
+    Prebuild,
+    Package,
+    Postbuild,
+    Source,
+}
+
+impl BuildPhase {
+    pub const ALL: [BuildPhase; 6] = [
+        BuildPhase::Copy,
+        BuildPhase::Shellcheck,
+        BuildPhase::Prebuild,
+        BuildPhase::Package,
+        BuildPhase::Postbuild,
+        BuildPhase::Source,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuildPhase::Copy => "copy",
+            BuildPhase::Shellcheck => "shellcheck",
+            BuildPhase::Prebuild => "prebuild",
+            BuildPhase::Package => "package",
+            BuildPhase::Postbuild => "postbuild",
+            BuildPhase::Source => "source",
+        }
+    }
+}
+
+/// 构建输入指纹：Rmake.toml 哈希 + 项目文件哈希
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct BuildFingerprint {
+    pub config_hash: String,
+    pub files: BTreeMap<String, String>,
+    /// 上次构建生成的产物（相对 .rmmp/dist）
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+}
+
+/// 某个阶段的执行决定及原因
+#[derive(Debug, Clone)]
+pub struct PhaseDecision {
+    pub phase: BuildPhase,
+    pub run: bool,
+    pub reason: String,
+}
+
+/// 构建计划
+#[derive(Debug, Clone)]
+pub struct BuildPlan {
+    pub decisions: Vec<PhaseDecision>,
+    pub fingerprint: BuildFingerprint,
+}
+
+impl BuildPlan {
+    /// 是否所有阶段都可以跳过
+    pub fn is_up_to_date(&self) -> bool {
+        self.decisions.iter().all(|d| !d.run)
+    }
+
+    pub fn should_run(&self, phase: BuildPhase) -> bool {
+        self.decisions.iter().any(|d| d.phase == phase && d.run)
+    }
+
+    /// 打印每个阶段运行或跳过的原因
+    pub fn explain(&self) {
+        println!("{} 构建计划:", "[explain]".magenta().bold());
+        for decision in &self.decisions {
+            let status = if decision.run { "run ".green().bold() } else { "skip".bright_black().bold() };
+            println!("    {} {:<10} {}", status, decision.phase.as_str(), decision.reason.bright_black());
+        }
+    }
+}
+
+impl BuildFingerprint {
+    /// 计算项目当前的输入指纹（排除 .rmmp 与 .git）
+    pub fn compute(project_path: &Path) -> Result<Self> {
+        let rmake_path = project_path.join(".rmmp/Rmake.toml");
+        let config_hash = sha256_bytes(&fs::read(&rmake_path)?);
+
+        let files = hash_tree(project_path, HashAlgorithm::Blake3, |e| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() == 0 || (name != ".rmmp" && name != ".git")
+        })?;
+
+        Ok(Self { config_hash, files, artifacts: Vec::new() })
+    }
+
+    /// 读取上次构建的指纹
+    pub fn load(project_path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(project_path.join(".rmmp").join(CACHE_FILE)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 保存本次构建的指纹，并记录 dist 中的产物
+    pub fn save(&mut self, project_path: &Path) -> Result<()> {
+        let dist_dir = project_path.join(".rmmp/dist");
+        self.artifacts = fs::read_dir(&dist_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.path().is_file())
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.artifacts.sort();
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(project_path.join(".rmmp").join(CACHE_FILE), content)?;
+        Ok(())
+    }
+
+    /// 删除构建缓存
+    pub fn clear(project_path: &Path) {
+        let _ = fs::remove_file(project_path.join(".rmmp").join(CACHE_FILE));
+    }
+
+    /// 输入摘要：Rmake.toml 哈希与全部文件哈希的组合
+    pub fn digest(&self) -> String {
+        let mut content = self.config_hash.clone();
+        for (path, hash) in &self.files {
+            content.push('\n');
+            content.push_str(path);
+            content.push('\0');
+            content.push_str(hash);
+        }
+        sha256_bytes(content.as_bytes())
+    }
+
+    /// 与上次指纹比较，返回变化的文件列表
+    pub fn changed_files(&self, previous: &BuildFingerprint) -> Vec<String> {
+        let mut changed: Vec<String> = self.files.iter()
+            .filter(|(path, hash)| previous.files.get(*path) != Some(*hash))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            previous.files.keys()
+                .filter(|path| !self.files.contains_key(*path))
+                .cloned(),
+        );
+        changed
+    }
+}
+
+/// 变更文件中是否包含 shell 脚本（已删除的 .sh 也算）
+fn scripts_changed(project_path: &Path, changed: &[String]) -> bool {
+    let config = fs::read_to_string(project_path.join(".rmmp/Rmake.toml"))
+        .ok()
+        .and_then(|content| toml::from_str::<RmakeConfig>(&content).ok());
+    let matcher = ScriptMatcher::new(config.as_ref().and_then(|c| c.check.as_ref()).and_then(|c| c.scripts.as_ref()));
+    changed.iter().any(|relative| {
+        let path = project_path.join(relative);
+        if !path.exists() {
+            return relative.ends_with(".sh");
+        }
+        matcher.classify(&path, relative).is_ok_and(|kind| kind == ScriptKind::Shell)
+    })
+}
+
+/// 根据上次构建的指纹决定各阶段是否需要运行
+pub fn plan_build(project_path: &Path, force: bool) -> Result<BuildPlan> {
+    let fingerprint = BuildFingerprint::compute(project_path)?;
+    let previous = BuildFingerprint::load(project_path);
+
+    let all = |reason: &str| -> Vec<PhaseDecision> {
+        BuildPhase::ALL.iter()
+            .map(|phase| PhaseDecision { phase: *phase, run: true, reason: reason.to_string() })
+            .collect()
+    };
+
+    let decisions = match previous {
+        _ if force => all("强制重新构建 (--force)"),
+        None => all("缓存未命中：没有上次构建记录"),
+        Some(prev) if prev.config_hash != fingerprint.config_hash => all("Rmake.toml 已变更"),
+        Some(prev) => {
+            let dist_dir = project_path.join(".rmmp/dist");
+            let missing: Vec<&String> = prev.artifacts.iter()
+                .filter(|name| !dist_dir.join(name).exists())
+                .collect();
+            let changed = fingerprint.changed_files(&prev);
+
+            if !missing.is_empty() {
+                all(&format!("产物缺失: {}", missing[0]))
+            } else if changed.is_empty() {
+                BuildPhase::ALL.iter()
+                    .map(|phase| PhaseDecision {
+                        phase: *phase,
+                        run: false,
+                        reason: "输入未变化，产物已存在".to_string(),
+                    })
+                    .collect()
+            } else {
+                let reason = format!("{} 个文件已变更 (如 {})", changed.len(), changed[0]);
+                let scripts_changed = scripts_changed(project_path, &changed);
+                BuildPhase::ALL.iter()
+                    .map(|phase| match phase {
+                        BuildPhase::Shellcheck if !scripts_changed => PhaseDecision {
+                            phase: *phase,
+                            run: false,
+                            reason: "没有 shell 脚本变更".to_string(),
+                        },
+                        _ => PhaseDecision { phase: *phase, run: true, reason: reason.clone() },
+                    })
+                    .collect()
+            }
+        }
+    };
+
+    Ok(BuildPlan { decisions, fingerprint })
+}
+
+/// 从远程缓存取回相同输入的构建产物并保存指纹；未命中或出错时返回 false，照常构建
+pub fn pull_remote(project_path: &Path, remote: &RemoteCache, fingerprint: &mut BuildFingerprint) -> bool {
+    match remote.pull_build(&fingerprint.digest(), &project_path.join(".rmmp/dist")) {
+        Ok(Some(artifacts)) => {
+            println!("{} 远程缓存命中（{}），取回 {} 个产物", "[cache]".cyan().bold(), remote.backend.describe(), artifacts.len());
+            if let Err(e) = fingerprint.save(project_path) {
+                println!("{} 无法写入构建缓存: {}", "[!]".yellow(), e);
+            }
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            println!("{} 无法读取远程缓存: {}", "[!]".yellow(), e);
+            false
+        }
+    }
+}
+
+/// 上传本次构建的产物（需先保存指纹以记录产物）；失败只警告
+pub fn push_remote(project_path: &Path, remote: &RemoteCache, fingerprint: &BuildFingerprint) {
+    if !remote.push {
+        return;
+    }
+    match remote.push_build(&fingerprint.digest(), &project_path.join(".rmmp/dist"), &fingerprint.artifacts) {
+        Ok(uploaded) => println!("{} 已上传到远程缓存（新增 {} 个文件）", "[cache]".cyan().bold(), uploaded),
+        Err(e) => println!("{} 无法上传到远程缓存: {}", "[!]".yellow(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_project() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join(".rmmp/dist")).unwrap();
+        fs::write(project_path.join(".rmmp/Rmake.toml"), "[build]\n").unwrap();
+        fs::write(project_path.join("module.prop"), "id=test\n").unwrap();
+        fs::write(project_path.join("service.sh"), "#!/system/bin/sh\n").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_plan_build_cache_miss_and_hit() {
+        let temp_dir = setup_project();
+        let project_path = temp_dir.path();
+
+        let mut plan = plan_build(project_path, false).unwrap();
+        assert!(BuildPhase::ALL.iter().all(|p| plan.should_run(*p)));
+        assert!(plan.decisions[0].reason.contains("缓存未命中"));
+
+        fs::write(project_path.join(".rmmp/dist/test-1.zip"), "zip").unwrap();
+        plan.fingerprint.save(project_path).unwrap();
+
+        let plan = plan_build(project_path, false).unwrap();
+        assert!(plan.is_up_to_date());
+
+        let plan = plan_build(project_path, true).unwrap();
+        assert!(!plan.is_up_to_date());
+
+        fs::remove_file(project_path.join(".rmmp/dist/test-1.zip")).unwrap();
+        let plan = plan_build(project_path, false).unwrap();
+        assert!(plan.should_run(BuildPhase::Package));
+    }
+
+    #[test]
+    fn test_plan_build_changes() {
+        let temp_dir = setup_project();
+        let project_path = temp_dir.path();
+
+        let mut plan = plan_build(project_path, false).unwrap();
+        plan.fingerprint.save(project_path).unwrap();
+
+        // 非脚本文件变更：跳过 shellcheck
+        fs::write(project_path.join("module.prop"), "id=test\nversion=2\n").unwrap();
+        let plan = plan_build(project_path, false).unwrap();
+        assert!(plan.should_run(BuildPhase::Package));
+        assert!(!plan.should_run(BuildPhase::Shellcheck));
+        assert!(plan.decisions[0].reason.starts_with("1 个文件已变更"));
+        plan.fingerprint.clone().save(project_path).unwrap();
+
+        // 无扩展名但带 shell shebang 的脚本变更：运行 shellcheck
+        fs::write(project_path.join("post-fs-data"), "#!/system/bin/sh\necho hi\n").unwrap();
+        let plan = plan_build(project_path, false).unwrap();
+        assert!(plan.should_run(BuildPhase::Shellcheck));
+
+        // 配置变更：完整重建
+        fs::write(project_path.join(".rmmp/Rmake.toml"), "[build]\nexclude = []\n").unwrap();
+        let plan = plan_build(project_path, false).unwrap();
+        assert!(plan.should_run(BuildPhase::Shellcheck));
+        assert_eq!(plan.decisions[0].reason, "Rmake.toml 已变更");
+    }
+
+    #[test]
+    fn test_remote_build_cache() {
+        use crate::core::remote_cache::RemoteBackend;
+
+        let remote_dir = TempDir::new().unwrap();
+        let remote = RemoteCache::new(RemoteBackend::Directory(remote_dir.path().to_path_buf()));
+
+        // CI 构建后上传
+        let ci = setup_project();
+        let mut plan = plan_build(ci.path(), false).unwrap();
+        fs::write(ci.path().join(".rmmp/dist/test-1.zip"), "zip").unwrap();
+        plan.fingerprint.save(ci.path()).unwrap();
+        push_remote(ci.path(), &remote, &plan.fingerprint);
+        assert!(remote_dir.path().join("objects").join(sha256_bytes(b"zip")).is_file());
+
+        // 相同输入的另一台机器直接取回产物，之后本地缓存命中
+        let teammate = setup_project();
+        let mut plan = plan_build(teammate.path(), false).unwrap();
+        assert!(pull_remote(teammate.path(), &remote, &mut plan.fingerprint));
+        assert_eq!(fs::read_to_string(teammate.path().join(".rmmp/dist/test-1.zip")).unwrap(), "zip");
+        assert!(plan_build(teammate.path(), false).unwrap().is_up_to_date());
+
+        // 输入不同时未命中
+        fs::write(teammate.path().join("service.sh"), "#!/system/bin/sh\necho changed\n").unwrap();
+        let mut plan = plan_build(teammate.path(), false).unwrap();
+        assert!(!pull_remote(teammate.path(), &remote, &mut plan.fingerprint));
+
+        // 远程对象损坏时视为未命中
+        let digest = BuildFingerprint::load(ci.path()).unwrap().digest();
+        fs::write(remote_dir.path().join("objects").join(sha256_bytes(b"zip")), "tampered").unwrap();
+        assert_eq!(remote.pull_build(&digest, &teammate.path().join(".rmmp/dist")).unwrap(), None);
+
+        // 被篡改的清单不能把产物写到 dist 之外
+        for name in ["../../customize.sh", "/tmp/evil.zip", "..", "sub\\evil.zip"] {
+            let manifest = serde_json::json!({
+                "artifacts": [{ "name": name, "sha256": sha256_bytes(b"zip"), "size": 3 }],
+                "created_at": "2026-01-01T00:00:00Z",
+            });
+            fs::write(remote_dir.path().join("builds").join(format!("{}.json", digest)), manifest.to_string()).unwrap();
+            assert!(remote.pull_build(&digest, &teammate.path().join(".rmmp/dist")).is_err(), "{}", name);
+        }
+        assert!(!teammate.path().join("customize.sh").exists());
+    }
+}
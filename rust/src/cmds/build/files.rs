@@ -0,0 +1,536 @@
+use anyhow::Result;
+use colored::Colorize;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use rayon::prelude::*;
+use regex::RegexSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::core::encoding::LineEndingRules;
+use crate::core::exclude_presets::DEFAULT_EXCLUDES;
+
+/// 项目中的一个文件或目录（相对项目根目录，使用 / 分隔）
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileEntry {
+    /// 用于规则匹配与显示的路径，非 UTF-8 字节会被替换
+    pub relative: String,
+    /// 原始相对路径，复制文件时使用，保留非 UTF-8 文件名
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// 符号链接（不跟随，打包时存为链接条目）
+    pub is_symlink: bool,
+}
+
+/// 一次构建中扫描得到的项目文件列表，模块打包与源代码打包共用
+#[derive(Debug, Clone)]
+pub struct ProjectFiles {
+    root: PathBuf,
+    entries: Vec<FileEntry>,
+}
+
+impl ProjectFiles {
+    /// 扫描项目目录；.rmmp 中只保留 Rmake.toml，符号链接不跟随
+    pub fn scan(project_path: &Path) -> Result<Self> {
+        let mut entries = Vec::new();
+        let walker = WalkDir::new(project_path)
+            .min_depth(1)
+            .follow_links(false)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| e.depth() != 2 || !e.path().parent().is_some_and(|p| p.ends_with(".rmmp")) || e.file_name() == "Rmake.toml");
+        for entry in walker {
+            let entry = entry?;
+            let path = entry.path().strip_prefix(project_path)?.to_path_buf();
+            let relative = path.to_string_lossy().replace('\\', "/");
+            entries.push(FileEntry { relative, path, is_dir: entry.file_type().is_dir(), is_symlink: entry.path_is_symlink() });
+        }
+        Ok(Self { root: project_path.to_path_buf(), entries })
+    }
+
+    /// 模块打包的候选条目（不含 .rmmp）
+    pub fn module_entries(&self) -> Vec<&FileEntry> {
+        self.entries.iter().filter(|e| !is_rmmp(&e.relative)).collect()
+    }
+
+    /// 源代码打包的候选条目（含 .rmmp/Rmake.toml）
+    pub fn source_entries(&self) -> Vec<&FileEntry> {
+        self.entries.iter().collect()
+    }
+
+    /// 将条目复制到目标目录（并行复制文件），文本文件按行尾规则转换
+    pub fn copy_to(&self, entries: &[&FileEntry], dest: &Path, line_endings: &LineEndingRules) -> Result<()> {
+        for entry in entries.iter().filter(|e| e.is_dir) {
+            fs::create_dir_all(dest.join(&entry.path))?;
+        }
+        entries.par_iter()
+            .filter(|e| !e.is_dir)
+            .try_for_each(|entry| {
+                let target = dest.join(&entry.path);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                if entry.is_symlink {
+                    return copy_symlink(&self.root.join(&entry.path), &target);
+                }
+                super::copy_file_with_line_ending_normalization(&self.root.join(&entry.path), &target, line_endings.policy(&entry.relative))
+            })
+    }
+}
+
+/// 复制符号链接本身（目标保持不变）
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> Result<()> {
+    if fs::symlink_metadata(target).is_ok() {
+        fs::remove_file(target)?;
+    }
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)?;
+    Ok(())
+}
+
+/// 非 Unix 平台无法可靠创建符号链接，复制链接指向的文件
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, target: &Path) -> Result<()> {
+    if source.is_file() {
+        fs::copy(source, target)?;
+    } else {
+        println!("    {} 跳过符号链接（Windows 上无法保留）: {}", "[!]".yellow(), source.display());
+    }
+    Ok(())
+}
+
+fn is_rmmp(relative: &str) -> bool {
+    relative == ".rmmp" || relative.starts_with(".rmmp/")
+}
+
+/// 一条 gitignore 风格的规则，单独编译以便列出匹配路径的全部规则
+struct ExclusionRule {
+    /// 原始文本（含 ! 前缀），用于显示
+    pattern: String,
+    /// 交给 gitignore 解析的文本（旧写法改写后的结果）
+    line: String,
+    /// ! 开头：重新包含之前的规则排除的路径
+    negated: bool,
+    matcher: Gitignore,
+}
+
+impl ExclusionRule {
+    fn parse(pattern: &str) -> Result<Self> {
+        let negated = pattern.starts_with('!');
+        if pattern.trim_start_matches('!').trim_matches('/').is_empty() {
+            anyhow::bail!("排除规则 '{}' 无效：缺少路径", pattern);
+        }
+        // 旧版本接受 **.log 这类写法（** 跨越目录），改写为等价的 gitignore 模式并给出警告，而不是中断构建
+        let line = rewrite_legacy_pattern(pattern);
+        if line != pattern {
+            println!("    {} 排除规则 '{}' 不是有效的 gitignore 模式，按 '{}' 处理", "[!]".yellow(), pattern, line);
+        }
+        let matcher = compile(&line).map_err(|e| anyhow::anyhow!("排除规则 '{}' 无效: {}", pattern, e))?;
+        Ok(Self { pattern: pattern.to_string(), line, negated, matcher })
+    }
+}
+
+fn compile(line: &str) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new("");
+    builder.add_line(None, line)?;
+    Ok(builder.build()?)
+}
+
+/// 将不构成完整路径层级的 **（如 **.log、src/**.tmp）改写为 ** 匹配任意层目录的 gitignore 模式
+fn rewrite_legacy_pattern(pattern: &str) -> String {
+    let mut out = String::with_capacity(pattern.len() + 2);
+    let mut rest = pattern;
+    while let Some(i) = rest.find("**") {
+        out.push_str(&rest[..i]);
+        rest = rest[i..].trim_start_matches('*');
+        let starts_component = out.is_empty() || out == "!" || out.ends_with('/');
+        let ends_component = rest.is_empty() || rest.starts_with('/');
+        out.push_str(match (starts_component, ends_component) {
+            (true, true) => "**",
+            (true, false) => "**/*",
+            (false, _) => "*",
+        });
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 预编译的排除规则（gitignore 语义）：不含 / 的模式匹配任一层的名称，含 / 的模式从项目根目录匹配，
+/// ** 匹配任意层目录，/ 结尾只匹配目录，! 开头重新包含；多条规则匹配时最后一条生效，
+/// 目录被排除后其中的路径不能再被 ! 规则重新包含
+pub struct ExclusionSet {
+    rules: Vec<ExclusionRule>,
+    matcher: Gitignore,
+}
+
+impl ExclusionSet {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let rules = patterns.iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty() && !p.starts_with('#'))
+            .map(ExclusionRule::parse)
+            .collect::<Result<Vec<_>>>()?;
+        let mut builder = GitignoreBuilder::new("");
+        for rule in &rules {
+            builder.add_line(None, &rule.line)?;
+        }
+        Ok(Self { matcher: builder.build()?, rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 匹配相对路径或其所在目录的全部规则（含 ! 规则），按声明顺序
+    pub fn matching(&self, relative: &str, is_dir: bool) -> Vec<&str> {
+        self.rules.iter()
+            .filter(|rule| !rule.matcher.matched_path_or_any_parents(relative, is_dir).is_none())
+            .map(|rule| rule.pattern.as_str())
+            .collect()
+    }
+
+    /// 最终排除路径的规则：所在目录被排除时为排除该目录的规则，否则为最后一条匹配路径本身的规则；
+    /// 为 ! 规则或没有规则匹配时返回 None
+    pub fn matched(&self, relative: &str, is_dir: bool) -> Option<&str> {
+        self.decisive(relative, is_dir).map(|i| self.rules[i].pattern.as_str())
+    }
+
+    fn decisive(&self, relative: &str, is_dir: bool) -> Option<usize> {
+        relative.match_indices('/')
+            .map(|(i, _)| (&relative[..i], true))
+            .chain(std::iter::once((relative, is_dir)))
+            .find_map(|(path, is_dir)| match self.matcher.matched(path, is_dir) {
+                Match::Ignore(glob) => self.rules.iter().rposition(|rule| rule.line == glob.original()),
+                _ => None,
+            })
+    }
+
+    /// 并行筛选条目，返回保留的条目以及每条排除规则（! 规则除外）排除的数量
+    pub fn filter<'a>(&self, entries: Vec<&'a FileEntry>) -> (Vec<&'a FileEntry>, Vec<(&str, usize)>) {
+        if self.is_empty() {
+            return (entries, Vec::new());
+        }
+        let decisions: Vec<(&FileEntry, Option<usize>)> = entries.into_par_iter()
+            .map(|e| (e, self.decisive(&e.relative, e.is_dir)))
+            .collect();
+
+        let mut counts = vec![0; self.rules.len()];
+        let mut kept = Vec::with_capacity(decisions.len());
+        for (entry, matched) in decisions {
+            match matched {
+                Some(i) => counts[i] += 1,
+                None => kept.push(entry),
+            }
+        }
+        let counts = self.rules.iter()
+            .zip(counts)
+            .filter(|(rule, _)| !rule.negated)
+            .map(|(rule, count)| (rule.pattern.as_str(), count))
+            .collect();
+        (kept, counts)
+    }
+}
+
+/// 默认排除规则：按路径中每一层的名称匹配 DEFAULT_EXCLUDES，[build] allow 匹配的路径例外
+pub struct DefaultExclusions {
+    set: RegexSet,
+    allow: ExclusionSet,
+}
+
+impl DefaultExclusions {
+    pub fn new(allow: &[String]) -> Result<Self> {
+        let set = RegexSet::new(DEFAULT_EXCLUDES.iter().map(|p| format!("^{}$", regex::escape(p).replace(r"\*", ".*"))))?;
+        Ok(Self { set, allow: ExclusionSet::new(allow)? })
+    }
+
+    /// 匹配路径的默认规则（不考虑 allow）；项目根目录的 .rmmp 不在此处理
+    fn default_rule(&self, relative: &str) -> Option<&'static str> {
+        relative.split('/').enumerate()
+            .filter(|(depth, name)| *depth > 0 || *name != ".rmmp")
+            .find_map(|(_, name)| self.set.matches(name).iter().next())
+            .map(|i| DEFAULT_EXCLUDES[i])
+    }
+
+    /// 排除该路径的默认规则；被 allow 放行时返回 None
+    pub fn matching(&self, relative: &str, is_dir: bool) -> Option<&'static str> {
+        let rule = self.default_rule(relative)?;
+        self.allow.matched(relative, is_dir).is_none().then_some(rule)
+    }
+
+    /// 放行该路径的 allow 规则（仅当默认规则本会排除它时）
+    pub fn allowed_by(&self, relative: &str, is_dir: bool) -> Option<&str> {
+        self.default_rule(relative)?;
+        self.allow.matched(relative, is_dir)
+    }
+
+    /// 筛选条目，返回保留的条目以及每条默认规则排除的数量
+    pub fn filter<'a>(&self, entries: Vec<&'a FileEntry>) -> (Vec<&'a FileEntry>, Vec<(&'static str, usize)>) {
+        let mut counts: Vec<(&'static str, usize)> = Vec::new();
+        let mut kept = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match self.matching(&entry.relative, entry.is_dir) {
+                Some(rule) => match counts.iter_mut().find(|(r, _)| *r == rule) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((rule, 1)),
+                },
+                None => kept.push(entry),
+            }
+        }
+        (kept, counts)
+    }
+}
+
+/// 打印各排除规则命中的数量
+pub fn print_exclusions(title: &str, counts: &[(&str, usize)]) {
+    if counts.is_empty() {
+        return;
+    }
+    println!("    {} {}:", "[!]".bright_yellow(), title);
+    for (pattern, count) in counts {
+        println!("      - {} {}", pattern, format!("(排除 {} 项)", count).bright_black());
+    }
+}
+
+/// 显示 [build.line_endings] 每条规则匹配的文件数
+pub fn print_line_endings(rules: &LineEndingRules, entries: &[&FileEntry]) {
+    let mut counts: Vec<(&str, &str, usize)> = Vec::new();
+    for entry in entries.iter().filter(|e| !e.is_dir && !e.is_symlink) {
+        if let Some((pattern, ending)) = rules.matching(&entry.relative) {
+            match counts.iter_mut().find(|(p, _, _)| *p == pattern) {
+                Some((_, _, count)) => *count += 1,
+                None => counts.push((pattern, ending.as_str(), 1)),
+            }
+        }
+    }
+    if counts.is_empty() {
+        return;
+    }
+    println!("    {} 行尾规则:", "[i]".cyan());
+    for (pattern, ending, count) in counts {
+        println!("      - {} → {} {}", pattern, ending, format!("({} 个文件)", count).bright_black());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_filter_and_copy() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join(".rmmp/build")).unwrap();
+        fs::create_dir_all(project_path.join("system/bin")).unwrap();
+        fs::create_dir_all(project_path.join("docs")).unwrap();
+        fs::write(project_path.join(".rmmp/Rmake.toml"), "[build]\n").unwrap();
+        fs::write(project_path.join(".rmmp/build/stale"), "x").unwrap();
+        fs::write(project_path.join("module.prop"), "id=demo\n").unwrap();
+        fs::write(project_path.join("system/bin/tool"), "bin").unwrap();
+        fs::write(project_path.join("system/bin/tool.log"), "log").unwrap();
+        fs::write(project_path.join("docs/guide.md"), "doc").unwrap();
+
+        let files = ProjectFiles::scan(project_path).unwrap();
+        let relatives = |entries: &[&FileEntry]| entries.iter().map(|e| e.relative.clone()).collect::<Vec<_>>();
+        assert!(!relatives(&files.source_entries()).contains(&".rmmp/build/stale".to_string()));
+        assert!(relatives(&files.source_entries()).contains(&".rmmp/Rmake.toml".to_string()));
+        assert!(!relatives(&files.module_entries()).iter().any(|r| r.starts_with(".rmmp")));
+
+        let exclusions = ExclusionSet::new(&["docs".to_string(), "*.log".to_string(), "# comment".to_string()]).unwrap();
+        let (kept, counts) = exclusions.filter(files.module_entries());
+        assert_eq!(relatives(&kept), vec!["module.prop", "system", "system/bin", "system/bin/tool"]);
+        assert_eq!(counts, vec![("docs", 2), ("*.log", 1)]);
+
+        let dest = project_path.join(".rmmp/build");
+        files.copy_to(&kept, &dest, &LineEndingRules::empty()).unwrap();
+        assert!(dest.join("system/bin/tool").exists());
+        assert!(!dest.join("docs").exists());
+    }
+
+    #[test]
+    fn test_default_exclusions() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        for dir in [".git", "lib/sub/.git", "lib/sub/.rmmp/build", "system/etc/.vscode", ".github"] {
+            fs::create_dir_all(project_path.join(dir)).unwrap();
+        }
+        for file in [".git/HEAD", "lib/sub/.git/config", "lib/sub/.rmmp/build/stale", "system/etc/.DS_Store", "system/etc/hosts",
+                     "system/etc/hosts~", ".github/README.md", ".gitignore", "Thumbs.db", "system/etc/.vscode/settings.json"] {
+            fs::write(project_path.join(file), "x").unwrap();
+        }
+        let files = ProjectFiles::scan(project_path).unwrap();
+        let relatives = |entries: &[&FileEntry]| entries.iter().map(|e| e.relative.clone()).collect::<Vec<_>>();
+
+        // 只按名称整层匹配：.github 与 .gitignore 不受 .git 影响
+        let defaults = DefaultExclusions::new(&[]).unwrap();
+        let (kept, counts) = defaults.filter(files.module_entries());
+        assert_eq!(relatives(&kept), vec![".github", ".github/README.md", ".gitignore", "lib", "lib/sub", "system", "system/etc", "system/etc/hosts"]);
+        assert_eq!(counts, vec![(".git", 4), ("Thumbs.db", 1), (".rmmp", 3), (".DS_Store", 1), (".vscode", 2), ("*~", 1)]);
+        // 源代码包保留根目录的 .rmmp/Rmake.toml
+        assert_eq!(defaults.matching(".rmmp/Rmake.toml", false), None);
+
+        // allow 放行
+        let defaults = DefaultExclusions::new(&["system/etc/.vscode".to_string()]).unwrap();
+        assert_eq!(defaults.matching("system/etc/.vscode/settings.json", false), None);
+        assert_eq!(defaults.allowed_by("system/etc/.vscode/settings.json", false), Some("system/etc/.vscode"));
+        assert_eq!(defaults.matching("lib/sub/.git/config", false), Some(".git"));
+        assert_eq!(defaults.allowed_by("system/etc/hosts", false), None);
+    }
+
+    #[test]
+    fn test_exclusion_set_gitignore_rules() {
+        let patterns = ["src/**/*.tmp", "*.log", "!keep.log", "build/", "/docs", "lib/*.so"].map(String::from);
+        let set = ExclusionSet::new(&patterns).unwrap();
+
+        // ** 匹配任意层目录，* 不跨越 /
+        assert_eq!(set.matched("src/a/b/c.tmp", false), Some("src/**/*.tmp"));
+        assert_eq!(set.matched("src/c.tmp", false), Some("src/**/*.tmp"));
+        assert_eq!(set.matched("other/src/c.tmp", false), None);
+        assert_eq!(set.matched("lib/arm64/libfoo.so", false), None);
+        // 不含 / 的模式匹配任一层的名称，! 重新包含，最后一条匹配的规则生效
+        assert_eq!(set.matched("system/bin/debug.log", false), Some("*.log"));
+        assert_eq!(set.matched("system/bin/keep.log", false), None);
+        assert_eq!(set.matching("system/bin/keep.log", false), vec!["*.log", "!keep.log"]);
+        // / 结尾只匹配目录，目录中的内容一并排除
+        assert_eq!(set.matched("build", false), None);
+        assert_eq!(set.matched("build", true), Some("build/"));
+        assert_eq!(set.matched("web/build/out.js", false), Some("build/"));
+        // / 开头只匹配根目录，名称整体匹配
+        assert_eq!(set.matched("docs/guide.md", false), Some("/docs"));
+        assert_eq!(set.matched("system/docs/guide.md", false), None);
+        assert_eq!(set.matched("mydocs", true), None);
+
+        let entries = [("build", true), ("build/a", false), ("a.log", false), ("keep.log", false)]
+            .map(|(relative, is_dir)| FileEntry { relative: relative.to_string(), path: PathBuf::from(relative), is_dir, is_symlink: false });
+        let (kept, counts) = set.filter(entries.iter().collect());
+        assert_eq!(kept.iter().map(|e| e.relative.as_str()).collect::<Vec<_>>(), vec!["keep.log"]);
+        assert_eq!(counts, vec![("src/**/*.tmp", 0), ("*.log", 1), ("build/", 2), ("/docs", 0), ("lib/*.so", 0)]);
+
+        assert!(ExclusionSet::new(&["!/".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_exclusion_set_excluded_dir_stays_excluded() {
+        // 与 gitignore 相同：目录被排除后，! 规则无法重新包含其中的文件
+        let set = ExclusionSet::new(&["build/".to_string(), "!build/keep".to_string()]).unwrap();
+        assert_eq!(set.matched("build/keep", false), Some("build/"));
+        assert_eq!(set.matching("build/keep", false), vec!["build/", "!build/keep"]);
+        // 只排除目录中的内容时可以重新包含
+        let set = ExclusionSet::new(&["build/*".to_string(), "!build/keep".to_string()]).unwrap();
+        assert_eq!(set.matched("build/keep", false), None);
+        assert_eq!(set.matched("build/other", false), Some("build/*"));
+    }
+
+    #[test]
+    fn test_exclusion_set_accepts_legacy_patterns() {
+        assert_eq!(rewrite_legacy_pattern("**.log"), "**/*.log");
+        assert_eq!(rewrite_legacy_pattern("src/**.tmp"), "src/**/*.tmp");
+        assert_eq!(rewrite_legacy_pattern("!cache**"), "!cache*");
+        assert_eq!(rewrite_legacy_pattern("src/**/*.tmp"), "src/**/*.tmp");
+
+        let set = ExclusionSet::new(&["**.log".to_string(), "src/**.tmp".to_string()]).unwrap();
+        assert_eq!(set.matched("system/bin/debug.log", false), Some("**.log"));
+        assert_eq!(set.matched("src/a/b.tmp", false), Some("src/**.tmp"));
+        assert_eq!(set.matched("other/b.tmp", false), None);
+    }
+
+    #[test]
+    fn test_copy_converts_bom_and_utf16() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::write(project_path.join("customize.sh"), b"\xEF\xBB\xBF#!/system/bin/sh\r\nui_print hi\r\n").unwrap();
+        let utf16: Vec<u8> = [0xFF, 0xFE].into_iter().chain("id=demo\r\n".encode_utf16().flat_map(u16::to_le_bytes)).collect();
+        fs::write(project_path.join("module.prop"), utf16).unwrap();
+
+        let files = ProjectFiles::scan(project_path).unwrap();
+        let dest = TempDir::new().unwrap();
+        files.copy_to(&files.module_entries(), dest.path(), &LineEndingRules::empty()).unwrap();
+        assert_eq!(fs::read(dest.path().join("customize.sh")).unwrap(), b"#!/system/bin/sh\nui_print hi\n");
+        assert_eq!(fs::read(dest.path().join("module.prop")).unwrap(), b"id=demo\n");
+    }
+
+    #[test]
+    fn test_copy_applies_line_ending_rules() {
+        use crate::core::encoding::LineEnding;
+        use std::collections::BTreeMap;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join("helper")).unwrap();
+        fs::write(project_path.join("helper/tool.ini"), b"[a]\nkey=1\r\nx=2\n").unwrap();
+        fs::write(project_path.join("helper/raw.sh"), b"echo a\r\n").unwrap();
+        fs::write(project_path.join("service.sh"), b"echo a\r\n").unwrap();
+
+        let rules = LineEndingRules::new(&BTreeMap::from([
+            ("*.ini".to_string(), LineEnding::Crlf),
+            ("helper/*.sh".to_string(), LineEnding::Keep),
+        ])).unwrap();
+        // 更具体的模式优先，未匹配的文本文件仍转为 LF
+        assert_eq!(rules.matching("helper/raw.sh"), Some(("helper/*.sh", LineEnding::Keep)));
+        assert_eq!(rules.policy("service.sh"), Some(LineEnding::Lf));
+        assert_eq!(rules.policy("system/bin/tool"), None);
+
+        let files = ProjectFiles::scan(project_path).unwrap();
+        let dest = TempDir::new().unwrap();
+        files.copy_to(&files.module_entries(), dest.path(), &rules).unwrap();
+        assert_eq!(fs::read(dest.path().join("helper/tool.ini")).unwrap(), b"[a]\r\nkey=1\r\nx=2\r\n");
+        assert_eq!(fs::read(dest.path().join("helper/raw.sh")).unwrap(), b"echo a\r\n");
+        assert_eq!(fs::read(dest.path().join("service.sh")).unwrap(), b"echo a\n");
+        // crlf 与 keep 不修改源文件
+        assert_eq!(fs::read(project_path.join("helper/tool.ini")).unwrap(), b"[a]\nkey=1\r\nx=2\n");
+        assert!(LineEndingRules::new(&BTreeMap::from([("[".to_string(), LineEnding::Lf)])).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_non_utf8_names_and_contents() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let name = OsStr::from_bytes(b"caf\xe9.bin");
+        fs::create_dir_all(project_path.join("system")).unwrap();
+        fs::write(project_path.join("system").join(name), b"\xff\x00").unwrap();
+        fs::write(project_path.join("service.sh"), b"echo \xe9\r\nexit 0\r\n").unwrap();
+
+        let files = ProjectFiles::scan(project_path).unwrap();
+        let entry = files.module_entries().into_iter().find(|e| e.path.ends_with(name)).unwrap();
+        assert_eq!(entry.relative, "system/caf\u{FFFD}.bin");
+
+        let dest = TempDir::new().unwrap();
+        files.copy_to(&files.module_entries(), dest.path(), &LineEndingRules::empty()).unwrap();
+        assert_eq!(fs::read(dest.path().join("system").join(name)).unwrap(), b"\xff\x00");
+        assert_eq!(fs::read(dest.path().join("service.sh")).unwrap(), b"echo \xe9\nexit 0\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_keeps_symlinks_and_modes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join("system/bin")).unwrap();
+        fs::create_dir_all(project_path.join("shared")).unwrap();
+        fs::write(project_path.join("shared/lib.sh"), "echo lib\r\n").unwrap();
+        fs::set_permissions(project_path.join("shared/lib.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink("../../shared/lib.sh", project_path.join("system/bin/lib")).unwrap();
+        std::os::unix::fs::symlink("shared", project_path.join("linked-dir")).unwrap();
+
+        let files = ProjectFiles::scan(project_path).unwrap();
+        let entries = files.module_entries();
+        let link = entries.iter().find(|e| e.relative == "system/bin/lib").unwrap();
+        assert!(link.is_symlink && !link.is_dir);
+        // 目录链接不展开
+        assert!(!entries.iter().any(|e| e.relative.starts_with("linked-dir/")));
+
+        let dest = TempDir::new().unwrap();
+        files.copy_to(&entries, dest.path(), &LineEndingRules::empty()).unwrap();
+        assert_eq!(fs::read_link(dest.path().join("system/bin/lib")).unwrap(), Path::new("../../shared/lib.sh"));
+        assert!(fs::symlink_metadata(dest.path().join("linked-dir")).unwrap().file_type().is_symlink());
+        // 行尾规范化后仍保留可执行位
+        assert_eq!(fs::metadata(dest.path().join("shared/lib.sh")).unwrap().permissions().mode() & 0o777, 0o755);
+    }
+}
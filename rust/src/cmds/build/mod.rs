@@ -1,13 +1,70 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::io::{Write};
 
-use crate::core::rmm_core::RmakeConfig;
+use crate::core::privacy::{local_roots, scan_path_leaks};
+use crate::core::provenance::{write_provenance, ProvenanceInput};
+use crate::core::recovery::{create_recovery_variant, verify_identical_payloads, RECOVERY_SUFFIX};
+use crate::core::process::{set_timeouts, CommandExt, CommandKind, TempGuard};
+use crate::core::rmm_core::{RmakeConfig, RmmCore};
+use crate::core::sandbox::{commands_digest, BUILD_SCRIPTS, is_trusted, trust, Sandbox, SandboxOptions};
+use crate::core::settings::ConfigResolver;
+use crate::core::shellcheck_wiki::{WikiCache, WikiExcerpt};
+use crate::core::bootloop::{guard_post_fs_data, guard_service, validate_guard, POST_FS_DATA_SCRIPT, SERVICE_SCRIPT};
+use crate::core::action::{generate_action_script, validate_action_script, ACTION_SCRIPT};
+use crate::core::env_file::{interpolate_toml, load_project_env};
+use crate::core::events::record_event_or_warn;
+use crate::core::exclude_presets::{build_excludes, source_excludes};
+use crate::core::generate::{generate_files, generation_vars};
+use crate::core::gitignore::ensure_rmmp_gitignore;
+use crate::core::layout::{inspect_rmmp, LayoutIssue};
+use crate::core::hashing::{sha256_file, HashAlgorithm};
+use crate::core::encoding::{to_utf8, LineEnding, LineEndingRules, TextEncoding};
+use crate::core::known_issues::KnownIssuesDatabase;
+use crate::core::check_report::{known_issue_findings, record_findings, Finding, FindingSeverity, SourceRange, KNOWN_ISSUES_TOOL};
+use crate::cmds::check::report_known_issues;
+use crate::core::i18n::validate_i18n;
+use crate::core::shell_script::ScriptMatcher;
+use crate::core::symbols::{find_strip_tool, split_symbols, SymbolEntry, SymbolsManifest, SYMBOLS_MANIFEST};
+use crate::core::size_history::{check_growth, format_size, growth_percent, SizeHistory, SizeRecord};
+use crate::core::zip_inspect::{parse_module_prop, read_prop_text};
+use crate::core::verify::{generate_verify_script, load_verify_config, VERIFY_SCRIPT};
+use crate::core::uninstall::{generate_uninstall_script, UNINSTALL_SCRIPT};
+use crate::core::control_files::write_control_files;
+use crate::core::mount_strategy::{bind_post_fs_data, bind_targets, MountStrategy};
+use crate::core::module_props::{integrity_warnings, render_system_prop, resetprop_post_fs_data, validate_props, ModuleProp, PropMethod, SYSTEM_PROP};
+use crate::core::lockfile::{fetch_asset, LockFile, LockStatus};
+use crate::core::remote_cache::RemoteCache;
+use crate::core::preflight::{check_path_lengths, check_space, dir_size, estimate_build_space, max_path_len, PreflightIssue};
+use crate::core::licenses::{collect_licenses, render_licenses, LICENSES_FILE};
+use crate::core::migrations::{append_to_customize, load_migrations, validate_migrations, CUSTOMIZE_SCRIPT};
+use crate::core::storage::RmmStorage;
+use crate::core::rmm_version::ensure_rmm_version;
+use crate::core::script_deps::{bundle_scripts, expand_script_refs, vendor_bundled_scripts, BUNDLED_SCRIPTS_DIR, VENDORED_SCRIPTS_DIR};
+use crate::core::checksums::{write_checksum_manifest, CHECKSUMS_FILE, CHECKSUMS_SIGNATURE_FILE};
+use crate::core::signing::{resolve_secret_key, write_embedded_signature, SecretKey};
+use crate::cmds::sign::sign_files;
+use crate::core::config_refs::{describe_issue, reference_issues, unpackaged_docs};
+use crate::core::update_json::{remove_stale_manifests, write_channel_manifests, UpdateBackend};
+use crate::core::errors::ProjectNotFoundError;
+
+mod archive;
+pub mod bench;
+pub mod cache;
+mod files;
+mod state;
+pub mod why;
+
+use archive::{create_zip_archive, PermissionRules};
+use cache::{plan_build, pull_remote, push_remote, BuildFingerprint, BuildPhase, BuildPlan};
+use files::{print_exclusions, print_line_endings, DefaultExclusions, ExclusionSet, FileEntry, ProjectFiles};
+use state::BuildState;
 
 /// Shellcheck 检查结果
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -37,6 +94,38 @@ struct ShellcheckReplacement {
     replacement: String,
 }
 
+impl ShellcheckIssue {
+    /// 转换为 check-report.json 的统一格式，路径相对于构建目录
+    fn to_finding(&self, build_dir: &Path) -> Finding {
+        let severity = match self.level.as_str() {
+            "error" => FindingSeverity::Error,
+            "warning" => FindingSeverity::Warning,
+            "info" => FindingSeverity::Info,
+            _ => FindingSeverity::Style,
+        };
+        let file = Path::new(&self.file).strip_prefix(build_dir).unwrap_or(Path::new(&self.file));
+        let mut finding = Finding::new(SHELLCHECK_TOOL, severity, self.message.clone())
+            .in_file(file.to_string_lossy().replace('\\', "/"), None)
+            .with_range(SourceRange {
+                line: self.line,
+                column: Some(self.column),
+                end_line: Some(self.end_line),
+                end_column: Some(self.end_column),
+            })
+            .with_code(format!("SC{}", self.code));
+        if let Some(fix) = &self.fix {
+            let replacements: Vec<String> = fix.replacements.iter()
+                .map(|r| format!("{}:{}-{}:{} → {:?}", r.line, r.column, r.end_line, r.end_column, r.replacement))
+                .collect();
+            finding = finding.with_fix(format!("替换 {}", replacements.join("; ")));
+        }
+        finding
+    }
+}
+
+/// shellcheck 在 check-report.json 中的工具名
+const SHELLCHECK_TOOL: &str = "shellcheck";
+
 /// Shellcheck 输出结果汇总
 #[derive(Debug, Serialize, Deserialize)]
 struct ShellcheckReport {
@@ -47,40 +136,293 @@ struct ShellcheckReport {
     info_count: u32,
     style_count: u32,
     issues: Vec<ShellcheckIssue>,
+    /// 涉及规则的说明摘要，键为 SC 代码（如 SC2086）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    wiki: BTreeMap<String, WikiExcerpt>,
+}
+
+/// 构建选项
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    /// 启用 shellcheck 自动修复
+    pub auto_fix: bool,
+    /// 打印每个阶段运行或跳过的原因
+    pub explain: bool,
+    /// 忽略构建缓存，强制完整构建
+    pub force: bool,
+    /// 从上次失败的阶段继续构建
+    pub resume: bool,
+    /// 调试构建：打包 verify.sh，不写入构建缓存与大小历史
+    pub debug: bool,
+    /// 额外生成 Recovery 卡刷包（与 Rmake.toml [build] recovery 任一开启即可）
+    pub recovery: bool,
+    /// 信任当前构建命令并记录到 meta.toml
+    pub trust: bool,
+    /// 在沙箱中运行 prebuild/postbuild，None 表示直接运行
+    pub sandbox: Option<SandboxOptions>,
+    /// 传给构建命令的额外环境变量（工作区构建用于传递依赖成员的产物目录）
+    pub env: BTreeMap<String, String>,
+    /// 增量构建（rmm watch）：保留 .rmmp/build，只重新复制这些变更的文件（相对项目根目录）；None 为完整构建
+    pub incremental: Option<BTreeSet<String>>,
+    /// 签名产物（密钥与范围见 Rmake.toml [sign]）
+    pub sign: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            auto_fix: true, // 默认启用自动修复
+            explain: false,
+            force: false,
+            resume: false,
+            debug: false,
+            recovery: false,
+            trust: false,
+            sandbox: None,
+            env: BTreeMap::new(),
+            incremental: None,
+            sign: false,
+        }
+    }
 }
 
 /// 构建模块项目
 pub fn build_project(project_path: &Path) -> Result<()> {
-    build_project_with_options(project_path, true) // 默认启用自动修复
+    build_project_with_options(project_path, &BuildOptions::default())
 }
 
 /// 构建模块项目（带选项）
-pub fn build_project_with_options(project_path: &Path, auto_fix: bool) -> Result<()> {
+pub fn build_project_with_options(project_path: &Path, options: &BuildOptions) -> Result<()> {
     println!("{}", "🔨 开始构建模块项目".green().bold());
+    let started_on = chrono::Utc::now().to_rfc3339();
     
     // 检查项目是否有效
     if !is_valid_project(project_path) {
-        anyhow::bail!("当前目录不是有效的 RMM 项目");
+        return Err(ProjectNotFoundError { path: project_path.to_path_buf() }.into());
+    }
+    ensure_rmm_version(project_path)?;
+    
+    // 为旧项目补充 .rmmp/.gitignore
+    if ensure_rmmp_gitignore(project_path)? {
+        println!("{} 已生成 .rmmp/.gitignore，构建输出将不再被 Git 跟踪", "[+]".green().bold());
+    }
+
+    // 旧版 RMM 留下的条目
+    let legacy: Vec<String> = inspect_rmmp(project_path)?.into_iter()
+        .filter(|issue| matches!(issue, LayoutIssue::Legacy { .. }))
+        .map(|issue| issue.name().to_string())
+        .collect();
+    if !legacy.is_empty() {
+        println!("{} .rmmp 中有旧版遗留条目: {}，运行 {} 迁移或清理", "[!]".yellow(), legacy.join(", "), "rmm clean --layout".cyan());
     }
     
     // 解析 Rmake.toml 配置
     let rmake_config = load_rmake_config(project_path)?;
+    set_timeouts(Some(ConfigResolver::new(Some(project_path)).timeouts()));
     println!("{} 解析构建配置", "[+]".green().bold());
     
-    // 创建构建目录
-    setup_build_directories(project_path)?;
+    // 提前检查签名私钥，避免构建完成后才失败
+    if options.sign {
+        signing_key(&rmake_config)?;
+    }
+    
+    // 根据 Rmake.toml 与文件哈希决定需要执行的阶段（签名构建总是重新打包，嵌入的签名取决于密钥）
+    let mut plan = plan_build(project_path, options.force || options.debug || options.sign)?;
+    if options.explain {
+        plan.explain();
+    }
+    if plan.is_up_to_date() {
+        println!("{} 输入未变化，跳过构建（使用 --force 强制重新构建）", "[cache]".cyan().bold());
+        return Ok(());
+    }
+    
+    // 远程缓存（cache.remote）中有相同输入的构建时直接取回产物；调试构建不读写远程缓存
+    let remote_cache = match RemoteCache::from_config() {
+        Ok(remote) => remote.filter(|_| !options.debug),
+        Err(e) => {
+            println!("{} 远程缓存配置无效，已忽略: {}", "[!]".yellow(), e);
+            None
+        }
+    };
+    if let Some(ref remote) = remote_cache
+        && !options.force
+        && !options.sign
+        && pull_remote(project_path, remote, &mut plan.fingerprint)
+    {
+        return Ok(());
+    }
+    
+    // 未信任的构建命令需要 --trust 确认，或在沙箱中运行
+    let mut runner = prepare_command_runner(project_path, &rmake_config, options)?;
+    runner.env = options.env.clone();
+    
+    // 恢复上次失败的构建（输入未变化时）
+    let resumed = if options.resume {
+        let state = BuildState::load_matching(project_path, &plan.fingerprint);
+        match &state {
+            Some(s) => println!("{} 从阶段 {} 继续构建", "[resume]".cyan().bold(),
+                s.failed.as_deref().unwrap_or("-")),
+            None => println!("{} 没有可恢复的构建状态，执行完整构建", "[resume]".cyan().bold()),
+        }
+        state
+    } else {
+        BuildState::clear(project_path);
+        None
+    };
+    let mut state = resumed
+        .filter(|s| s.has_progress())
+        .unwrap_or_else(|| BuildState::new(&plan.fingerprint));
+    
+    // 扫描一次项目文件，模块与源代码打包共用
+    let files = ProjectFiles::scan(project_path)?;
+    
+    // 清理构建目录前检查磁盘空间与路径长度，避免构建到一半失败
+    preflight_build(project_path, &files, !state.has_progress())?;
+    
+    // 创建构建目录（恢复构建时保留已完成阶段的输出，增量构建只清理变更的文件）
+    if !state.has_progress() {
+        match incremental_changes(project_path, &rmake_config, options) {
+            Some(changed) => prepare_incremental_build(project_path, changed)?,
+            None => setup_build_directories(project_path)?,
+        }
+    }
     
     // 执行构建流程
-    execute_build_process(project_path, &rmake_config, auto_fix)?;
+    execute_build_process(project_path, &rmake_config, options, &plan, &mut state, &files, &runner)?;
     
-    // 执行源代码打包流程
-    execute_source_packaging(project_path, &rmake_config)?;
+    // 执行源代码打包流程（构建命令可能在项目中生成文件，此时重新扫描）
+    if plan.should_run(BuildPhase::Source) {
+        let files = if runs_project_commands(project_path, &rmake_config) {
+            ProjectFiles::scan(project_path)?
+        } else {
+            files
+        };
+        state.run_phase(project_path, BuildPhase::Source, || {
+            execute_source_packaging(project_path, &rmake_config, &files, &runner)
+        })?;
+    }
+    
+    // 生成构建溯源（产物哈希、提交、配置哈希与输入摘要）
+    let mut provenance_options = BTreeMap::from([("debug".to_string(), serde_json::Value::from(options.debug))]);
+    if let Some(ref sandbox) = runner.sandbox {
+        provenance_options.insert("sandbox".to_string(), serde_json::Value::from(format!("{:?}", sandbox.isolation()).to_lowercase()));
+    }
+    let provenance_input = ProvenanceInput {
+        inputs_digest: plan.fingerprint.digest(),
+        input_files: plan.fingerprint.files.len(),
+        started_on,
+        options: provenance_options,
+    };
+    match write_provenance(project_path, &provenance_input) {
+        Ok((statement, path)) => println!("{} 生成构建溯源: {}（{} 个产物）", "[+]".green().bold(), path.display(), statement.subject.len()),
+        Err(e) => println!("{} 无法生成构建溯源: {}", "[!]".yellow(), e),
+    }
+    
+    // 构建成功：清除进度记录并保存输入指纹（调试构建清除缓存，下次普通构建完整执行）
+    BuildState::clear(project_path);
+    if options.debug {
+        BuildFingerprint::clear(project_path);
+    } else if let Err(e) = plan.fingerprint.save(project_path) {
+        println!("{} 无法写入构建缓存: {}", "[!]".yellow(), e);
+    } else if let Some(ref remote) = remote_cache {
+        push_remote(project_path, remote, &plan.fingerprint);
+    }
     
     println!("\n{}", "🎉 模块构建完成！".green().bold());
     
     Ok(())
 }
 
+/// 构建过程中是否会执行项目命令或脚本（可能在项目目录中生成文件）
+fn runs_project_commands(project_path: &Path, rmake_config: &RmakeConfig) -> bool {
+    let build = &rmake_config.build;
+    !build.prebuild.is_empty() || !build.build.is_empty() || !build.postbuild.is_empty()
+        || project_path.join("scripts/prebuild.sh").exists()
+        || project_path.join("scripts/postbuild.sh").exists()
+}
+
+/// prebuild / postbuild 的运行方式
+struct CommandRunner {
+    sandbox: Option<Sandbox>,
+    /// 附加到每个命令的环境变量，命令自身的同名变量优先
+    env: BTreeMap<String, String>,
+}
+
+impl CommandRunner {
+    fn command(&self, project_path: &Path, program: &str, args: &[&str], env: &BTreeMap<String, String>) -> Command {
+        let mut merged = self.env.clone();
+        merged.extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let env = &merged;
+        match self.sandbox {
+            Some(ref sandbox) => sandbox.command(project_path, program, args, env),
+            None => {
+                let mut command = Command::new(program);
+                command.args(args).current_dir(project_path).envs(env);
+                command
+            }
+        }
+    }
+
+    fn shell(&self, project_path: &Path, command: &str, env: &BTreeMap<String, String>) -> Command {
+        if cfg!(target_os = "windows") {
+            self.command(project_path, "cmd", &["/C", command], env)
+        } else {
+            self.command(project_path, "sh", &["-c", command], env)
+        }
+    }
+}
+
+/// 决定构建命令的运行方式：沙箱模式直接使用沙箱；否则命令必须已被信任（或本次使用 --trust）
+fn prepare_command_runner(project_path: &Path, rmake_config: &RmakeConfig, options: &BuildOptions) -> Result<CommandRunner> {
+    if let Some(ref sandbox_options) = options.sandbox {
+        let sandbox = Sandbox::new(sandbox_options.clone())?;
+        println!("{} 在沙箱中运行构建命令: {}", "[sandbox]".magenta().bold(), sandbox.isolation().describe());
+        if !sandbox.isolation().is_isolated() {
+            println!("{} 沙箱仅隔离环境变量，构建命令仍可读写任意文件并访问网络", "[!]".yellow());
+        }
+        return Ok(CommandRunner { sandbox: Some(sandbox), env: BTreeMap::new() });
+    }
+    let build = &rmake_config.build;
+    let Some(digest) = commands_digest(project_path, &build.prebuild, &build.postbuild) else {
+        return Ok(CommandRunner { sandbox: None, env: BTreeMap::new() });
+    };
+
+    let core = RmmCore::new();
+    let mut meta = core.get_meta_config().unwrap_or_default();
+    if is_trusted(&meta, project_path, &digest) {
+        return Ok(CommandRunner { sandbox: None, env: BTreeMap::new() });
+    }
+    if !options.trust {
+        println!("{} 以下构建命令将以当前用户权限运行：", "[trust]".yellow().bold());
+        for (stage, command) in build.prebuild.iter().map(|c| ("prebuild", c)).chain(build.postbuild.iter().map(|c| ("postbuild", c))) {
+            println!("    {}: {}", stage, command.cyan());
+        }
+        for script in BUILD_SCRIPTS {
+            if project_path.join(script).exists() {
+                println!("    {}", script.cyan());
+            }
+        }
+        anyhow::bail!("构建命令尚未被信任（首次构建或命令已变更），确认安全后使用 rmm build --trust，或使用 --sandbox 在沙箱中运行");
+    }
+    trust(&mut meta, project_path, &digest);
+    core.update_meta_config(&meta)?;
+    println!("{} 已信任当前构建命令（命令变更后需重新确认）", "[trust]".green().bold());
+    Ok(CommandRunner { sandbox: None, env: BTreeMap::new() })
+}
+
+/// 信任项目当前的构建命令（rmm init 创建的项目自动信任）
+pub fn trust_project_commands(project_path: &Path) -> Result<()> {
+    let rmake_config = load_rmake_config(project_path)?;
+    let build = &rmake_config.build;
+    if let Some(digest) = commands_digest(project_path, &build.prebuild, &build.postbuild) {
+        let core = RmmCore::new();
+        let mut meta = core.get_meta_config()?;
+        trust(&mut meta, project_path, &digest);
+        core.update_meta_config(&meta)?;
+    }
+    Ok(())
+}
+
 /// 检查是否是有效的项目
 fn is_valid_project(project_path: &Path) -> bool {
     project_path.join("module.prop").exists() 
@@ -88,14 +430,55 @@ fn is_valid_project(project_path: &Path) -> bool {
         && project_path.join(".rmmp/Rmake.toml").exists()
 }
 
-/// 加载 Rmake.toml 配置
+/// 加载 Rmake.toml 配置，并用 .rmm.env 中的变量展开 ${NAME}
 fn load_rmake_config(project_path: &Path) -> Result<RmakeConfig> {
     let rmake_path = project_path.join(".rmmp/Rmake.toml");
     let content = fs::read_to_string(&rmake_path)?;
-    let config: RmakeConfig = toml::from_str(&content)?;
+    let mut value: toml::Value = toml::from_str(&content)?;
+    interpolate_toml(&mut value, &load_project_env(project_path)?);
+    let config: RmakeConfig = value.try_into()?;
     Ok(config)
 }
 
+/// 构建前检查：.rmmp 所在磁盘的可用空间（源文件总大小 × preflight.space_factor）与构建目录中的路径长度
+fn preflight_build(project_path: &Path, files: &ProjectFiles, cleans_build_dir: bool) -> Result<()> {
+    let config = ConfigResolver::new(Some(project_path));
+    if config.get_bool("preflight.skip") {
+        return Ok(());
+    }
+    let factor = config.get_parsed::<f64>("preflight.space_factor")?.unwrap_or(3.0);
+    let source_bytes: u64 = files.source_entries().iter()
+        .filter(|e| !e.is_dir && !e.is_symlink)
+        .filter_map(|e| fs::metadata(project_path.join(&e.path)).ok())
+        .map(|m| m.len())
+        .sum();
+    let build_dir = project_path.join(".rmmp/build");
+    // 旧的构建目录会先被清理，其占用计入可用空间
+    let reclaimable = if cleans_build_dir { dir_size(&build_dir) } else { 0 };
+    let mut issues: Vec<PreflightIssue> = check_space(&project_path.join(".rmmp"), estimate_build_space(source_bytes, factor), reclaimable)
+        .into_iter()
+        .collect();
+    issues.extend(check_path_lengths(&build_dir, files.module_entries().iter().map(|e| e.path.as_path()), max_path_len()));
+    if issues.is_empty() {
+        return Ok(());
+    }
+    
+    for issue in issues.iter().take(10) {
+        println!("{} {}", "[preflight]".red().bold(), issue.describe());
+    }
+    if issues.len() > 10 {
+        println!("{} 另有 {} 个问题未列出", "[preflight]".red().bold(), issues.len() - 10);
+    }
+    if issues.iter().any(|i| matches!(i, PreflightIssue::Space { .. })) {
+        println!("  {} 清理磁盘后重试（{} 可删除旧的构建产物），或通过 {} 调整估算系数", "💡".blue().bold(), "rmm clean".cyan(), "RMM_SPACE_FACTOR".cyan());
+    }
+    if issues.iter().any(|i| matches!(i, PreflightIssue::PathTooLong { .. })) {
+        println!("  {} 将项目移到更短的路径下，或缩短模块中的目录层级（Windows 上也可启用长路径支持）", "💡".blue().bold());
+    }
+    println!("  {} 确认无误时可设置 {} 跳过检查", "💡".blue().bold(), "RMM_SKIP_PREFLIGHT=1".cyan());
+    anyhow::bail!("构建前检查未通过：{}", issues[0].describe())
+}
+
 /// 设置构建目录
 fn setup_build_directories(project_path: &Path) -> Result<()> {
     let build_dir = project_path.join(".rmmp/build");
@@ -116,29 +499,117 @@ fn setup_build_directories(project_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// 可以增量构建时返回变更的文件：需要已有构建目录，且未开启符号拆分（strip 会就地修改构建目录中的二进制）
+fn incremental_changes<'a>(project_path: &Path, rmake_config: &RmakeConfig, options: &'a BuildOptions) -> Option<&'a BTreeSet<String>> {
+    let changed = options.incremental.as_ref()?;
+    let splits_symbols = rmake_config.build.symbols.as_ref().is_some_and(|c| c.split);
+    (project_path.join(".rmmp/build").is_dir() && !splits_symbols).then_some(changed)
+}
+
+/// 增量构建：删除变更文件在构建目录中的副本，以及根目录下的文件（脚本、module.prop 等会被重新生成或就地修改），
+/// 其余文件保留，复制阶段只补齐缺失的文件
+fn prepare_incremental_build(project_path: &Path, changed: &BTreeSet<String>) -> Result<()> {
+    let build_dir = project_path.join(".rmmp/build");
+    for relative in changed {
+        if relative.split('/').any(|part| part == "..") {
+            continue;
+        }
+        let target = build_dir.join(relative);
+        match fs::symlink_metadata(&target) {
+            Ok(meta) if meta.is_dir() => fs::remove_dir_all(&target)?,
+            Ok(_) => fs::remove_file(&target)?,
+            Err(_) => {}
+        }
+    }
+    for entry in fs::read_dir(&build_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    fs::create_dir_all(project_path.join(".rmmp/dist"))?;
+    
+    println!("{} 增量构建：{} 个文件变更，保留构建目录中的其余文件", "[+]".green().bold(), changed.len());
+    Ok(())
+}
+
 /// 执行构建流程
 fn execute_build_process(
     project_path: &Path,
     rmake_config: &RmakeConfig,
-    auto_fix: bool,
+    options: &BuildOptions,
+    plan: &BuildPlan,
+    state: &mut BuildState,
+    files: &ProjectFiles,
+    runner: &CommandRunner,
 ) -> Result<()> {
-    // 1. 复制文件到构建目录
-    copy_files_to_build(project_path, rmake_config)?;
-    
-    // 2. 复制 update.json 到 dist 目录
-    copy_update_json_to_dist(project_path)?;
-    
-    // 3. 执行 shell 脚本检查
-    check_shell_scripts(project_path, auto_fix)?;
-    
-    // 4. 执行 prebuild 配置
-    execute_prebuild(project_path, rmake_config)?;
+    // 1. 复制文件到构建目录，校验并复制 update.json 到 dist 目录
+    state.run_phase(project_path, BuildPhase::Copy, || {
+        validate_locales(project_path, rmake_config)?;
+        let incremental = incremental_changes(project_path, rmake_config, options).is_some();
+        copy_files_to_build(project_path, rmake_config, files, incremental)?;
+        warn_config_references(project_path);
+        fetch_assets(project_path, rmake_config)?;
+        bundle_script_dependencies(project_path, rmake_config)?;
+        write_third_party_licenses(project_path, rmake_config)?;
+        prepare_action_script(project_path, rmake_config)?;
+        prepare_uninstall_script(project_path, rmake_config)?;
+        prepare_module_props(project_path, rmake_config)?;
+        prepare_bind_mounts(project_path, rmake_config)?;
+        prepare_bootloop_guard(project_path, rmake_config)?;
+        prepare_migrations(project_path)?;
+        prepare_control_files(project_path, rmake_config)?;
+        render_generated_files(project_path, rmake_config)?;
+        if options.debug {
+            write_verify_script(project_path)?;
+        }
+        copy_update_json_to_dist(project_path, rmake_config)
+    })?;
+    
+    // 2. 执行 shell 脚本检查（脚本未变更时跳过）
+    if plan.should_run(BuildPhase::Shellcheck) {
+        state.run_phase(project_path, BuildPhase::Shellcheck, || {
+            check_shell_scripts(project_path, rmake_config, options.auto_fix)
+        })?;
+    }
     
-    // 5. 打包模块
-    package_module(project_path, rmake_config)?;
+    // 3. 执行 prebuild 配置
+    state.run_phase(project_path, BuildPhase::Prebuild, || {
+        execute_prebuild(project_path, rmake_config, runner)
+    })?;
+    
+    // 4. 打包模块
+    state.run_phase(project_path, BuildPhase::Package, || {
+        // 调试构建保留符号，不拆分
+        let symbols = if options.debug { Vec::new() } else { strip_native_symbols(project_path, rmake_config)? };
+        let signer = if options.sign { Some(signing_key(rmake_config)?) } else { None };
+        let zip_path = package_module(project_path, rmake_config, options.debug, signer.as_ref())?;
+        check_known_issues(project_path, rmake_config, &zip_path)?;
+        let mut signed = vec![zip_path.clone()];
+        if options.recovery || rmake_config.build.recovery {
+            signed.push(package_recovery_variant(project_path, &zip_path)?);
+        }
+        if !symbols.is_empty() {
+            package_symbols(project_path, symbols)?;
+        }
+        if let Some(key) = &signer {
+            let update_json = rmake_config.sign.as_ref().is_some_and(|sign| sign.update_json);
+            sign_files(project_path, key, &signed, update_json)?;
+        }
+        if !options.debug {
+            record_artifact_size(project_path, rmake_config, &zip_path)?;
+        }
+        record_event_or_warn(project_path, "build", &[
+            ("artifact", zip_path.file_name().unwrap_or_default().to_string_lossy().to_string()),
+            ("sha256", sha256_file(&zip_path)?),
+        ]);
+        Ok(())
+    })?;
     
-    // 6. 执行 postbuild
-    execute_postbuild(project_path, rmake_config)?;
+    // 5. 执行 postbuild
+    state.run_phase(project_path, BuildPhase::Postbuild, || {
+        execute_postbuild(project_path, rmake_config, runner)
+    })?;
     
     Ok(())
 }
@@ -147,96 +618,129 @@ fn execute_build_process(
 fn copy_files_to_build(
     project_path: &Path,
     rmake_config: &RmakeConfig,
+    files: &ProjectFiles,
+    incremental: bool,
 ) -> Result<()> {
     let build_dir = project_path.join(".rmmp/build");
     
-    // 获取需要复制的文件和目录
-    let entries = get_build_entries(project_path, rmake_config)?;
-    
-    for entry in entries {
-        let relative_path = entry.strip_prefix(project_path)?;
-        let dest_path = build_dir.join(relative_path);
-          if entry.is_dir() {
-            fs::create_dir_all(&dest_path)?;
-            copy_directory(&entry, &dest_path)?;
-        } else {
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent)?;
-            }
-            copy_file_with_line_ending_normalization(&entry, &dest_path)?;
-        }
+    // 先应用默认排除规则（VCS 目录、系统垃圾文件等），再应用 exclude 规则
+    let defaults = DefaultExclusions::new(&rmake_config.build.allow)?;
+    let (entries, counts) = defaults.filter(files.module_entries());
+    print_exclusions("默认排除规则", &counts);
+    let exclusions = ExclusionSet::new(&build_excludes(&rmake_config.build)?)?;
+    if !rmake_config.build.exclude_presets.is_empty() {
+        println!("    {} 排除预设: {}", "[!]".bright_yellow(), rmake_config.build.exclude_presets.join(", "));
     }
+    let (entries, counts) = exclusions.filter(entries);
+    print_exclusions("应用排除规则", &counts);
+    print_include_patterns("额外包含规则", &rmake_config.build.include);
+    let line_endings = LineEndingRules::new(&rmake_config.build.line_endings)?;
+    print_line_endings(&line_endings, &entries);
+    
+    // 增量构建只复制构建目录中缺失的文件（未变更的文件仍保留上次的副本）
+    let total = entries.len();
+    let entries: Vec<&FileEntry> = if incremental {
+        entries.into_iter().filter(|e| e.is_dir || fs::symlink_metadata(build_dir.join(&e.path)).is_err()).collect()
+    } else {
+        entries
+    };
+    files.copy_to(&entries, &build_dir, &line_endings)?;
     
-    println!("{} 复制文件到构建目录", "[+]".green().bold());
+    if incremental {
+        println!("{} 增量复制文件到构建目录（{}/{} 个条目）", "[+]".green().bold(), entries.len(), total);
+    } else {
+        println!("{} 复制文件到构建目录", "[+]".green().bold());
+    }
     Ok(())
 }
 
-/// 获取需要构建的文件和目录
-fn get_build_entries(
-    project_path: &Path,
-    rmake_config: &RmakeConfig,
-) -> Result<Vec<PathBuf>> {
-    let mut entries = Vec::new();
-    
-    // 首先获取项目中的所有文件和目录（基础文件）
-    let mut base_entries = Vec::new();
-    for entry in fs::read_dir(project_path)? {
-        let entry = entry?;
-        let path = entry.path();
-        let file_name = path.file_name().unwrap().to_string_lossy();
-        
-        // 排除 .rmmp 目录（构建系统目录）
-        if file_name == ".rmmp" {
-            continue;
+/// 配置引用的文档与脚本：缺失或大小写不一致时警告，文档被排除规则过滤时提示未打包
+fn warn_config_references(project_path: &Path) {
+    for (reference, status) in reference_issues(project_path) {
+        println!("{} {}", "[!]".yellow(), describe_issue(&reference, &status));
+    }
+    for reference in unpackaged_docs(project_path, &project_path.join(".rmmp/build")) {
+        println!("{} {} 引用的 {} 被排除规则过滤，不会打包进模块", "[!]".yellow(), reference.key, reference.path);
+    }
+}
+
+/// 下载 [assets] 中的文件到构建目录，并按 rmm.lock 校验哈希
+fn fetch_assets(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    if rmake_config.assets.is_empty() {
+        return Ok(());
+    }
+    let build_dir = project_path.join(".rmmp/build");
+    let cache_dir = RmmStorage::resolve().cache_dir();
+    let mut lock = LockFile::load(project_path)?;
+    let mut changed = false;
+    for (name, asset) in &rmake_config.assets {
+        if asset.dest.starts_with('/') || asset.dest.split(['/', '\\']).any(|part| part == "..") {
+            anyhow::bail!("[assets.{}] dest 必须是模块内的相对路径: {}", name, asset.dest);
         }
-        
-        base_entries.push(path);
-    }
-      // 应用 exclude 规则（排除文件）
-    let exclude_patterns = &rmake_config.build.exclude;
-    if !exclude_patterns.is_empty() {
-        println!("    {} 应用排除规则:", "[!]".bright_yellow());
-        for pattern in exclude_patterns {
-            println!("      - {}", pattern);
+        let (bytes, status) = fetch_asset(&mut lock, name, asset, &cache_dir, false)?;
+        let target = build_dir.join(&asset.dest);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
         }
+        fs::write(&target, bytes)?;
+        let note = match status {
+            LockStatus::Verified => "已校验",
+            LockStatus::Recorded | LockStatus::Updated => {
+                changed = true;
+                "已记录到 rmm.lock"
+            }
+        };
+        println!("{} 资源 {} → {} {}", "[+]".green().bold(), name.cyan(), asset.dest, format!("({})", note).bright_black());
     }
-    
-    base_entries.retain(|path| {
-        let file_name = path.file_name().unwrap().to_string_lossy();
-        let path_str = path.to_string_lossy();
-        
-        for pattern in exclude_patterns {
-            // 简单模式匹配
-            if pattern.contains('*') {
-                // 通配符匹配
-                if pattern.ends_with("*") {
-                    let prefix = &pattern[..pattern.len() - 1];
-                    if file_name.starts_with(prefix) || path_str.contains(prefix) {
-                        println!("      {} 排除文件: {} (匹配 {})", "[x]".red(), file_name, pattern);
-                        return false;
-                    }
-                }
-                if pattern.starts_with("*") {
-                    let suffix = &pattern[1..];
-                    if file_name.ends_with(suffix) || path_str.contains(suffix) {
-                        println!("      {} 排除文件: {} (匹配 {})", "[x]".red(), file_name, pattern);
-                        return false;
-                    }
-                }
-            } else {
-                // 精确匹配
-                if file_name == pattern.as_str() || path_str.contains(pattern) {
-                    println!("      {} 排除文件: {} (匹配 {})", "[x]".red(), file_name, pattern);
-                    return false;
-                }
+    if changed {
+        lock.save(project_path)?;
+    }
+    Ok(())
+}
+
+/// 构建命令通过 script:<id> 引用的脚本依赖：校验后复制到 .rmmp/build/scripts
+fn bundle_script_dependencies(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let build = &rmake_config.build;
+    let commands = build.prebuild.iter().chain(&build.build).chain(&build.postbuild);
+    let mut lock = LockFile::load(project_path)?;
+    let bundled = bundle_scripts(project_path, commands, &mut lock, &RmmStorage::resolve().cache_dir())?;
+    let mut changed = false;
+    for (id, status) in &bundled {
+        let note = match status {
+            LockStatus::Verified => "已校验",
+            LockStatus::Recorded | LockStatus::Updated => {
+                changed = true;
+                "已记录到 rmm.lock"
             }
-        }
-        true
-    });
+        };
+        println!("{} 脚本依赖 {} → {}/{} {}", "[+]".green().bold(), id.cyan(), BUNDLED_SCRIPTS_DIR, id, format!("({})", note).bright_black());
+    }
+    if changed {
+        lock.save(project_path)?;
+    }
+    Ok(())
+}
 
-    entries.extend(base_entries);    // 应用 include 规则（额外包含文件）
-    // include 表示额外包含的文件，这些文件可能在其他位置或者需要特别包含
-    let include_patterns: Vec<&String> = rmake_config.build.include
+/// 按 [assets] 与 [third_party] 生成 LICENSES-THIRD-PARTY 并放入模块
+fn write_third_party_licenses(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let entries = collect_licenses(project_path, rmake_config)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let module_id = read_project_info(project_path)?.id;
+    fs::write(project_path.join(".rmmp/build").join(LICENSES_FILE), render_licenses(&module_id, &entries))?;
+    println!("{} 生成 {}（{} 个第三方组件）", "[+]".green().bold(), LICENSES_FILE, entries.len());
+    let unlicensed: Vec<&str> = entries.iter().filter(|e| e.license.is_none()).map(|e| e.name.as_str()).collect();
+    if !unlicensed.is_empty() {
+        println!("{} 以下组件未声明 license: {}", "[!]".yellow(), unlicensed.join(", "));
+    }
+    Ok(())
+}
+
+/// 打印 include 规则
+/// include 表示额外包含的文件，这些文件可能在其他位置或者需要特别包含
+fn print_include_patterns(title: &str, patterns: &[String]) {
+    let include_patterns: Vec<&String> = patterns
         .iter()
         .filter(|pattern| {
             let trimmed = pattern.trim();
@@ -245,77 +749,251 @@ fn get_build_entries(
         .collect();
     
     if !include_patterns.is_empty() {
-        println!("    {} 额外包含规则:", "[+]".green());
+        println!("    {} {}:", "[+]".green(), title);
         for pattern in &include_patterns {
             println!("      + {}", pattern);
-            // 这里可以添加实际的文件搜索逻辑
-            // 现在只是提示用户这些是额外包含的文件
         }
     }
+}
+
+/// 按 [check.i18n] 校验本地化资源：文件缺失或解析失败时中止构建，键差异仅警告
+fn validate_locales(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let Some(i18n) = rmake_config.check.as_ref().and_then(|c| c.i18n.as_ref()) else {
+        return Ok(());
+    };
     
-    Ok(entries)
+    let report = validate_i18n(project_path, i18n);
+    for warning in &report.warnings {
+        println!("    {} {}", "[!]".yellow(), warning);
+    }
+    if !report.errors.is_empty() {
+        anyhow::bail!("本地化资源校验失败:\n  {}", report.errors.join("\n  "));
+    }
+    println!("{} 校验本地化资源 ({} 种语言)", "[+]".green().bold(), i18n.locales.len());
+    Ok(())
 }
 
-/// 递归复制目录
-fn copy_directory(src: &Path, dest: &Path) -> Result<()> {
-    // 🔧 修复：添加源目录有效性检查
-    if !src.exists() {
-        return Err(anyhow::anyhow!("源目录不存在: {}", src.display()));
+/// 生成（按 [action] 配置）并校验构建目录中的 action.sh
+fn prepare_action_script(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let action_path = project_path.join(".rmmp/build").join(ACTION_SCRIPT);
+    
+    if let Some(action_config) = &rmake_config.action {
+        if action_path.exists() {
+            anyhow::bail!("项目中已存在 {}，与 Rmake.toml [action] 配置冲突，请二选一", ACTION_SCRIPT);
+        }
+        fs::write(&action_path, generate_action_script(action_config)?)?;
+        println!("{} 根据 [action] 生成 {}", "[+]".green().bold(), ACTION_SCRIPT);
     }
-    if !src.is_dir() {
-        return Err(anyhow::anyhow!("源路径不是目录: {}", src.display()));
+    
+    if action_path.exists() {
+        if validate_action_script(&action_path)? {
+            println!("{} {} 缺少可执行权限，已自动设置为 0755", "[!]".yellow(), ACTION_SCRIPT);
+        }
+        println!("{} 校验 {}", "[+]".green().bold(), ACTION_SCRIPT);
     }
+    
+    Ok(())
+}
 
-    // 确保目标目录存在
-    fs::create_dir_all(dest)?;
+/// 按 [uninstall] 生成 uninstall.sh；项目自带的 uninstall.sh 保留在前，生成的清理命令追加在后
+fn prepare_uninstall_script(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let Some(config) = &rmake_config.uninstall else {
+        return Ok(());
+    };
+    let script_path = project_path.join(".rmmp/build").join(UNINSTALL_SCRIPT);
+    let existing = fs::read_to_string(&script_path).ok();
+    fs::write(&script_path, generate_uninstall_script(config, existing.as_deref())?)?;
+    println!("{} 根据 [uninstall] 生成 {}（清理 {} 个路径）", "[+]".green().bold(), UNINSTALL_SCRIPT, config.paths.len());
+    Ok(())
+}
 
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        
-        // 🔧 修复：添加路径有效性检查
-        if !src_path.exists() {
-            println!("⚠️ 警告: 源路径不存在，跳过: {}", src_path.display());
-            continue;
-        }
-        
-        let dest_path = dest.join(entry.file_name());
-        
-        if src_path.is_dir() {
-            if let Err(e) = copy_directory(&src_path, &dest_path) {
-                println!("⚠️ 警告: 复制子目录失败 {}: {}", src_path.display(), e);
-            }
+/// 按 [bootloop_guard] 在 post-fs-data.sh 与 service.sh 开头加入启动失败守护，并检查生成脚本的语法
+fn prepare_bootloop_guard(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let Some(config) = &rmake_config.bootloop_guard else {
+        return Ok(());
+    };
+    validate_guard(config)?;
+    let build_dir = project_path.join(".rmmp/build");
+    for name in [POST_FS_DATA_SCRIPT, SERVICE_SCRIPT] {
+        let script_path = build_dir.join(name);
+        let existing = fs::read_to_string(&script_path).ok();
+        let content = if name == POST_FS_DATA_SCRIPT {
+            guard_post_fs_data(config, existing.as_deref())
         } else {
-            if let Err(e) = copy_file_with_line_ending_normalization(&src_path, &dest_path) {
-                println!("⚠️ 警告: 复制文件失败 {}: {}", src_path.display(), e);
-            }
+            guard_service(config, existing.as_deref())
+        };
+        fs::write(&script_path, content)?;
+        // sh 不可用（如 Windows）时跳过语法检查，脚本仍由 shellcheck 阶段检查
+        if let Ok(output) = Command::new("sh").arg("-n").arg(&script_path).output()
+            && !output.status.success()
+        {
+            anyhow::bail!("加入 [bootloop_guard] 后 {} 存在语法错误: {}", name, String::from_utf8_lossy(&output.stderr).trim());
+        }
+    }
+    println!("{} 根据 [bootloop_guard] 加入启动失败守护（连续 {} 次未完成启动后禁用模块）", "[+]".green().bold(), config.max_attempts);
+    Ok(())
+}
+
+/// 按 [module.props] 写入 system.prop 或在 post-fs-data.sh 中加入 resetprop 调用
+fn prepare_module_props(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let Some(config) = rmake_config.module.as_ref().filter(|m| !m.props.is_empty()) else {
+        return Ok(());
+    };
+    let errors = validate_props(config);
+    if !errors.is_empty() {
+        anyhow::bail!("[module.props] 无效:\n  {}", errors.join("\n  "));
+    }
+    for warning in integrity_warnings(config.props.keys().map(String::as_str), "[module.props]") {
+        println!("{} {}", "[!]".yellow(), warning);
+    }
+    
+    let build_dir = project_path.join(".rmmp/build");
+    let (system_props, resetprops): (Vec<ModuleProp>, Vec<ModuleProp>) = config.resolve_props()
+        .into_iter()
+        .partition(|prop| prop.method == PropMethod::SystemProp);
+    if !system_props.is_empty() {
+        let path = build_dir.join(SYSTEM_PROP);
+        let existing = fs::read_to_string(&path).ok();
+        fs::write(&path, render_system_prop(existing.as_deref(), &system_props)?)?;
+    }
+    if !resetprops.is_empty() {
+        let path = build_dir.join(POST_FS_DATA_SCRIPT);
+        let existing = fs::read_to_string(&path).ok();
+        fs::write(&path, resetprop_post_fs_data(&resetprops, existing.as_deref()))?;
+    }
+    println!("{} 根据 [module.props] 设置 {} 个属性（{} 个写入 {}，{} 个通过 resetprop）", "[+]".green().bold(),
+        system_props.len() + resetprops.len(), system_props.len(), SYSTEM_PROP, resetprops.len());
+    Ok(())
+}
+
+/// [control] mount = "bind"：在 post-fs-data.sh 中逐个 bind mount 分区目录中的文件（先于启动失败守护加入，守护在最前）
+fn prepare_bind_mounts(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    if rmake_config.control.as_ref().and_then(|c| c.mount) != Some(MountStrategy::Bind) {
+        return Ok(());
+    }
+    let build_dir = project_path.join(".rmmp/build");
+    let files = bind_targets(&build_dir);
+    if files.is_empty() {
+        return Ok(());
+    }
+    let script_path = build_dir.join(POST_FS_DATA_SCRIPT);
+    let existing = fs::read_to_string(&script_path).ok();
+    fs::write(&script_path, bind_post_fs_data(&files, existing.as_deref()))?;
+    println!("{} 根据 [control] mount = \"bind\" 在 {} 中加入 {} 个文件的 bind mount", "[+]".green().bold(), POST_FS_DATA_SCRIPT, files.len());
+    Ok(())
+}
+
+/// 按 rmmproject.toml [[migrations]] 打包迁移脚本，并在 customize.sh 末尾追加调用
+fn prepare_migrations(project_path: &Path) -> Result<()> {
+    let migrations = load_migrations(project_path)?;
+    if migrations.is_empty() {
+        return Ok(());
+    }
+    let errors = validate_migrations(project_path, &migrations);
+    if !errors.is_empty() {
+        anyhow::bail!("[[migrations]] 无效:\n  {}", errors.join("\n  "));
+    }
+    let build_dir = project_path.join(".rmmp/build");
+    for migration in &migrations {
+        let script = migration.script.trim_start_matches("./");
+        let target = build_dir.join(script);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
         }
+        fs::copy(project_path.join(script), &target)?;
+    }
+    let script_path = build_dir.join(CUSTOMIZE_SCRIPT);
+    let existing = fs::read_to_string(&script_path).ok();
+    fs::write(&script_path, append_to_customize(existing.as_deref(), &migrations))?;
+    println!("{} 根据 [[migrations]] 在 {} 中加入 {} 个迁移脚本", "[+]".green().bold(), CUSTOMIZE_SCRIPT, migrations.len());
+    Ok(())
+}
+
+/// 按 [control] 写入 skip_mount / disable / remove
+fn prepare_control_files(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let Some(config) = &rmake_config.control else {
+        return Ok(());
+    };
+    let written = write_control_files(&project_path.join(".rmmp/build"), config)?;
+    if !written.is_empty() {
+        println!("{} 根据 [control] 写入控制文件: {}", "[+]".green().bold(), written.join(", "));
+    }
+    if config.remove {
+        println!("{} [control] remove = true：安装后下次重启模块就会被删除", "[!]".yellow());
+    }
+    Ok(())
+}
+
+/// 按 [[build.generate]] 渲染文件到构建目录
+fn render_generated_files(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
+    let rules = &rmake_config.build.generate;
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let build_dir = project_path.join(".rmmp/build");
+    let vars = generation_vars(project_path)?;
+    for path in generate_files(&build_dir, rules, &vars)? {
+        let relative = path.strip_prefix(&build_dir).unwrap_or(&path);
+        println!("{} 生成 {}", "[+]".green().bold(), relative.display());
     }
     Ok(())
 }
 
-/// 复制 update.json 到 dist 目录
-fn copy_update_json_to_dist(project_path: &Path) -> Result<()> {
+/// 调试构建：按 rmmproject.toml [verify] 生成 verify.sh 到构建目录
+fn write_verify_script(project_path: &Path) -> Result<()> {
+    let Some(config) = load_verify_config(project_path)? else {
+        println!("{} rmmproject.toml 中没有 [verify] 配置，跳过 {}", "[i]".cyan(), VERIFY_SCRIPT);
+        return Ok(());
+    };
+    let script_path = project_path.join(".rmmp/build").join(VERIFY_SCRIPT);
+    fs::write(&script_path, generate_verify_script(&config))?;
+    println!("{} 生成 {}（调试构建）", "[+]".green().bold(), VERIFY_SCRIPT);
+    Ok(())
+}
+
+/// 校验 update.json 并生成基础清单及各通道清单到 dist 目录；项目根目录的 update.json 是唯一来源，
+/// 先删除本次不会重新生成的旧清单
+fn copy_update_json_to_dist(project_path: &Path, rmake_config: &RmakeConfig) -> Result<()> {
     let update_json_path = project_path.join("update.json");
     let dist_dir = project_path.join(".rmmp/dist");
-    let dest_path = dist_dir.join("update.json");
-      if update_json_path.exists() {
-        copy_file_with_line_ending_normalization(&update_json_path, &dest_path)?;
+    for stale in remove_stale_manifests(project_path, rmake_config.update.as_ref())? {
+        println!("{} 删除过期的清单: {}", "[-]".yellow().bold(), stale.display());
+    }
+    
+    if update_json_path.exists() {
+        let written = write_channel_manifests(&update_json_path, &dist_dir, rmake_config.update.as_ref())
+            .map_err(|e| anyhow::anyhow!("update.json 校验失败: {}", e))?;
         println!("{} 复制 update.json 到分发目录", "[+]".green().bold());
+        for path in written.iter().skip(1) {
+            if path.extension().is_some_and(|ext| ext == "json") {
+                println!("    {} 生成通道清单: {}", "[+]".green(), path.display());
+            } else {
+                println!("    {} 生成当前版本的更新日志: {}", "[+]".green(), path.display());
+            }
+        }
+        if rmake_config.update.as_ref().is_some_and(|u| u.backend == UpdateBackend::Pages) {
+            println!("    {} update.json 托管于 GitHub Pages，运行 rmm pages 发布", "[i]".cyan());
+        }
     }
     
     Ok(())
 }
 
 /// 检查 shell 脚本
-fn check_shell_scripts(project_path: &Path, auto_fix: bool) -> Result<()> {
+fn check_shell_scripts(project_path: &Path, rmake_config: &RmakeConfig, auto_fix: bool) -> Result<()> {
     let build_dir = project_path.join(".rmmp/build");
     let rmmp_dir = project_path.join(".rmmp");
     
-    // 查找所有 .sh 文件
-    let sh_files = find_shell_scripts(&build_dir)?;
+    // 查找所有 shell 脚本（扩展名、shebang 与 [check.scripts] 规则），跳过二进制文件
+    let scripts_config = rmake_config.check.as_ref().and_then(|c| c.scripts.as_ref());
+    let scan = ScriptMatcher::new(scripts_config).scan(&build_dir)?;
+    for binary in &scan.skipped_binary {
+        println!("{} 跳过二进制文件: {}", "[!]".yellow().bold(), binary.display());
+    }
+    let sh_files = scan.scripts;
     
     if sh_files.is_empty() {
+        record_findings(project_path, SHELLCHECK_TOOL, Vec::new())?;
         return Ok(());
     }
     
@@ -324,11 +1002,13 @@ fn check_shell_scripts(project_path: &Path, auto_fix: bool) -> Result<()> {
     // 检查是否安装了 shellcheck
     let shellcheck_available = Command::new("shellcheck")
         .arg("--version")
-        .output()
+        .output_with_timeout(CommandKind::Shellcheck)
         .is_ok();
     
     if !shellcheck_available {
         println!("{} shellcheck 未安装，跳过脚本检查", "[!]".yellow().bold());
+        let skipped = Finding::new(SHELLCHECK_TOOL, FindingSeverity::Info, "shellcheck 未安装，已跳过脚本检查");
+        record_findings(project_path, SHELLCHECK_TOOL, vec![skipped])?;
         return Ok(());
     }    
     // 创建检查报告
@@ -340,6 +1020,7 @@ fn check_shell_scripts(project_path: &Path, auto_fix: bool) -> Result<()> {
         info_count: 0,
         style_count: 0,
         issues: Vec::new(),
+        wiki: BTreeMap::new(),
     };
     
     let mut has_errors = false;
@@ -354,20 +1035,20 @@ fn check_shell_scripts(project_path: &Path, auto_fix: bool) -> Result<()> {
         let json_output = Command::new("shellcheck")
             .arg("--format=json")
             .arg(&sh_file)
-            .output()?;
+            .output_with_timeout(CommandKind::Shellcheck)?;
         
         // 获取带 wiki 链接的详细输出
         let wiki_output = Command::new("shellcheck")
             .arg("-W")
             .arg("10") // 显示最多10个wiki链接
             .arg(&sh_file)
-            .output()?;
+            .output_with_timeout(CommandKind::Shellcheck)?;
         
         // 获取 diff 格式的修复建议
         let diff_output = Command::new("shellcheck")
             .arg("--format=diff")
             .arg(&sh_file)
-            .output()?;
+            .output_with_timeout(CommandKind::Shellcheck)?;
         
         // 解析 JSON 输出
         if !json_output.stdout.is_empty() {
@@ -413,6 +1094,16 @@ fn check_shell_scripts(project_path: &Path, auto_fix: bool) -> Result<()> {
     }    
     report.total_issues = report.error_count + report.warning_count + report.info_count + report.style_count;
     
+    // 嵌入规则说明，避免报告依赖无法访问的 shellcheck.net
+    let codes: BTreeSet<u32> = report.issues.iter().map(|issue| issue.code).collect();
+    if !codes.is_empty() {
+        let wiki = WikiCache::from_config(&ConfigResolver::new(Some(project_path)));
+        report.wiki = wiki.excerpts(&codes)
+            .into_iter()
+            .map(|(code, excerpt)| (format!("SC{}", code), excerpt))
+            .collect();
+    }
+    
     // 写入 JSON 格式报告（机器友好）
     let json_report_path = rmmp_dir.join("shellcheck.json");
     let json_content = serde_json::to_string_pretty(&report)?;
@@ -424,6 +1115,8 @@ fn check_shell_scripts(project_path: &Path, auto_fix: bool) -> Result<()> {
     let ai_content = generate_ai_friendly_report(&report);
     fs::write(&ai_report_path, ai_content)?;
     println!("{} AI 友好报告已保存到: {}", "[+]".green().bold(), ai_report_path.display());
+    let findings = report.issues.iter().map(|issue| issue.to_finding(&build_dir)).collect();
+    record_findings(project_path, SHELLCHECK_TOOL, findings)?;
       // 保存修复建议
     if !all_fixes.is_empty() {
         let fixes_path = rmmp_dir.join("shellcheck-fixes.diff");
@@ -485,40 +1178,14 @@ fn check_shell_scripts(project_path: &Path, auto_fix: bool) -> Result<()> {
     Ok(())
 }
 
-/// 查找所有 shell 脚本文件
-fn find_shell_scripts(dir: &Path) -> Result<Vec<PathBuf>> {
-    let mut sh_files = Vec::new();
-    
-    if !dir.exists() {
-        return Ok(sh_files);
-    }
-    
-    find_shell_scripts_recursive(dir, &mut sh_files)?;
-    Ok(sh_files)
-}
-
-/// 递归查找 shell 脚本
-fn find_shell_scripts_recursive(dir: &Path, sh_files: &mut Vec<PathBuf>) -> Result<()> {
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        
-        if path.is_dir() {
-            find_shell_scripts_recursive(&path, sh_files)?;
-        } else if let Some(extension) = path.extension() {
-            if extension == "sh" {
-                sh_files.push(path);
-            }
-        }
-    }
-    Ok(())
-}
-
 /// 执行 prebuild 脚本
 fn execute_prebuild(
     project_path: &Path,
     rmake_config: &RmakeConfig,
+    runner: &CommandRunner,
 ) -> Result<()> {
+    let env = load_project_env(project_path)?;
+    
     // 执行 Rmake.toml 中定义的 prebuild 命令
     if !rmake_config.build.prebuild.is_empty() {
         println!("{} 执行 prebuild 命令", "[exec]".blue().bold());
@@ -526,17 +1193,8 @@ fn execute_prebuild(
         for command in &rmake_config.build.prebuild {
             println!("    运行: {}", command.cyan());
             
-            let output = if cfg!(target_os = "windows") {
-                Command::new("cmd")
-                    .args(&["/C", command])
-                    .current_dir(project_path)
-                    .output()?
-            } else {
-                Command::new("sh")
-                    .args(&["-c", command])
-                    .current_dir(project_path)
-                    .output()?
-            };
+            let output = runner.shell(project_path, &expand_script_refs(command), &env)
+                .output_with_timeout(CommandKind::Script)?;
             
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
@@ -556,10 +1214,9 @@ fn execute_prebuild(
     if prebuild_script.exists() {
         println!("{} 执行传统 prebuild 脚本", "[+]".green().bold());
         
-        let output = Command::new("sh")
-            .arg(&prebuild_script)
-            .current_dir(project_path)
-            .output()?;
+        let script = prebuild_script.to_string_lossy();
+        let output = runner.command(project_path, "sh", &[&script], &env)
+            .output_with_timeout(CommandKind::Script)?;
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -575,33 +1232,158 @@ fn execute_prebuild(
     Ok(())
 }
 
-/// 打包模块
+/// 按 Rmake.toml [sign] key 查找签名私钥
+fn signing_key(rmake_config: &RmakeConfig) -> Result<SecretKey> {
+    resolve_secret_key(rmake_config.sign.as_ref().and_then(|sign| sign.key.as_deref()))
+        .context("--sign 需要签名私钥")
+}
+
+/// 打包模块；signer 为 Some 且 [sign] embed 开启时在 zip 中嵌入 rmm-checksums.json 的签名
 fn package_module(
     project_path: &Path,
-    _rmake_config: &RmakeConfig,
-) -> Result<()> {
+    rmake_config: &RmakeConfig,
+    debug: bool,
+    signer: Option<&SecretKey>,
+) -> Result<PathBuf> {
     let build_dir = project_path.join(".rmmp/build");
     let dist_dir = project_path.join(".rmmp/dist");
     
     // 读取项目信息
     let project_info = read_project_info(project_path)?;
-    let module_name = format!("{}-{}.zip", project_info.id, project_info.version_code);
+    let suffix = if debug { "-debug" } else { "" };
+    let module_name = format!("{}-{}{}.zip", project_info.id, project_info.version_code, suffix);
     let output_path = dist_dir.join(&module_name);
     
     println!("{} 打包模块: {}", "[zip]".magenta().bold(), module_name.cyan());
     
-    // 创建 ZIP 文件
-    create_zip_archive(&build_dir, &output_path)?;
+    // 嵌入的签名覆盖校验清单，未配置 checksums 时使用 sha256 生成清单
+    let embedded = signer.filter(|_| rmake_config.sign.clone().unwrap_or_default().embed);
+    let checksums = rmake_config.build.checksums.or(embedded.map(|_| HashAlgorithm::Sha256));
+    if let Some(algorithm) = checksums {
+        let (manifest, _) = write_checksum_manifest(&build_dir, algorithm)?;
+        println!("{} 校验清单: {}（{}，{} 个文件）", "[+]".green().bold(), CHECKSUMS_FILE, algorithm.as_str(), manifest.files.len());
+    }
+    if let Some(key) = embedded {
+        write_embedded_signature(&build_dir, key)?;
+        println!("{} 嵌入签名: {}（密钥 {}）", "[sign]".magenta().bold(), CHECKSUMS_SIGNATURE_FILE, key.name);
+    }
+    
+    // 先写入临时文件，成功后再改名，失败或取消时不会在 dist 中留下残缺的 zip
+    let partial = TempGuard::new(dist_dir.join(format!("{}.partial", module_name)));
+    let permissions = PermissionRules::new(&rmake_config.build.permissions)?;
+    create_zip_archive(&build_dir, partial.path(), &permissions)?;
+    fs::rename(partial.keep(), &output_path)?;
     
     println!("{} 模块打包完成: {}", "✅".green().bold(), output_path.display());
     
+    Ok(output_path)
+}
+
+/// 生成 Recovery 卡刷包，并校验其模块内容与管理器安装包一致
+fn package_recovery_variant(project_path: &Path, zip_path: &Path) -> Result<PathBuf> {
+    let project_info = read_project_info(project_path)?;
+    let stem = zip_path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = format!("{}{}.zip", stem, RECOVERY_SUFFIX);
+    let output_path = zip_path.with_file_name(&name);
+
+    let partial = TempGuard::new(output_path.with_extension("zip.partial"));
+    create_recovery_variant(zip_path, partial.path(), &project_info.id)?;
+    verify_identical_payloads(zip_path, partial.path())?;
+    fs::rename(partial.keep(), &output_path)?;
+    println!("{} 卡刷包: {}（模块内容与安装包一致）", "[zip]".magenta().bold(), name.cyan());
+    if project_path.join(".rmmp/build/customize.sh").exists() {
+        println!("{} Recovery 安装时不会执行 customize.sh，请确认模块不依赖它", "[!]".yellow());
+    }
+    Ok(output_path)
+}
+
+/// 按 [build.symbols] 剥离构建目录中的原生二进制，原始文件保存到 .rmmp/symbols
+fn strip_native_symbols(project_path: &Path, rmake_config: &RmakeConfig) -> Result<Vec<SymbolEntry>> {
+    let Some(config) = rmake_config.build.symbols.as_ref().filter(|c| c.split) else {
+        return Ok(Vec::new());
+    };
+    let Some(strip_tool) = find_strip_tool(config) else {
+        println!("{} 未找到 strip 工具（可设置 [build.symbols] strip 或 RMM_STRIP），跳过符号拆分", "[!]".yellow().bold());
+        return Ok(Vec::new());
+    };
+
+    let build_dir = project_path.join(".rmmp/build");
+    let entries = split_symbols(&build_dir, &project_path.join(".rmmp/symbols"), config, &strip_tool)?;
+    if entries.is_empty() {
+        println!("{} 构建目录中没有原生二进制，跳过符号拆分", "[i]".cyan());
+    }
+    for entry in &entries {
+        println!("    {} {} {} → {}", "[strip]".cyan(), entry.path,
+            format_size(entry.original_size), format_size(entry.stripped_size));
+    }
+    Ok(entries)
+}
+
+/// 将 .rmmp/symbols 打包为 <id>-<versionCode>-symbols.zip，附带 symbols.json 清单
+fn package_symbols(project_path: &Path, entries: Vec<SymbolEntry>) -> Result<PathBuf> {
+    let project_info = read_project_info(project_path)?;
+    let symbols_dir = project_path.join(".rmmp/symbols");
+    let manifest = SymbolsManifest {
+        module_id: project_info.id.clone(),
+        version_code: project_info.version_code.clone(),
+        entries,
+    };
+    fs::write(symbols_dir.join(SYMBOLS_MANIFEST), serde_json::to_string_pretty(&manifest)?)?;
+
+    let name = format!("{}-{}-symbols.zip", project_info.id, project_info.version_code);
+    let output_path = project_path.join(".rmmp/dist").join(&name);
+    let partial = TempGuard::new(output_path.with_extension("zip.partial"));
+    create_zip_archive(&symbols_dir, partial.path(), &PermissionRules::empty())?;
+    fs::rename(partial.keep(), &output_path)?;
+    println!("{} 符号包: {}（{} 个二进制）", "[zip]".magenta().bold(), name.cyan(), manifest.entries.len());
+    Ok(output_path)
+}
+
+/// 记录产物大小到 .rmmp/history.json，并按 [check.size] 检查增长
+fn record_artifact_size(project_path: &Path, rmake_config: &RmakeConfig, zip_path: &Path) -> Result<()> {
+    let prop = parse_module_prop(&read_prop_text(&project_path.join("module.prop"))?);
+    let record = SizeRecord {
+        version: prop.get("version").cloned().unwrap_or_default(),
+        version_code: prop.get("versionCode").cloned().unwrap_or_default(),
+        artifact: zip_path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        size: fs::metadata(zip_path)?.len(),
+        timestamp: chrono::Local::now().to_rfc3339(),
+    };
+    
+    let mut history = SizeHistory::load(project_path)?;
+    let previous = history.previous(&record.version_code)
+        .and_then(|p| growth_percent(p.size, record.size).map(|g| (p.version.clone(), g)));
+    if let Some((previous_version, growth)) = previous {
+        println!("    {} 产物大小 {}（相对 {} {:+.1}%）", "[size]".cyan(), format_size(record.size), previous_version, growth);
+    }
+    let violation = rmake_config.check.as_ref()
+        .and_then(|c| c.size.as_ref())
+        .and_then(|size_config| check_growth(&history, &record, size_config));
+    
+    history.record(record);
+    history.save(project_path)?;
+    
+    if let Some(message) = violation {
+        anyhow::bail!("{}", message);
+    }
+    Ok(())
+}
+
+/// 按已知问题数据库检查产物，存在 error 级别问题时中止构建
+fn check_known_issues(project_path: &Path, rmake_config: &RmakeConfig, zip_path: &Path) -> Result<()> {
+    let config = rmake_config.check.as_ref().and_then(|c| c.known_issues.as_ref());
+    let findings = KnownIssuesDatabase::load().check_zip(zip_path, config)?;
+    let errors = report_known_issues(&findings);
+    record_findings(project_path, KNOWN_ISSUES_TOOL, known_issue_findings(&findings))?;
+    if errors > 0 {
+        anyhow::bail!("产物存在 {} 个已知的高危问题，可在 [check.known_issues] 中忽略或调整级别", errors);
+    }
     Ok(())
 }
 
 /// 读取项目信息
 fn read_project_info(project_path: &Path) -> Result<ProjectInfo> {
-    let module_prop_path = project_path.join("module.prop");
-    let content = fs::read_to_string(&module_prop_path)?;
+    let content = read_prop_text(&project_path.join("module.prop"))?;
     
     let mut id = String::new();
     let mut version_code = String::new();
@@ -625,47 +1407,6 @@ struct ProjectInfo {
     version_code: String,
 }
 
-/// 创建 ZIP 压缩包
-fn create_zip_archive(source_dir: &Path, output_path: &Path) -> Result<()> {
-    let file = fs::File::create(output_path)?;
-    let mut zip = zip::ZipWriter::new(file);
-    
-    add_directory_to_zip(&mut zip, source_dir, source_dir)?;
-    
-    zip.finish()?;
-    Ok(())
-}
-
-/// 添加目录到 ZIP
-fn add_directory_to_zip<W: Write + std::io::Seek>(
-    zip: &mut zip::ZipWriter<W>,
-    dir: &Path,
-    base_dir: &Path,
-) -> Result<()> {
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        let relative_path = path.strip_prefix(base_dir)?;
-          if path.is_dir() {
-            // 添加目录 - 确保使用正斜杠分隔符
-            let dir_name = format!("{}/", relative_path.display().to_string().replace('\\', "/"));
-            zip.add_directory(dir_name, zip::write::SimpleFileOptions::default())?;
-            
-            // 递归添加子目录
-            add_directory_to_zip(zip, &path, base_dir)?;
-        } else {
-            // 添加文件 - 确保使用正斜杠分隔符
-            let file_name = relative_path.display().to_string().replace('\\', "/");
-            zip.start_file(file_name, zip::write::SimpleFileOptions::default())?;
-            
-            let file_content = fs::read(&path)?;
-            zip.write_all(&file_content)?;
-        }
-    }
-    
-    Ok(())
-}
-
 /// 创建 tar.gz 压缩包
 fn create_tar_gz_archive(source_dir: &Path, output_path: &Path) -> Result<()> {
     use flate2::Compression;
@@ -713,12 +1454,10 @@ fn add_directory_to_tar<W: Write>(
             }
         };
         
-        // 确保路径使用正确的分隔符，并且不为空
-        let normalized_path = if relative_path.as_os_str().is_empty() {
-            continue; // 跳过根目录自身
-        } else {
-            relative_path.to_string_lossy().replace('\\', "/")
-        };
+        // 跳过根目录自身；路径直接以 Path 传给 tar，保留非 UTF-8 文件名的原始字节
+        if relative_path.as_os_str().is_empty() {
+            continue;
+        }
         
         if path.is_dir() {
             // 添加目录条目（以 / 结尾）
@@ -728,15 +1467,9 @@ fn add_directory_to_tar<W: Write>(
             header.set_size(0);
             header.set_cksum();
             
-            let dir_path = if normalized_path.is_empty() {
-                continue; // 跳过空路径
-            } else {
-                format!("{}/", normalized_path)
-            };
-            
             // 🔧 修复：添加错误处理
-            if let Err(e) = tar.append_data(&mut header, &dir_path, std::io::empty()) {
-                println!("⚠️ 警告: 添加目录到tar失败 {}: {}", dir_path, e);
+            if let Err(e) = tar.append_data(&mut header, relative_path, std::io::empty()) {
+                println!("⚠️ 警告: 添加目录到tar失败 {}: {}", relative_path.display(), e);
                 continue;
             }
             
@@ -744,10 +1477,6 @@ fn add_directory_to_tar<W: Write>(
             add_directory_to_tar(tar, &path, base_dir)?;
         } else {
             // 添加文件
-            if normalized_path.is_empty() {
-                continue; // 跳过空路径
-            }
-            
             // 🔧 修复：更安全的文件打开方式
             let mut file = match fs::File::open(&path) {
                 Ok(f) => f,
@@ -771,8 +1500,8 @@ fn add_directory_to_tar<W: Write>(
             header.set_cksum();
             
             // 🔧 修复：添加错误处理
-            if let Err(e) = tar.append_data(&mut header, &normalized_path, &mut file) {
-                println!("⚠️ 警告: 添加文件到tar失败 {}: {}", normalized_path, e);
+            if let Err(e) = tar.append_data(&mut header, relative_path, &mut file) {
+                println!("⚠️ 警告: 添加文件到tar失败 {}: {}", relative_path.display(), e);
                 continue;
             }
         }
@@ -785,7 +1514,10 @@ fn add_directory_to_tar<W: Write>(
 fn execute_postbuild(
     project_path: &Path,
     rmake_config: &RmakeConfig,
+    runner: &CommandRunner,
 ) -> Result<()> {
+    let env = load_project_env(project_path)?;
+    
     // 执行 Rmake.toml 中定义的 postbuild 命令
     if !rmake_config.build.postbuild.is_empty() {
         println!("{} 执行 postbuild 命令", "[exec]".blue().bold());
@@ -793,17 +1525,8 @@ fn execute_postbuild(
         for command in &rmake_config.build.postbuild {
             println!("    运行: {}", command.cyan());
             
-            let output = if cfg!(target_os = "windows") {
-                Command::new("cmd")
-                    .args(&["/C", command])
-                    .current_dir(project_path)
-                    .output()?
-            } else {
-                Command::new("sh")
-                    .args(&["-c", command])
-                    .current_dir(project_path)
-                    .output()?
-            };
+            let output = runner.shell(project_path, &expand_script_refs(command), &env)
+                .output_with_timeout(CommandKind::Script)?;
             
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);                println!("{} postbuild 命令执行失败: {}\n错误: {}", 
@@ -823,10 +1546,9 @@ fn execute_postbuild(
     if postbuild_script.exists() {
         println!("{} 执行传统 postbuild 脚本", "[+]".green().bold());
         
-        let output = Command::new("sh")
-            .arg(&postbuild_script)
-            .current_dir(project_path)
-            .output()?;
+        let script = postbuild_script.to_string_lossy();
+        let output = runner.command(project_path, "sh", &[&script], &env)
+            .output_with_timeout(CommandKind::Script)?;
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -846,6 +1568,8 @@ fn execute_postbuild(
 fn execute_source_packaging(
     project_path: &Path,
     rmake_config: &RmakeConfig,
+    files: &ProjectFiles,
+    runner: &CommandRunner,
 ) -> Result<()> {
     println!("{} 开始源代码打包", "[tar]".cyan().bold());
     
@@ -857,171 +1581,79 @@ fn execute_source_packaging(
     fs::create_dir_all(&source_build_dir)?;
     
     // 复制源代码文件（依据 src 配置）
-    copy_source_files(project_path, &source_build_dir, rmake_config)?;
+    copy_source_files(&source_build_dir, rmake_config, files)?;
+    let vendored = vendor_bundled_scripts(project_path, &source_build_dir)?;
+    if !vendored.is_empty() {
+        println!("{} 随附 {} 个脚本依赖到 .rmmp/{}", "[+]".green().bold(), vendored.len(), VENDORED_SCRIPTS_DIR);
+    }
     
     // 执行源代码 prebuild
-    execute_source_prebuild(project_path)?;
+    execute_source_prebuild(project_path, runner)?;
+    
+    // 检查是否泄漏本机绝对路径
+    warn_path_leaks(project_path, &source_build_dir)?;
     
     // 打包源代码
     package_source_code(project_path, &source_build_dir)?;
     
     // 执行源代码 postbuild
-    execute_source_postbuild(project_path)?;
+    execute_source_postbuild(project_path, runner)?;
     
     println!("{} 源代码打包完成", "✅".green().bold());
     
     Ok(())
 }
 
-/// 复制源代码文件
-fn copy_source_files(project_path: &Path, source_build_dir: &Path, rmake_config: &RmakeConfig) -> Result<()> {
-    // 根据 Rmake.toml 中的 build.src 配置复制源代码文件
+/// 复制源代码文件（依据默认排除规则、build.src 的 exclude 规则与排除预设，.rmmp 中只包含 Rmake.toml）
+fn copy_source_files(source_build_dir: &Path, rmake_config: &RmakeConfig, files: &ProjectFiles) -> Result<()> {
+    let defaults = DefaultExclusions::new(&rmake_config.build.allow)?;
+    let (entries, counts) = defaults.filter(files.source_entries());
+    print_exclusions("默认排除规则", &counts);
+    let exclusions = ExclusionSet::new(&source_excludes(&rmake_config.build)?)?;
+    let (entries, counts) = exclusions.filter(entries);
+    print_exclusions("源代码排除规则", &counts);
     if let Some(src_config) = &rmake_config.build.src {
-        // 首先获取所有文件
-        let mut source_entries = Vec::new();
-        for entry in fs::read_dir(project_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            let file_name = path.file_name().unwrap().to_string_lossy();
-            
-            // 不排除 .rmmp 目录，因为源代码需要包含配置
-            source_entries.push(path);
-        }
-          // 应用 src exclude 规则
-        if !src_config.exclude.is_empty() {
-            println!("    {} 源代码排除规则:", "[!]".bright_yellow());
-            for pattern in &src_config.exclude {
-                println!("      - {}", pattern);
-            }
-        }
-        
-        source_entries.retain(|path| {
-            let file_name = path.file_name().unwrap().to_string_lossy();
-            let path_str = path.to_string_lossy();
-            
-            for pattern in &src_config.exclude {
-                if pattern.contains('*') {
-                    if pattern.ends_with("*") {
-                        let prefix = &pattern[..pattern.len() - 1];
-                        if file_name.starts_with(prefix) || path_str.contains(prefix) {
-                            println!("      {} 排除源文件: {} (匹配 {})", "[x]".red(), file_name, pattern);
-                            return false;
-                        }
-                    }
-                    if pattern.starts_with("*") {
-                        let suffix = &pattern[1..];
-                        if file_name.ends_with(suffix) || path_str.contains(suffix) {
-                            println!("      {} 排除源文件: {} (匹配 {})", "[x]".red(), file_name, pattern);
-                            return false;
-                        }
-                    }
-                } else {
-                    if file_name == pattern.as_str() || path_str.contains(pattern) {
-                        println!("      {} 排除源文件: {} (匹配 {})", "[x]".red(), file_name, pattern);
-                        return false;
-                    }
-                }
-            }
-            true
-        });
-          // 复制文件
-        for path in source_entries {
-            // 🔧 修复：添加路径有效性检查
-            if !path.exists() {
-                println!("⚠️ 警告: 源文件不存在，跳过: {}", path.display());
-                continue;
-            }
-            
-            let file_name = match path.file_name() {
-                Some(name) => name,
-                None => {
-                    println!("⚠️ 警告: 无法获取文件名，跳过: {}", path.display());
-                    continue;
-                }
-            };
-            let dest_path = source_build_dir.join(file_name);
-            
-            if path.is_dir() {
-                if file_name == ".rmmp" {
-                    // 特殊处理 .rmmp 目录，只复制 Rmake.toml
-                    if let Err(e) = fs::create_dir_all(&dest_path) {
-                        println!("⚠️ 警告: 创建目录失败 {}: {}", dest_path.display(), e);
-                        continue;
-                    }
-                    let rmake_source = path.join("Rmake.toml");
-                    let rmake_dest = dest_path.join("Rmake.toml");
-                    if rmake_source.exists() {
-                        if let Err(e) = fs::copy(&rmake_source, &rmake_dest) {
-                            println!("⚠️ 警告: 复制配置文件失败: {}", e);
-                        } else {
-                            println!("    ✅ 包含配置文件: .rmmp/Rmake.toml");
-                        }
-                    }                } else {
-                    if let Err(e) = copy_directory(&path, &dest_path) {
-                        println!("⚠️ 警告: 复制目录失败 {}: {}", path.display(), e);
-                    }
-                }
-            } else {
-                if let Err(e) = copy_file_with_line_ending_normalization(&path, &dest_path) {
-                    println!("⚠️ 警告: 复制文件失败 {}: {}", path.display(), e);
-                }
-            }
-        }// 处理 src include（额外包含文件）
-        let src_include_patterns: Vec<&String> = src_config.include
-            .iter()
-            .filter(|pattern| {
-                let trimmed = pattern.trim();
-                !trimmed.starts_with('#') && trimmed != "rmm"
-            })
-            .collect();
-            
-        if !src_include_patterns.is_empty() {
-            println!("    {} 源代码额外包含:", "[+]".green());
-            for include_pattern in &src_include_patterns {
-                println!("      + {}", include_pattern);
-            }
-        }
-    } else {
-        // 如果没有 src 配置，复制所有文件（包括 .rmmp/Rmake.toml）
-        for entry in fs::read_dir(project_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            let file_name = path.file_name().unwrap().to_string_lossy();
-            
-            let dest_path = source_build_dir.join(file_name.as_ref());
-            
-            if path.is_dir() {
-                if file_name == ".rmmp" {
-                    // 特殊处理 .rmmp 目录，只复制 Rmake.toml
-                    fs::create_dir_all(&dest_path)?;
-                    let rmake_source = path.join("Rmake.toml");
-                    let rmake_dest = dest_path.join("Rmake.toml");                    if rmake_source.exists() {
-                        copy_file_with_line_ending_normalization(&rmake_source, &rmake_dest)?;
-                        println!("    ✅ 包含配置文件: .rmmp/Rmake.toml");
-                    }
-                } else {
-                    copy_directory(&path, &dest_path)?;
-                }            } else {
-                copy_file_with_line_ending_normalization(&path, &dest_path)?;
-            }
-        }
+        print_include_patterns("源代码额外包含", &src_config.include);
+    }
+    
+    files.copy_to(&entries, source_build_dir, &LineEndingRules::new(&rmake_config.build.line_endings)?)?;
+    if entries.iter().any(|e| e.relative == ".rmmp/Rmake.toml") {
+        println!("    ✅ 包含配置文件: .rmmp/Rmake.toml");
     }
     
     println!("{} 复制源代码文件", "[+]".green().bold());
     Ok(())
 }
 
+/// 源代码包中出现本机绝对路径（项目目录、用户主目录）时给出警告
+fn warn_path_leaks(project_path: &Path, source_build_dir: &Path) -> Result<()> {
+    let leaks = scan_path_leaks(source_build_dir, &local_roots(project_path))?;
+    if leaks.is_empty() {
+        return Ok(());
+    }
+    println!("{} 源代码包中有 {} 处本机绝对路径：", "[privacy]".yellow().bold(), leaks.len());
+    for leak in leaks.iter().take(10) {
+        println!("    {}:{} {}", leak.file, leak.line, leak.path.bright_black());
+    }
+    if leaks.len() > 10 {
+        println!("    …");
+    }
+    println!("    改为相对路径，或在 [build.src] exclude 中排除这些文件");
+    Ok(())
+}
+
 /// 执行源代码 prebuild
-fn execute_source_prebuild(project_path: &Path) -> Result<()> {
+fn execute_source_prebuild(project_path: &Path, runner: &CommandRunner) -> Result<()> {
+    let env = load_project_env(project_path)?;
+    
     let prebuild_script = project_path.join("scripts/source-prebuild.sh");
     
     if prebuild_script.exists() {
         println!("{} 执行源代码 prebuild 脚本", "[+]".green().bold());
         
-        let output = Command::new("sh")
-            .arg(&prebuild_script)
-            .current_dir(project_path)
-            .output()?;
+        let script = prebuild_script.to_string_lossy();
+        let output = runner.command(project_path, "sh", &[&script], &env)
+            .output_with_timeout(CommandKind::Script)?;
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -1073,16 +1705,17 @@ fn package_source_code(project_path: &Path, source_build_dir: &Path) -> Result<(
 }
 
 /// 执行源代码 postbuild
-fn execute_source_postbuild(project_path: &Path) -> Result<()> {
+fn execute_source_postbuild(project_path: &Path, runner: &CommandRunner) -> Result<()> {
+    let env = load_project_env(project_path)?;
+    
     let postbuild_script = project_path.join("scripts/source-postbuild.sh");
     
     if postbuild_script.exists() {
         println!("{} 执行源代码 postbuild 脚本", "[+]".green().bold());
         
-        let output = Command::new("sh")
-            .arg(&postbuild_script)
-            .current_dir(project_path)
-            .output()?;
+        let script = postbuild_script.to_string_lossy();
+        let output = runner.command(project_path, "sh", &[&script], &env)
+            .output_with_timeout(CommandKind::Script)?;
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -1141,7 +1774,7 @@ fn generate_ai_friendly_report(report: &ShellcheckReport) -> String {
         if !errors.is_empty() {
             content.push_str("## 🚨 Errors (Build Blocking)\n\n");
             for issue in errors {
-                content.push_str(&format_issue_for_ai(issue));
+                content.push_str(&format_issue_for_ai(issue, report.wiki.get(&format!("SC{}", issue.code))));
             }
         }
         
@@ -1149,7 +1782,7 @@ fn generate_ai_friendly_report(report: &ShellcheckReport) -> String {
         if !warnings.is_empty() {
             content.push_str("## ⚠️ Warnings\n\n");
             for issue in warnings {
-                content.push_str(&format_issue_for_ai(issue));
+                content.push_str(&format_issue_for_ai(issue, report.wiki.get(&format!("SC{}", issue.code))));
             }
         }
         
@@ -1157,7 +1790,7 @@ fn generate_ai_friendly_report(report: &ShellcheckReport) -> String {
         if !infos.is_empty() {
             content.push_str("## ℹ️ Info\n\n");
             for issue in infos {
-                content.push_str(&format_issue_for_ai(issue));
+                content.push_str(&format_issue_for_ai(issue, report.wiki.get(&format!("SC{}", issue.code))));
             }
         }
         
@@ -1165,7 +1798,7 @@ fn generate_ai_friendly_report(report: &ShellcheckReport) -> String {
         if !styles.is_empty() {
             content.push_str("## 🎨 Style\n\n");
             for issue in styles {
-                content.push_str(&format_issue_for_ai(issue));
+                content.push_str(&format_issue_for_ai(issue, report.wiki.get(&format!("SC{}", issue.code))));
             }
         }
           // 建议
@@ -1215,7 +1848,7 @@ fn generate_ai_friendly_report(report: &ShellcheckReport) -> String {
 }
 
 /// 格式化单个问题为 AI 友好格式
-fn format_issue_for_ai(issue: &ShellcheckIssue) -> String {
+fn format_issue_for_ai(issue: &ShellcheckIssue, wiki: Option<&WikiExcerpt>) -> String {
     let mut content = String::new();
     
     content.push_str(&format!("### SC{} in `{}`\n\n", issue.code, issue.file));
@@ -1237,6 +1870,11 @@ fn format_issue_for_ai(issue: &ShellcheckIssue) -> String {
         content.push_str("\n");
     }
     
+    // 内嵌规则说明（来自本地缓存或内置说明）
+    if let Some(wiki) = wiki {
+        content.push_str(&format!("**Explanation**:\n> {}\n\n", wiki.excerpt));
+    }
+    
     // 添加 shellcheck 规则链接
     content.push_str(&format!("**Reference**: [ShellCheck SC{}](https://www.shellcheck.net/wiki/SC{})\n\n", 
                              issue.code, issue.code));
@@ -1255,6 +1893,7 @@ fn recheck_fixed_scripts(sh_files: &[PathBuf]) -> Result<ShellcheckReport> {
         info_count: 0,
         style_count: 0,
         issues: Vec::new(),
+        wiki: BTreeMap::new(),
     };
     
     for sh_file in sh_files {
@@ -1264,7 +1903,7 @@ fn recheck_fixed_scripts(sh_files: &[PathBuf]) -> Result<ShellcheckReport> {
         let json_output = Command::new("shellcheck")
             .arg("--format=json")
             .arg(&sh_file)
-            .output()?;
+            .output_with_timeout(CommandKind::Shellcheck)?;
         
         // 解析 JSON 输出
         if !json_output.stdout.is_empty() {
@@ -1300,7 +1939,7 @@ fn apply_fixes_directly(sh_files: &[PathBuf]) -> Result<usize> {
         let fix_output = Command::new("shellcheck")
             .arg("--format=diff")
             .arg(&sh_file)
-            .output()?;
+            .output_with_timeout(CommandKind::Shellcheck)?;
         
         if fix_output.stdout.is_empty() {
             continue; // 没有修复建议
@@ -1317,7 +1956,7 @@ fn apply_fixes_directly(sh_files: &[PathBuf]) -> Result<usize> {
                     let source_fix_output = Command::new("shellcheck")
                         .arg("--format=diff")
                         .arg(&source_file)
-                        .output()?;
+                        .output_with_timeout(CommandKind::Shellcheck)?;
                     
                     if !source_fix_output.stdout.is_empty() {
                         let source_diff = String::from_utf8_lossy(&source_fix_output.stdout);
@@ -1429,7 +2068,7 @@ fn try_git_apply(project_path: &Path, fixes_path: &Path) -> Result<()> {
         .arg("--verbose")
         .arg(relative_fixes_path)
         .current_dir(project_path)
-        .output()?;
+        .output_with_timeout(CommandKind::Git)?;
     
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -1444,124 +2083,70 @@ fn try_git_apply(project_path: &Path, fixes_path: &Path) -> Result<()> {
 }
 
 /// 规范化文件的行尾序列为 LF
-fn normalize_line_endings(content: &str) -> String {
-    content.replace("\r\n", "\n").replace("\r", "\n")
+fn normalize_line_endings(content: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(content.len());
+    let mut bytes = content.iter().peekable();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'\r' {
+            bytes.next_if_eq(&&b'\n');
+            normalized.push(b'\n');
+        } else {
+            normalized.push(byte);
+        }
+    }
+    normalized
 }
 
-/// 检查文件是否需要规范化行尾序列
-fn needs_line_ending_normalization(file_path: &Path) -> bool {
-    let extension = file_path.extension().and_then(|s| s.to_str()).unwrap_or("");
-    matches!(extension, "sh" | "prop" | "txt" | "md" | "conf" | "json" | "toml" | "xml" | "yml" | "yaml")
-        || file_path.file_name().and_then(|s| s.to_str()).map_or(false, |name| {
-            matches!(name, "module.prop" | "service.sh" | "post-fs-data.sh" | "uninstall.sh" | "customize.sh")
-        })
+/// 将行尾序列转换为 CRLF（已有的 CRLF 与单独的 CR 不会重复转换）
+fn crlf_line_endings(content: &[u8]) -> Vec<u8> {
+    let normalized = normalize_line_endings(content);
+    let mut converted = Vec::with_capacity(normalized.len() + normalized.len() / 16);
+    for &byte in &normalized {
+        if byte == b'\n' {
+            converted.push(b'\r');
+        }
+        converted.push(byte);
+    }
+    converted
 }
 
-/// 复制文件并规范化行尾序列
-fn copy_file_with_line_ending_normalization(src: &Path, dst: &Path) -> Result<()> {
-    if needs_line_ending_normalization(src) {
-        // 需要规范化行尾序列的文件
-        let content = std::fs::read_to_string(src)?;
-        let has_crlf = content.contains("\r\n") || content.contains("\r");
-        let normalized_content = normalize_line_endings(&content);
-        
-        if has_crlf {
-            // 修复源文件的行尾序列
-            std::fs::write(src, &normalized_content)?;
-            println!("    {} 修复源文件行尾序列: {}", "[~]".bright_yellow(), src.display());
+/// 按行尾策略复制文件：lf / crlf 时规范化编码与行尾序列，keep 与二进制文件（None）原样复制
+fn copy_file_with_line_ending_normalization(src: &Path, dst: &Path, policy: Option<LineEnding>) -> Result<()> {
+    if let Some(ending @ (LineEnding::Lf | LineEnding::Crlf)) = policy {
+        // 需要规范化的文本文件
+        // 按字节处理，其他工具生成的非 UTF-8 文本不会导致失败
+        let mut content = std::fs::read(src)?;
+        // 去掉 BOM、UTF-16 转为 UTF-8（修复行尾时一并写回源文件）；无法无损转换时原样复制，由 rmm check 报告
+        match to_utf8(&content) {
+            Ok((TextEncoding::Utf8, _)) => {}
+            Ok((encoding, converted)) => {
+                println!("    {} 转换编码 {} → UTF-8: {}", "[~]".bright_yellow(), encoding.as_str(), src.display());
+                content = converted.into_owned();
+            }
+            Err(e) => {
+                println!("    {} 无法转换编码，已原样复制: {}（{}）", "[!]".yellow(), src.display(), e);
+                std::fs::copy(src, dst)?;
+                return Ok(());
+            }
         }
+        let normalized_content = if ending == LineEnding::Crlf {
+            crlf_line_endings(&content)
+        } else {
+            let normalized_content = normalize_line_endings(&content);
+            if content.contains(&b'\r') {
+                // 修复源文件的行尾序列
+                std::fs::write(src, &normalized_content)?;
+                println!("    {} 修复源文件行尾序列: {}", "[~]".bright_yellow(), src.display());
+            }
+            normalized_content
+        };
         
-        // 写入构建目录
+        // 写入构建目录，保留源文件权限（如可执行位）
         std::fs::write(dst, normalized_content)?;
+        std::fs::set_permissions(dst, std::fs::metadata(src)?.permissions())?;
     } else {
-        // 二进制文件或不需要规范化的文件
+        // 二进制文件或 keep 策略的文件
         std::fs::copy(src, dst)?;
     }
     Ok(())
 }
-
-/// 应用排除规则并收集路径
-fn apply_exclusions_and_collect_paths(
-    project_path: &Path,
-    entries: Vec<PathBuf>,
-    is_source_packaging: bool,
-    rmake_config: &RmakeConfig,
-) -> Result<Vec<PathBuf>> {
-    let mut paths_to_copy = Vec::new();
-    let mut excluded_messages = Vec::new();
-    
-    // 编译排除模式
-    let compiled_exclusions: Vec<regex::Regex> = rmake_config.build.exclude
-        .iter()
-        .filter_map(|pattern| {
-            let trimmed = pattern.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                None // 忽略空行和注释
-            } else {
-                // 编译正则表达式
-                match regex::Regex::new(&format!("^{}$", regex::escape(trimmed).replace(r"\*", ".*"))) {
-                    Ok(re) => Some(re),
-                    Err(e) => {
-                        println!("⚠️ 警告: 排除模式编译失败 {}: {}", trimmed, e);
-                        None
-                    }
-                }
-            }
-        })
-        .collect();
-      for entry in entries {
-        let relative_path = entry.strip_prefix(project_path)?;
-        
-        // 检查是否被排除
-        let mut is_excluded = false;
-        let mut matched_pattern = None;
-        
-        for pattern_regex in &compiled_exclusions {
-            if pattern_regex.is_match(&relative_path.display().to_string()) {
-                is_excluded = true;
-                matched_pattern = Some(pattern_regex.as_str());
-                break;
-            }
-        }
-
-        if is_excluded {
-            // 确保正确区分文件和目录
-            let item_type_str = if entry.is_dir() {
-                "目录" // Directory
-            } else {
-                "文件" // File
-            };
-
-            let exclusion_reason = matched_pattern
-                .map_or_else(String::new, |p| format!(" (匹配 {})", p.cyan()));
-
-            excluded_messages.push(format!(
-                "      [x] {} {}: {}{}",
-                item_type_str, // 使用更准确的类型字符串
-                if is_source_packaging { "排除源" } else { "排除" }.yellow(),
-                relative_path.display().to_string().yellow(),
-                exclusion_reason
-            ));
-            continue; // Skip this entry from being added to paths_to_copy
-        }
-
-        // If the entry is a file, add it to the list of paths to copy
-        if entry.is_file() {
-            paths_to_copy.push(entry);
-        } else if entry.is_dir() {
-            // If it's a directory, we may want to copy the whole directory
-            // 这里可以根据需要决定是否复制整个目录
-            paths_to_copy.push(entry);
-        }
-    }
-    
-    // 输出排除的文件和目录
-    if !excluded_messages.is_empty() {
-        println!("{} 排除的文件和目录:", "[!]".bright_yellow());
-        for message in excluded_messages {
-            println!("{}", message);
-        }
-    }
-    
-    Ok(paths_to_copy)
-}
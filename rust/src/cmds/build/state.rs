@@ -0,0 +1,135 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::cache::{BuildFingerprint, BuildPhase};
+use crate::core::process::check_cancelled;
+
+/// 构建状态文件名（位于 .rmmp 下）
+const STATE_FILE: &str = "build-state.json";
+
+/// 构建进度记录，用于 `rmm build --resume`
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct BuildState {
+    /// 构建输入指纹摘要，输入变化后状态失效
+    pub inputs_digest: String,
+    /// 已完成的阶段
+    pub completed: Vec<String>,
+    /// 失败的阶段
+    pub failed: Option<String>,
+}
+
+impl BuildState {
+    pub fn new(fingerprint: &BuildFingerprint) -> Self {
+        Self {
+            inputs_digest: fingerprint.digest(),
+            ..Default::default()
+        }
+    }
+
+    /// 读取与当前输入匹配的构建状态；输入已变化时自动清除
+    pub fn load_matching(project_path: &Path, fingerprint: &BuildFingerprint) -> Option<Self> {
+        let path = project_path.join(".rmmp").join(STATE_FILE);
+        let content = fs::read_to_string(&path).ok()?;
+        let state: BuildState = serde_json::from_str(&content).ok()?;
+        if state.inputs_digest != fingerprint.digest() {
+            println!("{} 配置或源文件已变化，清除构建状态", "[resume]".cyan().bold());
+            Self::clear(project_path);
+            return None;
+        }
+        Some(state)
+    }
+
+    pub fn save(&self, project_path: &Path) -> Result<()> {
+        fs::write(
+            project_path.join(".rmmp").join(STATE_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// 删除构建状态文件
+    pub fn clear(project_path: &Path) {
+        let _ = fs::remove_file(project_path.join(".rmmp").join(STATE_FILE));
+    }
+
+    pub fn is_completed(&self, phase: BuildPhase) -> bool {
+        self.completed.iter().any(|p| p == phase.as_str())
+    }
+
+    /// 是否已有阶段完成（恢复构建时需保留构建目录）
+    pub fn has_progress(&self) -> bool {
+        !self.completed.is_empty()
+    }
+
+    /// 执行一个阶段并记录结果；已完成的阶段直接跳过
+    pub fn run_phase<F>(&mut self, project_path: &Path, phase: BuildPhase, f: F) -> Result<()>
+    where
+        F: FnOnce() -> Result<()>,
+    {
+        if self.is_completed(phase) {
+            println!("{} 跳过已完成阶段: {}", "[resume]".cyan().bold(), phase.as_str());
+            return Ok(());
+        }
+        check_cancelled()?;
+
+        match f() {
+            Ok(()) => {
+                self.completed.push(phase.as_str().to_string());
+                self.failed = None;
+                self.save(project_path)
+            }
+            Err(e) => {
+                self.failed = Some(phase.as_str().to_string());
+                if let Err(save_err) = self.save(project_path) {
+                    println!("{} 无法写入构建状态: {}", "[!]".yellow(), save_err);
+                } else {
+                    println!(
+                        "{} 阶段 {} 失败，修复后可使用 rmm build --resume 从此阶段继续",
+                        "[!]".yellow(),
+                        phase.as_str()
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_build_state_resume_and_invalidate() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join(".rmmp")).unwrap();
+        fs::write(project_path.join(".rmmp/Rmake.toml"), "[build]\n").unwrap();
+        fs::write(project_path.join("module.prop"), "id=test\n").unwrap();
+
+        let fingerprint = BuildFingerprint::compute(project_path).unwrap();
+        let mut state = BuildState::new(&fingerprint);
+        state.run_phase(project_path, BuildPhase::Copy, || Ok(())).unwrap();
+        assert!(state
+            .run_phase(project_path, BuildPhase::Package, || anyhow::bail!("boom"))
+            .is_err());
+
+        let mut resumed = BuildState::load_matching(project_path, &fingerprint).unwrap();
+        assert!(resumed.is_completed(BuildPhase::Copy));
+        assert_eq!(resumed.failed.as_deref(), Some("package"));
+
+        let mut ran = false;
+        resumed.run_phase(project_path, BuildPhase::Copy, || { ran = true; Ok(()) }).unwrap();
+        assert!(!ran);
+
+        // 输入变化后状态失效并被清除
+        fs::write(project_path.join("module.prop"), "id=test\nversion=2\n").unwrap();
+        let changed = BuildFingerprint::compute(project_path).unwrap();
+        assert!(BuildState::load_matching(project_path, &changed).is_none());
+        assert!(!project_path.join(".rmmp").join(STATE_FILE).exists());
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::core::exclude_presets::expand_presets;
+use crate::core::rmm_core::{BuildConfig, RmakeConfig};
+
+use super::files::{DefaultExclusions, ExclusionSet};
+use super::load_rmake_config;
+
+/// 一条排除规则及其来源
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: String,
+    origin: String,
+}
+
+/// 某个产物对路径的打包决定
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PackagingDecision {
+    /// module（模块 zip）或 source（源代码包）
+    pub target: String,
+    pub included: bool,
+    /// 做出决定的规则及来源
+    pub reasons: Vec<String>,
+}
+
+/// 路径的完整打包解释
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WhyReport {
+    pub path: String,
+    pub exists: bool,
+    pub decisions: Vec<PackagingDecision>,
+    /// 不影响结果的附加说明（include 规则、.gitignore）
+    pub notes: Vec<String>,
+}
+
+/// 将用户给出的路径转换为相对项目根目录、使用 / 分隔的路径
+pub fn normalize_relative(project_path: &Path, path: &str) -> String {
+    let given = Path::new(path);
+    let canonical = given.is_absolute().then(|| given.canonicalize().ok()).flatten();
+    let given = canonical.as_deref().unwrap_or(given);
+    let relative = given.strip_prefix(project_path).unwrap_or(given);
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    let relative = relative.trim_start_matches("./").trim_end_matches('/');
+    relative.to_string()
+}
+
+fn labelled(patterns: &[String], origin: &str) -> Vec<Rule> {
+    patterns.iter()
+        .map(|p| Rule { pattern: p.trim().to_string(), origin: origin.to_string() })
+        .collect()
+}
+
+fn preset_rules(build: &BuildConfig) -> Result<Vec<Rule>> {
+    let mut rules = Vec::new();
+    for name in &build.exclude_presets {
+        let origin = format!(".rmmp/Rmake.toml [build] exclude_presets \"{}\"", name.trim());
+        rules.extend(labelled(&expand_presets(std::slice::from_ref(name))?, &origin));
+    }
+    Ok(rules)
+}
+
+/// 按规则判断相对路径是否被排除：最后一条匹配的规则生效
+fn decide(target: &str, relative: &str, is_dir: bool, rules: &[Rule]) -> Result<PackagingDecision> {
+    let patterns: Vec<String> = rules.iter().map(|r| r.pattern.clone()).collect();
+    let set = ExclusionSet::new(&patterns)?;
+    let matched = set.matching(relative, is_dir);
+    let reasons: Vec<String> = matched.iter()
+        .map(|pattern| {
+            let origin = rules.iter()
+                .find(|r| r.pattern.trim() == *pattern)
+                .map(|r| r.origin.as_str())
+                .unwrap_or_default();
+            let kind = if pattern.starts_with('!') { "例外规则" } else { "排除规则" };
+            format!("{} '{}'（{}）", kind, pattern, origin)
+        })
+        .collect();
+    Ok(PackagingDecision {
+        target: target.to_string(),
+        included: set.matched(relative, is_dir).is_none(),
+        reasons: if matched.is_empty() { vec!["没有排除规则匹配".to_string()] } else { reasons },
+    })
+}
+
+/// 被默认排除规则排除时的决定
+fn default_decision(target: &str, relative: &str, is_dir: bool, defaults: &DefaultExclusions) -> Option<PackagingDecision> {
+    defaults.matching(relative, is_dir).map(|rule| PackagingDecision {
+        target: target.to_string(),
+        included: false,
+        reasons: vec![format!("默认排除规则 '{}'（可在 .rmmp/Rmake.toml [build] allow 中放行）", rule)],
+    })
+}
+
+/// 解释路径在模块 zip 与源代码包中的打包决定
+pub fn explain_path(project_path: &Path, rmake_config: &RmakeConfig, relative: &str) -> Result<WhyReport> {
+    let build = &rmake_config.build;
+    let in_rmmp = relative == ".rmmp" || relative.starts_with(".rmmp/");
+    let defaults = DefaultExclusions::new(&build.allow)?;
+    let is_dir = project_path.join(relative).is_dir();
+
+    let module = if in_rmmp {
+        PackagingDecision {
+            target: "module".to_string(),
+            included: false,
+            reasons: vec!["内置规则：.rmmp 目录不参与模块打包".to_string()],
+        }
+    } else if let Some(decision) = default_decision("module", relative, is_dir, &defaults) {
+        decision
+    } else {
+        let mut rules = preset_rules(build)?;
+        rules.extend(labelled(&build.exclude, ".rmmp/Rmake.toml [build] exclude"));
+        decide("module", relative, is_dir, &rules)?
+    };
+
+    let source = if in_rmmp && relative != ".rmmp/Rmake.toml" {
+        PackagingDecision {
+            target: "source".to_string(),
+            included: false,
+            reasons: vec!["内置规则：.rmmp 中只打包 Rmake.toml".to_string()],
+        }
+    } else if let Some(decision) = default_decision("source", relative, is_dir, &defaults) {
+        decision
+    } else {
+        let src_exclude = build.src.as_ref().map(|s| s.exclude.clone()).unwrap_or_default();
+        let mut rules = preset_rules(build)?;
+        rules.extend(labelled(&src_exclude, ".rmmp/Rmake.toml [build.src] exclude"));
+        decide("source", relative, is_dir, &rules)?
+    };
+
+    let mut notes = Vec::new();
+    if let Some(pattern) = defaults.allowed_by(relative, is_dir) {
+        notes.push(format!("[build] allow 规则 '{}' 放行了默认排除规则", pattern));
+    }
+    let includes = build.include.iter()
+        .chain(build.src.iter().flat_map(|s| s.include.iter()))
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty() && !p.starts_with('#') && *p != "rmm")
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let include_set = ExclusionSet::new(&includes)?;
+    for pattern in include_set.matching(relative, is_dir) {
+        notes.push(format!("include 规则 '{}' 匹配（include 仅作提示，不会覆盖排除规则）", pattern));
+    }
+    let gitignored = git2::Repository::discover(project_path).ok().is_some_and(|repo| {
+        repo.workdir()
+            .and_then(|workdir| project_path.join(relative).strip_prefix(workdir).ok().map(Path::to_path_buf))
+            .is_some_and(|path| repo.is_path_ignored(path).unwrap_or(false))
+    });
+    if gitignored {
+        notes.push("被 .gitignore 忽略（rmm 打包不读取 .gitignore，不影响结果）".to_string());
+    }
+
+    Ok(WhyReport {
+        path: relative.to_string(),
+        exists: project_path.join(relative).exists(),
+        decisions: vec![module, source],
+        notes,
+    })
+}
+
+/// rmm why-excluded：打印路径的打包决定及依据
+pub fn why_excluded(project_path: &Path, path: &str, json: bool) -> Result<()> {
+    let rmake_config = load_rmake_config(project_path)?;
+    let relative = normalize_relative(project_path, path);
+    if relative.is_empty() {
+        anyhow::bail!("请指定项目中的文件或目录");
+    }
+    let report = explain_path(project_path, &rmake_config, &relative)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("{} {}", "[why]".magenta().bold(), report.path.bright_white());
+    if !report.exists {
+        println!("    {} 路径不存在，以下为按规则推断的结果", "[!]".yellow());
+    }
+    for decision in &report.decisions {
+        let title = if decision.target == "module" { "模块 zip" } else { "源代码包" };
+        let status = if decision.included { "包含".green().bold() } else { "排除".red().bold() };
+        println!("  {} {}", title, status);
+        for reason in &decision.reasons {
+            println!("      - {}", reason);
+        }
+    }
+    for note in &report.notes {
+        println!("  {} {}", "[i]".cyan(), note.bright_black());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_explain_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join(".rmmp")).unwrap();
+        fs::write(project_path.join(".rmmp/Rmake.toml"), r#"
+[build]
+include = ["docs/"]
+exclude = ["*.log", "docs"]
+exclude_presets = ["node"]
+prebuild = []
+build = []
+postbuild = []
+
+[build.src]
+include = []
+exclude = ["*.log"]
+"#).unwrap();
+        let config = load_rmake_config(project_path).unwrap();
+
+        assert_eq!(normalize_relative(project_path, &project_path.join("docs/a.md").to_string_lossy()), "docs/a.md");
+        assert_eq!(normalize_relative(project_path, "./docs/"), "docs");
+
+        let report = explain_path(project_path, &config, "docs/a.md").unwrap();
+        assert!(!report.exists);
+        assert!(!report.decisions[0].included);
+        assert!(report.decisions[0].reasons[0].contains("'docs'"));
+        assert!(report.decisions[1].included);
+        assert!(report.notes[0].contains("include"));
+
+        let report = explain_path(project_path, &config, "web/node_modules/x.js").unwrap();
+        assert!(report.decisions.iter().all(|d| !d.included));
+        assert!(report.decisions[1].reasons[0].contains("exclude_presets \"node\""));
+
+        let report = explain_path(project_path, &config, ".rmmp/Rmake.toml").unwrap();
+        assert!(!report.decisions[0].included);
+        assert!(report.decisions[1].included);
+
+        // 默认排除规则先于用户规则
+        let report = explain_path(project_path, &config, "lib/sub/.git/config").unwrap();
+        assert!(report.decisions.iter().all(|d| !d.included && d.reasons[0].contains("默认排除规则 '.git'")));
+        let mut config = config;
+        config.build.allow = vec!["lib/sub/.git".to_string()];
+        let report = explain_path(project_path, &config, "lib/sub/.git/config").unwrap();
+        assert!(report.decisions.iter().all(|d| d.included));
+        assert!(report.notes[0].contains("allow"));
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::cmds::build::cache::{push_remote, BuildFingerprint};
+use crate::core::download::{DownloadManager, MirrorHealthDb, DIRECT_SOURCE};
+use crate::core::remote_cache::RemoteCache;
+use crate::core::rmm_core::RmmCore;
+
+/// rmm cache stats：常驻进程运行时由其执行，显示常驻缓存的统计
+pub fn show_stats(json: bool) -> Result<()> {
+    let stats = RmmCore::new().cache_statistics();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    for stat in &stats {
+        let ttl = match stat.ttl_secs {
+            0 => "不缓存".yellow().to_string(),
+            secs => format!("{} 秒", secs),
+        };
+        println!("  {} 命中 {} / 未命中 {}，条目 {}（已过期 {}），有效期 {}",
+            format!("{:<8}", stat.cache).green().bold(), stat.hits, stat.misses, stat.entries, stat.expired, ttl);
+    }
+    if !RmmCore::shared_caches_enabled() {
+        println!("{} 常驻进程未运行，以上仅为本次命令的统计", "[i]".cyan());
+    }
+    println!("    {}", "有效期可在 meta.toml [cache] 或 RMM_CACHE_*_TTL 中调整，见 rmm env --config".bright_black());
+    Ok(())
+}
+
+/// rmm cache mirrors：各下载来源的成功率、延迟与速度
+pub fn show_mirrors(json: bool, reset: bool) -> Result<()> {
+    let path = MirrorHealthDb::path();
+    if reset {
+        MirrorHealthDb::default().save(&path)?;
+        println!("{} 已清空下载来源的健康记录", "[+]".green().bold());
+        return Ok(());
+    }
+    let db = MirrorHealthDb::load(&path);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&db)?);
+        return Ok(());
+    }
+    let configured: Vec<String> = std::iter::once(DIRECT_SOURCE.to_string())
+        .chain(DownloadManager::from_config().mirrors().iter().cloned())
+        .collect();
+    let names: std::collections::BTreeSet<&String> = configured.iter().chain(db.sources.keys()).collect();
+    for name in names {
+        let label = if name == DIRECT_SOURCE { "直连".to_string() } else { name.clone() };
+        let Some(health) = db.sources.get(name) else {
+            println!("  {} {}", label.green().bold(), "尚无记录".bright_black());
+            continue;
+        };
+        let status = if health.is_suspended() { "暂停".red().to_string() } else { "可用".green().to_string() };
+        let latency = health.latency_ms.map(|ms| format!("{:.0} ms", ms)).unwrap_or_else(|| "-".to_string());
+        let speed = health.throughput.map(|bps| format!("{:.1} KiB/s", bps / 1024.0)).unwrap_or_else(|| "-".to_string());
+        println!("  {} [{}] 成功 {} / 失败 {}，延迟 {}，速度 {}", label.green().bold(), status, health.successes, health.failures, latency, speed);
+        if !configured.contains(name) {
+            println!("      {}", "已不在 network.mirrors 中".bright_black());
+        }
+        if let Some(error) = health.last_error.as_deref().filter(|_| health.failure_streak > 0) {
+            println!("      {}", format!("最近错误: {}", error).yellow());
+        }
+    }
+    Ok(())
+}
+
+/// rmm cache remote：远程缓存的配置与项目上次构建的状态，--push 上传上次构建的产物
+pub fn show_remote(project_path: &Path, push: bool) -> Result<()> {
+    let Some(mut remote) = RemoteCache::from_config()? else {
+        println!("{} 未配置远程缓存", "[i]".cyan());
+        println!("    {}", "设置 RMM_REMOTE_CACHE 或 meta.toml [cache] remote，如 /mnt/shared/rmm-cache、https://dav.example.com/rmm 或 s3://bucket/rmm".bright_black());
+        return Ok(());
+    };
+    println!("  {} {}", "远程缓存".green().bold(), remote.backend.describe());
+    println!("  {} {}", "自动上传".green().bold(), if remote.push { "开启" } else { "关闭（cache.remote_push）" });
+
+    let Some(fingerprint) = BuildFingerprint::load(project_path) else {
+        println!("{} 项目尚未构建", "[i]".cyan());
+        return Ok(());
+    };
+    let digest = fingerprint.digest();
+    if BuildFingerprint::compute(project_path)?.digest() != digest {
+        println!("{} 项目输入在上次构建后已变化，运行 {} 后再上传", "[!]".yellow(), "rmm build".cyan());
+        return Ok(());
+    }
+    match remote.get_build(&digest)? {
+        Some(manifest) => println!("{} 上次构建已在远程缓存中（{} 个产物，{}）", "[+]".green().bold(), manifest.artifacts.len(), manifest.created_at),
+        None if push => {
+            remote.push = true;
+            push_remote(project_path, &remote, &fingerprint);
+        }
+        None => println!("{} 上次构建不在远程缓存中，使用 {} 上传", "[i]".cyan(), "--push".cyan()),
+    }
+    Ok(())
+}
@@ -0,0 +1,373 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+use crate::core::bootloop::validate_guard;
+use crate::core::checksums::{ChecksumManifest, CHECKSUMS_FILE};
+use crate::core::config_refs::{describe_issue, reference_issues, ReferenceStatus};
+use crate::core::check_report::{known_issue_findings, Finding, FindingSeverity, ValidationReport, KNOWN_ISSUES_TOOL};
+use crate::core::encoding::scan_encodings;
+use crate::core::env_file::ENV_LOCAL_FILE;
+use crate::core::exclude_presets::expand_presets;
+use crate::cmds::device::artifact::list_artifacts;
+use crate::core::i18n::{validate_i18n, I18nReport};
+use crate::core::layout::{inspect_rmmp, LayoutIssue};
+use crate::core::known_issues::{KnownIssueFinding, KnownIssuesDatabase, Severity};
+use crate::core::privacy::{find_local_paths, local_roots};
+use crate::core::gitignore::{is_tracked, tracked_build_outputs};
+use crate::core::rmm_core::RmakeConfig;
+use crate::core::uninstall::uncovered_runtime_writes;
+use crate::core::control_files::control_warnings;
+use crate::core::mount_strategy::mount_issues;
+use crate::core::module_props::{integrity_warnings, is_valid_prop_name, validate_props, SYSTEM_PROP};
+use crate::core::licenses::license_warnings;
+use crate::core::migrations::{load_migrations, validate_migrations};
+use crate::core::update_json::{divergent_update_json, parse_manifest, verify_channel};
+use crate::core::zip_inspect::parse_module_prop;
+
+pub mod service;
+
+use service::{dry_run_service, SERVICE_DRY_RUN_TOOL};
+
+/// rmm check 自身的检查项在报告中的工具名
+const CHECK_TOOL: &str = "rmm-check";
+
+/// 检查结果，同时写入 .rmmp/check-report.json
+#[derive(Debug, Default)]
+struct CheckReport {
+    findings: Vec<Finding>,
+}
+
+impl CheckReport {
+    fn ok(&self, message: &str) {
+        println!("  {} {}", "✅".green(), message);
+    }
+
+    fn push(&mut self, finding: Finding) {
+        println!("{}", finding.render());
+        self.findings.push(finding);
+    }
+
+    fn warn(&mut self, message: &str) {
+        self.push(Finding::new(CHECK_TOOL, FindingSeverity::Warning, message));
+    }
+
+    fn error(&mut self, message: &str) {
+        self.push(Finding::new(CHECK_TOOL, FindingSeverity::Error, message));
+    }
+
+    fn count(&self, severity: FindingSeverity) -> usize {
+        self.findings.iter().filter(|f| f.severity == severity).count()
+    }
+}
+
+/// 打印已知问题检查结果，返回 error 级别的数量
+pub fn report_known_issues(findings: &[KnownIssueFinding]) -> usize {
+    for finding in known_issue_findings(findings) {
+        println!("{}", finding.render());
+    }
+    findings.iter().filter(|f| f.severity == Severity::Error).count()
+}
+
+/// 检查模块项目配置，存在错误时返回 Err；update_db 时先从上游更新已知问题数据库，
+/// dry_run_service 时在主机上试运行 service.sh
+pub fn check_project(project_path: &Path, update_db: bool, dry_run_service_sh: bool) -> Result<()> {
+    println!("{} 检查项目: {}", "[🔍]".cyan().bold(), project_path.display());
+    let mut report = CheckReport::default();
+    let mut module_id = None;
+    let mut known_issues_config = None;
+    let mut uninstall_config = None;
+    let mut control_config = None;
+
+    // module.prop
+    match fs::read_to_string(project_path.join("module.prop")) {
+        Ok(content) => {
+            let prop = parse_module_prop(&content);
+            module_id = prop.get("id").cloned();
+            let missing: Vec<&str> = ["id", "name", "version", "versionCode"]
+                .into_iter()
+                .filter(|key| prop.get(*key).is_none_or(|v| v.is_empty()))
+                .collect();
+            if missing.is_empty() {
+                report.ok("module.prop");
+            } else {
+                report.error(&format!("module.prop 缺少字段: {}", missing.join(", ")));
+            }
+        }
+        Err(_) => report.error("缺少 module.prop"),
+    }
+
+    // Rmake.toml
+    match fs::read_to_string(project_path.join(".rmmp/Rmake.toml")) {
+        Ok(content) => match toml::from_str::<RmakeConfig>(&content) {
+            Ok(config) => {
+                known_issues_config = config.check.as_ref().and_then(|c| c.known_issues.clone());
+                uninstall_config = config.uninstall.clone();
+                control_config = config.control.clone();
+                let channels = config.update.as_ref().map(|u| u.channels.iter());
+                let invalid: Vec<String> = channels
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(name, channel)| verify_channel(name, channel).err())
+                    .map(|e| e.to_string())
+                    .collect();
+                if invalid.is_empty() {
+                    report.ok(".rmmp/Rmake.toml");
+                }
+                for message in invalid {
+                    report.error(&message);
+                }
+                
+                // 排除预设
+                if let Err(e) = expand_presets(&config.build.exclude_presets) {
+                    report.error(&e.to_string());
+                }
+                
+                // 本地化资源
+                if let Some(i18n) = config.check.as_ref().and_then(|c| c.i18n.as_ref()) {
+                    let i18n_report = validate_i18n(project_path, i18n);
+                    if i18n_report == I18nReport::default() {
+                        report.ok(&format!("本地化资源 ({} 种语言)", i18n.locales.len()));
+                    }
+                    for message in &i18n_report.errors {
+                        report.error(message);
+                    }
+                    for message in &i18n_report.warnings {
+                        report.warn(message);
+                    }
+                }
+
+                // 启动失败守护
+                if let Some(guard) = &config.bootloop_guard {
+                    match validate_guard(guard) {
+                        Ok(()) => report.ok(&format!("启动失败守护（{} 次）", guard.max_attempts)),
+                        Err(e) => report.error(&e.to_string()),
+                    }
+                }
+
+                // 模块运行时属性
+                if let Some(module) = config.module.as_ref().filter(|m| !m.props.is_empty()) {
+                    let errors = validate_props(module);
+                    if errors.is_empty() {
+                        report.ok(&format!("[module.props]（{} 个属性）", module.props.len()));
+                    }
+                    for message in &errors {
+                        report.error(message);
+                    }
+                    for message in integrity_warnings(module.props.keys().map(String::as_str), "[module.props]") {
+                        report.warn(&message);
+                    }
+                }
+
+                // 上次构建的文件校验清单
+                let build_dir = project_path.join(".rmmp/build");
+                if config.build.checksums.is_some() && build_dir.join(CHECKSUMS_FILE).exists() {
+                    match ChecksumManifest::load(&build_dir).and_then(|manifest| Ok((manifest.verify(&build_dir)?, manifest))) {
+                        Ok((problems, manifest)) if problems.is_empty() => {
+                            report.ok(&format!("校验清单与构建目录一致（{}，{} 个文件）", manifest.algorithm.as_str(), manifest.files.len()))
+                        }
+                        Ok((problems, _)) => report.warn(&format!(
+                            "构建目录与校验清单不一致: {}（共 {} 项），请重新运行 rmm build", problems[0], problems.len()
+                        )),
+                        Err(e) => report.error(&e.to_string()),
+                    }
+                }
+
+                // 第三方组件的许可证
+                for message in license_warnings(project_path, &config) {
+                    report.warn(&message);
+                }
+            }
+            Err(e) => report.error(&format!("Rmake.toml 解析失败: {}", e)),
+        },
+        Err(_) => report.error("缺少 .rmmp/Rmake.toml"),
+    }
+
+    // 数据迁移脚本
+    match load_migrations(project_path) {
+        Ok(migrations) => {
+            let errors = validate_migrations(project_path, &migrations);
+            if !migrations.is_empty() && errors.is_empty() {
+                report.ok(&format!("数据迁移脚本 ({} 个)", migrations.len()));
+            }
+            for message in &errors {
+                report.error(message);
+            }
+        }
+        Err(e) => report.error(&e.to_string()),
+    }
+
+    // 项目中手写的 system.prop
+    if let Ok(content) = fs::read_to_string(project_path.join(SYSTEM_PROP)) {
+        let props = parse_module_prop(&content);
+        for name in props.keys().filter(|name| !is_valid_prop_name(name)) {
+            report.push(Finding::new(CHECK_TOOL, FindingSeverity::Error, format!("属性名 '{}' 无效", name)).in_file(SYSTEM_PROP, None));
+        }
+        for message in integrity_warnings(props.keys().map(String::as_str), SYSTEM_PROP) {
+            report.push(Finding::new(CHECK_TOOL, FindingSeverity::Warning, message).in_file(SYSTEM_PROP, None));
+        }
+    }
+
+    // update.json
+    let update_json = project_path.join("update.json");
+    if update_json.exists() {
+        match fs::read_to_string(&update_json).map_err(anyhow::Error::from).and_then(|c| parse_manifest(&c)) {
+            Ok(_) => report.ok("update.json"),
+            Err(e) => report.error(&format!("update.json 无效: {}", e)),
+        }
+    }
+    // .rmmp 中的 update.json 副本必须与项目根目录一致
+    for (copy, reason) in divergent_update_json(project_path) {
+        let file = copy.strip_prefix(project_path).unwrap_or(&copy).to_string_lossy().replace('\\', "/");
+        report.push(Finding::new(CHECK_TOOL, FindingSeverity::Error, reason)
+            .in_file(file, None)
+            .with_fix("只修改项目根目录的 update.json，运行 rmm build 重新生成副本或 rmm clean 删除"));
+    }
+
+    // 构建输出不应被 Git 跟踪
+    if !project_path.join(".rmmp/.gitignore").exists() {
+        report.warn("缺少 .rmmp/.gitignore，运行 rmm build 会自动生成");
+    }
+    for issue in inspect_rmmp(project_path)? {
+        match issue {
+            LayoutIssue::Legacy { name, reason, .. } => {
+                report.warn(&format!(".rmmp/{} 是旧版遗留条目（{}），运行 rmm clean --layout 迁移或清理", name, reason));
+            }
+            LayoutIssue::Unknown { name } => {
+                report.warn(&format!(".rmmp/{} 不属于 RMM 的目录约定，运行 rmm clean --layout 查看", name));
+            }
+        }
+    }
+    let tracked = tracked_build_outputs(project_path)?;
+    if !tracked.is_empty() {
+        report.warn(&format!(
+            "{} 个构建输出已被 Git 跟踪（如 {}），建议执行 git rm -r --cached 移除",
+            tracked.len(),
+            tracked[0]
+        ));
+    }
+
+    // 发布的元数据中不应包含本机路径
+    let roots = local_roots(project_path);
+    for file in ["rmmproject.toml", "module.prop", "update.json", ".rmmp/Rmake.toml"] {
+        let Ok(content) = fs::read_to_string(project_path.join(file)) else { continue };
+        for (index, line) in content.lines().enumerate() {
+            for path in find_local_paths(line, &roots) {
+                report.push(Finding::new(CHECK_TOOL, FindingSeverity::Warning, format!("包含本机路径 {}，发布后会暴露本地目录结构", path))
+                    .in_file(file, Some(index as u32 + 1)));
+            }
+        }
+    }
+
+    // 配置中引用的文档与脚本
+    for (reference, status) in reference_issues(project_path) {
+        let mut finding = Finding::new(CHECK_TOOL, FindingSeverity::Warning, describe_issue(&reference, &status))
+            .in_file(reference.file, None);
+        if let ReferenceStatus::CaseMismatch { actual } = &status {
+            finding = finding.with_fix(format!("将 {} 改为 \"{}\"", reference.key, actual));
+        }
+        report.push(finding);
+    }
+
+    // 文本文件的编码：BOM 与 UTF-16 会在构建时转换，无法无损转换的文件会原样打包
+    for (file, encoding, error) in scan_encodings(project_path) {
+        let file = file.to_string_lossy().replace('\\', "/");
+        let (severity, message) = match error {
+            Some(error) => (FindingSeverity::Error, format!("{} 无法无损转换为 UTF-8: {}", encoding.as_str(), error)),
+            None => (FindingSeverity::Warning, format!("编码为 {}，构建时会转换为 UTF-8", encoding.as_str())),
+        };
+        report.push(Finding::new(CHECK_TOOL, severity, message).in_file(file, None).with_fix("用编辑器以 UTF-8（无 BOM）重新保存"));
+    }
+
+    if is_tracked(project_path, ENV_LOCAL_FILE)? {
+        report.warn(&format!("{} 已被 Git 跟踪，其中的本机配置或密钥可能被提交", ENV_LOCAL_FILE));
+    }
+
+    // 运行时写入的持久化文件应在卸载时清理
+    for write in uncovered_runtime_writes(project_path, uninstall_config.as_ref()) {
+        report.push(Finding::new(CHECK_TOOL, FindingSeverity::Warning, format!("写入 {}，卸载后会残留", write.path))
+            .in_file(write.script.clone(), Some(write.line as u32))
+            .with_fix("加入 [uninstall] paths 或 waive"));
+    }
+
+    // 控制文件与挂载内容
+    for message in control_warnings(project_path, control_config.as_ref()) {
+        report.warn(&message);
+    }
+    if let Some(strategy) = control_config.as_ref().and_then(|c| c.mount) {
+        for issue in mount_issues(project_path, strategy) {
+            report.push(Finding::new(CHECK_TOOL, FindingSeverity::Warning, issue.message).in_file(issue.file, None));
+        }
+    }
+
+    // 在主机上试运行 service.sh
+    if dry_run_service_sh {
+        if project_path.join("service.sh").is_file() {
+            let result = dry_run_service(project_path)?;
+            if result.is_clean() {
+                report.ok(&format!("service.sh 试运行（{}，{} ms）", result.shell, result.elapsed_ms));
+            }
+            for finding in result.findings() {
+                report.push(finding);
+            }
+        } else {
+            report.warn("没有 service.sh，跳过试运行");
+        }
+    }
+
+    // 最新产物与已知问题数据库
+    let database = if update_db {
+        match KnownIssuesDatabase::update() {
+            Ok(database) => {
+                report.ok(&format!("已知问题数据库已更新（版本 {}，{} 条规则）", database.version, database.rules.len()));
+                database
+            }
+            Err(e) => {
+                report.warn(&format!("{:#}，使用本地数据库", e));
+                KnownIssuesDatabase::load()
+            }
+        }
+    } else {
+        KnownIssuesDatabase::load()
+    };
+    let mut checked_zip = false;
+    let latest = module_id.and_then(|id| list_artifacts(project_path, &id).into_iter().next());
+    if let Some(zip_path) = latest {
+        let name = zip_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let findings = database.check_zip(&zip_path, known_issues_config.as_ref())?;
+        if findings.is_empty() {
+            report.ok(&format!("{} 未发现已知问题", name));
+        } else {
+            println!("  {} {} 已知问题:", "[!]".yellow(), name);
+        }
+        for finding in known_issue_findings(&findings) {
+            report.push(finding);
+        }
+        checked_zip = true;
+    }
+
+    let errors = report.count(FindingSeverity::Error);
+    let warnings = report.count(FindingSeverity::Warning);
+    let (known, own): (Vec<Finding>, Vec<Finding>) = report.findings.into_iter().partition(|f| f.tool == KNOWN_ISSUES_TOOL);
+    let (service, own) = own.into_iter().partition(|f| f.tool == SERVICE_DRY_RUN_TOOL);
+    let mut merged = ValidationReport::load(project_path);
+    merged.replace_tool(CHECK_TOOL, own);
+    if checked_zip {
+        merged.replace_tool(KNOWN_ISSUES_TOOL, known);
+    }
+    if dry_run_service_sh {
+        merged.replace_tool(SERVICE_DRY_RUN_TOOL, service);
+    }
+    let report_path = merged.save(project_path)?;
+
+    println!();
+    println!("{} 检查报告已保存到: {}", "[+]".green().bold(), report_path.display());
+    if errors > 0 {
+        anyhow::bail!("发现 {} 个错误，{} 个警告", errors, warnings);
+    }
+    if warnings > 0 {
+        println!("{} 检查完成，{} 个警告", "[!]".yellow(), warnings);
+    }
+    Ok(())
+}
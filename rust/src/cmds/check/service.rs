@@ -0,0 +1,328 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+
+use crate::cmds::test::host::{HostShell, DEFAULT_PROPS, HOST_TESTS_DIR, TEST_PROPS_FILE};
+use crate::core::check_report::{Finding, FindingSeverity};
+use crate::core::process::{timeout_for, CommandExt, CommandKind, TempGuard};
+use crate::core::storage::copy_dir_all;
+use crate::core::zip_inspect::parse_module_prop;
+
+/// 试运行结果在报告中的工具名
+pub const SERVICE_DRY_RUN_TOOL: &str = "service-dry-run";
+/// 开机完成相关的属性，避免 service.sh 中等待开机的循环永远不结束
+const BOOT_PROPS: &str = "sys.boot_completed=1
+dev.bootcomplete=1
+init.svc.bootanim=stopped
+";
+/// 不复制到模拟 MODDIR 的项目条目
+const SKIPPED_ENTRIES: [&str; 3] = [".rmmp", ".git", HOST_TESTS_DIR];
+
+/// 设备命令的替身：每次调用记录到 $RMM_SERVICE_CALLS，属性读写使用 $RMM_SERVICE_PROPS
+const STUB_COMMANDS: &[(&str, &str)] = &[
+    ("getprop", r#"if [ $# -eq 0 ]; then
+  sed 's/^\([^=]*\)=\(.*\)$/[\1]: [\2]/' "$RMM_SERVICE_PROPS"
+  exit 0
+fi
+value=$(grep "^$1=" "$RMM_SERVICE_PROPS" | tail -n 1 | cut -d= -f2-)
+[ -n "$value" ] && echo "$value" || echo "$2""#),
+    ("resetprop", r#"while [ "${1#-}" != "$1" ]; do shift; done
+if [ $# -lt 2 ]; then exec getprop "$@"; fi
+echo "$1=$2" >> "$RMM_SERVICE_PROPS""#),
+    ("setprop", r#"echo "$1=$2" >> "$RMM_SERVICE_PROPS""#),
+    ("magisk", r#"case "$1" in
+  -V) echo 27000 ;;
+  -v) echo 27.0:MAGISK ;;
+  --path) echo "$RMM_SERVICE_ROOT/magisk" ;;
+esac"#),
+    ("su", r#"if [ "$1" = "-c" ]; then shift; exec sh -c "$*"; fi"#),
+    ("ksud", ""),
+    ("apd", ""),
+    ("getenforce", "echo Enforcing"),
+    ("setenforce", ""),
+    ("settings", ""),
+    ("am", ""),
+    ("pm", ""),
+    ("cmd", ""),
+    ("svc", ""),
+    ("wm", ""),
+    ("dumpsys", ""),
+    ("logcat", ""),
+    ("log", ""),
+    ("start", ""),
+    ("stop", ""),
+    ("chcon", ""),
+    ("restorecon", ""),
+    ("mount", ""),
+    ("umount", ""),
+    ("reboot", ""),
+    // 开机后的延时不必真的等待；死循环中的 sleep 仍会因超时被发现
+    ("sleep", ""),
+];
+
+/// 试运行脚本：service.sh 的输出写入日志，使其留在后台的进程不占用管道；结束后清理整个进程组
+const RUNNER: &str = r#"cd "$MODDIR" || exit 1
+"$RMM_SERVICE_SHELL" $RMM_SERVICE_SHELL_ARGS "$MODDIR/service.sh" > "$RMM_SERVICE_LOG" 2>&1 < /dev/null
+rmm_status=$?
+trap '' TERM
+kill -TERM 0 2> /dev/null
+exit $rmm_status
+"#;
+
+/// 找不到的命令
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MissingCommand {
+    pub command: String,
+    /// service.sh 中的行号（shell 报告时）
+    pub line: Option<u32>,
+}
+
+/// rmm check --dry-run-service 的结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ServiceDryRun {
+    /// 运行使用的 shell
+    pub shell: String,
+    /// 超时（秒），None 表示不限制
+    pub timeout_secs: Option<u64>,
+    pub timed_out: bool,
+    /// 退出码，超时时为 None
+    pub exit_code: Option<i32>,
+    pub elapsed_ms: u128,
+    pub missing: Vec<MissingCommand>,
+    /// 对设备命令替身的调用，按先后顺序
+    pub calls: Vec<String>,
+    pub output: String,
+}
+
+impl ServiceDryRun {
+    /// 正常结束且没有找不到的命令
+    pub fn is_clean(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0) && self.missing.is_empty()
+    }
+
+    /// 调用次数最多的设备命令（不含 sleep），用于提示超时时卡在哪里
+    pub fn most_frequent_call(&self) -> Option<(&str, usize)> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for call in self.calls.iter().filter(|call| !call.starts_with("sleep")) {
+            *counts.entry(call.as_str()).or_default() += 1;
+        }
+        counts.into_iter().max_by_key(|(_, count)| *count)
+    }
+
+    /// 转换为统一报告格式
+    pub fn findings(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if self.timed_out {
+            let mut message = format!("service.sh 在 {} 秒内没有结束，可能存在死循环或永远不满足的等待条件", self.timeout_secs.unwrap_or_default());
+            if let Some((call, count)) = self.most_frequent_call().filter(|(_, count)| *count > 1) {
+                message.push_str(&format!("（反复调用 {}，{} 次）", call, count));
+            }
+            findings.push(Finding::new(SERVICE_DRY_RUN_TOOL, FindingSeverity::Error, message)
+                .in_file("service.sh", None)
+                .with_fix("长时间运行的循环放入后台 ( ... ) &；等待的属性可在 tests/test.prop 中设置后重试"));
+        } else if let Some(code) = self.exit_code.filter(|code| *code != 0) {
+            let mut finding = Finding::new(SERVICE_DRY_RUN_TOOL, FindingSeverity::Warning, format!("service.sh 以退出码 {} 结束", code))
+                .in_file("service.sh", None);
+            let tail: Vec<&str> = self.output.lines().rev().take(5).collect();
+            if !tail.is_empty() {
+                finding = finding.with_fix(tail.into_iter().rev().collect::<Vec<_>>().join("\n      "));
+            }
+            findings.push(finding);
+        }
+        for missing in &self.missing {
+            findings.push(Finding::new(SERVICE_DRY_RUN_TOOL, FindingSeverity::Warning, format!("找不到命令 {}", missing.command))
+                .in_file("service.sh", missing.line)
+                .with_fix("确认设备上存在该命令，或改用 $MODDIR 中自带的二进制并先用 command -v 检查"));
+        }
+        findings
+    }
+}
+
+/// 从 shell 的错误输出中提取找不到的命令，兼容 dash、bash 与 busybox ash 的格式
+pub fn missing_commands(output: &str) -> Vec<MissingCommand> {
+    let mut missing: Vec<MissingCommand> = Vec::new();
+    for line in output.lines() {
+        let Some(rest) = line.strip_suffix(": command not found").or_else(|| line.strip_suffix(": not found")) else {
+            continue;
+        };
+        let parts: Vec<&str> = rest.split(": ").collect();
+        let Some(command) = parts.last().map(|c| c.trim()).filter(|c| !c.is_empty()) else {
+            continue;
+        };
+        let line = parts.iter().rev().skip(1)
+            .find_map(|part| part.trim().trim_start_matches("line ").parse::<u32>().ok());
+        if !missing.iter().any(|m| m.command == command) {
+            missing.push(MissingCommand { command: command.to_string(), line });
+        }
+    }
+    missing
+}
+
+/// 准备模拟环境：项目文件复制到 modules/<id> 作为 MODDIR，设备命令替身放入 bin
+fn prepare_environment(project_path: &Path, work_dir: &Path) -> Result<std::path::PathBuf> {
+    let id = fs::read_to_string(project_path.join("module.prop")).ok()
+        .and_then(|content| parse_module_prop(&content).get("id").cloned())
+        .unwrap_or_else(|| "module".to_string());
+    let moddir = work_dir.join("modules").join(id);
+    fs::create_dir_all(&moddir)?;
+    for entry in fs::read_dir(project_path)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if SKIPPED_ENTRIES.iter().any(|skipped| name == *skipped) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &moddir.join(&name))?;
+        } else {
+            fs::copy(entry.path(), moddir.join(&name))?;
+        }
+    }
+
+    let bin = work_dir.join("bin");
+    fs::create_dir_all(&bin)?;
+    for (name, body) in STUB_COMMANDS {
+        let path = bin.join(name);
+        fs::write(&path, format!("#!/bin/sh\necho \"{} $*\" >> \"$RMM_SERVICE_CALLS\"\n{}\n", name, body))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+    }
+
+    let mut props = format!("{}{}", DEFAULT_PROPS, BOOT_PROPS);
+    if let Ok(extra) = fs::read_to_string(project_path.join(HOST_TESTS_DIR).join(TEST_PROPS_FILE)) {
+        props.push_str(&extra);
+        if !props.ends_with('\n') {
+            props.push('\n');
+        }
+    }
+    fs::write(work_dir.join("props"), props)?;
+    fs::write(work_dir.join("calls"), "")?;
+    fs::create_dir_all(work_dir.join("tmp"))?;
+    fs::write(work_dir.join("run.sh"), RUNNER)?;
+    Ok(moddir)
+}
+
+/// 在主机上以模拟的设备环境运行 service.sh，超时由 [timeouts] service 控制
+pub fn dry_run_service(project_path: &Path) -> Result<ServiceDryRun> {
+    if !project_path.join("service.sh").is_file() {
+        anyhow::bail!("{} 下没有 service.sh", project_path.display());
+    }
+    let shell = HostShell::detect(project_path);
+    let work_dir = project_path.join(".rmmp").join("service-dry-run");
+    let _ = fs::remove_dir_all(&work_dir);
+    let _guard = TempGuard::new(&work_dir);
+    let moddir = prepare_environment(project_path, &work_dir)?;
+
+    let path = std::env::join_paths(std::iter::once(work_dir.join("bin"))
+        .chain(std::env::var_os("PATH").map(|p| std::env::split_paths(&p).collect::<Vec<_>>()).unwrap_or_default()))?;
+    let timeout = timeout_for(CommandKind::Service);
+    let started = Instant::now();
+    let result = Command::new(&shell.program)
+        .args(&shell.args)
+        .arg(work_dir.join("run.sh"))
+        .env_clear()
+        .env("PATH", path)
+        .env("HOME", &work_dir)
+        .env("TMPDIR", work_dir.join("tmp"))
+        .env("LANG", "C")
+        .env("MODDIR", &moddir)
+        .env("BOOTMODE", "true")
+        .env("MAGISK_VER", "27.0")
+        .env("MAGISK_VER_CODE", "27000")
+        .env("API", "34")
+        .env("ARCH", "arm64")
+        .env("RMM_SERVICE_SHELL", &shell.program)
+        .env("RMM_SERVICE_SHELL_ARGS", shell.args.join(" "))
+        .env("RMM_SERVICE_ROOT", &work_dir)
+        .env("RMM_SERVICE_LOG", work_dir.join("service.log"))
+        .env("RMM_SERVICE_PROPS", work_dir.join("props"))
+        .env("RMM_SERVICE_CALLS", work_dir.join("calls"))
+        .current_dir(&moddir)
+        .output_with_timeout(CommandKind::Service);
+    let elapsed = started.elapsed();
+
+    let (timed_out, exit_code) = match result {
+        Ok(output) => (false, output.status.code()),
+        Err(_) if timeout.is_some_and(|t| elapsed >= t) => (true, None),
+        Err(e) => return Err(e).context("无法试运行 service.sh"),
+    };
+    let output = fs::read_to_string(work_dir.join("service.log")).unwrap_or_default();
+    let calls = fs::read_to_string(work_dir.join("calls")).unwrap_or_default()
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .collect();
+    Ok(ServiceDryRun {
+        shell: shell.describe(),
+        timeout_secs: timeout.map(|t| t.as_secs()),
+        timed_out,
+        exit_code,
+        elapsed_ms: elapsed.as_millis(),
+        missing: missing_commands(&output),
+        calls,
+        output,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_commands() {
+        let output = "/data/adb/modules/demo/service.sh: 3: busybox: not found
+/data/adb/modules/demo/service.sh: line 7: toybox: command not found
+/data/adb/modules/demo/service.sh: line 9: busybox: not found
+grep: pattern not found in file
+";
+        assert_eq!(missing_commands(output), vec![
+            MissingCommand { command: "busybox".to_string(), line: Some(3) },
+            MissingCommand { command: "toybox".to_string(), line: Some(7) },
+        ]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dry_run_service() {
+        use crate::core::process::{set_timeouts, TimeoutConfig};
+
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path();
+        fs::write(project.join("module.prop"), "id=demo\n").unwrap();
+        fs::create_dir_all(project.join("tests")).unwrap();
+        fs::write(project.join("tests/test.prop"), "persist.demo.mode=fast\n").unwrap();
+        fs::write(project.join("service.sh"), r#"MODDIR=${0%/*}
+until [ "$(getprop sys.boot_completed)" = 1 ]; do sleep 1; done
+resetprop -n ro.demo.mode "$(getprop persist.demo.mode)"
+echo "mode=$(getprop ro.demo.mode) dir=${MODDIR##*/}"
+rmm_missing_tool --version
+"#).unwrap();
+
+        // 等待开机的循环立即结束，找不到的命令带行号
+        let result = dry_run_service(project).unwrap();
+        assert!(!result.timed_out);
+        assert!(result.output.contains("mode=fast dir=demo"), "{}", result.output);
+        assert_eq!(result.missing.len(), 1, "{}", result.output);
+        assert_eq!(result.missing[0].command, "rmm_missing_tool");
+        assert_eq!(result.missing[0].line, Some(5));
+        assert!(result.calls.contains(&"resetprop -n ro.demo.mode fast".to_string()));
+        assert!(!project.join(".rmmp/service-dry-run").exists());
+
+        // 等待永远不会出现的属性：超时并指出反复调用的命令
+        fs::write(project.join("service.sh"), "while [ \"$(getprop init.svc.never)\" != running ]; do sleep 1; done\n").unwrap();
+        set_timeouts(Some(TimeoutConfig { service: Some(1), ..Default::default() }));
+        let result = dry_run_service(project).unwrap();
+        set_timeouts(None);
+        assert!(result.timed_out);
+        assert_eq!(result.exit_code, None);
+        let findings = result.findings();
+        assert_eq!(findings[0].severity, FindingSeverity::Error);
+        assert!(findings[0].message.contains("getprop init.svc.never"), "{}", findings[0].message);
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::Result;
+use clap::Command;
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::errors::ProjectNotFoundError;
+
+/// 生成的 GitHub Actions 工作流路径（相对项目根目录）
+const GITHUB_WORKFLOW_PATH: &str = ".github/workflows/rmm.yml";
+
+/// 工作流中的一个 rmm 步骤
+struct CiStep {
+    /// 步骤名称
+    name: &'static str,
+    /// rmm 参数（首个为子命令）
+    args: &'static [&'static str],
+    /// GitHub Actions `if:` 条件
+    condition: Option<&'static str>,
+    /// 需要的 secrets
+    secrets: &'static [&'static str],
+}
+
+/// 工作流流水线：构建 → 检查 → 测试 → 发布
+const PIPELINE: &[CiStep] = &[
+    CiStep { name: "Build module", args: &["build", "--no-auto-fix", "--force"], condition: None, secrets: &[] },
+    CiStep { name: "Check module", args: &["check"], condition: None, secrets: &[] },
+    // 设备测试需要连接设备（如模拟器 runner），通过仓库变量 RMM_DEVICE_TESTS 启用
+    CiStep {
+        name: "Test module",
+        args: &["test"],
+        condition: Some("vars.RMM_DEVICE_TESTS == 'true'"),
+        secrets: &[],
+    },
+    CiStep {
+        name: "Publish release",
+        args: &["publish"],
+        condition: Some("startsWith(github.ref, 'refs/tags/')"),
+        secrets: &["GITHUB_TOKEN", "UPSTREAM_ACCESS_TOKEN"],
+    },
+];
+
+const GITHUB_WORKFLOW_TEMPLATE: &str = r#"# 由 `rmm ci init --github` 生成，可通过重新运行该命令更新
+name: RMM Module
+
+on:
+  push:
+    branches: [main, master]
+    tags: ['v*']
+  pull_request:
+  workflow_dispatch:
+
+permissions:
+  contents: write
+
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - name: Checkout
+        uses: actions/checkout@v4
+        with:
+          fetch-depth: 0
+
+      - name: Setup Python
+        uses: actions/setup-python@v5
+        with:
+          python-version: '3.12'
+
+      - name: Install rmm
+        run: pip install pyrmm=={{version}}
+
+{{steps}}
+      - name: Upload artifacts
+        uses: actions/upload-artifact@v4
+        with:
+          name: {{project_id}}
+          path: .rmmp/dist/
+"#;
+
+/// 检查某个 rmm 步骤在当前 CLI 中是否可用，返回不可用的原因
+fn validate_step(cli: &Command, step: &CiStep) -> Option<String> {
+    let (subcommand, flags) = step.args.split_first()?;
+    let Some(sub) = cli.find_subcommand(subcommand) else {
+        return Some(format!("当前 rmm 没有 `{}` 命令", subcommand));
+    };
+    for flag in flags.iter().filter(|f| f.starts_with("--")) {
+        let long = flag.trim_start_matches("--");
+        if !sub.get_arguments().any(|arg| arg.get_long() == Some(long)) {
+            return Some(format!("`rmm {}` 不支持参数 {}", subcommand, flag));
+        }
+    }
+    None
+}
+
+/// 根据当前 CLI 定义渲染 GitHub Actions 工作流
+pub fn render_github_workflow(cli: &Command, project_id: &str) -> String {
+    let mut steps = String::new();
+    for step in PIPELINE {
+        if let Some(reason) = validate_step(cli, step) {
+            steps.push_str(&format!("      # 已跳过 {}: {}\n\n", step.name, reason));
+            continue;
+        }
+        steps.push_str(&format!("      - name: {}\n", step.name));
+        if let Some(condition) = step.condition {
+            steps.push_str(&format!("        if: {}\n", condition));
+        }
+        if !step.secrets.is_empty() {
+            steps.push_str("        env:\n");
+            for secret in step.secrets {
+                steps.push_str(&format!("          {}: ${{{{ secrets.{} }}}}\n", secret, secret));
+            }
+        }
+        steps.push_str(&format!("        run: rmm {}\n\n", step.args.join(" ")));
+    }
+
+    GITHUB_WORKFLOW_TEMPLATE
+        .replace("{{version}}", env!("CARGO_PKG_VERSION"))
+        .replace("{{project_id}}", project_id)
+        .replace("{{steps}}\n", &steps)
+}
+
+/// 初始化 GitHub Actions 工作流
+pub fn init_github(project_path: &Path, cli: &Command, force: bool) -> Result<PathBuf> {
+    if !project_path.join(".rmmp/Rmake.toml").exists() {
+        return Err(ProjectNotFoundError { path: project_path.to_path_buf() }.into());
+    }
+
+    let workflow_path = project_path.join(GITHUB_WORKFLOW_PATH);
+    if workflow_path.exists() && !force {
+        anyhow::bail!("{} 已存在，使用 --force 覆盖", GITHUB_WORKFLOW_PATH);
+    }
+
+    let project_id = project_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("module");
+
+    for step in PIPELINE {
+        if let Some(reason) = validate_step(cli, step) {
+            println!("{} 跳过步骤 {}: {}", "[!]".yellow(), step.name, reason);
+        }
+    }
+
+    if let Some(parent) = workflow_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&workflow_path, render_github_workflow(cli, project_id))?;
+    println!("{} 生成工作流: {}", "[+]".green().bold(), workflow_path.display());
+    println!("{} 发布步骤需要仓库 secrets: GITHUB_TOKEN, UPSTREAM_ACCESS_TOKEN", "[i]".cyan());
+
+    Ok(workflow_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_github_workflow_tracks_cli() {
+        let cli = crate::Cli::command();
+        let workflow = render_github_workflow(&cli, "demo");
+
+        assert!(workflow.contains("run: rmm build --no-auto-fix --force"));
+        assert!(workflow.contains("GITHUB_TOKEN: ${{ secrets.GITHUB_TOKEN }}"));
+        assert!(workflow.contains("UPSTREAM_ACCESS_TOKEN: ${{ secrets.UPSTREAM_ACCESS_TOKEN }}"));
+        assert!(workflow.contains("if: startsWith(github.ref, 'refs/tags/')"));
+        assert!(workflow.contains("if: vars.RMM_DEVICE_TESTS == 'true'"));
+        assert!(workflow.contains("name: demo"));
+        for placeholder in ["{{version}}", "{{project_id}}", "{{steps}}"] {
+            assert!(!workflow.contains(placeholder));
+        }
+    }
+
+    #[test]
+    fn test_init_github_refuses_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join(".rmmp")).unwrap();
+        fs::write(project_path.join(".rmmp/Rmake.toml"), "[build]\n").unwrap();
+
+        let cli = crate::Cli::command();
+        let path = init_github(project_path, &cli, false).unwrap();
+        assert!(path.exists());
+        assert!(init_github(project_path, &cli, false).is_err());
+        assert!(init_github(project_path, &cli, true).is_ok());
+    }
+}
@@ -0,0 +1,236 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::core::guard::{ensure_safe_target, is_project_dir, move_to_trash, restore_trash, PROJECT_MARKER};
+use crate::core::layout::{inspect_rmmp, migrate_legacy, output_entries, rmmp_path, LayoutIssue, LegacyAction, RMMP_DIR, RMMP_LAYOUT};
+use crate::core::storage::RmmStorage;
+
+/// 需要清理的路径：默认为构建输出，all 时为整个 .rmmp
+pub fn clean_targets(project_path: &Path, all: bool) -> Vec<PathBuf> {
+    let candidates = if all {
+        vec![project_path.join(RMMP_DIR)]
+    } else {
+        output_entries().map(|entry| rmmp_path(project_path, entry.name)).collect()
+    };
+    candidates.into_iter().filter(|p| p.exists()).collect()
+}
+
+/// 清理项目，内容移入回收站，可用 rmm clean --undo 恢复
+pub fn clean_project(project_path: &Path, trash_dir: &Path, all: bool, force: bool) -> Result<()> {
+    ensure_safe_target(project_path, "clean", force)?;
+    if !is_project_dir(project_path) && !force {
+        anyhow::bail!(
+            "{} 不是 RMM 项目（缺少 {}），确认无误请使用 --force",
+            project_path.display(), PROJECT_MARKER
+        );
+    }
+
+    let targets = clean_targets(project_path, all);
+    if targets.is_empty() {
+        println!("{} 没有需要清理的内容", "[i]".cyan());
+        return Ok(());
+    }
+    let manifest = move_to_trash(trash_dir, if all { "clean --all" } else { "clean" }, &targets)?;
+    for entry in &manifest.entries {
+        println!("  {} {}", "🧹".yellow(), entry.original.display());
+    }
+    println!("{} 已移入回收站，可使用 {} 恢复", "[+]".green().bold(), "rmm clean --undo".cyan());
+    Ok(())
+}
+
+/// 撤销最近一次清理
+pub fn undo_clean(trash_dir: &Path) -> Result<()> {
+    let manifest = restore_trash(trash_dir)?;
+    for entry in &manifest.entries {
+        println!("  {} {}", "↩".green(), entry.original.display());
+    }
+    println!("{} 已撤销 {}（{}）", "[+]".green().bold(), manifest.operation, manifest.created_at);
+    Ok(())
+}
+
+/// 对 .rmmp 中的一个旧版或未知条目的处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutChoice {
+    Migrate(&'static str),
+    Remove,
+    Skip,
+}
+
+/// 非交互时的默认处理：旧版条目按建议迁移或删除，未知条目保留
+pub fn default_choice(issue: &LayoutIssue) -> LayoutChoice {
+    match issue {
+        LayoutIssue::Legacy { action: LegacyAction::Rename(target), .. } => LayoutChoice::Migrate(target),
+        LayoutIssue::Legacy { action: LegacyAction::Remove, .. } => LayoutChoice::Remove,
+        LayoutIssue::Unknown { .. } => LayoutChoice::Skip,
+    }
+}
+
+/// 打印 .rmmp 中的旧版与未知条目
+pub fn print_layout_issues(issues: &[LayoutIssue]) {
+    for issue in issues {
+        match issue {
+            LayoutIssue::Legacy { name, action, reason } => {
+                let suggestion = match action {
+                    LegacyAction::Rename(target) => format!("建议迁移为 {}", target),
+                    LegacyAction::Remove => "建议删除".to_string(),
+                };
+                println!("  {} {}/{} — {}，{}", "[legacy]".yellow(), RMMP_DIR, name.cyan(), reason, suggestion);
+            }
+            LayoutIssue::Unknown { name } => {
+                println!("  {} {}/{} — 不属于 RMM 的目录约定", "[unknown]".bright_black(), RMMP_DIR, name.cyan());
+            }
+        }
+    }
+}
+
+/// 询问一个条目的处理方式，直接回车使用默认处理
+fn prompt_choice(issue: &LayoutIssue) -> Result<LayoutChoice> {
+    let default = default_choice(issue);
+    let options = match issue {
+        LayoutIssue::Legacy { action: LegacyAction::Rename(_), .. } => "[m]迁移 / [d]删除 / [s]跳过",
+        _ => "[d]删除 / [s]跳过",
+    };
+    let default_label = match default {
+        LayoutChoice::Migrate(_) => "m",
+        LayoutChoice::Remove => "d",
+        LayoutChoice::Skip => "s",
+    };
+    print!("  处理 {}/{}？{}（默认 {}）: ", RMMP_DIR, issue.name(), options, default_label);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    Ok(match line.trim().to_lowercase().as_str() {
+        "m" => match default {
+            LayoutChoice::Migrate(target) => LayoutChoice::Migrate(target),
+            _ => LayoutChoice::Skip,
+        },
+        "d" => LayoutChoice::Remove,
+        "s" => LayoutChoice::Skip,
+        _ => default,
+    })
+}
+
+/// 按选择迁移或清理 .rmmp 中的旧版/未知条目，删除的条目移入回收站
+pub fn apply_layout_choices(project_path: &Path, trash_dir: &Path, choices: &[(LayoutIssue, LayoutChoice)]) -> Result<()> {
+    let mut removed = Vec::new();
+    for (issue, choice) in choices {
+        match choice {
+            LayoutChoice::Migrate(target) => {
+                let destination = migrate_legacy(project_path, issue.name(), target)?;
+                println!("  {} {}/{} → {}", "↪".green(), RMMP_DIR, issue.name(), destination.display());
+            }
+            LayoutChoice::Remove => removed.push(rmmp_path(project_path, issue.name())),
+            LayoutChoice::Skip => {}
+        }
+    }
+    if !removed.is_empty() {
+        let manifest = move_to_trash(trash_dir, "clean --layout", &removed)?;
+        for entry in &manifest.entries {
+            println!("  {} {}", "🧹".yellow(), entry.original.display());
+        }
+        println!("{} 已移入回收站，可使用 {} 恢复", "[+]".green().bold(), "rmm clean --undo".cyan());
+    }
+    Ok(())
+}
+
+/// rmm clean --layout：检查 .rmmp 目录约定，逐项迁移或清理旧版与未知条目
+pub fn clean_layout(project_path: &Path, trash_dir: &Path, yes: bool) -> Result<()> {
+    let issues = inspect_rmmp(project_path)?;
+    for entry in RMMP_LAYOUT.iter().filter(|entry| rmmp_path(project_path, entry.name).exists()) {
+        if !issues.iter().any(|issue| issue.name() == entry.name) {
+            println!("  {} {}/{} {}", "✓".green(), RMMP_DIR, entry.name, entry.description.bright_black());
+        }
+    }
+    if issues.is_empty() {
+        println!("{} {} 符合目录约定", "[+]".green().bold(), RMMP_DIR);
+        return Ok(());
+    }
+    println!("{} {} 中有 {} 个不符合约定的条目:", "[!]".yellow().bold(), RMMP_DIR, issues.len());
+    print_layout_issues(&issues);
+    let choices = issues.into_iter()
+        .map(|issue| {
+            let choice = if yes { default_choice(&issue) } else { prompt_choice(&issue)? };
+            Ok((issue, choice))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    apply_layout_choices(project_path, trash_dir, &choices)
+}
+
+/// rmm clean 入口
+pub fn run_clean(project_path: &Path, all: bool, force: bool, undo: bool, layout: bool, yes: bool) -> Result<()> {
+    let trash_dir = RmmStorage::resolve().trash_dir();
+    if undo {
+        undo_clean(&trash_dir)
+    } else if layout {
+        clean_layout(project_path, &trash_dir, yes)
+    } else {
+        clean_project(project_path, &trash_dir, all, force)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clean_requires_marker_and_can_undo() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("demo");
+        let trash = temp_dir.path().join("trash");
+        fs::create_dir_all(project.join(".rmmp/build")).unwrap();
+        fs::create_dir_all(project.join(".rmmp/dist")).unwrap();
+        fs::write(project.join(".rmmp/Rmake.toml"), "").unwrap();
+        fs::write(project.join(".rmmp/dist/demo.zip"), "zip").unwrap();
+
+        let err = clean_project(&project, &trash, true, false).unwrap_err().to_string();
+        assert!(err.contains(PROJECT_MARKER));
+        assert!(project.join(".rmmp").exists());
+
+        fs::write(project.join(PROJECT_MARKER), "").unwrap();
+        clean_project(&project, &trash, false, false).unwrap();
+        assert!(!project.join(".rmmp/dist").exists());
+        assert!(project.join(".rmmp/Rmake.toml").exists());
+
+        undo_clean(&trash).unwrap();
+        assert_eq!(fs::read_to_string(project.join(".rmmp/dist/demo.zip")).unwrap(), "zip");
+        assert!(undo_clean(&trash).is_err());
+
+        // 未指定 --force 时拒绝清理根目录
+        assert!(clean_project(Path::new("/"), &trash, true, false).is_err());
+    }
+
+    #[test]
+    fn test_clean_layout_migrates_and_removes_legacy_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("demo");
+        let trash = temp_dir.path().join("trash");
+        fs::create_dir_all(project.join(".rmmp/tags")).unwrap();
+        fs::create_dir_all(project.join(".rmmp/dist")).unwrap();
+        fs::write(project.join(".rmmp/rmake.toml"), "[build]\n").unwrap();
+        fs::write(project.join(".rmmp/notes.txt"), "mine").unwrap();
+        fs::write(project.join(".rmmp/build.log"), "").unwrap();
+
+        let issues = inspect_rmmp(&project).unwrap();
+        assert_eq!(issues.iter().map(LayoutIssue::name).collect::<Vec<_>>(), vec!["notes.txt", "rmake.toml", "tags"]);
+        assert_eq!(default_choice(&issues[0]), LayoutChoice::Skip);
+        assert_eq!(default_choice(&issues[1]), LayoutChoice::Migrate("Rmake.toml"));
+        assert_eq!(default_choice(&issues[2]), LayoutChoice::Remove);
+
+        clean_layout(&project, &trash, true).unwrap();
+        assert_eq!(fs::read_to_string(project.join(".rmmp/Rmake.toml")).unwrap(), "[build]\n");
+        assert!(!project.join(".rmmp/tags").exists());
+        assert!(project.join(".rmmp/notes.txt").exists());
+        assert_eq!(inspect_rmmp(&project).unwrap(), vec![LayoutIssue::Unknown { name: "notes.txt".to_string() }]);
+
+        // 删除的条目可以恢复
+        undo_clean(&trash).unwrap();
+        assert!(project.join(".rmmp/tags").is_dir());
+
+        // 默认清理只涉及构建输出
+        assert_eq!(clean_targets(&project, false), vec![project.join(".rmmp/dist")]);
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::Result;
+use clap::{Arg, Command};
+use clap_complete::Shell;
+use std::io::Write;
+
+use crate::core::adb::Adb;
+use crate::core::settings::ConfigResolver;
+
+/// 动态补全的参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DynamicValue {
+    /// --serial：已连接的设备序列号
+    Serial,
+    /// MODULE_ID：设备上已安装的模块（按命令行中的 --serial 选择设备）
+    Module { serial: Option<String> },
+}
+
+/// 参数是否需要动态补全
+fn is_dynamic(arg: &Arg) -> bool {
+    matches!(arg.get_id().as_str(), "serial" | "module_id")
+}
+
+/// 包含动态补全参数的顶层命令，shell 脚本只在这些命令下调用 rmm complete
+fn dynamic_commands(cli: &Command) -> Vec<String> {
+    fn has_dynamic(cmd: &Command) -> bool {
+        cmd.get_arguments().any(is_dynamic) || cmd.get_subcommands().any(has_dynamic)
+    }
+    cli.get_subcommands()
+        .filter(|cmd| has_dynamic(cmd))
+        .map(|cmd| cmd.get_name().to_string())
+        .collect()
+}
+
+/// 按 clap 定义分析命令行，判断光标所在位置（words[index]）是否需要动态补全
+pub fn dynamic_value(cli: &Command, index: usize, words: &[String]) -> Option<DynamicValue> {
+    let mut cli = cli.clone();
+    cli.build();
+    let mut cmd = &cli;
+    let mut positionals = 0;
+    let mut serial = None;
+    // 正在等待取值的选项
+    let mut pending: Option<&Arg> = None;
+    for word in words.iter().take(index).skip(1) {
+        if let Some(arg) = pending.take() {
+            if arg.get_id() == "serial" {
+                serial = Some(word.clone());
+            }
+            continue;
+        }
+        if let Some(option) = word.strip_prefix('-').filter(|o| !o.is_empty()) {
+            let (name, inline_value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (option, None),
+            };
+            let arg = cmd.get_arguments().find(|arg| match name.strip_prefix('-') {
+                Some(long) => arg.get_long() == Some(long),
+                None => name.chars().count() == 1 && arg.get_short() == name.chars().next(),
+            });
+            let Some(arg) = arg.filter(|arg| arg.get_action().takes_values()) else {
+                continue;
+            };
+            match inline_value {
+                Some(value) if arg.get_id() == "serial" => serial = Some(value.to_string()),
+                Some(_) => {}
+                None => pending = Some(arg),
+            }
+            continue;
+        }
+        match cmd.find_subcommand(word) {
+            Some(sub) if positionals == 0 => cmd = sub,
+            _ => positionals += 1,
+        }
+    }
+
+    if let Some(arg) = pending {
+        return (arg.get_id() == "serial").then_some(DynamicValue::Serial);
+    }
+    let current = words.get(index).map(String::as_str).unwrap_or("");
+    if current.starts_with('-') {
+        return None;
+    }
+    let module_id = cmd.get_positionals().find(|arg| arg.get_id() == "module_id")?;
+    (module_id.get_index() == Some(positionals + 1)).then_some(DynamicValue::Module { serial })
+}
+
+/// 查询设备得到候选值；adb 不可用或设备无法访问时返回空列表，由 shell 回退到静态补全
+pub fn candidates(value: &DynamicValue, adb: &Adb) -> Vec<String> {
+    match value {
+        DynamicValue::Serial => adb.devices()
+            .map(|devices| devices.into_iter().filter(|(_, state)| state == "device").map(|(serial, _)| serial).collect())
+            .unwrap_or_default(),
+        DynamicValue::Module { serial } => {
+            let adb = match serial {
+                Some(serial) => adb.clone().with_serial(serial),
+                None => adb.clone(),
+            };
+            adb.installed_modules().unwrap_or_default()
+        }
+    }
+}
+
+/// rmm complete：打印光标位置的动态补全候选（每行一个），不需要动态补全时不输出
+pub fn complete(cli: &Command, index: usize, words: &[String]) -> Result<()> {
+    let Some(value) = dynamic_value(cli, index, words) else {
+        return Ok(());
+    };
+    let adb = Adb::from_config(&ConfigResolver::new(None));
+    let prefix = words.get(index).map(String::as_str).unwrap_or("");
+    let mut stdout = std::io::stdout().lock();
+    for candidate in candidates(&value, &adb).iter().filter(|c| c.starts_with(prefix)) {
+        writeln!(stdout, "{}", candidate)?;
+    }
+    Ok(())
+}
+
+/// 调用 rmm complete 的 shell 片段；powershell 与 elvish 只有静态补全
+fn dynamic_script(shell: Shell, commands: &[String]) -> Option<String> {
+    let script = match shell {
+        Shell::Bash => format!(r#"
+# 动态补全：--serial 补全已连接的设备，MODULE_ID 补全设备上已安装的模块；adb 不可用时回退到静态补全
+_rmm_dynamic() {{
+    local i
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "${{COMP_WORDS[i]}}" in
+            {pattern})
+                local IFS=$'\n'
+                local candidates=($(rmm complete "$COMP_CWORD" "${{COMP_WORDS[@]}}" 2>/dev/null))
+                if [[ ${{#candidates[@]}} -gt 0 ]]; then
+                    COMPREPLY=("${{candidates[@]}}")
+                    return 0
+                fi
+                break ;;
+        esac
+    done
+    _rmm "$@"
+}}
+complete -F _rmm_dynamic -o bashdefault -o default rmm
+"#, pattern = commands.join("|")),
+        Shell::Zsh => format!(r#"
+# 动态补全：--serial 补全已连接的设备，MODULE_ID 补全设备上已安装的模块；adb 不可用时回退到静态补全
+_rmm_dynamic() {{
+    if (( ${{words[(I)({pattern})]}} )); then
+        local -a candidates
+        candidates=("${{(@f)$(rmm complete $((CURRENT - 1)) "${{words[@]}}" 2>/dev/null)}}")
+        if [[ -n "${{candidates[1]}}" ]]; then
+            compadd -a candidates
+            return
+        fi
+    fi
+    _rmm "$@"
+}}
+compdef _rmm_dynamic rmm
+"#, pattern = commands.join("|")),
+        Shell::Fish => format!(r#"
+# 动态补全：--serial 补全已连接的设备，MODULE_ID 补全设备上已安装的模块
+function __rmm_dynamic
+    set -l words (commandline -opc) (commandline -ct)
+    rmm complete (math (count $words) - 1) $words 2>/dev/null
+end
+complete -c rmm -n '__fish_seen_subcommand_from {commands}' -a '(__rmm_dynamic)'
+"#, commands = commands.join(" ")),
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// rmm completions：生成 shell 补全脚本（静态补全 + 设备相关的动态补全）
+pub fn print_completions(shell: Shell, cli: &mut Command) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    clap_complete::generate(shell, cli, "rmm", &mut stdout);
+    if let Some(script) = dynamic_script(shell, &dynamic_commands(cli)) {
+        stdout.write_all(script.as_bytes())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::fake_device::FakeDevice;
+    use clap::CommandFactory;
+    use tempfile::TempDir;
+
+    fn words(line: &str) -> Vec<String> {
+        line.split(' ').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_dynamic_value_positions() {
+        let cli = crate::Cli::command();
+        let at_end = |line: &str| {
+            let words = words(line);
+            dynamic_value(&cli, words.len() - 1, &words)
+        };
+        assert_eq!(at_end("rmm device snapshot --serial "), Some(DynamicValue::Serial));
+        assert_eq!(at_end("rmm test -s emu"), Some(DynamicValue::Serial));
+        assert_eq!(at_end("rmm device rollback -s emulator-5554 -p . "), Some(DynamicValue::Module { serial: Some("emulator-5554".to_string()) }));
+        assert_eq!(at_end("rmm device flag --serial=abc disable "), Some(DynamicValue::Module { serial: Some("abc".to_string()) }));
+        // flag 的第一个位置参数是控制文件，不是模块
+        assert_eq!(at_end("rmm device flag "), None);
+        assert_eq!(at_end("rmm device action demo "), None);
+        assert_eq!(at_end("rmm device rollback --snapshot "), None);
+        // sync 的 -s 是搜索路径
+        assert_eq!(at_end("rmm sync -s "), None);
+        assert_eq!(dynamic_commands(&cli), ["device", "test"]);
+    }
+
+    #[test]
+    fn test_candidates_from_fake_device() {
+        let temp_dir = TempDir::new().unwrap();
+        let device = FakeDevice::open_or_create(temp_dir.path()).unwrap();
+        std::fs::create_dir_all(device.path("/data/adb/modules/demo")).unwrap();
+        std::fs::create_dir_all(device.path("/data/adb/modules/zygisk_lsposed")).unwrap();
+        let adb = device.adb().unwrap();
+
+        assert_eq!(candidates(&DynamicValue::Serial, &adb), ["fake-device"]);
+        assert_eq!(candidates(&DynamicValue::Module { serial: None }, &adb), ["demo", "zygisk_lsposed"]);
+
+        // 设备离线时没有候选，shell 回退到静态补全
+        device.update(|state| state.online = false).unwrap();
+        assert!(candidates(&DynamicValue::Module { serial: None }, &adb).is_empty());
+    }
+}
@@ -0,0 +1,588 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::cmds::Commands;
+use crate::core::hashing::random_token;
+use crate::core::process::{cancel, install_interrupt_handler, is_cancelled, reset_cancelled};
+use crate::core::rmm_core::RmmCore;
+use crate::core::storage::RmmStorage;
+
+/// RMM_ROOT 下记录常驻进程地址与令牌的文件
+const DAEMON_FILE: &str = "daemon.json";
+/// 常驻进程日志
+const LOG_FILE: &str = "daemon.log";
+/// 设置后不再委托给常驻进程
+const NO_DAEMON_ENV: &str = "RMM_NO_DAEMON";
+/// 命令输出结束后的状态行前缀（输出中不会出现 NUL）；常驻进程在状态行前补一个换行，
+/// 保证输出没有以换行结尾时状态行仍从行首开始，客户端转发时去掉这个换行
+const STATUS_MARKER: &str = "\0rmm-daemon:";
+/// 客户端收到 Ctrl-C 后发送的取消行
+const CANCEL_REQUEST: &str = "cancel";
+/// 连接令牌的随机字节数
+const TOKEN_BYTES: usize = 32;
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(200);
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// daemon.json 的内容
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DaemonInfo {
+    pub pid: u32,
+    pub port: u16,
+    pub token: String,
+    /// 常驻进程的 RMM 版本，与 CLI 不一致时不委托
+    pub version: String,
+    pub started_at: String,
+}
+
+/// 客户端请求（一行 JSON）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum DaemonRequest {
+    /// 在常驻进程中执行命令；args 含程序名
+    Run {
+        token: String,
+        cwd: PathBuf,
+        args: Vec<String>,
+        /// 客户端的全部环境变量（PATH、HOME、GITHUB_TOKEN 等都会影响命令结果）
+        env: BTreeMap<String, String>,
+        color: bool,
+    },
+    Status { token: String },
+    Stop { token: String },
+}
+
+impl DaemonRequest {
+    fn token(&self) -> &str {
+        match self {
+            Self::Run { token, .. } | Self::Status { token } | Self::Stop { token } => token,
+        }
+    }
+}
+
+/// 命令执行结果（输出之后的状态行）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunStatus {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// rmm daemon status 的回应
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub version: String,
+    pub started_at: String,
+    pub requests: u64,
+}
+
+fn daemon_file() -> PathBuf {
+    RmmStorage::resolve().root().join(DAEMON_FILE)
+}
+
+fn read_info() -> Option<DaemonInfo> {
+    serde_json::from_str(&fs::read_to_string(daemon_file()).ok()?).ok()
+}
+
+fn write_info(info: &DaemonInfo) -> Result<()> {
+    let path = daemon_file();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(info)?)?;
+    // 令牌仅当前用户可读
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+/// 发送请求并返回连接；常驻进程未运行时返回 None
+fn send(info: &DaemonInfo, request: &DaemonRequest) -> Option<TcpStream> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, info.port));
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).ok()?;
+    let mut line = serde_json::to_string(request).ok()?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).ok()?;
+    Some(stream)
+}
+
+/// 查询正在运行的常驻进程
+fn query_status(info: &DaemonInfo) -> Option<DaemonStatus> {
+    let stream = send(info, &DaemonRequest::Status { token: info.token.clone() })?;
+    stream.set_read_timeout(Some(START_TIMEOUT)).ok()?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).ok()?;
+    serde_json::from_str(&line).ok()
+}
+
+/// 适合交给常驻进程执行的命令：不读取标准输入、不长期占用进程
+pub fn is_delegable(cmd: &Commands) -> bool {
+    matches!(cmd,
+        Commands::Build { .. }
+        | Commands::Check { .. }
+        | Commands::Query { .. }
+        | Commands::Grep { .. }
+        | Commands::Size { .. }
+        | Commands::Inspect { .. }
+        | Commands::WhyExcluded { .. }
+        | Commands::Schema { .. }
+        | Commands::Cache { .. }
+        | Commands::Version
+    )
+}
+
+/// CLI 入口调用：常驻进程在运行时转交命令并转发输出；返回 None 表示应在本进程执行
+pub fn delegate(cmd: &Commands, args: &[String]) -> Option<std::result::Result<(), String>> {
+    if !cfg!(unix) || std::env::var_os(NO_DAEMON_ENV).is_some() || !is_delegable(cmd) {
+        return None;
+    }
+    let info = read_info().filter(|info| info.version == env!("CARGO_PKG_VERSION"))?;
+    let request = DaemonRequest::Run {
+        token: info.token.clone(),
+        cwd: std::env::current_dir().ok()?,
+        args: args.to_vec(),
+        env: client_env(),
+        color: colored::control::SHOULD_COLORIZE.should_colorize(),
+    };
+    // 第一次 Ctrl-C 只标记取消，由 forward_output 转发给常驻进程
+    install_interrupt_handler();
+    let stream = send(&info, &request)?;
+    Some(forward_output(stream, &mut std::io::stdout()))
+}
+
+/// 随请求发送的环境变量（非 UTF-8 的变量无法放入 JSON，跳过）
+fn client_env() -> BTreeMap<String, String> {
+    std::env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+        .collect()
+}
+
+/// 把常驻进程的输出原样写到 out，直到状态行；每行末尾的换行推迟到读到下一行时再写，
+/// 状态行前由常驻进程补上的换行因此不会出现在输出中。收到 Ctrl-C 时向常驻进程发送取消行，
+/// 命令清理后以失败状态结束
+fn forward_output(stream: TcpStream, out: &mut impl Write) -> std::result::Result<(), String> {
+    let _ = stream.set_read_timeout(Some(POLL_INTERVAL));
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+    let mut pending_newline = false;
+    let mut cancel_sent = false;
+    loop {
+        if !cancel_sent && is_cancelled() {
+            cancel_sent = true;
+            let _ = writeln!(writer, "{}", CANCEL_REQUEST);
+        }
+        // 超时时已读到的部分保留在 line 中，下次继续读取
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Err("常驻进程意外断开连接".to_string()),
+            Ok(_) => {}
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(_) => return Err("常驻进程意外断开连接".to_string()),
+        }
+        if let Some(status) = line.strip_prefix(STATUS_MARKER.as_bytes()) {
+            let status: RunStatus = serde_json::from_slice(status)
+                .map_err(|e| format!("无法解析常驻进程的回应: {}", e))?;
+            return match status.ok {
+                true => Ok(()),
+                false => Err(status.error.unwrap_or_default()),
+            };
+        }
+        if pending_newline {
+            let _ = out.write_all(b"\n");
+        }
+        pending_newline = line.ends_with(b"\n");
+        let _ = out.write_all(line.strip_suffix(b"\n").unwrap_or(&line));
+        let _ = out.flush();
+        line.clear();
+    }
+}
+
+/// rmm daemon start：默认在后台启动，--foreground 时在当前终端运行
+pub fn start(foreground: bool) -> Result<()> {
+    if !cfg!(unix) {
+        anyhow::bail!("rmm daemon 目前仅支持 Linux / macOS / Termux");
+    }
+    if let Some(info) = read_info()
+        && query_status(&info).is_some()
+    {
+        println!("{} 常驻进程已在运行 (pid {}, 端口 {})", "[i]".cyan(), info.pid, info.port);
+        return Ok(());
+    }
+    if foreground {
+        return serve_forever();
+    }
+
+    // 以相同的解释器与入口脚本重新启动自身
+    let mut argv = std::env::args();
+    let program = argv.next().context("无法确定 rmm 可执行文件")?;
+    let root = RmmStorage::resolve().root().to_path_buf();
+    fs::create_dir_all(&root)?;
+    let log_path = root.join(LOG_FILE);
+    let log = fs::OpenOptions::new().create(true).append(true).open(&log_path)?;
+    let mut command = std::process::Command::new(program);
+    command.args(argv.take_while(|arg| arg != "daemon"))
+        .args(["daemon", "start", "--foreground"])
+        .stdin(std::process::Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    #[cfg(unix)]
+    {
+        // 脱离当前终端的进程组，避免 Ctrl-C 影响常驻进程
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    command.spawn().context("启动常驻进程失败")?;
+
+    let deadline = Instant::now() + START_TIMEOUT;
+    while Instant::now() < deadline {
+        if let Some(info) = read_info()
+            && query_status(&info).is_some()
+        {
+            println!("{} 常驻进程已启动 (pid {}, 端口 {})", "[+]".green().bold(), info.pid, info.port);
+            println!("    {}", format!("日志: {}", log_path.display()).bright_black());
+            return Ok(());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    anyhow::bail!("常驻进程未能在 {} 秒内启动，请查看 {}", START_TIMEOUT.as_secs(), log_path.display())
+}
+
+/// rmm daemon stop
+pub fn stop() -> Result<()> {
+    let Some(info) = read_info() else {
+        println!("{} 常驻进程未运行", "[i]".cyan());
+        return Ok(());
+    };
+    match send(&info, &DaemonRequest::Stop { token: info.token.clone() }) {
+        Some(stream) => {
+            // 等待常驻进程关闭连接
+            let _ = stream.set_read_timeout(Some(START_TIMEOUT));
+            let _ = BufReader::new(stream).read_line(&mut String::new());
+            println!("{} 常驻进程已停止 (pid {})", "[-]".yellow().bold(), info.pid);
+        }
+        None => {
+            let _ = fs::remove_file(daemon_file());
+            println!("{} 常驻进程未运行，已清理残留的 {}", "[i]".cyan(), DAEMON_FILE);
+        }
+    }
+    Ok(())
+}
+
+/// rmm daemon status
+pub fn status(json: bool) -> Result<()> {
+    let status = read_info().and_then(|info| query_status(&info).map(|status| (info, status)));
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status.as_ref().map(|(_, status)| status))?);
+        return Ok(());
+    }
+    match status {
+        Some((info, status)) => {
+            println!("{} 常驻进程运行中", "[+]".green().bold());
+            println!("  pid: {}", status.pid);
+            println!("  端口: {}", info.port);
+            println!("  版本: {}", status.version);
+            println!("  启动时间: {}", status.started_at);
+            println!("  已处理请求: {}", status.requests);
+        }
+        None => println!("{} 常驻进程未运行", "[i]".cyan()),
+    }
+    Ok(())
+}
+
+/// 常驻进程主循环：逐个处理请求（命令会切换工作目录与输出，不能并发执行）
+fn serve_forever() -> Result<()> {
+    install_interrupt_handler();
+    RmmCore::enable_shared_caches();
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).context("无法监听本地端口")?;
+    listener.set_nonblocking(true)?;
+    let info = DaemonInfo {
+        pid: std::process::id(),
+        port: listener.local_addr()?.port(),
+        token: random_token(TOKEN_BYTES)?,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        started_at: chrono::Local::now().to_rfc3339(),
+    };
+    write_info(&info)?;
+    println!("{} rmm daemon 监听 127.0.0.1:{} (pid {})", "[+]".green().bold(), info.port, info.pid);
+
+    let mut requests = 0u64;
+    while !is_cancelled() {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let _ = stream.set_nonblocking(false);
+        match handle_connection(&info, stream, requests) {
+            Ok(Handled::Stop) => break,
+            Ok(Handled::Done) => requests += 1,
+            Err(e) => eprintln!("{} {}", "[!]".yellow(), e),
+        }
+    }
+
+    // 只删除属于自己的记录
+    if read_info().is_some_and(|current| current.token == info.token) {
+        let _ = fs::remove_file(daemon_file());
+    }
+    println!("{} rmm daemon 已退出，共处理 {} 个请求", "[-]".yellow().bold(), requests);
+    Ok(())
+}
+
+enum Handled {
+    Done,
+    Stop,
+}
+
+fn handle_connection(info: &DaemonInfo, stream: TcpStream, requests: u64) -> Result<Handled> {
+    stream.set_read_timeout(Some(START_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let request: DaemonRequest = serde_json::from_str(&line).context("无效的请求")?;
+    if request.token() != info.token {
+        anyhow::bail!("拒绝令牌不匹配的请求");
+    }
+    let mut stream = stream;
+    match request {
+        DaemonRequest::Status { .. } => {
+            let status = DaemonStatus {
+                pid: info.pid,
+                version: info.version.clone(),
+                started_at: info.started_at.clone(),
+                requests,
+            };
+            writeln!(stream, "{}", serde_json::to_string(&status)?)?;
+            Ok(Handled::Done)
+        }
+        DaemonRequest::Stop { .. } => Ok(Handled::Stop),
+        DaemonRequest::Run { cwd, args, env, color, .. } => {
+            let status = run_request(&stream, &cwd, args, &env, color);
+            write!(stream, "\n{}{}\n", STATUS_MARKER, serde_json::to_string(&status)?)?;
+            Ok(Handled::Done)
+        }
+    }
+}
+
+/// 在客户端的工作目录与环境变量下执行命令，输出直接写入连接
+fn run_request(stream: &TcpStream, cwd: &std::path::Path, args: Vec<String>, env: &BTreeMap<String, String>, color: bool) -> RunStatus {
+    let saved_dir = std::env::current_dir().ok();
+    let saved_env: Vec<_> = std::env::vars_os().collect();
+    if let Err(e) = std::env::set_current_dir(cwd) {
+        return RunStatus { ok: false, error: Some(format!("无法进入目录 {}: {}", cwd.display(), e)) };
+    }
+    // SAFETY: 请求逐个处理，环境只在命令开始前与结束后修改；此时仍存在的其他线程
+    // （rayon 线程池、取消监听线程）处于空闲或阻塞在套接字上，不会读取环境变量
+    unsafe { replace_env(env) };
+    colored::control::set_override(color);
+    let watcher = watch_cancel(stream);
+
+    let result = redirect_output(stream, || crate::run_args(args));
+
+    let cancelled = watcher.map(|watcher| watcher.finish(stream)).unwrap_or(false);
+    colored::control::unset_override();
+    unsafe { replace_env(saved_env) };
+    if let Some(dir) = saved_dir {
+        let _ = std::env::set_current_dir(dir);
+    }
+    match result {
+        Ok(Ok(())) => RunStatus { ok: true, error: None },
+        Ok(Err(e)) => RunStatus { ok: false, error: Some(if cancelled { format!("{}（客户端已取消）", e) } else { e }) },
+        Err(e) => RunStatus { ok: false, error: Some(e.to_string()) },
+    }
+}
+
+/// 用给定的变量替换当前进程的全部环境变量
+unsafe fn replace_env<K: AsRef<OsStr>, V: AsRef<OsStr>>(env: impl IntoIterator<Item = (K, V)>) {
+    for (key, _) in std::env::vars_os() {
+        unsafe { std::env::remove_var(key) };
+    }
+    for (key, value) in env {
+        unsafe { std::env::set_var(key, value) };
+    }
+}
+
+/// 命令执行期间监听客户端的取消行
+struct CancelWatcher {
+    done: Arc<AtomicBool>,
+    handle: JoinHandle<bool>,
+}
+
+/// 收到取消行或客户端断开时按 Ctrl-C 标记取消，命令清理后退出
+fn watch_cancel(stream: &TcpStream) -> Option<CancelWatcher> {
+    let reader = stream.try_clone().ok()?;
+    reader.set_read_timeout(None).ok()?;
+    let done = Arc::new(AtomicBool::new(false));
+    let finished = Arc::clone(&done);
+    let handle = std::thread::spawn(move || {
+        let mut line = String::new();
+        let read = BufReader::new(reader).read_line(&mut line);
+        let requested = match read {
+            Ok(0) | Err(_) => !finished.load(Ordering::SeqCst),
+            Ok(_) => line.trim_end() == CANCEL_REQUEST,
+        };
+        if requested {
+            cancel();
+        }
+        requested
+    });
+    Some(CancelWatcher { done, handle })
+}
+
+impl CancelWatcher {
+    /// 停止监听并返回客户端是否取消了命令；取消标记随之清除，常驻进程继续处理后续请求
+    fn finish(self, stream: &TcpStream) -> bool {
+        self.done.store(true, Ordering::SeqCst);
+        let _ = stream.shutdown(Shutdown::Read);
+        let cancelled = self.handle.join().unwrap_or(false);
+        if cancelled {
+            reset_cancelled();
+        }
+        cancelled
+    }
+}
+
+/// 执行期间把标准输出与标准错误（含子进程）重定向到连接
+#[cfg(unix)]
+fn redirect_output<T>(stream: &TcpStream, f: impl FnOnce() -> T) -> Result<T> {
+    use std::os::fd::AsRawFd;
+
+    std::io::stdout().flush()?;
+    // SAFETY: 只操作标准输出/错误的文件描述符，执行结束后恢复
+    let saved = unsafe { (libc::dup(1), libc::dup(2)) };
+    if saved.0 < 0 || saved.1 < 0 {
+        anyhow::bail!("无法保存标准输出: {}", std::io::Error::last_os_error());
+    }
+    unsafe {
+        libc::dup2(stream.as_raw_fd(), 1);
+        libc::dup2(stream.as_raw_fd(), 2);
+    }
+    let result = f();
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+    unsafe {
+        libc::dup2(saved.0, 1);
+        libc::dup2(saved.1, 2);
+        libc::close(saved.0);
+        libc::close(saved.1);
+    }
+    Ok(result)
+}
+
+#[cfg(not(unix))]
+fn redirect_output<T>(_stream: &TcpStream, _f: impl FnOnce() -> T) -> Result<T> {
+    anyhow::bail!("rmm daemon 目前仅支持 Linux / macOS / Termux")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_roundtrip_and_status_line() {
+        let request = DaemonRequest::Run {
+            token: "abc".to_string(),
+            cwd: PathBuf::from("/tmp/demo"),
+            args: vec!["rmm".to_string(), "check".to_string()],
+            env: BTreeMap::from([("RMM_LANG".to_string(), "en".to_string())]),
+            color: false,
+        };
+        let line = serde_json::to_string(&request).unwrap();
+        assert!(line.contains(r#""action":"run""#));
+        assert!(!line.contains('\n'));
+        let parsed: DaemonRequest = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed, request);
+        assert_eq!(parsed.token(), "abc");
+
+        let stop: DaemonRequest = serde_json::from_str(r#"{"action":"stop","token":"t"}"#).unwrap();
+        assert_eq!(stop, DaemonRequest::Stop { token: "t".to_string() });
+
+        // 令牌每次不同，为 32 个随机字节的十六进制
+        let (a, b) = (random_token(TOKEN_BYTES).unwrap(), random_token(TOKEN_BYTES).unwrap());
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, b);
+
+        // 客户端按状态行结束转发
+        let (result, output) = forward_reply(b"line one\n", r#"{"ok":false,"error":"boom"}"#);
+        assert_eq!(result, Err("boom".to_string()));
+        assert_eq!(output, b"line one\n");
+    }
+
+    /// 模拟常驻进程：写出命令输出与状态行，返回客户端的结果与转发的输出
+    fn forward_reply(output: &'static [u8], status: &'static str) -> (std::result::Result<(), String>, Vec<u8>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(output).unwrap();
+            write!(stream, "\n{}{}\n", STATUS_MARKER, status).unwrap();
+        });
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let mut forwarded = Vec::new();
+        let result = forward_output(stream, &mut forwarded);
+        server.join().unwrap();
+        (result, forwarded)
+    }
+
+    #[test]
+    fn test_request_carries_client_environment() {
+        let env = client_env();
+        for key in ["PATH", "HOME"] {
+            assert_eq!(env.get(key).cloned(), std::env::var(key).ok(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_cancel_watcher() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // 客户端发送取消行
+        let mut client = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let watcher = watch_cancel(&server).unwrap();
+        writeln!(client, "{}", CANCEL_REQUEST).unwrap();
+        while !watcher.handle.is_finished() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(watcher.finish(&server));
+
+        // 命令正常结束：停止监听，不算取消，仍可写出状态行
+        let client = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        let watcher = watch_cancel(&server).unwrap();
+        assert!(!watcher.finish(&server));
+        write!(server, "\n{}{{\"ok\":true}}\n", STATUS_MARKER).unwrap();
+        assert_eq!(forward_output(client, &mut Vec::new()), Ok(()));
+    }
+
+    #[test]
+    fn test_status_line_after_output_without_newline() {
+        let (result, output) = forward_reply(b"progress 50%\rprogress 100%", r#"{"ok":true}"#);
+        assert_eq!(result, Ok(()));
+        assert_eq!(output, b"progress 50%\rprogress 100%");
+
+        let (result, output) = forward_reply(b"", r#"{"ok":true}"#);
+        assert_eq!(result, Ok(()));
+        assert!(output.is_empty());
+        let (_, output) = forward_reply(b"a\n\nb\n", r#"{"ok":true}"#);
+        assert_eq!(output, b"a\n\nb\n");
+    }
+}
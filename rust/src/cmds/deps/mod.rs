@@ -0,0 +1,139 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::core::deps::{DepKind, DepNode, DepResolver, DepStatus, DepTree};
+use crate::core::rmm_core::RmmCore;
+
+/// 解析项目的依赖图（registry 取自 meta.toml）
+pub fn resolve_tree(project_path: &Path) -> Result<DepTree> {
+    let registry = RmmCore::new().get_meta_config()
+        .map(|meta| meta.projects)
+        .unwrap_or_default();
+    DepResolver::new(registry).resolve(project_path)
+}
+
+/// 单个节点的显示文本
+fn node_label(node: &DepNode, conflicted: bool) -> String {
+    let mut label = node.id.bold().to_string();
+    if node.kind == DepKind::Script {
+        label.push_str(&format!(" {}", "[script]".magenta()));
+    }
+    label.push_str(&format!(" {}", node.version.as_deref().unwrap_or("?").green()));
+    if let Some(ref constraint) = node.constraint {
+        label.push_str(&format!(" {}", format!("(要求 {})", constraint).bright_black()));
+    }
+    let source = match node.path {
+        Some(ref path) => format!("{} → {}", node.source.describe(), path.display()),
+        None => node.source.describe(),
+    };
+    label.push_str(&format!(" {}", format!("[{}]", source).cyan()));
+    match node.status {
+        DepStatus::Ok => {}
+        DepStatus::Missing(ref reason) => label.push_str(&format!(" {}", format!("❌ 缺失: {}", reason).red())),
+        DepStatus::Unsatisfied(ref reason) => label.push_str(&format!(" {}", format!("❌ {}", reason).red())),
+        DepStatus::Cycle => label.push_str(&format!(" {}", "↻ 循环依赖".yellow())),
+    }
+    if conflicted {
+        label.push_str(&format!(" {}", "⚠️ 冲突".yellow()));
+    }
+    label
+}
+
+fn print_children(node: &DepNode, prefix: &str, tree: &DepTree) {
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i + 1 == node.children.len();
+        let conflicted = tree.conflicts.iter().any(|c| c.id == child.id);
+        println!("{}{}{}", prefix, if last { "└── " } else { "├── " }, node_label(child, conflicted));
+        print_children(child, &format!("{}{}", prefix, if last { "    " } else { "│   " }), tree);
+    }
+}
+
+/// rmm deps tree：打印依赖图，存在缺失、版本不满足或冲突时返回错误
+pub fn show_tree(project_path: &Path, json: bool) -> Result<()> {
+    let tree = resolve_tree(project_path)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tree)?);
+    } else {
+        println!("{}", node_label(&tree.root, false));
+        if tree.root.children.is_empty() {
+            println!("{} 项目没有声明依赖", "[i]".cyan());
+        }
+        print_children(&tree.root, "", &tree);
+        for conflict in &tree.conflicts {
+            println!("{} {} 被解析为多个版本: {}", "⚠️ ".yellow().bold(), conflict.id.bold(), conflict.variants.join(", "));
+        }
+    }
+
+    let problems = tree.problems().len();
+    if problems > 0 || !tree.conflicts.is_empty() {
+        anyhow::bail!("依赖存在问题：{} 个缺失或版本不满足，{} 个冲突", problems, tree.conflicts.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::deps::*;
+    use std::collections::HashMap;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_module(dir: &std::path::Path, id: &str, version: &str, deps: &[&str], scripts: &[&str]) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("module.prop"), format!("id={}\nversion={}\n", id, version)).unwrap();
+        let list = |items: &[&str]| items.iter().map(|d| format!("{:?}", d)).collect::<Vec<_>>().join(", ");
+        fs::write(dir.join("rmmproject.toml"), format!(
+            "authors = []\n\n[project]\nid = \"{id}\"\ndescription = \"\"\nreadme = \"\"\nchangelog = \"\"\nlicense = \"\"\n\
+             dependencies = [{}]\nscript_dependencies = [{}]\n",
+            list(deps), list(scripts)
+        )).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_dependency_tree() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write_module(&root.join("app"), "app", "v1.0", &["libfoo>=1.2", "tools @ ../tools", "ghost"], &["busybox==1.36 @ https://example.com/busybox"]);
+        write_module(&root.join("libfoo"), "libfoo", "v1.3", &["app"], &[]);
+        write_module(&root.join("tools"), "tools", "v2.0", &[], &["busybox==1.31 @ bin/busybox"]);
+        fs::create_dir_all(root.join("tools/bin")).unwrap();
+        fs::write(root.join("tools/bin/busybox"), "").unwrap();
+
+        let registry = HashMap::from([
+            ("libfoo".to_string(), root.join("libfoo").display().to_string()),
+            ("app".to_string(), root.join("app").display().to_string()),
+        ]);
+        let tree = DepResolver::new(registry).resolve(&root.join("app")).unwrap();
+        assert_eq!(tree.root.id, "app");
+        let ids: Vec<&str> = tree.root.children.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["libfoo", "tools", "ghost", "busybox"]);
+
+        let libfoo = &tree.root.children[0];
+        assert_eq!(libfoo.version.as_deref(), Some("v1.3"));
+        assert_eq!(libfoo.status, DepStatus::Ok);
+        assert_eq!(libfoo.children[0].status, DepStatus::Cycle);
+        assert!(matches!(tree.root.children[2].status, DepStatus::Missing(_)));
+        assert_eq!(tree.root.children[3].version.as_deref(), Some("1.36"));
+
+        assert_eq!(tree.problems().len(), 1);
+        assert_eq!(tree.conflicts.len(), 1);
+        assert_eq!(tree.conflicts[0].id, "busybox");
+    }
+
+    #[test]
+    fn test_dep_spec_and_versions() {
+        let spec = parse_dep_spec("busybox >= 1.36 @ https://example.com/bb", DepKind::Script).unwrap();
+        assert_eq!(spec.id, "busybox");
+        assert_eq!(spec.constraint.as_deref(), Some(">=1.36"));
+        assert_eq!(spec.source, DepSource::Url("https://example.com/bb".to_string()));
+        assert_eq!(parse_dep_spec("lib @ path:../lib", DepKind::Module).unwrap().source, DepSource::Path("../lib".to_string()));
+        assert!(parse_dep_spec("bad id", DepKind::Module).is_err());
+        assert!(parse_dep_spec("lib>=", DepKind::Module).is_err());
+
+        assert!(satisfies("v1.10", ">=1.9"));
+        assert!(!satisfies("1.2", ">1.2.0"));
+        assert!(satisfies("1.2", "==1.2.0"));
+        assert!(satisfies("2", "!=1"));
+    }
+}
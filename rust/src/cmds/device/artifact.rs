@@ -0,0 +1,106 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Android ABI 及构建产物文件名中常见的别名
+const ABI_ALIASES: [(&str, &[&str]); 4] = [
+    ("arm64-v8a", &["arm64", "aarch64"]),
+    ("armeabi-v7a", &["armeabi", "armv7", "armv7a", "arm"]),
+    ("x86_64", &["x86_64", "x64", "amd64"]),
+    ("x86", &["x86", "i686"]),
+];
+
+/// 列出 .rmmp/dist 中模块的安装包（不含符号包与卡刷包），按修改时间从新到旧排序
+pub fn list_artifacts(project_path: &Path, module_id: &str) -> Vec<PathBuf> {
+    let prefix = format!("{}-", module_id);
+    let mut artifacts: Vec<PathBuf> = fs::read_dir(project_path.join(".rmmp/dist"))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension().is_some_and(|ext| ext == "zip")
+                && p.file_name().is_some_and(|n| {
+                    let name = n.to_string_lossy();
+                    name.starts_with(&prefix) && !name.ends_with("-symbols.zip") && !name.ends_with("-recovery.zip")
+                })
+        })
+        .collect();
+    artifacts.sort_by_key(|p| std::cmp::Reverse(fs::metadata(p).and_then(|m| m.modified()).ok()));
+    artifacts
+}
+
+/// 从产物文件名识别 ABI（如 demo-100-arm64-v8a.zip → arm64-v8a），未标注架构时返回 None
+pub fn artifact_abi(file_name: &str) -> Option<&'static str> {
+    let stem = file_name.strip_suffix(".zip").unwrap_or(file_name).to_lowercase();
+    let tokens: Vec<&str> = stem.split(['-', '.']).collect();
+    ABI_ALIASES.iter()
+        .find(|(_, aliases)| tokens.iter().any(|t| aliases.contains(t)))
+        .map(|(abi, _)| *abi)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// 按设备支持的 ABI（优先级从高到低）选择产物；
+/// 没有匹配架构的产物时使用未标注架构的通用包，否则报错并列出可用产物
+pub fn select_artifact(artifacts: &[PathBuf], device_abis: &[String]) -> Result<PathBuf> {
+    if artifacts.is_empty() {
+        anyhow::bail!("未找到构建产物，请先运行 rmm build");
+    }
+    let abi_of = |path: &PathBuf| artifact_abi(&file_name(path));
+
+    let matched = device_abis.iter()
+        .find_map(|abi| artifacts.iter().find(|p| abi_of(p) == Some(abi.as_str())))
+        .or_else(|| artifacts.iter().find(|p| abi_of(p).is_none()));
+    if let Some(path) = matched {
+        return Ok(path.clone());
+    }
+
+    let available = artifacts.iter()
+        .map(|p| format!("  {} ({})", file_name(p), abi_of(p).unwrap_or("通用")))
+        .collect::<Vec<_>>()
+        .join("\n");
+    anyhow::bail!(
+        "没有适用于设备 ABI [{}] 的构建产物，可用产物:\n{}\n可直接指定要安装的 zip 文件",
+        device_abis.join(", "),
+        available
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_select_artifact_by_abi() {
+        assert_eq!(artifact_abi("demo-100-arm64-v8a.zip"), Some("arm64-v8a"));
+        assert_eq!(artifact_abi("demo-100-armeabi-v7a.zip"), Some("armeabi-v7a"));
+        assert_eq!(artifact_abi("demo-100-x86_64.zip"), Some("x86_64"));
+        assert_eq!(artifact_abi("demo-100-x86.zip"), Some("x86"));
+        assert_eq!(artifact_abi("demo-100.zip"), None);
+
+        let temp_dir = TempDir::new().unwrap();
+        let dist_dir = temp_dir.path().join(".rmmp/dist");
+        fs::create_dir_all(&dist_dir).unwrap();
+        for name in ["demo-100-arm64-v8a.zip", "demo-100-x86_64.zip", "demo-100-symbols.zip", "demo-100-recovery.zip", "other-100.zip"] {
+            fs::write(dist_dir.join(name), "zip").unwrap();
+        }
+        let artifacts = list_artifacts(temp_dir.path(), "demo");
+        assert_eq!(artifacts.len(), 2);
+
+        let abis = vec!["arm64-v8a".to_string(), "armeabi-v7a".to_string()];
+        let selected = select_artifact(&artifacts, &abis).unwrap();
+        assert_eq!(file_name(&selected), "demo-100-arm64-v8a.zip");
+
+        let err = select_artifact(&artifacts, &["armeabi-v7a".to_string()]).unwrap_err().to_string();
+        assert!(err.contains("demo-100-x86_64.zip (x86_64)"));
+
+        fs::write(dist_dir.join("demo-100.zip"), "zip").unwrap();
+        let artifacts = list_artifacts(temp_dir.path(), "demo");
+        let selected = select_artifact(&artifacts, &["armeabi-v7a".to_string()]).unwrap();
+        assert_eq!(file_name(&selected), "demo-100.zip");
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::artifact::{list_artifacts, select_artifact};
+use super::{confirm, install_zip};
+use crate::cmds::deps::resolve_tree;
+use crate::core::adb::{Adb, MODULES_DIR, MODULES_UPDATE_DIR};
+use crate::core::deps::{read_project, satisfies, DepKind, DepNode, DepSource, DepStatus, DepTree};
+use crate::core::lockfile::download;
+use crate::core::storage::RmmStorage;
+use crate::core::zip_inspect::parse_module_prop;
+
+/// 设备上已安装的模块
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceModule {
+    pub version: Option<String>,
+    /// 位于 modules_update，重启后生效
+    pub pending: bool,
+}
+
+/// 依赖的安装来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencySource {
+    /// 本地项目（使用其 .rmmp/dist 中的产物）或 zip 文件
+    Local(PathBuf),
+    /// update.json 地址（直接指向 .zip 时下载该文件）
+    Remote(String),
+}
+
+/// 依赖在设备上的状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencyState {
+    /// 设备上的版本满足要求
+    Satisfied(String),
+    /// 需要安装；source 为 None 时无法自动安装
+    Needed { reason: String, source: Option<DependencySource> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyStep {
+    pub id: String,
+    pub constraint: Option<String>,
+    pub state: DependencyState,
+}
+
+/// 读取设备上所有模块的 module.prop；modules_update 中待生效的版本优先
+pub fn device_modules(adb: &Adb) -> Result<BTreeMap<String, DeviceModule>> {
+    let output = adb.su(&format!(
+        "for f in {}/*/module.prop {}/*/module.prop; do [ -f \"$f\" ] && echo \"==> $f\" && cat \"$f\"; done; true",
+        MODULES_DIR, MODULES_UPDATE_DIR
+    ))?;
+    Ok(parse_device_modules(&output))
+}
+
+pub(crate) fn parse_device_modules(output: &str) -> BTreeMap<String, DeviceModule> {
+    let mut modules: BTreeMap<String, DeviceModule> = BTreeMap::new();
+    for section in output.split("==> ").skip(1) {
+        let (path, content) = section.split_once('\n').unwrap_or((section, ""));
+        let Some(dir) = path.trim().strip_suffix("/module.prop") else { continue };
+        let pending = dir.rsplit('/').nth(1) == Some("modules_update");
+        let prop = parse_module_prop(content);
+        let Some(id) = prop.get("id").cloned().or_else(|| dir.rsplit('/').next().map(str::to_string)).filter(|id| !id.is_empty()) else {
+            continue;
+        };
+        if !pending && modules.get(&id).is_some_and(|module| module.pending) {
+            continue;
+        }
+        modules.insert(id, DeviceModule { version: prop.get("version").cloned(), pending });
+    }
+    modules
+}
+
+/// 按安装顺序（被依赖的模块在前）列出项目的模块依赖及其在设备上的状态
+pub fn plan_device_dependencies(tree: &DepTree, installed: &BTreeMap<String, DeviceModule>) -> Vec<DependencyStep> {
+    fn visit(node: &DepNode, root_id: &str, installed: &BTreeMap<String, DeviceModule>, steps: &mut Vec<DependencyStep>) {
+        for child in node.children.iter().filter(|child| child.kind == DepKind::Module) {
+            visit(child, root_id, installed, steps);
+            if child.id != root_id && !steps.iter().any(|step| step.id == child.id) {
+                steps.push(dependency_step(child, installed.get(&child.id)));
+            }
+        }
+    }
+    let mut steps = Vec::new();
+    visit(&tree.root, &tree.root.id, installed, &mut steps);
+    steps
+}
+
+fn dependency_step(node: &DepNode, device: Option<&DeviceModule>) -> DependencyStep {
+    let constraint = node.constraint.clone();
+    let satisfied = device
+        .map(|module| module.version.clone().unwrap_or_default())
+        .filter(|version| constraint.as_deref().is_none_or(|c| satisfies(version, c)));
+    let state = match satisfied {
+        Some(version) => DependencyState::Satisfied(version),
+        None => {
+            let mut reason = match device {
+                Some(module) => format!("设备上的版本 {} 不满足 {}", module.version.as_deref().unwrap_or("?"), constraint.as_deref().unwrap_or_default()),
+                None => "设备上未安装".to_string(),
+            };
+            let source = match (&node.status, &node.source, &node.path) {
+                (DepStatus::Missing(why) | DepStatus::Unsatisfied(why), _, _) => {
+                    reason.push_str(&format!("，本地依赖{}", why));
+                    None
+                }
+                (_, DepSource::Url(url), _) => Some(DependencySource::Remote(url.clone())),
+                (_, _, Some(path)) => Some(DependencySource::Local(path.clone())),
+                _ => None,
+            };
+            DependencyState::Needed { reason, source }
+        }
+    };
+    DependencyStep { id: node.id.clone(), constraint, state }
+}
+
+/// 安装模块前检查其 project.dependencies：设备上缺失或版本不满足的依赖按顺序先安装，assume_yes 时不询问
+pub fn install_dependencies(adb: &Adb, project_path: &Path, assume_yes: bool) -> Result<()> {
+    let declared = read_project(project_path)?.is_some_and(|project| !project.project.dependencies.is_empty());
+    if !declared {
+        return Ok(());
+    }
+    let steps = plan_device_dependencies(&resolve_tree(project_path)?, &device_modules(adb)?);
+    let mut needed = Vec::new();
+    for step in &steps {
+        match &step.state {
+            DependencyState::Satisfied(version) => println!("{} 依赖 {} 已安装（{}）", "[+]".green().bold(), step.id.bright_white(), version),
+            DependencyState::Needed { reason, source } => {
+                let from = match source {
+                    Some(DependencySource::Local(path)) => path.display().to_string(),
+                    Some(DependencySource::Remote(url)) => url.clone(),
+                    None => "无法自动安装".to_string(),
+                };
+                println!("{} 依赖 {}: {}（{}）", "[!]".yellow(), step.id.bright_white(), reason, from.bright_black());
+                needed.push(step);
+            }
+        }
+    }
+    if needed.is_empty() {
+        return Ok(());
+    }
+    let unavailable: Vec<&str> = needed.iter()
+        .filter(|step| matches!(step.state, DependencyState::Needed { source: None, .. }))
+        .map(|step| step.id.as_str())
+        .collect();
+    if !unavailable.is_empty() {
+        anyhow::bail!("缺少依赖 {}，且无法自动安装：请先手动安装，或在 rmmproject.toml 中为其指定项目路径或 update.json 地址（--no-deps 跳过检查）", unavailable.join(", "));
+    }
+    if !assume_yes && !confirm(&format!("先安装以上 {} 个依赖？", needed.len()))? {
+        anyhow::bail!("未安装依赖 {}，模块运行时可能失败（--no-deps 跳过检查）", needed.iter().map(|step| step.id.as_str()).collect::<Vec<_>>().join(", "));
+    }
+
+    let manager = adb.detect_manager()?;
+    let abis = adb.abis()?;
+    for step in needed {
+        let DependencyState::Needed { source: Some(source), .. } = &step.state else { continue };
+        let zip = obtain_zip(&step.id, source, step.constraint.as_deref(), &abis)?;
+        install_zip(adb, manager, &zip).with_context(|| format!("安装依赖 {} 失败", step.id))?;
+        println!("{} 已安装依赖 {}（{}）", "[+]".green().bold(), step.id.bright_white(), zip.file_name().unwrap_or_default().to_string_lossy());
+    }
+    Ok(())
+}
+
+/// 取得依赖的 zip：本地项目按设备 ABI 选择产物，远程来源读取 update.json 后下载（经过 network.mirrors）
+fn obtain_zip(id: &str, source: &DependencySource, constraint: Option<&str>, abis: &[String]) -> Result<PathBuf> {
+    let url = match source {
+        DependencySource::Local(path) if path.is_file() => return Ok(path.clone()),
+        DependencySource::Local(path) => {
+            return select_artifact(&list_artifacts(path, id), abis)
+                .with_context(|| format!("依赖 {} 没有构建产物，请先在 {} 中运行 rmm build", id, path.display()));
+        }
+        DependencySource::Remote(url) => url,
+    };
+    let (zip_url, version) = if url.ends_with(".zip") {
+        (url.clone(), None)
+    } else {
+        let manifest: serde_json::Value = serde_json::from_slice(&download(url, None)?)
+            .with_context(|| format!("依赖 {} 的 update.json 无法解析: {}", id, url))?;
+        let zip_url = manifest["zipUrl"].as_str()
+            .ok_or_else(|| anyhow::anyhow!("依赖 {} 的 update.json 缺少 zipUrl: {}", id, url))?;
+        let version = manifest["version"].as_str().map(str::to_string);
+        if let (Some(version), Some(constraint)) = (&version, constraint)
+            && !satisfies(version, constraint)
+        {
+            anyhow::bail!("依赖 {} 的 update.json 提供的版本 {} 不满足 {}", id, version, constraint);
+        }
+        (zip_url.to_string(), version)
+    };
+    println!("{} 下载依赖 {} {}", "[*]".cyan(), id, version.as_deref().unwrap_or_default());
+    let bytes = download(&zip_url, None)?;
+    let dir = RmmStorage::resolve().cache_dir().join("modules");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}-{}.zip", id, version.as_deref().unwrap_or("latest")));
+    fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_modules() {
+        let output = "==> /data/adb/modules/core_lib/module.prop\nid=core_lib\nversion=v1.0\n\
+            ==> /tmp/x/data/adb/modules/noid/module.prop\nversion=2\n\
+            ==> /data/adb/modules_update/core_lib/module.prop\nid=core_lib\nversion=v1.2\n";
+        let modules = parse_device_modules(output);
+        assert_eq!(modules["core_lib"], DeviceModule { version: Some("v1.2".to_string()), pending: true });
+        assert_eq!(modules["noid"].version.as_deref(), Some("2"));
+        assert!(!modules["noid"].pending);
+    }
+
+    #[test]
+    fn test_plan_device_dependencies() {
+        use crate::core::deps::DepResolver;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for (dir, prop, deps) in [
+            ("app", "id=app\nversion=v1\n", r#"["core_lib>=1.2 @ ../core", "ui @ https://example.com/ui/update.json", "ghost"]"#),
+            ("core", "id=core_lib\nversion=v1.3\n", r#"["base @ ../base"]"#),
+            ("base", "id=base\nversion=v2\n", "[]"),
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+            fs::write(root.join(dir).join("module.prop"), prop).unwrap();
+            fs::write(root.join(dir).join("rmmproject.toml"), format!(
+                "authors = []\n\n[project]\nid = \"{}\"\ndescription = \"\"\nreadme = \"\"\nchangelog = \"\"\nlicense = \"\"\ndependencies = {}\n",
+                dir, deps
+            )).unwrap();
+        }
+        let tree = DepResolver::new(Default::default()).resolve(&root.join("app")).unwrap();
+        let installed = BTreeMap::from([
+            ("base".to_string(), DeviceModule { version: Some("v2".to_string()), pending: false }),
+            ("core_lib".to_string(), DeviceModule { version: Some("v1.1".to_string()), pending: false }),
+        ]);
+        let steps = plan_device_dependencies(&tree, &installed);
+
+        // 被依赖的模块在前
+        let ids: Vec<&str> = steps.iter().map(|step| step.id.as_str()).collect();
+        assert_eq!(ids, ["base", "core_lib", "ui", "ghost"]);
+        assert_eq!(steps[0].state, DependencyState::Satisfied("v2".to_string()));
+        assert!(matches!(&steps[1].state, DependencyState::Needed { reason, source: Some(DependencySource::Local(path)) }
+            if reason.contains("v1.1") && path.ends_with("core")));
+        assert!(matches!(&steps[2].state, DependencyState::Needed { source: Some(DependencySource::Remote(url)), .. } if url.ends_with("update.json")));
+        assert!(matches!(&steps[3].state, DependencyState::Needed { source: None, .. }));
+    }
+}
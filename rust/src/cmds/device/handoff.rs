@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::artifact::{list_artifacts, select_artifact};
+use super::read_module_id;
+use crate::cmds::serve::{content_type, lan_address, parse_request_line};
+use crate::core::hashing::random_token;
+use crate::core::process::is_cancelled;
+
+/// 通过局域网 HTTP 交给设备下载的模块 zip
+struct Handoff {
+    zip_path: PathBuf,
+    file_name: String,
+    /// 随机路径前缀，避免局域网内其他设备猜到下载地址
+    token: String,
+}
+
+/// 路径令牌的随机字节数（二维码中的地址不宜过长）
+const TOKEN_BYTES: usize = 16;
+
+/// URL 路径中的文件名编码（只保留不需要转义的字符）
+fn encode_path_segment(name: &str) -> String {
+    name.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// 将文本渲染为终端中的二维码（深色背景下同样可扫）
+pub fn render_qr(text: &str) -> Result<String> {
+    let code = QrCode::new(text.as_bytes()).context("无法生成二维码")?;
+    Ok(code.render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+impl Handoff {
+    fn new(zip_path: PathBuf) -> Result<Self> {
+        let file_name = zip_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        Ok(Self { zip_path, file_name, token: random_token(TOKEN_BYTES)? })
+    }
+
+    fn url_path(&self) -> String {
+        format!("/{}/{}", self.token, encode_path_segment(&self.file_name))
+    }
+
+    /// 处理一个请求，zip 被完整下载时返回 true
+    fn handle(&self, stream: TcpStream) -> Result<bool> {
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        let peer = stream.peer_addr().map(|a| a.ip().to_string()).unwrap_or_default();
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let mut stream = stream;
+        let request = parse_request_line(request_line.trim());
+        let Some((method @ ("GET" | "HEAD"), path)) = request else {
+            write!(stream, "HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+            return Ok(false);
+        };
+        if path != self.url_path() {
+            write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+            println!("{} {} {} {} {}", "[qr]".cyan(), peer.bright_black(), method, path, "404".yellow());
+            return Ok(false);
+        }
+
+        let mut file = File::open(&self.zip_path)?;
+        let length = file.metadata()?.len();
+        write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nContent-Disposition: attachment; filename=\"{}\"\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+            content_type(&self.file_name), length, self.file_name.replace('"', "_"))?;
+        if method == "HEAD" {
+            return Ok(false);
+        }
+        let sent = std::io::copy(&mut file, &mut stream)?;
+        stream.flush()?;
+        println!("{} {} 已下载 {} ({} 字节)", "[qr]".cyan(), peer.bright_black(), self.file_name, sent);
+        Ok(sent == length)
+    }
+}
+
+/// 不经 ADB 确定要交付的产物：未指定 zip 时按 --abi 选择（未指定 ABI 时使用通用包）
+fn resolve_handoff_artifact(project_path: &Path, zip: Option<PathBuf>, abis: &[String]) -> Result<PathBuf> {
+    if let Some(zip) = zip {
+        if !zip.is_file() {
+            anyhow::bail!("文件不存在: {}", zip.display());
+        }
+        return Ok(zip);
+    }
+    let module_id = read_module_id(project_path)?;
+    select_artifact(&list_artifacts(project_path, &module_id), abis)
+        .context("未指定 --abi 时只能自动选择通用包，可用 --abi 或直接指定 zip")
+}
+
+/// rmm device install --qr：在局域网内提供模块 zip 并打印二维码，设备扫码下载后在 Root 管理器中安装；
+/// zip 被完整下载一次或按 Ctrl-C 后停止
+pub fn install_via_qr(project_path: &Path, zip: Option<PathBuf>, abis: &[String], port: u16) -> Result<PathBuf> {
+    let zip_path = resolve_handoff_artifact(project_path, zip, abis)?;
+    let handoff = Handoff::new(zip_path)?;
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)))
+        .with_context(|| format!("无法监听端口 {}", port))?;
+    let host = lan_address().map(|ip| ip.to_string())
+        .ok_or_else(|| anyhow::anyhow!("无法确定本机局域网地址，设备需与本机在同一局域网"))?;
+    let url = format!("http://{}:{}{}", host, listener.local_addr()?.port(), handoff.url_path());
+
+    println!("{}", render_qr(&url)?);
+    println!("{} 用设备扫描二维码下载 {}", "📱".green().bold(), handoff.file_name.cyan().bold());
+    println!("    {}", url.green());
+    println!("    下载完成后在 KernelSU / Magisk / APatch 管理器中选择“从本地安装”并选中该 zip；按 Ctrl-C 取消");
+
+    listener.set_nonblocking(true)?;
+    while !is_cancelled() {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                match handoff.handle(stream) {
+                    Ok(true) => {
+                        println!("{} 下载完成，请在设备上完成安装并重启", "[+]".green().bold());
+                        return Ok(handoff.zip_path);
+                    }
+                    Ok(false) => {}
+                    Err(e) => println!("{} 请求处理失败: {}", "[qr]".yellow(), e),
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    anyhow::bail!("已取消，设备未下载 {}", handoff.file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_handoff_serves_zip_only_under_token() {
+        let temp = TempDir::new().unwrap();
+        let zip = temp.path().join("demo 1.zip");
+        std::fs::write(&zip, "zip-bytes").unwrap();
+        let handoff = Handoff::new(zip.clone()).unwrap();
+        assert!(handoff.url_path().ends_with("/demo%201.zip"));
+        assert_eq!(handoff.token.len(), 32);
+        assert_ne!(Handoff::new(zip.clone()).unwrap().token, handoff.token);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let path = handoff.url_path();
+        let server = std::thread::spawn(move || {
+            (0..3).map(|_| handoff.handle(listener.accept().unwrap().0).unwrap()).collect::<Vec<_>>()
+        });
+        let request = |line: String| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "{}\r\nHost: test\r\n\r\n", line).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        assert!(request("GET /demo%201.zip HTTP/1.1".to_string()).starts_with("HTTP/1.1 404"));
+        assert!(request(format!("HEAD {} HTTP/1.1", path)).contains("Content-Length: 9\r\n"));
+        let response = request(format!("GET {} HTTP/1.1", path));
+        assert!(response.contains("Content-Disposition: attachment; filename=\"demo 1.zip\""));
+        assert!(response.ends_with("\r\n\r\nzip-bytes"));
+        assert_eq!(server.join().unwrap(), vec![false, false, true]);
+
+        let qr = render_qr("http://192.168.1.5:8080/x/demo.zip").unwrap();
+        assert!(qr.lines().count() > 10);
+        assert!(resolve_handoff_artifact(temp.path(), Some(temp.path().join("missing.zip")), &[]).is_err());
+    }
+}
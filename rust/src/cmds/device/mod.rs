@@ -0,0 +1,650 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+pub mod artifact;
+pub mod deps;
+pub mod handoff;
+pub mod perf;
+pub mod wireless;
+
+use artifact::{list_artifacts, select_artifact};
+
+use crate::core::action::ACTION_SCRIPT;
+use crate::core::control_files::{CONTROL_FILES, REMOVE, SKIP_MOUNT};
+use crate::core::adb::{shell_quote, Adb, RootManager, DEVICE_TMP_DIR, MODULES_DIR, MODULES_UPDATE_DIR};
+use crate::core::env_file::load_project_env;
+use crate::core::events::record_event_or_warn;
+use crate::core::preflight::{parse_df_available, PreflightIssue, DEVICE_SPACE_FACTOR};
+use crate::core::settings::ConfigResolver;
+use crate::core::signing::{signature_path, trusted_keys, verify_artifact, ArtifactVerification, Check, PublicKey, VerifyPolicy, DEVICE_KEYS_DIR};
+use crate::core::storage::RmmStorage;
+use crate::core::zip_inspect::{parse_module_prop, read_prop_text};
+
+/// 快照元数据文件名
+const SNAPSHOT_META: &str = "snapshot.json";
+/// 快照归档文件名
+const SNAPSHOT_ARCHIVE: &str = "module.tar.gz";
+
+/// 设备上模块状态快照
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModuleSnapshot {
+    pub module_id: String,
+    pub created_at: String,
+    pub serial: Option<String>,
+    /// 快照时模块目录是否存在
+    pub existed: bool,
+    /// 快照时存在的状态标记（disable / remove / skip_mount）
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// 快照时是否有待生效的更新（modules_update）
+    #[serde(default)]
+    pub pending_update: bool,
+}
+
+/// 按项目的分层配置连接设备，serial 为命令行 --serial；无线调试设备掉线时自动重连
+pub fn connect(project_path: &Path, serial: Option<String>) -> Result<Adb> {
+    let config = ConfigResolver::new(Some(project_path)).with_cli("device.serial", serial);
+    Ok(wireless::ensure_connected(Adb::from_config(&config).with_envs(load_project_env(project_path)?)))
+}
+
+/// 不依赖项目的 adb（用于 pair / connect 等管理 adb server 的命令）
+pub fn server_adb() -> Adb {
+    Adb::from_config(&ConfigResolver::new(None))
+}
+
+/// 从项目 module.prop 读取模块 ID
+pub fn read_module_id(project_path: &Path) -> Result<String> {
+    let content = read_prop_text(&project_path.join("module.prop"))?;
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("id="))
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("module.prop 中缺少 id"))
+}
+
+/// 某个模块的快照目录
+fn module_snapshots_dir(module_id: &str) -> PathBuf {
+    RmmStorage::resolve().snapshots_dir().join(module_id)
+}
+
+/// 列出模块的所有快照（按时间升序）
+pub fn list_snapshots(module_id: &str) -> Result<Vec<(PathBuf, ModuleSnapshot)>> {
+    let dir = module_snapshots_dir(module_id);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(&dir)?.filter_map(|e| e.ok()) {
+        let meta_path = entry.path().join(SNAPSHOT_META);
+        let snapshot = fs::read_to_string(&meta_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<ModuleSnapshot>(&content).ok());
+        if let Some(snapshot) = snapshot {
+            snapshots.push((entry.path(), snapshot));
+        }
+    }
+    snapshots.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(snapshots)
+}
+
+/// 解析设备快照脚本输出
+fn parse_snapshot_output(output: &str) -> (bool, Vec<String>, bool) {
+    let mut archived = false;
+    let mut flags = Vec::new();
+    let mut pending = false;
+    for line in output.lines().map(str::trim) {
+        match line {
+            "archived" => archived = true,
+            "pending" => pending = true,
+            _ => {
+                if let Some(flag) = line.strip_prefix("flag:") {
+                    flags.push(flag.to_string());
+                }
+            }
+        }
+    }
+    (archived, flags, pending)
+}
+
+/// 在设备上为模块创建快照（模块目录归档 + 状态标记），保存到 RMM_ROOT/snapshots
+pub fn snapshot_module(adb: &Adb, module_id: &str) -> Result<PathBuf> {
+    let module_dir = format!("{}/{}", MODULES_DIR, module_id);
+    let remote_archive = format!("{}/rmm-snapshot-{}.tar.gz", DEVICE_TMP_DIR, module_id);
+
+    let script = format!(
+        "m={m}; if [ -d \"$m\" ]; then \
+         for f in {flags}; do [ -e \"$m/$f\" ] && echo \"flag:$f\"; done; \
+         tar -czf {archive} -C {modules} {id} && chmod 644 {archive} && echo archived; \
+         fi; [ -d {update}/{id} ] && echo pending; true",
+        m = shell_quote(&module_dir),
+        flags = CONTROL_FILES.join(" "),
+        archive = shell_quote(&remote_archive),
+        modules = MODULES_DIR,
+        id = shell_quote(module_id),
+        update = MODULES_UPDATE_DIR,
+    );
+    let output = adb.su(&script)?;
+    let (existed, flags, pending_update) = parse_snapshot_output(&output);
+
+    let snapshot_dir = module_snapshots_dir(module_id)
+        .join(chrono::Local::now().format("%Y%m%d-%H%M%S").to_string());
+    fs::create_dir_all(&snapshot_dir)?;
+
+    if existed {
+        adb.pull(&remote_archive, &snapshot_dir.join(SNAPSHOT_ARCHIVE))?;
+        adb.su(&format!("rm -f {}", shell_quote(&remote_archive)))?;
+    }
+
+    let snapshot = ModuleSnapshot {
+        module_id: module_id.to_string(),
+        created_at: chrono::Local::now().to_rfc3339(),
+        serial: adb.serial().map(str::to_string),
+        existed,
+        flags,
+        pending_update,
+    };
+    fs::write(snapshot_dir.join(SNAPSHOT_META), serde_json::to_string_pretty(&snapshot)?)?;
+
+    println!("{} 已创建快照: {}", "[+]".green().bold(), snapshot_dir.display());
+    if !existed {
+        println!("{} 设备上尚未安装 {}，回滚时将直接移除", "[i]".cyan(), module_id);
+    }
+    Ok(snapshot_dir)
+}
+
+/// 将设备上的模块恢复到快照状态（默认最新快照）
+pub fn rollback_module(adb: &Adb, module_id: &str, snapshot_name: Option<&str>) -> Result<PathBuf> {
+    let snapshots = list_snapshots(module_id)?;
+    let (snapshot_dir, snapshot) = match snapshot_name {
+        Some(name) => snapshots.into_iter().find(|(path, _)| path.file_name().is_some_and(|n| n == name)),
+        None => snapshots.into_iter().last(),
+    }
+    .ok_or_else(|| anyhow::anyhow!("没有找到模块 {} 的快照", module_id))?;
+
+    let module_dir = format!("{}/{}", MODULES_DIR, module_id);
+    let update_dir = format!("{}/{}", MODULES_UPDATE_DIR, module_id);
+    adb.su(&format!("rm -rf {} {}", shell_quote(&update_dir), shell_quote(&module_dir)))?;
+
+    if snapshot.existed {
+        let remote_archive = format!("{}/rmm-snapshot-{}.tar.gz", DEVICE_TMP_DIR, module_id);
+        adb.push(&snapshot_dir.join(SNAPSHOT_ARCHIVE), &remote_archive)?;
+        adb.su(&format!(
+            "tar -xzf {archive} -C {modules} && rm -f {archive}",
+            archive = shell_quote(&remote_archive),
+            modules = MODULES_DIR,
+        ))?;
+    }
+
+    println!("{} 已回滚 {} 到快照 {}", "[+]".green().bold(), module_id, snapshot.created_at);
+    if !snapshot.flags.is_empty() {
+        println!("{} 恢复状态标记: {}", "[i]".cyan(), snapshot.flags.join(", "));
+    }
+    Ok(snapshot_dir)
+}
+
+/// 在设备上执行模块的 action.sh（等同于管理器中的 "Action" 按钮）
+pub fn run_action(adb: &Adb, module_id: &str) -> Result<()> {
+    let module_dir = format!("{}/{}", MODULES_DIR, module_id);
+    let action = format!("{}/{}", module_dir, ACTION_SCRIPT);
+    let exists = adb.su(&format!("[ -f {} ] && echo yes", shell_quote(&action)))?;
+    if exists.trim() != "yes" {
+        anyhow::bail!("模块 {} 没有 {}", module_id, ACTION_SCRIPT);
+    }
+
+    println!("{} 执行 {} 的 {}", "[*]".cyan(), module_id.bright_white(), ACTION_SCRIPT);
+    let output = adb.su(&format!(
+        "cd {dir} && MODPATH={dir} sh {action} </dev/null",
+        dir = shell_quote(&module_dir),
+        action = ACTION_SCRIPT,
+    ))?;
+    print!("{}", output);
+    Ok(())
+}
+
+/// 切换设备上已安装模块的控制文件（skip_mount / disable / remove），enable 为 None 时取反，返回切换后的状态
+pub fn set_control_flag(adb: &Adb, module_id: &str, flag: &str, enable: Option<bool>) -> Result<bool> {
+    if !CONTROL_FILES.contains(&flag) {
+        anyhow::bail!("未知的控制文件: {}（可选 {}）", flag, CONTROL_FILES.join(" / "));
+    }
+    let module_dir = format!("{}/{}", MODULES_DIR, module_id);
+    let path = shell_quote(&format!("{}/{}", module_dir, flag));
+    let action = match enable {
+        Some(true) => format!("touch {}", path),
+        Some(false) => format!("rm -f {}", path),
+        None => format!("if [ -e {p} ]; then rm -f {p}; else touch {p}; fi", p = path),
+    };
+    let output = adb.su(&format!(
+        "[ -d {dir} ] || {{ echo missing; exit 0; }}; {action}; [ -e {path} ] && echo on || echo off",
+        dir = shell_quote(&module_dir),
+    ))?;
+    let enabled = match output.trim() {
+        "missing" => anyhow::bail!("设备上没有安装模块 {}", module_id),
+        state => state == "on",
+    };
+
+    let state = if enabled { "已启用".green() } else { "已移除".yellow() };
+    println!("{} {} 的 {} {}", "[+]".green().bold(), module_id.bright_white(), flag.cyan(), state);
+    if flag == SKIP_MOUNT || flag == REMOVE {
+        println!("    {}", "重启后生效".bright_black());
+    }
+    Ok(enabled)
+}
+
+/// 解析 KEY=VALUE 形式的属性修改
+pub fn parse_prop_assignment(value: &str) -> Result<(String, String)> {
+    let (key, value) = value.split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("无效的属性修改 '{}'，应为 KEY=VALUE", value))?;
+    Ok((key.trim().to_string(), value.trim().to_string()))
+}
+
+/// 修改 module.prop 内容：保留原有顺序与注释，替换已有键，追加新键
+pub fn update_prop_content(content: &str, changes: &[(String, String)]) -> Result<String> {
+    for (key, value) in changes {
+        if key.is_empty() || key.contains(['=', '\n', '#']) || value.contains('\n') {
+            anyhow::bail!("无效的属性: {}={}", key, value);
+        }
+        if key == "id" {
+            anyhow::bail!("不能修改模块 id（设备上的模块目录名依赖它）");
+        }
+        if key == "versionCode" && value.parse::<i64>().is_err() {
+            anyhow::bail!("versionCode 必须是整数: {}", value);
+        }
+    }
+
+    let mut pending: Vec<&(String, String)> = changes.iter().collect();
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let key = line.split_once('=').map(|(k, _)| k.trim());
+            match pending.iter().position(|(k, _)| Some(k.as_str()) == key) {
+                Some(index) => {
+                    let (key, value) = pending.remove(index);
+                    format!("{}={}", key, value)
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect();
+    lines.extend(pending.into_iter().map(|(key, value)| format!("{}={}", key, value)));
+    Ok(lines.join("\n") + "\n")
+}
+
+/// 用 $EDITOR 编辑内容
+fn edit_in_editor(content: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+    let path = std::env::temp_dir().join(format!("rmm-module-{}.prop", std::process::id()));
+    fs::write(&path, content)?;
+    let status = std::process::Command::new(&editor).arg(&path).status()
+        .with_context(|| format!("无法启动编辑器: {}", editor))?;
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+    if !status.success() {
+        anyhow::bail!("编辑器退出异常，未做修改");
+    }
+    Ok(edited?)
+}
+
+/// 读取设备上已安装模块的 module.prop，修改后写回（0644 root:root），可选重启管理器应用
+pub fn push_prop(adb: &Adb, module_id: &str, changes: &[(String, String)], edit: bool, restart: bool) -> Result<()> {
+    let remote_prop = format!("{}/{}/module.prop", MODULES_DIR, module_id);
+    let content = adb.su(&format!("cat {} 2>/dev/null", shell_quote(&remote_prop)))?;
+    if content.trim().is_empty() {
+        anyhow::bail!("设备上未安装模块 {}", module_id);
+    }
+
+    if changes.is_empty() && !edit {
+        println!("{} {}:", "[📄]".cyan().bold(), remote_prop.bright_white());
+        print!("{}", content);
+        return Ok(());
+    }
+
+    let mut updated = update_prop_content(&content, changes)?;
+    if edit {
+        updated = edit_in_editor(&updated)?;
+        let id = parse_module_prop(&updated).get("id").cloned();
+        if id.as_deref() != Some(module_id) {
+            anyhow::bail!("不能修改或删除模块 id（设备上的模块目录名依赖它）");
+        }
+    }
+    if updated.trim_end() == content.trim_end() {
+        println!("{} module.prop 没有变化", "[i]".cyan());
+        return Ok(());
+    }
+
+    // 修改前快照，便于 rmm device rollback 恢复
+    snapshot_module(adb, module_id)?;
+
+    let local = std::env::temp_dir().join(format!("rmm-push-{}.prop", std::process::id()));
+    fs::write(&local, &updated)?;
+    let remote_tmp = format!("{}/rmm-module.prop", DEVICE_TMP_DIR);
+    let pushed = adb.push(&local, &remote_tmp);
+    let _ = fs::remove_file(&local);
+    pushed?;
+    adb.su(&format!(
+        "cp {tmp} {prop} && chown 0:0 {prop} && chmod 0644 {prop}; restorecon {prop} 2>/dev/null; rm -f {tmp}",
+        tmp = shell_quote(&remote_tmp),
+        prop = shell_quote(&remote_prop),
+    ))?;
+
+    let before = parse_module_prop(&content);
+    let after = parse_module_prop(&updated);
+    for (key, value) in &after {
+        if before.get(key) != Some(value) {
+            println!("    {} {} = {}", "[~]".yellow(), key, value.green());
+        }
+    }
+    println!("{} 已更新 {}", "[+]".green().bold(), remote_prop);
+
+    if restart {
+        let manager = adb.detect_manager()?;
+        adb.su(&format!("am force-stop {}", manager.app_package()))?;
+        println!("{} 已重启 {} 管理器应用，重新打开以检测更新", "[+]".green().bold(), manager.as_str());
+    }
+    Ok(())
+}
+
+/// 确定要安装的产物：指定了 zip 时直接使用，否则按设备 ABI 从 .rmmp/dist 中选择
+pub fn resolve_artifact(adb: &Adb, project_path: &Path, zip: Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(zip) = zip {
+        if !zip.is_file() {
+            anyhow::bail!("文件不存在: {}", zip.display());
+        }
+        return Ok(zip);
+    }
+    let module_id = read_module_id(project_path)?;
+    let abis = adb.abis()?;
+    let zip = select_artifact(&list_artifacts(project_path, &module_id), &abis)?;
+    println!("{} 设备 ABI: {}，选择 {}", "[+]".green().bold(),
+        abis.join(", ").bright_white(),
+        zip.file_name().unwrap_or_default().to_string_lossy().cyan());
+    Ok(zip)
+}
+
+/// 安装前校验产物：被篡改或损坏时拒绝安装，Strict 时还拒绝未签名的产物
+pub fn verify_before_install(zip_path: &Path, policy: VerifyPolicy) -> Result<()> {
+    if policy == VerifyPolicy::Off {
+        return Ok(());
+    }
+    let verification = verify_artifact(zip_path, &trusted_keys()?)?;
+    print_artifact_verification(zip_path, &verification);
+
+    let failures = verification.failures();
+    if !failures.is_empty() {
+        anyhow::bail!("产物校验失败，拒绝安装:\n  {}", failures.join("\n  "));
+    }
+    if policy == VerifyPolicy::Strict && !verification.is_signed() {
+        anyhow::bail!("--strict: 拒绝安装未签名的产物（需要 {} 或 rmm build --sign 嵌入的签名，且公钥位于 RMM_ROOT/keys）", signature_path(zip_path).display());
+    }
+    Ok(())
+}
+
+/// 打印产物各项校验的结果
+pub fn print_artifact_verification(zip_path: &Path, verification: &ArtifactVerification) {
+    println!("{} 校验产物 {}", "[*]".cyan(), zip_path.file_name().unwrap_or_default().to_string_lossy());
+    for (name, check) in [("校验和", &verification.checksum), ("文件清单", &verification.contents), ("签名", &verification.signature)] {
+        match check {
+            Check::Passed(detail) => println!("    {} {}: {}", "[+]".green(), name, detail),
+            Check::Missing => println!("    {} {}: 未提供", "[!]".yellow(), name),
+            Check::Failed(reason) => println!("    {} {}: {}", "[x]".red(), name, reason),
+        }
+    }
+}
+
+/// 推送并通过设备上的 Root 管理器安装模块 zip
+pub fn install_module(adb: &Adb, project_path: &Path, zip: Option<PathBuf>, policy: VerifyPolicy) -> Result<PathBuf> {
+    let zip_path = resolve_artifact(adb, project_path, zip)?;
+    verify_before_install(&zip_path, policy)?;
+    let manager = adb.detect_manager()?;
+    println!("{} 检测到 Root 管理器: {}", "[+]".green().bold(), manager.as_str().bright_white());
+
+    let file_name = install_zip(adb, manager, &zip_path)?;
+
+    println!("{} 已安装 {}，重启设备后生效", "[+]".green().bold(), file_name);
+    record_event_or_warn(project_path, "device_install", &[
+        ("artifact", file_name),
+        ("device", adb.serial().unwrap_or("default").to_string()),
+        ("manager", manager.as_str().to_string()),
+    ]);
+    Ok(zip_path)
+}
+
+/// 安装后重启设备使模块生效：--reboot 直接重启，--no-reboot 跳过，否则在终端中询问
+pub fn offer_reboot(adb: &Adb, reboot: bool, no_reboot: bool) -> Result<()> {
+    if no_reboot {
+        return Ok(());
+    }
+    let confirmed = reboot || (std::io::stdin().is_terminal() && confirm("立即重启设备？")?);
+    if !confirmed {
+        println!("{} 稍后手动重启，或使用 --reboot 在安装后自动重启", "💡".blue().bold());
+        return Ok(());
+    }
+    adb.reboot()?;
+    println!("{} 设备正在重启", "[+]".green().bold());
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} {} [y/N]: ", "[?]".cyan(), question);
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// 推送 zip 到设备临时目录并由 Root 管理器安装，返回文件名；临时文件总会被清理
+fn install_zip(adb: &Adb, manager: RootManager, zip_path: &Path) -> Result<String> {
+    let file_name = zip_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let remote_zip = format!("{}/{}", DEVICE_TMP_DIR, file_name);
+    check_device_space(adb, zip_path)?;
+    let install = adb.push(zip_path, &remote_zip)
+        .and_then(|_| adb.su(&manager.install_command(&remote_zip)));
+    let _ = adb.shell(&format!("rm -f {}", shell_quote(&remote_zip)));
+    install?;
+    Ok(file_name)
+}
+
+/// 推送前检查设备 /data 的可用空间（zip 大小 × DEVICE_SPACE_FACTOR）；无法读取 df 输出时不检查
+fn check_device_space(adb: &Adb, zip_path: &Path) -> Result<()> {
+    if ConfigResolver::new(None).get_bool("preflight.skip") {
+        return Ok(());
+    }
+    let required = fs::metadata(zip_path)?.len().saturating_mul(DEVICE_SPACE_FACTOR);
+    let Some(available) = adb.shell(&format!("df -Pk {}", DEVICE_TMP_DIR)).ok().and_then(|out| parse_df_available(&out)) else {
+        return Ok(());
+    };
+    if available < required {
+        let issue = PreflightIssue::Space { path: PathBuf::from("/data"), required, available };
+        anyhow::bail!("设备空间不足：{}；请清理设备存储或卸载不用的模块后重试（设置 RMM_SKIP_PREFLIGHT=1 可跳过检查）", issue.describe());
+    }
+    Ok(())
+}
+
+/// 将公钥安装到设备的 /data/adb/rmm/keys/<name>.pub（0644），供模块脚本自行校验，返回设备上的路径
+pub fn install_public_key(adb: &Adb, key: &PublicKey) -> Result<String> {
+    let remote_key = format!("{}/{}.pub", DEVICE_KEYS_DIR, key.name);
+    let local = std::env::temp_dir().join(format!("rmm-key-{}.pub", std::process::id()));
+    fs::write(&local, format!("{}\n", key.to_hex()))?;
+    let remote_tmp = format!("{}/rmm-key.pub", DEVICE_TMP_DIR);
+    let pushed = adb.push(&local, &remote_tmp);
+    let _ = fs::remove_file(&local);
+    pushed?;
+    adb.su(&format!(
+        "mkdir -p {dir} && cp {tmp} {key} && chmod 0644 {key}; rm -f {tmp}",
+        dir = DEVICE_KEYS_DIR,
+        tmp = shell_quote(&remote_tmp),
+        key = shell_quote(&remote_key),
+    ))?;
+    println!("{} 已安装公钥 {}（ID {}）: {}", "[+]".green().bold(), key.name.bright_white(), key.id(), remote_key);
+    Ok(remote_key)
+}
+
+/// 确定命令行给出的模块 ID（未指定时读取项目 module.prop）
+pub fn resolve_module_id(module_id: Option<String>, project_path: &Path) -> Result<String> {
+    match module_id {
+        Some(id) => Ok(id),
+        None => read_module_id(project_path),
+    }
+}
+
+/// 打印模块的快照列表
+pub fn print_snapshots(module_id: &str) -> Result<()> {
+    let snapshots = list_snapshots(module_id)?;
+    if snapshots.is_empty() {
+        println!("{} 模块 {} 没有快照", "[!]".yellow(), module_id);
+        return Ok(());
+    }
+    println!("{} 模块 {} 的快照:", "[📸]".cyan().bold(), module_id.bright_white());
+    for (path, snapshot) in snapshots {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let state = if snapshot.existed { "已安装".green() } else { "未安装".bright_black() };
+        println!("  {} {} {}", name.bright_white(), state, snapshot.flags.join(",").yellow());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_module_id() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("module.prop"), "name=Test\nid=demo_module\n").unwrap();
+        assert_eq!(read_module_id(temp_dir.path()).unwrap(), "demo_module");
+
+        fs::write(temp_dir.path().join("module.prop"), "name=Test\n").unwrap();
+        assert!(read_module_id(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_parse_snapshot_output() {
+        let (existed, flags, pending) = parse_snapshot_output("flag:disable\narchived\npending\n");
+        assert!(existed);
+        assert_eq!(flags, vec!["disable".to_string()]);
+        assert!(pending);
+
+        let (existed, flags, pending) = parse_snapshot_output("");
+        assert!(!existed && flags.is_empty() && !pending);
+    }
+
+    #[test]
+    fn test_update_prop_content() {
+        let content = "id=demo\n# comment\nversion=v1\nversionCode=1\n";
+        let changes = vec![
+            parse_prop_assignment("version=v0.0.1-test").unwrap(),
+            parse_prop_assignment("updateJson = https://example.com/update.json").unwrap(),
+        ];
+        assert_eq!(
+            update_prop_content(content, &changes).unwrap(),
+            "id=demo\n# comment\nversion=v0.0.1-test\nversionCode=1\nupdateJson=https://example.com/update.json\n"
+        );
+
+        assert!(update_prop_content(content, &[("id".to_string(), "other".to_string())]).is_err());
+        assert!(update_prop_content(content, &[("versionCode".to_string(), "abc".to_string())]).is_err());
+        assert!(parse_prop_assignment("novalue").is_err());
+    }
+
+    #[cfg(unix)]
+    fn write_module_zip(path: &Path, id: &str) {
+        use std::io::Write;
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        zip.start_file("module.prop", zip::write::SimpleFileOptions::default()).unwrap();
+        write!(zip, "id={}\nversion=v1\nversionCode=1\n", id).unwrap();
+        zip.start_file(ACTION_SCRIPT, zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"echo action-ran\n").unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_device_flows_on_fake_device() {
+        use crate::core::fake_device::{FakeDevice, FakeFailure};
+
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("demo");
+        let dist = project.join(".rmmp/dist");
+        fs::create_dir_all(&dist).unwrap();
+        fs::write(project.join("module.prop"), "id=demo\n").unwrap();
+        write_module_zip(&dist.join("demo-1-x86_64.zip"), "demo");
+        write_module_zip(&dist.join("demo-1-arm64-v8a.zip"), "demo");
+        let device = FakeDevice::open_or_create(&temp_dir.path().join("device")).unwrap();
+        let adb = device.adb().unwrap();
+
+        // 按设备 ABI 选择产物，通过 KernelSU 安装到 modules_update，并清理临时 zip
+        assert_eq!(adb.detect_manager().unwrap(), crate::core::adb::RootManager::KernelSU);
+        let installed = install_module(&adb, &project, None, VerifyPolicy::Off).unwrap();
+        assert_eq!(installed.file_name().unwrap(), "demo-1-arm64-v8a.zip");
+        assert!(device.path("/data/adb/modules_update/demo/module.prop").is_file());
+        assert_eq!(fs::read_dir(device.path(DEVICE_TMP_DIR)).unwrap().count(), 0);
+
+        // --strict 拒绝未签名的产物，--verify 只提示缺少签名
+        let err = install_module(&adb, &project, None, VerifyPolicy::Strict).unwrap_err().to_string();
+        assert!(err.contains("拒绝安装未签名的产物"), "{}", err);
+        verify_before_install(&installed, VerifyPolicy::Verify).unwrap();
+
+        // 公钥安装到设备供模块脚本自行校验
+        let key = PublicKey { name: "release".to_string(), key: ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]).verifying_key() };
+        let remote_key = install_public_key(&adb, &key).unwrap();
+        assert_eq!(fs::read_to_string(device.path(&remote_key)).unwrap().trim(), key.to_hex());
+
+        adb.reboot().unwrap();
+        assert!(adb.wait_for_boot(5));
+        assert!(device.path("/data/adb/modules/demo/module.prop").is_file());
+        run_action(&adb, "demo").unwrap();
+
+        // 控制文件切换
+        assert!(set_control_flag(&adb, "demo", "disable", None).unwrap());
+        assert!(device.path("/data/adb/modules/demo/disable").exists());
+        assert!(!set_control_flag(&adb, "demo", "disable", None).unwrap());
+        assert!(set_control_flag(&adb, "missing", "disable", Some(true)).unwrap_err().to_string().contains("没有安装"));
+
+        // remove 标记在重启后删除模块
+        set_control_flag(&adb, "demo", REMOVE, Some(true)).unwrap();
+        adb.reboot().unwrap();
+        assert!(!device.path("/data/adb/modules/demo").exists());
+
+        // 故障注入：连接断开、设备未 Root
+        device.update(|state| state.failures.push(FakeFailure {
+            pattern: "push".to_string(),
+            stderr: "error: device offline".to_string(),
+        })).unwrap();
+        let err = install_module(&adb, &project, None, VerifyPolicy::Off).unwrap_err().to_string();
+        assert!(err.contains("设备连接已断开"), "{}", err);
+        device.update(|state| {
+            state.failures.clear();
+            state.rooted = false;
+        }).unwrap();
+        assert!(adb.detect_manager().is_err());
+
+        // 管理器二进制不在 PATH 中时按安装位置检测
+        device.update(|state| {
+            state.rooted = true;
+            state.manager = None;
+        }).unwrap();
+        assert!(adb.detect_manager().is_err());
+        fs::create_dir_all(device.path("/data/adb/magisk")).unwrap();
+        assert_eq!(adb.detect_manager().unwrap(), crate::core::adb::RootManager::Magisk);
+
+        // 错误的序列号与离线设备
+        assert!(adb.clone().with_serial("other").shell("true").unwrap_err().to_string().contains("设备连接已断开"));
+        device.update(|state| state.online = false).unwrap();
+        assert_eq!(adb.state(), None);
+        device.update(|state| {
+            state.online = true;
+            state.bootloop = true;
+            state.rooted = true;
+        }).unwrap();
+        adb.reboot().unwrap();
+        assert!(!adb.wait_for_boot(1));
+    }
+}
+
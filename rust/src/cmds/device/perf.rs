@@ -0,0 +1,186 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::core::adb::{shell_quote, Adb, MODULES_DIR};
+use crate::core::control_files::DISABLE;
+use crate::core::process::check_cancelled;
+
+/// 启动阶段及其在 logcat 中的标志（任一子串命中即可，取首次出现的时间）
+const MILESTONES: &[(&str, &[&str])] = &[
+    ("post-fs-data", &["Running module post-fs-data scripts", "exec post-fs-data", "post-fs-data.sh"]),
+    ("zygote", &["boot_progress_start"]),
+    ("service", &["Running module service scripts", "exec service", "service.sh"]),
+    ("boot", &["boot_progress_enable_screen"]),
+];
+
+/// 读取本次开机日志（monotonic 时间戳为开机后的秒数）
+const LOGCAT_COMMAND: &str = "logcat -d -v monotonic -b main -b system -b events";
+
+/// 一次开机的各阶段时间（秒）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BootSample {
+    pub module_enabled: bool,
+    pub milestones: BTreeMap<String, f64>,
+}
+
+/// 启用 / 禁用模块时各阶段的平均时间及差值
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PerfReport {
+    pub module_id: String,
+    pub runs: usize,
+    pub enabled: BTreeMap<String, f64>,
+    pub disabled: BTreeMap<String, f64>,
+    /// enabled - disabled，正数表示模块拖慢了该阶段
+    pub delta: BTreeMap<String, f64>,
+    pub samples: Vec<BootSample>,
+}
+
+/// 解析 `logcat -v monotonic` 输出，返回各阶段首次出现的时间
+pub fn parse_boot_milestones(logcat: &str) -> BTreeMap<String, f64> {
+    let mut milestones = BTreeMap::new();
+    for line in logcat.lines() {
+        let Some(seconds) = line.split_whitespace().next().and_then(|t| t.parse::<f64>().ok()) else {
+            continue;
+        };
+        for (name, patterns) in MILESTONES {
+            if !milestones.contains_key(*name) && patterns.iter().any(|p| line.contains(p)) {
+                milestones.insert(name.to_string(), seconds);
+            }
+        }
+    }
+    milestones
+}
+
+/// 各阶段的平均值（只统计出现过该阶段的样本）
+fn average(samples: &[&BootSample]) -> BTreeMap<String, f64> {
+    let mut sums: BTreeMap<String, (f64, usize)> = BTreeMap::new();
+    for sample in samples {
+        for (name, seconds) in &sample.milestones {
+            let entry = sums.entry(name.clone()).or_default();
+            entry.0 += seconds;
+            entry.1 += 1;
+        }
+    }
+    sums.into_iter().map(|(name, (sum, count))| (name, sum / count as f64)).collect()
+}
+
+/// 汇总样本
+pub fn summarize(module_id: &str, runs: usize, samples: Vec<BootSample>) -> PerfReport {
+    let (enabled, disabled): (Vec<&BootSample>, Vec<&BootSample>) = samples.iter().partition(|s| s.module_enabled);
+    let enabled = average(&enabled);
+    let disabled = average(&disabled);
+    let delta = enabled.iter()
+        .filter_map(|(name, on)| disabled.get(name).map(|off| (name.clone(), on - off)))
+        .collect();
+    PerfReport { module_id: module_id.to_string(), runs, enabled, disabled, delta, samples }
+}
+
+/// 设置 disable 标记、重启并等待开机完成，读取本次开机的阶段时间
+fn measure_boot(adb: &Adb, module_dir: &str, enabled: bool, boot_timeout: u64) -> Result<BootSample> {
+    let flag = shell_quote(&format!("{}/{}", module_dir, DISABLE));
+    adb.su(&if enabled { format!("rm -f {}", flag) } else { format!("touch {}", flag) })?;
+    adb.reboot()?;
+    if !adb.wait_for_boot(boot_timeout) {
+        anyhow::bail!("设备在 {} 秒内未完成开机", boot_timeout);
+    }
+    let milestones = parse_boot_milestones(&adb.shell(LOGCAT_COMMAND)?);
+    if !milestones.contains_key("boot") {
+        anyhow::bail!("logcat 中没有 boot_progress_enable_screen，无法测量开机时间");
+    }
+    Ok(BootSample { module_enabled: enabled, milestones })
+}
+
+/// 交替启用 / 禁用模块各重启 runs 次，比较各启动阶段的平均时间；结束后恢复原来的 disable 状态
+pub fn measure_boot_impact(adb: &Adb, module_id: &str, runs: usize, boot_timeout: u64) -> Result<PerfReport> {
+    let module_dir = format!("{}/{}", MODULES_DIR, module_id);
+    let state = adb.su(&format!(
+        "[ -d {dir} ] || {{ echo missing; exit 0; }}; [ -e {dir}/{flag} ] && echo disabled || echo enabled",
+        dir = shell_quote(&module_dir),
+        flag = DISABLE,
+    ))?;
+    let originally_enabled = match state.trim() {
+        "missing" => anyhow::bail!("设备上没有安装模块 {}", module_id),
+        state => state == "enabled",
+    };
+
+    let runs = runs.max(1);
+    let mut samples = Vec::new();
+    let result = (|| -> Result<()> {
+        for run in 1..=runs {
+            for enabled in [true, false] {
+                check_cancelled()?;
+                let label = if enabled { "启用" } else { "禁用" };
+                println!("{} 第 {}/{} 轮：{}模块并重启", "[*]".cyan(), run, runs, label);
+                let sample = measure_boot(adb, &module_dir, enabled, boot_timeout)?;
+                println!("    开机完成于 {:.2}s", sample.milestones["boot"]);
+                samples.push(sample);
+            }
+        }
+        Ok(())
+    })();
+
+    // 恢复原状态（下次重启生效）
+    let flag = shell_quote(&format!("{}/{}", module_dir, DISABLE));
+    let restore = adb.su(&if originally_enabled { format!("rm -f {}", flag) } else { format!("touch {}", flag) });
+    result?;
+    restore?;
+    Ok(summarize(module_id, runs, samples))
+}
+
+/// 打印对比表
+pub fn print_perf_report(report: &PerfReport) {
+    println!("{} {} 的开机时间影响（{} 轮平均，开机后秒数）:", "[⏱]".cyan().bold(), report.module_id.bright_white(), report.runs);
+    println!("    {:<14} {:>9} {:>9} {:>9}", "阶段", "启用", "禁用", "差值");
+    for (name, _) in MILESTONES {
+        let (Some(on), Some(off)) = (report.enabled.get(*name), report.disabled.get(*name)) else {
+            continue;
+        };
+        let delta = on - off;
+        let delta_text = format!("{:+.2}s", delta);
+        let delta_text = if delta > 0.5 { delta_text.red() } else if delta < -0.5 { delta_text.green() } else { delta_text.normal() };
+        println!("    {:<14} {:>8.2}s {:>8.2}s {:>9}", name, on, off, delta_text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_boot_milestones() {
+        let logcat = "--------- beginning of main
+     1.204   412   412 I Magisk  : * Running module post-fs-data scripts
+     6.880   560   560 I boot_progress_start: 6880
+     9.101   790   790 I Magisk  : * Running module service scripts
+    18.532  1000  1000 I boot_progress_enable_screen: 18532
+    19.000  1000  1000 I boot_progress_enable_screen: 19000
+";
+        let milestones = parse_boot_milestones(logcat);
+        assert_eq!(milestones["post-fs-data"], 1.204);
+        assert_eq!(milestones["zygote"], 6.88);
+        assert_eq!(milestones["service"], 9.101);
+        assert_eq!(milestones["boot"], 18.532);
+    }
+
+    #[test]
+    fn test_summarize_boot_samples() {
+        let sample = |enabled, boot: f64, service: Option<f64>| BootSample {
+            module_enabled: enabled,
+            milestones: std::iter::once(("boot".to_string(), boot))
+                .chain(service.map(|s| ("service".to_string(), s)))
+                .collect(),
+        };
+        let report = summarize("demo", 2, vec![
+            sample(true, 20.0, Some(10.0)),
+            sample(false, 18.0, Some(9.0)),
+            sample(true, 22.0, None),
+            sample(false, 18.0, Some(9.0)),
+        ]);
+        assert_eq!(report.enabled["boot"], 21.0);
+        assert_eq!(report.disabled["boot"], 18.0);
+        assert_eq!(report.delta["boot"], 3.0);
+        assert_eq!(report.delta["service"], 1.0);
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::io::{BufRead, Write};
+
+use crate::core::adb::{address_host, is_network_address, Adb, KnownDevice};
+use crate::core::rmm_core::RmmCore;
+
+/// 记录成功连接的设备（按主机去重，端口以最新为准）
+fn remember_device(adb: &Adb, address: &str) -> Result<()> {
+    let model = adb.clone().with_serial(address)
+        .shell("getprop ro.product.model")
+        .map(|m| m.trim().to_string())
+        .unwrap_or_default();
+    let core = RmmCore::new();
+    let mut meta = core.get_meta_config()?;
+    meta.devices.insert(address_host(address).to_string(), KnownDevice {
+        address: address.to_string(),
+        model,
+        last_connected: chrono::Local::now().to_rfc3339(),
+    });
+    core.update_meta_config(&meta)
+}
+
+/// 已记录的设备，最近连接的在前
+fn known_devices() -> Vec<KnownDevice> {
+    let mut devices: Vec<KnownDevice> = RmmCore::new().get_meta_config()
+        .map(|meta| meta.devices.into_values().collect())
+        .unwrap_or_default();
+    devices.sort_by(|a, b| b.last_connected.cmp(&a.last_connected));
+    devices
+}
+
+/// 连接记录中的设备：先试上次的地址，失败时通过 mDNS 查找新端口，返回实际连接的地址
+fn reconnect(adb: &Adb, device: &KnownDevice) -> Result<String> {
+    let error = match adb.connect_address(&device.address) {
+        Ok(()) => return Ok(device.address.clone()),
+        Err(e) => e,
+    };
+    match adb.discover_address(address_host(&device.address)) {
+        Some(address) if address != device.address => {
+            adb.connect_address(&address)?;
+            Ok(address)
+        }
+        _ => Err(error),
+    }
+}
+
+/// rmm device pair：配对后尝试通过 mDNS 找到连接端口并自动连接
+pub fn pair_device(adb: &Adb, address: &str, code: Option<String>) -> Result<()> {
+    if !is_network_address(address) {
+        anyhow::bail!("配对地址应为 ip:port（设备上“使用配对码配对”中显示的地址）: {}", address);
+    }
+    let code = match code {
+        Some(code) => code,
+        None => {
+            print!("请输入设备上显示的 6 位配对码: ");
+            std::io::stdout().flush()?;
+            let mut line = String::new();
+            std::io::stdin().lock().read_line(&mut line)?;
+            line.trim().to_string()
+        }
+    };
+    adb.pair(address, &code)?;
+    println!("{} 已与 {} 配对", "[+]".green().bold(), address.cyan());
+
+    match adb.discover_address(address_host(address)) {
+        Some(connect_address) => connect_device(adb, Some(&connect_address)),
+        None => {
+            println!("    {} 未通过 mDNS 找到连接端口，请运行 rmm device connect <ip:port>（设备无线调试页面上显示的地址）", "[!]".yellow());
+            Ok(())
+        }
+    }
+}
+
+/// rmm device connect：连接指定地址；未指定时重新连接所有记录的设备
+pub fn connect_device(adb: &Adb, address: Option<&str>) -> Result<()> {
+    if let Some(address) = address {
+        if !is_network_address(address) {
+            anyhow::bail!("连接地址应为 ip:port: {}", address);
+        }
+        adb.connect_address(address)?;
+        remember_device(adb, address)?;
+        println!("{} 已连接 {}", "[+]".green().bold(), address.cyan());
+        return Ok(());
+    }
+
+    let devices = known_devices();
+    if devices.is_empty() {
+        anyhow::bail!("没有记录的无线调试设备，请先运行 rmm device pair 或 rmm device connect <ip:port>");
+    }
+    let mut connected = 0;
+    for device in &devices {
+        match reconnect(adb, device) {
+            Ok(address) => {
+                remember_device(adb, &address)?;
+                println!("{} 已连接 {} {}", "[+]".green().bold(), address.cyan(), device.model.bright_black());
+                connected += 1;
+            }
+            Err(e) => println!("{} {}", "[!]".yellow(), e),
+        }
+    }
+    if connected == 0 {
+        anyhow::bail!("所有记录的设备都无法连接，请确认无线调试已开启");
+    }
+    Ok(())
+}
+
+/// rmm device disconnect：断开指定地址或所有记录的设备
+pub fn disconnect_device(adb: &Adb, address: Option<&str>) -> Result<()> {
+    let addresses: Vec<String> = match address {
+        Some(address) => vec![address.to_string()],
+        None => known_devices().into_iter().map(|d| d.address).collect(),
+    };
+    for address in addresses {
+        adb.disconnect_address(&address)?;
+        println!("{} 已断开 {}", "[-]".yellow().bold(), address);
+    }
+    Ok(())
+}
+
+/// rmm device forget：删除设备记录（按主机或 ip:port）
+pub fn forget_device(adb: &Adb, address: &str) -> Result<()> {
+    let core = RmmCore::new();
+    let mut meta = core.get_meta_config()?;
+    let removed = meta.devices.remove(address_host(address))
+        .with_context(|| format!("没有 {} 的设备记录", address))?;
+    core.update_meta_config(&meta)?;
+    let _ = adb.disconnect_address(&removed.address);
+    println!("{} 已删除设备记录 {}", "[-]".yellow().bold(), removed.address);
+    Ok(())
+}
+
+/// rmm device list：列出当前连接与记录的无线调试设备
+pub fn list_devices(adb: &Adb) -> Result<()> {
+    let attached = adb.devices()?;
+    println!("{}", "已连接设备:".bold());
+    if attached.is_empty() {
+        println!("  {}", "（无）".bright_black());
+    }
+    for (serial, state) in &attached {
+        let state = if state == "device" { state.green() } else { state.yellow() };
+        println!("  {} {}", serial.cyan(), state);
+    }
+
+    let known = known_devices();
+    if !known.is_empty() {
+        println!("{}", "记录的无线调试设备:".bold());
+    }
+    for device in known {
+        let online = attached.iter().any(|(serial, state)| serial == &device.address && state == "device");
+        println!("  {} {} {}{}",
+            device.address.cyan(),
+            device.model,
+            device.last_connected.bright_black(),
+            if online { " ●".green().to_string() } else { String::new() }
+        );
+    }
+    Ok(())
+}
+
+/// 执行设备命令前确保连接：网络设备掉线时自动重连；未指定设备且没有在线设备时连接最近使用的记录设备
+pub fn ensure_connected(adb: Adb) -> Adb {
+    if let Some(serial) = adb.serial().map(str::to_string) {
+        if !is_network_address(&serial) || adb.state().as_deref() == Some("device") {
+            return adb;
+        }
+        let device = known_devices().into_iter()
+            .find(|d| address_host(&d.address) == address_host(&serial))
+            .unwrap_or_else(|| KnownDevice { address: serial.clone(), ..Default::default() });
+        println!("{} {} 未连接，正在重新连接…", "[adb]".cyan(), serial);
+        return match reconnect(&adb, &device) {
+            Ok(address) => {
+                let _ = remember_device(&adb, &address);
+                adb.with_serial(&address)
+            }
+            Err(e) => {
+                println!("{} {}", "[!]".yellow(), e);
+                adb
+            }
+        };
+    }
+
+    let Ok(attached) = adb.devices() else { return adb };
+    if attached.iter().any(|(_, state)| state == "device") {
+        return adb;
+    }
+    for device in known_devices() {
+        println!("{} 没有在线设备，尝试连接 {}…", "[adb]".cyan(), device.address);
+        if let Ok(address) = reconnect(&adb, &device) {
+            let _ = remember_device(&adb, &address);
+            return adb.with_serial(&address);
+        }
+    }
+    adb
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::core::settings::{spec, ConfigResolver};
+use crate::core::storage::RmmStorage;
+
+/// 显示 RMM 运行环境信息
+pub fn show_env(paths: bool) -> Result<()> {
+    let storage = RmmStorage::resolve();
+
+    if paths {
+        print_paths(&storage);
+        return Ok(());
+    }
+
+    println!("{} RMM 环境变量:", "[🌐]".cyan().bold());
+    for key in ["RMM_ROOT", "XDG_DATA_HOME", "APPDATA", "PREFIX", "TERMUX_VERSION", "HOME"] {
+        let value = std::env::var(key).unwrap_or_default();
+        if value.is_empty() {
+            println!("  {} = {}", key.bright_white(), "(未设置)".bright_black());
+        } else {
+            println!("  {} = {}", key.bright_white(), value.green());
+        }
+    }
+    println!();
+    print_paths(&storage);
+
+    Ok(())
+}
+
+/// 显示当前选用的存储路径
+fn print_paths(storage: &RmmStorage) {
+    let legacy = RmmStorage::legacy_root();
+
+    println!("{} RMM 路径:", "[📁]".cyan().bold());
+    println!("  {}: {} ({})",
+        "RMM_ROOT".bright_white(),
+        storage.root().display().to_string().green(),
+        storage.source().as_str().yellow()
+    );
+    println!("  {}: {}", "meta.toml".bright_white(), storage.meta_path().display());
+    println!("  {}: {}", "cache".bright_white(), storage.cache_dir().display());
+    if legacy != storage.root() && legacy.exists() {
+        println!("  {}: {} {}",
+            "legacy".bright_white(),
+            legacy.display().to_string().bright_black(),
+            "(旧版目录仍存在)".yellow()
+        );
+    }
+    if let Some(target) = RmmStorage::pending_migration() {
+        println!("  {} 正在使用旧版目录，运行 {} 迁移到 {}",
+            "💡".blue().bold(), "rmm env --migrate".cyan(), target.root().display());
+    }
+}
+
+/// rmm env --migrate：将旧版 ~/data/adb/.rmm 迁移到当前存储位置
+pub fn migrate() -> Result<()> {
+    match RmmStorage::migrate_legacy()? {
+        Some(storage) => println!("{} 已迁移旧版 RMM 数据到: {}", "📦".cyan().bold(), storage.root().display()),
+        None => println!("{} 没有需要迁移的旧版数据", "[i]".cyan()),
+    }
+    Ok(())
+}
+
+/// 显示所有分层配置项的最终取值与来源（命令行 > 环境变量 > 项目 > 全局 > 默认）
+pub fn show_config(project_path: &Path) -> Result<()> {
+    let config = ConfigResolver::new(Some(project_path));
+    println!("{} RMM 配置（项目: {}）:", "[⚙️]".cyan().bold(), project_path.display());
+    for resolved in config.resolve_all() {
+        let value = match resolved.value {
+            Some(_) => resolved.display_value().green(),
+            None => resolved.display_value().bright_black(),
+        };
+        let description = spec(&resolved.key).map(|s| s.description).unwrap_or_default();
+        println!("  {} = {} {} {}",
+            resolved.key.bright_white(),
+            value,
+            format!("← {}", resolved.source.describe()).yellow(),
+            format!("# {}", description).bright_black()
+        );
+    }
+    Ok(())
+}
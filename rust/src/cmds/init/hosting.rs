@@ -0,0 +1,160 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::core::rmm_core::GitInfo;
+use crate::core::settings::ConfigResolver;
+
+use super::parse_github_url;
+
+/// 请求 GitHub API 的超时时间
+const API_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 从代码托管平台读取的仓库信息，用于预填项目文件
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoMetadata {
+    pub description: Option<String>,
+    /// SPDX 许可证标识，如 MIT、Apache-2.0
+    pub license_spdx: Option<String>,
+    pub topics: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiRepo {
+    description: Option<String>,
+    license: Option<ApiLicense>,
+    #[serde(default)]
+    topics: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiLicense {
+    spdx_id: Option<String>,
+}
+
+/// 解析 GitHub `GET /repos/{owner}/{repo}` 的响应
+pub fn parse_repo_metadata(json: &str) -> Result<RepoMetadata> {
+    let repo: ApiRepo = serde_json::from_str(json)?;
+    Ok(RepoMetadata {
+        description: repo.description
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty()),
+        // GitHub 无法识别许可证时返回 NOASSERTION
+        license_spdx: repo.license
+            .and_then(|l| l.spdx_id)
+            .filter(|id| !id.is_empty() && id != "NOASSERTION"),
+        topics: repo.topics,
+    })
+}
+
+/// 读取 GitHub token（分层配置 github.token：GITHUB_ACCESS_TOKEN、GITHUB_TOKEN 或 GH_TOKEN）
+fn github_token() -> Option<String> {
+    ConfigResolver::new(None).get("github.token")
+}
+
+/// 通过 GitHub API 获取仓库信息
+pub fn fetch_repo_metadata(owner: &str, repo: &str, token: &str) -> Result<RepoMetadata> {
+    let url = format!("https://api.github.com/repos/{}/{}", owner, repo);
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let body = runtime.block_on(async {
+        let response = reqwest::Client::builder()
+            .timeout(API_TIMEOUT)
+            .user_agent(concat!("rmm/", env!("CARGO_PKG_VERSION")))
+            .build()?
+            .get(&url)
+            .bearer_auth(token)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("GitHub API 返回 {}", status);
+        }
+        Ok(response.text().await?)
+    })?;
+    parse_repo_metadata(&body)
+}
+
+/// 初始化时检测远程仓库信息；仅在远程为 GitHub 且配置了 token 时请求，失败时只打印警告
+pub fn detect_repo_metadata(git_info: &Option<GitInfo>) -> Option<RepoMetadata> {
+    let remote_url = git_info.as_ref()?.remote_url.as_ref()?;
+    let (owner, repo) = parse_github_url(remote_url)?;
+    let token = github_token()?;
+
+    match fetch_repo_metadata(&owner, &repo, &token) {
+        Ok(metadata) => {
+            println!("{} 已读取 GitHub 仓库信息: {}/{}", "🔍".yellow().bold(), owner, repo);
+            if let Some(ref license) = metadata.license_spdx {
+                println!("  {}: {}", "许可证".cyan().bold(), license.green());
+            }
+            if !metadata.topics.is_empty() {
+                println!("  {}: {}", "主题".cyan().bold(), metadata.topics.join(", ").green());
+            }
+            Some(metadata)
+        }
+        Err(e) => {
+            println!("{} 无法读取 GitHub 仓库信息，使用默认值: {}", "⚠️ ".yellow().bold(), e);
+            None
+        }
+    }
+}
+
+const MIT_TEXT: &str = r#"Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+"#;
+
+/// 按 SPDX 标识生成 LICENSE 内容：MIT 生成全文，其他许可证生成版权声明与全文链接
+pub fn license_text(spdx: &str, holder: &str, year: i32) -> String {
+    if spdx.eq_ignore_ascii_case("MIT") {
+        return format!("MIT License\n\nCopyright (c) {} {}\n\n{}", year, holder, MIT_TEXT);
+    }
+    format!(
+        "SPDX-License-Identifier: {spdx}\n\nCopyright (c) {year} {holder}\n\n\
+         This project is licensed under {spdx}.\n\
+         Full license text: https://spdx.org/licenses/{spdx}.html\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metadata_and_license() {
+        let metadata = parse_repo_metadata(r#"{
+            "full_name": "alice/demo",
+            "description": "  Better fonts  ",
+            "license": {"key": "apache-2.0", "spdx_id": "Apache-2.0"},
+            "topics": ["magisk", "fonts"]
+        }"#).unwrap();
+        assert_eq!(metadata.description.as_deref(), Some("Better fonts"));
+        assert_eq!(metadata.license_spdx.as_deref(), Some("Apache-2.0"));
+        assert_eq!(metadata.topics, vec!["magisk", "fonts"]);
+
+        let metadata = parse_repo_metadata(r#"{"description": "", "license": {"spdx_id": "NOASSERTION"}}"#).unwrap();
+        assert_eq!(metadata, RepoMetadata::default());
+
+        let mit = license_text("MIT", "alice", 2026);
+        assert!(mit.starts_with("MIT License\n\nCopyright (c) 2026 alice\n"));
+        assert!(!mit.contains("LIghtJUNction"));
+        let apache = license_text("Apache-2.0", "alice", 2026);
+        assert!(apache.contains("SPDX-License-Identifier: Apache-2.0"));
+        assert!(apache.contains("https://spdx.org/licenses/Apache-2.0.html"));
+    }
+}
@@ -1,45 +1,174 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::{Path, PathBuf};
-use toml;
+use std::path::Path;
 use colored::*;
 use chrono::{Utc, Datelike};
 use serde_json;
 use git2::{Repository, Config};
 
+mod hosting;
+
+use hosting::{detect_repo_metadata, license_text, RepoMetadata};
+
+use crate::core::action::ACTION_SCRIPT;
+use crate::core::control_files::ControlConfig;
+use crate::core::mount_strategy::MountStrategy;
+use crate::core::env_file::ENV_LOCAL_FILE;
+use crate::core::events::record_event_or_warn;
+use crate::core::gitignore::{ensure_project_ignores, ensure_rmmp_gitignore};
+use crate::core::layout::{init_dirs, rmmp_path, RMMP_DIR};
+use crate::core::guard::ensure_safe_target;
 use crate::core::rmm_core::{
     Author, BuildConfig, BuildSystem, ModuleProp, ProjectInfo, 
-    RmakeConfig, RmmProject, SrcConfig, UrlsInfo, GitAnalyzer, GitInfo
+    RmakeConfig, RmmCore, RmmProject, SrcConfig, UrlsInfo, GitInfo
 };
+use crate::core::project_id::generate_project_uuid;
+use crate::core::toml_doc::write_toml;
+use crate::core::update_json::{verify_channel, ChannelConfig, UpdateConfig};
+use crate::core::vcs::{analyze_vcs_info, vcs_user};
+
+/// 可选生成的启动脚本
+pub const BOOT_SCRIPTS: [&str; 5] = ["post-fs-data.sh", "post-mount.sh", "service.sh", "boot-completed.sh", "uninstall.sh"];
+
+/// 项目创建选项：CLI 与 Python 绑定共用
+#[derive(Debug, Clone, Default)]
+pub struct ProjectOptions {
+    /// 项目 ID（module.prop 中的 id）
+    pub id: String,
+    /// 作者，为空时从 Git 配置获取
+    pub author: String,
+    /// 邮箱，为空时从 Git 配置获取
+    pub email: String,
+    /// 将邮箱写入 rmmproject.toml（默认不写入，避免随源码包发布）
+    pub include_email: bool,
+    /// 初始化后应用的已安装模板
+    pub template: Option<String>,
+    /// 许可证 SPDX 标识，None 时使用 GitHub 仓库声明的许可证，否则 MIT
+    pub license: Option<String>,
+    /// 发布通道，写入 Rmake.toml [update.channels.<name>]
+    pub channels: Vec<String>,
+    /// 目录不在 Git 仓库中时执行 git init
+    pub git_init: bool,
+    /// 初始版本，如 v0.1.0；None 时使用默认版本
+    pub version: Option<String>,
+    /// 生成的启动脚本，取值见 BOOT_SCRIPTS
+    pub boot_scripts: Vec<String>,
+    /// 允许在主目录、根目录等位置初始化
+    pub force: bool,
+    /// 挂载方式（magic / overlayfs / bind），写入 Rmake.toml [control] mount 并生成对应的目录结构
+    pub mount: Option<String>,
+}
+
+impl ProjectOptions {
+    /// 校验选项，避免生成一半文件后才失败
+    pub fn validate(&self) -> Result<()> {
+        // 验证项目ID格式（符合KernelSU要求）
+        // ID必须与这个正则表达式匹配：^[a-zA-Z][a-zA-Z0-9._-]+$
+        // 例如：✓ a_module，✓ a.module，✓ module-101，✗ a module，✗ 1_module，✗ -a-module
+        let id_regex = regex::Regex::new(r"^[a-zA-Z][a-zA-Z0-9._-]+$").unwrap();
+        if !id_regex.is_match(&self.id) {
+            anyhow::bail!("项目ID格式无效。必须以字母开头，只能包含字母、数字、点、下划线和连字符，且至少2个字符");
+        }
+        for script in &self.boot_scripts {
+            if !BOOT_SCRIPTS.contains(&script.as_str()) {
+                anyhow::bail!("未知的启动脚本: '{}'，可选: {}", script, BOOT_SCRIPTS.join(", "));
+            }
+        }
+        for channel in &self.channels {
+            verify_channel(channel, &ChannelConfig::default())?;
+        }
+        self.mount_strategy()?;
+        if let Some(ref version) = self.version
+            && (version.trim().is_empty() || version.contains(['\n', '\r']))
+        {
+            anyhow::bail!("无效的初始版本: '{}'", version);
+        }
+        if let Some(ref license) = self.license
+            && (license.is_empty() || !license.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')))
+        {
+            anyhow::bail!("无效的许可证 SPDX 标识: '{}'", license);
+        }
+        Ok(())
+    }
+
+    pub fn mount_strategy(&self) -> Result<Option<MountStrategy>> {
+        self.mount.as_deref().map(str::parse).transpose()
+    }
+}
+
+/// 创建项目：初始化文件、注册到 meta.toml、信任构建命令并应用模板
+pub fn create_project(project_path: &Path, options: &ProjectOptions) -> Result<()> {
+    let project_path = project_path.canonicalize().unwrap_or_else(|_| project_path.to_path_buf());
+    init_project(&project_path, options)?;
+
+    let core = RmmCore::new();
+    let registered = core.get_meta_config().and_then(|mut meta| {
+        let path = project_path.to_string_lossy().to_string();
+        core.track_project(&mut meta, &options.id, &path)?;
+        meta.projects.insert(options.id.clone(), path);
+        core.update_meta_config(&meta)
+    });
+    if let Err(e) = registered {
+        eprintln!("⚠️ 警告: 无法更新 meta 配置: {}", e);
+    }
+    // 自己创建的项目默认信任其构建命令
+    if let Err(e) = crate::cmds::build::trust_project_commands(&project_path) {
+        eprintln!("⚠️ 警告: 无法记录项目信任: {}", e);
+    }
+
+    match options.template {
+        Some(ref name) => {
+            let (author, email) = get_smart_user_info(&options.author, &options.email, &project_path)?;
+            let vars = BTreeMap::from([
+                ("project_id", options.id.clone()),
+                ("author", author),
+                ("email", if options.include_email { email } else { String::new() }),
+            ]);
+            crate::cmds::template::apply_to_project(name, &project_path, &vars)
+                .map_err(|e| anyhow::anyhow!("应用模板失败: {}", e))?;
+        }
+        None => crate::cmds::template::offer_templates(),
+    }
+    Ok(())
+}
 
 /// 初始化新的模块项目
-pub fn init_project(project_path: &Path, project_id: &str, author: &str, email: &str) -> Result<()> {
+pub fn init_project(project_path: &Path, options: &ProjectOptions) -> Result<()> {
     let project_path = project_path.canonicalize().unwrap_or_else(|_| project_path.to_path_buf());
+    let project_id = options.id.as_str();
+    let mount = options.mount_strategy()?;
       // 确保项目目录存在
     if !project_path.exists() {
         anyhow::bail!("项目目录不存在: {}", project_path.display());
-    }    // 检查是否已经是一个项目，如果是，则打印警告而不是直接退出
+    }
+    // 拒绝在主目录、根目录等位置生成项目文件
+    ensure_safe_target(&project_path, "init", options.force)?;
+    options.validate()?;    // 检查是否已经是一个项目，如果是，则打印警告而不是直接退出
     if project_path.join("module.prop").exists() || project_path.join(".rmmp").exists() {
         println!("{} 检测到目录已包含项目文件，将跳过已存在的文件和目录。", "⚠️ ".yellow().bold());
     } else {
         println!("{} 正在初始化模块项目: {}", "🚀".green().bold(), project_id.cyan().bold());
-    }// 验证项目ID格式（符合KernelSU要求）
-    // ID必须与这个正则表达式匹配：^[a-zA-Z][a-zA-Z0-9._-]+$
-    // 例如：✓ a_module，✓ a.module，✓ module-101，✗ a module，✗ 1_module，✗ -a-module
-    let id_regex = regex::Regex::new(r"^[a-zA-Z][a-zA-Z0-9._-]+$").unwrap();
-    if !id_regex.is_match(project_id) {
-        anyhow::bail!("项目ID格式无效。必须以字母开头，只能包含字母、数字、点、下划线和连字符，且至少2个字符");
     }
 
-    // 获取智能用户信息
-    let (smart_author, smart_email) = get_smart_user_info(author, email, &project_path)?;
+    // 获取智能用户信息；邮箱仅在明确要求时写入
+    let (smart_author, smart_email) = get_smart_user_info(&options.author, &options.email, &project_path)?;
+    let smart_email = if options.include_email { smart_email } else { String::new() };
+
+    if options.git_init && Repository::discover(&project_path).is_err() {
+        Repository::init(&project_path)?;
+        println!("{} 初始化 Git 仓库", "[+]".green().bold());
+    }
 
     // 检测 Git 信息
-    let git_info = GitAnalyzer::analyze_git_info(&project_path)?;
+    let git_info = analyze_vcs_info(&project_path)?;
     
     if let Some(ref git) = git_info {
-        println!("{} 检测到 Git 仓库", "🔍".yellow().bold());
+        match git.vcs {
+            "none" => println!("{} 未使用版本控制，使用 [vcs] 配置的仓库信息", "🔍".yellow().bold()),
+            "hg" => println!("{} 检测到 Mercurial 仓库", "🔍".yellow().bold()),
+            _ => println!("{} 检测到 Git 仓库", "🔍".yellow().bold()),
+        }
         println!("  {}: {}", 
             "分支".cyan().bold(), 
             git.branch.green().bold()
@@ -64,6 +193,9 @@ pub fn init_project(project_path: &Path, project_id: &str, author: &str, email:
         println!();
     }
 
+    // 远程为 GitHub 且配置了 token 时，读取仓库描述、许可证与主题
+    let repo_metadata = detect_repo_metadata(&git_info).unwrap_or_default();
+
     println!("{} 正在初始化模块项目: {}", 
         "🚀".green().bold(), 
         project_id.cyan().bold()
@@ -73,23 +205,32 @@ pub fn init_project(project_path: &Path, project_id: &str, author: &str, email:
     create_rmmp_structure(&project_path)?;
 
     // 2. 创建Rmake.toml
-    create_rmake_config(&project_path)?;    // 3. 创建rmmproject.toml
-    create_project_config(&project_path, project_id, &smart_author, &smart_email, &git_info)?;
+    create_rmake_config(&project_path, &options.channels, mount)?;    // 3. 创建rmmproject.toml
+    create_project_config(&project_path, project_id, &smart_author, &smart_email, &git_info, &repo_metadata)?;
 
     // 4. 创建module.prop
-    create_module_prop(&project_path, project_id, &smart_author, &git_info)?;
+    create_module_prop(&project_path, project_id, &smart_author, options.version.as_deref(), &git_info, &repo_metadata)?;
 
     // 5. 创建system目录
-    create_system_structure(&project_path)?;
+    create_system_structure(&project_path, mount)?;
 
     // 6. 创建customize.sh
     create_customize_script(&project_path)?;
 
-    // 7. 创建update.json
-    create_update_json(&project_path, project_id, &git_info)?;
+    // 7. 创建action.sh
+    create_action_script(&project_path)?;
 
-    // 8. 创建其他推荐文件
-    create_documentation_files(&project_path, project_id)?;println!();
+    // 8. 创建启动脚本
+    create_boot_scripts(&project_path, &options.boot_scripts)?;
+
+    // 9. 创建update.json
+    create_update_json(&project_path, project_id, options.version.as_deref(), &git_info)?;
+
+    // 10. 创建其他推荐文件
+    let license = options.license.as_deref().or(repo_metadata.license_spdx.as_deref()).unwrap_or("MIT");
+    create_documentation_files(&project_path, project_id, &smart_author, license)?;
+
+    record_event_or_warn(&project_path, "init", &[("id", project_id.to_string())]);println!();
     println!("{} 模块项目初始化完成！", "🎉".green().bold());
     println!("{} 项目路径: {}", 
         "📁".cyan().bold(), 
@@ -145,18 +286,13 @@ fn get_smart_user_info(author: &str, email: &str, project_path: &Path) -> Result
     Ok((final_author, final_email))
 }
 
-/// 从Git配置获取用户信息
+/// 从版本控制配置获取用户信息（git config / hg ui.username）
 fn get_git_user_config(project_path: &Path) -> Result<(String, String)> {
-    // 尝试从项目级Git配置获取
-    if let Ok(repo) = Repository::open(project_path) {
-        if let Ok(config) = repo.config() {
-            let name = config.get_string("user.name").unwrap_or_default();
-            let email = config.get_string("user.email").unwrap_or_default();
-            return Ok((name, email));
-        }
+    if let Some(user) = vcs_user(project_path) {
+        return Ok(user);
     }
     
-    // 如果项目级配置不可用，尝试全局配置
+    // 如果项目级配置不可用，尝试全局 Git 配置
     if let Ok(config) = Config::open_default() {
         let name = config.get_string("user.name").unwrap_or_default();
         let email = config.get_string("user.email").unwrap_or_default();
@@ -168,36 +304,42 @@ fn get_git_user_config(project_path: &Path) -> Result<(String, String)> {
 
 /// 创建.rmmp目录结构
 fn create_rmmp_structure(project_path: &Path) -> Result<()> {
-    let rmmp_dir = project_path.join(".rmmp");
-    let build_dir = rmmp_dir.join("build");
-    let dist_dir = rmmp_dir.join("dist");
+    let rmmp_dir = project_path.join(RMMP_DIR);
 
     if rmmp_dir.exists() {
-        println!("{} 目录 {} 已存在，跳过创建。", "[!]".yellow().bold(), ".rmmp".cyan().bold());
+        println!("{} 目录 {} 已存在，跳过创建。", "[!]".yellow().bold(), RMMP_DIR.cyan().bold());
     } else {
         fs::create_dir_all(&rmmp_dir)?;
-        println!("{} 创建 {} 目录结构", "[+]".green().bold(), ".rmmp".cyan().bold());
+        println!("{} 创建 {} 目录结构", "[+]".green().bold(), RMMP_DIR.cyan().bold());
     }
 
-    if build_dir.exists() {
-        println!("{} 目录 {} 已存在，跳过创建。", "[!]".yellow().bold(), ".rmmp/build".cyan().bold());
-    } else {
-        fs::create_dir_all(&build_dir)?;
-        println!("{} 创建 {} 目录", "[+]".green().bold(), ".rmmp/build".cyan().bold());
+    for entry in init_dirs() {
+        let display = format!("{}/{}", RMMP_DIR, entry.name);
+        let dir = rmmp_path(project_path, entry.name);
+        if dir.exists() {
+            println!("{} 目录 {} 已存在，跳过创建。", "[!]".yellow().bold(), display.cyan().bold());
+        } else {
+            fs::create_dir_all(&dir)?;
+            println!("{} 创建 {} 目录", "[+]".green().bold(), display.cyan().bold());
+        }
     }
 
-    if dist_dir.exists() {
-        println!("{} 目录 {} 已存在，跳过创建。", "[!]".yellow().bold(), ".rmmp/dist".cyan().bold());
+    if ensure_rmmp_gitignore(project_path)? {
+        println!("{} 创建 {} 文件", "[+]".green().bold(), ".rmmp/.gitignore".cyan().bold());
     } else {
-        fs::create_dir_all(&dist_dir)?;
-        println!("{} 创建 {} 目录", "[+]".green().bold(), ".rmmp/dist".cyan().bold());
+        println!("{} 文件 {} 已存在，跳过创建。", "[!]".yellow().bold(), ".rmmp/.gitignore".cyan().bold());
+    }
+
+    // 本机环境文件可能包含密钥，不应提交
+    if ensure_project_ignores(project_path, &[ENV_LOCAL_FILE])? {
+        println!("{} 将 {} 加入 {}", "[+]".green().bold(), ENV_LOCAL_FILE.cyan().bold(), ".gitignore".cyan().bold());
     }
     Ok(())
 }
 
 /// 作者注：重复实现，主要是为了稳定性 这个是内部调用的办法。 rmmcore主要是设计给给外部调用的
 /// 创建Rmake.toml配置文件
-fn create_rmake_config(project_path: &Path) -> Result<()> {
+fn create_rmake_config(project_path: &Path, channels: &[String], mount: Option<MountStrategy>) -> Result<()> {
     let rmake_path = project_path.join(".rmmp").join("Rmake.toml");
     
     if rmake_path.exists() {
@@ -219,6 +361,14 @@ fn create_rmake_config(project_path: &Path) -> Result<()> {
                 "*.tmp".to_string(), 
                 "*.log".to_string()
             ],
+            exclude_presets: Vec::new(),
+            allow: Vec::new(),
+            generate: Vec::new(),
+            recovery: false,
+            permissions: Default::default(),
+            checksums: None,
+            line_endings: Default::default(),
+            symbols: None,
             prebuild: vec!["echo 'Starting build'".to_string()],
             build: vec!["rmm".to_string()],
             postbuild: vec!["echo 'Build completed'".to_string()],
@@ -247,11 +397,26 @@ fn create_rmake_config(project_path: &Path) -> Result<()> {
                 scripts
             }),
         },
+        update: (!channels.is_empty()).then(|| UpdateConfig {
+            channels: channels.iter().map(|name| (name.clone(), ChannelConfig::default())).collect(),
+            ..Default::default()
+        }),
+        action: None,
+        check: None,
+        timeouts: None,
+        publish: None,
+        uninstall: None,
+        bootloop_guard: None,
+        control: mount.map(|mount| ControlConfig { mount: Some(mount), ..Default::default() }),
+        assets: Default::default(),
+        third_party: Default::default(),
+        vcs: None,
+        module: None,
+        sign: None,
     };
     
-    let rmake_content = toml::to_string_pretty(&rmake_config)?;
-    // 保存到 .rmmp/Rmake.toml
-    fs::write(&rmake_path, rmake_content)?;
+    // 保存到 .rmmp/Rmake.toml（--force 覆盖时保留已有注释）
+    write_toml(&rmake_path, &rmake_config)?;
     println!("{} 创建 {}", 
         "[+]".green().bold(), 
         ".rmmp/Rmake.toml".cyan().bold()
@@ -260,7 +425,7 @@ fn create_rmake_config(project_path: &Path) -> Result<()> {
 }
 
 /// 创建项目配置文件
-fn create_project_config(project_path: &Path, project_id: &str, author: &str, email: &str, git_info: &Option<GitInfo>) -> Result<()> {
+fn create_project_config(project_path: &Path, project_id: &str, author: &str, email: &str, git_info: &Option<GitInfo>, metadata: &RepoMetadata) -> Result<()> {
     let project_config_path = project_path.join("rmmproject.toml");
     
     if project_config_path.exists() {
@@ -297,11 +462,14 @@ fn create_project_config(project_path: &Path, project_id: &str, author: &str, em
     let project_config = RmmProject {
         project: ProjectInfo {
             id: project_id.to_string(),
-            description: format!("A Rmm project: {}", project_id),
+            description: metadata.description.clone()
+                .unwrap_or_else(|| format!("A Rmm project: {}", project_id)),
             readme: "README.md".to_string(),
             changelog: "CHANGELOG.md".to_string(),
             license: "LICENSE".to_string(),
             dependencies: Vec::new(),
+            script_dependencies: Vec::new(),
+            keywords: metadata.topics.clone(),
             scripts: Some({
                 let mut scripts = HashMap::new();
                 scripts.insert("build".to_string(), "rmm build".to_string());
@@ -309,6 +477,7 @@ fn create_project_config(project_path: &Path, project_id: &str, author: &str, em
                 scripts.insert("test".to_string(), "rmm test".to_string());
                 scripts
             }),
+            uuid: Some(generate_project_uuid()),
         },
         authors: vec![Author {
             name: author.to_string(),
@@ -322,10 +491,11 @@ fn create_project_config(project_path: &Path, project_id: &str, author: &str, em
             build_backend: "rmm".to_string(),
         }),
         tool: None,
+        verify: None,
+        migrations: Vec::new(),
     };
 
-    let project_content = toml::to_string_pretty(&project_config)?;
-    fs::write(&project_config_path, project_content)?;
+    write_toml(&project_config_path, &project_config)?;
     println!("{} 创建 {}", 
         "[+]".green().bold(), 
         "rmmproject.toml".cyan().bold()
@@ -334,7 +504,7 @@ fn create_project_config(project_path: &Path, project_id: &str, author: &str, em
 }
 
 /// 创建module.prop文件
-fn create_module_prop(project_path: &Path, project_id: &str, author: &str, git_info: &Option<GitInfo>) -> Result<()> {
+fn create_module_prop(project_path: &Path, project_id: &str, author: &str, version: Option<&str>, git_info: &Option<GitInfo>, metadata: &RepoMetadata) -> Result<()> {
     let module_prop_path = project_path.join("module.prop");
     
     if module_prop_path.exists() {
@@ -362,10 +532,13 @@ fn create_module_prop(project_path: &Path, project_id: &str, author: &str, git_i
         id: project_id.to_string(),
         name: format!("{} Module", 
             project_id.chars().next().unwrap().to_uppercase().to_string() + &project_id[1..]),
-        version: env!("CARGO_PKG_VERSION").to_string(),
+        version: version.unwrap_or(env!("CARGO_PKG_VERSION")).to_string(),
         version_code: version_code.to_string(),
         author: author.to_string(),
-        description: format!("A rmm project: {}", project_id),
+        // module.prop 为单行格式，去掉描述中的换行
+        description: metadata.description.as_deref()
+            .map(|d| d.replace(['\r', '\n'], " "))
+            .unwrap_or_else(|| format!("A rmm project: {}", project_id)),
         update_json: update_json_url,
     };
 
@@ -389,8 +562,20 @@ fn create_module_prop(project_path: &Path, project_id: &str, author: &str, git_i
     Ok(())
 }
 
-/// 创建system目录结构
-fn create_system_structure(project_path: &Path) -> Result<()> {
+/// 创建分区目录结构：bind 方式只能替换设备上已有的文件，不生成示例文件；overlayfs 额外生成 vendor/
+fn create_system_structure(project_path: &Path, mount: Option<MountStrategy>) -> Result<()> {
+    if mount == Some(MountStrategy::Bind) {
+        if !project_path.join("system").exists() {
+            fs::create_dir_all(project_path.join("system"))?;
+            println!("{} 创建 {} 目录（bind 方式只能替换设备上已存在的文件，请按设备上的路径放置）", "[+]".green().bold(), "system".cyan().bold());
+        }
+        return Ok(());
+    }
+    if mount == Some(MountStrategy::Overlayfs) && !project_path.join("vendor").exists() {
+        fs::create_dir_all(project_path.join("vendor/etc"))?;
+        println!("{} 创建 {} 目录（OverlayFS 会直接挂载到 /vendor）", "[+]".green().bold(), "vendor/etc".cyan().bold());
+    }
+
     let system_dir = project_path.join("system");
     let system_etc_dir = system_dir.join("etc");
     let example_conf_path = system_etc_dir.join("example.conf");
@@ -500,11 +685,72 @@ ui_print "- 模块安装完成"
     Ok(())
 }
 
+/// 创建action.sh（管理器 "Action" 按钮执行的脚本）
+fn create_action_script(project_path: &Path) -> Result<()> {
+    let action_script_path = project_path.join(ACTION_SCRIPT);
+    
+    if action_script_path.exists() {
+        println!("{} 文件 {} 已存在，跳过创建。", "[!]".yellow().bold(), ACTION_SCRIPT.cyan().bold());
+        return Ok(());
+    }
+
+    let action_script = r#"#!/system/bin/sh
+# 在 KernelSU / Magisk 管理器中点击模块的 "Action" 按钮时执行
+# 也可以删除此文件，改用 Rmake.toml 中的 [action] 配置生成菜单
+MODDIR=${0%/*}
+
+echo "- 模块目录: $MODDIR"
+echo "- 在这里添加你的操作"
+"#;
+
+    fs::write(&action_script_path, action_script)?;
+    
+    // 设置可执行权限（仅在Unix系统上）
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&action_script_path)?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&action_script_path, perms)?;
+    }
+    
+    println!("{} 创建 {}", 
+        "[+]".green().bold(), 
+        ACTION_SCRIPT.cyan().bold()
+    );
+    Ok(())
+}
+
+/// 创建选中的启动脚本（已存在的跳过）
+fn create_boot_scripts(project_path: &Path, scripts: &[String]) -> Result<()> {
+    for script in scripts {
+        let path = project_path.join(script);
+        if path.exists() {
+            println!("{} 文件 {} 已存在，跳过创建。", "[!]".yellow().bold(), script.cyan().bold());
+            continue;
+        }
+        let content = format!(
+            "#!/system/bin/sh\n# {} 由 Root 管理器在对应阶段执行\nMODDIR=${{0%/*}}\n\n# 在这里添加你的命令\n",
+            script
+        );
+        fs::write(&path, content)?;
+
+        // 设置可执行权限（仅在Unix系统上）
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        println!("{} 创建 {}", "[+]".green().bold(), script.cyan().bold());
+    }
+    Ok(())
+}
+
 /// 创建文档文件
-fn create_documentation_files(project_path: &Path, project_id: &str) -> Result<()> {
+fn create_documentation_files(project_path: &Path, project_id: &str, author: &str, license: &str) -> Result<()> {
     create_readme(project_path, project_id)?;
     create_changelog(project_path)?;
-    create_license(project_path)?;
+    create_license(project_path, author, license)?;
     Ok(())
 }
 
@@ -606,7 +852,7 @@ fn create_changelog(project_path: &Path) -> Result<()> {
 }
 
 /// 创建LICENSE文件
-fn create_license(project_path: &Path) -> Result<()> {
+fn create_license(project_path: &Path, author: &str, spdx: &str) -> Result<()> {
     let license_path = project_path.join("LICENSE");
     
     if license_path.exists() {
@@ -614,37 +860,11 @@ fn create_license(project_path: &Path) -> Result<()> {
         return Ok(());
     }
 
-    let license_content = r#"#在此处添加你的许可证
-    
-# 请不要移除以下许可信息
-MIT License
-
-Copyright (c) 2025 LIghtJUNction
-
-Permission is hereby granted, free of charge, to any person obtaining a copy
-of this software and associated documentation files (the "Software"), to deal
-in the Software without restriction, including without limitation the rights
-to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
-copies of the Software, and to permit persons to whom the Software is
-furnished to do so, subject to the following conditions:
-
-The above copyright notice and this permission notice shall be included in all
-copies or substantial portions of the Software.
-
-THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
-IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
-FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
-AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
-LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
-OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
-SOFTWARE.
-
-"#;
-
-    fs::write(&license_path, license_content)?;
-    println!("{} 创建 {}", 
+    fs::write(&license_path, license_text(spdx, author, Utc::now().year()))?;
+    println!("{} 创建 {} ({})", 
         "[+]".green().bold(), 
-        "LICENSE".cyan().bold()
+        "LICENSE".cyan().bold(),
+        spdx
     );
     Ok(())
 }
@@ -663,7 +883,7 @@ fn generate_update_json_url(remote_url: &str, project_id: &str) -> String {
 }
 
 /// 解析GitHub URL，返回 (owner, repo)
-fn parse_github_url(url: &str) -> Option<(String, String)> {
+pub(crate) fn parse_github_url(url: &str) -> Option<(String, String)> {
     let patterns = [
         r"github\.com[:/]([^/]+)/([^/\.]+)(?:\.git)?",
         r"github\.com/([^/]+)/([^/\.]+)",
@@ -685,6 +905,7 @@ fn parse_github_url(url: &str) -> Option<(String, String)> {
 fn create_update_json(
     project_path: &Path, 
     project_id: &str, 
+    initial_version: Option<&str>,
     git_info: &Option<GitInfo>
 ) -> Result<()> {
     let update_json_path = project_path.join("update.json");
@@ -702,7 +923,9 @@ fn create_update_json(
         now.year(), now.month(), now.day(), 1).parse().unwrap_or(2025061301);
     
     // 生成版本号
-    let version = if let Some(git) = git_info {
+    let version = if let Some(version) = initial_version {
+        version.to_string()
+    } else if let Some(git) = git_info {
         if let Some(commit_hash) = &git.last_commit_hash {
             format!("v0.1.0-{}", &commit_hash[..8])
         } else {
@@ -730,13 +953,15 @@ fn create_update_json(
                 project_id, project_id, version_code_int)
     };    // 生成 changelog URL，需要考虑项目的相对路径
     let changelog_url = if let Some(git) = git_info {
+        // [vcs] 中未配置分支时使用 main
+        let branch = if git.branch.is_empty() { "main" } else { git.branch.as_str() };
         if let Some(remote_url) = &git.remote_url {            if let Some((owner, repo)) = parse_github_url(remote_url) {
-                // 计算项目相对于 Git 仓库根目录的路径
-                let project_relative_path = if let Ok(repo_root) = get_git_repo_root(project_path) {
+                // 计算项目相对于仓库根目录的路径
+                let project_relative_path = {
                     // 规范化项目路径
                     let normalized_project_path = project_path.canonicalize().unwrap_or_else(|_| project_path.to_path_buf());
                     
-                    if let Ok(relative_path) = normalized_project_path.strip_prefix(&repo_root) {
+                    if let Ok(relative_path) = normalized_project_path.strip_prefix(&git.repo_root) {
                         if relative_path.as_os_str().is_empty() {
                             "CHANGELOG.md".to_string()
                         } else {
@@ -747,17 +972,15 @@ fn create_update_json(
                     } else {
                         "CHANGELOG.md".to_string()
                     }
-                } else {
-                    "CHANGELOG.md".to_string()
                 };
                 
                 format!("https://raw.githubusercontent.com/{}/{}/{}/{}", 
-                        owner, repo, git.branch, project_relative_path)
+                        owner, repo, branch, project_relative_path)
             } else {
-                format!("https://github.com/USER/REPO/raw/{}/CHANGELOG.md", git.branch)
+                format!("https://github.com/USER/REPO/raw/{}/CHANGELOG.md", branch)
             }
         } else {
-            format!("https://github.com/USER/REPO/raw/{}/CHANGELOG.md", git.branch)
+            format!("https://github.com/USER/REPO/raw/{}/CHANGELOG.md", branch)
         }
     } else {
         "https://github.com/USER/REPO/raw/main/CHANGELOG.md".to_string()
@@ -779,16 +1002,51 @@ fn create_update_json(
     Ok(())
 }
 
-/// 获取 Git 仓库的根目录
-fn get_git_repo_root(path: &Path) -> Result<PathBuf> {
-    let repo = git2::Repository::discover(path)
-        .map_err(|e| anyhow::anyhow!("无法找到 Git 仓库: {}", e))?;
-    
-    let workdir = repo.workdir()
-        .ok_or_else(|| anyhow::anyhow!("无法获取 Git 工作目录"))?;
-    
-    // 规范化路径，确保路径格式一致
-    Ok(workdir.canonicalize()?)
-}
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_init_project_with_options() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("demo_mod");
+        fs::create_dir_all(&project).unwrap();
+        let options = ProjectOptions {
+            id: "demo_mod".to_string(),
+            author: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            include_email: true,
+            license: Some("GPL-3.0-only".to_string()),
+            channels: vec!["beta".to_string()],
+            git_init: true,
+            version: Some("v1.2.0".to_string()),
+            boot_scripts: vec!["service.sh".to_string()],
+            mount: Some("overlayfs".to_string()),
+            ..Default::default()
+        };
+        init_project(&project, &options).unwrap();
+
+        assert!(project.join(".git").is_dir());
+        assert!(project.join("service.sh").is_file());
+        assert!(!project.join("post-fs-data.sh").exists());
+        assert!(fs::read_to_string(project.join("module.prop")).unwrap().contains("version=v1.2.0\n"));
+        assert!(fs::read_to_string(project.join("update.json")).unwrap().contains("\"version\": \"v1.2.0\""));
+        assert!(fs::read_to_string(project.join("LICENSE")).unwrap().contains("GPL-3.0-only"));
+        let rmake: RmakeConfig = toml::from_str(&fs::read_to_string(project.join(".rmmp/Rmake.toml")).unwrap()).unwrap();
+        assert!(rmake.update.unwrap().channels.contains_key("beta"));
+        assert_eq!(rmake.control.unwrap().mount, Some(MountStrategy::Overlayfs));
+        assert!(project.join("vendor/etc").is_dir());
+
+        let invalid = |options: ProjectOptions| options.validate().is_err();
+        assert!(invalid(ProjectOptions { id: "1bad".to_string(), ..Default::default() }));
+        assert!(invalid(ProjectOptions { boot_scripts: vec!["evil.sh".to_string()], ..options.clone() }));
+        assert!(invalid(ProjectOptions { channels: vec!["be ta".to_string()], ..options.clone() }));
+        assert!(invalid(ProjectOptions { license: Some("MIT\nx".to_string()), ..options.clone() }));
+        assert!(invalid(ProjectOptions { mount: Some("unionfs".to_string()), ..options.clone() }));
+        assert!(options.validate().is_ok());
+    }
+}
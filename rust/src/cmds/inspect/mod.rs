@@ -0,0 +1,50 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::io::Write;
+use std::path::Path;
+
+use crate::core::zip_inspect::{inspect_zip, read_zip_member};
+
+/// 检查构建好的模块 zip
+pub fn inspect(zip_path: &Path, json: bool, member: Option<&str>) -> Result<()> {
+    // 输出指定成员内容
+    if let Some(member) = member {
+        let content = read_zip_member(zip_path, member)?;
+        std::io::stdout().write_all(&content)?;
+        return Ok(());
+    }
+
+    let inspection = inspect_zip(zip_path)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&inspection)?);
+        return Ok(());
+    }
+
+    println!("{} {}", "[🔍]".cyan().bold(), inspection.path.bright_white());
+    println!("  大小: {} 字节", inspection.size);
+    println!("  SHA-256: {}", inspection.sha256.bright_black());
+
+    match &inspection.module_prop {
+        Some(prop) => {
+            println!("\n{} module.prop:", "[+]".green().bold());
+            for (key, value) in prop {
+                println!("  {} = {}", key.bright_white(), value);
+            }
+        }
+        None => println!("\n{} 缺少 module.prop", "[!]".yellow()),
+    }
+
+    let check = |ok: bool| if ok { "✅" } else { "❌" };
+    println!("\n{} 安装器:", "[+]".green().bold());
+    println!("  {} update-binary", check(inspection.has_update_binary));
+    println!("  {} updater-script", check(inspection.has_updater_script));
+
+    let files: Vec<_> = inspection.entries.iter().filter(|e| !e.is_dir).collect();
+    println!("\n{} 文件 ({}):", "[+]".green().bold(), files.len());
+    for entry in files {
+        let hash = entry.sha256.as_deref().unwrap_or("");
+        println!("  {} {:>10}  {}", &hash[..hash.len().min(12)].bright_black(), entry.size, entry.name);
+    }
+
+    Ok(())
+}
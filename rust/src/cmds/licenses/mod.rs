@@ -0,0 +1,43 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+use crate::cmds::device::read_module_id;
+use crate::core::licenses::{collect_licenses, license_warnings, render_licenses, LICENSES_FILE};
+use crate::core::rmm_core::RmmCore;
+
+/// rmm licenses：列出 [assets] 与 [third_party] 中的第三方组件及许可证，output 时写出 LICENSES-THIRD-PARTY
+pub fn list_licenses(project_path: &Path, json: bool, output: Option<&Path>) -> Result<()> {
+    let rmake = RmmCore::new().get_rmake_config(project_path)?;
+    let entries = collect_licenses(project_path, &rmake)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if let Some(output) = output {
+        fs::write(output, render_licenses(&read_module_id(project_path)?, &entries))?;
+        println!("{} 已写出 {}（{} 个组件）", "[+]".green().bold(), output.display(), entries.len());
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("{} 没有声明第三方组件（Rmake.toml [assets] / [third_party]）", "[i]".cyan());
+    }
+    for entry in &entries {
+        let license = match &entry.license {
+            Some(license) => license.green(),
+            None => "未声明".red(),
+        };
+        println!("  {} {} {}", entry.name.bright_white().bold(), entry.version.as_deref().unwrap_or("").yellow(), license);
+        println!("      {}", entry.paths.join(", ").bright_black());
+    }
+    let warnings = license_warnings(project_path, &rmake);
+    for message in &warnings {
+        println!("  {} {}", "⚠️".yellow(), message.yellow());
+    }
+    if !entries.is_empty() && warnings.is_empty() {
+        println!("{} 构建时会将许可证清单打包为 {}", "[+]".green().bold(), LICENSES_FILE);
+    }
+    Ok(())
+}
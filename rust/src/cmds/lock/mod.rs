@@ -0,0 +1,94 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::cmds::template::install_template;
+use crate::core::guard::is_project_dir;
+use crate::core::lockfile::{asset_key, fetch_asset, template_key, LockFile, ResourceKind, LOCK_FILE};
+use crate::core::rmm_core::RmmCore;
+use crate::core::storage::RmmStorage;
+
+/// 在项目的 rmm.lock 下执行远程获取；不是 RMM 项目时不锁定
+pub fn with_project_lock<T>(project_path: &Path, f: impl FnOnce(Option<&mut LockFile>) -> Result<T>) -> Result<T> {
+    if !is_project_dir(project_path) {
+        return f(None);
+    }
+    let mut lock = LockFile::load(project_path)?;
+    let before = lock.clone();
+    let result = f(Some(&mut lock))?;
+    if lock != before {
+        lock.save(project_path)?;
+        println!("{} 已更新 {}", "[lock]".cyan(), LOCK_FILE);
+    }
+    Ok(result)
+}
+
+/// rmm lock update：重新获取锁定的资源并刷新哈希；names 为空时刷新全部并移除不再使用的资源
+pub fn update_lock(project_path: &Path, names: &[String]) -> Result<()> {
+    let storage = RmmStorage::resolve();
+    let rmake = RmmCore::new().get_rmake_config(project_path)?;
+    let mut lock = LockFile::load(project_path)?;
+    let selected = |key: &str| names.is_empty() || names.iter().any(|n| key == n || key.split_once(':').is_some_and(|(_, name)| name == n));
+
+    for (name, asset) in &rmake.assets {
+        if !selected(&asset_key(name)) {
+            continue;
+        }
+        let old = lock.resources.get(&asset_key(name)).map(|r| r.sha256.clone());
+        fetch_asset(&mut lock, name, asset, &storage.cache_dir(), true)?;
+        print_status(&asset_key(name), old.as_deref(), &lock);
+    }
+
+    let templates: Vec<String> = lock.resources.iter()
+        .filter(|(key, r)| r.kind == ResourceKind::Template && selected(key))
+        .filter_map(|(key, _)| key.strip_prefix("template:").map(str::to_string))
+        .collect();
+    for spec in templates {
+        let key = template_key(&spec);
+        let old = lock.resources.get(&key).map(|r| r.sha256.clone());
+        install_template(&spec, &storage.templates_dir(), &storage.cache_dir(), true, Some(&mut lock), true)?;
+        print_status(&key, old.as_deref(), &lock);
+    }
+
+    if names.is_empty() {
+        let stale: Vec<String> = lock.resources.iter()
+            .filter(|(key, r)| r.kind == ResourceKind::Asset && !rmake.assets.keys().any(|name| asset_key(name) == **key))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stale {
+            lock.resources.remove(&key);
+            println!("  {} {} {}", "-".red(), key, "(已不在 Rmake.toml [assets] 中)".bright_black());
+        }
+    }
+    lock.save(project_path)?;
+    println!("{} 已刷新 {}（{} 项）", "[+]".green().bold(), LOCK_FILE, lock.resources.len());
+    Ok(())
+}
+
+/// 打印一项资源刷新前后的哈希
+fn print_status(key: &str, old: Option<&str>, lock: &LockFile) {
+    let new = lock.resources.get(key).map(|r| r.sha256.as_str()).unwrap_or_default();
+    match old {
+        Some(old) if old == new => println!("  {} {} {}", "=".bright_black(), key, "未变化".bright_black()),
+        Some(old) => println!("  {} {} {} → {}", "~".yellow(), key, &old[..old.len().min(12)], &new[..new.len().min(12)]),
+        None => println!("  {} {} {}", "+".green(), key, &new[..new.len().min(12)]),
+    }
+}
+
+/// rmm lock list
+pub fn list_lock(project_path: &Path, json: bool) -> Result<()> {
+    let lock = LockFile::load(project_path)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&lock.resources)?);
+        return Ok(());
+    }
+    if lock.resources.is_empty() {
+        println!("{} {} 中没有锁定的资源", "[i]".cyan(), LOCK_FILE);
+        return Ok(());
+    }
+    for (key, resource) in &lock.resources {
+        println!("  {} {}", key.green().bold(), resource.version.as_deref().unwrap_or("").yellow());
+        println!("      {} {}", resource.url, format!("sha256:{}", &resource.sha256[..resource.sha256.len().min(16)]).bright_black());
+    }
+    Ok(())
+}
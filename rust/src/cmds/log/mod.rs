@@ -0,0 +1,73 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::core::events::{read_events, ProjectEvent};
+
+/// 按类型筛选并保留最近 limit 条事件
+fn select_events(events: Vec<ProjectEvent>, kind: Option<&str>, limit: Option<usize>) -> Vec<ProjectEvent> {
+    let mut selected: Vec<ProjectEvent> = events
+        .into_iter()
+        .filter(|e| kind.is_none_or(|k| e.kind == k))
+        .collect();
+    if let Some(limit) = limit {
+        let skip = selected.len().saturating_sub(limit);
+        selected.drain(..skip);
+    }
+    selected
+}
+
+/// 显示项目事件日志
+pub fn show_log(project_path: &Path, kind: Option<&str>, limit: Option<usize>, json: bool) -> Result<()> {
+    let events = select_events(read_events(project_path)?, kind, limit);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+        return Ok(());
+    }
+    if events.is_empty() {
+        println!("{} 没有事件记录", "[i]".cyan());
+        return Ok(());
+    }
+
+    for event in &events {
+        let kind = match event.kind.as_str() {
+            "init" | "build" => event.kind.green(),
+            "version_bump" => event.kind.yellow(),
+            "publish" => event.kind.magenta(),
+            _ => event.kind.cyan(),
+        };
+        println!("{} {:<14} {} {}",
+            event.timestamp.bright_black(),
+            kind.bold(),
+            event.summary(),
+            format!("({})", event.actor).bright_black()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::events::record_event;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_select_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        record_event(project_path, "init", &[("id", "demo".to_string())]).unwrap();
+        record_event(project_path, "version_bump", &[("from", "v1".to_string()), ("to", "v2".to_string())]).unwrap();
+        record_event(project_path, "version_bump", &[("from", "v2".to_string()), ("to", "v3".to_string())]).unwrap();
+
+        let events = read_events(project_path).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[1].summary(), "v1 -> v2");
+
+        let bumps = select_events(events.clone(), Some("version_bump"), Some(1));
+        assert_eq!(bumps.len(), 1);
+        assert_eq!(bumps[0].details["to"], "v3");
+        assert_eq!(select_events(events, None, None).len(), 3);
+    }
+}
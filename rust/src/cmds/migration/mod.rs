@@ -0,0 +1,62 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::migrations::{load_migrations, migration_boilerplate, validate_migrations, MigrationConfig, MIGRATIONS_DIR};
+use crate::core::rmm_core::RmmCore;
+
+/// rmm migration new：生成迁移脚本模板并登记到 rmmproject.toml [[migrations]]
+pub fn new_migration(project_path: &Path, below: u64, name: Option<&str>) -> Result<PathBuf> {
+    let file_name = match name {
+        Some(name) if name.ends_with(".sh") => name.to_string(),
+        Some(name) => format!("{}.sh", name),
+        None => format!("migrate-below-{}.sh", below),
+    };
+    if file_name.contains(['/', '\\']) {
+        anyhow::bail!("迁移脚本名不能包含路径: {}", file_name);
+    }
+    let relative = format!("{}/{}", MIGRATIONS_DIR, file_name);
+    let path = project_path.join(&relative);
+    if path.exists() {
+        anyhow::bail!("{} 已存在", relative);
+    }
+
+    let core = RmmCore::new();
+    let mut project = core.get_project_config(project_path)?;
+    if project.migrations.iter().any(|m| m.script.trim_start_matches("./") == relative) {
+        anyhow::bail!("rmmproject.toml 中已声明 {}", relative);
+    }
+    fs::create_dir_all(project_path.join(MIGRATIONS_DIR))?;
+    fs::write(&path, migration_boilerplate(below))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    project.migrations.push(MigrationConfig { script: relative.clone(), below, since: None });
+    core.update_project_config(project_path, &project)?;
+
+    println!("{} 已创建 {}，覆盖安装 versionCode < {} 的版本时执行", "[+]".green().bold(), relative.cyan(), below);
+    Ok(path)
+}
+
+/// rmm migration list
+pub fn list_migrations(project_path: &Path) -> Result<()> {
+    let migrations = load_migrations(project_path)?;
+    if migrations.is_empty() {
+        println!("{} rmmproject.toml 中没有声明 [[migrations]]", "[i]".cyan());
+        return Ok(());
+    }
+    for migration in &migrations {
+        let range = match migration.since {
+            Some(since) => format!("{} ≤ versionCode < {}", since, migration.below),
+            None => format!("versionCode < {}", migration.below),
+        };
+        println!("  {} {}", migration.script.green(), range.bright_black());
+    }
+    for message in validate_migrations(project_path, &migrations) {
+        println!("  {} {}", "❌".red(), message.red());
+    }
+    Ok(())
+}
@@ -1,8 +1,38 @@
 pub mod rmmbox;
+pub mod alias;
 pub mod init;
 pub mod build;
 pub mod run;
 pub mod sync;
+pub mod env;
+pub mod ci;
+pub mod device;
+pub mod test;
+pub mod inspect;
+pub mod check;
+pub mod query;
+pub mod size;
+pub mod pages;
+pub mod template;
+pub mod log;
+pub mod registry;
+pub mod schema;
+pub mod clean;
+pub mod deps;
+pub mod prop;
+pub mod workspace;
+pub mod serve;
+pub mod daemon;
+pub mod lock;
+pub mod licenses;
+pub mod migration;
+pub mod rmm_self;
+pub mod upstream;
+pub mod completions;
+pub mod cache;
+pub mod watch;
+pub mod publish;
+pub mod sign;
 
 pub use rmmbox::RmmBox;
 
@@ -15,6 +45,42 @@ pub enum Commands {
     Init {
         /// 项目ID（同时作为文件夹名）
         project_id: String,
+        
+        /// 使用已安装的模板（见 rmm template list）
+        #[arg(short, long)]
+        template: Option<String>,
+        
+        /// 许可证 SPDX 标识，如 GPL-3.0-only（默认使用 GitHub 仓库声明的许可证或 MIT）
+        #[arg(long)]
+        license: Option<String>,
+        
+        /// 发布通道，写入 Rmake.toml [update.channels]（可重复）
+        #[arg(long, value_name = "NAME")]
+        channel: Vec<String>,
+        
+        /// 将作者邮箱写入 rmmproject.toml（默认不写入，也可设置 RMM_INCLUDE_EMAIL=1）
+        #[arg(long, default_value = "false")]
+        with_email: bool,
+        
+        /// 目录不在 Git 仓库中时执行 git init
+        #[arg(long, default_value = "false")]
+        git_init: bool,
+        
+        /// 初始版本，如 v0.1.0
+        #[arg(long, value_name = "VERSION")]
+        initial_version: Option<String>,
+        
+        /// 生成启动脚本，如 service.sh、post-fs-data.sh（可重复）
+        #[arg(long, value_name = "SCRIPT")]
+        boot_script: Vec<String>,
+        
+        /// 允许在主目录、根目录等位置初始化
+        #[arg(long, default_value = "false")]
+        force: bool,
+        
+        /// 目标 Root 方案的挂载方式，写入 Rmake.toml [control] mount
+        #[arg(long, value_parser = ["magic", "overlayfs", "bind"])]
+        mount: Option<String>,
     },    /// 🔨 构建模块项目
     Build {
         /// 项目路径（可选，默认为当前目录）
@@ -25,6 +91,54 @@ pub enum Commands {
         #[arg(long, default_value = "false")]
         no_auto_fix: bool,
         
+        /// 显示每个构建阶段运行或跳过的原因
+        #[arg(long, default_value = "false")]
+        explain: bool,
+        
+        /// 忽略构建缓存，强制完整构建
+        #[arg(long, default_value = "false")]
+        force: bool,
+        
+        /// 从上次失败的阶段继续构建（输入未变化时）
+        #[arg(long, default_value = "false")]
+        resume: bool,
+        
+        /// 调试构建：打包 rmmproject.toml [verify] 生成的 verify.sh
+        #[arg(long, default_value = "false")]
+        debug: bool,
+        
+        /// 同时生成 Recovery 卡刷包（也可在 Rmake.toml [build] 设置 recovery = true）
+        #[arg(long, default_value = "false")]
+        recovery: bool,
+        
+        /// 信任项目的构建命令并记录到 meta.toml（命令变更后需重新信任）
+        #[arg(long, default_value = "false")]
+        trust: bool,
+        
+        /// 在沙箱中运行 prebuild/postbuild（也可设置 RMM_SANDBOX=1；Windows/macOS 上仅隔离环境变量）
+        #[arg(long, default_value = "false")]
+        sandbox: bool,
+        
+        /// 签名产物：生成 <zip>.sig，并按 Rmake.toml [sign] 在 zip 中嵌入签名
+        #[arg(long, default_value = "false")]
+        sign: bool,
+        
+        /// 沙箱中额外允许写入的路径（可重复）
+        #[arg(long = "allow-path", value_name = "PATH")]
+        allow_path: Vec<String>,
+        
+        /// 沙箱中允许访问网络
+        #[arg(long, default_value = "false")]
+        allow_network: bool,
+        
+        /// 按依赖顺序构建项目所在工作区的全部成员（同 rmm workspace build）
+        #[arg(long, default_value = "false", conflicts_with = "script")]
+        workspace: bool,
+        
+        /// 只构建 meta.toml 中带有该标签的工作区成员（可重复，满足任一即可）
+        #[arg(long = "tag", value_name = "TAG", requires = "workspace")]
+        tags: Vec<String>,
+        
         /// 运行 Rmake.toml 中定义的脚本
         #[arg(value_name = "SCRIPT")]
         script: Option<String>,    },
@@ -39,6 +153,50 @@ pub enum Commands {
         script: Option<String>,
     },
     
+    /// 📤 发布到 GitHub Release（按 Rmake.toml [[publish.steps]] 执行，失败后重新运行从未完成的步骤继续）
+    Publish {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(value_name = "PROJECT_PATH")]
+        project_path: Option<String>,
+        
+        /// 发布通道（决定执行的步骤与发布说明模板）
+        #[arg(long, value_name = "NAME")]
+        channel: Option<String>,
+        
+        /// 丢弃上次未完成的进度，从第一步开始
+        #[arg(long, default_value = "false")]
+        restart: bool,
+        
+        /// 跳过 webhook 通知
+        #[arg(long, default_value = "false")]
+        no_notify: bool,
+    },
+    
+    /// 👀 监听项目文件，变化时增量重新构建
+    Watch {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 禁用 shellcheck 自动修复（默认启用自动修复）
+        #[arg(long, default_value = "false")]
+        no_auto_fix: bool,
+        
+        /// 调试构建：打包 rmmproject.toml [verify] 生成的 verify.sh
+        #[arg(long, default_value = "false")]
+        debug: bool,
+        
+        /// 信任项目的构建命令并记录到 meta.toml（命令变更后需重新信任）
+        #[arg(long, default_value = "false")]
+        trust: bool,
+    },
+    
+    /// 🔏 签名构建产物，管理 RMM_ROOT/keys 中的签名密钥（仅支持 ed25519，不支持 RSA / PEM 私钥）
+    Sign {
+        #[command(subcommand)]
+        command: SignCommands,
+    },
+    
     /// 🔄 同步项目元数据
     Sync {
         /// 特定项目名称（可选，默认同步所有项目）
@@ -53,15 +211,1029 @@ pub enum Commands {
         #[arg(short, long, value_delimiter = ',')]
         search_paths: Option<Vec<String>>,
         
-        /// 搜索最大深度
-        #[arg(short, long, default_value = "3")]        
+        /// 搜索最大深度（默认 3，可用 RMM_SYNC_MAX_DEPTH 设置）
+        #[arg(short, long)]
         max_depth: Option<usize>,
+        
+        /// 只同步带有该标签的已注册项目（可重复，满足任一即可）
+        #[arg(long = "tag", value_name = "TAG", conflicts_with = "project_name")]
+        tags: Vec<String>,
+    },
+    
+    /// 🌐 显示 RMM 运行环境
+    Env {
+        /// 仅显示 RMM_ROOT 等存储路径
+        #[arg(long, default_value = "false")]
+        paths: bool,
+
+        /// 显示所有分层配置项的取值与来源
+        #[arg(long, default_value = "false")]
+        config: bool,
+
+        /// 将旧版 ~/data/adb/.rmm 中的数据迁移到当前存储位置
+        #[arg(long, default_value = "false", conflicts_with_all = ["paths", "config"])]
+        migrate: bool,
+
+        /// 解析项目层配置时使用的项目路径（默认当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+    },
+    
+    /// ⚙️ 持续集成配置
+    Ci {
+        #[command(subcommand)]
+        command: CiCommands,
+    },
+    
+    /// 📱 设备模块快照与回滚
+    Device {
+        #[command(subcommand)]
+        command: DeviceCommands,
+    },
+    
+    /// 🧪 在设备上测试最新构建（自动快照与回滚）
+    Test {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+        
+        /// 测试通过后保留安装的构建，不回滚
+        #[arg(long, default_value = "false")]
+        keep: bool,
+        
+        /// 安装后不重启设备
+        #[arg(long, default_value = "false")]
+        no_reboot: bool,
+        
+        /// 等待开机完成的超时时间（秒）
+        #[arg(long, default_value = "120")]
+        boot_timeout: u64,
+        
+        /// 运行安装后验证（模块内的 verify.sh 或 rmmproject.toml [verify]）
+        #[arg(long, default_value = "false")]
+        verify: bool,
+        
+        /// 开机后截屏并保存到 .rmmp/test-report
+        #[arg(long, default_value = "false")]
+        screenshot: bool,
+        
+        /// 开机后录屏指定秒数（最长 180）并保存到 .rmmp/test-report
+        #[arg(long, value_name = "SECS")]
+        screenrecord: Option<u64>,
+        
+        /// 不连接设备，在主机上用 busybox sh 运行 tests/ 下的 *.sh 与 *.bats
+        #[arg(long, default_value = "false", conflicts_with_all = ["serial", "keep", "no_reboot", "verify", "screenshot", "screenrecord"])]
+        host: bool,
+    },
+    
+    /// 🔎 查询所有已注册项目的配置值
+    Query {
+        /// 点分配置键，如 project.scripts.build
+        #[arg(value_name = "KEY")]
+        key: String,
+        
+        /// 仅查询指定配置文件（project / rmake / prop / update）
+        #[arg(short, long)]
+        file: Option<String>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+    
+    /// 🔎 在所有已注册项目的配置文件中搜索
+    Grep {
+        /// 正则表达式
+        #[arg(value_name = "PATTERN")]
+        pattern: String,
+        
+        /// 忽略大小写
+        #[arg(short, long, default_value = "false")]
+        ignore_case: bool,
+    },
+    
+    /// 📜 查看项目事件日志
+    Log {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 只显示指定类型的事件（init、version_bump、build、publish、device_install）
+        #[arg(short, long)]
+        kind: Option<String>,
+        
+        /// 只显示最近 N 条
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+        
+        /// 以 JSON 输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+    
+    /// 🧩 模板管理
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommands,
+    },
+    
+    /// 🗂️ 导入/导出项目注册表（迁移到新机器）
+    Registry {
+        #[command(subcommand)]
+        command: RegistryCommands,
+    },
+    
+    /// 🧩 查看模块与脚本依赖
+    Deps {
+        #[command(subcommand)]
+        command: DepsCommands,
+    },
+    
+    /// 🏷️ 查看或修改 module.prop（带校验，版本字段同步到 update.json）
+    Prop {
+        #[command(subcommand)]
+        command: PropCommands,
+    },
+    
+    /// 🗃️ 工作区：按依赖顺序构建多个模块项目
+    Workspace {
+        #[command(subcommand)]
+        command: WorkspaceCommands,
+    },
+    
+    /// 🌍 发布 update.json 到 GitHub Pages
+    Pages {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 仅提交，不推送到 origin
+        #[arg(long, default_value = "false")]
+        no_push: bool,
+    },
+    
+    /// 📡 在局域网内提供 .rmmp/dist，用于测试设备的更新流程
+    Serve {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 监听地址
+        #[arg(long, default_value = "0.0.0.0")]
+        host: std::net::IpAddr,
+        
+        /// 监听端口
+        #[arg(long, default_value = "8080")]
+        port: u16,
+        
+        /// update.json 中使用的主机名或 IP（默认自动检测局域网地址）
+        #[arg(long)]
+        advertise: Option<String>,
+    },
+    
+    /// 🧹 清理构建输出（移入回收站，可撤销）
+    Clean {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 清理整个 .rmmp 目录
+        #[arg(long, default_value = "false")]
+        all: bool,
+        
+        /// 跳过项目标记与危险目录检查
+        #[arg(long, default_value = "false")]
+        force: bool,
+        
+        /// 撤销最近一次清理
+        #[arg(long, default_value = "false")]
+        undo: bool,
+        
+        /// 检查 .rmmp 目录约定，逐项迁移或清理旧版与未知条目
+        #[arg(long, default_value = "false")]
+        layout: bool,
+        
+        /// 配合 --layout：不询问，按建议处理旧版条目（未知条目保留）
+        #[arg(short, long, default_value = "false")]
+        yes: bool,
+    },
+    
+    /// 📦 显示产物大小历史与趋势
+    Size {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+    
+    /// ✅ 检查模块项目配置
+    Check {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 先从上游仓库更新已知问题数据库
+        #[arg(long, default_value = "false")]
+        update_db: bool,
+        
+        /// 在主机上以模拟的设备环境试运行 service.sh，发现死循环与找不到的命令
+        #[arg(long, default_value = "false")]
+        dry_run_service: bool,
+    },
+    
+    /// 🔍 检查构建好的模块 zip
+    Inspect {
+        /// zip 文件路径
+        #[arg(value_name = "ZIP")]
+        zip_path: String,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+        
+        /// 输出指定成员的内容（如 module.prop）
+        #[arg(short, long)]
+        member: Option<String>,
+    },
+    
+    /// 🔍 解释某个路径是否会被打包，以及由哪条规则决定
+    WhyExcluded {
+        /// 文件或目录路径（相对项目根目录或绝对路径）
+        #[arg(value_name = "PATH")]
+        path: String,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+    
+    /// 📐 输出配置文件的 JSON Schema，供编辑器校验与补全
+    Schema {
+        /// 配置类型：rmake、project、meta 或 all
+        #[arg(value_name = "TARGET")]
+        target: String,
+        
+        /// 输出文件（all 时为输出目录，默认当前目录）
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
+    /// ⏱️ 打包流程基准测试（内部命令）
+    #[command(hide = true)]
+    Bench {
+        /// 合成项目的文件数
+        #[arg(long, default_value = "2000")]
+        files: usize,
+        
+        /// 合成项目的总大小（MB）
+        #[arg(long, default_value = "50")]
+        size_mb: u64,
+        
+        /// 重复次数（取中位数）
+        #[arg(short = 'n', long, default_value = "5")]
+        iterations: usize,
+        
+        /// 基线文件（默认 RMM_ROOT/bench/baseline.json）
+        #[arg(long)]
+        baseline: Option<String>,
+        
+        /// 将本次结果保存为基线
+        #[arg(long, default_value = "false")]
+        save_baseline: bool,
+        
+        /// 任一阶段比基线慢超过该百分比时失败
+        #[arg(long, value_name = "PERCENT")]
+        max_regression: Option<f64>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+    
+    /// 🔒 管理 rmm.lock（远程模板与资源的来源和 sha256）
+    Lock {
+        #[command(subcommand)]
+        command: LockCommands,
+    },
+    
+    /// 📜 列出第三方组件的许可证（[assets] / [third_party]）
+    Licenses {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 以 JSON 输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+        
+        /// 将 LICENSES-THIRD-PARTY 写出到指定文件
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    
+    /// 🔀 管理覆盖安装旧版本时的数据迁移脚本
+    Migration {
+        #[command(subcommand)]
+        command: MigrationCommands,
+    },
+    
+    /// 🌐 上游母仓库：发布前检查模块 id 的命名空间归属
+    Upstream {
+        #[command(subcommand)]
+        command: UpstreamCommands,
+    },
+    
+    /// 🧰 管理项目使用的 rmm 版本
+    #[command(name = "self")]
+    RmmSelf {
+        #[command(subcommand)]
+        command: SelfCommands,
+    },
+    
+    /// 🛰️ 常驻进程：保持缓存常驻，CLI 在其运行时自动委托（设置 RMM_NO_DAEMON 可禁用）
+    Daemon {
+        #[command(subcommand)]
+        command: DaemonCommands,
+    },
+    
+    /// 🗃️ 查看 meta.toml、项目配置与仓库信息缓存，下载来源的健康统计与远程缓存
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
     },
     
     /// 显示版本信息
     Version,
     
+    /// ⌨️ 生成 shell 补全脚本（bash / zsh / fish 会从 adb 补全设备序列号与模块 ID）
+    Completions {
+        /// 目标 shell
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    
+    /// 补全脚本调用：打印光标位置的动态补全候选
+    #[command(name = "complete", hide = true)]
+    Complete {
+        /// 光标所在单词的下标
+        index: usize,
+        
+        /// 完整的命令行单词（含程序名）
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        words: Vec<String>,
+    },
+    
     /// 未匹配命令，外部转发
     #[command(external_subcommand)]
     External(Vec<String>),
 }
+
+/// CI 子命令
+#[derive(Debug, Subcommand)]
+pub enum CiCommands {
+    /// 生成 CI 工作流
+    Init {
+        /// 生成 GitHub Actions 工作流
+        #[arg(long, default_value = "false")]
+        github: bool,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 覆盖已存在的工作流文件
+        #[arg(long, default_value = "false")]
+        force: bool,
+    },
+}
+
+/// 模板子命令
+#[derive(Debug, Subcommand)]
+pub enum TemplateCommands {
+    /// 安装模板，如 github:user/repo#webui
+    Install {
+        /// 模板来源：github:user/repo#目录、git 地址或本地路径
+        #[arg(value_name = "SOURCE")]
+        source: String,
+        
+        /// 覆盖已安装的同名模板
+        #[arg(long, default_value = "false")]
+        force: bool,
+    },
+    /// 列出已安装的模板及来源
+    List,
+    /// 将模板提交到共享 git 仓库
+    Publish {
+        /// 模板名称（RMM_ROOT/templates 下的目录）
+        #[arg(value_name = "NAME")]
+        name: String,
+        
+        /// 共享仓库路径（不存在时自动创建）
+        #[arg(short, long)]
+        repo: String,
+        
+        /// 设置模板版本
+        #[arg(long)]
+        version: Option<String>,
+    },
+}
+
+/// 注册表子命令
+#[derive(Debug, Subcommand)]
+pub enum RegistryCommands {
+    /// 导出 meta.toml 中的项目为 JSON
+    Export {
+        /// 输出文件（默认输出到标准输出）
+        #[arg(short, long)]
+        output: Option<String>,
+        
+        /// 路径前缀重映射 OLD=NEW（可重复）
+        #[arg(long = "map", value_name = "OLD=NEW")]
+        map: Vec<String>,
+    },
+    /// 从导出的 JSON 导入项目
+    Import {
+        /// 导出文件路径
+        #[arg(value_name = "FILE")]
+        file: String,
+        
+        /// 路径前缀重映射 OLD=NEW（可重复）
+        #[arg(long = "map", value_name = "OLD=NEW")]
+        map: Vec<String>,
+        
+        /// 覆盖已存在的同名项目
+        #[arg(long, default_value = "false")]
+        overwrite: bool,
+    },
+    /// 为已注册项目添加标签
+    Tag {
+        /// 项目名称
+        #[arg(value_name = "PROJECT")]
+        project: String,
+        
+        /// 标签（可多个）
+        #[arg(value_name = "TAG", required = true)]
+        tags: Vec<String>,
+    },
+    /// 移除已注册项目的标签
+    Untag {
+        /// 项目名称
+        #[arg(value_name = "PROJECT")]
+        project: String,
+        
+        /// 标签（可多个）
+        #[arg(value_name = "TAG", required = true)]
+        tags: Vec<String>,
+    },
+    /// 列出已注册项目及其标签
+    List {
+        /// 只列出带有该标签的项目（可重复，满足任一即可）
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+}
+
+/// 设备子命令
+#[derive(Debug, Subcommand)]
+pub enum DeviceCommands {
+    /// 安装模块到设备（未指定 zip 时按设备 ABI 从 .rmmp/dist 选择；先安装设备上缺少的依赖模块）
+    Install {
+        /// 要安装的 zip 文件（可选）
+        #[arg(value_name = "ZIP")]
+        zip: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+        
+        /// 不使用 ADB：在局域网内提供 zip 并打印二维码，设备扫码下载后在管理器中安装
+        #[arg(long, default_value = "false", conflicts_with = "serial")]
+        qr: bool,
+        
+        /// 安装前校验产物（dist 中的 provenance.json、随包的 rmm-checksums.json 与 <zip>.sig），不一致时拒绝安装
+        #[arg(long, default_value = "false", conflicts_with = "qr")]
+        verify: bool,
+        
+        /// 同 --verify，且拒绝安装未由 RMM_ROOT/keys 中的公钥签名的产物
+        #[arg(long, default_value = "false", conflicts_with = "qr")]
+        strict: bool,
+        
+        /// --qr 时选择产物使用的设备 ABI（可多次指定，按优先级）
+        #[arg(long, requires = "qr")]
+        abi: Vec<String>,
+        
+        /// --qr 时监听的端口（默认随机）
+        #[arg(long, default_value = "0", requires = "qr")]
+        port: u16,
+        
+        /// 不检查 rmmproject.toml 中声明的模块依赖
+        #[arg(long, default_value = "false")]
+        no_deps: bool,
+        
+        /// 设备上缺少依赖时直接安装，不询问
+        #[arg(short, long, default_value = "false")]
+        yes: bool,
+        
+        /// 安装后直接重启设备，不询问
+        #[arg(long, default_value = "false", conflicts_with = "qr")]
+        reboot: bool,
+        
+        /// 安装后不重启设备，也不询问
+        #[arg(long, default_value = "false", conflicts_with_all = ["qr", "reboot"])]
+        no_reboot: bool,
+    },
+    /// 将公钥安装到设备的 /data/adb/rmm/keys，供模块脚本自行校验签名
+    InstallKey {
+        /// 公钥名称（RMM_ROOT/keys/<name>.pub）或文件路径；只有一个公钥时可省略
+        #[arg(value_name = "KEY")]
+        key: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+    },
+    
+    /// 为设备上的模块创建快照
+    Snapshot {
+        /// 模块ID（可选，默认读取项目 module.prop）
+        #[arg(value_name = "MODULE_ID")]
+        module_id: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+    },
+    
+    /// 将设备上的模块恢复到快照状态
+    Rollback {
+        /// 模块ID（可选，默认读取项目 module.prop）
+        #[arg(value_name = "MODULE_ID")]
+        module_id: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+        
+        /// 快照名称（默认最新快照）
+        #[arg(long)]
+        snapshot: Option<String>,
+        
+        /// 回滚后重启设备
+        #[arg(long, default_value = "false")]
+        reboot: bool,
+    },
+    
+    /// 执行设备上模块的 action.sh
+    Action {
+        /// 模块ID（可选，默认读取项目 module.prop）
+        #[arg(value_name = "MODULE_ID")]
+        module_id: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+    },
+    
+    /// 切换设备上已安装模块的控制文件（重复执行即取反），用于调试
+    Flag {
+        /// 控制文件
+        #[arg(value_parser = ["skip_mount", "disable", "remove"])]
+        flag: String,
+        
+        /// 模块ID（可选，默认读取项目 module.prop）
+        #[arg(value_name = "MODULE_ID")]
+        module_id: Option<String>,
+        
+        /// 创建控制文件
+        #[arg(long, default_value = "false", conflicts_with = "off")]
+        on: bool,
+        
+        /// 删除控制文件
+        #[arg(long, default_value = "false")]
+        off: bool,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+    },
+    
+    /// 读取并修改设备上已安装模块的 module.prop
+    PushProp {
+        /// 模块ID（可选，默认读取项目 module.prop）
+        #[arg(value_name = "MODULE_ID")]
+        module_id: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+        
+        /// 修改属性，如 --set version=v1.0-test（可重复）
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        
+        /// 使用 $EDITOR 编辑
+        #[arg(long, default_value = "false")]
+        edit: bool,
+        
+        /// 写回后重启管理器应用
+        #[arg(long, default_value = "false")]
+        restart: bool,
+    },
+    
+    /// 测量模块对开机时间的影响：交替启用 / 禁用模块多次重启，比较 logcat 中各启动阶段的平均时间
+    Perf {
+        /// 模块ID（可选，默认读取项目 module.prop）
+        #[arg(value_name = "MODULE_ID")]
+        module_id: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 设备序列号（adb -s）
+        #[arg(short, long)]
+        serial: Option<String>,
+        
+        /// 每种状态的重启次数
+        #[arg(long, default_value = "3")]
+        runs: usize,
+        
+        /// 等待开机完成的超时时间（秒）
+        #[arg(long, default_value = "120")]
+        boot_timeout: u64,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+    
+    /// 列出模块的快照
+    Snapshots {
+        /// 模块ID（可选，默认读取项目 module.prop）
+        #[arg(value_name = "MODULE_ID")]
+        module_id: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+    },
+    
+    /// 使用配对码与无线调试设备配对，并自动连接
+    Pair {
+        /// 配对地址（设备上“使用配对码配对”中显示的 ip:port）
+        #[arg(value_name = "IP:PORT")]
+        address: String,
+        
+        /// 6 位配对码（省略时交互输入）
+        #[arg(long)]
+        code: Option<String>,
+    },
+    
+    /// 连接无线调试设备并记录到 meta.toml（未指定地址时重新连接所有记录的设备）
+    Connect {
+        #[arg(value_name = "IP:PORT")]
+        address: Option<String>,
+    },
+    
+    /// 断开无线调试设备（未指定地址时断开所有记录的设备）
+    Disconnect {
+        #[arg(value_name = "IP:PORT")]
+        address: Option<String>,
+    },
+    
+    /// 列出已连接与记录的设备
+    List,
+    
+    /// 删除设备记录
+    Forget {
+        /// 设备地址（ip 或 ip:port）
+        #[arg(value_name = "ADDRESS")]
+        address: String,
+    },
+}
+
+/// 依赖子命令
+#[derive(Debug, Subcommand)]
+pub enum DepsCommands {
+    /// 显示解析后的依赖图（版本、来源、冲突与缺失项）
+    Tree {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+}
+
+/// module.prop 子命令
+#[derive(Debug, Subcommand)]
+pub enum PropCommands {
+    /// 显示属性值（未指定 KEY 时显示全部）
+    Get {
+        /// 属性名，如 version
+        key: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+    },
+    
+    /// 修改属性（保留其余行与注释）
+    Set {
+        /// 属性名，如 versionCode
+        key: String,
+        
+        /// 新值
+        value: String,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 允许修改 id 或不递增的 versionCode
+        #[arg(long, default_value = "false")]
+        force: bool,
+    },
+}
+
+/// 工作区子命令（rmmworkspace.toml）
+#[derive(Debug, Subcommand)]
+pub enum WorkspaceCommands {
+    /// 按构建顺序显示成员及其依赖
+    Graph {
+        /// 工作区内任意路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+    
+    /// 按拓扑顺序构建成员，复用未变化成员的构建缓存
+    Build {
+        /// 工作区内任意路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 只构建自该 Git 引用以来文件有变化的成员及依赖它们的成员
+        #[arg(long, value_name = "GIT_REF")]
+        affected_since: Option<String>,
+        
+        /// 只构建 meta.toml 中带有该标签的成员（可重复，满足任一即可）
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        
+        /// 忽略构建缓存，强制完整构建
+        #[arg(long, default_value = "false")]
+        force: bool,
+        
+        /// 信任各成员的构建命令并记录到 meta.toml
+        #[arg(long, default_value = "false")]
+        trust: bool,
+        
+        /// 在沙箱中运行 prebuild/postbuild（Windows/macOS 上仅隔离环境变量）
+        #[arg(long, default_value = "false")]
+        sandbox: bool,
+    },
+    
+    /// 检查成员产物之间是否提供了相同的系统文件
+    Conflicts {
+        /// 工作区内任意路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+}
+
+/// 常驻进程子命令
+#[derive(Debug, Subcommand)]
+pub enum DaemonCommands {
+    /// 启动常驻进程（默认在后台运行）
+    Start {
+        /// 在当前终端前台运行
+        #[arg(long, default_value = "false")]
+        foreground: bool,
+    },
+    
+    /// 停止常驻进程
+    Stop,
+    
+    /// 查看常驻进程状态
+    Status {
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+}
+
+/// 缓存子命令
+#[derive(Debug, Subcommand)]
+pub enum CacheCommands {
+    /// 各缓存的命中 / 未命中次数、条目数与有效期（常驻进程运行时显示其常驻缓存）
+    Stats {
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+    
+    /// 下载来源（直连与 network.mirrors 镜像）的健康统计
+    Mirrors {
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+        
+        /// 清空健康记录，暂停中的镜像重新参与竞速
+        #[arg(long, default_value = "false")]
+        reset: bool,
+    },
+    
+    /// 远程缓存（cache.remote）的配置，以及项目上次构建是否已在远程缓存中
+    Remote {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 上传项目上次构建的产物（忽略 cache.remote_push）
+        #[arg(long, default_value = "false")]
+        push: bool,
+    },
+}
+
+/// rmm.lock 子命令
+#[derive(Debug, Subcommand)]
+pub enum LockCommands {
+    /// 重新获取锁定的资源并刷新哈希
+    Update {
+        /// 只刷新指定资源（如 busybox、asset:busybox、template:github:user/repo）
+        #[arg(value_name = "NAME")]
+        names: Vec<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+    },
+    
+    /// 列出锁定的资源
+    List {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+}
+
+/// rmm self 子命令
+#[derive(Debug, Subcommand)]
+pub enum SelfCommands {
+    /// 将当前 rmm 版本写入 rmmproject.toml [build-system] requires
+    Pin {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 固定为完全相同的版本（==），默认兼容同一次版本（~=）
+        #[arg(long, default_value = "false")]
+        exact: bool,
+    },
+}
+
+/// rmm migration 子命令
+#[derive(Debug, Subcommand)]
+pub enum MigrationCommands {
+    /// 生成迁移脚本模板并登记到 rmmproject.toml [[migrations]]
+    New {
+        /// 设备上已安装的 versionCode 小于该值时执行
+        #[arg(long)]
+        below: u64,
+        
+        /// 脚本名称（默认 migrate-below-<below>.sh）
+        #[arg(value_name = "NAME")]
+        name: Option<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+    },
+    /// 列出声明的迁移脚本
+    List {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+    },
+}
+
+/// 上游母仓库子命令
+#[derive(Debug, Subcommand)]
+pub enum UpstreamCommands {
+    /// 按上游索引规则检查模块 id 是否属于 UPSTREAM_ACCESS_TOKEN 对应的用户或组织
+    Verify {
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// id 尚未登记时通过上游接口预留
+        #[arg(long, default_value = "false")]
+        reserve: bool,
+        
+        /// 以 JSON 格式输出
+        #[arg(long, default_value = "false")]
+        json: bool,
+    },
+}
+
+/// 签名子命令
+#[derive(Debug, Subcommand)]
+pub enum SignCommands {
+    /// 签名产物，生成 <file>.sig（未指定文件时签名 .rmmp/dist 中当前版本的 zip）
+    Artifacts {
+        /// 要签名的文件（可多个）
+        #[arg(value_name = "FILE")]
+        files: Vec<String>,
+        
+        /// 项目路径（可选，默认为当前目录）
+        #[arg(short, long)]
+        project_path: Option<String>,
+        
+        /// 私钥名称（RMM_ROOT/keys/<name>.key）或文件路径，默认读取 [sign] key；须为 rmm sign keygen 生成的 ed25519 私钥
+        #[arg(long)]
+        key: Option<String>,
+        
+        /// 同时签名 dist 中的 update.json 与通道清单
+        #[arg(long, default_value = "false")]
+        update_json: bool,
+    },
+    /// 生成 ed25519 密钥对（RMM_ROOT/keys/<name>.key 与 <name>.pub）
+    Keygen {
+        /// 密钥名称
+        #[arg(value_name = "NAME")]
+        name: String,
+        
+        /// 覆盖同名密钥
+        #[arg(long, default_value = "false")]
+        force: bool,
+    },
+    /// 列出 RMM_ROOT/keys 中的密钥
+    List,
+    /// 校验产物的签名、随包文件清单与 provenance.json 中的哈希
+    Verify {
+        /// 要校验的 zip
+        #[arg(value_name = "ZIP")]
+        zip: String,
+        
+        /// 拒绝未签名的产物
+        #[arg(long, default_value = "false")]
+        strict: bool,
+    },
+}
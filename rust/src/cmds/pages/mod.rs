@@ -0,0 +1,300 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use git2::{BranchType, Oid, Repository, Signature, Tree};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::cmds::device::read_module_id;
+use crate::cmds::init::parse_github_url;
+use crate::core::process::{CommandExt, CommandKind};
+use crate::core::rmm_core::RmakeConfig;
+use crate::core::update_json::{PagesConfig, UpdateBackend};
+
+/// 默认发布分支
+const DEFAULT_BRANCH: &str = "gh-pages";
+const FILE_MODE: i32 = 0o100644;
+const TREE_MODE: i32 = 0o040000;
+
+/// 发布目标
+#[derive(Debug, Clone, PartialEq)]
+enum PagesTarget {
+    /// 独立分支（如 gh-pages），文件位于分支根目录
+    Branch(String),
+    /// 当前分支中的目录（如 docs）
+    Dir(String),
+}
+
+impl PagesTarget {
+    fn from_config(config: Option<&PagesConfig>) -> Self {
+        match config {
+            Some(PagesConfig { dir: Some(dir), .. }) => PagesTarget::Dir(dir.trim_matches('/').to_string()),
+            Some(PagesConfig { branch: Some(branch), .. }) => PagesTarget::Branch(branch.clone()),
+            _ => PagesTarget::Branch(DEFAULT_BRANCH.to_string()),
+        }
+    }
+
+    /// 文件在目标树中的前缀
+    fn prefix(&self) -> Vec<String> {
+        match self {
+            PagesTarget::Branch(_) => Vec::new(),
+            PagesTarget::Dir(dir) => dir.split('/').filter(|p| !p.is_empty()).map(str::to_string).collect(),
+        }
+    }
+}
+
+/// 在树中写入（或替换）指定路径的 blob，返回新树 ID
+fn insert_path(repo: &Repository, base: Option<&Tree>, path: &[String], blob: Oid) -> Result<Oid> {
+    let mut builder = repo.treebuilder(base)?;
+    match path {
+        [name] => {
+            builder.insert(name, blob, FILE_MODE)?;
+        }
+        [name, rest @ ..] => {
+            let subtree = base
+                .and_then(|t| t.get_name(name))
+                .and_then(|e| e.to_object(repo).ok())
+                .and_then(|o| o.into_tree().ok());
+            let oid = insert_path(repo, subtree.as_ref(), rest, blob)?;
+            builder.insert(name, oid, TREE_MODE)?;
+        }
+        [] => anyhow::bail!("路径不能为空"),
+    }
+    Ok(builder.write()?)
+}
+
+/// 读取树中指定路径的子树
+fn subtree<'r>(repo: &'r Repository, tree: &Tree<'r>, path: &[String]) -> Option<Tree<'r>> {
+    if path.is_empty() {
+        return Some(tree.clone());
+    }
+    let entry = tree.get_path(Path::new(&path.join("/"))).ok()?;
+    entry.to_object(repo).ok()?.into_tree().ok()
+}
+
+/// 生成模块索引页：列出目标目录中所有包含 update.json 的模块
+fn render_index(repo: &Repository, root: &Tree) -> Result<String> {
+    let mut rows = String::new();
+    for entry in root.iter() {
+        let Some(name) = entry.name() else { continue };
+        let Some(module_tree) = entry.to_object(repo).ok().and_then(|o| o.into_tree().ok()) else {
+            continue;
+        };
+        let Some(manifest) = module_tree
+            .get_name("update.json")
+            .and_then(|e| e.to_object(repo).ok())
+            .and_then(|o| o.into_blob().ok())
+            .and_then(|b| serde_json::from_slice::<serde_json::Value>(b.content()).ok())
+        else {
+            continue;
+        };
+
+        let mut channels: Vec<String> = module_tree
+            .iter()
+            .filter_map(|e| e.name().map(str::to_string))
+            .filter_map(|n| n.strip_prefix("update-").and_then(|c| c.strip_suffix(".json")).map(str::to_string))
+            .collect();
+        channels.sort();
+        let channel_links: Vec<String> = channels
+            .iter()
+            .map(|c| format!("<a href=\"{0}/update-{1}.json\">{1}</a>", name, c))
+            .collect();
+
+        rows.push_str(&format!(
+            "      <tr><td>{id}</td><td>{version}</td><td>{code}</td><td><a href=\"{id}/update.json\">update.json</a></td><td>{channels}</td></tr>\n",
+            id = name,
+            version = manifest["version"].as_str().unwrap_or("-"),
+            code = manifest["versionCode"],
+            channels = channel_links.join(" "),
+        ));
+    }
+
+    Ok(format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n  <meta charset=\"utf-8\">\n  <title>Modules</title>\n</head>\n<body>\n  <h1>Modules</h1>\n  <table>\n    <thead><tr><th>ID</th><th>Version</th><th>versionCode</th><th>Manifest</th><th>Channels</th></tr></thead>\n    <tbody>\n{}    </tbody>\n  </table>\n  <p>Generated by rmm</p>\n</body>\n</html>\n",
+        rows
+    ))
+}
+
+/// 提交签名：优先使用 Git 配置，未配置时使用 rmm 默认身份
+fn signature(repo: &Repository) -> Result<Signature<'static>> {
+    Ok(repo.signature().or_else(|_| Signature::now("rmm", "rmm@localhost"))?)
+}
+
+/// 将模块的 update 清单发布到 GitHub Pages，返回提交 ID
+pub fn publish_pages(project_path: &Path, push: bool) -> Result<Oid> {
+    let rmake_path = project_path.join(".rmmp/Rmake.toml");
+    let rmake: RmakeConfig = toml::from_str(&fs::read_to_string(&rmake_path)?)?;
+    let update = rmake.update.as_ref();
+    if update.is_none_or(|u| u.backend != UpdateBackend::Pages) {
+        println!("{} Rmake.toml 中 [update] backend 不是 \"pages\"，仍按 GitHub Pages 发布", "[!]".yellow());
+    }
+    let target = PagesTarget::from_config(update.and_then(|u| u.pages.as_ref()));
+
+    let module_id = read_module_id(project_path)?;
+    let dist_dir = project_path.join(".rmmp/dist");
+    let mut manifests: Vec<(String, Vec<u8>)> = fs::read_dir(&dist_dir)
+        .with_context(|| "未找到 .rmmp/dist，请先运行 rmm build")?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let is_manifest = name == "update.json" || (name.starts_with("update-") && name.ends_with(".json"));
+            is_manifest.then(|| fs::read(e.path()).ok().map(|c| (name, c))).flatten()
+        })
+        .collect();
+    if !manifests.iter().any(|(n, _)| n == "update.json") {
+        anyhow::bail!("dist 中没有 update.json，请先运行 rmm build");
+    }
+    manifests.sort();
+
+    let repo = Repository::discover(project_path).with_context(|| "项目不在 Git 仓库中")?;
+    let (ref_name, parent) = match &target {
+        PagesTarget::Branch(branch) => {
+            let parent = repo.find_branch(branch, BranchType::Local)
+                .or_else(|_| repo.find_branch(&format!("origin/{}", branch), BranchType::Remote))
+                .ok()
+                .and_then(|b| b.get().peel_to_commit().ok());
+            (format!("refs/heads/{}", branch), parent)
+        }
+        PagesTarget::Dir(_) => ("HEAD".to_string(), repo.head().ok().and_then(|h| h.peel_to_commit().ok())),
+    };
+    let base_tree = parent.as_ref().map(|c| c.tree()).transpose()?;
+
+    // 写入模块清单
+    let prefix = target.prefix();
+    let mut files: Vec<(Vec<String>, Vec<u8>)> = manifests
+        .into_iter()
+        .map(|(name, content)| {
+            let mut path = prefix.clone();
+            path.push(module_id.clone());
+            path.push(name);
+            (path, content)
+        })
+        .collect();
+    if matches!(target, PagesTarget::Branch(_)) {
+        files.push((vec![".nojekyll".to_string()], Vec::new()));
+    }
+
+    let mut tree_oid = base_tree.as_ref().map(|t| t.id());
+    for (path, content) in &files {
+        let blob = repo.blob(content)?;
+        let current = tree_oid.map(|oid| repo.find_tree(oid)).transpose()?;
+        tree_oid = Some(insert_path(&repo, current.as_ref(), path, blob)?);
+    }
+
+    // 重新生成索引页
+    let tree = repo.find_tree(tree_oid.expect("至少写入了一个文件"))?;
+    let pages_root = subtree(&repo, &tree, &prefix).ok_or_else(|| anyhow::anyhow!("无法读取发布目录"))?;
+    let index = render_index(&repo, &pages_root)?;
+    let mut index_path = prefix.clone();
+    index_path.push("index.html".to_string());
+    let index_blob = repo.blob(index.as_bytes())?;
+    let tree_oid = insert_path(&repo, Some(&tree), &index_path, index_blob)?;
+    files.push((index_path, index.into_bytes()));
+
+    if base_tree.as_ref().is_some_and(|t| t.id() == tree_oid) {
+        println!("{} GitHub Pages 内容没有变化", "[i]".cyan());
+        return Ok(parent.map(|c| c.id()).unwrap_or_else(Oid::zero));
+    }
+
+    let tree = repo.find_tree(tree_oid)?;
+    let sig = signature(&repo)?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let message = format!("Publish update.json for {}", module_id);
+    let commit = repo.commit(Some(&ref_name), &sig, &sig, &message, &tree, &parents)?;
+    println!("{} 已提交到 {}: {}", "[+]".green().bold(), ref_name, &commit.to_string()[..7]);
+
+    // 目录模式：同步工作区与索引，避免提交后出现未暂存的差异
+    if let (PagesTarget::Dir(_), Some(workdir)) = (&target, repo.workdir()) {
+        let mut index = repo.index()?;
+        for (path, content) in &files {
+            let relative = path.join("/");
+            let full = workdir.join(&relative);
+            if let Some(parent) = full.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&full, content)?;
+            index.add_path(Path::new(&relative))?;
+        }
+        index.write()?;
+    }
+
+    if let Some((owner, repo_name)) = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|r| r.url().and_then(parse_github_url))
+    {
+        println!(
+            "{} updateJson 地址: https://{}.github.io/{}/{}/update.json",
+            "[i]".cyan(), owner, repo_name, module_id
+        );
+    }
+
+    if push {
+        let refspec = match &target {
+            PagesTarget::Branch(branch) => branch.clone(),
+            PagesTarget::Dir(_) => "HEAD".to_string(),
+        };
+        let workdir = repo.workdir().unwrap_or(project_path);
+        let status = Command::new("git")
+            .args(["push", "origin", &refspec])
+            .current_dir(workdir)
+            .status_with_timeout(CommandKind::Git)?;
+        if !status.success() {
+            anyhow::bail!("git push origin {} 失败", refspec);
+        }
+        println!("{} 已推送 {}", "[+]".green().bold(), refspec);
+    }
+
+    Ok(commit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup_repo(rmake: &str) -> (TempDir, Repository) {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let project = temp_dir.path();
+        fs::create_dir_all(project.join(".rmmp/dist")).unwrap();
+        fs::write(project.join(".rmmp/Rmake.toml"), rmake).unwrap();
+        fs::write(project.join("module.prop"), "id=demo\n").unwrap();
+        fs::write(
+            project.join(".rmmp/dist/update.json"),
+            r#"{"version":"v1","versionCode":1,"zipUrl":"https://x","changelog":"https://y"}"#,
+        ).unwrap();
+        fs::write(project.join(".rmmp/dist/update-beta.json"), "{}").unwrap();
+        (temp_dir, repo)
+    }
+
+    #[test]
+    fn test_publish_pages_branch() {
+        let (temp_dir, repo) = setup_repo("[build]\ninclude=[]\nexclude=[]\nprebuild=[]\nbuild=[]\npostbuild=[]\n\n[update]\nbackend = \"pages\"\n");
+        publish_pages(temp_dir.path(), false).unwrap();
+
+        let commit = repo.find_branch(DEFAULT_BRANCH, BranchType::Local).unwrap().get().peel_to_commit().unwrap();
+        let tree = commit.tree().unwrap();
+        assert!(tree.get_path(Path::new("demo/update.json")).is_ok());
+        assert!(tree.get_path(Path::new("demo/update-beta.json")).is_ok());
+        assert!(tree.get_path(Path::new(".nojekyll")).is_ok());
+        let index = tree.get_path(Path::new("index.html")).unwrap().to_object(&repo).unwrap().into_blob().unwrap();
+        let html = String::from_utf8_lossy(index.content()).to_string();
+        assert!(html.contains("demo/update.json") && html.contains("beta"));
+
+        // 内容未变化时不产生新提交
+        publish_pages(temp_dir.path(), false).unwrap();
+        let again = repo.find_branch(DEFAULT_BRANCH, BranchType::Local).unwrap().get().peel_to_commit().unwrap();
+        assert_eq!(commit.id(), again.id());
+    }
+
+    #[test]
+    fn test_publish_pages_dir() {
+        let (temp_dir, repo) = setup_repo("[build]\ninclude=[]\nexclude=[]\nprebuild=[]\nbuild=[]\npostbuild=[]\n\n[update]\nbackend = \"pages\"\n\n[update.pages]\ndir = \"docs\"\n");
+        publish_pages(temp_dir.path(), false).unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert!(head.tree().unwrap().get_path(Path::new("docs/demo/update.json")).is_ok());
+        assert!(temp_dir.path().join("docs/index.html").exists());
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+use crate::core::zip_inspect::{parse_module_prop, read_prop_text, replace_prop_values};
+
+/// 修改后需要同步到 update.json 的字段
+const VERSION_KEYS: [&str; 2] = ["version", "versionCode"];
+
+/// 校验一次修改：返回需要提示的警告；不满足且未指定 force 时返回错误
+pub fn validate_change(current: Option<&str>, key: &str, value: &str, force: bool) -> Result<Vec<String>> {
+    if key.is_empty() || key.contains(['=', '\n', '#']) || key.trim() != key {
+        anyhow::bail!("无效的属性名: '{}'", key);
+    }
+    if value.contains(['\n', '\r']) {
+        anyhow::bail!("属性值不能包含换行");
+    }
+
+    let mut warnings = Vec::new();
+    match key {
+        "id" => {
+            if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')) {
+                anyhow::bail!("无效的模块 id: '{}'", value);
+            }
+            if let Some(current) = current
+                && current != value
+            {
+                let message = format!("修改模块 id 会让设备把它当作新模块安装，旧模块 '{}' 不会被移除", current);
+                if !force {
+                    anyhow::bail!("{}；确认无误请使用 --force", message);
+                }
+                warnings.push(message);
+            }
+        }
+        "version" if value.trim().is_empty() => anyhow::bail!("version 不能为空"),
+        "versionCode" => {
+            let code: i64 = value.parse()
+                .map_err(|_| anyhow::anyhow!("versionCode 必须是整数: {}", value))?;
+            if let Some(previous) = current.and_then(|c| c.parse::<i64>().ok())
+                && code <= previous
+            {
+                let message = format!("versionCode 未递增（{} → {}），设备不会将其识别为更新", previous, code);
+                if !force {
+                    anyhow::bail!("{}；确认无误请使用 --force", message);
+                }
+                warnings.push(message);
+            }
+        }
+        _ => {}
+    }
+    Ok(warnings)
+}
+
+/// 在 module.prop 内容中设置属性：已有键原位替换（其余行原样保留），新键追加到末尾
+pub fn set_prop_value(content: &[u8], key: &str, value: &str) -> Vec<u8> {
+    let exists = content.split(|&b| b == b'\n')
+        .any(|line| line.strip_prefix(key.as_bytes()).is_some_and(|rest| rest.first() == Some(&b'=')));
+    if exists {
+        return replace_prop_values(content, &[(key, value)]);
+    }
+    let mut output = content.to_vec();
+    if !output.is_empty() && !output.ends_with(b"\n") {
+        output.push(b'\n');
+    }
+    output.extend_from_slice(format!("{}={}\n", key, value).as_bytes());
+    output
+}
+
+/// 将 version / versionCode 同步到项目根目录的 update.json（不存在时跳过），返回是否写入
+pub fn sync_update_json(project_path: &Path, key: &str, value: &str) -> Result<bool> {
+    let path = project_path.join("update.json");
+    if !VERSION_KEYS.contains(&key) || !path.exists() {
+        return Ok(false);
+    }
+    let content = fs::read_to_string(&path)?;
+    let mut json: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("无法解析 {}", path.display()))?;
+    let Some(object) = json.as_object_mut() else {
+        anyhow::bail!("{} 不是 JSON 对象", path.display());
+    };
+    let new_value = match key {
+        "versionCode" => serde_json::Value::from(value.parse::<i64>()?),
+        _ => serde_json::Value::from(value),
+    };
+    if object.get(key) == Some(&new_value) {
+        return Ok(false);
+    }
+    object.insert(key.to_string(), new_value);
+    fs::write(&path, serde_json::to_string_pretty(&json)?)?;
+    Ok(true)
+}
+
+/// rmm prop get：显示单个属性或全部属性
+pub fn get_prop(project_path: &Path, key: Option<&str>) -> Result<()> {
+    let props = parse_module_prop(&read_prop_text(&project_path.join("module.prop"))?);
+    match key {
+        Some(key) => {
+            let value = props.get(key).ok_or_else(|| anyhow::anyhow!("module.prop 中没有属性 '{}'", key))?;
+            println!("{}", value);
+        }
+        None => {
+            for (key, value) in &props {
+                println!("{}={}", key.bright_white(), value.green());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// rmm prop set：校验后修改 module.prop，版本字段同步到 update.json
+pub fn set_prop(project_path: &Path, key: &str, value: &str, force: bool) -> Result<()> {
+    let prop_path = project_path.join("module.prop");
+    let content = fs::read(&prop_path).with_context(|| format!("无法读取 {}", prop_path.display()))?;
+    let props = parse_module_prop(&read_prop_text(&prop_path)?);
+    let current = props.get(key).map(String::as_str);
+
+    for warning in validate_change(current, key, value, force)? {
+        println!("{} {}", "⚠️".yellow(), warning);
+    }
+    if current == Some(value) {
+        println!("{} {} 已是 {}", "[i]".cyan(), key.bright_white(), value.green());
+        return Ok(());
+    }
+
+    fs::write(&prop_path, set_prop_value(&content, key, value))?;
+    println!("{} {}: {} → {}",
+        "[+]".green().bold(),
+        key.bright_white(),
+        current.unwrap_or("(未设置)").bright_black(),
+        value.green()
+    );
+    if sync_update_json(project_path, key, value)? {
+        println!("{} 已同步 {} 到 update.json", "[+]".green().bold(), key);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_set_prop_validates_and_syncs_update_json() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path();
+        fs::write(project.join("module.prop"), "id=demo\n# keep me\nversion=v1.0\nversionCode=10\ndescription=café\n").unwrap();
+        fs::write(project.join("update.json"), r#"{"version":"v1.0","versionCode":10,"zipUrl":"https://x"}"#).unwrap();
+
+        set_prop(project, "versionCode", "11", false).unwrap();
+        set_prop(project, "version", "v1.1", false).unwrap();
+        set_prop(project, "updateJson", "https://example.com/update.json", false).unwrap();
+        let content = fs::read_to_string(project.join("module.prop")).unwrap();
+        assert_eq!(content, "id=demo\n# keep me\nversion=v1.1\nversionCode=11\ndescription=café\nupdateJson=https://example.com/update.json\n");
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(project.join("update.json")).unwrap()).unwrap();
+        assert_eq!(json["versionCode"], 11);
+        assert_eq!(json["version"], "v1.1");
+        assert_eq!(json["zipUrl"], "https://x");
+
+        // versionCode 必须递增，id 修改需要 --force
+        assert!(set_prop(project, "versionCode", "9", false).is_err());
+        assert!(set_prop(project, "versionCode", "abc", true).is_err());
+        assert!(set_prop(project, "id", "other", false).is_err());
+        assert_eq!(validate_change(Some("11"), "versionCode", "9", true).unwrap().len(), 1);
+        assert!(validate_change(None, "bad=key", "x", true).is_err());
+        set_prop(project, "id", "other", true).unwrap();
+        assert!(fs::read_to_string(project.join("module.prop")).unwrap().starts_with("id=other\n"));
+    }
+}
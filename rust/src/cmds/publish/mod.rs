@@ -0,0 +1,585 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::cmds::init::parse_github_url;
+use crate::cmds::schema::write_schemas;
+use crate::cmds::upstream::{print_verification, verify_project};
+use crate::core::config_refs::ensure_docs_present;
+use crate::core::download::DownloadManager;
+use crate::core::errors::ProjectNotFoundError;
+use crate::core::events::record_event_or_warn;
+use crate::core::github::{pin_update_json, release_tag, verify_asset, ApiError, GithubClient, PullRequestFile, Release, ReleaseAsset};
+use crate::core::hashing::sha256_file;
+use crate::core::process::{check_cancelled, CommandExt, CommandKind};
+use crate::core::provenance::PROVENANCE_FILE;
+use crate::core::publish_pipeline::{default_steps, plan_steps, validate_steps, PublishState, PublishStep, StepKind, StepStatus};
+use crate::core::release_notes::{write_release_notes, PublishConfig, ReleaseContext};
+use crate::core::rmm_core::RmmCore;
+use crate::core::rmm_version::ensure_rmm_version;
+use crate::core::settings::ConfigResolver;
+use crate::core::signing::{resolve_secret_key, sign_file, signature_path};
+use crate::core::update_json::{changelog_excerpt_name, parse_manifest, update_json_copies};
+use crate::core::vcs::analyze_vcs_info;
+use crate::core::webhooks::{release_vars, send_webhooks};
+use crate::core::zip_inspect::{parse_module_prop, read_prop_text};
+
+/// rmm publish 的选项
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+    /// 发布通道，决定执行哪些步骤与发布说明模板
+    pub channel: Option<String>,
+    /// 丢弃上次未完成的进度，从第一步开始
+    pub restart: bool,
+    /// 跳过 webhook 步骤
+    pub no_notify: bool,
+    /// 命令行 --token，优先于 GITHUB_ACCESS_TOKEN 等环境变量
+    pub token: Option<String>,
+}
+
+/// 一次发布共用的信息
+struct PublishContext<'a> {
+    project_path: &'a Path,
+    config: PublishConfig,
+    options: &'a PublishOptions,
+    /// module.prop 的 id、version
+    module_id: String,
+    version: String,
+    version_code: u64,
+    token: Option<String>,
+    /// 已完成步骤的输出（tag、url、repo 等）
+    outputs: BTreeMap<String, String>,
+}
+
+impl PublishContext<'_> {
+    fn tag(&self) -> String {
+        self.outputs.get("tag").cloned().unwrap_or_else(|| release_tag(&self.version))
+    }
+
+    fn repo(&self) -> Result<String> {
+        match self.outputs.get("repo") {
+            Some(repo) => Ok(repo.clone()),
+            None => github_repo(self.project_path),
+        }
+    }
+
+    fn token(&self) -> Result<&str> {
+        self.token.as_deref().ok_or_else(|| anyhow::anyhow!("未设置 GitHub 令牌"))
+    }
+
+    fn dist_dir(&self) -> PathBuf {
+        self.project_path.join(".rmmp/dist")
+    }
+}
+
+/// 按 Rmake.toml [[publish.steps]] 发布项目（未配置时为 github-release → webhook）；
+/// 进度记录在 .rmmp/publish-state.json，失败后重新运行从未完成的步骤继续
+pub fn publish_project(project_path: &Path, options: &PublishOptions) -> Result<()> {
+    if !project_path.join("rmmproject.toml").exists() || !project_path.join("module.prop").exists() {
+        return Err(ProjectNotFoundError { path: project_path.to_path_buf() }.into());
+    }
+    ensure_rmm_version(project_path)?;
+    ensure_docs_present(project_path)?;
+    println!("{} 发布项目: {}", "🚀".green().bold(), project_path.display());
+
+    let module_prop = parse_module_prop(&read_prop_text(&project_path.join("module.prop"))?);
+    let field = |key: &str| module_prop.get(key).cloned().unwrap_or_default();
+    let version_code = field("versionCode").parse::<u64>()
+        .map_err(|_| anyhow::anyhow!("module.prop 中的 versionCode 无效: {}", field("versionCode")))?;
+    let config = RmmCore::new().get_rmake_config(project_path)?.publish.unwrap_or_default();
+
+    let steps: Vec<PublishStep> = if config.steps.is_empty() { default_steps() } else { config.steps.clone() };
+    validate_steps(&steps)?;
+    let channel = options.channel.as_deref();
+    let mut state = PublishState::load(project_path)?;
+    if state.begin(version_code, channel, options.restart) {
+        println!("{} 继续 versionCode {} 上次未完成的发布（--restart 从头开始）", "[i]".cyan(), version_code);
+    }
+    state.save(project_path)?;
+    let plan = plan_steps(&steps, &state, version_code, channel);
+
+    println!("{} 发布流程:", "[i]".cyan());
+    for planned in &plan {
+        let status = match &planned.status {
+            StepStatus::Pending => "待执行".to_string(),
+            StepStatus::Done => "已完成".green().to_string(),
+            StepStatus::Skipped(reason) => format!("跳过（{}）", reason).bright_black().to_string(),
+        };
+        println!("    {} {}", planned.id.cyan(), status);
+    }
+
+    // 需要 GitHub 的步骤在开始前检查令牌与上游命名空间，避免执行到一半才失败
+    let resolver = ConfigResolver::new(Some(project_path)).with_cli("github.token", options.token.clone());
+    let needs_github = plan.iter().any(|p| {
+        p.status == StepStatus::Pending && matches!(p.step.run, StepKind::GithubRelease | StepKind::UpstreamPr)
+    });
+    let token = resolver.get("github.token");
+    if needs_github {
+        if token.is_none() {
+            println!("  {} 使用 --token，或设置环境变量 GITHUB_ACCESS_TOKEN、GITHUB_TOKEN、GH_TOKEN", "💡".blue().bold());
+            anyhow::bail!("发布到 GitHub 需要令牌（github.token）");
+        }
+        verify_upstream_namespace(project_path, &resolver)?;
+    }
+
+    let mut context = PublishContext {
+        project_path,
+        config,
+        options,
+        module_id: field("id"),
+        version: field("version"),
+        version_code,
+        token,
+        outputs: state.current.as_ref().map(|run| run.outputs.clone()).unwrap_or_default(),
+    };
+    for planned in &plan {
+        match &planned.status {
+            StepStatus::Done => {
+                println!("{} 步骤 {} 已在上次发布中完成，跳过", "[i]".cyan(), planned.id);
+                continue;
+            }
+            StepStatus::Skipped(reason) => {
+                println!("{} 跳过步骤 {}: {}", "[i]".cyan(), planned.id, reason);
+                continue;
+            }
+            StepStatus::Pending => {}
+        }
+        if planned.step.run == StepKind::Webhook && options.no_notify {
+            println!("{} --no-notify：跳过步骤 {}", "[i]".cyan(), planned.id);
+            continue;
+        }
+        check_cancelled()?;
+        println!("{} 执行发布步骤: {}", "[publish]".magenta().bold(), planned.id.cyan());
+        let outputs = run_step(&context, &planned.step)
+            .with_context(|| format!("发布步骤 {} 失败，修复后重新运行 rmm publish 将从该步骤继续", planned.id))?;
+        context.outputs.extend(outputs.clone());
+        state.complete(&planned.id, outputs);
+        state.save(project_path)?;
+    }
+
+    state.finish();
+    state.save(project_path)?;
+    println!("{} 发布完成！", "🎉".green().bold());
+    let steps_run: Vec<&str> = plan.iter()
+        .filter(|p| !matches!(p.status, StepStatus::Skipped(_)))
+        .map(|p| p.id.as_str())
+        .collect();
+    record_event_or_warn(project_path, "publish", &[
+        ("tag", context.tag()),
+        ("url", context.outputs.get("url").cloned().unwrap_or_default()),
+        ("steps", steps_run.join(",")),
+    ]);
+    Ok(())
+}
+
+/// 执行一个步骤，返回记录到发布进度中的输出
+fn run_step(context: &PublishContext, step: &PublishStep) -> Result<BTreeMap<String, String>> {
+    let project_path = context.project_path;
+    match step.run {
+        StepKind::Build => crate::cmds::build::build_project(project_path).map(|_| BTreeMap::new()),
+        StepKind::Check => crate::cmds::check::check_project(project_path, false, false).map(|_| BTreeMap::new()),
+        StepKind::Tag => create_tag(project_path, &context.tag()),
+        StepKind::GithubRelease => github_release(context),
+        StepKind::UpstreamPr => upstream_pr(context),
+        StepKind::Webhook => {
+            notify_webhooks(context);
+            Ok(BTreeMap::new())
+        }
+        StepKind::Command => run_command(context, step.command.as_deref().unwrap_or_default()),
+    }
+}
+
+/// 仓库名 owner/repo：优先 rmmproject.toml [urls] github，其次 Git 远程仓库
+fn github_repo(project_path: &Path) -> Result<String> {
+    let from_config = RmmCore::new().get_project_config(project_path).ok()
+        .and_then(|project| project.urls)
+        .and_then(|urls| parse_github_url(&urls.github));
+    from_config
+        .or_else(|| {
+            analyze_vcs_info(project_path).ok().flatten()
+                .and_then(|info| info.remote_url)
+                .and_then(|url| parse_github_url(&url))
+        })
+        .map(|(owner, repo)| format!("{}/{}", owner, repo))
+        .ok_or_else(|| anyhow::anyhow!("无法确定 GitHub 仓库：请在 rmmproject.toml [urls] github 中设置，或添加 GitHub 远程仓库 origin"))
+}
+
+/// 配置了 UPSTREAM_ACCESS_TOKEN 与上游索引时，发布前检查模块 id 的命名空间归属并预留新 id
+fn verify_upstream_namespace(project_path: &Path, resolver: &ConfigResolver) -> Result<()> {
+    if resolver.get("upstream.token").is_none() {
+        return Ok(());
+    }
+    if resolver.get("upstream.index").is_none() {
+        println!("{} 已设置 UPSTREAM_ACCESS_TOKEN，但未配置上游索引（RMM_UPSTREAM_INDEX 或 [publish] upstream_index），跳过命名空间检查", "[!]".yellow());
+        return Ok(());
+    }
+    let verification = verify_project(project_path, true).context("上游检查失败")?;
+    print_verification(&verification);
+    Ok(())
+}
+
+/// 创建 git 标签并推送到 origin；标签已存在时只推送
+fn create_tag(project_path: &Path, tag: &str) -> Result<BTreeMap<String, String>> {
+    let exists = Command::new("git")
+        .args(["rev-parse", "-q", "--verify", &format!("refs/tags/{}", tag)])
+        .current_dir(project_path)
+        .output_with_timeout(CommandKind::Git)?
+        .status
+        .success();
+    if exists {
+        println!("{} 标签 {} 已存在", "[i]".cyan(), tag);
+    } else {
+        let status = Command::new("git")
+            .args(["tag", "-a", tag, "-m", &format!("Release {}", tag)])
+            .current_dir(project_path)
+            .status_with_timeout(CommandKind::Git)?;
+        if !status.success() {
+            anyhow::bail!("创建标签 {} 失败", tag);
+        }
+        println!("{} 已创建标签 {}", "[+]".green().bold(), tag);
+    }
+    let status = Command::new("git")
+        .args(["push", "origin", tag])
+        .current_dir(project_path)
+        .status_with_timeout(CommandKind::Git)?;
+    if !status.success() {
+        anyhow::bail!("推送标签 {} 失败", tag);
+    }
+    Ok(BTreeMap::from([("tag".to_string(), tag.to_string())]))
+}
+
+/// rmm build --debug 生成的 <id>-<versionCode>-debug.zip 的后缀
+const DEBUG_ZIP_SUFFIX: &str = "-debug.zip";
+
+/// .rmmp/dist 中本次发布的构建产物：<id>-<versionCode>.zip 及带 -<abi>、-recovery、-symbols、-source 后缀的产物与更新日志摘录，
+/// 不含调试构建与未完成的文件；versionCode 按整段匹配，12 不会选中 120 或 112 的产物
+pub fn release_artifacts(dist_dir: &Path, module_id: &str, version_code: u64) -> Result<Vec<PathBuf>> {
+    let prefix = format!("{}-{}", module_id, version_code);
+    let changelog = changelog_excerpt_name(version_code as i64);
+    let mut artifacts: Vec<PathBuf> = fs::read_dir(dist_dir)
+        .with_context(|| format!("无法读取 {}，请先运行 rmm build", dist_dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            let artifact = name.strip_prefix(&prefix)
+                .is_some_and(|rest| rest.starts_with(['.', '-']) && rest != DEBUG_ZIP_SUFFIX);
+            (artifact || name == changelog) && !name.ends_with(".partial")
+        })
+        .collect();
+    artifacts.sort();
+    Ok(artifacts)
+}
+
+/// 创建或更新 GitHub Release：上传构建产物，把 update.json 的 zipUrl 改为实际的附件地址后上传清单、配置 JSON Schema 与发布说明
+fn github_release(context: &PublishContext) -> Result<BTreeMap<String, String>> {
+    let project_path = context.project_path;
+    let dist_dir = context.dist_dir();
+    let update_json = dist_dir.join("update.json");
+    let manifest = parse_manifest(&fs::read_to_string(&update_json)
+        .with_context(|| format!("无法读取 {}，请先运行 rmm build", update_json.display()))?)?;
+    if manifest.base.version_code.to_string() != context.version_code.to_string() {
+        anyhow::bail!(
+            "{} 的 versionCode {} 与 module.prop 的 {} 不一致，请重新运行 rmm build",
+            update_json.display(), manifest.base.version_code, context.version_code
+        );
+    }
+    let zip_name = format!("{}-{}.zip", context.module_id, context.version_code);
+    let artifacts = release_artifacts(&dist_dir, &context.module_id, context.version_code)?;
+    if !artifacts.iter().any(|path| path.ends_with(&zip_name)) {
+        anyhow::bail!("未找到 {}，请先运行 rmm build", dist_dir.join(&zip_name).display());
+    }
+
+    let client = GithubClient::new(context.token()?)?;
+    println!("{} 已连接到 GitHub 用户: {}", "[+]".green().bold(), client.login()?);
+    let repo = context.repo()?;
+    let tag = context.tag();
+    let name = format!("Release {}", manifest.base.version);
+    let release = match client.release_by_tag(&repo, &tag)? {
+        Some(release) => {
+            println!("{} Release {} 已存在，将更新附件与说明", "[!]".yellow(), tag);
+            release
+        }
+        None => {
+            let release = client.create_release(&repo, &tag, &name, "")?;
+            println!("{} 已创建 Release: {}", "[+]".green().bold(), release.html_url);
+            release
+        }
+    };
+    let retries = ConfigResolver::new(Some(project_path)).get_parsed::<u32>("publish.upload_retries")?.unwrap_or(5);
+
+    // 先上传构建产物，得到模块 zip 的实际下载地址
+    let mut zip_url = None;
+    for artifact in &artifacts {
+        let asset = upload_with_retries(&client, &repo, &release, artifact, retries)?;
+        if asset.name == zip_name {
+            zip_url = Some(asset.browser_download_url);
+        }
+    }
+    let zip_url = zip_url.ok_or_else(|| anyhow::anyhow!("上传后未找到 {} 的下载地址", zip_name))?;
+
+    // 更新分发目录中的 update.json 与通道清单
+    let manifests: Vec<PathBuf> = update_json_copies(project_path).into_iter()
+        .filter(|path| path.starts_with(&dist_dir))
+        .collect();
+    let mut signatures = Vec::new();
+    for path in &manifests {
+        let pinned = pin_update_json(&fs::read_to_string(path)?, &zip_url, &tag)?;
+        fs::write(path, pinned)?;
+        println!("{} {} zipUrl → {}", "[+]".green().bold(), path.file_name().unwrap_or_default().to_string_lossy(), zip_url);
+        if let Some(signature) = resign_manifest(project_path, path)? {
+            signatures.push(signature);
+        }
+    }
+
+    // 发布说明与其余附件；配置文件的 JSON Schema 随版本发布，供编辑器按版本引用
+    let mut attachments = manifests.clone();
+    attachments.extend(signatures);
+    let schemas = write_schemas(&dist_dir)?;
+    println!("{} 已生成配置 schema: {}", "[+]".green().bold(),
+        schemas.iter().map(|p| p.file_name().unwrap_or_default().to_string_lossy()).collect::<Vec<_>>().join(", "));
+    attachments.extend(schemas);
+    if context.config.provenance {
+        let provenance = dist_dir.join(PROVENANCE_FILE);
+        if provenance.is_file() {
+            attachments.push(provenance);
+        } else {
+            println!("{} 已启用 [publish] provenance，但未找到 {}，请先运行 rmm build", "[!]".yellow(), PROVENANCE_FILE);
+        }
+    }
+    let release_context = ReleaseContext {
+        repo: repo.clone(),
+        tag: tag.clone(),
+        assets: artifacts.iter().chain(&attachments).cloned().collect(),
+        mirrors: DownloadManager::from_config().mirrors().to_vec(),
+        channel: context.options.channel.clone(),
+    };
+    let body = match write_release_notes(project_path, context.config.notes.as_ref(), &release_context) {
+        Ok((body, notes_file)) => {
+            println!("{} 已生成发布说明: {}", "[+]".green().bold(), notes_file.display());
+            attachments.push(notes_file);
+            body
+        }
+        Err(e) => {
+            println!("{} 渲染发布说明失败，使用 update.json 中的 changelog: {}", "[!]".yellow(), e);
+            manifest.base.changelog.clone()
+        }
+    };
+    for attachment in &attachments {
+        upload_with_retries(&client, &repo, &release, attachment, retries)?;
+    }
+    let release = client.update_release(&repo, release.id, &name, &body)?;
+    println!("{} Release 链接: {}", "[+]".green().bold(), release.html_url);
+
+    Ok(BTreeMap::from([
+        ("tag".to_string(), tag),
+        ("url".to_string(), release.html_url),
+        ("repo".to_string(), repo),
+        ("zip_url".to_string(), zip_url),
+    ]))
+}
+
+/// 清单在 rmm build --sign 时签过名：修改 zipUrl 后用 [sign] 私钥重新签名；没有私钥时删除失效的签名
+fn resign_manifest(project_path: &Path, manifest: &Path) -> Result<Option<PathBuf>> {
+    let signature = signature_path(manifest);
+    if !signature.is_file() {
+        return Ok(None);
+    }
+    let key = RmmCore::new().get_rmake_config(project_path).ok()
+        .and_then(|rmake| rmake.sign)
+        .ok_or_else(|| anyhow::anyhow!("Rmake.toml 中没有 [sign]"))
+        .and_then(|sign| resolve_secret_key(sign.key.as_deref()));
+    match key {
+        Ok(key) => Ok(Some(sign_file(manifest, &key)?)),
+        Err(e) => {
+            fs::remove_file(&signature)?;
+            println!("{} 无法重新签名 {}，已删除失效的签名: {}", "[!]".yellow(), manifest.file_name().unwrap_or_default().to_string_lossy(), e);
+            Ok(None)
+        }
+    }
+}
+
+/// 上传附件：先删除同名附件（上一次发布或中断上传留下的），服务器错误与校验失败时按指数退避重试
+fn upload_with_retries(client: &GithubClient, repo: &str, release: &Release, path: &Path, retries: u32) -> Result<ReleaseAsset> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let size = fs::metadata(path)?.len();
+    let sha256 = sha256_file(path)?;
+    let mut last_error = None;
+    for attempt in 0..=retries {
+        for asset in client.list_assets(repo, release.id)?.into_iter().filter(|asset| asset.name == name) {
+            println!("    删除已存在的附件: {}", asset.name);
+            client.delete_asset(repo, asset.id)?;
+        }
+        if let Some(ref e) = last_error {
+            let delay = 2u64.saturating_pow(attempt).min(60);
+            println!("{} 第 {}/{} 次重试上传 {}（{}s 后）: {}", "[!]".yellow(), attempt, retries, name, delay, e);
+            std::thread::sleep(Duration::from_secs(delay));
+        }
+        check_cancelled()?;
+        let result = client.upload_asset(release, path)
+            .and_then(|asset| verify_asset(&asset, size, &sha256).map(|_| asset));
+        match result {
+            Ok(asset) => {
+                println!("{} 已上传 {}（{} 字节{}）", "[+]".green().bold(), name, size, if asset.digest.is_some() { "，SHA-256 一致" } else { "" });
+                return Ok(asset);
+            }
+            // 4xx（令牌权限、文件名冲突等）重试不会成功
+            Err(e) if e.downcast_ref::<ApiError>().is_some_and(|api| !api.is_transient()) => return Err(e),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    let error = last_error.unwrap_or_else(|| anyhow::anyhow!("未知错误"));
+    Err(error.context(format!("重试 {} 次后仍然无法上传 {}", retries, name)))
+}
+
+/// 向 [publish] upstream_repo 提交 PR，在 modules/<id>.json 中登记本次发布的 update.json
+fn upstream_pr(context: &PublishContext) -> Result<BTreeMap<String, String>> {
+    let upstream_repo = context.config.upstream_repo.as_deref()
+        .ok_or_else(|| anyhow::anyhow!("upstream-pr 步骤需要在 Rmake.toml [publish] 中设置 upstream_repo"))?;
+    let update_json = context.dist_dir().join("update.json");
+    let update: serde_json::Value = serde_json::from_str(&fs::read_to_string(&update_json)
+        .with_context(|| format!("无法读取 {}", update_json.display()))?)?;
+    let tag = context.tag();
+    let repo = context.repo()?;
+    let entry = serde_json::json!({
+        "id": context.module_id,
+        "repo": repo,
+        "tag": tag,
+        "update": update,
+    });
+    let file = PullRequestFile {
+        path: format!("modules/{}.json", context.module_id),
+        content: serde_json::to_string_pretty(&entry)? + "\n",
+        branch: format!("rmm/{}-{}", context.module_id, tag),
+        message: format!("{}: {}", context.module_id, tag),
+        body: format!("发布 {} {}\n\n由 rmm publish 提交", repo, tag),
+    };
+    let token = ConfigResolver::new(Some(context.project_path)).get("upstream.token")
+        .map_or_else(|| context.token().map(str::to_string), Ok)?;
+    let url = GithubClient::new(&token)?.submit_pull_request(upstream_repo, &file)
+        .with_context(|| format!("向 {} 提交 PR 失败", upstream_repo))?;
+    println!("{} 已向上游提交 PR: {}", "[+]".green().bold(), url);
+    Ok(BTreeMap::from([("upstream_pr".to_string(), url)]))
+}
+
+/// 按 [[publish.webhooks]] 发送发布通知；通知失败只警告，不影响发布结果
+fn notify_webhooks(context: &PublishContext) {
+    let tag = context.tag();
+    let repo = context.repo().unwrap_or_default();
+    let url = context.outputs.get("url").cloned()
+        .unwrap_or_else(|| format!("https://github.com/{}/releases/tag/{}", repo, tag));
+    let channel = context.options.channel.as_deref();
+    let outcomes = release_vars(context.project_path, &tag, &url, &repo, channel)
+        .and_then(|vars| send_webhooks(&context.config.webhooks, &vars, channel));
+    match outcomes {
+        Ok(outcomes) => {
+            for outcome in outcomes {
+                if outcome.ok {
+                    println!("{} 已通知 {}", "[+]".green().bold(), outcome.name);
+                } else {
+                    println!("{} 通知 {} 失败（尝试 {} 次）: {}", "[!]".yellow(), outcome.name, outcome.attempts, outcome.error.unwrap_or_default());
+                }
+            }
+        }
+        Err(e) => println!("{} 发送发布通知失败: {}", "[!]".yellow(), e),
+    }
+}
+
+/// 在项目根目录下执行 command 步骤，通过环境变量传递本次发布的 tag、url 与通道
+fn run_command(context: &PublishContext, command: &str) -> Result<BTreeMap<String, String>> {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("powershell");
+        cmd.arg("-Command").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    let status = cmd
+        .current_dir(context.project_path)
+        .env("RMM_PUBLISH_TAG", context.tag())
+        .env("RMM_PUBLISH_URL", context.outputs.get("url").cloned().unwrap_or_default())
+        .env("RMM_PUBLISH_CHANNEL", context.options.channel.as_deref().unwrap_or("default"))
+        .status_with_timeout(CommandKind::Script)?;
+    if !status.success() {
+        anyhow::bail!("命令退出码 {:?}: {}", status.code(), command);
+    }
+    Ok(BTreeMap::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_release_artifacts() {
+        let dir = tempdir().unwrap();
+        for name in [
+            "demo-120.zip",
+            "demo-120-source.tar.gz",
+            "demo-120-debug.zip",
+            "demo-120.zip.partial",
+            "demo-110.zip",
+            "changelog-120.md",
+            "update.json",
+            "update-beta.json",
+        ] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        let names: Vec<String> = release_artifacts(dir.path(), "demo", 120).unwrap().iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["changelog-120.md", "demo-120-source.tar.gz", "demo-120.zip"]);
+        assert!(release_artifacts(&dir.path().join("missing"), "demo", 120).is_err());
+    }
+
+    #[test]
+    fn test_release_artifacts_debug_in_module_id() {
+        let dir = tempdir().unwrap();
+        for name in ["foo-debugger-3.zip", "foo-debugger-3-debug.zip", "foo-debugger-3-source.tar.gz"] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        let names: Vec<String> = release_artifacts(dir.path(), "foo-debugger", 3).unwrap().iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["foo-debugger-3-source.tar.gz", "foo-debugger-3.zip"]);
+    }
+
+    #[test]
+    fn test_release_artifacts_neighbouring_version_codes() {
+        let dir = tempdir().unwrap();
+        for name in [
+            "demo-12.zip",
+            "demo-12-arm64-v8a.zip",
+            "demo-12-recovery.zip",
+            "demo-12-symbols.zip",
+            "demo-12-source.tar.gz",
+            "changelog-12.md",
+            "demo-120.zip",
+            "demo-112.zip",
+            "demo-1-12.zip",
+            "demo2-12.zip",
+            "changelog-120.md",
+        ] {
+            fs::write(dir.path().join(name), name).unwrap();
+        }
+        let names: Vec<String> = release_artifacts(dir.path(), "demo", 12).unwrap().iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec![
+            "changelog-12.md",
+            "demo-12-arm64-v8a.zip",
+            "demo-12-recovery.zip",
+            "demo-12-source.tar.gz",
+            "demo-12-symbols.zip",
+            "demo-12.zip",
+        ]);
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use colored::Colorize;
+use regex::RegexBuilder;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::rmm_core::RmmCore;
+use crate::core::zip_inspect::parse_module_prop;
+
+/// 可查询的项目配置文件
+const CONFIG_FILES: [(&str, &str); 4] = [
+    ("project", "rmmproject.toml"),
+    ("rmake", ".rmmp/Rmake.toml"),
+    ("prop", "module.prop"),
+    ("update", "update.json"),
+];
+
+/// 获取所有已注册项目（按名称排序）
+fn registered_projects() -> Result<Vec<(String, PathBuf)>> {
+    let meta = RmmCore::new().get_meta_config()?;
+    let mut projects: Vec<(String, PathBuf)> = meta
+        .projects
+        .into_iter()
+        .map(|(name, path)| (name, PathBuf::from(path)))
+        .collect();
+    projects.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(projects)
+}
+
+/// 读取配置文件并转换为统一的 JSON 值
+fn load_config(project_path: &Path, file: &str) -> Option<Value> {
+    let content = fs::read_to_string(project_path.join(file)).ok()?;
+    if file.ends_with(".toml") {
+        let value: toml::Value = toml::from_str(&content).ok()?;
+        serde_json::to_value(value).ok()
+    } else if file.ends_with(".json") {
+        serde_json::from_str(&content).ok()
+    } else {
+        serde_json::to_value(parse_module_prop(&content)).ok()
+    }
+}
+
+/// 按点分路径取值，如 project.scripts.build；数组可用数字下标
+pub fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').filter(|k| !k.is_empty()).try_fold(value, |current, part| match current {
+        Value::Object(map) => map.get(part),
+        Value::Array(items) => part.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 在所有已注册项目的配置中查询键值
+pub fn query_projects(key: &str, file: Option<&str>, json: bool) -> Result<()> {
+    if let Some(file) = file.filter(|f| !CONFIG_FILES.iter().any(|(name, _)| name == f)) {
+        anyhow::bail!(
+            "未知的配置文件类型 '{}'，可选: {}",
+            file,
+            CONFIG_FILES.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    let mut results = serde_json::Map::new();
+    for (name, path) in registered_projects()? {
+        let found = CONFIG_FILES
+            .iter()
+            .filter(|(kind, _)| file.is_none_or(|f| f == *kind))
+            .find_map(|(_, config_file)| {
+                load_config(&path, config_file)
+                    .and_then(|config| lookup(&config, key).cloned())
+                    .map(|value| (*config_file, value))
+            });
+
+        if json {
+            results.insert(name, found.map(|(_, v)| v).unwrap_or(Value::Null));
+            continue;
+        }
+        match found {
+            Some((config_file, value)) => println!(
+                "{} {} {}",
+                name.bright_white().bold(),
+                format_value(&value).green(),
+                format!("({})", config_file).bright_black()
+            ),
+            None => println!("{} {}", name.bright_white().bold(), "(未设置)".bright_black()),
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&Value::Object(results))?);
+    }
+    Ok(())
+}
+
+/// 在所有已注册项目的配置文件中搜索正则表达式
+pub fn grep_projects(pattern: &str, ignore_case: bool) -> Result<usize> {
+    let regex = RegexBuilder::new(pattern).case_insensitive(ignore_case).build()?;
+
+    let mut matches = 0;
+    for (name, path) in registered_projects()? {
+        for (_, config_file) in CONFIG_FILES {
+            let Ok(content) = fs::read_to_string(path.join(config_file)) else {
+                continue;
+            };
+            for (line_no, line) in content.lines().enumerate() {
+                if regex.is_match(line) {
+                    matches += 1;
+                    println!(
+                        "{}:{}:{}: {}",
+                        name.bright_white().bold(),
+                        config_file.cyan(),
+                        (line_no + 1).to_string().yellow(),
+                        line.trim()
+                    );
+                }
+            }
+        }
+    }
+
+    if matches == 0 {
+        println!("{} 没有匹配项", "[!]".yellow());
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_dotted_key() {
+        let value = serde_json::json!({
+            "project": { "scripts": { "build": "rmm build" }, "authors": ["a", "b"] }
+        });
+        assert_eq!(lookup(&value, "project.scripts.build"), Some(&Value::from("rmm build")));
+        assert_eq!(lookup(&value, "project.authors.1"), Some(&Value::from("b")));
+        assert!(lookup(&value, "project.missing").is_none());
+        assert!(lookup(&value, "project.scripts.build.extra").is_none());
+    }
+}
@@ -0,0 +1,316 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::core::rmm_core::{MetaConfig, RmmCore};
+
+/// 导出文件格式版本
+const EXPORT_FORMAT: u32 = 1;
+
+/// meta.toml 项目注册表的导出格式
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegistryExport {
+    pub format: u32,
+    pub username: String,
+    pub email: String,
+    pub version: String,
+    /// 项目名（别名）→ 项目路径
+    pub projects: BTreeMap<String, String>,
+    /// 项目名 → 标签
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// 路径前缀重映射规则 OLD=NEW
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathMapping {
+    pub from: String,
+    pub to: String,
+}
+
+impl PathMapping {
+    /// 解析 OLD=NEW
+    pub fn parse(rule: &str) -> Result<Self> {
+        let (from, to) = rule.split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("无效的路径映射 '{}'，应为 OLD=NEW", rule))?;
+        let from = from.trim_end_matches(['/', '\\']);
+        if from.is_empty() {
+            anyhow::bail!("路径映射 '{}' 的旧前缀不能为空", rule);
+        }
+        Ok(Self { from: from.to_string(), to: to.trim_end_matches(['/', '\\']).to_string() })
+    }
+
+    /// 仅在完整路径段上匹配前缀，/home/a 不会匹配 /home/ab
+    pub fn apply(&self, path: &str) -> Option<String> {
+        let rest = path.strip_prefix(&self.from)?;
+        if rest.is_empty() || rest.starts_with(['/', '\\']) {
+            Some(format!("{}{}", self.to, rest))
+        } else {
+            None
+        }
+    }
+}
+
+/// 解析命令行给出的全部映射规则
+pub fn parse_mappings(rules: &[String]) -> Result<Vec<PathMapping>> {
+    rules.iter().map(|rule| PathMapping::parse(rule)).collect()
+}
+
+/// 按顺序应用第一条匹配的映射规则
+pub fn remap_path(path: &str, mappings: &[PathMapping]) -> String {
+    mappings.iter()
+        .find_map(|m| m.apply(path))
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// 导入结果
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    /// 名称已存在且路径不同，未覆盖
+    pub conflicts: Vec<String>,
+    /// 映射后路径在本机不存在（仍会登记）
+    pub missing: Vec<String>,
+}
+
+/// 根据 meta.toml 生成导出内容
+pub fn build_export(meta: &MetaConfig, mappings: &[PathMapping]) -> RegistryExport {
+    RegistryExport {
+        format: EXPORT_FORMAT,
+        username: meta.username.clone(),
+        email: meta.email.clone(),
+        version: meta.version.clone(),
+        projects: meta.projects.iter()
+            .map(|(name, path)| (name.clone(), remap_path(path, mappings)))
+            .collect(),
+        tags: meta.tags.clone(),
+    }
+}
+
+/// 将导出内容合并到 meta.toml，已有的同名项目仅在 overwrite 时替换
+pub fn merge_export(
+    meta: &mut MetaConfig,
+    export: &RegistryExport,
+    mappings: &[PathMapping],
+    overwrite: bool,
+) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for (name, path) in &export.projects {
+        let path = remap_path(path, mappings);
+        if !Path::new(&path).exists() {
+            summary.missing.push(name.clone());
+        }
+        match meta.projects.get(name) {
+            None => summary.added.push(name.clone()),
+            Some(existing) if *existing == path => continue,
+            Some(_) if overwrite => summary.updated.push(name.clone()),
+            Some(_) => {
+                summary.conflicts.push(name.clone());
+                continue;
+            }
+        }
+        meta.projects.insert(name.clone(), path);
+        if let Some(tags) = export.tags.get(name) {
+            meta.tags.entry(name.clone()).or_default().extend(tags.iter().cloned());
+        }
+    }
+    summary
+}
+
+/// 导出项目注册表为 JSON，写入文件或标准输出
+pub fn export_registry(output: Option<&Path>, mappings: &[PathMapping]) -> Result<()> {
+    let meta = RmmCore::new().get_meta_config()?;
+    let content = serde_json::to_string_pretty(&build_export(&meta, mappings))?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, content + "\n")?;
+            eprintln!("{} 已导出 {} 个项目到 {}", "[+]".green().bold(), meta.projects.len(), path.display());
+        }
+        None => println!("{}", content),
+    }
+    Ok(())
+}
+
+/// 从 JSON 文件导入项目注册表
+pub fn import_registry(input: &Path, mappings: &[PathMapping], overwrite: bool) -> Result<ImportSummary> {
+    let content = std::fs::read_to_string(input)
+        .map_err(|e| anyhow::anyhow!("无法读取 {}: {}", input.display(), e))?;
+    let export: RegistryExport = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("无效的注册表导出文件: {}", e))?;
+    if export.format > EXPORT_FORMAT {
+        anyhow::bail!("导出文件格式版本 {} 高于当前支持的 {}，请升级 rmm", export.format, EXPORT_FORMAT);
+    }
+
+    let core = RmmCore::new();
+    let mut meta = core.get_meta_config()
+        .unwrap_or_else(|_| core.create_default_meta(&export.email, &export.username, &export.version));
+    let summary = merge_export(&mut meta, &export, mappings, overwrite);
+    core.update_meta_config(&meta)?;
+
+    for name in &summary.added {
+        println!("  {} {} → {}", "+".green().bold(), name.green(), meta.projects[name]);
+    }
+    for name in &summary.updated {
+        println!("  {} {} → {}", "~".yellow().bold(), name.yellow(), meta.projects[name]);
+    }
+    for name in &summary.conflicts {
+        println!("  {} {} 已存在且路径不同，跳过（使用 --overwrite 覆盖）", "!".red().bold(), name.red());
+    }
+    for name in &summary.missing {
+        println!("  {} {} 的路径在本机不存在，可使用 --map 重映射", "?".yellow(), name);
+    }
+    Ok(summary)
+}
+
+/// 校验标签名：非空，只含字母、数字、- 和 _
+fn validate_tag(tag: &str) -> Result<()> {
+    if tag.is_empty() || !tag.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!("无效的标签 '{}'，只能包含字母、数字、- 和 _", tag);
+    }
+    Ok(())
+}
+
+/// 为已注册项目添加（或移除）标签，返回修改后的标签
+pub fn set_tags(meta: &mut MetaConfig, project: &str, tags: &[String], remove: bool) -> Result<BTreeSet<String>> {
+    if !meta.projects.contains_key(project) {
+        anyhow::bail!("项目 {} 未在 meta.toml 中注册，先运行 rmm sync", project);
+    }
+    for tag in tags {
+        validate_tag(tag)?;
+    }
+    let own = meta.tags.entry(project.to_string()).or_default();
+    if remove {
+        own.retain(|t| !tags.contains(t));
+    } else {
+        own.extend(tags.iter().cloned());
+    }
+    let result = own.clone();
+    if result.is_empty() {
+        meta.tags.remove(project);
+    }
+    Ok(result)
+}
+
+/// rmm registry tag / untag
+pub fn tag_project(project: &str, tags: &[String], remove: bool) -> Result<()> {
+    let core = RmmCore::new();
+    let mut meta = core.get_meta_config()?;
+    let result = set_tags(&mut meta, project, tags, remove)?;
+    core.update_meta_config(&meta)?;
+    let shown = if result.is_empty() { "无".to_string() } else { result.into_iter().collect::<Vec<_>>().join(", ") };
+    println!("{} {} 的标签: {}", "[+]".green().bold(), project.bold(), shown.cyan());
+    Ok(())
+}
+
+/// rmm registry list：列出已注册项目，可按标签筛选
+pub fn list_projects(tags: &[String], json: bool) -> Result<()> {
+    let meta = RmmCore::new().get_meta_config()?;
+    let projects: BTreeMap<String, String> = if tags.is_empty() {
+        meta.projects.iter().map(|(name, path)| (name.clone(), path.clone())).collect()
+    } else {
+        meta.projects_with_tags(tags)
+    };
+    if json {
+        let value: Vec<_> = projects.iter()
+            .map(|(name, path)| serde_json::json!({
+                "name": name,
+                "path": path,
+                "tags": meta.tags.get(name).cloned().unwrap_or_default(),
+            }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+    if projects.is_empty() {
+        println!("{} 没有匹配的项目", "[i]".cyan());
+        return Ok(());
+    }
+    for (name, path) in &projects {
+        let tags = meta.tags.get(name)
+            .map(|t| format!(" [{}]", t.iter().cloned().collect::<Vec<_>>().join(", ")))
+            .unwrap_or_default();
+        println!("  {}{} {}", name.bold(), tags.cyan(), path.bright_black());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_remap_and_merge() {
+        let mapping = PathMapping::parse("/home/old/=/Users/new").unwrap();
+        assert_eq!(mapping.apply("/home/old/proj"), Some("/Users/new/proj".to_string()));
+        assert_eq!(mapping.apply("/home/older/proj"), None);
+        assert!(PathMapping::parse("no-separator").is_err());
+
+        let mut meta = MetaConfig {
+            email: "a@b.c".to_string(),
+            username: "a".to_string(),
+            version: "0.1.0".to_string(),
+            projects: HashMap::from([
+                ("alias".to_string(), "/home/old/alias".to_string()),
+                ("demo".to_string(), "/elsewhere/demo".to_string()),
+            ]),
+            trusted: HashMap::new(),
+            devices: Default::default(),
+            tags: Default::default(),
+            cache: None,
+            uuids: Default::default(),
+            network: None,
+        };
+        let export = build_export(&meta, std::slice::from_ref(&mapping));
+        assert_eq!(export.projects["alias"], "/Users/new/alias");
+        assert_eq!(export.projects["demo"], "/elsewhere/demo");
+
+        let mut incoming = export.clone();
+        incoming.projects.insert("alias".to_string(), "/home/old/alias".to_string());
+        incoming.projects.insert("fresh".to_string(), "/home/old/fresh".to_string());
+        meta.projects.insert("demo".to_string(), "/local/demo".to_string());
+
+        let summary = merge_export(&mut meta, &incoming, std::slice::from_ref(&mapping), false);
+        assert_eq!(summary.added, vec!["fresh".to_string()]);
+        assert_eq!(summary.conflicts, vec!["alias".to_string(), "demo".to_string()]);
+        assert_eq!(meta.projects["fresh"], "/Users/new/fresh");
+        assert_eq!(meta.projects["demo"], "/local/demo");
+
+        let summary = merge_export(&mut meta, &incoming, &[mapping], true);
+        assert!(summary.added.is_empty());
+        assert_eq!(summary.updated, vec!["alias".to_string(), "demo".to_string()]);
+        assert_eq!(meta.projects["alias"], "/Users/new/alias");
+        assert_eq!(meta.projects["demo"], "/elsewhere/demo");
+    }
+
+    #[test]
+    fn test_project_tags() {
+        let mut meta = MetaConfig {
+            projects: HashMap::from([
+                ("a".to_string(), "/p/a".to_string()),
+                ("b".to_string(), "/p/b".to_string()),
+                ("c".to_string(), "/p/c".to_string()),
+            ]),
+            ..Default::default()
+        };
+        set_tags(&mut meta, "a", &["work".to_string(), "zygisk".to_string()], false).unwrap();
+        set_tags(&mut meta, "b", &["zygisk".to_string()], false).unwrap();
+        assert!(set_tags(&mut meta, "missing", &["work".to_string()], false).is_err());
+        assert!(set_tags(&mut meta, "c", &["bad tag".to_string()], false).is_err());
+
+        let tagged = meta.projects_with_tags(&["zygisk".to_string()]);
+        assert_eq!(tagged.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(meta.projects_with_tags(&["work".to_string(), "none".to_string()]).len(), 1);
+
+        assert!(set_tags(&mut meta, "b", &["zygisk".to_string()], true).unwrap().is_empty());
+        assert!(!meta.tags.contains_key("b"));
+
+        let export = build_export(&meta, &[]);
+        let mut imported = MetaConfig::default();
+        merge_export(&mut imported, &export, &[], false);
+        assert_eq!(imported.tags["a"].iter().collect::<Vec<_>>(), vec!["work", "zygisk"]);
+    }
+}
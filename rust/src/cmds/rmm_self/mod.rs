@@ -0,0 +1,21 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::core::rmm_core::{BuildSystem, RmmCore};
+use crate::core::rmm_version::{pinned_requirement, replace_requirement, RMM_VERSION};
+
+/// rmm self pin：把当前 rmm 版本写入 rmmproject.toml [build-system] requires
+pub fn pin_version(project_path: &Path, exact: bool) -> Result<String> {
+    let core = RmmCore::new();
+    let mut project = core.get_project_config(project_path)?;
+    let requirement = pinned_requirement(exact);
+    let build_system = project.build_system.get_or_insert_with(|| BuildSystem {
+        requires: Vec::new(),
+        build_backend: "rmm".to_string(),
+    });
+    replace_requirement(&mut build_system.requires, requirement.clone());
+    core.update_project_config(project_path, &project)?;
+    println!("{} 已将项目固定到 {}（当前 rmm {}）", "[+]".green().bold(), requirement.cyan(), RMM_VERSION);
+    Ok(requirement)
+}
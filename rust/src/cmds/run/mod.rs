@@ -2,7 +2,10 @@ use anyhow::Result;
 use colored::Colorize;
 use std::path::Path;
 
+use crate::core::env_file::load_project_env;
+use crate::core::process::{CommandExt, CommandKind};
 use crate::core::rmm_core::RmmCore;
+use crate::core::errors::ProjectNotFoundError;
 
 /// 运行 rmmproject.toml 中定义的脚本
 pub fn run_script(project_path: &Path, script_name: Option<&str>) -> Result<()> {
@@ -10,7 +13,7 @@ pub fn run_script(project_path: &Path, script_name: Option<&str>) -> Result<()>
     
     // 检查项目是否有效
     if !is_valid_project(project_path) {
-        anyhow::bail!("当前目录不是有效的 RMM 项目");
+        return Err(ProjectNotFoundError { path: project_path.to_path_buf() }.into());
     }
     
     if let Some(script) = script_name {
@@ -117,7 +120,10 @@ fn execute_command(project_path: &Path, command: &str) -> Result<()> {
         cmd
     };
     
-    let output = cmd.output()?;
+    // 加载 .rmm.env / .rmm.env.local
+    cmd.envs(load_project_env(project_path)?);
+    
+    let output = cmd.output_with_timeout(CommandKind::Script)?;
     
     // 输出命令结果
     if !output.stdout.is_empty() {
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::rmm_core::{MetaConfig, RmakeConfig, RmmProject};
+
+/// 可导出 JSON Schema 的配置文件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    /// .rmmp/Rmake.toml
+    Rmake,
+    /// rmmproject.toml
+    Project,
+    /// RMM_ROOT/meta.toml
+    Meta,
+}
+
+impl SchemaKind {
+    pub const ALL: [SchemaKind; 3] = [SchemaKind::Rmake, SchemaKind::Project, SchemaKind::Meta];
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "rmake" | "rmake.toml" => Ok(SchemaKind::Rmake),
+            "project" | "rmmproject" | "rmmproject.toml" => Ok(SchemaKind::Project),
+            "meta" | "meta.toml" => Ok(SchemaKind::Meta),
+            other => anyhow::bail!("未知的配置类型 '{}'，可选: rmake、project、meta、all", other),
+        }
+    }
+
+    /// 导出的 schema 文件名，如 rmake.schema.json
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            SchemaKind::Rmake => "rmake.schema.json",
+            SchemaKind::Project => "rmmproject.schema.json",
+            SchemaKind::Meta => "meta.schema.json",
+        }
+    }
+}
+
+/// 由 serde 结构体生成 JSON Schema
+pub fn generate_schema(kind: SchemaKind) -> serde_json::Value {
+    let mut schema = match kind {
+        SchemaKind::Rmake => schemars::schema_for!(RmakeConfig),
+        SchemaKind::Project => schemars::schema_for!(RmmProject),
+        SchemaKind::Meta => schemars::schema_for!(MetaConfig),
+    };
+    schema.insert("$comment".to_string(), format!("Generated by rmm {}", env!("CARGO_PKG_VERSION")).into());
+    schema.to_value()
+}
+
+/// 把全部 schema 写入目录，返回生成的文件（rmm schema all 与 rmm publish 共用）
+pub fn write_schemas(dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    SchemaKind::ALL.iter()
+        .map(|kind| {
+            let path = dir.join(kind.file_name());
+            fs::write(&path, serde_json::to_string_pretty(&generate_schema(*kind))? + "\n")?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// rmm schema：输出到标准输出，或写入文件（all 时写入目录）
+pub fn print_schema(target: &str, output: Option<&Path>) -> Result<()> {
+    if target.trim().eq_ignore_ascii_case("all") {
+        for path in write_schemas(output.unwrap_or(Path::new(".")))? {
+            eprintln!("{} 已生成 {}", "[+]".green().bold(), path.display());
+        }
+        return Ok(());
+    }
+
+    let kind = SchemaKind::parse(target)?;
+    let content = serde_json::to_string_pretty(&generate_schema(kind))?;
+    match output {
+        Some(path) => {
+            fs::write(path, content + "\n")?;
+            eprintln!("{} 已生成 {}", "[+]".green().bold(), path.display());
+        }
+        None => println!("{}", content),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_schema() {
+        let schema = generate_schema(SchemaKind::Rmake);
+        let build = &schema["properties"]["build"];
+        assert!(build.is_object());
+        assert!(schema["required"].as_array().unwrap().contains(&"build".into()));
+        assert!(schema["properties"]["timeouts"].is_object());
+
+        let schema = generate_schema(SchemaKind::Project);
+        assert!(schema["properties"]["build-system"].is_object());
+        assert!(schema["properties"]["tool"].is_object());
+
+        assert_eq!(SchemaKind::parse("rmmproject.toml").unwrap(), SchemaKind::Project);
+        assert!(SchemaKind::parse("nope").is_err());
+    }
+
+    #[test]
+    fn test_write_schemas() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = write_schemas(&dir.path().join("dist")).unwrap();
+        let names: Vec<String> = paths.iter().map(|p| p.file_name().unwrap().to_string_lossy().to_string()).collect();
+        assert_eq!(names, vec!["rmake.schema.json", "rmmproject.schema.json", "meta.schema.json"]);
+        for path in &paths {
+            let schema: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+            assert!(schema["properties"].is_object());
+        }
+    }
+}
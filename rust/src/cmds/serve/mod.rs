@@ -0,0 +1,290 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::cmds::device::artifact::list_artifacts;
+use crate::cmds::device::read_module_id;
+use crate::core::process::is_cancelled;
+
+/// 本地更新服务器选项
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    /// 监听地址
+    pub bind: IpAddr,
+    pub port: u16,
+    /// update.json 中使用的主机名，None 时自动检测局域网地址
+    pub advertise: Option<String>,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            bind: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: 8080,
+            advertise: None,
+        }
+    }
+}
+
+/// 请求处理所需的上下文
+struct ServeContext {
+    project_path: PathBuf,
+    dist_dir: PathBuf,
+    base_url: String,
+    module_id: String,
+}
+
+/// 本机的局域网地址（通过 UDP "连接" 选择出口网卡，不发送数据）
+pub(crate) fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip()).filter(|ip| !ip.is_loopback())
+}
+
+/// 按扩展名返回 Content-Type
+pub fn content_type(name: &str) -> &'static str {
+    let name = name.to_ascii_lowercase();
+    if name.ends_with(".zip") {
+        "application/zip"
+    } else if name.ends_with(".json") {
+        "application/json; charset=utf-8"
+    } else if name.ends_with(".md") {
+        "text/markdown; charset=utf-8"
+    } else if name.ends_with(".tar.gz") || name.ends_with(".gz") {
+        "application/gzip"
+    } else if name.ends_with(".txt") || name.ends_with(".sh") || name.ends_with(".prop") {
+        "text/plain; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// 将 update.json 改写为指向本地服务器：zipUrl 指向最新产物，changelog 指向本地 CHANGELOG.md
+pub fn rewrite_update_json(content: &str, base_url: &str, zip_name: Option<&str>, has_changelog: bool) -> Result<String> {
+    let mut json: serde_json::Value = serde_json::from_str(content).context("update.json 不是有效的 JSON")?;
+    let object = json.as_object_mut().ok_or_else(|| anyhow::anyhow!("update.json 不是 JSON 对象"))?;
+    if let Some(zip_name) = zip_name {
+        object.insert("zipUrl".to_string(), serde_json::Value::from(format!("{}/{}", base_url, zip_name)));
+    }
+    if has_changelog {
+        object.insert("changelog".to_string(), serde_json::Value::from(format!("{}/CHANGELOG.md", base_url)));
+    }
+    Ok(serde_json::to_string_pretty(&json)?)
+}
+
+/// 解析请求行，返回 (方法, 去掉查询参数的路径)
+pub(crate) fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    parts.next().filter(|version| version.starts_with("HTTP/"))?;
+    Some((method, target.split(['?', '#']).next().unwrap_or(target)))
+}
+
+/// 只允许访问 dist 目录下的单个文件名，防止路径穿越
+fn safe_file_name(path: &str) -> Option<&str> {
+    let name = path.strip_prefix('/')?;
+    let valid = !name.is_empty() && !name.contains(['/', '\\']) && !name.starts_with('.');
+    valid.then_some(name)
+}
+
+impl ServeContext {
+    fn latest_artifact(&self) -> Option<String> {
+        list_artifacts(&self.project_path, &self.module_id)
+            .into_iter()
+            .next()
+            .and_then(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+    }
+
+    /// 返回 (状态码, Content-Type, 响应体)
+    fn respond(&self, path: &str) -> (u16, &'static str, Vec<u8>) {
+        let Some(name) = safe_file_name(path) else {
+            return (404, "text/plain; charset=utf-8", b"not found".to_vec());
+        };
+        let changelog = self.project_path.join("CHANGELOG.md");
+        if name == "CHANGELOG.md" {
+            return match fs::read(&changelog) {
+                Ok(body) => (200, content_type(name), body),
+                Err(_) => (404, "text/plain; charset=utf-8", b"not found".to_vec()),
+            };
+        }
+
+        // update.json 与 update-<channel>.json：改写下载地址
+        if name.starts_with("update") && name.ends_with(".json") {
+            let source = [self.dist_dir.join(name), self.project_path.join(name)]
+                .into_iter()
+                .find(|p| p.is_file());
+            let Some(source) = source else {
+                return (404, "text/plain; charset=utf-8", b"not found".to_vec());
+            };
+            let rewritten = fs::read_to_string(&source)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| rewrite_update_json(&content, &self.base_url, self.latest_artifact().as_deref(), changelog.is_file()));
+            return match rewritten {
+                Ok(body) => (200, content_type(name), body.into_bytes()),
+                Err(e) => (500, "text/plain; charset=utf-8", e.to_string().into_bytes()),
+            };
+        }
+
+        match fs::read(self.dist_dir.join(name)) {
+            Ok(body) => (200, content_type(name), body),
+            Err(_) => (404, "text/plain; charset=utf-8", b"not found".to_vec()),
+        }
+    }
+
+    fn handle(&self, stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        let peer = stream.peer_addr().map(|a| a.ip().to_string()).unwrap_or_default();
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // 读取并忽略请求头
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let (status, content_type, body, method, path) = match parse_request_line(request_line.trim()) {
+            Some((method @ ("GET" | "HEAD"), path)) => {
+                let (status, content_type, body) = self.respond(path);
+                (status, content_type, body, method, path)
+            }
+            Some((method, path)) => (405, "text/plain; charset=utf-8", b"method not allowed".to_vec(), method, path),
+            None => (400, "text/plain; charset=utf-8", b"bad request".to_vec(), "-", "-"),
+        };
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        };
+
+        let mut stream = stream;
+        write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+            status, reason, content_type, body.len())?;
+        if method != "HEAD" {
+            stream.write_all(&body)?;
+        }
+        stream.flush()?;
+
+        let status_text = if status == 200 { status.to_string().green() } else { status.to_string().yellow() };
+        println!("{} {} {} {} {}", "[serve]".cyan(), peer.bright_black(), method, path, status_text);
+        Ok(())
+    }
+}
+
+/// rmm serve：在局域网内提供 .rmmp/dist，update.json 自动改写为本地地址，Ctrl-C 停止
+pub fn serve(project_path: &Path, options: &ServeOptions) -> Result<()> {
+    let module_id = read_module_id(project_path)?;
+    let dist_dir = project_path.join(".rmmp/dist");
+    if !dist_dir.is_dir() {
+        anyhow::bail!("没有找到 .rmmp/dist，请先运行 rmm build");
+    }
+
+    let listener = TcpListener::bind(SocketAddr::new(options.bind, options.port))
+        .with_context(|| format!("无法监听 {}:{}", options.bind, options.port))?;
+    let port = listener.local_addr()?.port();
+    let host = match options.advertise {
+        Some(ref host) => host.clone(),
+        None if !options.bind.is_unspecified() => options.bind.to_string(),
+        None => lan_address().map(|ip| ip.to_string()).unwrap_or_else(|| "127.0.0.1".to_string()),
+    };
+    let context = ServeContext {
+        project_path: project_path.to_path_buf(),
+        dist_dir,
+        base_url: format!("http://{}:{}", host, port),
+        module_id,
+    };
+
+    println!("{} 本地更新服务器已启动: {}", "🌐".green().bold(), context.base_url.cyan().bold());
+    println!("    update.json: {}", format!("{}/update.json", context.base_url).green());
+    match context.latest_artifact() {
+        Some(name) => println!("    最新产物:    {}", format!("{}/{}", context.base_url, name).green()),
+        None => println!("    {} dist 中没有 {} 的安装包，zipUrl 不会被改写", "[!]".yellow(), context.module_id),
+    }
+    println!("    在测试设备上将模块的 updateJson 指向上面的地址（设备需与本机在同一局域网），按 Ctrl-C 停止");
+
+    // 非阻塞监听，便于响应 Ctrl-C
+    listener.set_nonblocking(true)?;
+    std::thread::scope(|scope| {
+        while !is_cancelled() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let context = &context;
+                    scope.spawn(move || {
+                        if let Err(e) = stream.set_nonblocking(false).map_err(anyhow::Error::from).and_then(|_| context.handle(stream)) {
+                            println!("{} 请求处理失败: {}", "[serve]".yellow(), e);
+                        }
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(Duration::from_millis(100)),
+                Err(e) => return Err(anyhow::Error::from(e)),
+            }
+        }
+        println!("{} 服务器已停止", "[serve]".cyan());
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_serves_dist_with_rewritten_update_json() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path();
+        fs::create_dir_all(project.join(".rmmp/dist")).unwrap();
+        fs::write(project.join("module.prop"), "id=demo\nversionCode=2\n").unwrap();
+        fs::write(project.join("CHANGELOG.md"), "# v2\n").unwrap();
+        fs::write(project.join(".rmmp/dist/demo-2.zip"), "zip").unwrap();
+        fs::write(project.join(".rmmp/dist/update.json"),
+            r#"{"version":"v2","versionCode":2,"zipUrl":"https://github.com/a/b/releases/download/demo-2.zip","changelog":"https://x"}"#).unwrap();
+
+        let context = ServeContext {
+            project_path: project.to_path_buf(),
+            dist_dir: project.join(".rmmp/dist"),
+            base_url: "http://192.168.1.5:8080".to_string(),
+            module_id: "demo".to_string(),
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            for _ in 0..3 {
+                let (stream, _) = listener.accept().unwrap();
+                context.handle(stream).unwrap();
+            }
+        });
+        let request = |line: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "{}\r\nHost: test\r\n\r\n", line).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = request("GET /update.json?t=1 HTTP/1.1");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: application/json"));
+        let body: serde_json::Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["zipUrl"], "http://192.168.1.5:8080/demo-2.zip");
+        assert_eq!(body["changelog"], "http://192.168.1.5:8080/CHANGELOG.md");
+        assert_eq!(body["versionCode"], 2);
+
+        let response = request("HEAD /demo-2.zip HTTP/1.1");
+        assert!(response.contains("Content-Type: application/zip\r\nContent-Length: 3\r\n"));
+        assert!(response.ends_with("\r\n\r\n"));
+        assert!(request("GET /../module.prop HTTP/1.1").starts_with("HTTP/1.1 404"));
+        server.join().unwrap();
+
+        assert_eq!(safe_file_name("/.gitignore"), None);
+        assert_eq!(content_type("demo-2-source.tar.gz"), "application/gzip");
+    }
+}
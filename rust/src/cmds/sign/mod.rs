@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cmds::device::print_artifact_verification;
+use crate::cmds::publish::release_artifacts;
+use crate::core::rmm_core::RmmCore;
+use crate::core::signing::{generate_key, resolve_secret_key, sign_file, trusted_keys, verify_artifact, PublicKey, SecretKey, SECRET_KEY_EXT};
+use crate::core::storage::RmmStorage;
+use crate::core::update_json::update_json_copies;
+use crate::core::zip_inspect::{parse_module_prop, read_prop_text};
+
+/// 为文件生成分离签名；update_json 时同时签名 dist 中的 update.json 与通道清单，返回签名文件
+pub fn sign_files(project_path: &Path, key: &SecretKey, files: &[PathBuf], update_json: bool) -> Result<Vec<PathBuf>> {
+    let dist_dir = project_path.join(".rmmp/dist");
+    let mut files = files.to_vec();
+    if update_json {
+        files.extend(update_json_copies(project_path).into_iter().filter(|path| path.starts_with(&dist_dir)));
+    }
+    files.iter().map(|file| {
+        let signature = sign_file(file, key)?;
+        println!("{} {}", "[sign]".magenta().bold(), signature.file_name().unwrap_or_default().to_string_lossy().cyan());
+        Ok(signature)
+    }).collect()
+}
+
+/// rmm sign artifacts：签名指定的文件，未指定时签名 .rmmp/dist 中当前 versionCode 的 zip
+pub fn sign_artifacts(project_path: &Path, files: &[PathBuf], key: Option<&str>, update_json: bool) -> Result<Vec<PathBuf>> {
+    let config = RmmCore::new().get_rmake_config(project_path).ok().and_then(|rmake| rmake.sign).unwrap_or_default();
+    let key = resolve_secret_key(key.or(config.key.as_deref()))?;
+    let files = if files.is_empty() {
+        current_zips(project_path)?
+    } else {
+        if let Some(missing) = files.iter().find(|file| !file.is_file()) {
+            anyhow::bail!("文件不存在: {}", missing.display());
+        }
+        files.to_vec()
+    };
+
+    let signatures = sign_files(project_path, &key, &files, update_json || config.update_json)?;
+    println!("{} 已使用密钥 {}（{}）签名 {} 个文件", "[+]".green().bold(),
+        key.name.bright_white(), key.public_key().id(), signatures.len());
+    Ok(signatures)
+}
+
+/// .rmmp/dist 中当前版本的 zip（模块、Recovery 卡刷包与符号包）
+fn current_zips(project_path: &Path) -> Result<Vec<PathBuf>> {
+    let prop = parse_module_prop(&read_prop_text(&project_path.join("module.prop"))?);
+    let version_code = prop.get("versionCode")
+        .and_then(|v| v.parse::<u64>().ok())
+        .ok_or_else(|| anyhow::anyhow!("module.prop 中的 versionCode 无效"))?;
+    let module_id = prop.get("id").ok_or_else(|| anyhow::anyhow!("module.prop 中缺少 id"))?;
+    let zips: Vec<PathBuf> = release_artifacts(&project_path.join(".rmmp/dist"), module_id, version_code)?
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+        .collect();
+    if zips.is_empty() {
+        anyhow::bail!(".rmmp/dist 中没有 versionCode {} 的产物，请先运行 rmm build", version_code);
+    }
+    Ok(zips)
+}
+
+/// rmm sign keygen：生成密钥对，公钥同时成为受信任的公钥
+pub fn keygen(name: &str, force: bool) -> Result<()> {
+    let key = generate_key(name, force)?;
+    let dir = RmmStorage::resolve().keys_dir();
+    println!("{} 已生成密钥 {}（{}）", "[+]".green().bold(), key.name.bright_white(), key.public_key().id());
+    println!("    私钥: {}", dir.join(format!("{}.{}", name, SECRET_KEY_EXT)).display());
+    println!("    公钥: {}", dir.join(format!("{}.pub", name)).display());
+    println!("{} 在 Rmake.toml 中设置 [sign] key = \"{}\" 后运行 rmm build --sign；运行 rmm device install-key {} 将公钥安装到设备",
+        "💡".blue().bold(), name, name);
+    println!("{} 请妥善备份私钥，不要提交到版本库", "[!]".yellow());
+    Ok(())
+}
+
+/// rmm sign list：列出密钥名称、ID 以及是否有私钥
+pub fn list_keys() -> Result<()> {
+    let dir = RmmStorage::resolve().keys_dir();
+    let mut keys: BTreeMap<String, (Option<PublicKey>, bool)> = BTreeMap::new();
+    for key in trusted_keys()? {
+        let name = key.name.clone();
+        keys.entry(name).or_default().0 = Some(key);
+    }
+    for entry in fs::read_dir(&dir).into_iter().flatten().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == SECRET_KEY_EXT) {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            keys.entry(name).or_default().1 = true;
+        }
+    }
+
+    if keys.is_empty() {
+        println!("{} {} 中没有密钥，运行 rmm sign keygen <name> 生成", "[i]".cyan(), dir.display());
+        return Ok(());
+    }
+    println!("{} {}", "[i]".cyan(), dir.display());
+    for (name, (public, secret)) in &keys {
+        let id = match public {
+            Some(key) => key.id(),
+            None => SecretKey::load(&dir.join(format!("{}.{}", name, SECRET_KEY_EXT)))
+                .map(|key| key.public_key().id())
+                .with_context(|| format!("无法读取密钥 {}", name))?,
+        };
+        let kind = match (public.is_some(), secret) {
+            (true, true) => "公钥 + 私钥".green(),
+            (true, false) => "公钥".normal(),
+            (false, _) => "仅私钥（公钥缺失，校验时不受信任）".yellow(),
+        };
+        println!("  {} {} {}", name.bright_white().bold(), id.bright_black(), kind);
+    }
+    Ok(())
+}
+
+/// rmm sign verify：用 RMM_ROOT/keys 中的公钥校验产物
+pub fn verify(zip_path: &Path, strict: bool) -> Result<()> {
+    if !zip_path.is_file() {
+        anyhow::bail!("文件不存在: {}", zip_path.display());
+    }
+    let verification = verify_artifact(zip_path, &trusted_keys()?)?;
+    print_artifact_verification(zip_path, &verification);
+    let failures = verification.failures();
+    if !failures.is_empty() {
+        anyhow::bail!("产物校验失败:\n  {}", failures.join("\n  "));
+    }
+    if strict && !verification.is_signed() {
+        anyhow::bail!("--strict: 产物未由受信任的公钥签名");
+    }
+    println!("{} 校验通过", "[+]".green().bold());
+    Ok(())
+}
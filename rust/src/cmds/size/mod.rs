@@ -0,0 +1,47 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::core::size_history::{format_size, growth_percent, SizeHistory};
+
+/// 趋势图最大宽度
+const BAR_WIDTH: usize = 30;
+
+/// 显示产物大小历史与趋势
+pub fn show_size_history(project_path: &Path, json: bool) -> Result<()> {
+    let history = SizeHistory::load(project_path)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&history)?);
+        return Ok(());
+    }
+    if history.records.is_empty() {
+        println!("{} 没有产物大小记录，运行 rmm build 后会自动记录", "[!]".yellow());
+        return Ok(());
+    }
+
+    let max = history.records.iter().map(|r| r.size).max().unwrap_or(1).max(1);
+    println!("{} 产物大小历史:", "[📦]".cyan().bold());
+    println!("  {:<16} {:>10} {:>12} {:>9}", "版本", "versionCode", "大小", "变化");
+
+    let mut previous: Option<u64> = None;
+    for record in &history.records {
+        let delta = previous
+            .and_then(|p| growth_percent(p, record.size))
+            .map(|g| {
+                let text = format!("{:+.1}%", g);
+                if g > 0.0 { text.red().to_string() } else { text.green().to_string() }
+            })
+            .unwrap_or_else(|| "-".bright_black().to_string());
+        let bar_len = ((record.size as f64 / max as f64) * BAR_WIDTH as f64).round().max(1.0) as usize;
+        println!(
+            "  {:<16} {:>10} {:>12} {:>9}  {}",
+            record.version,
+            record.version_code,
+            format_size(record.size),
+            delta,
+            "█".repeat(bar_len).cyan()
+        );
+        previous = Some(record.size);
+    }
+    Ok(())
+}
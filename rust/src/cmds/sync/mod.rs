@@ -5,7 +5,13 @@ use std::path::{Path, PathBuf};
 use std::fs;
 use std::collections::HashMap;
 
-use crate::core::rmm_core::{RmmCore, GitAnalyzer, MetaConfig};
+use crate::core::events::record_event_or_warn;
+use crate::core::notify::ConsoleSink;
+use crate::core::rmm_core::{RmmCore, ProjectTracking};
+use crate::core::vcs::{analyze_vcs_info, vcs_user};
+use crate::core::zip_inspect::{read_prop_text, replace_prop_values};
+use crate::core::rmm_version::ensure_rmm_version;
+use std::sync::Arc;
 
 /// 作者信息
 #[derive(Debug, Clone, PartialEq)]
@@ -21,18 +27,10 @@ impl AuthorInfo {
         self.name.is_empty() || self.email.is_empty()
     }
     
+    /// 从版本控制配置（git config / hg ui.username）获取用户信息
     fn from_git(path: &Path) -> Option<Self> {
-        GitAnalyzer::analyze_git_info(path).ok().flatten().and_then(|git_info| {
-            // 从 git config 获取用户信息
-            if let Ok(repo) = git2::Repository::open(&git_info.repo_root) {
-                let config = repo.config().ok()?;
-                let name = config.get_string("user.name").ok()?;
-                let email = config.get_string("user.email").ok()?;
-                Some(AuthorInfo { name, email })
-            } else {
-                None
-            }
-        })
+        let (name, email) = vcs_user(path)?;
+        (!name.is_empty() && !email.is_empty()).then_some(AuthorInfo { name, email })
     }
 }
 
@@ -88,8 +86,7 @@ impl VersionInfo {
     
     /// 从 module.prop 读取版本信息
     fn from_module_prop(project_path: &Path) -> Result<Self> {
-        let module_prop_path = project_path.join("module.prop");
-        let content = fs::read_to_string(module_prop_path)?;
+        let content = read_prop_text(&project_path.join("module.prop"))?;
         
         let mut version = String::new();
         let mut version_code = String::new();
@@ -108,19 +105,11 @@ impl VersionInfo {
     /// 更新 module.prop 文件
     fn update_module_prop(&self, project_path: &Path) -> Result<()> {
         let module_prop_path = project_path.join("module.prop");
-        let content = fs::read_to_string(&module_prop_path)?;
-        
-        let mut new_content = String::new();
-        for line in content.lines() {
-            if line.starts_with("version=") {
-                new_content.push_str(&format!("version={}\n", self.version));
-            } else if line.starts_with("versionCode=") {
-                new_content.push_str(&format!("versionCode={}\n", self.version_code));
-            } else {
-                new_content.push_str(line);
-                new_content.push('\n');
-            }
-        }
+        let content = fs::read(&module_prop_path)?;
+        let new_content = replace_prop_values(&content, &[
+            ("version", &self.version),
+            ("versionCode", &self.version_code),
+        ]);
         
         fs::write(module_prop_path, new_content)?;
         Ok(())
@@ -133,12 +122,22 @@ pub fn sync_projects(
     projects_only: bool,
     search_paths: Option<Vec<&str>>,
     max_depth: Option<usize>,
+    tags: &[String],
 ) -> Result<()> {
-    let core = RmmCore::new();
+    let core = RmmCore::new().with_event_sink(Arc::new(ConsoleSink::default()));
     
     println!("{} 开始同步项目...", "[🔄]".cyan().bold());
     
-    if let Some(name) = project_name {
+    if !tags.is_empty() {
+        // 只同步带有指定标签的项目
+        let tagged = core.get_meta_config()?.projects_with_tags(tags);
+        if tagged.is_empty() {
+            println!("  ❓ 没有带有标签 {} 的项目", tags.join(", ").yellow());
+        }
+        for name in tagged.keys() {
+            sync_specific_project(&core, name)?;
+        }
+    } else if let Some(name) = project_name {
         // 同步特定项目
         sync_specific_project(&core, name)?;
     } else {
@@ -209,6 +208,8 @@ fn search_and_add_project(core: &RmmCore, project_name: &str, meta: &mut crate::
 /// 同步项目元数据（版本、作者信息等）
 fn sync_project_metadata(core: &RmmCore, project_path: &Path, meta: &mut crate::core::rmm_core::MetaConfig) -> Result<()> {
     println!("  🔄 同步项目元数据...");
+    // 项目要求的 rmm 版本不满足时不改动项目文件
+    ensure_rmm_version(project_path)?;
       // 1. 版本管理
     println!("    📦 检查版本信息...");
     if let Err(e) = sync_version_info(core, project_path) {
@@ -259,6 +260,10 @@ fn sync_version_info(core: &RmmCore, project_path: &Path) -> Result<()> {
         if version_info.version != old_version || version_info.version_code != old_code {
             version_info.update_module_prop(project_path)?;
             sync_update_json(project_path, &version_info)?;
+            record_event_or_warn(project_path, "version_bump", &[
+                ("from", format!("{} ({})", old_version, old_code)),
+                ("to", format!("{} ({})", version_info.version, version_info.version_code)),
+            ]);
             println!("    🆙 版本已升级: {} ({}) -> {} ({})", 
                 old_version.bright_black(), old_code.bright_black(),
                 version_info.version.bright_green(), version_info.version_code.bright_green());
@@ -451,20 +456,39 @@ fn sync_all_projects(
                 // 获取当前 meta 配置
                 let mut meta = core.get_meta_config()?;
                 let mut path_updates = 0;
+                let mut uuid_updates = 0;
                 
                 for project in found_projects {
                     let project_name = &project.name;
                     let project_path = &project.path;
-                      if let Some(existing_path) = meta.projects.get(project_name) {                        // 项目已存在，检查路径是否需要更新
-                        let normalized_path = normalize_path(project_path);
-                        
-                        // 防止空路径
-                        let safe_path = if normalized_path.is_empty() {
-                            ".".to_string()
-                        } else {
-                            normalized_path
-                        };
-                        
+                    let normalized_path = normalize_path(project_path);
+                    
+                    // 防止空路径
+                    let safe_path = if normalized_path.is_empty() {
+                        ".".to_string()
+                    } else {
+                        normalized_path
+                    };
+                    
+                    // 按 UUID 找回移动或改名的项目
+                    match core.track_project(&mut meta, project_name, &safe_path) {
+                        Ok(ProjectTracking::Unchanged) => {}
+                        Ok(ProjectTracking::New) => uuid_updates += 1,
+                        Ok(ProjectTracking::Moved { name, from }) => {
+                            println!("    🚚 项目已移动: {} → {}", name.yellow(), project_name.green());
+                            println!("      旧路径: {}", from.bright_black());
+                            println!("      新路径: {}", safe_path.green());
+                            path_updates += 1;
+                        }
+                        Ok(ProjectTracking::Copied { original }) => {
+                            println!("    ⚠️  {} 与 {} 的 UUID 相同（复制的项目），已生成新的 UUID", project_name.yellow(), original.bright_black());
+                            uuid_updates += 1;
+                        }
+                        Err(e) => println!("    ⚠️  无法记录项目 {} 的 UUID: {}", project_name.yellow(), e.to_string().yellow()),
+                    }
+                    
+                    if let Some(existing_path) = meta.projects.get(project_name) {
+                        // 项目已存在，检查路径是否需要更新
                         if existing_path != &safe_path {
                             println!("    🔄 更新项目路径: {}", project_name.yellow());
                             println!("      旧路径: {}", existing_path.bright_black());
@@ -479,16 +503,9 @@ fn sync_all_projects(
                             if let Err(e) = sync_project_metadata(core, project_path, &mut meta) {
                                 println!("    ⚠️  同步失败: {}", e.to_string().yellow());
                             }
-                        }                    } else {                        // 新项目 - 检查是否与现有项目路径重复
-                        let normalized_path = normalize_path(project_path);
-                        
-                        // 防止空路径
-                        let safe_path = if normalized_path.is_empty() {
-                            ".".to_string()
-                        } else {
-                            normalized_path
-                        };
-                        
+                        }
+                    } else {
+                        // 新项目 - 检查是否与现有项目路径重复
                         let is_duplicate_path = meta.projects.values().any(|existing_path| {
                             // 标准化路径比较
                             let existing_canonical = std::path::Path::new(existing_path)
@@ -521,7 +538,7 @@ fn sync_all_projects(
                 }
                 
                 // 更新 meta 配置
-                if new_projects_count > 0 || path_updates > 0 {
+                if new_projects_count > 0 || path_updates > 0 || uuid_updates > 0 {
                     core.update_meta_config(&meta)?;
                 }
                 
@@ -684,29 +701,14 @@ fn smart_version_bump(current_version: &str, project_path: &Path) -> String {
     // 移除可能的 'v' 前缀进行处理
     let version_without_v = current_version.trim_start_matches('v');
     
-    // 获取Git提交hash作为patch
-    let patch_hash = if let Ok(Some(git_info)) = GitAnalyzer::analyze_git_info(project_path) {
-        if let Ok(repo) = git2::Repository::open(&git_info.repo_root) {
-            if let Ok(head) = repo.head() {
-                if let Some(commit) = head.target() {
-                    let commit_str = commit.to_string();
-                    if commit_str.len() >= 8 {
-                        commit_str[..8].to_string() // 使用8位commit hash
-                    } else {
-                        "unknown".to_string()
-                    }
-                } else {
-                    "unknown".to_string()
-                }
-            } else {
-                "unknown".to_string()
-            }
-        } else {
-            "unknown".to_string()
-        }
-    } else {
-        "unknown".to_string()
-    };
+    // 获取提交hash作为patch（没有版本控制时使用 [vcs] revision）
+    let patch_hash = analyze_vcs_info(project_path)
+        .ok()
+        .flatten()
+        .and_then(|git_info| git_info.last_commit_hash)
+        .filter(|hash| !hash.is_empty())
+        .map(|hash| hash.chars().take(8).collect::<String>()) // 使用8位commit hash（或 [vcs] revision）
+        .unwrap_or_else(|| "unknown".to_string());
     
     // 检查当前版本是否已经包含patch部分
     if let Some(dash_pos) = version_without_v.find('-') {
@@ -751,7 +753,7 @@ fn smart_version_bump(current_version: &str, project_path: &Path) -> String {
     format!("v{}-{}", version_without_v, patch_hash)
 }
 
-/// 同步版本信息到update.json
+/// 同步版本信息到update.json（只写项目根目录，.rmmp 中的副本在构建时重新生成）
 fn sync_update_json(project_path: &Path, version_info: &VersionInfo) -> Result<()> {
     let update_json_path = project_path.join("update.json");
     
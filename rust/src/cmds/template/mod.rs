@@ -0,0 +1,242 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use git2::{IndexAddOption, Repository, Signature};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cmds::init::parse_github_url;
+use crate::core::hashing::sha256_bytes;
+use crate::core::lockfile::{template_key, LockFile, ResourceKind, LOCK_FILE};
+use crate::core::storage::RmmStorage;
+use crate::core::toml_doc::write_toml;
+use crate::core::template::{
+    apply_template, install_from_dir, list_templates, load_manifest, parse_spec, template_digest,
+    TemplateManifest, TEMPLATE_MANIFEST,
+};
+
+/// 从 git 仓库或本地目录安装模板，返回模板清单
+///
+/// 提供 lock 时（在项目中执行）git 模板检出 rmm.lock 记录的提交并校验内容摘要；update 时改为获取最新提交并刷新记录
+pub fn install_template(
+    spec: &str,
+    templates_dir: &Path,
+    cache_dir: &Path,
+    force: bool,
+    mut lock: Option<&mut LockFile>,
+    update: bool,
+) -> Result<TemplateManifest> {
+    let parsed = parse_spec(spec)?;
+    let local = PathBuf::from(&parsed.url);
+
+    // 本地非 git 目录直接安装，其余来源先克隆到缓存目录
+    if local.is_dir() && Repository::open(&local).is_err() {
+        let source_dir = parsed.subdir.as_ref().map_or(local.clone(), |s| local.join(s));
+        return install_from_dir(&source_dir, templates_dir, spec, None, force);
+    }
+
+    let checkout = cache_dir.join("templates").join(&sha256_bytes(spec.as_bytes())[..16]);
+    if checkout.exists() {
+        fs::remove_dir_all(&checkout)?;
+    }
+    println!("{} 获取模板: {}", "[exec]".blue().bold(), parsed.url.cyan());
+    let result = (|| {
+        let repo = Repository::clone(&parsed.url, &checkout)
+            .with_context(|| format!("无法克隆模板仓库: {}", parsed.url))?;
+        let key = template_key(spec);
+        let locked_commit = lock.as_deref()
+            .filter(|_| !update)
+            .and_then(|lock| lock.resources.get(&key))
+            .filter(|locked| locked.url == parsed.url)
+            .and_then(|locked| locked.version.clone());
+        if let Some(commit) = &locked_commit {
+            checkout_commit(&repo, commit)?;
+            println!("    {} 使用 {} 锁定的提交 {}", "[lock]".cyan(), LOCK_FILE, &commit[..commit.len().min(7)]);
+        }
+        let commit = repo.head().ok().and_then(|h| h.target()).map(|oid| oid.to_string());
+        let source_dir = parsed.subdir.as_ref().map_or(checkout.clone(), |s| checkout.join(s));
+        // 先校验再安装，避免不一致的内容进入模板目录
+        if let Some(lock) = lock.as_deref_mut() {
+            let digest = template_digest(&source_dir)?;
+            lock.verify(&key, ResourceKind::Template, &parsed.url, commit.as_deref(), &digest, update)?;
+        }
+        install_from_dir(&source_dir, templates_dir, spec, commit, force)
+    })();
+    let _ = fs::remove_dir_all(&checkout);
+    result
+}
+
+/// 检出指定提交
+fn checkout_commit(repo: &Repository, commit: &str) -> Result<()> {
+    let oid = git2::Oid::from_str(commit)?;
+    let object = repo.find_object(oid, None)
+        .with_context(|| format!("模板仓库中找不到 {} 锁定的提交 {}，运行 rmm lock update 刷新", LOCK_FILE, commit))?;
+    repo.checkout_tree(&object, Some(git2::build::CheckoutBuilder::new().force()))?;
+    repo.set_head_detached(oid)?;
+    Ok(())
+}
+
+/// 打印已安装模板
+pub fn print_templates(templates_dir: &Path) -> Result<()> {
+    let templates = list_templates(templates_dir)?;
+    if templates.is_empty() {
+        println!("{} 没有已安装的模板", "[i]".cyan());
+        println!("    使用 {} 安装", "rmm template install github:user/repo#name".green());
+        return Ok(());
+    }
+
+    println!("{} 已安装的模板 ({}):", "[📦]".cyan().bold(), templates_dir.display());
+    for template in templates {
+        let origin = template.origin.as_ref().map_or(format!("local: {}", template.path.display()), |o| match &o.commit {
+            Some(commit) => format!("{} @ {}", o.source, &commit[..commit.len().min(7)]),
+            None => o.source.clone(),
+        });
+        println!("  {} {} {}",
+            template.manifest.name.green().bold(),
+            template.manifest.version.yellow(),
+            origin.bright_black()
+        );
+        if !template.manifest.description.is_empty() {
+            println!("      {}", template.manifest.description);
+        }
+    }
+    Ok(())
+}
+
+/// 将模板打包到 git 仓库的 <name>/ 目录并提交，返回仓库工作目录
+pub fn publish_template(
+    templates_dir: &Path,
+    name: &str,
+    repo_path: &Path,
+    version: Option<&str>,
+) -> Result<PathBuf> {
+    let template_dir = templates_dir.join(name);
+    if !template_dir.is_dir() {
+        anyhow::bail!("模板不存在: {}", template_dir.display());
+    }
+
+    // 没有清单时生成一个，指定版本时更新清单
+    let manifest_path = template_dir.join(TEMPLATE_MANIFEST);
+    let mut manifest = if manifest_path.exists() {
+        load_manifest(&template_dir)?
+    } else {
+        TemplateManifest { name: name.to_string(), version: "0.1.0".to_string(), description: String::new() }
+    };
+    if let Some(version) = version {
+        manifest.version = version.to_string();
+    }
+    write_toml(&manifest_path, &manifest)?;
+    let manifest = load_manifest(&template_dir)?;
+
+    fs::create_dir_all(repo_path)?;
+    let repo = Repository::open(repo_path).or_else(|_| Repository::init(repo_path))?;
+    let workdir = repo.workdir().ok_or_else(|| anyhow::anyhow!("不支持裸仓库"))?.to_path_buf();
+    let target = workdir.join(&manifest.name);
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    fs::create_dir_all(&target)?;
+    apply_template(&template_dir, &target, &BTreeMap::new())?;
+    fs::copy(&manifest_path, target.join(TEMPLATE_MANIFEST))?;
+
+    let mut index = repo.index()?;
+    index.add_all([manifest.name.as_str()], IndexAddOption::DEFAULT, None)?;
+    index.update_all([manifest.name.as_str()], None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    if parent.as_ref().is_some_and(|p| p.tree_id() == tree.id()) {
+        println!("{} 模板 '{}' 没有变化", "[i]".cyan(), manifest.name);
+        return Ok(workdir);
+    }
+
+    let sig = repo.signature().or_else(|_| Signature::now("rmm", "rmm@localhost"))?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let message = format!("Publish template {} {}", manifest.name, manifest.version);
+    repo.commit(Some("HEAD"), &sig, &sig, &message, &tree, &parents)?;
+    println!("{} 已提交模板 {} {}", "[+]".green().bold(), manifest.name.green(), manifest.version.yellow());
+
+    let source = repo.find_remote("origin").ok()
+        .and_then(|r| r.url().and_then(parse_github_url))
+        .map(|(owner, repo)| format!("github:{}/{}", owner, repo));
+    match source {
+        Some(source) => {
+            println!("    推送后即可安装: {}", format!("rmm template install {}#{}", source, manifest.name).green());
+        }
+        None => {
+            println!("    添加远程仓库并推送后即可安装: {}", format!("rmm template install <repo>#{}", manifest.name).green());
+        }
+    }
+    Ok(workdir)
+}
+
+/// 初始化时应用已安装的模板
+pub fn apply_to_project(name: &str, project_path: &Path, vars: &BTreeMap<&str, String>) -> Result<()> {
+    let template_dir = RmmStorage::resolve().templates_dir().join(name);
+    let manifest = load_manifest(&template_dir)
+        .with_context(|| format!("模板 '{}' 未安装，可运行 rmm template list 查看", name))?;
+    let written = apply_template(&template_dir, project_path, vars)?;
+    println!("{} 应用模板 {} {} ({} 个文件)",
+        "[+]".green().bold(), manifest.name.green(), manifest.version.yellow(), written.len());
+    Ok(())
+}
+
+/// 未指定模板时提示可用模板
+pub fn offer_templates() {
+    let Ok(templates) = list_templates(&RmmStorage::resolve().templates_dir()) else {
+        return;
+    };
+    if templates.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = templates.iter().map(|t| t.manifest.name.as_str()).collect();
+    println!("{} 可用模板: {}，使用 {} 应用",
+        "💡".blue().bold(), names.join(", ").green(), "rmm init <ID> --template <NAME>".cyan());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::template::TemplateRegistry;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_publish_and_install_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let local = root.join("local");
+        fs::create_dir_all(local.join("webui/webroot")).unwrap();
+        fs::write(local.join("webui/webroot/index.html"), "<h1>{{project_id}}</h1>").unwrap();
+
+        let shared = root.join("shared");
+        publish_template(&local, "webui", &shared, Some("1.2.0")).unwrap();
+        let repo = Repository::open(&shared).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert!(head.tree().unwrap().get_path(Path::new("webui/template.toml")).is_ok());
+
+        let installed = root.join("installed");
+        let spec = format!("{}#webui", shared.display());
+        let mut lock = LockFile::default();
+        let manifest = install_template(&spec, &installed, &root.join("cache"), false, Some(&mut lock), false).unwrap();
+        assert_eq!(manifest.version, "1.2.0");
+        assert!(install_template(&spec, &installed, &root.join("cache"), false, None, false).is_err());
+        assert_eq!(lock.resources[&template_key(&spec)].version, Some(head.id().to_string()));
+
+        // 上游更新后仍安装锁定的提交，update 时才取最新版本
+        publish_template(&local, "webui", &shared, Some("1.3.0")).unwrap();
+        let manifest = install_template(&spec, &root.join("pinned"), &root.join("cache"), false, Some(&mut lock), false).unwrap();
+        assert_eq!(manifest.version, "1.2.0");
+        let manifest = install_template(&spec, &root.join("updated"), &root.join("cache"), false, Some(&mut lock), true).unwrap();
+        assert_eq!(manifest.version, "1.3.0");
+        assert_ne!(lock.resources[&template_key(&spec)].version, Some(head.id().to_string()));
+
+        let registry = TemplateRegistry::load(&installed).unwrap();
+        assert_eq!(registry.templates["webui"].commit, Some(head.id().to_string()));
+
+        let project = root.join("project");
+        let vars = BTreeMap::from([("project_id", "demo".to_string())]);
+        apply_template(&installed.join("webui"), &project, &vars).unwrap();
+        assert_eq!(fs::read_to_string(project.join("webroot/index.html")).unwrap(), "<h1>demo</h1>");
+        assert!(!project.join(TEMPLATE_MANIFEST).exists());
+    }
+}
@@ -0,0 +1,378 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::check_report::{record_findings, Finding, FindingSeverity};
+use crate::core::process::{CommandExt, CommandKind, TempGuard};
+use crate::core::settings::ConfigResolver;
+
+/// 主机测试文件所在目录（相对项目根目录）
+pub const HOST_TESTS_DIR: &str = "tests";
+/// 主机测试在 check-report.json 中的工具名
+pub const HOST_TESTS_TOOL: &str = "host-tests";
+/// 测试用的属性文件（key=value），覆盖 getprop 的默认值
+pub(crate) const TEST_PROPS_FILE: &str = "test.prop";
+/// 测试结果标记行
+const MARKER: &str = "RMM_TEST";
+
+/// getprop 的默认值，模拟一台普通的 arm64 设备
+pub(crate) const DEFAULT_PROPS: &str = "ro.build.version.sdk=34
+ro.build.version.release=14
+ro.product.cpu.abi=arm64-v8a
+ro.product.model=rmm-host
+ro.boot.verifiedbootstate=green
+";
+
+/// 模块脚本常用的安装环境函数的替身；ui_print / abort 输出到测试日志，getprop / resetprop 读写属性文件
+const STUBS: &str = r#"ui_print() { echo "$@"; }
+abort() { echo "$@"; exit 1; }
+set_perm() { :; }
+set_perm_recursive() { :; }
+getprop() {
+  if [ $# -eq 0 ]; then
+    sed 's/^\([^=]*\)=\(.*\)$/[\1]: [\2]/' "$RMM_TEST_PROPS"
+    return 0
+  fi
+  rmm_value=$(grep "^$1=" "$RMM_TEST_PROPS" | tail -n 1 | cut -d= -f2-)
+  [ -n "$rmm_value" ] && echo "$rmm_value" || echo "$2"
+}
+resetprop() {
+  [ "$1" = "-n" ] && shift
+  if [ $# -lt 2 ]; then getprop "$@"; return; fi
+  echo "$1=$2" >> "$RMM_TEST_PROPS"
+}
+run() {
+  output=$("$@" 2>&1) && status=0 || status=$?
+  return 0
+}
+load() { . "$BATS_TEST_DIRNAME/${1%.bash}.bash"; }
+"#;
+
+/// 一个测试用例的结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HostTestCase {
+    /// 相对项目根目录的测试文件
+    pub file: String,
+    pub name: String,
+    /// bats 用例所在行
+    pub line: Option<u32>,
+    pub passed: bool,
+    /// 失败时的输出
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub output: String,
+}
+
+/// rmm test --host 的结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HostTestReport {
+    /// 运行测试使用的 shell
+    pub shell: String,
+    pub passed: usize,
+    pub failed: usize,
+    pub cases: Vec<HostTestCase>,
+}
+
+/// 运行测试的 shell：优先使用 busybox（与设备上的 ash 行为一致），否则退回系统 sh
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostShell {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl HostShell {
+    /// test.busybox 配置 > PATH 中的 busybox > sh
+    pub fn detect(project_path: &Path) -> Self {
+        let busybox = ConfigResolver::new(Some(project_path)).get("test.busybox")
+            .map(|path| project_path.join(path))
+            .or_else(|| find_in_path("busybox"));
+        match busybox {
+            Some(program) => Self { program, args: vec!["sh".to_string()] },
+            None => Self { program: PathBuf::from("sh"), args: Vec::new() },
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        std::iter::once(self.program.display().to_string()).chain(self.args.iter().cloned()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// tests/ 下的 *.sh 与 *.bats
+pub fn discover_tests(project_path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(project_path.join(HOST_TESTS_DIR)) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries.filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "sh" || ext == "bats"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// 将 bats 文件中的 `@test "名称" {` 转换为 shell 函数，返回转换后的脚本与 (名称, 行号)
+pub fn translate_bats(source: &str) -> (String, Vec<(String, u32)>) {
+    let mut script = String::new();
+    let mut tests = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+        let name = trimmed.strip_prefix("@test")
+            .and_then(|rest| rest.trim().strip_suffix('{'))
+            .map(|name| name.trim().trim_matches(|c| c == '"' || c == '\''));
+        match name {
+            Some(name) => {
+                script.push_str(&format!("rmm_test_{}() {{\n", tests.len()));
+                tests.push((name.to_string(), index as u32 + 1));
+            }
+            None => {
+                script.push_str(line);
+                script.push('\n');
+            }
+        }
+    }
+    // 与 bats 一致：每个用例在独立子 shell 中以 set -e 运行，前后调用 setup / teardown
+    script.push_str(&format!(r#"
+rmm_run_test() {{
+  echo "{marker} begin $1"
+  ( set -e; if command -v setup >/dev/null 2>&1; then setup; fi; "rmm_test_$1" )
+  rmm_status=$?
+  if command -v teardown >/dev/null 2>&1; then ( teardown ); fi
+  if [ $rmm_status -eq 0 ]; then echo "{marker} ok $1"; else echo "{marker} fail $1"; fi
+}}
+"#, marker = MARKER));
+    for index in 0..tests.len() {
+        script.push_str(&format!("rmm_run_test {}\n", index));
+    }
+    (script, tests)
+}
+
+/// 解析 bats 运行输出，按用例拆分
+fn parse_bats_output(file: &str, tests: &[(String, u32)], stdout: &str) -> Vec<HostTestCase> {
+    let mut cases = Vec::new();
+    let mut output = String::new();
+    for line in stdout.lines() {
+        let mut words = line.splitn(3, ' ');
+        let (Some(MARKER), Some(status), Some(index)) = (words.next(), words.next(), words.next()) else {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        };
+        let Some((name, line)) = index.parse::<usize>().ok().and_then(|i| tests.get(i)) else {
+            continue;
+        };
+        match status {
+            "begin" => output.clear(),
+            "ok" | "fail" => cases.push(HostTestCase {
+                file: file.to_string(),
+                name: name.clone(),
+                line: Some(*line),
+                passed: status == "ok",
+                output: if status == "ok" { String::new() } else { std::mem::take(&mut output) },
+            }),
+            _ => {}
+        }
+    }
+    // 在中途退出（如顶层的 exit）而没有结果的用例视为失败
+    for (name, line) in tests {
+        if !cases.iter().any(|case| &case.name == name) {
+            cases.push(HostTestCase { file: file.to_string(), name: name.clone(), line: Some(*line), passed: false, output: output.clone() });
+        }
+    }
+    cases
+}
+
+/// 准备隔离的运行目录：替身函数与属性文件
+fn prepare_environment(project_path: &Path, work_dir: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(work_dir.join("tmp"))?;
+    fs::write(work_dir.join("stubs.sh"), STUBS)?;
+    let mut props = DEFAULT_PROPS.to_string();
+    if let Ok(extra) = fs::read_to_string(project_path.join(HOST_TESTS_DIR).join(TEST_PROPS_FILE)) {
+        props.push_str(&extra);
+        if !props.ends_with('\n') {
+            props.push('\n');
+        }
+    }
+    let props_path = work_dir.join("props");
+    fs::write(&props_path, props)?;
+    Ok(props_path)
+}
+
+/// 运行一个测试文件；每个文件使用全新的属性文件，互不影响
+fn run_test_file(project_path: &Path, shell: &HostShell, work_dir: &Path, file: &Path) -> Result<Vec<HostTestCase>> {
+    let props = prepare_environment(project_path, work_dir)?;
+    let relative = file.strip_prefix(project_path).unwrap_or(file).to_string_lossy().replace('\\', "/");
+    let source = fs::read_to_string(file).with_context(|| format!("无法读取测试文件 {}", file.display()))?;
+    let bats = file.extension().is_some_and(|ext| ext == "bats");
+
+    let mut wrapper = String::from("exec 2>&1\n. \"$RMM_TEST_STUBS\"\ncd \"$MODPATH\" || exit 1\n");
+    let tests = if bats {
+        let (script, tests) = translate_bats(&source);
+        wrapper.push_str(&script);
+        tests
+    } else {
+        wrapper.push_str(". \"$RMM_TEST_FILE\"\n");
+        Vec::new()
+    };
+    let wrapper_path = work_dir.join("run.sh");
+    fs::write(&wrapper_path, wrapper)?;
+
+    let output = Command::new(&shell.program)
+        .args(&shell.args)
+        .arg(&wrapper_path)
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .env("HOME", work_dir)
+        .env("TMPDIR", work_dir.join("tmp"))
+        .env("LANG", "C")
+        .env("MODPATH", project_path)
+        .env("BOOTMODE", "true")
+        .env("API", "34")
+        .env("ARCH", "arm64")
+        .env("RMM_TEST_STUBS", work_dir.join("stubs.sh"))
+        .env("RMM_TEST_PROPS", &props)
+        .env("RMM_TEST_FILE", file)
+        .env("BATS_TEST_DIRNAME", project_path.join(HOST_TESTS_DIR))
+        .current_dir(project_path)
+        .output_with_timeout(CommandKind::Script)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if bats {
+        return Ok(parse_bats_output(&relative, &tests, &stdout));
+    }
+    let passed = output.status.success();
+    Ok(vec![HostTestCase {
+        name: file.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+        file: relative,
+        line: None,
+        passed,
+        output: if passed { String::new() } else { stdout.to_string() },
+    }])
+}
+
+/// 失败的用例转换为统一报告格式
+pub fn host_test_findings(report: &HostTestReport) -> Vec<Finding> {
+    report.cases.iter()
+        .filter(|case| !case.passed)
+        .map(|case| {
+            let finding = Finding::new(HOST_TESTS_TOOL, FindingSeverity::Error, format!("测试失败: {}", case.name))
+                .in_file(case.file.clone(), case.line);
+            let tail: Vec<&str> = case.output.lines().rev().take(5).collect();
+            if tail.is_empty() {
+                finding
+            } else {
+                finding.with_fix(tail.into_iter().rev().collect::<Vec<_>>().join("\n      "))
+            }
+        })
+        .collect()
+}
+
+/// 在主机上运行 tests/ 下的测试，结果合并到 .rmmp/check-report.json
+pub fn run_host_tests(project_path: &Path) -> Result<HostTestReport> {
+    let files = discover_tests(project_path);
+    if files.is_empty() {
+        anyhow::bail!("{} 目录下没有测试文件（*.sh 或 *.bats）", project_path.join(HOST_TESTS_DIR).display());
+    }
+    let shell = HostShell::detect(project_path);
+    let work_dir = project_path.join(".rmmp").join("host-test");
+    let _guard = TempGuard::new(&work_dir);
+
+    let mut cases = Vec::new();
+    for file in &files {
+        let _ = fs::remove_dir_all(&work_dir);
+        cases.extend(run_test_file(project_path, &shell, &work_dir, file)?);
+    }
+    let failed = cases.iter().filter(|case| !case.passed).count();
+    let report = HostTestReport { shell: shell.describe(), passed: cases.len() - failed, failed, cases };
+    record_findings(project_path, HOST_TESTS_TOOL, host_test_findings(&report))?;
+    Ok(report)
+}
+
+/// 打印测试结果
+pub fn print_host_report(report: &HostTestReport) {
+    println!("{} 使用 {} 运行主机测试", "[*]".cyan(), report.shell.bright_white());
+    for case in &report.cases {
+        let location = match case.line {
+            Some(line) => format!("{}:{}", case.file, line),
+            None => case.file.clone(),
+        };
+        if case.passed {
+            println!("  {} {} {}", "✓".green(), case.name, location.bright_black());
+        } else {
+            println!("  {} {} {}", "✗".red(), case.name.red(), location.bright_black());
+            for line in case.output.lines() {
+                println!("      {}", line.bright_black());
+            }
+        }
+    }
+    println!("{} {} 通过，{} 失败", "[=]".cyan(), report.passed.to_string().green(), report.failed.to_string().red());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_translate_bats() {
+        let (script, tests) = translate_bats("setup() { x=1; }\n@test \"adds numbers\" {\n  [ $((1 + 1)) -eq 2 ]\n}\n");
+        assert_eq!(tests, vec![("adds numbers".to_string(), 2)]);
+        assert!(script.contains("rmm_test_0() {\n  [ $((1 + 1)) -eq 2 ]"));
+        assert!(script.ends_with("rmm_run_test 0\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_host_tests() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path();
+        fs::create_dir_all(project.join("tests")).unwrap();
+        fs::write(project.join("functions.sh"), "is_tablet() { [ \"$(getprop ro.build.characteristics)\" = tablet ]; }\n").unwrap();
+        fs::write(project.join("tests/test.prop"), "ro.build.characteristics=tablet\n").unwrap();
+        fs::write(project.join("tests/functions.bats"), r#"
+setup() { . "$MODPATH/functions.sh"; }
+
+@test "detects tablet" {
+  is_tablet
+}
+
+@test "resetprop is visible to getprop" {
+  resetprop ro.build.characteristics phone
+  run is_tablet
+  [ "$status" -ne 0 ]
+}
+
+@test "fails loudly" {
+  ui_print "- checking"
+  false
+  echo unreachable
+}
+"#).unwrap();
+        fs::write(project.join("tests/plain.sh"), "[ \"$(getprop ro.product.cpu.abi)\" = arm64-v8a ] || abort 'wrong abi'\n").unwrap();
+
+        let report = run_host_tests(project).unwrap();
+        let result = |name: &str| report.cases.iter().find(|c| c.name == name).unwrap();
+        assert!(result("detects tablet").passed);
+        assert!(result("resetprop is visible to getprop").passed);
+        assert!(result("plain").passed);
+        let failed = result("fails loudly");
+        assert!(!failed.passed);
+        assert_eq!(failed.line, Some(14));
+        assert!(failed.output.contains("- checking"));
+        assert!(!failed.output.contains("unreachable"));
+        assert_eq!((report.passed, report.failed), (3, 1));
+
+        let findings = crate::core::check_report::ValidationReport::load(project).findings;
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].tool, HOST_TESTS_TOOL);
+        assert_eq!(findings[0].location().unwrap(), "tests/functions.bats:14");
+        assert!(!project.join(".rmmp/host-test").exists());
+    }
+}
@@ -0,0 +1,307 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cmds::device::{connect, read_module_id, resolve_artifact, rollback_module, snapshot_module};
+use crate::core::events::record_event_or_warn;
+use crate::core::adb::{shell_quote, Adb, DEVICE_TMP_DIR, MODULES_DIR};
+use crate::core::process::TempGuard;
+use crate::core::verify::{generate_verify_script, load_verify_config, parse_verify_output, VerifyResult, VERIFY_SCRIPT};
+
+pub mod host;
+
+/// 验证报告文件（位于 .rmmp 下）
+const VERIFY_REPORT: &str = "verify-report.json";
+/// 截图与录屏的保存目录（CI 中可作为构建产物上传）
+const TEST_REPORT_DIR: &str = ".rmmp/test-report";
+/// screenrecord 允许的最长录制时间（秒）
+const SCREENRECORD_MAX_SECS: u64 = 180;
+
+/// 设备测试选项
+#[derive(Debug, Clone)]
+pub struct TestOptions {
+    /// 设备序列号
+    pub serial: Option<String>,
+    /// 测试结束后保留安装的构建（不回滚）
+    pub keep: bool,
+    /// 安装后不重启设备
+    pub no_reboot: bool,
+    /// 等待开机完成的超时时间（秒）
+    pub boot_timeout: u64,
+    /// 运行安装后验证脚本（verify.sh）
+    pub verify: bool,
+    /// 开机后截屏
+    pub screenshot: bool,
+    /// 开机后录屏的时长（秒），None 表示不录屏
+    pub screenrecord: Option<u64>,
+}
+
+impl Default for TestOptions {
+    fn default() -> Self {
+        Self {
+            serial: None,
+            keep: false,
+            no_reboot: false,
+            boot_timeout: 120,
+            verify: false,
+            screenshot: false,
+            screenrecord: None,
+        }
+    }
+}
+
+/// 在设备上测试最新构建：快照 → 安装 → 重启验证 → 自动回滚
+pub fn run_device_test(project_path: &Path, options: &TestOptions) -> Result<()> {
+    let adb = connect(project_path, options.serial.clone())?;
+    run_device_test_on(&adb, project_path, options)
+}
+
+/// 在已连接的设备（或模拟设备）上执行 rmm test 流程
+pub fn run_device_test_on(adb: &Adb, project_path: &Path, options: &TestOptions) -> Result<()> {
+    let module_id = read_module_id(project_path)?;
+    let adb = adb.clone();
+    let zip_path = resolve_artifact(&adb, project_path, None)?;
+    let manager = adb.detect_manager()?;
+    println!("{} 检测到 Root 管理器: {}", "[+]".green().bold(), manager.as_str().bright_white());
+
+    // 1. 安装前快照
+    snapshot_module(&adb, &module_id)?;
+
+    // 2. 推送并安装
+    let file_name = zip_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let remote_zip = format!("{}/{}", DEVICE_TMP_DIR, file_name);
+    let install = adb.push(&zip_path, &remote_zip)
+        .and_then(|_| adb.su(&manager.install_command(&remote_zip)));
+    let _ = adb.shell(&format!("rm -f {}", shell_quote(&remote_zip)));
+    if let Err(e) = install {
+        println!("{} 安装失败，恢复快照", "[!]".yellow());
+        rollback_module(&adb, &module_id, None)?;
+        return Err(e);
+    }
+    println!("{} 已安装 {}", "[+]".green().bold(), file_name);
+    record_event_or_warn(project_path, "device_install", &[
+        ("artifact", file_name.clone()),
+        ("device", options.serial.clone().unwrap_or_else(|| "default".to_string())),
+        ("manager", manager.as_str().to_string()),
+    ]);
+
+    // 3. 重启并确认设备正常开机
+    let mut result = Ok(());
+    if !options.no_reboot {
+        println!("{} 重启设备，等待开机完成（最多 {} 秒）", "[*]".cyan(), options.boot_timeout);
+        adb.reboot()?;
+        if !adb.wait_for_boot(options.boot_timeout) {
+            result = Err(anyhow::anyhow!("设备未在 {} 秒内完成开机，可能发生了 bootloop", options.boot_timeout));
+        } else {
+            let module_dir = shell_quote(&format!("{}/{}", MODULES_DIR, module_id));
+            let state = adb.su(&format!(
+                "[ -d {m} ] && [ ! -e {m}/disable ] && echo active || echo inactive",
+                m = module_dir
+            ))?;
+            if state.trim() == "active" {
+                println!("{} 模块已加载且处于启用状态", "✅".green().bold());
+            } else {
+                result = Err(anyhow::anyhow!("重启后模块未处于启用状态"));
+            }
+        }
+    }
+
+    // 截屏 / 录屏作为视觉证据（开机失败时同样尝试，便于排查 bootloop）
+    if options.screenshot || options.screenrecord.is_some() {
+        capture_screen(&adb, project_path, options);
+    }
+
+    // 4. 安装后验证
+    if options.verify && result.is_ok() {
+        if options.no_reboot {
+            println!("{} 未重启设备，挂载相关的断言可能失败", "[!]".yellow());
+        }
+        result = run_verification(&adb, project_path, &module_id);
+    }
+
+    // 5. 自动清理：恢复测试前的状态
+    if result.is_err() || !options.keep {
+        rollback_module(&adb, &module_id, None)?;
+        if !options.no_reboot {
+            println!("{} 已恢复测试前的模块状态，重启后生效", "[i]".cyan());
+        }
+    } else {
+        println!("{} 保留已安装的测试构建 (--keep)", "[i]".cyan());
+    }
+
+    result
+}
+
+/// 在设备上运行 verify.sh 并写入 .rmmp/verify-report.json
+/// 优先使用模块内打包的脚本（调试构建），否则根据 [verify] 配置临时生成
+fn run_verification(adb: &Adb, project_path: &Path, module_id: &str) -> Result<()> {
+    let installed = format!("{}/{}/{}", MODULES_DIR, module_id, VERIFY_SCRIPT);
+    let has_installed = adb.su(&format!("[ -f {} ] && echo yes || echo no", shell_quote(&installed)))?
+        .trim() == "yes";
+
+    let output = if has_installed {
+        println!("{} 运行模块内的 {}", "[*]".cyan(), VERIFY_SCRIPT);
+        adb.su(&format!("sh {} || true", shell_quote(&installed)))?
+    } else {
+        let config = load_verify_config(project_path)?
+            .ok_or_else(|| anyhow::anyhow!("rmmproject.toml 中没有 [verify] 配置，且模块未包含 {}", VERIFY_SCRIPT))?;
+        let local = TempGuard::new(project_path.join(".rmmp").join(VERIFY_SCRIPT));
+        fs::write(local.path(), generate_verify_script(&config))?;
+        let remote = format!("{}/rmm-{}", DEVICE_TMP_DIR, VERIFY_SCRIPT);
+        adb.push(local.path(), &remote)?;
+        println!("{} 运行根据 [verify] 生成的验证脚本", "[*]".cyan());
+        adb.su(&format!("sh {r} || true; rm -f {r}", r = shell_quote(&remote)))?
+    };
+
+    let results = parse_verify_output(&output);
+    print_verify_results(&results);
+    fs::write(
+        project_path.join(".rmmp").join(VERIFY_REPORT),
+        serde_json::to_string_pretty(&results)?,
+    )?;
+
+    let failed = results.iter().filter(|r| !r.passed).count();
+    if results.is_empty() {
+        anyhow::bail!("验证脚本没有输出任何断言结果");
+    }
+    if failed > 0 {
+        anyhow::bail!("{} / {} 条验证断言失败", failed, results.len());
+    }
+    Ok(())
+}
+
+/// 截图 / 录屏文件名，如 screenshot-20250101-120000.png
+fn capture_file_name(kind: &str, timestamp: &str) -> String {
+    let extension = if kind == "screenrecord" { "mp4" } else { "png" };
+    format!("{}-{}.{}", kind, timestamp, extension)
+}
+
+/// screencap -p 的输出是否为 PNG（部分设备在锁屏或开机未完成时输出为空或报错文本）
+fn is_png(data: &[u8]) -> bool {
+    data.starts_with(b"\x89PNG\r\n\x1a\n")
+}
+
+fn take_screenshot(adb: &Adb, path: &Path) -> Result<()> {
+    let data = adb.exec_out("screencap -p")?;
+    if !is_png(&data) {
+        anyhow::bail!("screencap 没有返回 PNG 图像");
+    }
+    fs::write(path, data)?;
+    Ok(())
+}
+
+fn record_screen(adb: &Adb, path: &Path, secs: u64) -> Result<()> {
+    let remote = format!("{}/rmm-screenrecord.mp4", DEVICE_TMP_DIR);
+    let result = adb.shell(&format!("screenrecord --time-limit {} {}", secs, shell_quote(&remote)))
+        .and_then(|_| adb.pull(&remote, path));
+    let _ = adb.shell(&format!("rm -f {}", shell_quote(&remote)));
+    result
+}
+
+/// 截屏 / 录屏并保存到 .rmmp/test-report；失败只提示，不影响测试结果
+fn capture_screen(adb: &Adb, project_path: &Path, options: &TestOptions) {
+    let dir = project_path.join(TEST_REPORT_DIR);
+    if let Err(e) = fs::create_dir_all(&dir) {
+        println!("{} 无法创建 {}: {}", "[!]".yellow(), dir.display(), e);
+        return;
+    }
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut captured: Vec<PathBuf> = Vec::new();
+
+    if options.screenshot {
+        let path = dir.join(capture_file_name("screenshot", &timestamp));
+        match take_screenshot(adb, &path) {
+            Ok(()) => captured.push(path),
+            Err(e) => println!("{} 截屏失败: {}", "[!]".yellow(), e),
+        }
+    }
+    if let Some(secs) = options.screenrecord {
+        let secs = secs.clamp(1, SCREENRECORD_MAX_SECS);
+        println!("{} 录屏 {} 秒", "[*]".cyan(), secs);
+        let path = dir.join(capture_file_name("screenrecord", &timestamp));
+        match record_screen(adb, &path, secs) {
+            Ok(()) => captured.push(path),
+            Err(e) => println!("{} 录屏失败: {}", "[!]".yellow(), e),
+        }
+    }
+
+    for path in &captured {
+        println!("{} 已保存 {}", "[+]".green().bold(), path.strip_prefix(project_path).unwrap_or(path).display());
+    }
+}
+
+/// 逐条打印验证结果
+fn print_verify_results(results: &[VerifyResult]) {
+    println!("{} 安装后验证:", "[verify]".magenta().bold());
+    for r in results {
+        let status = if r.passed { "✅" } else { "❌" };
+        if r.detail.is_empty() {
+            println!("    {} {:<8} {}", status, r.kind, r.target);
+        } else {
+            println!("    {} {:<8} {} {}", status, r.kind, r.target, r.detail.bright_black());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmds::device::artifact::list_artifacts;
+    use tempfile::TempDir;
+
+    fn find_latest_zip(project_path: &Path, module_id: &str) -> Option<PathBuf> {
+        list_artifacts(project_path, module_id).into_iter().next()
+    }
+
+    #[test]
+    fn test_find_latest_zip() {
+        let temp_dir = TempDir::new().unwrap();
+        let dist_dir = temp_dir.path().join(".rmmp/dist");
+        fs::create_dir_all(&dist_dir).unwrap();
+        assert!(find_latest_zip(temp_dir.path(), "demo").is_none());
+
+        fs::write(dist_dir.join("demo-100.zip"), "zip").unwrap();
+        fs::write(dist_dir.join("demo-100-source.tar.gz"), "tar").unwrap();
+        fs::write(dist_dir.join("other-200.zip"), "zip").unwrap();
+        fs::write(dist_dir.join("demo-100-symbols.zip"), "zip").unwrap();
+        let zip = find_latest_zip(temp_dir.path(), "demo").unwrap();
+        assert_eq!(zip.file_name().unwrap(), "demo-100.zip");
+    }
+
+    #[test]
+    fn test_capture_file_names() {
+        assert_eq!(capture_file_name("screenshot", "20250101-120000"), "screenshot-20250101-120000.png");
+        assert_eq!(capture_file_name("screenrecord", "20250101-120000"), "screenrecord-20250101-120000.mp4");
+        assert!(is_png(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(!is_png(b"Error opening display"));
+        assert!(!is_png(b""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_device_test_flow_on_fake_device() {
+        use crate::core::fake_device::FakeDevice;
+        use std::io::Write;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("demo");
+        fs::create_dir_all(project.join(".rmmp/dist")).unwrap();
+        fs::write(project.join("module.prop"), "id=demo\nversionCode=1\n").unwrap();
+        let mut zip = zip::ZipWriter::new(fs::File::create(project.join(".rmmp/dist/demo-1.zip")).unwrap());
+        zip.start_file("module.prop", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"id=demo\nversionCode=1\n").unwrap();
+        zip.finish().unwrap();
+
+        let device = FakeDevice::open_or_create(&temp_dir.path().join("device")).unwrap();
+        let options = TestOptions { keep: true, boot_timeout: 5, ..TestOptions::default() };
+        run_device_test_on(&device.adb().unwrap(), &project, &options).unwrap();
+
+        // 安装后重启，模块从 modules_update 移到 modules 并处于启用状态
+        assert!(device.path(&format!("{}/demo/module.prop", MODULES_DIR)).is_file());
+        assert!(!device.path("/data/adb/modules_update/demo").exists());
+        let commands = device.commands();
+        assert!(commands.iter().any(|c| c.starts_with("push ") && c.ends_with("/data/local/tmp/demo-1.zip")));
+        assert!(commands.iter().any(|c| c == "reboot"));
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::core::settings::ConfigResolver;
+use crate::core::upstream::{verify_upstream, OwnershipStatus, UpstreamVerification};
+
+use super::device::read_module_id;
+
+/// rmm upstream verify：发布到上游母仓库前检查模块 id 的命名空间归属，可选预留新 id
+pub fn verify_project(project_path: &Path, reserve: bool) -> Result<UpstreamVerification> {
+    let config = ConfigResolver::new(Some(project_path));
+    let index = config.get("upstream.index").ok_or_else(|| anyhow::anyhow!(
+        "未配置上游索引：设置 RMM_UPSTREAM_INDEX，或在 Rmake.toml [publish] 中设置 upstream_index"
+    ))?;
+    let token = config.get("upstream.token").ok_or_else(|| anyhow::anyhow!(
+        "未设置 UPSTREAM_ACCESS_TOKEN：在 GitHub 生成具有 read:user（组织发布还需 read:org）权限的令牌，并添加到仓库 secrets"
+    ))?;
+    let id = read_module_id(project_path)?;
+    verify_upstream(&index, &token, &id, reserve)
+}
+
+/// 打印检查结果
+pub fn print_verification(verification: &UpstreamVerification) {
+    let who = verification.identity.login.bright_white();
+    match &verification.status {
+        OwnershipStatus::Owned(owner) => {
+            println!("{} 模块 id {} 在上游索引中属于 {}，{} 可以发布", "[+]".green().bold(), verification.id.cyan(), owner, who);
+        }
+        OwnershipStatus::Reserved(owner) => {
+            println!("{} 已在上游为 {} 预留模块 id {}", "[+]".green().bold(), owner, verification.id.cyan());
+        }
+        OwnershipStatus::Unclaimed => {
+            println!("{} 模块 id {} 尚未在上游登记，发布前运行 rmm upstream verify --reserve 预留", "[!]".yellow(), verification.id.cyan());
+        }
+    }
+    if let Some(repository) = &verification.publisher.repository {
+        println!("    发布者: {} {}", repository, verification.publisher.workflow.as_deref().unwrap_or_default().bright_black());
+    }
+}
@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use notify::{Event, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use crate::cmds::build::{build_project_with_options, BuildOptions};
+use crate::core::process::is_cancelled;
+
+/// 收到变更后等待写入平静的时间，编辑器保存时的多个事件合并为一次构建
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// 等待事件时检查 Ctrl-C 的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 变更后需要完整构建的文件（排除规则、生成配置等可能改变）
+const FULL_REBUILD_FILES: &[&str] = &[".rmmp/Rmake.toml"];
+
+/// 一次构建前合并的文件变更
+#[derive(Debug, Default, PartialEq)]
+pub struct ChangeSet {
+    /// 变更的文件（相对项目根目录，以 / 分隔）
+    pub files: BTreeSet<String>,
+    /// 需要完整构建
+    pub full: bool,
+}
+
+impl ChangeSet {
+    /// 记录变更的路径，忽略项目外、.rmmp 与 .git 中的路径以及编辑器临时文件
+    pub fn add(&mut self, project_path: &Path, path: &Path) {
+        let Ok(relative) = path.strip_prefix(project_path) else { return };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if FULL_REBUILD_FILES.contains(&relative.as_str()) {
+            self.full = true;
+        } else if is_watched(&relative) {
+            self.files.insert(relative);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.files.is_empty()
+    }
+
+    /// 增量构建的变更文件，需要完整构建时为 None
+    fn incremental(self) -> Option<BTreeSet<String>> {
+        (!self.full).then_some(self.files)
+    }
+}
+
+/// 变更是否会影响模块内容
+pub fn is_watched(relative: &str) -> bool {
+    let first = relative.split('/').next().unwrap_or_default();
+    if first.is_empty() || matches!(first, ".rmmp" | ".git" | ".hg" | ".svn") {
+        return false;
+    }
+    let name = relative.rsplit('/').next().unwrap_or_default();
+    let temporary = name.ends_with('~')
+        || name.ends_with(".swp")
+        || name.ends_with(".swx")
+        || name.starts_with(".#")
+        || name == "4913"; // vim 检查目录是否可写时创建的文件
+    !temporary
+}
+
+/// 监听项目目录：先完整构建一次，之后在 system/、脚本或 module.prop 等文件变化时增量构建；Ctrl-C 退出
+pub fn watch_project(project_path: &Path, options: &BuildOptions) -> Result<()> {
+    let (sender, receiver) = channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let _ = sender.send(event);
+    }).context("无法创建文件监听")?;
+    watcher.watch(project_path, RecursiveMode::Recursive)
+        .with_context(|| format!("无法监听 {}", project_path.display()))?;
+
+    rebuild(project_path, options, None);
+    println!("{} 正在监听 {}（Ctrl-C 退出）", "[watch]".cyan().bold(), project_path.display());
+
+    loop {
+        let mut changes = ChangeSet::default();
+        while changes.is_empty() {
+            if is_cancelled() {
+                println!("{} 已停止监听", "[watch]".cyan().bold());
+                return Ok(());
+            }
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(event) => collect(&mut changes, project_path, event),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => anyhow::bail!("文件监听已中断"),
+            }
+        }
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            collect(&mut changes, project_path, event);
+        }
+
+        if changes.full {
+            println!("\n{} Rmake.toml 已修改，执行完整构建", "[watch]".cyan().bold());
+        } else {
+            let files: Vec<&str> = changes.files.iter().map(String::as_str).collect();
+            println!("\n{} {} 个文件变更: {}", "[watch]".cyan().bold(), files.len(), files.join(", "));
+        }
+        rebuild(project_path, options, changes.incremental());
+    }
+}
+
+fn collect(changes: &mut ChangeSet, project_path: &Path, event: notify::Result<Event>) {
+    match event {
+        Ok(event) if event.kind.is_access() => {}
+        Ok(event) => {
+            for path in &event.paths {
+                changes.add(project_path, path);
+            }
+        }
+        Err(e) => println!("{} 文件监听出错: {}", "[!]".yellow(), e),
+    }
+}
+
+/// 构建失败只打印错误，继续监听
+fn rebuild(project_path: &Path, options: &BuildOptions, incremental: Option<BTreeSet<String>>) {
+    let options = BuildOptions { incremental, ..options.clone() };
+    let started = Instant::now();
+    match build_project_with_options(project_path, &options) {
+        Ok(()) => println!("{} 构建完成（{:.1}s），继续监听", "[watch]".cyan().bold(), started.elapsed().as_secs_f64()),
+        Err(e) => println!("{} 构建失败: {}，修改文件后将重新构建", "[watch]".red().bold(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_set() {
+        let root = Path::new("/project");
+        let mut changes = ChangeSet::default();
+        changes.add(root, &root.join("system/bin/tool"));
+        changes.add(root, &root.join("customize.sh"));
+        changes.add(root, &root.join("module.prop"));
+        changes.add(root, &root.join(".rmmp/build/module.prop"));
+        changes.add(root, &root.join(".git/index"));
+        changes.add(root, &root.join("service.sh.swp"));
+        changes.add(root, &root.join("module.prop~"));
+        changes.add(root, Path::new("/elsewhere/module.prop"));
+        assert!(!changes.full);
+        assert_eq!(
+            changes.files.iter().map(String::as_str).collect::<Vec<_>>(),
+            vec!["customize.sh", "module.prop", "system/bin/tool"]
+        );
+
+        changes.add(root, &root.join(".rmmp/Rmake.toml"));
+        assert!(changes.full);
+        assert_eq!(changes.incremental(), None);
+        assert!(ChangeSet::default().is_empty());
+        assert!(!is_watched(""));
+    }
+}
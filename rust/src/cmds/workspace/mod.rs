@@ -0,0 +1,289 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::cmds::build::{build_project_with_options, BuildOptions};
+use crate::cmds::device::artifact::list_artifacts;
+use crate::core::rmm_core::{MetaConfig, RmmCore};
+use crate::core::workspace::{
+    dependency_env_var, find_workspace_root, mounted_paths, ConflictPolicy, FileConflict, Workspace, WORKSPACE_FILE,
+};
+
+/// 从 start 向上定位并加载工作区
+pub fn load_workspace(start: &Path) -> Result<Workspace> {
+    let root = find_workspace_root(start)
+        .ok_or_else(|| anyhow::anyhow!("{} 及其上级目录中没有 {}", start.display(), WORKSPACE_FILE))?;
+    Workspace::load(&root)
+}
+
+fn relative(workspace: &Workspace, path: &Path) -> String {
+    path.strip_prefix(&workspace.root).unwrap_or(path).display().to_string()
+}
+
+/// 成员 dist 目录中最新产物的修改时间，用于判断本次是否重新构建
+fn newest_artifact(member_path: &Path) -> Option<SystemTime> {
+    fs::read_dir(member_path.join(".rmmp/dist")).ok()?
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// rmm workspace graph：按构建顺序列出成员及其依赖
+pub fn show_graph(start: &Path, json: bool) -> Result<()> {
+    let workspace = load_workspace(start)?;
+    let order = workspace.build_order()?;
+    if json {
+        let value = serde_json::json!({
+            "root": workspace.root,
+            "members": workspace.members,
+            "order": order.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("{} {}", "🗂️ 工作区".green().bold(), workspace.root.display());
+    for (index, member) in order.iter().enumerate() {
+        let deps = if member.depends_on.is_empty() {
+            String::new()
+        } else {
+            format!(" ← {}", member.depends_on.join(", ")).bright_black().to_string()
+        };
+        println!("  {}. {} {}{}",
+            index + 1,
+            member.id.bold(),
+            format!("[{}]", relative(&workspace, &member.path)).cyan(),
+            deps
+        );
+    }
+    Ok(())
+}
+
+/// 比较各成员最新产物中的系统文件，返回冲突列表（没有产物的成员跳过）
+pub fn detect_conflicts(workspace: &Workspace) -> Result<Vec<FileConflict>> {
+    let mut paths = BTreeMap::new();
+    for member in &workspace.members {
+        if let Some(zip_path) = list_artifacts(&member.path, &member.id).into_iter().next() {
+            paths.insert(member.id.clone(), mounted_paths(&zip_path)?);
+        }
+    }
+    workspace.find_conflicts(&paths)
+}
+
+fn print_conflicts(conflicts: &[FileConflict]) {
+    for conflict in conflicts {
+        println!("  {} {} ← {}", "[!]".yellow(), conflict.path.bold(), conflict.members.join(", ").cyan());
+    }
+}
+
+/// 按 [workspace] conflicts 策略检查成员间的文件冲突
+fn check_conflicts(workspace: &Workspace) -> Result<()> {
+    if workspace.conflicts == ConflictPolicy::Ignore {
+        return Ok(());
+    }
+    let conflicts = detect_conflicts(workspace)?;
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    println!("{} {} 个系统文件由多个成员提供，设备上只有一个会生效:", "[workspace]".magenta().bold(), conflicts.len());
+    print_conflicts(&conflicts);
+    if workspace.conflicts == ConflictPolicy::Error {
+        anyhow::bail!("工作区成员存在 {} 个文件冲突，可在 {} 的 allow_conflicts 中放行", conflicts.len(), WORKSPACE_FILE);
+    }
+    Ok(())
+}
+
+/// rmm workspace conflicts：列出成员间的文件冲突
+pub fn show_conflicts(start: &Path, json: bool) -> Result<()> {
+    let workspace = load_workspace(start)?;
+    let conflicts = detect_conflicts(&workspace)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&conflicts)?);
+    } else if conflicts.is_empty() {
+        println!("{} 成员之间没有文件冲突", "✅".green());
+    } else {
+        print_conflicts(&conflicts);
+    }
+    Ok(())
+}
+
+/// 在 meta.toml 中带有任一指定标签的成员
+fn tagged_members(workspace: &Workspace, meta: &MetaConfig, tags: &[String]) -> BTreeSet<String> {
+    workspace.members.iter()
+        .filter(|member| meta.tags_for_path(&member.path).iter().any(|t| tags.contains(t)))
+        .map(|member| member.id.clone())
+        .collect()
+}
+
+fn join_or_none(ids: &BTreeSet<String>) -> String {
+    if ids.is_empty() { "无".to_string() } else { ids.iter().cloned().collect::<Vec<_>>().join(", ") }
+}
+
+/// rmm workspace build：按依赖顺序构建成员；指定 affected_since 时只构建变更成员及其依赖者，
+/// 指定 tags 时只构建 meta.toml 中带有这些标签的成员
+pub fn build_workspace(start: &Path, options: &BuildOptions, affected_since: Option<&str>, tags: &[String]) -> Result<()> {
+    let workspace = load_workspace(start)?;
+    let order = workspace.build_order()?;
+    let mut selected = affected_since.map(|git_ref| workspace.affected_since(git_ref)).transpose()?;
+    if let (Some(git_ref), Some(selected)) = (affected_since, &selected) {
+        println!("{} 自 {} 以来受影响的成员: {}", "[workspace]".magenta().bold(), git_ref.cyan(), join_or_none(selected));
+    }
+    if !tags.is_empty() {
+        let tagged = tagged_members(&workspace, &RmmCore::new().get_meta_config().unwrap_or_default(), tags);
+        println!("{} 带有标签 {} 的成员: {}", "[workspace]".magenta().bold(), tags.join(", ").cyan(), join_or_none(&tagged));
+        selected = Some(match selected {
+            Some(affected) => affected.intersection(&tagged).cloned().collect(),
+            None => tagged,
+        });
+    }
+
+    let mut rebuilt: BTreeSet<String> = BTreeSet::new();
+    let mut built = 0;
+    for (index, member) in order.iter().enumerate() {
+        let label = format!("[{}/{}]", index + 1, order.len());
+        if selected.as_ref().is_some_and(|s| !s.contains(&member.id)) {
+            println!("{} {} {} 未选中，跳过", "[workspace]".magenta().bold(), label, member.id.bold());
+            continue;
+        }
+        println!("{} {} 构建 {} {}",
+            "[workspace]".magenta().bold(),
+            label,
+            member.id.bold(),
+            format!("[{}]", relative(&workspace, &member.path)).cyan()
+        );
+
+        let mut member_options = options.clone();
+        for dep in &member.depends_on {
+            let dist: PathBuf = workspace.member(dep).map(|d| d.path.join(".rmmp/dist")).unwrap_or_default();
+            member_options.env.insert(dependency_env_var(dep), dist.display().to_string());
+        }
+        // 依赖本次重新构建过，其产物不在成员自身的缓存指纹中，需强制构建
+        if member.depends_on.iter().any(|d| rebuilt.contains(d)) {
+            member_options.force = true;
+        }
+
+        crate::core::process::configure_from_project(&member.path);
+        let before = newest_artifact(&member.path);
+        build_project_with_options(&member.path, &member_options)
+            .with_context(|| format!("工作区成员 {} 构建失败", member.id))?;
+        if newest_artifact(&member.path) != before {
+            rebuilt.insert(member.id.clone());
+        }
+        built += 1;
+    }
+
+    println!("{} 完成：构建 {} 个成员，其中 {} 个重新生成产物，{} 个跳过",
+        "[workspace]".magenta().bold(),
+        built,
+        rebuilt.len(),
+        order.len() - built
+    );
+    check_conflicts(&workspace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_member(root: &Path, dir: &str, id: &str, deps: &str) {
+        let path = root.join(dir);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("module.prop"), format!("id={}\nversion=v1\nversionCode=1\n", id)).unwrap();
+        fs::write(path.join("rmmproject.toml"), format!(
+            "authors = []\n\n[project]\nid = \"{}\"\ndescription = \"\"\nreadme = \"\"\nchangelog = \"\"\nlicense = \"\"\ndependencies = [{}]\n",
+            id, deps
+        )).unwrap();
+    }
+
+    #[test]
+    fn test_affected_since_includes_dependents() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join(WORKSPACE_FILE), "[workspace]\nmembers = [\"modules/*\", \"tools/extra\"]\nexclude = [\"modules/old\"]\n").unwrap();
+        write_member(root, "modules/core", "core_lib", "");
+        write_member(root, "modules/feature", "feature", "\"core_lib>=1\"");
+        write_member(root, "modules/old", "old", "");
+        write_member(root, "tools/extra", "extra", "\"core @ ../../modules/core\"");
+
+        let repo = git2::Repository::init(root).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[]).unwrap();
+
+        let workspace = load_workspace(&root.join("modules/feature")).unwrap();
+        let order: Vec<&str> = workspace.build_order().unwrap().iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(order, vec!["core_lib", "feature", "extra"]);
+        assert_eq!(workspace.member("extra").unwrap().depends_on, vec!["core_lib".to_string()]);
+        assert!(workspace.member("old").is_none());
+        assert!(workspace.affected_since("HEAD").unwrap().is_empty());
+
+        fs::write(root.join("modules/feature/service.sh"), "#!/system/bin/sh\n").unwrap();
+        assert_eq!(workspace.affected_since("HEAD").unwrap(), BTreeSet::from(["feature".to_string()]));
+
+        fs::write(root.join("modules/core/module.prop"), "id=core_lib\nversion=v2\nversionCode=2\n").unwrap();
+        let affected = workspace.affected_since("HEAD").unwrap();
+        assert_eq!(affected, BTreeSet::from(["core_lib".to_string(), "extra".to_string(), "feature".to_string()]));
+        assert!(workspace.affected_since("no-such-ref").is_err());
+    }
+
+    #[test]
+    fn test_tagged_members_from_registry() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join(WORKSPACE_FILE), "[workspace]\nmembers = [\"modules/*\"]\n").unwrap();
+        write_member(root, "modules/a", "a", "");
+        write_member(root, "modules/b", "b", "");
+        let workspace = load_workspace(root).unwrap();
+
+        let mut meta = MetaConfig::default();
+        meta.projects.insert("alpha".to_string(), root.join("modules/a/.").display().to_string());
+        meta.projects.insert("beta".to_string(), root.join("modules/b").display().to_string());
+        meta.tags.insert("alpha".to_string(), BTreeSet::from(["zygisk".to_string()]));
+        meta.tags.insert("beta".to_string(), BTreeSet::from(["work".to_string()]));
+
+        assert_eq!(tagged_members(&workspace, &meta, &["zygisk".to_string()]), BTreeSet::from(["a".to_string()]));
+        assert_eq!(tagged_members(&workspace, &meta, &["zygisk".to_string(), "work".to_string()]).len(), 2);
+        assert!(tagged_members(&workspace, &meta, &["none".to_string()]).is_empty());
+    }
+
+    fn write_artifact(root: &Path, dir: &str, name: &str, entries: &[&str]) {
+        use std::io::Write;
+        let dist = root.join(dir).join(".rmmp/dist");
+        fs::create_dir_all(&dist).unwrap();
+        let mut writer = zip::ZipWriter::new(fs::File::create(dist.join(name)).unwrap());
+        for entry in entries {
+            writer.start_file(*entry, zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(b"x").unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_detect_conflicts_between_members() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join(WORKSPACE_FILE), "[workspace]\nmembers = [\"modules/*\"]\nconflicts = \"error\"\nallow_conflicts = [\"/system/etc/permissions/*\"]\n").unwrap();
+        write_member(root, "modules/a", "mod_a", "");
+        write_member(root, "modules/b", "mod_b", "");
+        write_member(root, "modules/c", "mod_c", "");
+        write_artifact(root, "modules/a", "mod_a-1.zip", &["module.prop", "service.sh", "system/etc/hosts", "system/vendor/etc/audio.conf", "system/etc/permissions/a.xml"]);
+        write_artifact(root, "modules/b", "mod_b-1.zip", &["module.prop", "service.sh", "system/etc/hosts", "vendor/etc/audio.conf", "system/etc/permissions/a.xml"]);
+        write_artifact(root, "modules/b", "mod_b-1-recovery.zip", &["system/bin/tool"]);
+        write_artifact(root, "modules/c", "mod_c-1.zip", &["module.prop", "system/bin/tool"]);
+
+        let workspace = load_workspace(root).unwrap();
+        assert_eq!(workspace.conflicts, ConflictPolicy::Error);
+        let conflicts = detect_conflicts(&workspace).unwrap();
+        assert_eq!(conflicts, vec![
+            FileConflict { path: "/system/etc/hosts".to_string(), members: vec!["mod_a".to_string(), "mod_b".to_string()] },
+            FileConflict { path: "/vendor/etc/audio.conf".to_string(), members: vec!["mod_a".to_string(), "mod_b".to_string()] },
+        ]);
+        assert!(check_conflicts(&workspace).unwrap_err().to_string().contains("2 个文件冲突"));
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fs;
+use std::path::Path;
+
+use super::adb::shell_quote;
+
+/// action.sh 文件名（管理器 "Action" 按钮执行）
+pub const ACTION_SCRIPT: &str = "action.sh";
+
+/// Rmake.toml 中的 [action] 配置：声明式生成菜单式 action.sh
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct ActionConfig {
+    /// 菜单标题
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// 菜单项，按音量键选择
+    #[serde(default)]
+    pub items: Vec<ActionItem>,
+}
+
+/// 单个菜单项
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ActionItem {
+    /// 显示名称
+    pub label: String,
+    /// 选中后执行的 shell 命令（可使用 $MODDIR）
+    pub command: String,
+}
+
+/// 根据 [action] 配置生成菜单式 action.sh
+/// 只有一个菜单项时直接执行，多个时用音量+切换、音量-确认
+pub fn generate_action_script(config: &ActionConfig) -> Result<String> {
+    if config.items.is_empty() {
+        anyhow::bail!("[action] 至少需要一个菜单项");
+    }
+
+    let mut script = String::from("#!/system/bin/sh\n# 由 rmm 根据 Rmake.toml [action] 生成，请勿直接修改\nMODDIR=${0%/*}\n\n");
+    if let Some(title) = &config.title {
+        script.push_str(&format!("echo {}\n", shell_quote(title)));
+    }
+
+    if let [item] = config.items.as_slice() {
+        script.push_str(&format!("echo {}\n{}\n", shell_quote(&format!("- {}", item.label)), item.command));
+        return Ok(script);
+    }
+
+    script.push_str(r#"echo "音量+ 切换选项，音量- 确认"
+
+wait_key() {
+    while true; do
+        key=$(getevent -qlc 1 2>/dev/null | awk '/KEY_VOLUME/ && / DOWN/ {print $3}')
+        case "$key" in
+            KEY_VOLUMEUP) return 0 ;;
+            KEY_VOLUMEDOWN) return 1 ;;
+        esac
+    done
+}
+
+show() {
+    case $index in
+"#);
+    for (i, item) in config.items.iter().enumerate() {
+        script.push_str(&format!("        {}) echo {} ;;\n", i + 1, shell_quote(&format!("> {}", item.label))));
+    }
+    script.push_str(&format!(
+        "    esac\n}}\n\nindex=1\nwhile true; do\n    show\n    if wait_key; then\n        index=$((index % {} + 1))\n    else\n        break\n    fi\ndone\n\ncase $index in\n",
+        config.items.len()
+    ));
+    for (i, item) in config.items.iter().enumerate() {
+        script.push_str(&format!("    {})\n        {}\n        ;;\n", i + 1, item.command));
+    }
+    script.push_str("esac\n");
+    Ok(script)
+}
+
+/// 校验 action.sh：必须非空且以 shebang 开头
+/// Unix 上缺少可执行权限时自动补全，返回是否修正了权限
+pub fn validate_action_script(path: &Path) -> Result<bool> {
+    let content = fs::read_to_string(path)?;
+    let first_line = content.lines().next().unwrap_or("");
+    if !first_line.starts_with("#!") {
+        anyhow::bail!("{} 缺少 shebang（如 #!/system/bin/sh）", ACTION_SCRIPT);
+    }
+    if content.lines().skip(1).all(|l| l.trim().is_empty() || l.trim_start().starts_with('#')) {
+        anyhow::bail!("{} 没有可执行的内容", ACTION_SCRIPT);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(path)?.permissions();
+        if perms.mode() & 0o111 == 0 {
+            perms.set_mode(0o755);
+            fs::set_permissions(path, perms)?;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_action_script_generation_and_validation() {
+        let mut config = ActionConfig { title: Some("Demo".to_string()), items: Vec::new() };
+        assert!(generate_action_script(&config).is_err());
+
+        config.items.push(ActionItem { label: "Enable".to_string(), command: "touch $MODDIR/on".to_string() });
+        let single = generate_action_script(&config).unwrap();
+        assert!(single.starts_with("#!/system/bin/sh"));
+        assert!(single.contains("touch $MODDIR/on"));
+        assert!(!single.contains("wait_key"));
+
+        config.items.push(ActionItem { label: "Don't".to_string(), command: "rm -f $MODDIR/on".to_string() });
+        let menu = generate_action_script(&config).unwrap();
+        assert!(menu.contains("wait_key"));
+        assert!(menu.contains("index % 2 + 1"));
+        assert!(menu.contains(r"'> Don'\''t'"));
+
+        let temp_dir = tempdir().unwrap();
+        let action_path = temp_dir.path().join("action.sh");
+        fs::write(&action_path, "echo hi\n").unwrap();
+        assert!(validate_action_script(&action_path).is_err());
+        fs::write(&action_path, "#!/system/bin/sh\n# only comments\n").unwrap();
+        assert!(validate_action_script(&action_path).is_err());
+        fs::write(&action_path, menu).unwrap();
+        assert!(validate_action_script(&action_path).is_ok());
+    }
+}
@@ -0,0 +1,403 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+use super::process::{is_cancelled, CommandExt, CommandKind};
+use super::settings::ConfigResolver;
+
+/// 设备上的模块目录
+pub const MODULES_DIR: &str = "/data/adb/modules";
+/// 设备上待更新的模块目录
+pub const MODULES_UPDATE_DIR: &str = "/data/adb/modules_update";
+/// 设备上的临时目录
+pub const DEVICE_TMP_DIR: &str = "/data/local/tmp";
+/// Root 管理器二进制的安装位置（ksud / apd 位于 /data/adb，magisk 位于 /data/adb/magisk）
+const MANAGER_BIN_DIRS: &str = "/data/adb:/data/adb/magisk";
+
+/// 设备上的 Root 管理器
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RootManager {
+    Magisk,
+    KernelSU,
+    APatch,
+}
+
+impl RootManager {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RootManager::Magisk => "magisk",
+            RootManager::KernelSU => "kernelsu",
+            RootManager::APatch => "apatch",
+        }
+    }
+
+    /// 管理器应用包名
+    pub fn app_package(&self) -> &'static str {
+        match self {
+            RootManager::Magisk => "com.topjohnwu.magisk",
+            RootManager::KernelSU => "me.weishu.kernelsu",
+            RootManager::APatch => "me.bmax.apatch",
+        }
+    }
+
+    /// 安装模块 zip 的命令（PATH 中补充 /data/adb 与 /data/adb/magisk）
+    pub fn install_command(&self, zip_path: &str) -> String {
+        let zip = shell_quote(zip_path);
+        let command = match self {
+            RootManager::Magisk => format!("magisk --install-module {}", zip),
+            RootManager::KernelSU => format!("ksud module install {}", zip),
+            RootManager::APatch => format!("apd module install {}", zip),
+        };
+        format!("PATH=\"$PATH:{}\" {}", MANAGER_BIN_DIRS, command)
+    }
+}
+
+/// meta.toml 中记录的无线调试设备（以主机地址为键）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default, JsonSchema)]
+pub struct KnownDevice {
+    /// 最近一次成功连接的地址（ip:port，无线调试端口每次开启可能变化）
+    pub address: String,
+    /// 设备型号（ro.product.model）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub model: String,
+    /// 最近一次连接时间（RFC 3339）
+    pub last_connected: String,
+}
+
+/// adb 命令的输出
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdbOutput {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// adb 命令的执行后端：默认调用 adb 程序，CI 与测试中可替换为模拟设备（见 fake_device）
+pub trait AdbBackend: std::fmt::Debug + Send + Sync {
+    /// 执行 adb 命令；serial 为 None 时作用于 adb server（connect / devices 等）
+    fn execute(&self, serial: Option<&str>, args: &[&str]) -> Result<AdbOutput>;
+}
+
+/// adb 命令封装
+#[derive(Debug, Clone)]
+pub struct Adb {
+    program: String,
+    serial: Option<String>,
+    envs: BTreeMap<String, String>,
+    /// 替换 adb 程序的后端，None 时调用 program
+    backend: Option<Arc<dyn AdbBackend>>,
+}
+
+impl Adb {
+    /// 按分层配置创建：device.adb（RMM_ADB）与 device.serial（--serial > ANDROID_SERIAL > .rmm.env）；
+    /// 设置了 device.fake（RMM_FAKE_DEVICE）时使用该目录中的模拟设备
+    pub fn from_config(config: &ConfigResolver) -> Self {
+        let adb = Self {
+            program: config.get("device.adb").unwrap_or_else(|| "adb".to_string()),
+            serial: config.get("device.serial"),
+            envs: BTreeMap::new(),
+            backend: None,
+        };
+        #[cfg(unix)]
+        if let Some(root) = config.get("device.fake") {
+            return match super::fake_device::FakeDevice::open_or_create(Path::new(&root)) {
+                Ok(device) => adb.with_backend(Arc::new(device)),
+                Err(e) => {
+                    eprintln!("⚠️  无法打开模拟设备 {}: {}", root, e);
+                    adb
+                }
+            };
+        }
+        adb
+    }
+
+    /// 直接使用指定后端（模拟设备）的 Adb
+    #[cfg(test)]
+    pub fn from_backend(backend: Arc<dyn AdbBackend>) -> Self {
+        Self { program: "adb".to_string(), serial: None, envs: BTreeMap::new(), backend: Some(backend) }
+    }
+
+    /// 使用指定的后端代替 adb 程序
+    pub fn with_backend(mut self, backend: Arc<dyn AdbBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// 为 adb 进程附加环境变量（如项目 .rmm.env 中的 ANDROID_SERIAL）
+    pub fn with_envs(mut self, envs: BTreeMap<String, String>) -> Self {
+        self.envs = envs;
+        self
+    }
+
+    pub fn serial(&self) -> Option<&str> {
+        self.serial.as_deref()
+    }
+
+    /// 切换到指定设备
+    pub fn with_serial(mut self, serial: &str) -> Self {
+        self.serial = Some(serial.to_string());
+        self
+    }
+
+    /// 通过后端或 adb 程序执行命令（serial 为 None 时不带 -s）
+    fn execute(&self, serial: Option<&str>, args: &[&str]) -> Result<AdbOutput> {
+        if let Some(backend) = &self.backend {
+            return backend.execute(serial, args);
+        }
+        let mut cmd = Command::new(&self.program);
+        cmd.envs(&self.envs);
+        if let Some(serial) = serial {
+            cmd.args(["-s", serial]);
+        }
+        let output = cmd.args(args).output_with_timeout(CommandKind::Adb)?;
+        Ok(AdbOutput { success: output.status.success(), stdout: output.stdout, stderr: output.stderr })
+    }
+
+    fn run(&self, args: &[&str]) -> Result<AdbOutput> {
+        let output = self.execute(self.serial.as_deref(), args)?;
+        if !output.success {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if is_connection_lost(&stderr) {
+                anyhow::bail!(
+                    "adb {} 失败: 设备连接已断开{}（{}）。请确认设备与电脑在同一网络且无线调试仍开启，然后运行 rmm device connect 重新连接",
+                    args.first().unwrap_or(&""),
+                    self.serial.as_deref().map(|s| format!(" {}", s)).unwrap_or_default(),
+                    stderr.trim()
+                );
+            }
+            anyhow::bail!(
+                "adb {} 失败: {}",
+                args.first().unwrap_or(&""),
+                stderr.trim()
+            );
+        }
+        Ok(output)
+    }
+
+    /// 执行作用于 adb server 的命令，返回 stdout 与 stderr 合并后的文本
+    fn run_server(&self, args: &[&str]) -> Result<String> {
+        let output = self.execute(None, args)?;
+        let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+        if !output.success {
+            anyhow::bail!("adb {} 失败: {}", args.first().unwrap_or(&""), text.trim());
+        }
+        Ok(text)
+    }
+
+    /// 使用配对码与开启了无线调试的设备配对（配对端口与连接端口不同）
+    pub fn pair(&self, address: &str, code: &str) -> Result<()> {
+        let output = self.run_server(&["pair", address, code])?;
+        if !output.contains("Successfully paired") {
+            anyhow::bail!("与 {} 配对失败: {}", address, output.trim());
+        }
+        Ok(())
+    }
+
+    /// 连接无线调试设备（adb connect 失败时退出码仍为 0，需要检查输出）
+    pub fn connect_address(&self, address: &str) -> Result<()> {
+        let output = self.run_server(&["connect", address])?;
+        if !is_connect_success(&output) {
+            anyhow::bail!("无法连接 {}: {}", address, output.trim());
+        }
+        Ok(())
+    }
+
+    /// 断开无线调试设备
+    pub fn disconnect_address(&self, address: &str) -> Result<()> {
+        self.run_server(&["disconnect", address])?;
+        Ok(())
+    }
+
+    /// 已连接的设备：(序列号, 状态)
+    pub fn devices(&self) -> Result<Vec<(String, String)>> {
+        Ok(parse_devices(&self.run_server(&["devices"])?))
+    }
+
+    /// 当前设备状态（device / offline / unauthorized），无法获取时为 None
+    pub fn state(&self) -> Option<String> {
+        let output = self.execute(self.serial.as_deref(), &["get-state"]).ok()?;
+        output.success.then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// 通过 mDNS 查找主机当前的无线调试连接地址
+    pub fn discover_address(&self, host: &str) -> Option<String> {
+        parse_mdns_connect_address(&self.run_server(&["mdns", "services"]).ok()?, host)
+    }
+
+    /// 在设备上执行 shell 命令
+    pub fn shell(&self, command: &str) -> Result<String> {
+        let output = self.run(&["shell", command])?;
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// 通过 exec-out 执行命令并返回原始输出（不经过终端转换，适合二进制数据）
+    pub fn exec_out(&self, command: &str) -> Result<Vec<u8>> {
+        Ok(self.run(&["exec-out", command])?.stdout)
+    }
+
+    /// 以 root 身份在设备上执行 shell 命令
+    pub fn su(&self, command: &str) -> Result<String> {
+        self.shell(&format!("su -c {}", shell_quote(command)))
+    }
+
+    /// 推送文件到设备
+    pub fn push(&self, local: &Path, remote: &str) -> Result<()> {
+        self.run(&["push", &local.to_string_lossy(), remote])?;
+        Ok(())
+    }
+
+    /// 从设备拉取文件
+    pub fn pull(&self, remote: &str, local: &Path) -> Result<()> {
+        self.run(&["pull", remote, &local.to_string_lossy()])?;
+        Ok(())
+    }
+
+    /// 重启设备
+    pub fn reboot(&self) -> Result<()> {
+        self.run(&["reboot"])?;
+        Ok(())
+    }
+
+    /// 等待设备连接并完成开机，超时返回 false
+    pub fn wait_for_boot(&self, timeout_secs: u64) -> bool {
+        let start = std::time::Instant::now();
+        while start.elapsed().as_secs() < timeout_secs && !is_cancelled() {
+            if self.shell("getprop sys.boot_completed").is_ok_and(|v| v.trim() == "1") {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_secs(2));
+        }
+        false
+    }
+
+    /// 设备支持的 ABI，按优先级排序（如 arm64-v8a, armeabi-v7a）
+    pub fn abis(&self) -> Result<Vec<String>> {
+        let output = self.shell("getprop ro.product.cpu.abilist; getprop ro.product.cpu.abi")?;
+        let mut abis: Vec<String> = Vec::new();
+        for abi in output.split([',', '\n']).map(str::trim).filter(|a| !a.is_empty()) {
+            if !abis.iter().any(|a| a == abi) {
+                abis.push(abi.to_string());
+            }
+        }
+        if abis.is_empty() {
+            anyhow::bail!("无法读取设备 ABI");
+        }
+        Ok(abis)
+    }
+
+    /// 设备上已安装的模块 id
+    pub fn installed_modules(&self) -> Result<Vec<String>> {
+        let output = self.su(&format!("ls {}", MODULES_DIR))?;
+        Ok(output.split_whitespace().map(str::to_string).collect())
+    }
+
+    /// 检测设备上的 Root 管理器
+    pub fn detect_manager(&self) -> Result<RootManager> {
+        // 管理器二进制不一定在 su 的 PATH 中，先检查各自的安装位置；
+        // 最后一个 command -v 未找到时退出码非 0，adb shell 会传回该退出码
+        let output = self.su("[ -x /data/adb/ksud ] && echo /data/adb/ksud; \
+            [ -x /data/adb/apd ] && echo /data/adb/apd; \
+            [ -d /data/adb/magisk ] && echo /data/adb/magisk; \
+            command -v ksud; command -v apd; command -v magisk; true")?;
+        parse_manager(&output)
+            .ok_or_else(|| anyhow::anyhow!("未在设备上检测到 Magisk / KernelSU / APatch"))
+    }
+}
+
+/// 判断 adb 错误是否由连接断开引起
+pub fn is_connection_lost(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    let device_missing = stderr.contains("device '") && stderr.contains("' not found");
+    device_missing || ["device offline", "no devices/emulators found", "connection reset", "protocol fault", "error: closed"]
+        .iter()
+        .any(|marker| stderr.contains(marker))
+}
+
+/// adb connect 的输出是否表示连接成功
+fn is_connect_success(output: &str) -> bool {
+    output.lines().any(|line| line.starts_with("connected to") || line.starts_with("already connected to"))
+}
+
+/// 形如 192.168.1.5:37123 的网络设备序列号
+pub fn is_network_address(serial: &str) -> bool {
+    serial.rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+}
+
+/// 网络地址中的主机部分
+pub fn address_host(address: &str) -> &str {
+    address.rsplit_once(':').map_or(address, |(host, _)| host)
+}
+
+/// 解析 adb devices 输出
+pub(crate) fn parse_devices(output: &str) -> Vec<(String, String)> {
+    output.lines()
+        .skip_while(|line| !line.starts_with("List of devices"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some((parts.next()?.to_string(), parts.next()?.to_string()))
+        })
+        .collect()
+}
+
+/// 从 adb mdns services 输出中找出指定主机的 _adb-tls-connect 地址
+pub(crate) fn parse_mdns_connect_address(output: &str, host: &str) -> Option<String> {
+    output.lines()
+        .filter(|line| line.contains("_adb-tls-connect."))
+        .filter_map(|line| line.split_whitespace().last())
+        .find(|address| is_network_address(address) && address_host(address) == host)
+        .map(str::to_string)
+}
+
+/// 根据安装位置检查与 `command -v` 的输出判断 Root 管理器（同时存在时 KernelSU / APatch 优先）
+fn parse_manager(output: &str) -> Option<RootManager> {
+    let found = |name: &str| output.lines().any(|l| l.trim().ends_with(&format!("/{}", name)));
+    if found("ksud") {
+        Some(RootManager::KernelSU)
+    } else if found("apd") {
+        Some(RootManager::APatch)
+    } else if found("magisk") {
+        Some(RootManager::Magisk)
+    } else {
+        None
+    }
+}
+
+/// 将字符串转义为 POSIX shell 单引号字面量
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wireless_adb_helpers() {
+        assert!(is_network_address("192.168.1.5:37123"));
+        assert!(is_network_address("[fe80::1]:5555"));
+        assert!(!is_network_address("emulator-5554"));
+        assert!(!is_network_address("R58M12ABCDE"));
+        assert_eq!(address_host("192.168.1.5:37123"), "192.168.1.5");
+
+        assert!(is_connection_lost("adb: device offline"));
+        assert!(is_connection_lost("error: device '192.168.1.5:37123' not found"));
+        assert!(is_connection_lost("adb: error: closed"));
+        assert!(!is_connection_lost("/system/bin/sh: magisk: inaccessible or not found"));
+        assert!(!is_connection_lost("adb: error: failed to stat remote object '/x': No such file or directory"));
+
+        let devices = parse_devices("* daemon started successfully\nList of devices attached\n192.168.1.5:37123\tdevice\nemulator-5554\toffline\n\n");
+        assert_eq!(devices, vec![
+            ("192.168.1.5:37123".to_string(), "device".to_string()),
+            ("emulator-5554".to_string(), "offline".to_string()),
+        ]);
+        let mdns = "List of discovered mdns services\nadb-R58M-abc\t_adb-tls-pairing._tcp.\t192.168.1.5:41000\nadb-R58M-abc\t_adb-tls-connect._tcp.\t192.168.1.5:39211\nadb-other\t_adb-tls-connect._tcp.\t192.168.1.9:40000\n";
+        assert_eq!(parse_mdns_connect_address(mdns, "192.168.1.5").as_deref(), Some("192.168.1.5:39211"));
+        assert_eq!(parse_mdns_connect_address(mdns, "192.168.1.7"), None);
+    }
+}
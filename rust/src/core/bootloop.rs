@@ -0,0 +1,147 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 计数器所在的启动阶段脚本
+pub const POST_FS_DATA_SCRIPT: &str = "post-fs-data.sh";
+/// 启动完成后清除计数的脚本
+pub const SERVICE_SCRIPT: &str = "service.sh";
+/// 模块目录中记录未完成启动次数的文件
+pub const BOOT_ATTEMPTS_MARKER: &str = ".rmm_boot_attempts";
+/// 被守护禁用时写入的说明文件
+pub const BOOTLOOP_REASON_FILE: &str = ".rmm_bootloop_disabled";
+/// 允许的最大连续失败次数
+const MAX_ATTEMPTS_LIMIT: u32 = 10;
+
+/// Rmake.toml 中的 [bootloop_guard]：连续多次未完成启动时自动禁用模块
+///
+/// ```toml
+/// [bootloop_guard]
+/// max_attempts = 3   # 连续 3 次启动未完成后，下次启动时禁用模块
+/// safe_mode = true   # 系统以安全模式启动时同样禁用
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct BootloopGuardConfig {
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_safe_mode")]
+    pub safe_mode: bool,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_safe_mode() -> bool {
+    true
+}
+
+impl Default for BootloopGuardConfig {
+    fn default() -> Self {
+        Self { max_attempts: default_max_attempts(), safe_mode: default_safe_mode() }
+    }
+}
+
+/// 检查配置
+pub fn validate_guard(config: &BootloopGuardConfig) -> Result<()> {
+    if !(1..=MAX_ATTEMPTS_LIMIT).contains(&config.max_attempts) {
+        anyhow::bail!("[bootloop_guard] max_attempts 必须在 1-{} 之间: {}", MAX_ATTEMPTS_LIMIT, config.max_attempts);
+    }
+    Ok(())
+}
+
+/// 在 shebang 之后插入片段，守护逻辑需先于模块自身的代码执行；没有脚本时创建
+pub(crate) fn insert_after_shebang(existing: Option<&str>, snippet: &str) -> String {
+    match existing {
+        Some(existing) if existing.starts_with("#!") => {
+            let (shebang, rest) = existing.split_once('\n').unwrap_or((existing, ""));
+            format!("{}\n{}{}", shebang, snippet, rest)
+        }
+        Some(existing) => format!("#!/system/bin/sh\n{}{}", snippet, existing),
+        None => format!("#!/system/bin/sh\n{}", snippet),
+    }
+}
+
+/// post-fs-data.sh：每次启动计数加一，超过 max_attempts 时写入 disable 并跳过本脚本其余部分
+pub fn guard_post_fs_data(config: &BootloopGuardConfig, existing: Option<&str>) -> String {
+    let max = config.max_attempts;
+    let snippet = format!(r#"# 由 rmm 根据 Rmake.toml [bootloop_guard] 生成：连续 {max} 次启动未完成时禁用模块
+RMM_GUARD_DIR=${{0%/*}}
+RMM_BOOT_ATTEMPTS=$(tr -dc '0-9' < "$RMM_GUARD_DIR/{marker}" 2>/dev/null)
+RMM_BOOT_ATTEMPTS=$(( ${{RMM_BOOT_ATTEMPTS:-0}} + 1 ))
+if [ "$RMM_BOOT_ATTEMPTS" -gt {max} ]; then
+    touch "$RMM_GUARD_DIR/disable"
+    rm -f "$RMM_GUARD_DIR/{marker}"
+    echo "连续 {max} 次启动未完成，rmm bootloop guard 已禁用模块 ($(date))" > "$RMM_GUARD_DIR/{reason}"
+    exit 0
+fi
+echo "$RMM_BOOT_ATTEMPTS" > "$RMM_GUARD_DIR/{marker}"
+
+"#, marker = BOOT_ATTEMPTS_MARKER, reason = BOOTLOOP_REASON_FILE);
+    insert_after_shebang(existing, &snippet)
+}
+
+/// service.sh：后台等待 sys.boot_completed 后清除计数；启用 safe_mode 时检测系统安全模式
+pub fn guard_service(config: &BootloopGuardConfig, existing: Option<&str>) -> String {
+    let safe_mode = if config.safe_mode {
+        format!(r#"    if [ "$(getprop ro.sys.safemode)" = "1" ] || [ "$(getprop persist.sys.safemode)" = "1" ]; then
+        touch "$RMM_GUARD_DIR/disable"
+        echo "系统以安全模式启动，rmm bootloop guard 已禁用模块 ($(date))" > "$RMM_GUARD_DIR/{reason}"
+    fi
+"#, reason = BOOTLOOP_REASON_FILE)
+    } else {
+        String::new()
+    };
+    let snippet = format!(r#"# 由 rmm 根据 Rmake.toml [bootloop_guard] 生成：启动完成后清除未完成启动计数
+RMM_GUARD_DIR=${{0%/*}}
+(
+    until [ "$(getprop sys.boot_completed)" = "1" ]; do
+        sleep 2
+    done
+{safe_mode}    rm -f "$RMM_GUARD_DIR/{marker}"
+) &
+
+"#, marker = BOOT_ATTEMPTS_MARKER);
+    insert_after_shebang(existing, &snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_bootloop_guard_scripts() {
+        use crate::core::rmm_core::RmakeConfig;
+
+        let config: RmakeConfig = toml::from_str("[build]\ninclude = []\nexclude = []\nprebuild = []\nbuild = []\npostbuild = []\n\n[bootloop_guard]\nmax_attempts = 2\n").unwrap();
+        let guard = config.bootloop_guard.unwrap();
+        assert_eq!(guard, BootloopGuardConfig { max_attempts: 2, safe_mode: true });
+        assert!(validate_guard(&guard).is_ok());
+        assert!(validate_guard(&BootloopGuardConfig { max_attempts: 0, ..Default::default() }).is_err());
+
+        let post_fs_data = guard_post_fs_data(&guard, Some("#!/system/bin/sh\nMODDIR=${0%/*}\necho module\n"));
+        assert!(post_fs_data.starts_with("#!/system/bin/sh\n# 由 rmm"));
+        assert!(post_fs_data.ends_with("\nMODDIR=${0%/*}\necho module\n"));
+        let service = guard_service(&guard, None);
+        assert!(service.starts_with("#!/system/bin/sh\n"));
+        assert!(service.contains("getprop ro.sys.safemode"));
+        assert!(!guard_service(&BootloopGuardConfig { safe_mode: false, ..guard.clone() }, None).contains("safemode"));
+
+        #[cfg(unix)]
+        {
+            let temp = tempdir().unwrap();
+            let script = temp.path().join("post-fs-data.sh");
+            fs::write(&script, &post_fs_data).unwrap();
+            let boot = || std::process::Command::new("sh").arg(&script).output().unwrap();
+            for attempt in 1..=2 {
+                assert_eq!(String::from_utf8_lossy(&boot().stdout), "module\n");
+                assert_eq!(fs::read_to_string(temp.path().join(BOOT_ATTEMPTS_MARKER)).unwrap().trim(), attempt.to_string());
+            }
+            assert!(boot().stdout.is_empty());
+            assert!(temp.path().join("disable").exists());
+            assert!(!temp.path().join(BOOT_ATTEMPTS_MARKER).exists());
+        }
+    }
+}
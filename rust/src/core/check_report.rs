@@ -0,0 +1,256 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::known_issues::{KnownIssueFinding, Severity};
+
+/// 所有检查工具共用的报告，位于 .rmmp 下
+pub const CHECK_REPORT_FILE: &str = "check-report.json";
+/// 已知问题数据库在报告中的工具名
+pub const KNOWN_ISSUES_TOOL: &str = "known-issues";
+/// 报告格式版本，字段含义变化时递增
+const REPORT_SCHEMA: u32 = 1;
+
+/// 检查结果级别，与 shellcheck 的级别一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingSeverity {
+    Style,
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<Severity> for FindingSeverity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Info => Self::Info,
+            Severity::Warning => Self::Warning,
+            Severity::Error => Self::Error,
+        }
+    }
+}
+
+/// 文件中的位置（行列从 1 开始）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceRange {
+    pub line: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u32>,
+}
+
+/// 一条检查结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// 产生结果的工具：rmm-check、shellcheck、known-issues
+    pub tool: String,
+    pub severity: FindingSeverity,
+    /// 相对项目（或 zip 内）的文件路径
+    pub file: Option<String>,
+    pub range: Option<SourceRange>,
+    /// 规则代码，如 SC2086 或已知问题规则 ID
+    pub code: Option<String>,
+    pub message: String,
+    /// 修复建议
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    pub fn new(tool: &str, severity: FindingSeverity, message: impl Into<String>) -> Self {
+        Self { tool: tool.to_string(), severity, file: None, range: None, code: None, message: message.into(), fix: None }
+    }
+
+    pub fn in_file(mut self, file: impl Into<String>, line: Option<u32>) -> Self {
+        self.file = Some(file.into());
+        self.range = line.map(|line| SourceRange { line, column: None, end_line: None, end_column: None });
+        self
+    }
+
+    pub fn with_range(mut self, range: SourceRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+
+    /// file:line:column 形式的位置
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_ref()?;
+        Some(match &self.range {
+            Some(SourceRange { line, column: Some(column), .. }) => format!("{}:{}:{}", file, line, column),
+            Some(range) => format!("{}:{}", file, range.line),
+            None => file.clone(),
+        })
+    }
+
+    /// 终端中的统一显示格式
+    pub fn render(&self) -> String {
+        let mut text = match self.severity {
+            FindingSeverity::Error => format!("  {} ", "❌".red()),
+            FindingSeverity::Warning => format!("  {} ", "⚠️".yellow()),
+            FindingSeverity::Info | FindingSeverity::Style => format!("  {} ", "[i]".cyan()),
+        };
+        if let Some(code) = &self.code {
+            text.push_str(&format!("{} ", format!("[{}]", code).bold()));
+        }
+        if let Some(location) = self.location() {
+            text.push_str(&format!("{} ", location));
+        }
+        text.push_str(&match self.severity {
+            FindingSeverity::Error => self.message.red().to_string(),
+            FindingSeverity::Warning => self.message.yellow().to_string(),
+            FindingSeverity::Info | FindingSeverity::Style => self.message.clone(),
+        });
+        if let Some(fix) = &self.fix {
+            text.push_str(&format!("\n      {}", fix.bright_black()));
+        }
+        text
+    }
+}
+
+/// 已知问题数据库的命中转换为统一格式
+pub fn known_issue_findings(findings: &[KnownIssueFinding]) -> Vec<Finding> {
+    findings.iter()
+        .map(|finding| {
+            let message = if finding.matched != finding.file {
+                format!("{}（{}）", finding.description, finding.matched)
+            } else {
+                finding.description.clone()
+            };
+            let mut result = Finding::new(KNOWN_ISSUES_TOOL, finding.severity.into(), message)
+                .in_file(finding.file.clone(), finding.line.map(|line| line as u32))
+                .with_code(finding.rule.clone());
+            result.fix = finding.reference.clone();
+            result
+        })
+        .collect()
+}
+
+/// 各级别数量
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportSummary {
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+    pub style: usize,
+}
+
+/// .rmmp/check-report.json：各工具最近一次运行的结果合并在一起，CI 只需解析这一个文件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub schema: u32,
+    pub generated_at: String,
+    /// 参与合并的工具及其最近运行时间
+    pub tools: BTreeMap<String, String>,
+    pub summary: ReportSummary,
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    pub fn path(project_path: &Path) -> PathBuf {
+        project_path.join(".rmmp").join(CHECK_REPORT_FILE)
+    }
+
+    /// 读取已有报告；不存在或格式版本不同时返回空报告
+    pub fn load(project_path: &Path) -> Self {
+        fs::read_to_string(Self::path(project_path))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|report| report.schema == REPORT_SCHEMA)
+            .unwrap_or_default()
+    }
+
+    /// 用某个工具的最新结果替换它之前的结果
+    pub fn replace_tool(&mut self, tool: &str, findings: Vec<Finding>) {
+        let now = chrono::Local::now().to_rfc3339();
+        self.schema = REPORT_SCHEMA;
+        self.findings.retain(|finding| finding.tool != tool);
+        self.findings.extend(findings);
+        self.findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.tool.cmp(&b.tool)));
+        self.tools.insert(tool.to_string(), now.clone());
+        self.generated_at = now;
+
+        let count = |severity| self.findings.iter().filter(|f| f.severity == severity).count();
+        self.summary = ReportSummary {
+            error: count(FindingSeverity::Error),
+            warning: count(FindingSeverity::Warning),
+            info: count(FindingSeverity::Info),
+            style: count(FindingSeverity::Style),
+        };
+    }
+
+    pub fn save(&self, project_path: &Path) -> Result<PathBuf> {
+        let path = Self::path(project_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("无法写入 {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// 将某个工具的结果合并进项目的 check-report.json
+pub fn record_findings(project_path: &Path, tool: &str, findings: Vec<Finding>) -> Result<PathBuf> {
+    let mut report = ValidationReport::load(project_path);
+    report.replace_tool(tool, findings);
+    report.save(project_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_unified_check_report() {
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        let known = known_issue_findings(&[KnownIssueFinding {
+            rule: "busybox-applet".to_string(),
+            severity: Severity::Error,
+            file: "customize.sh".to_string(),
+            line: Some(3),
+            matched: "busybox --install".to_string(),
+            description: "覆盖系统命令".to_string(),
+            reference: Some("https://example.com/rule".to_string()),
+        }]);
+        assert_eq!(known[0].location().as_deref(), Some("customize.sh:3"));
+        assert_eq!(known[0].message, "覆盖系统命令（busybox --install）");
+        assert_eq!(known[0].fix.as_deref(), Some("https://example.com/rule"));
+
+        let mut report = ValidationReport::load(project);
+        report.replace_tool("rmm-check", vec![Finding::new("rmm-check", FindingSeverity::Warning, "缺少 .rmmp/.gitignore")]);
+        report.replace_tool(KNOWN_ISSUES_TOOL, known);
+        report.save(project).unwrap();
+
+        let mut report = ValidationReport::load(project);
+        assert_eq!(report.findings[0].tool, KNOWN_ISSUES_TOOL);
+        assert_eq!((report.summary.error, report.summary.warning), (1, 1));
+        report.replace_tool(KNOWN_ISSUES_TOOL, Vec::new());
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!((report.summary.error, report.summary.warning), (0, 1));
+        assert_eq!(report.tools.len(), 2);
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(ValidationReport::path(project)).unwrap()).unwrap();
+        assert_eq!(json["schema"], 1);
+        assert_eq!(json["findings"][0]["severity"], "error");
+        assert_eq!(json["findings"][0]["range"]["line"], 3);
+        assert!(json["findings"][1]["file"].is_null());
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::hashing::{hash_tree, HashAlgorithm};
+
+/// 模块根目录中的文件校验清单（随 zip 打包）
+pub const CHECKSUMS_FILE: &str = "rmm-checksums.json";
+/// 清单的签名（rmm build --sign 时随 zip 打包），不记录在清单中
+pub const CHECKSUMS_SIGNATURE_FILE: &str = "rmm-checksums.json.sig";
+/// 清单格式版本，字段含义变化时递增
+const CHECKSUMS_FORMAT: u32 = 1;
+
+/// 文件校验清单：头部声明算法，校验方据此选择哈希实现
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecksumManifest {
+    pub format: u32,
+    pub algorithm: HashAlgorithm,
+    pub generated_by: String,
+    /// 相对模块根目录的路径 → 哈希
+    pub files: BTreeMap<String, String>,
+}
+
+impl ChecksumManifest {
+    /// 并行计算目录下全部文件（清单自身与其签名除外）的哈希
+    pub fn compute(dir: &Path, algorithm: HashAlgorithm) -> Result<Self> {
+        let files = hash_tree(dir, algorithm, |e| !(e.depth() == 1 && is_manifest_file(&e.file_name().to_string_lossy())))?;
+        Ok(Self {
+            format: CHECKSUMS_FORMAT,
+            algorithm,
+            generated_by: format!("rmm {}", env!("CARGO_PKG_VERSION")),
+            files,
+        })
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(CHECKSUMS_FILE);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("无法读取 {}", path.display()))?;
+        let manifest: Self = serde_json::from_str(&content)
+            .with_context(|| format!("无法解析 {}", path.display()))?;
+        if manifest.format != CHECKSUMS_FORMAT {
+            anyhow::bail!("不支持的校验清单格式版本: {}", manifest.format);
+        }
+        Ok(manifest)
+    }
+
+    /// 按清单头部的算法校验目录，返回不一致、缺失或多出的文件
+    pub fn verify(&self, dir: &Path) -> Result<Vec<String>> {
+        let actual = hash_tree(dir, self.algorithm, |e| !(e.depth() == 1 && is_manifest_file(&e.file_name().to_string_lossy())))?;
+        Ok(self.compare(&actual))
+    }
+
+    /// 与实际哈希比较，返回不一致、缺失或多出的文件
+    fn compare(&self, actual: &BTreeMap<String, String>) -> Vec<String> {
+        let mut problems: Vec<String> = self.files.iter()
+            .filter_map(|(path, hash)| match actual.get(path) {
+                None => Some(format!("缺失: {}", path)),
+                Some(found) if found != hash => Some(format!("哈希不一致: {}", path)),
+                Some(_) => None,
+            })
+            .collect();
+        problems.extend(actual.keys().filter(|path| !self.files.contains_key(*path)).map(|path| format!("未记录: {}", path)));
+        problems
+    }
+
+    /// 读取 zip 中随包的清单，zip 不含清单时返回 None
+    pub fn from_zip(zip_path: &Path) -> Result<Option<Self>> {
+        let file = fs::File::open(zip_path)
+            .with_context(|| format!("无法打开 {}", zip_path.display()))?;
+        let mut archive = zip::ZipArchive::new(file)?;
+        let Ok(mut entry) = archive.by_name(CHECKSUMS_FILE) else {
+            return Ok(None);
+        };
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        let manifest: Self = serde_json::from_str(&content)
+            .with_context(|| format!("无法解析 {} 中的 {}", zip_path.display(), CHECKSUMS_FILE))?;
+        if manifest.format != CHECKSUMS_FORMAT {
+            anyhow::bail!("不支持的校验清单格式版本: {}", manifest.format);
+        }
+        Ok(Some(manifest))
+    }
+
+    /// 按清单校验 zip 中的文件（无需解压），返回值与 verify 相同
+    pub fn verify_zip(&self, zip_path: &Path) -> Result<Vec<String>> {
+        let mut archive = zip::ZipArchive::new(fs::File::open(zip_path)?)?;
+        let mut actual = BTreeMap::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            if entry.is_dir() || is_manifest_file(entry.name()) {
+                continue;
+            }
+            let mut data = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut data)?;
+            actual.insert(entry.name().to_string(), self.algorithm.hash_bytes(&data));
+        }
+        Ok(self.compare(&actual))
+    }
+}
+
+/// 模块根目录中的清单文件及其签名
+fn is_manifest_file(name: &str) -> bool {
+    name == CHECKSUMS_FILE || name == CHECKSUMS_SIGNATURE_FILE
+}
+
+/// 生成清单并写入目录根部
+pub fn write_checksum_manifest(dir: &Path, algorithm: HashAlgorithm) -> Result<(ChecksumManifest, PathBuf)> {
+    let manifest = ChecksumManifest::compute(dir, algorithm)?;
+    let path = dir.join(CHECKSUMS_FILE);
+    fs::write(&path, serde_json::to_string_pretty(&manifest)?)
+        .with_context(|| format!("无法写入 {}", path.display()))?;
+    Ok((manifest, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_checksum_manifest() {
+        use crate::core::hashing::{hash_file, sha256_file};
+
+        let temp = tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("system/bin")).unwrap();
+        fs::write(dir.join("module.prop"), "id=test\n").unwrap();
+        // 超过 mmap 阈值的文件
+        fs::write(dir.join("system/bin/tool"), vec![7u8; 256 * 1024]).unwrap();
+
+        let big = dir.join("system/bin/tool");
+        assert_eq!(hash_file(&big, HashAlgorithm::Sha256).unwrap(), sha256_file(&big).unwrap());
+        assert_eq!(hash_file(&big, HashAlgorithm::Blake3).unwrap(), blake3::hash(&fs::read(&big).unwrap()).to_hex().to_string());
+
+        let (manifest, path) = write_checksum_manifest(dir, HashAlgorithm::default()).unwrap();
+        assert_eq!(manifest.algorithm, HashAlgorithm::Blake3);
+        assert_eq!(manifest.files.len(), 2);
+        assert!(!manifest.files.contains_key(CHECKSUMS_FILE));
+        let header: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(header["algorithm"], "blake3");
+
+        let loaded = ChecksumManifest::load(dir).unwrap();
+        assert!(loaded.verify(dir).unwrap().is_empty());
+        fs::write(dir.join("module.prop"), "id=changed\n").unwrap();
+        fs::write(dir.join("extra.sh"), "echo\n").unwrap();
+        let problems = loaded.verify(dir).unwrap();
+        assert_eq!(problems, vec!["哈希不一致: module.prop", "未记录: extra.sh"]);
+
+        let (manifest, _) = write_checksum_manifest(dir, HashAlgorithm::Sha256).unwrap();
+        assert_eq!(manifest.files["module.prop"], sha256_file(&dir.join("module.prop")).unwrap());
+    }
+}
@@ -0,0 +1,217 @@
+use std::fs;
+use std::path::{Component, Path};
+
+/// 脚本引用的扩展名（构建命令与 [project.scripts] 中的参数）
+const SCRIPT_EXTENSIONS: &[&str] = &["sh", "bash", "py", "js", "ts", "ps1", "bat"];
+
+/// 配置文件中引用的项目文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigReference {
+    /// 声明引用的文件，如 rmmproject.toml
+    pub file: &'static str,
+    /// 声明位置，如 project.readme、build.prebuild[0]
+    pub key: String,
+    /// 相对项目根目录的路径
+    pub path: String,
+    /// 是否为说明文档（readme / changelog / license），发布时必须存在
+    pub doc: bool,
+}
+
+/// 引用路径在项目中的状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReferenceStatus {
+    Found,
+    /// 只有大小写不同的文件，actual 为实际路径；FAT/exFAT 等设备上可用，区分大小写的文件系统上找不到
+    CaseMismatch { actual: String },
+    Missing,
+}
+
+/// 收集 rmmproject.toml 与 .rmmp/Rmake.toml 中引用的文件；配置无法解析时跳过
+pub fn collect_references(project_path: &Path) -> Vec<ConfigReference> {
+    let mut references = Vec::new();
+    if let Some(project) = read_toml(&project_path.join("rmmproject.toml")) {
+        let info = project.get("project");
+        for key in ["readme", "changelog", "license"] {
+            if let Some(path) = info.and_then(|i| i.get(key)).and_then(|v| v.as_str())
+                && is_local_path(path)
+            {
+                references.push(ConfigReference { file: "rmmproject.toml", key: format!("project.{}", key), path: path.to_string(), doc: true });
+            }
+        }
+        if let Some(scripts) = info.and_then(|i| i.get("scripts")).and_then(|v| v.as_table()) {
+            for (name, command) in scripts {
+                push_command_refs(&mut references, "rmmproject.toml", &format!("project.scripts.{}", name), command.as_str());
+            }
+        }
+    }
+    if let Some(rmake) = read_toml(&project_path.join(".rmmp/Rmake.toml")) {
+        let build = rmake.get("build");
+        for phase in ["prebuild", "build", "postbuild"] {
+            let commands = build.and_then(|b| b.get(phase)).and_then(|v| v.as_array()).into_iter().flatten();
+            for (index, command) in commands.enumerate() {
+                push_command_refs(&mut references, ".rmmp/Rmake.toml", &format!("build.{}[{}]", phase, index), command.as_str());
+            }
+        }
+        if let Some(scripts) = build.and_then(|b| b.get("scripts")).and_then(|v| v.as_table()) {
+            for (name, command) in scripts {
+                push_command_refs(&mut references, ".rmmp/Rmake.toml", &format!("build.scripts.{}", name), command.as_str());
+            }
+        }
+    }
+    references
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    toml::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn is_local_path(path: &str) -> bool {
+    !path.trim().is_empty() && !path.contains("://")
+}
+
+/// 命令中形如 ./tool 或 scripts/x.sh 的参数视为文件引用
+fn push_command_refs(references: &mut Vec<ConfigReference>, file: &'static str, key: &str, command: Option<&str>) {
+    let Some(command) = command else { return };
+    for token in command.split_whitespace() {
+        let token = token.trim_matches(['"', '\'']);
+        if token.starts_with(['-', '$']) || token.contains(':') || !is_local_path(token) {
+            continue;
+        }
+        let is_script = Path::new(token).extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| SCRIPT_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+        if token.starts_with("./") || is_script {
+            references.push(ConfigReference { file, key: key.to_string(), path: token.to_string(), doc: false });
+        }
+    }
+}
+
+/// 逐级按文件名精确匹配；在不区分大小写的文件系统上同样能发现大小写不一致
+pub fn resolve_reference(project_path: &Path, relative: &str) -> ReferenceStatus {
+    let mut current = project_path.to_path_buf();
+    let mut actual = Vec::new();
+    let mut mismatch = false;
+    for component in Path::new(relative).components() {
+        let name = match component {
+            Component::Normal(name) => name.to_string_lossy().to_string(),
+            Component::CurDir => continue,
+            // 指向项目外的路径只检查是否存在
+            _ => return match project_path.join(relative).exists() {
+                true => ReferenceStatus::Found,
+                false => ReferenceStatus::Missing,
+            },
+        };
+        let Ok(entries) = fs::read_dir(&current) else { return ReferenceStatus::Missing };
+        let names: Vec<String> = entries.filter_map(|e| e.ok()).map(|e| e.file_name().to_string_lossy().to_string()).collect();
+        let found = match names.iter().find(|n| **n == name) {
+            Some(exact) => exact.clone(),
+            None => match names.iter().find(|n| n.to_lowercase() == name.to_lowercase()) {
+                Some(other) => {
+                    mismatch = true;
+                    other.clone()
+                }
+                None => return ReferenceStatus::Missing,
+            },
+        };
+        current.push(&found);
+        actual.push(found);
+    }
+    match mismatch {
+        true => ReferenceStatus::CaseMismatch { actual: actual.join("/") },
+        false => ReferenceStatus::Found,
+    }
+}
+
+/// 有问题的引用及其状态
+pub fn reference_issues(project_path: &Path) -> Vec<(ConfigReference, ReferenceStatus)> {
+    collect_references(project_path).into_iter()
+        .map(|reference| {
+            let status = resolve_reference(project_path, &reference.path);
+            (reference, status)
+        })
+        .filter(|(_, status)| *status != ReferenceStatus::Found)
+        .collect()
+}
+
+/// 问题描述，用于 rmm check 与构建输出
+pub fn describe_issue(reference: &ConfigReference, status: &ReferenceStatus) -> String {
+    match status {
+        ReferenceStatus::Found => format!("{} = \"{}\"", reference.key, reference.path),
+        ReferenceStatus::CaseMismatch { actual } => format!(
+            "{} 引用的 {} 大小写与实际文件 {} 不一致，在区分大小写的文件系统上会找不到", reference.key, reference.path, actual
+        ),
+        ReferenceStatus::Missing => format!("{} 引用的文件 {} 不存在", reference.key, reference.path),
+    }
+}
+
+/// 发布前检查：声明的说明文档必须存在
+pub fn ensure_docs_present(project_path: &Path) -> anyhow::Result<()> {
+    let missing: Vec<String> = reference_issues(project_path).into_iter()
+        .filter(|(reference, status)| reference.doc && *status == ReferenceStatus::Missing)
+        .map(|(reference, _)| format!("{} ({})", reference.path, reference.key))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!("rmmproject.toml 声明的文档不存在: {}", missing.join(", "));
+    }
+    Ok(())
+}
+
+/// 存在但未打包进构建目录的说明文档（被 [build] exclude 排除）
+pub fn unpackaged_docs(project_path: &Path, build_dir: &Path) -> Vec<ConfigReference> {
+    collect_references(project_path).into_iter()
+        .filter(|reference| reference.doc)
+        .filter(|reference| resolve_reference(project_path, &reference.path) != ReferenceStatus::Missing)
+        .filter(|reference| resolve_reference(build_dir, &reference.path) == ReferenceStatus::Missing)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_config_references() {
+        let temp_dir = tempdir().unwrap();
+        let project = temp_dir.path();
+        fs::create_dir_all(project.join(".rmmp/build")).unwrap();
+        fs::create_dir_all(project.join("scripts")).unwrap();
+        fs::write(project.join("rmmproject.toml"), r#"
+[project]
+id = "demo"
+readme = "README.MD"
+changelog = "CHANGELOG.md"
+license = "LICENSE"
+[project.scripts]
+build = "rmm build"
+lint = "bash scripts/lint.sh --fix"
+"#).unwrap();
+        fs::write(project.join(".rmmp/Rmake.toml"), "[build]\nprebuild = [\"./scripts/gen.sh\", \"echo $HOME\"]\n").unwrap();
+        fs::write(project.join("README.md"), "# demo").unwrap();
+        fs::write(project.join("LICENSE"), "MIT").unwrap();
+        fs::write(project.join("scripts/gen.sh"), "").unwrap();
+
+        assert_eq!(resolve_reference(project, "./scripts/gen.sh"), ReferenceStatus::Found);
+        assert_eq!(resolve_reference(project, "readme.md"), ReferenceStatus::CaseMismatch { actual: "README.md".to_string() });
+
+        let issues: Vec<(String, ReferenceStatus)> = reference_issues(project).into_iter()
+            .map(|(reference, status)| (reference.key, status))
+            .collect();
+        assert_eq!(issues, vec![
+            ("project.readme".to_string(), ReferenceStatus::CaseMismatch { actual: "README.md".to_string() }),
+            ("project.changelog".to_string(), ReferenceStatus::Missing),
+            ("project.scripts.lint".to_string(), ReferenceStatus::Missing),
+        ]);
+
+        // 缺少声明的文档时发布失败；大小写不一致只警告
+        let error = ensure_docs_present(project).unwrap_err().to_string();
+        assert!(error.contains("CHANGELOG.md") && !error.contains("README"), "{}", error);
+        fs::write(project.join("CHANGELOG.md"), "").unwrap();
+        ensure_docs_present(project).unwrap();
+
+        // 构建目录中缺少的文档视为未打包
+        fs::write(project.join(".rmmp/build/LICENSE"), "MIT").unwrap();
+        let unpackaged: Vec<String> = unpackaged_docs(project, &project.join(".rmmp/build")).into_iter().map(|r| r.path).collect();
+        assert_eq!(unpackaged, ["README.MD", "CHANGELOG.md"]);
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::{Read, Write};
+
+use super::settings::ConfigResolver;
+
+/// 表情标记对应的文本标签，其余表情直接去掉
+const MARKERS: &[(char, &str)] = &[
+    ('✅', "[OK]"),
+    ('✔', "[OK]"),
+    ('✓', "[OK]"),
+    ('❌', "[ERR]"),
+    ('✗', "[ERR]"),
+    ('✘', "[ERR]"),
+    ('⚠', "[WARN]"),
+    ('ℹ', "[INFO]"),
+    ('💡', "[INFO]"),
+];
+
+/// 是否为需要替换或去掉的表情字符；不包括制表符、箭头等普通符号
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x2300..=0x23FF)
+        || MARKERS.iter().any(|(marker, _)| *marker == c)
+}
+
+/// 纯文本输出：表情标记换成 [OK] / [WARN] / [ERR] 等标签，其余表情与 ANSI 转义序列去掉
+pub fn plain_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        // ANSI 转义序列（颜色、清行等）
+        if c == '\x1b' {
+            if chars.next_if_eq(&'[').is_some() {
+                while chars.next().is_some_and(|c| !('@'..='~').contains(&c)) {}
+            }
+            continue;
+        }
+        // 变体选择符与零宽连接符属于前一个表情
+        if c == '\u{FE0F}' || c == '\u{200D}' {
+            continue;
+        }
+        if !is_emoji(c) {
+            result.push(c);
+            continue;
+        }
+        let tag = MARKERS.iter().find(|(marker, _)| *marker == c).map(|(_, tag)| *tag);
+        while chars.next_if(|c| *c == '\u{FE0F}' || *c == '\u{200D}' || is_emoji(*c)).is_some() {}
+        if result.ends_with('[') && chars.next_if_eq(&']').is_some() {
+            // [🔍] 这类方括号标记整体替换
+            result.pop();
+            result.push_str(tag.unwrap_or("[*]"));
+        } else if let Some(tag) = tag {
+            result.push_str(tag);
+        } else {
+            chars.next_if_eq(&' ');
+        }
+    }
+    result
+}
+
+/// 是否使用纯文本输出：--plain 或 RMM_NO_EMOJI
+pub fn plain_enabled(flag: bool) -> bool {
+    ConfigResolver::new(None).with_flag("output.plain", flag).get_bool("output.plain")
+}
+
+/// 把 reader 的内容转换为纯文本后写入 writer；按 UTF-8 字符边界分块，不等待换行
+pub fn copy_plain(mut reader: impl Read, mut writer: impl Write) -> std::io::Result<()> {
+    let mut buffer = [0u8; 8192];
+    let mut pending = Vec::new();
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        pending.extend_from_slice(&buffer[..read]);
+        let (text, consumed) = match std::str::from_utf8(&pending) {
+            Ok(text) => (text.to_string(), pending.len()),
+            // 末尾是不完整的字符：留到下一块
+            Err(e) if e.error_len().is_none() => (String::from_utf8_lossy(&pending[..e.valid_up_to()]).to_string(), e.valid_up_to()),
+            Err(_) => (String::from_utf8_lossy(&pending).to_string(), pending.len()),
+        };
+        writer.write_all(plain_text(&text).as_bytes())?;
+        writer.flush()?;
+        pending.drain(..consumed);
+    }
+    if !pending.is_empty() {
+        writer.write_all(plain_text(&String::from_utf8_lossy(&pending)).as_bytes())?;
+    }
+    writer.flush()
+}
+
+/// 纯文本输出的作用域：标准输出与标准错误（含子进程）经过转换后写回终端，离开作用域时恢复
+pub struct PlainOutput {
+    #[cfg(unix)]
+    filters: Vec<unix::Filter>,
+}
+
+impl PlainOutput {
+    /// 未启用时返回 None；启用时关闭颜色，并在 Unix 上过滤输出中的表情
+    pub fn install(flag: bool) -> Option<Self> {
+        if !plain_enabled(flag) {
+            return None;
+        }
+        colored::control::set_override(false);
+        Some(Self {
+            #[cfg(unix)]
+            filters: [1, 2].into_iter().filter_map(|fd| unix::Filter::install(fd).ok()).collect(),
+        })
+    }
+}
+
+impl Drop for PlainOutput {
+    fn drop(&mut self) {
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+        #[cfg(unix)]
+        for filter in self.filters.drain(..) {
+            filter.finish();
+        }
+        colored::control::unset_override();
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::File;
+    use std::os::fd::FromRawFd;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::time::Duration;
+
+    /// 恢复输出后等待转换线程写完剩余内容的时间；后台子进程仍持有管道时不再等待
+    const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub struct Filter {
+        fd: i32,
+        saved: i32,
+        done: Receiver<()>,
+    }
+
+    impl Filter {
+        /// 用管道替换 fd，由后台线程转换后写入原来的输出
+        pub fn install(fd: i32) -> std::io::Result<Self> {
+            let mut pipe = [0i32; 2];
+            // SAFETY: 只操作新建的管道与标准输出/错误的文件描述符，finish 时恢复
+            unsafe {
+                if libc::pipe(pipe.as_mut_ptr()) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                let saved = libc::dup(fd);
+                let output = libc::dup(fd);
+                if saved < 0 || output < 0 {
+                    libc::close(pipe[0]);
+                    libc::close(pipe[1]);
+                    return Err(std::io::Error::last_os_error());
+                }
+                libc::dup2(pipe[1], fd);
+                libc::close(pipe[1]);
+                let reader = File::from_raw_fd(pipe[0]);
+                let writer = File::from_raw_fd(output);
+                let (sender, done) = channel();
+                std::thread::spawn(move || {
+                    let _ = super::copy_plain(reader, writer);
+                    let _ = sender.send(());
+                });
+                Ok(Self { fd, saved, done })
+            }
+        }
+
+        pub fn finish(self) {
+            // SAFETY: 恢复 install 时保存的文件描述符
+            unsafe {
+                libc::dup2(self.saved, self.fd);
+                libc::close(self.saved);
+            }
+            let _ = self.done.recv_timeout(DRAIN_TIMEOUT);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_output() {
+        assert_eq!(plain_text("✅ 检查通过！"), "[OK] 检查通过！");
+        assert_eq!(plain_text("❌ 构建失败: x"), "[ERR] 构建失败: x");
+        assert_eq!(plain_text("  ⚠️ 未找到 shellcheck"), "  [WARN] 未找到 shellcheck");
+        assert_eq!(plain_text("[🔍] 检查项目"), "[*] 检查项目");
+        assert_eq!(plain_text("[✓] ok"), "[OK] ok");
+        assert_eq!(plain_text("📦 已迁移 → ~/.rmm"), "已迁移 → ~/.rmm");
+        assert_eq!(plain_text("👨‍💻 作者"), "作者");
+        // 颜色转义序列去掉，方括号标记与制表符保留
+        assert_eq!(plain_text("\x1b[1;32m[+]\x1b[0m 完成\n└── a"), "[+] 完成\n└── a");
+
+        // 多字节字符被分块截断时等待下一块
+        let input = "✅ 构建完成\n".as_bytes();
+        struct Chunks<'a>(&'a [u8]);
+        impl std::io::Read for Chunks<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = self.0.len().min(2).min(buf.len());
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+        let mut output = Vec::new();
+        copy_plain(Chunks(input), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "[OK] 构建完成\n");
+    }
+}
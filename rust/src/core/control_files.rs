@@ -0,0 +1,127 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::mount_strategy::MountStrategy;
+use super::workspace::PARTITIONS;
+
+/// 管理器识别的模块控制文件
+pub const SKIP_MOUNT: &str = "skip_mount";
+pub const DISABLE: &str = "disable";
+pub const REMOVE: &str = "remove";
+pub const CONTROL_FILES: [&str; 3] = [SKIP_MOUNT, DISABLE, REMOVE];
+
+/// skip_mount 的生成方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SkipMountMode {
+    /// 不生成（项目中自带的 skip_mount 仍会打包）
+    #[default]
+    Never,
+    /// 始终生成
+    Always,
+    /// 模块没有需要挂载的分区目录（纯脚本模块）时生成
+    Auto,
+}
+
+/// Rmake.toml 中的 [control] 配置：构建时写入模块控制文件
+///
+/// ```toml
+/// [control]
+/// skip_mount = "auto"   # never / always / auto
+/// disable = true        # 安装后默认禁用
+/// mount = "overlayfs"   # magic / overlayfs / bind
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct ControlConfig {
+    #[serde(default)]
+    pub skip_mount: SkipMountMode,
+    /// 目标 Root 方案的挂载方式；bind 时总是写入 skip_mount
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mount: Option<MountStrategy>,
+    /// 打包 disable，安装后模块处于禁用状态
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disable: bool,
+    /// 打包 remove，下次重启时管理器会删除模块（仅用于调试卸载流程）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub remove: bool,
+}
+
+/// 目录中是否有会被挂载的分区内容（system/、vendor/ 等非空目录）
+pub fn has_mount_content(dir: &Path) -> bool {
+    PARTITIONS.iter().any(|partition| {
+        fs::read_dir(dir.join(partition)).is_ok_and(|mut entries| entries.next().is_some())
+    })
+}
+
+/// 按 [control] 在构建目录中写入控制文件，返回写入的文件名
+pub fn write_control_files(build_dir: &Path, config: &ControlConfig) -> Result<Vec<&'static str>> {
+    let skip_mount = match config.skip_mount {
+        _ if config.mount == Some(MountStrategy::Bind) => true,
+        SkipMountMode::Never => false,
+        SkipMountMode::Always => true,
+        SkipMountMode::Auto => !has_mount_content(build_dir),
+    };
+    let mut written = Vec::new();
+    for (name, enabled) in [(SKIP_MOUNT, skip_mount), (DISABLE, config.disable), (REMOVE, config.remove)] {
+        if enabled {
+            fs::write(build_dir.join(name), "")?;
+            written.push(name);
+        }
+    }
+    Ok(written)
+}
+
+/// 控制文件相关的检查警告：skip_mount 与分区目录同时存在时分区内容不会生效
+pub fn control_warnings(project_path: &Path, config: Option<&ControlConfig>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let skip_mount = project_path.join(SKIP_MOUNT).exists()
+        || config.is_some_and(|c| c.skip_mount == SkipMountMode::Always);
+    if skip_mount && has_mount_content(project_path) {
+        warnings.push(format!(
+            "模块包含 {} 的同时启用了 skip_mount，这些文件不会被挂载；纯脚本模块以外请移除 skip_mount 或改用 skip_mount = \"auto\"",
+            PARTITIONS.iter().filter(|p| project_path.join(p).is_dir()).map(|p| format!("{}/", p)).collect::<Vec<_>>().join("、")
+        ));
+    }
+    if config.is_some_and(|c| c.remove) {
+        warnings.push("[control] remove = true：安装后下次重启模块就会被删除，请勿发布".to_string());
+    }
+    if project_path.join(REMOVE).exists() {
+        warnings.push("项目中存在 remove 文件，安装后下次重启模块就会被删除".to_string());
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_control_files_for_script_only_modules() {
+        let temp = tempdir().unwrap();
+        let build = temp.path();
+        fs::write(build.join("service.sh"), "#!/system/bin/sh\n").unwrap();
+        let config: ControlConfig = toml::from_str("skip_mount = \"auto\"\ndisable = true\n").unwrap();
+        assert_eq!(config.skip_mount, SkipMountMode::Auto);
+        assert_eq!(write_control_files(build, &config).unwrap(), vec!["skip_mount", "disable"]);
+        assert!(build.join("skip_mount").exists() && build.join("disable").exists());
+
+        // 有挂载内容时 auto 不生成 skip_mount
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        fs::create_dir_all(project.join("system/bin")).unwrap();
+        fs::write(project.join("system/bin/tool"), "bin").unwrap();
+        assert!(write_control_files(project, &ControlConfig { skip_mount: SkipMountMode::Auto, ..Default::default() }).unwrap().is_empty());
+        assert!(control_warnings(project, None).is_empty());
+
+        fs::write(project.join("skip_mount"), "").unwrap();
+        let warnings = control_warnings(project, None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("system/"));
+        let remove = ControlConfig { remove: true, ..Default::default() };
+        assert_eq!(control_warnings(project, Some(&remove)).len(), 2);
+    }
+}
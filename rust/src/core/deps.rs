@@ -0,0 +1,327 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::rmm_core::RmmProject;
+use super::zip_inspect::{parse_module_prop, read_prop_text};
+
+/// 依赖类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DepKind {
+    /// 其他模块（project.dependencies）
+    Module,
+    /// 脚本或二进制（project.script_dependencies），如 busybox
+    Script,
+}
+
+/// 依赖来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum DepSource {
+    /// 在 meta.toml 已登记的项目中按 ID 查找
+    Registry,
+    /// 本地路径（相对声明依赖的项目）
+    Path(String),
+    Url(String),
+}
+
+impl DepSource {
+    pub fn describe(&self) -> String {
+        match self {
+            DepSource::Registry => "registry".to_string(),
+            DepSource::Path(path) => format!("path: {}", path),
+            DepSource::Url(url) => format!("url: {}", url),
+        }
+    }
+}
+
+/// 解析后的依赖声明：`id[op version][ @ 来源]`，如 `busybox>=1.36 @ https://…`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepSpec {
+    pub id: String,
+    pub kind: DepKind,
+    /// 版本约束，如 >=1.2
+    pub constraint: Option<String>,
+    pub source: DepSource,
+}
+
+const OPERATORS: [&str; 7] = [">=", "<=", "==", "!=", ">", "<", "="];
+
+/// 解析依赖声明
+pub fn parse_dep_spec(spec: &str, kind: DepKind) -> Result<DepSpec> {
+    let (requirement, source) = match spec.split_once('@') {
+        Some((requirement, source)) => (requirement.trim(), Some(source.trim())),
+        None => (spec.trim(), None),
+    };
+    let split = requirement.find(['>', '<', '=', '!']).unwrap_or(requirement.len());
+    let id = requirement[..split].trim();
+    let constraint = requirement[split..].replace(' ', "");
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')) {
+        anyhow::bail!("无效的依赖声明 '{}'：应为 id[>=版本][ @ 路径或 URL]", spec);
+    }
+    let valid_constraint = OPERATORS.iter()
+        .find_map(|op| constraint.strip_prefix(op))
+        .is_some_and(|version| !version.is_empty());
+    if !constraint.is_empty() && !valid_constraint {
+        anyhow::bail!("无效的版本约束 '{}'（依赖 {}）", constraint, id);
+    }
+    let source = match source {
+        None | Some("") => DepSource::Registry,
+        Some(source) if source.starts_with("https://") || source.starts_with("http://") => DepSource::Url(source.to_string()),
+        Some(source) => DepSource::Path(source.trim_start_matches("path:").to_string()),
+    };
+    Ok(DepSpec {
+        id: id.to_string(),
+        kind,
+        constraint: (!constraint.is_empty()).then_some(constraint),
+        source,
+    })
+}
+
+/// 按数字段比较版本号（忽略前缀 v 与非数字字符）
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim().trim_start_matches(['v', 'V'])
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|p| !p.is_empty())
+            .filter_map(|p| p.parse().ok())
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    for i in 0..a.len().max(b.len()) {
+        match a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    Ordering::Equal
+}
+
+/// 版本是否满足约束
+pub fn satisfies(version: &str, constraint: &str) -> bool {
+    let Some((op, wanted)) = OPERATORS.iter().find_map(|op| constraint.strip_prefix(op).map(|v| (*op, v))) else {
+        return true;
+    };
+    let ordering = compare_versions(version, wanted);
+    match op {
+        ">=" => ordering != Ordering::Less,
+        "<=" => ordering != Ordering::Greater,
+        ">" => ordering == Ordering::Greater,
+        "<" => ordering == Ordering::Less,
+        "!=" => ordering != Ordering::Equal,
+        _ => ordering == Ordering::Equal,
+    }
+}
+
+/// 节点解析状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "lowercase")]
+pub enum DepStatus {
+    Ok,
+    /// 找不到依赖
+    Missing(String),
+    /// 版本不满足约束
+    Unsatisfied(String),
+    /// 循环依赖（不再展开）
+    Cycle,
+}
+
+/// 依赖树节点
+#[derive(Debug, Clone, Serialize)]
+pub struct DepNode {
+    pub id: String,
+    pub kind: DepKind,
+    /// 解析得到的版本；URL 来源未固定版本时为空
+    pub version: Option<String>,
+    pub constraint: Option<String>,
+    pub source: DepSource,
+    /// 本地解析到的路径
+    pub path: Option<PathBuf>,
+    #[serde(flatten)]
+    pub status: DepStatus,
+    pub children: Vec<DepNode>,
+}
+
+/// 同一依赖被解析为不同版本或来源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DepConflict {
+    pub id: String,
+    /// 出现过的 "版本 (来源)"
+    pub variants: Vec<String>,
+}
+
+/// 解析完成的依赖图
+#[derive(Debug, Clone, Serialize)]
+pub struct DepTree {
+    pub root: DepNode,
+    pub conflicts: Vec<DepConflict>,
+}
+
+impl DepTree {
+    /// 所有缺失或版本不满足的节点
+    pub fn problems(&self) -> Vec<&DepNode> {
+        fn walk<'a>(node: &'a DepNode, out: &mut Vec<&'a DepNode>) {
+            if matches!(node.status, DepStatus::Missing(_) | DepStatus::Unsatisfied(_)) {
+                out.push(node);
+            }
+            node.children.iter().for_each(|child| walk(child, out));
+        }
+        let mut out = Vec::new();
+        walk(&self.root, &mut out);
+        out
+    }
+}
+
+/// 依赖解析器：registry 为 meta.toml 中的 项目名 → 路径
+pub struct DepResolver {
+    registry: HashMap<String, String>,
+}
+
+impl DepResolver {
+    pub fn new(registry: HashMap<String, String>) -> Self {
+        Self { registry }
+    }
+
+    /// 从项目开始解析完整依赖图
+    pub fn resolve(&self, project_path: &Path) -> Result<DepTree> {
+        let project_path = project_path.canonicalize().unwrap_or_else(|_| project_path.to_path_buf());
+        let (id, version) = module_identity(&project_path);
+        let mut stack = vec![project_path.clone()];
+        let children = self.resolve_children(&project_path, &mut stack)?;
+        let root = DepNode {
+            id,
+            kind: DepKind::Module,
+            version,
+            constraint: None,
+            source: DepSource::Path(project_path.display().to_string()),
+            path: Some(project_path),
+            status: DepStatus::Ok,
+            children,
+        };
+        let conflicts = find_conflicts(&root);
+        Ok(DepTree { root, conflicts })
+    }
+
+    fn resolve_children(&self, project_path: &Path, stack: &mut Vec<PathBuf>) -> Result<Vec<DepNode>> {
+        let Some(project) = read_project(project_path)? else {
+            return Ok(Vec::new());
+        };
+        let specs = project.project.dependencies.iter()
+            .map(|spec| parse_dep_spec(spec, DepKind::Module))
+            .chain(project.project.script_dependencies.iter().map(|spec| parse_dep_spec(spec, DepKind::Script)))
+            .collect::<Result<Vec<_>>>()?;
+        specs.into_iter().map(|spec| self.resolve_spec(project_path, spec, stack)).collect()
+    }
+
+    fn resolve_spec(&self, base: &Path, spec: DepSpec, stack: &mut Vec<PathBuf>) -> Result<DepNode> {
+        let mut node = DepNode {
+            id: spec.id.clone(),
+            kind: spec.kind,
+            version: None,
+            constraint: spec.constraint.clone(),
+            source: spec.source.clone(),
+            path: None,
+            status: DepStatus::Ok,
+            children: Vec::new(),
+        };
+        let location = match (&spec.source, spec.kind) {
+            (DepSource::Path(path), _) => Some(base.join(path)),
+            (DepSource::Registry, DepKind::Module) => self.registry.get(&spec.id).map(PathBuf::from),
+            (DepSource::Registry, DepKind::Script) => {
+                node.status = DepStatus::Missing("脚本依赖需要指定路径或 URL 来源".to_string());
+                return Ok(node);
+            }
+            (DepSource::Url(_), _) => None,
+        };
+
+        match location {
+            Some(path) if path.exists() => {
+                let path = path.canonicalize().unwrap_or(path);
+                if spec.kind == DepKind::Module {
+                    node.version = module_identity(&path).1;
+                    if stack.contains(&path) {
+                        node.status = DepStatus::Cycle;
+                    } else {
+                        stack.push(path.clone());
+                        node.children = self.resolve_children(&path, stack)?;
+                        stack.pop();
+                    }
+                }
+                node.path = Some(path);
+            }
+            Some(path) => {
+                node.status = DepStatus::Missing(format!("路径不存在: {}", path.display()));
+                return Ok(node);
+            }
+            None if spec.source == DepSource::Registry => {
+                node.status = DepStatus::Missing("未在 meta.toml 中登记（可先运行 rmm sync）".to_string());
+                return Ok(node);
+            }
+            None => {}
+        }
+
+        // URL 与脚本依赖的版本由 == / = 约束固定
+        if node.version.is_none() {
+            node.version = spec.constraint.as_deref()
+                .and_then(|c| c.strip_prefix("==").or_else(|| c.strip_prefix('=')))
+                .map(str::to_string);
+        }
+        if let (Some(version), Some(constraint)) = (&node.version, &spec.constraint)
+            && !satisfies(version, constraint)
+        {
+            node.status = DepStatus::Unsatisfied(format!("{} 不满足 {}", version, constraint));
+        }
+        Ok(node)
+    }
+}
+
+pub(crate) fn read_project(project_path: &Path) -> Result<Option<RmmProject>> {
+    let file = project_path.join("rmmproject.toml");
+    if !file.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&file)?;
+    let project = toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("无法解析 {}: {}", file.display(), e))?;
+    Ok(Some(project))
+}
+
+/// 从 module.prop 读取 (id, version)，id 缺失时使用目录名
+pub(crate) fn module_identity(project_path: &Path) -> (String, Option<String>) {
+    let prop = read_prop_text(&project_path.join("module.prop"))
+        .map(|text| parse_module_prop(&text))
+        .unwrap_or_default();
+    let id = prop.get("id").cloned().unwrap_or_else(|| {
+        project_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+    });
+    (id, prop.get("version").cloned())
+}
+
+/// 同一 ID 出现多个不同的 版本/来源 组合即为冲突
+fn find_conflicts(root: &DepNode) -> Vec<DepConflict> {
+    fn collect(node: &DepNode, seen: &mut BTreeMap<String, Vec<String>>) {
+        for child in &node.children {
+            if !matches!(child.status, DepStatus::Missing(_)) {
+                let variant = format!(
+                    "{} ({})",
+                    child.version.as_deref().unwrap_or("?"),
+                    child.path.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| child.source.describe())
+                );
+                let variants = seen.entry(child.id.clone()).or_default();
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+            }
+            collect(child, seen);
+        }
+    }
+    let mut seen = BTreeMap::new();
+    collect(root, &mut seen);
+    seen.into_iter()
+        .filter(|(_, variants)| variants.len() > 1)
+        .map(|(id, variants)| DepConflict { id, variants })
+        .collect()
+}
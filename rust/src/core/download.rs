@@ -0,0 +1,434 @@
+use anyhow::{Context, Result};
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+use super::hashing::sha256_bytes;
+use super::settings::ConfigResolver;
+use super::storage::RmmStorage;
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+/// 竞速探测的超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// 超过该大小且来源支持 Range 时分段并行下载
+const SEGMENT_THRESHOLD: u64 = 4 * 1024 * 1024;
+const SEGMENTS: u64 = 4;
+/// 连续失败达到该次数的来源在冷却期内不参与竞速
+const SUSPEND_AFTER_FAILURES: u32 = 3;
+const SUSPEND_MINUTES: i64 = 60;
+/// 滑动平均中新样本的权重
+const EWMA_WEIGHT: f64 = 0.3;
+/// 按记录的速度估算下载耗时时假定的文件大小
+const ESTIMATE_BYTES: f64 = 1024.0 * 1024.0;
+/// 排序靠前的来源在竞速中领先的时间（每个名次）
+const PROBE_HEAD_START: Duration = Duration::from_millis(100);
+/// RMM_ROOT 缓存目录下的镜像健康记录
+pub const MIRROR_HEALTH_FILE: &str = "mirror-health.json";
+/// 不经镜像、直接访问原始地址的来源名
+pub const DIRECT_SOURCE: &str = "direct";
+
+/// 一个来源的健康统计，决定其是否参与竞速
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MirrorHealth {
+    pub successes: u64,
+    pub failures: u64,
+    /// 连续失败次数，成功后清零
+    pub failure_streak: u32,
+    /// 响应延迟的滑动平均（毫秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    /// 下载速度的滑动平均（字节/秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throughput: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_failure: Option<String>,
+}
+
+fn ewma(previous: Option<f64>, sample: f64) -> f64 {
+    previous.map_or(sample, |p| p * (1.0 - EWMA_WEIGHT) + sample * EWMA_WEIGHT)
+}
+
+impl MirrorHealth {
+    fn record_latency(&mut self, latency: Duration) {
+        self.latency_ms = Some(ewma(self.latency_ms, latency.as_secs_f64() * 1000.0));
+    }
+
+    fn record_success(&mut self, bytes: usize, elapsed: Duration) {
+        self.successes += 1;
+        self.failure_streak = 0;
+        if elapsed.as_secs_f64() > 0.0 {
+            self.throughput = Some(ewma(self.throughput, bytes as f64 / elapsed.as_secs_f64()));
+        }
+    }
+
+    fn record_failure(&mut self, error: &str) {
+        self.failures += 1;
+        self.failure_streak += 1;
+        self.last_error = Some(error.to_string());
+        self.last_failure = Some(chrono::Utc::now().to_rfc3339());
+    }
+
+    /// 按延迟与速度估算的下载耗时（秒），没有记录时为 None
+    pub fn expected_secs(&self) -> Option<f64> {
+        let latency = self.latency_ms? / 1000.0;
+        Some(latency + self.throughput.map_or(0.0, |t| ESTIMATE_BYTES / t.max(1.0)))
+    }
+
+    /// 连续失败过多且仍在冷却期内
+    pub fn is_suspended(&self) -> bool {
+        self.failure_streak >= SUSPEND_AFTER_FAILURES
+            && self.last_failure.as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .is_some_and(|t| chrono::Utc::now().signed_duration_since(t) < chrono::Duration::minutes(SUSPEND_MINUTES))
+    }
+}
+
+/// 所有来源的健康记录：来源名（direct 或镜像前缀）→ 统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MirrorHealthDb {
+    #[serde(default)]
+    pub sources: BTreeMap<String, MirrorHealth>,
+}
+
+impl MirrorHealthDb {
+    pub fn path() -> PathBuf {
+        RmmStorage::resolve().cache_dir().join(MIRROR_HEALTH_FILE)
+    }
+
+    /// 读取健康记录，不存在或损坏时为空
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn entry(&mut self, source: &str) -> &mut MirrorHealth {
+        self.sources.entry(source.to_string()).or_default()
+    }
+
+    /// 参与竞速的来源：跳过暂停中的来源（全部暂停时仍全部尝试），按估算的下载耗时排序，
+    /// 没有延迟记录的来源保持原顺序排在最后
+    pub fn select(&self, candidates: Vec<DownloadSource>) -> Vec<DownloadSource> {
+        let healthy: Vec<DownloadSource> = candidates.iter()
+            .filter(|c| !self.sources.get(&c.name).is_some_and(MirrorHealth::is_suspended))
+            .cloned()
+            .collect();
+        let mut selected = if healthy.is_empty() { candidates } else { healthy };
+        let expected = |source: &DownloadSource| self.sources.get(&source.name).and_then(MirrorHealth::expected_secs);
+        selected.sort_by(|a, b| match (expected(a), expected(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        });
+        selected
+    }
+}
+
+/// 下载来源：原始地址或加了镜像前缀的地址
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadSource {
+    /// 健康记录中的名称
+    pub name: String,
+    pub url: String,
+}
+
+/// 竞速探测结果
+#[derive(Debug, Clone, Copy)]
+struct Probe {
+    /// 文件大小（已知时）
+    total: Option<u64>,
+    /// 是否支持 Range 分段
+    ranges: bool,
+}
+
+/// 解析 network.mirrors：逗号或空白分隔的镜像前缀
+pub fn parse_mirrors(value: &str) -> Vec<String> {
+    value.split([',', ' ', '\n'])
+        .map(|m| m.trim().trim_end_matches('/'))
+        .filter(|m| !m.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 将 [start, end] 闭区间均分为 count 段
+pub fn segment_ranges(total: u64, count: u64) -> Vec<(u64, u64)> {
+    let size = total.div_ceil(count.max(1)).max(1);
+    (0..total).step_by(size as usize).map(|start| (start, (start + size).min(total) - 1)).collect()
+}
+
+/// 多来源下载器：各来源竞速，最先响应的来源优先下载，失败或内容不符时换下一个
+#[derive(Debug, Clone, Default)]
+pub struct DownloadManager {
+    mirrors: Vec<String>,
+    /// 健康记录文件，None 时不记录
+    health_path: Option<PathBuf>,
+}
+
+impl DownloadManager {
+    pub fn new(mirrors: Vec<String>, health_path: Option<PathBuf>) -> Self {
+        Self { mirrors, health_path }
+    }
+
+    /// 使用 network.mirrors 配置与 RMM_ROOT 下的健康记录
+    pub fn from_config() -> Self {
+        let mirrors = ConfigResolver::new(None).get("network.mirrors").map(|v| parse_mirrors(&v)).unwrap_or_default();
+        Self::new(mirrors, Some(MirrorHealthDb::path()))
+    }
+
+    pub fn mirrors(&self) -> &[String] {
+        &self.mirrors
+    }
+
+    /// 原始地址与各镜像地址；非 http(s) 地址不经镜像
+    pub fn sources(&self, url: &str) -> Vec<DownloadSource> {
+        let mut sources = vec![DownloadSource { name: DIRECT_SOURCE.to_string(), url: url.to_string() }];
+        if url.starts_with("https://") || url.starts_with("http://") {
+            sources.extend(self.mirrors.iter().map(|mirror| DownloadSource {
+                name: mirror.clone(),
+                url: format!("{}/{}", mirror, url),
+            }));
+        }
+        sources
+    }
+
+    /// 下载 url；expected_sha256 给出时跳过内容不符的来源
+    pub fn fetch(&self, url: &str, expected_sha256: Option<&str>) -> Result<Vec<u8>> {
+        let mut health = self.health_path.as_deref().map(MirrorHealthDb::load).unwrap_or_default();
+        let sources = health.select(self.sources(url));
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let result = runtime.block_on(fetch_racing(sources, expected_sha256, &mut health));
+        if let Some(path) = &self.health_path {
+            // 健康记录只影响来源选择，写入失败不影响下载结果
+            let _ = health.save(path);
+        }
+        result.with_context(|| format!("下载失败: {}", url))
+    }
+}
+
+fn client(timeout: Duration) -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(concat!("rmm/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+/// 探测所有来源，按响应先后依次尝试下载；排序靠前的来源提前开始探测，响应相近时优先
+async fn fetch_racing(sources: Vec<DownloadSource>, expected_sha256: Option<&str>, health: &mut MirrorHealthDb) -> Result<Vec<u8>> {
+    let probe_client = client(PROBE_TIMEOUT)?;
+    let download_client = client(DOWNLOAD_TIMEOUT)?;
+    let mut probes = JoinSet::new();
+    for (rank, source) in sources.into_iter().enumerate() {
+        let client = probe_client.clone();
+        probes.spawn(async move {
+            tokio::time::sleep(PROBE_HEAD_START * rank as u32).await;
+            let started = Instant::now();
+            let result = probe(&client, &source.url).await;
+            (source, result, started.elapsed())
+        });
+    }
+
+    let mut errors = Vec::new();
+    while let Some(joined) = probes.join_next().await {
+        let Ok((source, result, latency)) = joined else { continue };
+        let entry = health.entry(&source.name);
+        let probe = match result {
+            Ok(probe) => probe,
+            Err(e) => {
+                entry.record_failure(&e.to_string());
+                errors.push(format!("{}: {}", source.name, e));
+                continue;
+            }
+        };
+        entry.record_latency(latency);
+
+        let started = Instant::now();
+        let result = download_from(&download_client, &source.url, probe).await.and_then(|bytes| {
+            match expected_sha256.map(|expected| (expected, sha256_bytes(&bytes))) {
+                Some((expected, actual)) if !expected.eq_ignore_ascii_case(&actual) => {
+                    anyhow::bail!("sha256 不匹配（预期 {}，实际 {}）", expected, actual)
+                }
+                _ => Ok(bytes),
+            }
+        });
+        match result {
+            Ok(bytes) => {
+                entry.record_success(bytes.len(), started.elapsed());
+                probes.abort_all();
+                return Ok(bytes);
+            }
+            Err(e) => {
+                entry.record_failure(&e.to_string());
+                errors.push(format!("{}: {}", source.name, e));
+            }
+        }
+    }
+    anyhow::bail!("所有来源均失败\n  {}", errors.join("\n  "))
+}
+
+/// 请求第一个字节，判断来源是否可用以及是否支持分段
+async fn probe(client: &reqwest::Client, url: &str) -> Result<Probe> {
+    let response = client.get(url).header(RANGE, "bytes=0-0").send().await?.error_for_status()?;
+    if response.status() == StatusCode::PARTIAL_CONTENT {
+        let total = response.headers().get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit('/').next())
+            .and_then(|v| v.parse().ok());
+        return Ok(Probe { total, ranges: total.is_some() });
+    }
+    Ok(Probe { total: response.content_length(), ranges: false })
+}
+
+async fn download_from(client: &reqwest::Client, url: &str, probe: Probe) -> Result<Vec<u8>> {
+    let bytes = match probe.total {
+        Some(total) if probe.ranges && total >= SEGMENT_THRESHOLD => download_segments(client, url, total).await?,
+        _ => client.get(url).send().await?.error_for_status()?.bytes().await?.to_vec(),
+    };
+    if let Some(total) = probe.total
+        && bytes.len() as u64 != total
+    {
+        anyhow::bail!("内容长度不一致（预期 {} 字节，实际 {} 字节）", total, bytes.len());
+    }
+    Ok(bytes)
+}
+
+/// 按 Range 并行下载各段后拼接
+async fn download_segments(client: &reqwest::Client, url: &str, total: u64) -> Result<Vec<u8>> {
+    let mut segments = JoinSet::new();
+    for (index, (start, end)) in segment_ranges(total, SEGMENTS).into_iter().enumerate() {
+        let client = client.clone();
+        let url = url.to_string();
+        segments.spawn(async move {
+            let response = client.get(&url).header(RANGE, format!("bytes={}-{}", start, end)).send().await?.error_for_status()?;
+            if response.status() != StatusCode::PARTIAL_CONTENT {
+                anyhow::bail!("来源未按 Range 返回分段");
+            }
+            let bytes = response.bytes().await?;
+            if bytes.len() as u64 != end - start + 1 {
+                anyhow::bail!("分段 {}-{} 长度不一致", start, end);
+            }
+            Ok::<_, anyhow::Error>((index, bytes))
+        });
+    }
+    let mut parts = Vec::new();
+    while let Some(joined) = segments.join_next().await {
+        parts.push(joined??);
+    }
+    parts.sort_by_key(|(index, _)| *index);
+    Ok(parts.into_iter().flat_map(|(_, bytes)| bytes.to_vec()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_download_manager_mirrors() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        assert_eq!(parse_mirrors("https://ghproxy.example/, https://mirror.example\n"), vec!["https://ghproxy.example", "https://mirror.example"]);
+        assert_eq!(segment_ranges(10, 4), vec![(0, 2), (3, 5), (6, 8), (9, 9)]);
+        assert_eq!(segment_ranges(3, 4), vec![(0, 0), (1, 1), (2, 2)]);
+
+        let manager = DownloadManager::new(vec!["https://ghproxy.example".to_string()], None);
+        let sources = manager.sources("https://github.com/a/b.zip");
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].name, DIRECT_SOURCE);
+        assert_eq!(sources[1].url, "https://ghproxy.example/https://github.com/a/b.zip");
+        // 非 http(s) 地址不经镜像
+        assert_eq!(manager.sources("file:///tmp/a.zip").len(), 1);
+
+        // 本地服务器：/bad/ 前缀返回被篡改的内容，支持 Range 探测
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut ranged = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                    ranged |= line.to_ascii_lowercase().starts_with("range:");
+                }
+                let body: &[u8] = if request_line.contains("/bad/") { b"tampered" } else { b"module" };
+                let head = if ranged {
+                    format!("HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-0/{}\r\nContent-Length: 1\r\nConnection: close\r\n\r\n", body.len())
+                } else {
+                    format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len())
+                };
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(if ranged { &body[..1] } else { body }).unwrap();
+            }
+        });
+
+        let temp_dir = tempdir().unwrap();
+        let health_path = temp_dir.path().join("mirror-health.json");
+        let url = "http://127.0.0.1:1/module.zip";
+        let expected = sha256_bytes(b"module");
+
+        // 直连不可达、镜像内容不符：全部失败并记录
+        let bad = format!("http://127.0.0.1:{}/bad", port);
+        let manager = DownloadManager::new(vec![bad.clone()], Some(health_path.clone()));
+        let error = format!("{:#}", manager.fetch(url, Some(&expected)).unwrap_err());
+        assert!(error.contains("sha256 不匹配"), "{}", error);
+        let db = MirrorHealthDb::load(&health_path);
+        assert_eq!(db.sources[DIRECT_SOURCE].failures, 1);
+        assert_eq!(db.sources[&bad].failure_streak, 1);
+        assert!(db.sources[&bad].latency_ms.is_some());
+
+        // 可用镜像：跳过不可达的直连
+        let good = format!("http://127.0.0.1:{}/good", port);
+        let manager = DownloadManager::new(vec![good.clone()], Some(health_path.clone()));
+        assert_eq!(manager.fetch(url, Some(&expected)).unwrap(), b"module");
+        let db = MirrorHealthDb::load(&health_path);
+        assert_eq!(db.sources[&good].successes, 1);
+        assert!(db.sources[&good].throughput.is_some());
+
+        // 连续失败的来源暂停，全部暂停时仍全部尝试
+        let mut db = db;
+        let direct = db.sources.get_mut(DIRECT_SOURCE).unwrap();
+        direct.failure_streak = 3;
+        assert!(direct.is_suspended());
+        let candidates = manager.sources(url);
+        assert_eq!(db.select(candidates.clone()), candidates[1..].to_vec());
+        assert_eq!(db.select(candidates[..1].to_vec()), candidates[..1].to_vec());
+
+        // 按记录的延迟与速度排序，没有记录的来源保持原顺序排在最后
+        let sources = ["direct", "slow", "unknown-a", "fast", "unknown-b", "laggy"]
+            .map(|name| DownloadSource { name: name.to_string(), url: format!("https://{}/a.zip", name) });
+        let health = |latency_ms: f64, throughput: Option<f64>| MirrorHealth { latency_ms: Some(latency_ms), throughput, ..Default::default() };
+        let db = MirrorHealthDb {
+            sources: BTreeMap::from([
+                ("direct".to_string(), health(300.0, Some(4.0 * 1024.0 * 1024.0))),
+                ("slow".to_string(), health(50.0, Some(64.0 * 1024.0))),
+                ("fast".to_string(), health(80.0, Some(8.0 * 1024.0 * 1024.0))),
+                ("laggy".to_string(), health(900.0, None)),
+            ]),
+        };
+        let order: Vec<String> = db.select(sources.to_vec()).into_iter().map(|s| s.name).collect();
+        assert_eq!(order, vec!["fast", "direct", "laggy", "slow", "unknown-a", "unknown-b"]);
+    }
+}
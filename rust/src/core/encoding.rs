@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF16LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF16BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// 文本文件的编码；设备上的 sh 只能正确处理不带 BOM 的 UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    /// Windows 编辑器常见的带 BOM 的 UTF-8，会破坏 shebang 解析
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl TextEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 BOM",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+        }
+    }
+}
+
+/// [build.line_endings] 中的行尾策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// 转为 LF（文本文件的默认策略，源文件中的 CRLF 会一并修复）
+    Lf,
+    /// 转为 CRLF，如供 Windows 端工具读取的 .ini
+    Crlf,
+    /// 原样复制，不转换编码与行尾
+    Keep,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "lf",
+            LineEnding::Crlf => "crlf",
+            LineEnding::Keep => "keep",
+        }
+    }
+}
+
+/// 行尾规则：[build.line_endings] 中的 glob → 策略，最长（最具体）的模式优先
+pub struct LineEndingRules {
+    rules: Vec<(glob::Pattern, LineEnding)>,
+}
+
+impl LineEndingRules {
+    pub fn new(line_endings: &BTreeMap<String, LineEnding>) -> Result<Self> {
+        let mut rules = line_endings.iter()
+            .map(|(pattern, ending)| {
+                let glob = glob::Pattern::new(pattern)
+                    .with_context(|| format!("[build.line_endings] 中的模式无效: {}", pattern))?;
+                Ok((glob, *ending))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        rules.sort_by_key(|(glob, _)| std::cmp::Reverse(glob.as_str().len()));
+        Ok(Self { rules })
+    }
+
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// 匹配相对路径（/ 分隔）的规则
+    pub fn matching(&self, relative: &str) -> Option<(&str, LineEnding)> {
+        self.rules.iter()
+            .find(|(glob, _)| glob.matches(relative))
+            .map(|(glob, ending)| (glob.as_str(), *ending))
+    }
+
+    /// 文件的行尾策略：配置规则优先，其余文本文件转为 LF，二进制文件为 None（原样复制）
+    pub fn policy(&self, relative: &str) -> Option<LineEnding> {
+        self.matching(relative)
+            .map(|(_, ending)| ending)
+            .or_else(|| is_text_file(Path::new(relative)).then_some(LineEnding::Lf))
+    }
+}
+
+/// 需要规范化（编码与行尾序列）的文本文件
+pub fn is_text_file(file_path: &Path) -> bool {
+    let extension = file_path.extension().and_then(|s| s.to_str()).unwrap_or("");
+    matches!(extension, "sh" | "prop" | "txt" | "md" | "conf" | "json" | "toml" | "xml" | "yml" | "yaml")
+        || file_path.file_name().and_then(|s| s.to_str()).is_some_and(|name| {
+            matches!(name, "module.prop" | "service.sh" | "post-fs-data.sh" | "uninstall.sh" | "customize.sh")
+        })
+}
+
+/// 按 BOM 判断编码；没有 BOM 时，若 0 字节只出现在奇数（偶数）位置且占一半以上则视为 UTF-16LE（BE）
+pub fn detect_encoding(content: &[u8]) -> TextEncoding {
+    if content.starts_with(UTF8_BOM) {
+        return TextEncoding::Utf8Bom;
+    }
+    if content.starts_with(UTF16LE_BOM) {
+        return TextEncoding::Utf16Le;
+    }
+    if content.starts_with(UTF16BE_BOM) {
+        return TextEncoding::Utf16Be;
+    }
+    let sample = &content[..content.len().min(512)];
+    if sample.len() >= 4 {
+        let zeros = |offset: usize| sample.iter().skip(offset).step_by(2).filter(|b| **b == 0).count();
+        let half = sample.len() / 2;
+        if zeros(1) * 2 >= half && zeros(0) == 0 {
+            return TextEncoding::Utf16Le;
+        }
+        if zeros(0) * 2 >= half && zeros(1) == 0 {
+            return TextEncoding::Utf16Be;
+        }
+    }
+    TextEncoding::Utf8
+}
+
+/// 转换为不带 BOM 的 UTF-8；无法无损转换（如 UTF-16 中的孤立代理项）时返回错误
+pub fn to_utf8(content: &[u8]) -> Result<(TextEncoding, Cow<'_, [u8]>)> {
+    let encoding = detect_encoding(content);
+    let converted = match encoding {
+        TextEncoding::Utf8 => Cow::Borrowed(content),
+        TextEncoding::Utf8Bom => Cow::Borrowed(&content[UTF8_BOM.len()..]),
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            let body = content.strip_prefix(UTF16LE_BOM)
+                .filter(|_| encoding == TextEncoding::Utf16Le)
+                .or_else(|| content.strip_prefix(UTF16BE_BOM).filter(|_| encoding == TextEncoding::Utf16Be))
+                .unwrap_or(content);
+            if !body.len().is_multiple_of(2) {
+                anyhow::bail!("{} 内容的字节数为奇数，文件可能已截断", encoding.as_str());
+            }
+            let units = body.chunks_exact(2).map(|pair| match encoding {
+                TextEncoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                _ => u16::from_be_bytes([pair[0], pair[1]]),
+            });
+            let text = char::decode_utf16(units)
+                .enumerate()
+                .map(|(index, c)| c.map_err(|e| anyhow::anyhow!("{} 第 {} 个字符是孤立的代理项 0x{:04X}", encoding.as_str(), index + 1, e.unpaired_surrogate())))
+                .collect::<Result<String>>()?;
+            Cow::Owned(text.into_bytes())
+        }
+    };
+    Ok((encoding, converted))
+}
+
+/// 项目中需要转换编码的文本文件（跳过 .rmmp 与 .git），返回 (相对路径, 编码, 转换错误)
+pub fn scan_encodings(project_path: &Path) -> Vec<(PathBuf, TextEncoding, Option<String>)> {
+    WalkDir::new(project_path)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| e.depth() != 1 || !matches!(e.file_name().to_str(), Some(".rmmp" | ".git")))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_text_file(e.path()))
+        .filter_map(|e| {
+            let content = std::fs::read(e.path()).ok()?;
+            let relative = e.path().strip_prefix(project_path).unwrap_or(e.path()).to_path_buf();
+            match to_utf8(&content) {
+                Ok((TextEncoding::Utf8, _)) => None,
+                Ok((encoding, _)) => Some((relative, encoding, None)),
+                Err(err) => Some((relative, detect_encoding(&content), Some(err.to_string()))),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_text_encoding_normalization() {
+        let utf16 = |text: &str, bom: bool, little: bool| -> Vec<u8> {
+            let mut bytes: Vec<u8> = if !bom { Vec::new() } else if little { vec![0xFF, 0xFE] } else { vec![0xFE, 0xFF] };
+            for unit in text.encode_utf16() {
+                bytes.extend(if little { unit.to_le_bytes() } else { unit.to_be_bytes() });
+            }
+            bytes
+        };
+        let script = "#!/system/bin/sh\necho 你好\n";
+        assert_eq!(to_utf8(script.as_bytes()).unwrap().0, TextEncoding::Utf8);
+        let with_bom = [b"\xEF\xBB\xBF".as_slice(), script.as_bytes()].concat();
+        let (encoding, converted) = to_utf8(&with_bom).unwrap();
+        assert_eq!((encoding, converted.as_ref()), (TextEncoding::Utf8Bom, script.as_bytes()));
+        for (bom, little, expected) in [(true, true, TextEncoding::Utf16Le), (true, false, TextEncoding::Utf16Be), (false, true, TextEncoding::Utf16Le), (false, false, TextEncoding::Utf16Be)] {
+            let content = utf16(script, bom, little);
+            let (encoding, converted) = to_utf8(&content).unwrap();
+            assert_eq!((encoding, converted.as_ref()), (expected, script.as_bytes()));
+        }
+        // Latin-1 等非 UTF-8 文本保持原样
+        assert_eq!(detect_encoding(b"echo caf\xe9\n"), TextEncoding::Utf8);
+
+        // 孤立代理项无法无损转换
+        let mut broken = utf16("echo", true, true);
+        broken.extend([0x00, 0xD8]);
+        let err = to_utf8(&broken).unwrap_err().to_string();
+        assert!(err.contains("孤立的代理项 0xD800"), "{}", err);
+
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        fs::create_dir_all(project.join(".rmmp")).unwrap();
+        fs::write(project.join("service.sh"), utf16(script, true, true)).unwrap();
+        fs::write(project.join("customize.sh"), &broken).unwrap();
+        fs::write(project.join("module.prop"), "id=demo\n").unwrap();
+        fs::write(project.join(".rmmp/notes.txt"), b"\xEF\xBB\xBFignored").unwrap();
+        let mut issues = scan_encodings(project);
+        issues.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].0, PathBuf::from("customize.sh"));
+        assert!(issues[0].2.is_some());
+        assert_eq!((issues[1].0.clone(), issues[1].1, issues[1].2.clone()), (PathBuf::from("service.sh"), TextEncoding::Utf16Le, None));
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// 项目环境文件（可提交）
+pub const ENV_FILE: &str = ".rmm.env";
+/// 本机环境文件（不提交，覆盖 .rmm.env）
+pub const ENV_LOCAL_FILE: &str = ".rmm.env.local";
+
+/// 解析 KEY=VALUE 格式的环境文件，支持注释、export 前缀与引号
+pub fn parse_env(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return None;
+            }
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"').and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// 替换字符串中的 ${NAME}：优先使用项目变量，其次进程环境变量，未定义的保持原样
+pub fn interpolate(value: &str, vars: &BTreeMap<String, String>) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            result.push_str(&rest[start..]);
+            return result;
+        };
+        let name = &after[..end];
+        match vars.get(name).cloned().or_else(|| std::env::var(name).ok()) {
+            Some(v) => result.push_str(&v),
+            None => result.push_str(&rest[start..start + 3 + end]),
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 加载项目的 .rmm.env 与 .rmm.env.local（后者覆盖前者），值中的 ${NAME} 会被展开
+pub fn load_project_env(project_path: &Path) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    for file in [ENV_FILE, ENV_LOCAL_FILE] {
+        let path = project_path.join(file);
+        if !path.exists() {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        for (key, value) in parse_env(&content) {
+            let value = interpolate(&value, &vars);
+            vars.insert(key, value);
+        }
+    }
+    Ok(vars)
+}
+
+/// 递归展开 TOML 值中所有字符串里的 ${NAME}
+pub fn interpolate_toml(value: &mut toml::Value, vars: &BTreeMap<String, String>) {
+    match value {
+        toml::Value::String(s) => *s = interpolate(s, vars),
+        toml::Value::Array(items) => items.iter_mut().for_each(|v| interpolate_toml(v, vars)),
+        toml::Value::Table(table) => table.iter_mut().for_each(|(_, v)| interpolate_toml(v, vars)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_project_env_files_and_interpolation() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join(".rmm.env"),
+            "# comment\nOUT_DIR=out\nexport TOKEN=\"public\"\nDIST=${OUT_DIR}/dist\n",
+        ).unwrap();
+        fs::write(temp_dir.path().join(".rmm.env.local"), "TOKEN='secret'\n").unwrap();
+
+        let vars = load_project_env(temp_dir.path()).unwrap();
+        assert_eq!(vars.get("TOKEN").map(String::as_str), Some("secret"));
+        assert_eq!(vars.get("DIST").map(String::as_str), Some("out/dist"));
+
+        let mut value: toml::Value = toml::from_str(
+            "[build]\npostbuild = [\"cp x ${DIST}\", \"echo ${RMM_TEST_UNDEFINED_VAR}\"]\n",
+        ).unwrap();
+        interpolate_toml(&mut value, &vars);
+        let postbuild = value["build"]["postbuild"].as_array().unwrap();
+        assert_eq!(postbuild[0].as_str(), Some("cp x out/dist"));
+        assert_eq!(postbuild[1].as_str(), Some("echo ${RMM_TEST_UNDEFINED_VAR}"));
+    }
+}
@@ -0,0 +1,109 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use std::fmt;
+use std::path::PathBuf;
+
+create_exception!(rmmcore, RmmError, PyRuntimeError, "RMM 异常的基类（继承 RuntimeError，兼容 except RuntimeError）");
+create_exception!(rmmcore, ProjectNotFound, RmmError, "路径不是有效的 RMM 项目");
+create_exception!(rmmcore, ConfigError, RmmError, "配置文件缺失、无法解析或取值无效");
+create_exception!(rmmcore, BuildError, RmmError, "构建、脚本、检查或测试失败");
+create_exception!(rmmcore, DeviceError, RmmError, "adb 与设备操作失败");
+create_exception!(rmmcore, NetworkError, RmmError, "网络请求失败");
+
+/// 路径不是有效的 RMM 项目；经过 anyhow 传递后仍可识别，转换为 Python 的 ProjectNotFound
+#[derive(Debug)]
+pub struct ProjectNotFoundError {
+    pub path: PathBuf,
+}
+
+impl fmt::Display for ProjectNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} 不是有效的 RMM 项目", self.path.display())
+    }
+}
+
+impl std::error::Error for ProjectNotFoundError {}
+
+/// Python 异常的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    General,
+    ProjectNotFound,
+    Config,
+    Build,
+    Device,
+    Network,
+}
+
+impl ErrorKind {
+    /// 按错误链细化类别：项目不存在、网络与 TOML 解析错误优先于调用方给出的类别
+    pub fn of(error: &anyhow::Error, fallback: Self) -> Self {
+        for cause in error.chain() {
+            if cause.is::<ProjectNotFoundError>() {
+                return ErrorKind::ProjectNotFound;
+            }
+            if cause.is::<reqwest::Error>() || cause.is::<super::github::ApiError>() {
+                return ErrorKind::Network;
+            }
+            if cause.is::<toml::de::Error>() || cause.is::<toml_edit::TomlError>() {
+                return ErrorKind::Config;
+            }
+        }
+        fallback
+    }
+
+    pub fn new_err(self, message: impl Into<String>) -> PyErr {
+        let message = message.into();
+        match self {
+            ErrorKind::General => RmmError::new_err(message),
+            ErrorKind::ProjectNotFound => ProjectNotFound::new_err(message),
+            ErrorKind::Config => ConfigError::new_err(message),
+            ErrorKind::Build => BuildError::new_err(message),
+            ErrorKind::Device => DeviceError::new_err(message),
+            ErrorKind::Network => NetworkError::new_err(message),
+        }
+    }
+}
+
+/// anyhow 错误转换为 Python 异常，message 为异常文本
+pub fn to_py_err(error: &anyhow::Error, fallback: ErrorKind, message: impl Into<String>) -> PyErr {
+    ErrorKind::of(error, fallback).new_err(message)
+}
+
+/// 读取项目文件（rmmproject.toml、module.prop、Rmake.toml）失败：文件不存在为 ProjectNotFound，其余为 ConfigError
+pub fn project_file_err(error: &anyhow::Error) -> PyErr {
+    let missing = error.chain()
+        .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+        .any(|e| e.kind() == std::io::ErrorKind::NotFound);
+    let fallback = if missing { ErrorKind::ProjectNotFound } else { ErrorKind::Config };
+    to_py_err(error, fallback, error.to_string())
+}
+
+/// 在 pymodule 中注册异常类型，Python 中可 from pyrmm.cli.rmmcore import RmmError
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("RmmError", py.get_type::<RmmError>())?;
+    m.add("ProjectNotFound", py.get_type::<ProjectNotFound>())?;
+    m.add("ConfigError", py.get_type::<ConfigError>())?;
+    m.add("BuildError", py.get_type::<BuildError>())?;
+    m.add("DeviceError", py.get_type::<DeviceError>())?;
+    m.add("NetworkError", py.get_type::<NetworkError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        let missing = anyhow::Error::from(ProjectNotFoundError { path: PathBuf::from("/tmp/demo") }).context("构建失败");
+        assert_eq!(ErrorKind::of(&missing, ErrorKind::Build), ErrorKind::ProjectNotFound);
+        assert_eq!(missing.root_cause().to_string(), "/tmp/demo 不是有效的 RMM 项目");
+
+        let parse = anyhow::Error::from(toml::from_str::<toml::Value>("a = ").unwrap_err()).context("Failed to parse Rmake.toml");
+        assert_eq!(ErrorKind::of(&parse, ErrorKind::Build), ErrorKind::Config);
+        assert_eq!(ErrorKind::of(&anyhow::anyhow!("adb 连接失败"), ErrorKind::Device), ErrorKind::Device);
+    }
+}
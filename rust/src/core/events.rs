@@ -0,0 +1,90 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// 项目事件日志（位于 .rmmp 下，每行一个 JSON 对象，只追加）
+pub const EVENTS_FILE: &str = "events.jsonl";
+
+/// 一条项目事件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProjectEvent {
+    pub timestamp: String,
+    /// 操作者，来自 git 配置或系统用户名
+    pub actor: String,
+    /// 事件类型：init、version_bump、build、publish、device_install 等
+    pub kind: String,
+    #[serde(default)]
+    pub details: BTreeMap<String, String>,
+}
+
+impl ProjectEvent {
+    /// 单行摘要，用于 rmm log
+    pub fn summary(&self) -> String {
+        let detail = |key: &str| self.details.get(key).map(String::as_str).unwrap_or("?");
+        match self.kind.as_str() {
+            "version_bump" => format!("{} -> {}", detail("from"), detail("to")),
+            "build" => format!("{} sha256:{}", detail("artifact"), &detail("sha256")[..detail("sha256").len().min(12)]),
+            "publish" => detail("url").to_string(),
+            "device_install" => format!("{} @ {}", detail("artifact"), detail("device")),
+            _ => self.details.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join(" "),
+        }
+    }
+}
+
+/// 当前操作者：git user.name <user.email>，否则为系统用户名
+fn current_actor(project_path: &Path) -> String {
+    let config = git2::Repository::discover(project_path)
+        .and_then(|repo| repo.config())
+        .or_else(|_| git2::Config::open_default());
+    if let Some((config, name)) = config.ok().and_then(|c| c.get_string("user.name").ok().map(|n| (c, n))) {
+        return match config.get_string("user.email") {
+            Ok(email) => format!("{} <{}>", name, email),
+            Err(_) => name,
+        };
+    }
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// 追加一条事件
+pub fn record_event(project_path: &Path, kind: &str, details: &[(&str, String)]) -> Result<ProjectEvent> {
+    let event = ProjectEvent {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        actor: current_actor(project_path),
+        kind: kind.to_string(),
+        details: details.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+    };
+
+    let rmmp_dir = project_path.join(".rmmp");
+    fs::create_dir_all(&rmmp_dir)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(rmmp_dir.join(EVENTS_FILE))?;
+    writeln!(file, "{}", serde_json::to_string(&event)?)?;
+    Ok(event)
+}
+
+/// 记录事件，失败时只打印警告，不影响主流程
+pub fn record_event_or_warn(project_path: &Path, kind: &str, details: &[(&str, String)]) {
+    if let Err(e) = record_event(project_path, kind, details) {
+        eprintln!("⚠️ 警告: 无法写入事件日志: {}", e);
+    }
+}
+
+/// 读取全部事件，跳过无法解析的行
+pub fn read_events(project_path: &Path) -> Result<Vec<ProjectEvent>> {
+    let path = project_path.join(".rmmp").join(EVENTS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+
+use super::rmm_core::BuildConfig;
+
+/// 内置排除预设：名称与对应的排除规则（规则语义与 [build] exclude 相同）
+pub const EXCLUDE_PRESETS: &[(&str, &[&str])] = &[
+    ("node", &[
+        "node_modules",
+        ".npm",
+        ".pnpm-store",
+        "**/.yarn/cache",
+        "npm-debug.log*",
+        "yarn-error.log",
+    ]),
+    ("python", &[
+        "__pycache__",
+        "*.pyc",
+        "*.pyo",
+        ".venv",
+        ".pytest_cache",
+        ".mypy_cache",
+        ".ruff_cache",
+        ".tox",
+        "*.egg-info",
+    ]),
+    ("rust", &[
+        "target/",
+        "*.rs.bk",
+    ]),
+    ("android-studio", &[
+        ".idea",
+        ".gradle",
+        "*.iml",
+        "local.properties",
+        ".externalNativeBuild",
+        ".cxx",
+        "captures/",
+    ]),
+];
+
+/// 默认排除的文件与目录名：路径中任一层的名称匹配即排除（* 为通配符），在用户规则之前应用，
+/// [build] allow 中的规则可放行；项目根目录的 .rmmp 另有处理，这里只匹配嵌套项目留下的 .rmmp
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    // 版本控制
+    ".git",
+    ".hg",
+    ".svn",
+    ".bzr",
+    "_darcs",
+    "CVS",
+    // 系统生成的文件
+    ".DS_Store",
+    "._*",
+    "__MACOSX",
+    ".Spotlight-V100",
+    ".Trashes",
+    "Thumbs.db",
+    "ehthumbs.db",
+    "desktop.ini",
+    // 编辑器
+    ".vscode",
+    ".idea",
+    ".fleet",
+    "*.swp",
+    "*.swo",
+    "*~",
+    // 构建系统
+    ".rmmp",
+];
+
+/// 预设名称列表
+pub fn preset_names() -> Vec<&'static str> {
+    EXCLUDE_PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+/// 展开预设为排除规则，未知预设报错
+pub fn expand_presets(names: &[String]) -> Result<Vec<String>> {
+    let mut patterns = Vec::new();
+    for name in names {
+        let (_, preset) = EXCLUDE_PRESETS.iter()
+            .find(|(preset, _)| *preset == name.trim())
+            .ok_or_else(|| anyhow::anyhow!(
+                "未知的排除预设 '{}'，可用预设: {}", name, preset_names().join(", ")
+            ))?;
+        patterns.extend(preset.iter().map(|p| p.to_string()));
+    }
+    Ok(patterns)
+}
+
+/// 模块打包的排除规则：exclude_presets + [build] exclude；预设在前，用户的 ! 规则可以重新包含预设排除的路径
+pub fn build_excludes(build: &BuildConfig) -> Result<Vec<String>> {
+    let mut patterns = expand_presets(&build.exclude_presets)?;
+    patterns.extend(build.exclude.iter().cloned());
+    Ok(patterns)
+}
+
+/// 源代码打包的排除规则：exclude_presets + [build.src] exclude
+pub fn source_excludes(build: &BuildConfig) -> Result<Vec<String>> {
+    let mut patterns = expand_presets(&build.exclude_presets)?;
+    patterns.extend(build.src.iter().flat_map(|src| src.exclude.iter().cloned()));
+    Ok(patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclude_presets_expand() {
+        use crate::core::rmm_core::SrcConfig;
+
+        let patterns = expand_presets(&["node".to_string(), "python".to_string()]).unwrap();
+        assert!(patterns.contains(&"node_modules".to_string()));
+        assert!(patterns.contains(&"__pycache__".to_string()));
+        assert!(expand_presets(&["cobol".to_string()]).unwrap_err().to_string().contains("android-studio"));
+
+        let build = BuildConfig {
+            exclude: vec![".git".to_string()],
+            exclude_presets: vec!["rust".to_string()],
+            src: Some(SrcConfig { include: Vec::new(), exclude: vec!["*.log".to_string()] }),
+            ..Default::default()
+        };
+        assert_eq!(build_excludes(&build).unwrap(), vec!["target/", "*.rs.bk", ".git"]);
+        assert_eq!(source_excludes(&build).unwrap(), vec!["target/", "*.rs.bk", "*.log"]);
+    }
+}
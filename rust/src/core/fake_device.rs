@@ -0,0 +1,295 @@
+use anyhow::{Context, Result};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::adb::{AdbBackend, AdbOutput, RootManager, MODULES_DIR, MODULES_UPDATE_DIR};
+use super::process::{CommandExt, CommandKind};
+use super::zip_inspect::{parse_module_prop, read_zip_member};
+
+const STATE_FILE: &str = "device.json";
+const COMMAND_LOG: &str = "commands.log";
+const INSTALL_QUEUE: &str = "install-queue";
+
+/// 模拟命令失败：执行的 adb 命令包含 pattern 时以 stderr 失败
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FakeFailure {
+    pub pattern: String,
+    pub stderr: String,
+}
+
+/// 模拟设备的状态，保存在 <root>/device.json，多次 rmm 调用之间保持一致
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FakeDeviceState {
+    pub serial: String,
+    /// getprop 返回的属性
+    pub props: BTreeMap<String, String>,
+    /// 设备上的 Root 管理器，None 时模拟未 Root 管理器的设备
+    pub manager: Option<RootManager>,
+    /// su 是否可用
+    pub rooted: bool,
+    /// false 时所有设备命令都以 "device offline" 失败
+    pub online: bool,
+    /// 重启后无法完成开机（模拟 bootloop）
+    #[serde(default)]
+    pub bootloop: bool,
+    #[serde(default)]
+    pub failures: Vec<FakeFailure>,
+}
+
+impl Default for FakeDeviceState {
+    fn default() -> Self {
+        let props = [
+            ("ro.product.model", "RMM Fake Device"),
+            ("ro.product.cpu.abilist", "arm64-v8a,armeabi-v7a"),
+            ("ro.product.cpu.abi", "arm64-v8a"),
+            ("ro.build.version.sdk", "34"),
+            ("sys.boot_completed", "1"),
+        ];
+        Self {
+            serial: "fake-device".to_string(),
+            props: props.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            manager: Some(RootManager::KernelSU),
+            rooted: true,
+            online: true,
+            bootloop: false,
+            failures: Vec::new(),
+        }
+    }
+}
+
+/// 不依赖硬件的模拟设备：设备文件系统映射到 <root>/fs，shell 命令由本机 sh 执行，
+/// su / getprop / ksud 等由 <root>/bin 中的脚本模拟
+#[derive(Debug, Clone)]
+pub struct FakeDevice {
+    root: PathBuf,
+}
+
+/// 模拟设备上的工具脚本
+fn tool_scripts(state: &FakeDeviceState) -> Vec<(&'static str, String)> {
+    let mut tools = vec![
+        ("getprop", r#"props="$RMM_FAKE_ROOT/props"
+if [ $# -eq 0 ]; then
+    sed 's/^\([^=]*\)=\(.*\)$/[\1]: [\2]/' "$props"
+else
+    awk -v key="$1" 'index($0, key "=") == 1 { print substr($0, length(key) + 2); exit }' "$props"
+fi"#.to_string()),
+        ("chown", ":".to_string()),
+        ("chcon", ":".to_string()),
+        ("restorecon", ":".to_string()),
+        ("screencap", r"printf '\211PNG\r\n\032\n'".to_string()),
+        ("screenrecord", r#"for f; do :; done; : > "$f""#.to_string()),
+    ];
+    tools.push(("su", if state.rooted {
+        r#"[ "$1" = "-c" ] && shift && exec sh -c "$1"
+exec sh"#.to_string()
+    } else {
+        r#"echo "su: permission denied" >&2; exit 1"#.to_string()
+    }));
+    if let Some(manager) = state.manager {
+        let binary = match manager {
+            RootManager::Magisk => "magisk",
+            RootManager::KernelSU => "ksud",
+            RootManager::APatch => "apd",
+        };
+        // 最后一个参数为模块 zip，交给 FakeDevice 在命令结束后解压到 modules_update
+        tools.push((binary, r#"for zip; do :; done
+[ -f "$zip" ] || { echo "! zip 不存在: $zip" >&2; exit 1; }
+echo "$zip" >> "$RMM_FAKE_ROOT/install-queue"
+echo "- Installing $(basename "$zip")""#.to_string()));
+    }
+    tools
+}
+
+fn output(success: bool, stdout: impl Into<Vec<u8>>, stderr: impl Into<Vec<u8>>) -> AdbOutput {
+    AdbOutput { success, stdout: stdout.into(), stderr: stderr.into() }
+}
+
+impl FakeDevice {
+    /// 打开模拟设备目录，不存在时以默认状态创建
+    pub fn open_or_create(root: &Path) -> Result<Self> {
+        let device = Self { root: root.to_path_buf() };
+        let state = if root.join(STATE_FILE).is_file() { device.state()? } else { FakeDeviceState::default() };
+        device.save(&state)?;
+        Ok(device)
+    }
+
+    pub fn state(&self) -> Result<FakeDeviceState> {
+        let path = self.root.join(STATE_FILE);
+        serde_json::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("{} 格式无效", path.display()))
+    }
+
+    /// 修改并保存状态
+    pub fn update(&self, f: impl FnOnce(&mut FakeDeviceState)) -> Result<()> {
+        let mut state = self.state()?;
+        f(&mut state);
+        self.save(&state)
+    }
+
+    fn save(&self, state: &FakeDeviceState) -> Result<()> {
+        let bin = self.root.join("bin");
+        fs::create_dir_all(&bin)?;
+        fs::create_dir_all(self.path(MODULES_DIR))?;
+        fs::create_dir_all(self.path("/data/local/tmp"))?;
+        fs::write(self.root.join(STATE_FILE), serde_json::to_string_pretty(state)?)?;
+        let props: String = state.props.iter().map(|(k, v)| format!("{}={}\n", k, v)).collect();
+        fs::write(self.root.join("props"), props)?;
+
+        for entry in fs::read_dir(&bin)? {
+            fs::remove_file(entry?.path())?;
+        }
+        for (name, body) in tool_scripts(state) {
+            let path = bin.join(name);
+            fs::write(&path, format!("#!/bin/sh\n{}\n", body))?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    }
+
+    /// 设备路径在本机上的位置
+    pub fn path(&self, device_path: &str) -> PathBuf {
+        self.root.join("fs").join(device_path.trim_start_matches('/'))
+    }
+
+    /// 执行过的 adb 命令（按顺序）
+    #[cfg(test)]
+    pub fn commands(&self) -> Vec<String> {
+        fs::read_to_string(self.root.join(COMMAND_LOG))
+            .map(|log| log.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// 连接到该模拟设备的 Adb
+    #[cfg(test)]
+    pub fn adb(&self) -> Result<super::adb::Adb> {
+        let serial = self.state()?.serial;
+        Ok(super::adb::Adb::from_backend(std::sync::Arc::new(self.clone())).with_serial(&serial))
+    }
+
+    /// 将命令中的设备绝对路径改写到 <root>/fs 下
+    fn map_paths(&self, command: &str) -> String {
+        let pattern = Regex::new(r#"(^|[\s'"=:;(])(/(?:data|sdcard)/)"#).expect("valid regex");
+        let fs_root = self.root.join("fs");
+        pattern.replace_all(command, |caps: &Captures| {
+            format!("{}{}{}", &caps[1], fs_root.display(), &caps[2])
+        }).into_owned()
+    }
+
+    fn run_shell(&self, command: &str) -> Result<AdbOutput> {
+        let path = format!("{}:{}", self.root.join("bin").display(), std::env::var("PATH").unwrap_or_default());
+        let result = Command::new("sh")
+            .arg("-c")
+            .arg(self.map_paths(command))
+            .current_dir(self.root.join("fs"))
+            .env("PATH", path)
+            .env("RMM_FAKE_ROOT", &self.root)
+            .output_with_timeout(CommandKind::Adb)?;
+        let mut result = output(result.status.success(), result.stdout, result.stderr);
+        if let Err(e) = self.process_installs() {
+            result.success = false;
+            result.stderr.extend(format!("! 安装失败: {:#}\n", e).into_bytes());
+        }
+        Ok(result)
+    }
+
+    /// 处理管理器脚本记录的安装：按 module.prop 中的 id 解压到 modules_update
+    fn process_installs(&self) -> Result<()> {
+        let queue = self.root.join(INSTALL_QUEUE);
+        let Ok(content) = fs::read_to_string(&queue) else {
+            return Ok(());
+        };
+        fs::remove_file(&queue)?;
+        for zip_path in content.lines().filter(|l| !l.is_empty()).map(Path::new) {
+            let prop = String::from_utf8_lossy(&read_zip_member(zip_path, "module.prop")?).to_string();
+            let id = parse_module_prop(&prop).get("id").cloned()
+                .ok_or_else(|| anyhow::anyhow!("module.prop 中缺少 id"))?;
+            let target = self.path(&format!("{}/{}", MODULES_UPDATE_DIR, id));
+            if target.exists() {
+                fs::remove_dir_all(&target)?;
+            }
+            zip::ZipArchive::new(fs::File::open(zip_path)?)?.extract(&target)?;
+        }
+        Ok(())
+    }
+
+    /// 重启：应用 modules_update 中的更新，删除带 remove 标记的模块
+    fn reboot(&self, state: &FakeDeviceState) -> Result<AdbOutput> {
+        let modules = self.path(MODULES_DIR);
+        let updates = self.path(MODULES_UPDATE_DIR);
+        for entry in fs::read_dir(&updates).into_iter().flatten().flatten() {
+            let target = modules.join(entry.file_name());
+            if target.exists() {
+                fs::remove_dir_all(&target)?;
+            }
+            fs::rename(entry.path(), target)?;
+        }
+        for entry in fs::read_dir(&modules)?.flatten() {
+            if entry.path().join("remove").exists() {
+                fs::remove_dir_all(entry.path())?;
+            }
+        }
+        let booted = if state.bootloop { "0" } else { "1" };
+        self.update(|state| {
+            state.props.insert("sys.boot_completed".to_string(), booted.to_string());
+        })?;
+        Ok(output(true, "", ""))
+    }
+
+    fn log(&self, line: &str) -> Result<()> {
+        use std::io::Write;
+        let mut log = fs::OpenOptions::new().create(true).append(true).open(self.root.join(COMMAND_LOG))?;
+        writeln!(log, "{}", line)?;
+        Ok(())
+    }
+}
+
+impl AdbBackend for FakeDevice {
+    fn execute(&self, serial: Option<&str>, args: &[&str]) -> Result<AdbOutput> {
+        let command = args.join(" ");
+        self.log(&command)?;
+        let state = self.state()?;
+
+        if let Some(failure) = state.failures.iter().find(|f| command.contains(&f.pattern)) {
+            return Ok(output(false, "", failure.stderr.clone()));
+        }
+        let status = if state.online { "device" } else { "offline" };
+        match args {
+            ["devices"] => return Ok(output(true, format!("List of devices attached\n{}\t{}\n", state.serial, status), "")),
+            ["connect", address] => return Ok(output(true, format!("connected to {}\n", address), "")),
+            ["disconnect" | "pair" | "mdns", ..] => return Ok(output(true, "", "")),
+            _ => {}
+        }
+        if serial.is_some_and(|s| s != state.serial) {
+            return Ok(output(false, "", format!("adb: device '{}' not found\n", serial.unwrap_or_default())));
+        }
+        if !state.online {
+            return Ok(output(false, "", "error: device offline\n"));
+        }
+
+        match args {
+            ["get-state"] => Ok(output(true, "device\n", "")),
+            ["shell" | "exec-out", rest @ ..] => self.run_shell(&rest.join(" ")),
+            ["push", local, remote] => {
+                let target = self.path(remote);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                match fs::copy(local, &target) {
+                    Ok(_) => Ok(output(true, format!("{}: 1 file pushed\n", local), "")),
+                    Err(e) => Ok(output(false, "", format!("adb: error: cannot stat '{}': {}\n", local, e))),
+                }
+            }
+            ["pull", remote, local] => match fs::copy(self.path(remote), local) {
+                Ok(_) => Ok(output(true, format!("{}: 1 file pulled\n", remote), "")),
+                Err(_) => Ok(output(false, "", format!("adb: error: failed to stat remote object '{}': No such file or directory\n", remote))),
+            },
+            ["reboot"] => self.reboot(&state),
+            _ => Ok(output(false, "", format!("fake adb: 不支持的命令: {}\n", command))),
+        }
+    }
+}
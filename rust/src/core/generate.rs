@@ -0,0 +1,137 @@
+use anyhow::Result;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::vcs::analyze_vcs_info;
+use super::zip_inspect::{parse_module_prop, read_prop_text};
+
+/// Rmake.toml 中的 [[build.generate]]：每次构建时渲染到构建目录的文件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct GenerateRule {
+    /// 构建目录中的目标路径，如 system/etc/version.txt
+    pub target: String,
+    /// 文件内容模板，{{name}} 会被替换为变量值
+    pub template: String,
+}
+
+/// 渲染模板：替换 {{name}}（允许两侧空格），{{env.NAME}} 读取环境变量；未知变量报错
+pub fn render_template(template: &str, vars: &BTreeMap<String, String>) -> Result<String> {
+    let placeholder = Regex::new(r"\{\{\s*([A-Za-z0-9_.]+)\s*\}\}")?;
+    let mut unknown = Vec::new();
+    let rendered = placeholder.replace_all(template, |caps: &regex::Captures| {
+        let name = &caps[1];
+        let value = match name.strip_prefix("env.") {
+            Some(var) => std::env::var(var).ok(),
+            None => vars.get(name).cloned(),
+        };
+        value.unwrap_or_else(|| {
+            unknown.push(name.to_string());
+            String::new()
+        })
+    });
+    if !unknown.is_empty() {
+        anyhow::bail!(
+            "未知的模板变量: {}（可用: {}，环境变量使用 env.NAME）",
+            unknown.join(", "),
+            vars.keys().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+    Ok(rendered.into_owned())
+}
+
+/// 构建时可用的模板变量：module.prop 字段、Git 信息与构建时间
+pub fn generation_vars(project_path: &Path) -> Result<BTreeMap<String, String>> {
+    let prop = parse_module_prop(&read_prop_text(&project_path.join("module.prop"))?);
+    let mut vars = BTreeMap::new();
+    for (key, name) in [
+        ("id", "id"),
+        ("name", "name"),
+        ("version", "version"),
+        ("versionCode", "version_code"),
+        ("author", "author"),
+        ("description", "description"),
+    ] {
+        vars.insert(name.to_string(), prop.get(key).cloned().unwrap_or_default());
+    }
+
+    let git = analyze_vcs_info(project_path).ok().flatten();
+    let hash = git.as_ref().and_then(|g| g.last_commit_hash.clone()).unwrap_or_default();
+    vars.insert("git_hash".to_string(), hash.chars().take(8).collect());
+    vars.insert("git_hash_full".to_string(), hash);
+    vars.insert("git_branch".to_string(), git.map(|g| g.branch).unwrap_or_default());
+
+    let now = chrono::Utc::now();
+    vars.insert("build_time".to_string(), now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    vars.insert("build_date".to_string(), now.format("%Y-%m-%d").to_string());
+    vars.insert("rmm_version".to_string(), env!("CARGO_PKG_VERSION").to_string());
+    Ok(vars)
+}
+
+/// 校验目标路径：必须是构建目录内的相对路径
+fn target_path(build_dir: &Path, target: &str) -> Result<PathBuf> {
+    let relative = Path::new(target.trim());
+    let escapes = relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if target.trim().is_empty() || escapes {
+        anyhow::bail!("[[build.generate]] target '{}' 必须是构建目录内的相对路径", target);
+    }
+    Ok(build_dir.join(relative))
+}
+
+/// 按规则渲染文件到构建目录（统一使用 LF 换行），返回写入的文件
+pub fn generate_files(build_dir: &Path, rules: &[GenerateRule], vars: &BTreeMap<String, String>) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for rule in rules {
+        let path = target_path(build_dir, &rule.target)?;
+        let content = render_template(&rule.template, vars)
+            .map_err(|e| anyhow::anyhow!("生成 {} 失败: {}", rule.target, e))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, content.replace("\r\n", "\n"))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_build_generate_rules() {
+        use crate::core::rmm_core::RmakeConfig;
+
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("module.prop"), "id=demo\nversion=v1.2\nversionCode=120\n").unwrap();
+        let config: RmakeConfig = toml::from_str(r#"
+[build]
+include = []
+exclude = []
+prebuild = []
+build = []
+postbuild = []
+
+[[build.generate]]
+target = "system/etc/version.txt"
+template = "v{{ version }} ({{version_code}}) {{git_hash}}\r\n"
+"#).unwrap();
+        assert_eq!(config.build.generate.len(), 1);
+
+        let vars = generation_vars(temp.path()).unwrap();
+        assert_eq!(vars["git_hash"], "");
+        let build_dir = temp.path().join("build");
+        let written = generate_files(&build_dir, &config.build.generate, &vars).unwrap();
+        assert_eq!(written, vec![build_dir.join("system/etc/version.txt")]);
+        assert_eq!(fs::read_to_string(&written[0]).unwrap(), "vv1.2 (120) \n");
+
+        let err = render_template("{{nope}}", &vars).unwrap_err().to_string();
+        assert!(err.contains("nope") && err.contains("version_code"));
+        let escape = GenerateRule { target: "../outside.txt".to_string(), template: String::new() };
+        assert!(generate_files(&build_dir, &[escape], &vars).is_err());
+    }
+}
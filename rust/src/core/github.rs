@@ -0,0 +1,548 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use colored::Colorize;
+use futures_util::future::{select, Either};
+use futures_util::TryStreamExt;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt;
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::pin::pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::io::ReaderStream;
+
+use super::size_history::format_size;
+use super::update_json::sibling_url;
+
+const GITHUB_API: &str = "https://api.github.com";
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+/// 上传附件时连续没有进展（发送数据或等待响应）的最长时间；不限制总时长，大文件在慢速网络上也能传完
+const UPLOAD_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+/// 检查上传是否停滞的最长间隔
+const UPLOAD_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+/// 流式上传时每次读取的字节数，也是判断上传是否停滞的粒度
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+/// 进度行的最短刷新间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+/// 未固定到具体标签的 Release 下载地址
+const LATEST_DOWNLOAD: &str = "/releases/latest/download/";
+
+/// GitHub Release
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub id: u64,
+    pub html_url: String,
+    /// 附件上传地址模板，如 https://uploads.github.com/repos/o/r/releases/1/assets{?name,label}
+    pub upload_url: String,
+}
+
+/// Release 附件
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub id: u64,
+    pub name: String,
+    pub size: u64,
+    /// uploaded；中断的上传会留下 starter 状态的附件
+    pub state: String,
+    pub browser_download_url: String,
+    /// GitHub 计算的摘要，如 "sha256:…"（较早上传的附件没有）
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+/// 上传进度：在终端中以单行刷新已发送的字节数、百分比与速度，非终端时不输出
+struct UploadProgress {
+    name: String,
+    total: u64,
+    sent: AtomicU64,
+    started: Instant,
+    /// 上次发送数据的时间（自 started 起的毫秒数）
+    last_advance: AtomicU64,
+    /// 上次刷新的时间，None 表示不输出
+    last_draw: Option<Mutex<Instant>>,
+}
+
+impl UploadProgress {
+    fn new(name: &str, total: u64) -> Self {
+        let started = Instant::now();
+        Self {
+            name: name.to_string(),
+            total,
+            sent: AtomicU64::new(0),
+            started,
+            last_advance: AtomicU64::new(0),
+            last_draw: std::io::stderr().is_terminal().then(|| Mutex::new(started)),
+        }
+    }
+
+    fn advance(&self, bytes: u64) {
+        let sent = self.sent.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.last_advance.store(self.started.elapsed().as_millis() as u64, Ordering::Relaxed);
+        let Some(last_draw) = &self.last_draw else { return };
+        let mut last_draw = last_draw.lock().unwrap_or_else(|e| e.into_inner());
+        if last_draw.elapsed() >= PROGRESS_INTERVAL || sent >= self.total {
+            *last_draw = Instant::now();
+            self.draw(sent);
+        }
+    }
+
+    fn draw(&self, sent: u64) {
+        let percent = (sent * 100).checked_div(self.total).unwrap_or(100);
+        let seconds = self.started.elapsed().as_secs_f64();
+        let speed = if seconds > 0.0 { (sent as f64 / seconds) as u64 } else { 0 };
+        eprint!("\r\x1b[2K{} {} {}/{} {}% {}/s", "[upload]".cyan().bold(), self.name,
+            format_size(sent), format_size(self.total), percent, format_size(speed));
+        let _ = std::io::stderr().flush();
+    }
+
+    /// 距上次发送数据的时间
+    fn idle(&self) -> Duration {
+        self.started.elapsed().saturating_sub(Duration::from_millis(self.last_advance.load(Ordering::Relaxed)))
+    }
+
+    /// 结束进度行（上传成功或失败都需要换行）
+    fn finish(&self) {
+        if self.last_draw.is_some() {
+            self.draw(self.sent.load(Ordering::Relaxed));
+            eprintln!();
+        }
+    }
+}
+
+/// upstream-pr 提交的文件
+#[derive(Debug, Clone)]
+pub struct PullRequestFile {
+    /// 仓库内的路径，如 modules/<id>.json
+    pub path: String,
+    pub content: String,
+    /// 分叉中的分支
+    pub branch: String,
+    /// 提交信息与 PR 标题
+    pub message: String,
+    pub body: String,
+}
+
+/// 以 module.prop 的 version 作为标签，补全 v 前缀
+pub fn release_tag(version: &str) -> String {
+    let version = version.trim();
+    if version.starts_with('v') { version.to_string() } else { format!("v{}", version) }
+}
+
+/// 去掉 upload_url 中的 {?name,label} 模板
+pub fn upload_endpoint(upload_url: &str) -> &str {
+    upload_url.split('{').next().unwrap_or(upload_url)
+}
+
+/// 校验上传结果：状态与大小一致，GitHub 返回 digest 时校验 SHA-256
+pub fn verify_asset(asset: &ReleaseAsset, size: u64, sha256: &str) -> Result<()> {
+    if asset.state != "uploaded" || asset.size != size {
+        anyhow::bail!("上传不完整: 服务器记录 {} 字节（{}），本地 {} 字节", asset.size, asset.state, size);
+    }
+    if let Some(digest) = &asset.digest
+        && digest.strip_prefix("sha256:").unwrap_or(digest) != sha256
+    {
+        anyhow::bail!("SHA-256 不一致: 服务器 {}，本地 {}", digest, sha256);
+    }
+    Ok(())
+}
+
+/// 把 update.json 的 zipUrl 改为上传后的附件地址；changelog 原本与 zipUrl 相邻时跟随新地址，
+/// 指向 releases/latest 时固定到 tag
+pub fn pin_update_json(content: &str, zip_url: &str, tag: &str) -> Result<String> {
+    let mut value: serde_json::Value = serde_json::from_str(content).context("update.json 不是有效的 JSON")?;
+    let manifest = value.as_object_mut().ok_or_else(|| anyhow::anyhow!("update.json 不是 JSON 对象"))?;
+    let old_zip_url = manifest.get("zipUrl").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    if let Some(changelog) = manifest.get("changelog").and_then(|v| v.as_str()) {
+        let name = changelog.rsplit('/').next().unwrap_or_default();
+        let pinned = match sibling_url(&old_zip_url, name) {
+            Some(sibling) if sibling == changelog => sibling_url(zip_url, name).unwrap_or_else(|| changelog.to_string()),
+            _ => changelog.replace(LATEST_DOWNLOAD, &format!("/releases/download/{}/", tag)),
+        };
+        manifest.insert("changelog".to_string(), pinned.into());
+    }
+    manifest.insert("zipUrl".to_string(), zip_url.into());
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+/// GitHub REST API 客户端（同步调用）
+pub struct GithubClient {
+    token: String,
+    runtime: tokio::runtime::Runtime,
+    client: reqwest::Client,
+    upload_idle_timeout: Duration,
+}
+
+impl GithubClient {
+    pub fn new(token: &str) -> Result<Self> {
+        Ok(Self {
+            token: token.to_string(),
+            runtime: tokio::runtime::Builder::new_current_thread().enable_all().build()?,
+            client: reqwest::Client::builder()
+                .connect_timeout(API_TIMEOUT)
+                .user_agent(concat!("rmm/", env!("CARGO_PKG_VERSION")))
+                .build()?,
+            upload_idle_timeout: UPLOAD_IDLE_TIMEOUT,
+        })
+    }
+
+    #[cfg(test)]
+    fn with_upload_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.upload_idle_timeout = timeout;
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        self.request_without_timeout(method, path).timeout(API_TIMEOUT)
+    }
+
+    /// 不限制总时长的请求，用于上传附件
+    fn request_without_timeout(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let url = if path.starts_with("https://") || path.starts_with("http://") { path.to_string() } else { format!("{}{}", GITHUB_API, path) };
+        self.client.request(method, url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+    }
+
+    /// 发送请求，返回状态码与响应正文
+    fn call(&self, method: Method, path: &str, body: Option<serde_json::Value>) -> Result<(StatusCode, String)> {
+        let mut request = self.request(method, path);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        self.runtime.block_on(async {
+            let response = request.send().await?;
+            let status = response.status();
+            Ok::<_, anyhow::Error>((status, response.text().await?))
+        })
+    }
+
+    /// 发送请求并解析 JSON，非 2xx 时返回 GitHub 的错误信息
+    fn call_json<T: DeserializeOwned>(&self, method: Method, path: &str, body: Option<serde_json::Value>) -> Result<T> {
+        let (status, text) = self.call(method, path, body)?;
+        if !status.is_success() {
+            return Err(api_error(status, &text));
+        }
+        serde_json::from_str(&text).with_context(|| format!("无法解析 GitHub API 响应: {}", path))
+    }
+
+    /// 令牌对应的用户名
+    pub fn login(&self) -> Result<String> {
+        #[derive(Deserialize)]
+        struct User {
+            login: String,
+        }
+        let user: User = self.call_json(Method::GET, "/user", None)
+            .context("无法通过 github.token 查询 GitHub 用户，请确认令牌有效")?;
+        Ok(user.login)
+    }
+
+    /// 按标签查找 Release，不存在时返回 None
+    pub fn release_by_tag(&self, repo: &str, tag: &str) -> Result<Option<Release>> {
+        let (status, text) = self.call(Method::GET, &format!("/repos/{}/releases/tags/{}", repo, tag), None)?;
+        match status {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(Some(serde_json::from_str(&text)?)),
+            status => Err(api_error(status, &text)),
+        }
+    }
+
+    pub fn create_release(&self, repo: &str, tag: &str, name: &str, body: &str) -> Result<Release> {
+        self.call_json(Method::POST, &format!("/repos/{}/releases", repo), Some(json!({
+            "tag_name": tag,
+            "name": name,
+            "body": body,
+            "draft": false,
+            "prerelease": false,
+        })))
+    }
+
+    pub fn update_release(&self, repo: &str, id: u64, name: &str, body: &str) -> Result<Release> {
+        self.call_json(Method::PATCH, &format!("/repos/{}/releases/{}", repo, id), Some(json!({
+            "name": name,
+            "body": body,
+            "draft": false,
+            "prerelease": false,
+        })))
+    }
+
+    pub fn list_assets(&self, repo: &str, release_id: u64) -> Result<Vec<ReleaseAsset>> {
+        self.call_json(Method::GET, &format!("/repos/{}/releases/{}/assets?per_page=100", repo, release_id), None)
+    }
+
+    pub fn delete_asset(&self, repo: &str, asset_id: u64) -> Result<()> {
+        let (status, text) = self.call(Method::DELETE, &format!("/repos/{}/releases/assets/{}", repo, asset_id), None)?;
+        if !status.is_success() && status != StatusCode::NOT_FOUND {
+            return Err(api_error(status, &text));
+        }
+        Ok(())
+    }
+
+    /// 从磁盘流式上传一个附件并显示进度（调用方需先删除同名附件，否则 GitHub 返回 422）；
+    /// 连接超时 30 秒，之后只在连续 5 分钟没有进展时中止，不限制总时长
+    pub fn upload_asset(&self, release: &Release, path: &Path) -> Result<ReleaseAsset> {
+        let name = path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow::anyhow!("无效的附件路径: {}", path.display()))?;
+        let size = fs::metadata(path).with_context(|| format!("无法读取 {}", path.display()))?.len();
+        let request = self.request_without_timeout(Method::POST, upload_endpoint(&release.upload_url))
+            .query(&[("name", name.as_str()), ("label", name.as_str())])
+            .header("Content-Type", "application/octet-stream")
+            .header("Content-Length", size);
+        let progress = Arc::new(UploadProgress::new(&name, size));
+        let idle_timeout = self.upload_idle_timeout;
+        let (status, text) = self.runtime.block_on(async {
+            let file = tokio::fs::File::open(path).await.with_context(|| format!("无法读取 {}", path.display()))?;
+            let reporter = Arc::clone(&progress);
+            let stream = ReaderStream::with_capacity(file, UPLOAD_CHUNK_SIZE)
+                .inspect_ok(move |chunk| reporter.advance(chunk.len() as u64));
+            let upload = async {
+                let response = request.body(reqwest::Body::wrap_stream(stream)).send().await?;
+                let status = response.status();
+                Ok::<_, anyhow::Error>((status, response.text().await?))
+            };
+            let watchdog = async {
+                while progress.idle() < idle_timeout {
+                    tokio::time::sleep((idle_timeout / 4).min(UPLOAD_WATCH_INTERVAL)).await;
+                }
+            };
+            let result = match select(pin!(upload), pin!(watchdog)).await {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => Err(anyhow::anyhow!("上传 {} 停滞：{:?} 内没有进展", name, idle_timeout)),
+            };
+            progress.finish();
+            result
+        })?;
+        if !status.is_success() {
+            return Err(api_error(status, &text));
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// 在 upstream 的分叉中提交文件并向 upstream 的默认分支发起 PR；已有同一分支的 PR 时只更新文件，返回 PR 链接
+    pub fn submit_pull_request(&self, upstream: &str, file: &PullRequestFile) -> Result<String> {
+        #[derive(Deserialize)]
+        struct Owner {
+            login: String,
+        }
+        #[derive(Deserialize)]
+        struct Repository {
+            full_name: String,
+            default_branch: String,
+            owner: Owner,
+        }
+        #[derive(Deserialize)]
+        struct GitObject {
+            sha: String,
+        }
+        #[derive(Deserialize)]
+        struct GitRef {
+            object: GitObject,
+        }
+        #[derive(Deserialize)]
+        struct PullRequest {
+            html_url: String,
+        }
+
+        let base: Repository = self.call_json(Method::GET, &format!("/repos/{}", upstream), None)?;
+        let fork: Repository = self.call_json(Method::POST, &format!("/repos/{}/forks", upstream), Some(json!({})))?;
+        let head: GitRef = self.call_json(Method::GET, &format!("/repos/{}/git/ref/heads/{}", upstream, base.default_branch), None)?;
+
+        // 分叉是异步创建的，新分叉可能需要几秒后才能写入
+        let branch_ref = json!({ "ref": format!("refs/heads/{}", file.branch), "sha": head.object.sha });
+        let mut attempt = 0;
+        loop {
+            let (status, text) = self.call(Method::POST, &format!("/repos/{}/git/refs", fork.full_name), Some(branch_ref.clone()))?;
+            match status {
+                // 分支已存在：沿用，更新其中的文件
+                status if status.is_success() || status == StatusCode::UNPROCESSABLE_ENTITY => break,
+                StatusCode::NOT_FOUND | StatusCode::CONFLICT if attempt < 5 => {
+                    attempt += 1;
+                    std::thread::sleep(Duration::from_secs(2 * attempt));
+                }
+                status => return Err(api_error(status, &text)),
+            }
+        }
+
+        let contents_path = format!("/repos/{}/contents/{}", fork.full_name, file.path);
+        let (status, text) = self.call(Method::GET, &format!("{}?ref={}", contents_path, file.branch), None)?;
+        let existing_sha = match status {
+            StatusCode::NOT_FOUND => None,
+            status if status.is_success() => Some(serde_json::from_str::<GitObject>(&text)?.sha),
+            status => return Err(api_error(status, &text)),
+        };
+        let mut update = json!({
+            "message": file.message,
+            "content": base64::engine::general_purpose::STANDARD.encode(&file.content),
+            "branch": file.branch,
+        });
+        if let Some(sha) = existing_sha {
+            update["sha"] = sha.into();
+        }
+        let _: serde_json::Value = self.call_json(Method::PUT, &contents_path, Some(update))?;
+
+        let head = format!("{}:{}", fork.owner.login, file.branch);
+        let open: Vec<PullRequest> = self.call_json(Method::GET, &format!("/repos/{}/pulls?state=open&head={}", upstream, head), None)?;
+        if let Some(pull) = open.into_iter().next() {
+            return Ok(pull.html_url);
+        }
+        let pull: PullRequest = self.call_json(Method::POST, &format!("/repos/{}/pulls", upstream), Some(json!({
+            "title": file.message,
+            "body": file.body,
+            "head": head,
+            "base": base.default_branch,
+        })))?;
+        Ok(pull.html_url)
+    }
+}
+
+/// GitHub API 返回的错误
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    /// 服务器错误或速率限制，稍后重试可能成功
+    pub fn is_transient(&self) -> bool {
+        self.status.is_server_error() || self.status == StatusCode::TOO_MANY_REQUESTS
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            StatusCode::UNAUTHORIZED => write!(f, "GitHub 令牌无效或已过期（{}）: {}", self.status, self.message),
+            StatusCode::FORBIDDEN => write!(f, "GitHub 令牌权限不足或触发了速率限制（{}）: {}", self.status, self.message),
+            _ => write!(f, "GitHub API 返回 {}: {}", self.status, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// 错误响应取 message 字段，没有时截取正文
+fn api_error(status: StatusCode, body: &str) -> anyhow::Error {
+    let message = serde_json::from_str::<serde_json::Value>(body).ok()
+        .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(str::to_string))
+        .unwrap_or_else(|| body.chars().take(200).collect());
+    ApiError { status, message }.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use tempfile::tempdir;
+
+    /// 本地上传端点：读取请求头后按 pause 的节奏慢速读取正文，stall 时不读取也不响应
+    fn upload_server(pause: Duration, stall: bool) -> Release {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            if stall {
+                std::thread::sleep(Duration::from_secs(10));
+                return;
+            }
+            let mut buffer = vec![0; 256 * 1024];
+            let mut received = 0;
+            while received < length {
+                received += reader.read(&mut buffer).unwrap();
+                std::thread::sleep(pause);
+            }
+            let body = format!(r#"{{"id":1,"name":"demo.zip","size":{},"state":"uploaded","browser_download_url":"x"}}"#, received);
+            let mut stream = stream;
+            write!(stream, "HTTP/1.1 201 Created\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+        });
+        Release {
+            id: 1,
+            html_url: String::new(),
+            upload_url: format!("http://127.0.0.1:{}/assets{{?name,label}}", port),
+        }
+    }
+
+    #[test]
+    fn test_upload_slower_than_idle_timeout_succeeds() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("demo.zip");
+        // 大于本机回环连接的缓冲区，保证客户端一直受服务器读取速度限制
+        let size = 32 * 1024 * 1024;
+        fs::write(&path, vec![7u8; size]).unwrap();
+        let idle_timeout = Duration::from_millis(400);
+        let client = GithubClient::new("token").unwrap().with_upload_idle_timeout(idle_timeout);
+
+        // 总耗时远超空闲超时，但一直有进展，不应中止
+        let started = Instant::now();
+        let asset = client.upload_asset(&upload_server(Duration::from_millis(10), false), &path).unwrap();
+        assert_eq!(asset.size, size as u64);
+        assert!(started.elapsed() > idle_timeout * 2, "{:?}", started.elapsed());
+
+        // 服务器不再读取也不响应：空闲超时后中止
+        let started = Instant::now();
+        let error = client.upload_asset(&upload_server(Duration::ZERO, true), &path).unwrap_err();
+        assert!(error.to_string().contains("停滞"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_github_release_helpers() {
+        assert_eq!(release_tag("1.2.0"), "v1.2.0");
+        assert_eq!(release_tag("v1.2.0"), "v1.2.0");
+        assert_eq!(
+            upload_endpoint("https://uploads.github.com/repos/o/r/releases/1/assets{?name,label}"),
+            "https://uploads.github.com/repos/o/r/releases/1/assets"
+        );
+
+        let manifest = r#"{
+  "version": "v1.2.0",
+  "versionCode": 120,
+  "zipUrl": "https://github.com/o/r/releases/latest/download/demo-120.zip",
+  "changelog": "https://github.com/o/r/releases/latest/download/changelog-120.md"
+}"#;
+        let zip_url = "https://github.com/o/r/releases/download/v1.2.0/demo-120.zip";
+        let pinned: serde_json::Value = serde_json::from_str(&pin_update_json(manifest, zip_url, "v1.2.0").unwrap()).unwrap();
+        assert_eq!(pinned["zipUrl"], zip_url);
+        assert_eq!(pinned["changelog"], "https://github.com/o/r/releases/download/v1.2.0/changelog-120.md");
+        assert_eq!(pinned["versionCode"], 120);
+
+        // 外部的更新日志只固定 releases/latest
+        let external = manifest.replace("releases/latest/download/changelog-120.md", "blob/main/CHANGELOG.md");
+        let pinned: serde_json::Value = serde_json::from_str(&pin_update_json(&external, zip_url, "v1.2.0").unwrap()).unwrap();
+        assert_eq!(pinned["changelog"], "https://github.com/o/r/blob/main/CHANGELOG.md");
+        assert!(pin_update_json("[]", zip_url, "v1.2.0").is_err());
+
+        let asset = ReleaseAsset {
+            id: 1,
+            name: "demo-120.zip".to_string(),
+            size: 3,
+            state: "uploaded".to_string(),
+            browser_download_url: zip_url.to_string(),
+            digest: Some("sha256:abc".to_string()),
+        };
+        assert!(verify_asset(&asset, 3, "abc").is_ok());
+        assert!(verify_asset(&asset, 4, "abc").is_err());
+        assert!(verify_asset(&asset, 3, "def").is_err());
+        assert!(verify_asset(&ReleaseAsset { state: "starter".to_string(), ..asset }, 3, "abc").is_err());
+    }
+}
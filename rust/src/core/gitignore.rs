@@ -0,0 +1,147 @@
+use anyhow::Result;
+use git2::Repository;
+use std::fs;
+use std::path::Path;
+
+use super::storage::canonicalize_or_absolute;
+
+/// .rmmp 中不应提交到 Git 的构建输出
+pub const RMMP_IGNORED: &[&str] = &[
+    "build/",
+    "dist/",
+    "source-build/",
+    "cache/",
+    "build-cache.json",
+    "build-state.json",
+    "symbols/",
+    "*.log",
+];
+
+/// 生成 .rmmp/.gitignore 内容
+pub fn rmmp_gitignore_content() -> String {
+    let mut content = String::from("# 由 rmm 生成：构建输出不应提交到 Git\n");
+    for pattern in RMMP_IGNORED {
+        content.push_str(pattern);
+        content.push('\n');
+    }
+    content
+}
+
+/// 确保 .rmmp/.gitignore 存在，已存在时不覆盖用户修改，返回是否新建
+pub fn ensure_rmmp_gitignore(project_path: &Path) -> Result<bool> {
+    let rmmp_dir = project_path.join(".rmmp");
+    let gitignore = rmmp_dir.join(".gitignore");
+    if !rmmp_dir.is_dir() || gitignore.exists() {
+        return Ok(false);
+    }
+    fs::write(&gitignore, rmmp_gitignore_content())?;
+    Ok(true)
+}
+
+/// 确保项目根目录 .gitignore 忽略指定条目，返回是否修改了文件
+pub fn ensure_project_ignores(project_path: &Path, entries: &[&str]) -> Result<bool> {
+    let gitignore = project_path.join(".gitignore");
+    let existing = fs::read_to_string(&gitignore).unwrap_or_default();
+    let missing: Vec<&&str> = entries
+        .iter()
+        .filter(|entry| !existing.lines().any(|line| line.trim() == **entry))
+        .collect();
+    if missing.is_empty() {
+        return Ok(false);
+    }
+
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for entry in missing {
+        content.push_str(entry);
+        content.push('\n');
+    }
+    fs::write(&gitignore, content)?;
+    Ok(true)
+}
+
+/// 判断 .rmmp 内的相对路径是否属于构建输出
+fn is_build_output(rmmp_relative: &str) -> bool {
+    RMMP_IGNORED.iter().any(|pattern| {
+        if let Some(dir) = pattern.strip_suffix('/') {
+            rmmp_relative.starts_with(&format!("{}/", dir))
+        } else if let Some(ext) = pattern.strip_prefix('*') {
+            rmmp_relative.ends_with(ext)
+        } else {
+            rmmp_relative == *pattern
+        }
+    })
+}
+
+/// 打开项目所在的 Git 仓库，返回仓库与项目相对仓库根目录的前缀（以 / 结尾或为空）
+fn open_project_repo(project_path: &Path) -> Option<(Repository, String)> {
+    let repo = Repository::discover(project_path).ok()?;
+    let workdir = canonicalize_or_absolute(repo.workdir()?);
+    let project = canonicalize_or_absolute(project_path);
+    let relative = project.strip_prefix(&workdir).unwrap_or(Path::new(""));
+    let mut prefix = relative.to_string_lossy().replace('\\', "/");
+    if !prefix.is_empty() {
+        prefix.push('/');
+    }
+    Some((repo, prefix))
+}
+
+/// 列出已被 Git 跟踪的构建输出（相对仓库根目录），项目不在 Git 仓库中时返回空列表
+pub fn tracked_build_outputs(project_path: &Path) -> Result<Vec<String>> {
+    let Some((repo, mut prefix)) = open_project_repo(project_path) else {
+        return Ok(Vec::new());
+    };
+    prefix.push_str(".rmmp/");
+
+    let index = repo.index()?;
+    Ok(index
+        .iter()
+        .filter_map(|entry| String::from_utf8(entry.path).ok())
+        .filter(|path| path.strip_prefix(&prefix).is_some_and(is_build_output))
+        .collect())
+}
+
+/// 项目内的文件是否已被 Git 跟踪
+pub fn is_tracked(project_path: &Path, relative: &str) -> Result<bool> {
+    let Some((repo, prefix)) = open_project_repo(project_path) else {
+        return Ok(false);
+    };
+    let index = repo.index()?;
+    Ok(index.get_path(Path::new(&format!("{}{}", prefix, relative)), 0).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_rmmp_gitignore_and_tracked_outputs() {
+        let temp_dir = tempdir().unwrap();
+        let project_path = temp_dir.path().join("module");
+        fs::create_dir_all(project_path.join(".rmmp/dist")).unwrap();
+        fs::write(project_path.join(".rmmp/Rmake.toml"), "[build]\n").unwrap();
+        fs::write(project_path.join(".rmmp/dist/module-1.zip"), "zip").unwrap();
+
+        assert!(ensure_rmmp_gitignore(&project_path).unwrap());
+        let content = fs::read_to_string(project_path.join(".rmmp/.gitignore")).unwrap();
+        assert!(content.contains("dist/") && content.contains("build/"));
+        fs::write(project_path.join(".rmmp/.gitignore"), "custom\n").unwrap();
+        assert!(!ensure_rmmp_gitignore(&project_path).unwrap());
+        assert_eq!(fs::read_to_string(project_path.join(".rmmp/.gitignore")).unwrap(), "custom\n");
+
+        // 不在 Git 仓库中
+        assert!(tracked_build_outputs(&project_path).unwrap().is_empty());
+
+        let repo = git2::Repository::init(temp_dir.path()).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("module/.rmmp/Rmake.toml")).unwrap();
+        index.add_path(std::path::Path::new("module/.rmmp/dist/module-1.zip")).unwrap();
+        index.write().unwrap();
+
+        let tracked = tracked_build_outputs(&project_path).unwrap();
+        assert_eq!(tracked, vec!["module/.rmmp/dist/module-1.zip".to_string()]);
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::storage::{canonicalize_or_absolute, copy_dir_all, home_dir};
+
+/// 项目标记文件：目录中存在它才被视为 RMM 项目
+pub const PROJECT_MARKER: &str = "rmmproject.toml";
+/// 回收站清单文件名
+const TRASH_MANIFEST: &str = "manifest.json";
+
+/// 目录是否带有项目标记
+pub fn is_project_dir(path: &Path) -> bool {
+    path.join(PROJECT_MARKER).is_file()
+}
+
+/// 判断目录是否为不应执行破坏性操作的位置（根目录、盘符根、主目录及其上级），返回原因
+pub fn dangerous_dir_reason(path: &Path) -> Option<&'static str> {
+    let path = canonicalize_or_absolute(path);
+    if path.parent().is_none() {
+        return Some("文件系统根目录");
+    }
+    let home = canonicalize_or_absolute(&home_dir()?);
+    if path == home {
+        Some("用户主目录")
+    } else if home.starts_with(&path) {
+        Some("用户主目录的上级目录")
+    } else {
+        None
+    }
+}
+
+/// 破坏性操作前的启发式检查；force 为 true 时跳过
+pub fn ensure_safe_target(path: &Path, operation: &str, force: bool) -> Result<()> {
+    if force {
+        return Ok(());
+    }
+    if let Some(reason) = dangerous_dir_reason(path) {
+        anyhow::bail!(
+            "拒绝在{}执行 {}: {}（确认无误请使用 --force）",
+            reason, operation, path.display()
+        );
+    }
+    Ok(())
+}
+
+/// 回收站中的一项
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrashEntry {
+    /// 原始位置
+    pub original: PathBuf,
+    /// 回收站内的相对路径
+    pub stored: String,
+}
+
+/// 回收站清单：只保留最近一次破坏性操作
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrashManifest {
+    pub operation: String,
+    pub created_at: String,
+    pub entries: Vec<TrashEntry>,
+}
+
+/// 移动文件或目录，跨设备时回退为复制后删除
+fn move_path(src: &Path, dest: &Path) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(src, dest).is_ok() {
+        return Ok(());
+    }
+    if src.is_dir() {
+        copy_dir_all(src, dest)?;
+        fs::remove_dir_all(src)?;
+    } else {
+        fs::copy(src, dest)?;
+        fs::remove_file(src)?;
+    }
+    Ok(())
+}
+
+/// 读取回收站清单
+pub fn read_trash(trash_dir: &Path) -> Result<Option<TrashManifest>> {
+    let manifest = trash_dir.join(TRASH_MANIFEST);
+    if !manifest.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&manifest)?;
+    Ok(Some(serde_json::from_str(&content).with_context(|| format!("回收站清单损坏: {}", manifest.display()))?))
+}
+
+/// 将路径移入回收站（替换上一次的内容），返回新的清单
+pub fn move_to_trash(trash_dir: &Path, operation: &str, paths: &[PathBuf]) -> Result<TrashManifest> {
+    if trash_dir.exists() {
+        fs::remove_dir_all(trash_dir)?;
+    }
+    fs::create_dir_all(trash_dir)?;
+
+    let mut manifest = TrashManifest {
+        operation: operation.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        entries: Vec::new(),
+    };
+    for (index, path) in paths.iter().enumerate() {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let stored = format!("{}-{}", index, name);
+        let original = canonicalize_or_absolute(path);
+        move_path(path, &trash_dir.join(&stored))
+            .with_context(|| format!("无法移入回收站: {}", path.display()))?;
+        manifest.entries.push(TrashEntry { original, stored });
+        // 每移动一项就写一次清单，中途失败也能恢复已移动的内容
+        fs::write(trash_dir.join(TRASH_MANIFEST), serde_json::to_string_pretty(&manifest)?)?;
+    }
+    Ok(manifest)
+}
+
+/// 撤销最近一次破坏性操作：将回收站内容移回原位置（目标已存在时拒绝覆盖）
+pub fn restore_trash(trash_dir: &Path) -> Result<TrashManifest> {
+    let manifest = read_trash(trash_dir)?.ok_or_else(|| anyhow::anyhow!("回收站为空，没有可撤销的操作"))?;
+    if let Some(entry) = manifest.entries.iter().find(|e| e.original.exists()) {
+        anyhow::bail!("无法撤销: {} 已存在", entry.original.display());
+    }
+    for entry in &manifest.entries {
+        move_path(&trash_dir.join(&entry.stored), &entry.original)?;
+    }
+    fs::remove_dir_all(trash_dir)?;
+    Ok(manifest)
+}
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use walkdir::{DirEntry, WalkDir};
+
+/// 小于该大小的文件直接读取，mmap 的映射开销不划算
+const MMAP_THRESHOLD: u64 = 64 * 1024;
+
+/// 清单与构建指纹使用的哈希算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Blake3,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    /// 计算字节内容的哈希（十六进制小写）
+    pub fn hash_bytes(&self, data: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Blake3 => blake3::hash(data).to_hex().to_string(),
+            HashAlgorithm::Sha256 => sha256_bytes(data),
+        }
+    }
+}
+
+/// 计算文件哈希：大文件使用内存映射，避免逐块复制
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> Result<String> {
+    let file = fs::File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let len = file.metadata()?.len();
+    if len < MMAP_THRESHOLD {
+        let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        return Ok(algorithm.hash_bytes(&data));
+    }
+    // SAFETY: 只读映射；哈希期间文件被其他进程截断属于构建目录被并发修改，结果本就不可信
+    let map = unsafe { memmap2::Mmap::map(&file) }
+        .with_context(|| format!("Failed to map {}", path.display()))?;
+    Ok(algorithm.hash_bytes(&map))
+}
+
+/// 并行计算目录下所有文件的哈希，返回 相对路径（/ 分隔）→ 哈希；enter 返回 false 的目录项被跳过。
+/// 符号链接不跟随，按链接目标计算哈希，与 zip 中符号链接条目的内容一致
+pub fn hash_tree<F>(root: &Path, algorithm: HashAlgorithm, enter: F) -> Result<BTreeMap<String, String>>
+where
+    F: FnMut(&DirEntry) -> bool,
+{
+    let files: Vec<(String, std::path::PathBuf, bool)> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(enter)
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() || e.path_is_symlink())
+        .map(|e| {
+            let relative = e.path().strip_prefix(root)?.to_string_lossy().replace('\\', "/");
+            let symlink = e.path_is_symlink();
+            Ok((relative, e.into_path(), symlink))
+        })
+        .collect::<Result<_>>()?;
+
+    files.into_par_iter()
+        .map(|(relative, path, symlink)| {
+            let hash = if symlink {
+                let target = fs::read_link(&path).with_context(|| format!("Failed to read link {}", path.display()))?;
+                algorithm.hash_bytes(target.to_string_lossy().replace('\\', "/").as_bytes())
+            } else {
+                hash_file(&path, algorithm)?
+            };
+            Ok((relative, hash))
+        })
+        .collect()
+}
+
+/// 计算字节内容的 SHA-256（十六进制小写）
+pub fn sha256_bytes(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+/// 流式计算文件的 SHA-256（十六进制小写）
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let file = fs::File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    sha256_reader(file).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// 流式计算任意读取源的 SHA-256（十六进制小写）
+pub fn sha256_reader<R: Read>(mut reader: R) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// HMAC-SHA256（RFC 2104），用于 S3 请求签名
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; 64];
+    if key.len() > block.len() {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 随机令牌：bytes 个系统随机字节的十六进制
+pub fn random_token(bytes: usize) -> Result<String> {
+    let mut buffer = vec![0u8; bytes];
+    getrandom::getrandom(&mut buffer).map_err(|e| anyhow::anyhow!("无法获取系统随机数: {}", e))?;
+    Ok(to_hex(&buffer))
+}
@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use super::known_issues::KnownIssuesConfig;
+use super::size_history::SizeCheckConfig;
+use super::shell_script::ScriptCheckConfig;
+
+/// Rmake.toml 中的 [check] 配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct CheckConfig {
+    /// 本地化资源校验，如 [check.i18n]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub i18n: Option<I18nConfig>,
+    /// 产物大小增长检查，如 [check.size]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<SizeCheckConfig>,
+    /// shell 脚本识别规则，如 [check.scripts]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scripts: Option<ScriptCheckConfig>,
+    /// 已知问题规则的忽略与级别覆盖，如 [check.known_issues]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub known_issues: Option<KnownIssuesConfig>,
+}
+
+/// [check.i18n] 配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct I18nConfig {
+    /// 语言文件路径模板（相对项目根目录），如 webroot/i18n/{locale}.json
+    pub pattern: String,
+    /// 需要提供的语言列表
+    pub locales: Vec<String>,
+    /// 作为键基准的语言（默认第一个）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+}
+
+/// 本地化校验结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct I18nReport {
+    /// 缺失文件、解析失败等错误
+    pub errors: Vec<String>,
+    /// 与基准语言相比缺失或多余的键
+    pub warnings: Vec<String>,
+}
+
+/// 收集 JSON 中所有叶子值的点分键
+fn collect_json_keys(value: &serde_json::Value, prefix: &str, keys: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                let key = if prefix.is_empty() { k.clone() } else { format!("{}.{}", prefix, k) };
+                collect_json_keys(v, &key, keys);
+            }
+        }
+        _ => {
+            keys.insert(prefix.to_string());
+        }
+    }
+}
+
+/// 简单校验 XML 标签是否配对，并收集 <string name="..."> 等带 name 属性的资源键
+fn parse_xml_keys(content: &str) -> Result<BTreeSet<String>, String> {
+    let name_re = regex::Regex::new(r#"\bname\s*=\s*"([^"]+)""#).unwrap();
+    let mut stack: Vec<String> = Vec::new();
+    let mut keys = BTreeSet::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("<!--") {
+            let end = after.find("-->").ok_or("注释未闭合")?;
+            rest = &after[end + 3..];
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let end = after.find("]]>").ok_or("CDATA 未闭合")?;
+            rest = &after[end + 3..];
+            continue;
+        }
+        let end = rest.find('>').ok_or("标签未闭合")?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            match stack.pop() {
+                Some(open) if open == name => {}
+                Some(open) => return Err(format!("标签 <{}> 与 </{}> 不匹配", open, name)),
+                None => return Err(format!("多余的结束标签 </{}>", name)),
+            }
+            continue;
+        }
+
+        let name = tag.split_whitespace().next().unwrap_or("").trim_end_matches('/').to_string();
+        if let Some(caps) = name_re.captures(tag).filter(|_| stack.len() == 1) {
+            keys.insert(caps[1].to_string());
+        }
+        if !tag.ends_with('/') {
+            stack.push(name);
+        }
+    }
+
+    match stack.pop() {
+        Some(open) => Err(format!("标签 <{}> 未闭合", open)),
+        None => Ok(keys),
+    }
+}
+
+/// 解析语言文件并返回键集合
+fn load_locale_keys(path: &Path) -> Result<BTreeSet<String>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let content = content.trim_start_matches('\u{feff}');
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => {
+            let value: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+            let mut keys = BTreeSet::new();
+            collect_json_keys(&value, "", &mut keys);
+            Ok(keys)
+        }
+        Some("xml") => parse_xml_keys(content),
+        _ => Err("仅支持 .json 和 .xml 语言文件".to_string()),
+    }
+}
+
+/// 按 [check.i18n] 配置校验本地化资源
+pub fn validate_i18n(project_path: &Path, config: &I18nConfig) -> I18nReport {
+    let mut report = I18nReport::default();
+    if !config.pattern.contains("{locale}") {
+        report.errors.push(format!("[check.i18n] pattern '{}' 中缺少 {{locale}} 占位符", config.pattern));
+        return report;
+    }
+
+    let mut loaded = Vec::new();
+    for locale in &config.locales {
+        let relative = config.pattern.replace("{locale}", locale);
+        let path = project_path.join(&relative);
+        if !path.exists() {
+            report.errors.push(format!("缺少语言文件: {}", relative));
+            continue;
+        }
+        match load_locale_keys(&path) {
+            Ok(keys) => loaded.push((locale.clone(), keys)),
+            Err(e) => report.errors.push(format!("{} 解析失败: {}", relative, e)),
+        }
+    }
+
+    let base_locale = config.base.clone().or_else(|| config.locales.first().cloned());
+    let Some(base_locale) = base_locale else {
+        return report;
+    };
+    if !config.locales.contains(&base_locale) {
+        report.errors.push(format!("基准语言 '{}' 不在 locales 列表中", base_locale));
+        return report;
+    }
+    let Some((_, base_keys)) = loaded.iter().find(|(l, _)| *l == base_locale) else {
+        return report;
+    };
+
+    for (locale, keys) in loaded.iter().filter(|(l, _)| *l != base_locale) {
+        let missing: Vec<&String> = base_keys.difference(keys).collect();
+        let extra: Vec<&String> = keys.difference(base_keys).collect();
+        if !missing.is_empty() {
+            report.warnings.push(format!(
+                "{} 缺少 {} 个键（相对 {}）: {}",
+                locale, missing.len(), base_locale, join_keys(&missing)
+            ));
+        }
+        if !extra.is_empty() {
+            report.warnings.push(format!(
+                "{} 有 {} 个多余的键（{} 中不存在）: {}",
+                locale, extra.len(), base_locale, join_keys(&extra)
+            ));
+        }
+    }
+    report
+}
+
+/// 拼接键列表，过长时截断
+fn join_keys(keys: &[&String]) -> String {
+    const LIMIT: usize = 5;
+    let shown: Vec<&str> = keys.iter().take(LIMIT).map(|k| k.as_str()).collect();
+    if keys.len() > LIMIT {
+        format!("{} …", shown.join(", "))
+    } else {
+        shown.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_i18n_validation_reports_missing_keys() {
+        let temp_dir = tempdir().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join("webroot/i18n")).unwrap();
+        fs::write(project_path.join("webroot/i18n/en.json"), r#"{"title": "Hi", "menu": {"open": "Open", "close": "Close"}}"#).unwrap();
+        fs::write(project_path.join("webroot/i18n/zh.json"), r#"{"title": "你好", "menu": {"open": "打开"}, "extra": "x"}"#).unwrap();
+
+        let mut config = I18nConfig {
+            pattern: "webroot/i18n/{locale}.json".to_string(),
+            locales: vec!["en".to_string(), "zh".to_string()],
+            base: None,
+        };
+        let report = validate_i18n(project_path, &config);
+        assert!(report.errors.is_empty());
+        assert_eq!(report.warnings.len(), 2);
+        assert!(report.warnings[0].contains("menu.close"));
+        assert!(report.warnings[1].contains("extra"));
+
+        // 缺失文件与解析失败
+        config.locales.push("ja".to_string());
+        fs::write(project_path.join("webroot/i18n/zh.json"), "{ broken").unwrap();
+        let report = validate_i18n(project_path, &config);
+        assert_eq!(report.errors.len(), 2);
+
+        // XML 资源
+        fs::create_dir_all(project_path.join("res")).unwrap();
+        fs::write(project_path.join("res/en.xml"), r#"<?xml version="1.0"?><resources><string name="a">A</string><string name="b">B</string></resources>"#).unwrap();
+        fs::write(project_path.join("res/fr.xml"), r#"<resources><!-- fr --><string name="a">A</string></resources>"#).unwrap();
+        fs::write(project_path.join("res/de.xml"), r#"<resources><string name="a">A</resources>"#).unwrap();
+        let xml = I18nConfig {
+            pattern: "res/{locale}.xml".to_string(),
+            locales: vec!["en".to_string(), "fr".to_string(), "de".to_string()],
+            base: Some("en".to_string()),
+        };
+        let report = validate_i18n(project_path, &xml);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].contains("de.xml"));
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("fr") && report.warnings[0].contains("b"));
+    }
+}
@@ -0,0 +1,309 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::shell_script::{has_shell_shebang, is_binary};
+
+/// 随 RMM 发布的已知问题数据库
+const BUNDLED_DATABASE: &str = include_str!("known_issues.toml");
+/// 上游最新数据库地址（rmm check --update-db）
+const DATABASE_URL: &str = "https://raw.githubusercontent.com/LIghtJUNction/RootManage-Module-Model/main/rust/src/core/known_issues.toml";
+/// RMM_ROOT/cache 下的缓存文件名
+const CACHE_FILE: &str = "known-issues.toml";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+/// 安装器目录由 RMM 生成，不参与检查
+const INSTALLER_DIR: &str = "META-INF/";
+
+/// 问题级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// 规则匹配对象
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleKind {
+    /// zip 中的文件路径（glob）
+    Path,
+    /// system.prop 或 resetprop 设置的属性，按 key=value 匹配（glob）
+    Prop,
+    /// shell 脚本中的单行（正则）
+    Script,
+}
+
+/// 数据库中的一条规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownIssueRule {
+    pub id: String,
+    pub kind: RuleKind,
+    pub severity: Severity,
+    pub patterns: Vec<String>,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+/// 已知问题数据库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnownIssuesDatabase {
+    /// 数据库版本，缓存版本不低于内置版本时才使用缓存
+    pub version: u32,
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<KnownIssueRule>,
+}
+
+/// Rmake.toml 中的 [check.known_issues] 配置
+///
+/// ```toml
+/// [check.known_issues]
+/// allow = ["RMM-S003"]          # 忽略指定规则
+///
+/// [check.known_issues.severity]
+/// "RMM-P005" = "error"          # 调整规则级别：error / warning / info
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct KnownIssuesConfig {
+    /// 忽略的规则 ID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// 按规则 ID 覆盖级别
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub severity: BTreeMap<String, Severity>,
+}
+
+/// 一次命中
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KnownIssueFinding {
+    pub rule: String,
+    pub severity: Severity,
+    /// zip 内的文件路径
+    pub file: String,
+    /// 脚本或 prop 文件中的行号（从 1 开始）
+    pub line: Option<usize>,
+    /// 命中的内容
+    pub matched: String,
+    pub description: String,
+    pub reference: Option<String>,
+}
+
+impl KnownIssuesDatabase {
+    pub fn parse(content: &str) -> Result<Self> {
+        let database: Self = toml::from_str(content).context("已知问题数据库格式无效")?;
+        // 提前编译全部模式，避免检查时才发现无效规则
+        CompiledRule::compile_all(&database.rules)?;
+        Ok(database)
+    }
+
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED_DATABASE).expect("内置已知问题数据库无效")
+    }
+
+    fn cache_path() -> PathBuf {
+        super::storage::RmmStorage::resolve().cache_dir().join(CACHE_FILE)
+    }
+
+    /// 使用更新过的缓存（版本不低于内置版本），否则使用内置数据库
+    pub fn load() -> Self {
+        let bundled = Self::bundled();
+        fs::read_to_string(Self::cache_path()).ok()
+            .and_then(|content| Self::parse(&content).ok())
+            .filter(|cached| cached.version >= bundled.version)
+            .unwrap_or(bundled)
+    }
+
+    /// 从上游仓库下载最新数据库并写入缓存，返回新数据库
+    pub fn update() -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let content = runtime.block_on(async {
+            let client = reqwest::Client::builder()
+                .timeout(DOWNLOAD_TIMEOUT)
+                .user_agent(concat!("rmm/", env!("CARGO_PKG_VERSION")))
+                .build()?;
+            client.get(DATABASE_URL).send().await?.error_for_status()?.text().await
+        }).context("下载已知问题数据库失败")?;
+        let database = Self::parse(&content)?;
+        let path = Self::cache_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, content)?;
+        Ok(database)
+    }
+
+    /// 检查构建好的模块 zip，按 [check.known_issues] 过滤并调整级别，结果按级别从高到低排列
+    pub fn check_zip(&self, zip_path: &Path, config: Option<&KnownIssuesConfig>) -> Result<Vec<KnownIssueFinding>> {
+        let rules: Vec<CompiledRule> = CompiledRule::compile_all(&self.rules)?
+            .into_iter()
+            .filter(|rule| !config.is_some_and(|c| c.allow.contains(&rule.rule.id)))
+            .collect();
+        let file = fs::File::open(zip_path)
+            .with_context(|| format!("Failed to open {}", zip_path.display()))?;
+        let mut archive = zip::ZipArchive::new(file)?;
+
+        let mut findings = Vec::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let name = entry.name().to_string();
+            if entry.is_dir() || name.starts_with(INSTALLER_DIR) {
+                continue;
+            }
+            for rule in rules.iter().filter(|r| r.rule.kind == RuleKind::Path) {
+                if rule.globs.iter().any(|glob| glob.matches(&name)) {
+                    findings.push(rule.finding(&name, None, &name));
+                }
+            }
+
+            let is_prop_file = name.ends_with(".prop") && name != "module.prop";
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            if is_binary(&content) {
+                continue;
+            }
+            let is_script = name.ends_with(".sh") || has_shell_shebang(&content);
+            if !is_prop_file && !is_script {
+                continue;
+            }
+            for (index, line) in String::from_utf8_lossy(&content).lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let property = if is_prop_file { prop_assignment(line) } else { resetprop_assignment(line) };
+                for rule in &rules {
+                    let matched = match rule.rule.kind {
+                        RuleKind::Prop => property.as_deref().filter(|p| rule.globs.iter().any(|glob| glob.matches(p))),
+                        RuleKind::Script if is_script => rule.regexes.iter().find_map(|re| re.find(line)).map(|m| m.as_str()),
+                        _ => None,
+                    };
+                    if let Some(matched) = matched {
+                        findings.push(rule.finding(&name, Some(index + 1), matched));
+                    }
+                }
+            }
+        }
+
+        if let Some(config) = config {
+            for finding in &mut findings {
+                if let Some(severity) = config.severity.get(&finding.rule) {
+                    finding.severity = *severity;
+                }
+            }
+        }
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.rule.cmp(&b.rule)));
+        Ok(findings)
+    }
+}
+
+/// 编译后的规则
+struct CompiledRule<'a> {
+    rule: &'a KnownIssueRule,
+    globs: Vec<glob::Pattern>,
+    regexes: Vec<Regex>,
+}
+
+impl<'a> CompiledRule<'a> {
+    fn compile_all(rules: &'a [KnownIssueRule]) -> Result<Vec<Self>> {
+        rules.iter().map(|rule| {
+            let invalid = |e: String| anyhow::anyhow!("规则 {} 的模式无效: {}", rule.id, e);
+            let (globs, regexes) = match rule.kind {
+                RuleKind::Script => (Vec::new(), rule.patterns.iter()
+                    .map(|p| Regex::new(p).map_err(|e| invalid(e.to_string())))
+                    .collect::<Result<_>>()?),
+                RuleKind::Path | RuleKind::Prop => (rule.patterns.iter()
+                    .map(|p| glob::Pattern::new(p).map_err(|e| invalid(e.to_string())))
+                    .collect::<Result<_>>()?, Vec::new()),
+            };
+            Ok(Self { rule, globs, regexes })
+        }).collect()
+    }
+
+    fn finding(&self, file: &str, line: Option<usize>, matched: &str) -> KnownIssueFinding {
+        KnownIssueFinding {
+            rule: self.rule.id.clone(),
+            severity: self.rule.severity,
+            file: file.to_string(),
+            line,
+            matched: matched.to_string(),
+            description: self.rule.description.clone(),
+            reference: self.rule.reference.clone(),
+        }
+    }
+}
+
+/// system.prop 中的一行 key=value
+fn prop_assignment(line: &str) -> Option<String> {
+    let (key, value) = line.split_once('=')?;
+    Some(format!("{}={}", key.trim(), value.trim()))
+}
+
+/// 脚本中的 resetprop [选项] key value
+fn resetprop_assignment(line: &str) -> Option<String> {
+    let mut words = line.split_whitespace().skip_while(|word| !word.ends_with("resetprop"));
+    words.next()?;
+    let mut args = words.filter(|word| !word.starts_with('-'));
+    let key = args.next()?;
+    let value = args.next()?.trim_matches(['"', '\'']);
+    Some(format!("{}={}", key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_known_issues_database_flags_built_module() {
+        use std::io::Write;
+
+        let temp = tempdir().unwrap();
+        let zip_path = temp.path().join("demo-1.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, content) in [
+            ("META-INF/com/google/android/update-binary", "#!/sbin/sh\nrm -rf /data\n"),
+            ("module.prop", "id=demo\nro.debuggable=1\n"),
+            ("system.prop", "# comment\nro.debuggable = 1\npersist.demo=1\n"),
+            ("system/vendor/etc/fstab.qcom", "/dev/block/by-name/userdata /data"),
+            ("service.sh", "#!/system/bin/sh\nsetenforce 0\nresetprop -n ro.build.tags test-keys\n"),
+            ("post-fs-data.sh", "# rm -rf /data\nrm -rf \"$MODPATH/tmp\"\n"),
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let database = KnownIssuesDatabase::bundled();
+        let findings = database.check_zip(&zip_path, None).unwrap();
+        let summary: Vec<(&str, &str, Option<usize>)> = findings.iter()
+            .map(|f| (f.rule.as_str(), f.file.as_str(), f.line))
+            .collect();
+        assert_eq!(summary, vec![
+            ("RMM-P001", "system/vendor/etc/fstab.qcom", None),
+            ("RMM-R001", "system.prop", Some(2)),
+            ("RMM-R002", "service.sh", Some(3)),
+            ("RMM-S003", "service.sh", Some(2)),
+        ]);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[2].matched, "ro.build.tags=test-keys");
+
+        let config = KnownIssuesConfig {
+            allow: vec!["RMM-P001".to_string()],
+            severity: [("RMM-S003".to_string(), Severity::Error)].into(),
+        };
+        let findings = database.check_zip(&zip_path, Some(&config)).unwrap();
+        assert_eq!(findings.iter().map(|f| f.rule.as_str()).collect::<Vec<_>>(), vec!["RMM-S003", "RMM-R001", "RMM-R002"]);
+        assert_eq!(findings[0].severity, Severity::Error);
+
+        assert!(KnownIssuesDatabase::parse("version = 1\n[[rule]]\nid = \"X\"\nkind = \"script\"\nseverity = \"info\"\npatterns = [\"(\"]\ndescription = \"\"\n").is_err());
+    }
+}
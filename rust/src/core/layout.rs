@@ -0,0 +1,159 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 项目中由 RMM 管理的目录
+pub const RMMP_DIR: &str = ".rmmp";
+
+/// .rmmp 条目的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// 用户编辑、应提交到 Git 的配置
+    Config,
+    /// 构建输出，rmm clean 删除
+    Output,
+    /// 构建缓存、历史等本地状态
+    State,
+    /// 检查、测试生成的报告
+    Report,
+}
+
+/// .rmmp 中的一个约定条目
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutEntry {
+    pub name: &'static str,
+    pub is_dir: bool,
+    pub kind: EntryKind,
+    /// rmm init 时创建
+    pub created_by_init: bool,
+    pub description: &'static str,
+}
+
+const fn entry(name: &'static str, is_dir: bool, kind: EntryKind, description: &'static str) -> LayoutEntry {
+    LayoutEntry { name, is_dir, kind, created_by_init: false, description }
+}
+
+/// .rmmp 目录约定：RMM 只会在 .rmmp 下创建这些条目，build / clean / init 均以此为准
+pub const RMMP_LAYOUT: &[LayoutEntry] = &[
+    entry("Rmake.toml", false, EntryKind::Config, "构建配置"),
+    entry(".gitignore", false, EntryKind::Config, "忽略构建输出"),
+    LayoutEntry { created_by_init: true, ..entry("build", true, EntryKind::Output, "模块构建目录") },
+    LayoutEntry { created_by_init: true, ..entry("dist", true, EntryKind::Output, "构建产物") },
+    entry("source-build", true, EntryKind::Output, "源代码包构建目录"),
+    entry("scripts", true, EntryKind::Config, "源代码包随附的脚本依赖"),
+    entry("cache", true, EntryKind::State, "项目级缓存"),
+    entry("symbols", true, EntryKind::State, "剥离的调试符号"),
+    entry("build-cache.json", false, EntryKind::State, "增量构建缓存"),
+    entry("build-state.json", false, EntryKind::State, "未完成构建的状态"),
+    entry("history.json", false, EntryKind::State, "产物大小历史"),
+    entry("events.jsonl", false, EntryKind::State, "项目事件日志"),
+    entry("verify.sh", false, EntryKind::State, "rmm test 的临时验证脚本"),
+    entry("check-report.json", false, EntryKind::Report, "合并的检查报告"),
+    entry("shellcheck.json", false, EntryKind::Report, "ShellCheck 报告"),
+    entry("shellcheck.llms.txt", false, EntryKind::Report, "ShellCheck 摘要"),
+    entry("shellcheck-fixes.diff", false, EntryKind::Report, "ShellCheck 修复建议"),
+    entry("verify-report.json", false, EntryKind::Report, "设备验证报告"),
+    entry("test-report", true, EntryKind::Report, "测试报告"),
+];
+
+/// 旧版条目的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyAction {
+    /// 已不再使用，可删除
+    Remove,
+    /// 迁移到新的名称
+    Rename(&'static str),
+}
+
+/// 旧版 RMM 留下的条目
+#[derive(Debug, Clone, Copy)]
+pub struct LegacyEntry {
+    pub name: &'static str,
+    pub action: LegacyAction,
+    pub reason: &'static str,
+}
+
+pub const LEGACY_ENTRIES: &[LegacyEntry] = &[
+    LegacyEntry { name: "tags", action: LegacyAction::Remove, reason: "旧版 clean 引用的标签目录，当前版本不再读写" },
+    LegacyEntry { name: "rmake.toml", action: LegacyAction::Rename("Rmake.toml"), reason: "旧版小写配置文件名，区分大小写的文件系统上不会被读取" },
+];
+
+/// .rmmp 中不符合约定的条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutIssue {
+    Legacy { name: String, action: LegacyAction, reason: &'static str },
+    Unknown { name: String },
+}
+
+impl LayoutIssue {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Legacy { name, .. } | Self::Unknown { name } => name,
+        }
+    }
+}
+
+/// .rmmp 下的约定路径
+pub fn rmmp_path(project_path: &Path, name: &str) -> PathBuf {
+    project_path.join(RMMP_DIR).join(name)
+}
+
+/// 约定中的条目
+pub fn find_entry(name: &str) -> Option<&'static LayoutEntry> {
+    RMMP_LAYOUT.iter().find(|entry| entry.name == name)
+}
+
+/// rmm clean 删除的条目（构建输出）
+pub fn output_entries() -> impl Iterator<Item = &'static LayoutEntry> {
+    RMMP_LAYOUT.iter().filter(|entry| entry.kind == EntryKind::Output)
+}
+
+/// rmm init 创建的目录
+pub fn init_dirs() -> impl Iterator<Item = &'static LayoutEntry> {
+    RMMP_LAYOUT.iter().filter(|entry| entry.created_by_init)
+}
+
+/// 检查 .rmmp 的顶层条目，找出旧版遗留与未知条目（*.log 与 RMM 生成的临时文件视为正常）
+pub fn inspect_rmmp(project_path: &Path) -> Result<Vec<LayoutIssue>> {
+    let rmmp = project_path.join(RMMP_DIR);
+    if !rmmp.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<(String, bool)> = fs::read_dir(&rmmp)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| (entry.file_name().to_string_lossy().to_string(), entry.path().is_dir()))
+        .collect();
+    names.sort();
+
+    // 名称符合约定但类型不符（如 build 是文件）同样视为未知条目
+    let issues = names.into_iter()
+        .filter(|(name, is_dir)| find_entry(name).is_none_or(|entry| entry.is_dir != *is_dir))
+        .filter(|(name, _)| !name.ends_with(".log") && !name.starts_with(".rmm-tmp"))
+        .map(|(name, _)| match LEGACY_ENTRIES.iter().find(|legacy| legacy.name == name) {
+            Some(legacy) => LayoutIssue::Legacy { name, action: legacy.action, reason: legacy.reason },
+            None => LayoutIssue::Unknown { name },
+        })
+        .collect();
+    Ok(issues)
+}
+
+/// 执行旧版条目的迁移；目标已存在时不覆盖，返回迁移后的路径
+pub fn migrate_legacy(project_path: &Path, name: &str, target: &str) -> Result<PathBuf> {
+    let source = rmmp_path(project_path, name);
+    let destination = rmmp_path(project_path, target);
+    // 大小写不敏感的文件系统上 rmake.toml 与 Rmake.toml 是同一个文件：目录中没有确切名称的目标
+    let same_file = name.eq_ignore_ascii_case(target) && destination.exists()
+        && !fs::read_dir(project_path.join(RMMP_DIR))?.filter_map(|e| e.ok()).any(|e| e.file_name() == target);
+    if destination.exists() && !same_file {
+        anyhow::bail!("{}/{} 已存在，请手动合并 {}/{}", RMMP_DIR, target, RMMP_DIR, name);
+    }
+    if same_file {
+        // 经过临时名改名，确保名称大小写生效
+        let temporary = rmmp_path(project_path, &format!(".rmm-tmp-{}", target));
+        fs::rename(&source, &temporary)?;
+        fs::rename(&temporary, &destination)?;
+    } else {
+        fs::rename(&source, &destination)?;
+    }
+    Ok(destination)
+}
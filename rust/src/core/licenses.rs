@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use super::rmm_core::RmakeConfig;
+use super::symbols::is_elf;
+
+/// 打包进模块根目录的第三方许可证清单
+pub const LICENSES_FILE: &str = "LICENSES-THIRD-PARTY";
+/// 没有 ELF 头但同样属于第三方制品的扩展名
+const ARTIFACT_EXTENSIONS: [&str; 3] = ["apk", "jar", "dex"];
+
+/// 第三方组件的许可证信息，[assets.<name>] 与 [third_party.<name>] 共用
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct LicenseInfo {
+    /// SPDX 许可证标识，如 "GPL-2.0-only"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// 项目内的许可证全文路径，全文会写入 LICENSES-THIRD-PARTY
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_file: Option<String>,
+    /// 上游源码地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Rmake.toml 中的 [third_party.<name>]：项目中直接提交的第三方文件
+///
+/// ```toml
+/// [third_party.curl]
+/// paths = ["bin/curl", "lib/libcurl.so"]
+/// version = "8.5.0"
+/// license = "curl"
+/// license_file = "licenses/curl.txt"
+/// source = "https://github.com/curl/curl"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct ThirdPartyConfig {
+    /// 模块内的路径（支持 glob）
+    pub paths: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(flatten)]
+    pub license: LicenseInfo,
+}
+
+/// 清单中的一个组件
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LicenseEntry {
+    pub name: String,
+    pub version: Option<String>,
+    pub license: Option<String>,
+    pub source: Option<String>,
+    pub paths: Vec<String>,
+    /// 许可证全文
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// 从 [assets] 与 [third_party] 收集组件；license_file 不存在时报错
+pub fn collect_licenses(project_path: &Path, rmake: &RmakeConfig) -> Result<Vec<LicenseEntry>> {
+    let assets = rmake.assets.iter()
+        .map(|(name, asset)| (name, asset.version.clone(), &asset.license, vec![asset.dest.clone()], Some(asset.url.clone())));
+    let vendored = rmake.third_party.iter()
+        .map(|(name, item)| (name, item.version.clone(), &item.license, item.paths.clone(), None));
+
+    let mut entries = Vec::new();
+    for (name, version, info, paths, url) in assets.chain(vendored) {
+        let text = match &info.license_file {
+            Some(file) => Some(fs::read_to_string(project_path.join(file))
+                .with_context(|| format!("{} 的许可证文件不存在: {}", name, file))?),
+            None => None,
+        };
+        entries.push(LicenseEntry {
+            name: name.clone(),
+            version,
+            license: info.license.clone(),
+            source: info.source.clone().or(url),
+            paths,
+            text,
+        });
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+/// 生成 LICENSES-THIRD-PARTY 的内容
+pub fn render_licenses(module_id: &str, entries: &[LicenseEntry]) -> String {
+    let rule = "=".repeat(72);
+    let mut out = format!("Third-party software included in {}\n", module_id);
+    for entry in entries {
+        out.push_str(&format!("\n{}\n{}", rule, entry.name));
+        if let Some(version) = &entry.version {
+            out.push_str(&format!(" {}", version));
+        }
+        out.push_str(&format!("\nLicense: {}\n", entry.license.as_deref().unwrap_or("UNKNOWN")));
+        if let Some(source) = &entry.source {
+            out.push_str(&format!("Source:  {}\n", source));
+        }
+        out.push_str(&format!("Files:   {}\n", entry.paths.join(", ")));
+        if let Some(text) = &entry.text {
+            out.push_str(&format!("{}\n{}\n", "-".repeat(72), text.trim_end()));
+        }
+    }
+    out
+}
+
+/// 路径是否被某个组件覆盖
+fn is_declared(relative: &str, rmake: &RmakeConfig) -> bool {
+    let matches = |pattern: &str| {
+        let pattern = pattern.trim_start_matches("./");
+        pattern == relative || glob::Pattern::new(pattern).is_ok_and(|p| p.matches(relative))
+    };
+    rmake.assets.values().any(|asset| matches(&asset.dest))
+        || rmake.third_party.values().flat_map(|item| &item.paths).any(|p| matches(p))
+}
+
+/// 目录中未在 [assets] / [third_party] 中声明的二进制文件（ELF、apk、jar、dex），跳过隐藏目录
+pub fn undeclared_binaries(dir: &Path, rmake: &RmakeConfig) -> Vec<String> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            let extension = e.path().extension().map(|x| x.to_string_lossy().to_lowercase()).unwrap_or_default();
+            ARTIFACT_EXTENSIONS.contains(&extension.as_str()) || is_elf(e.path())
+        })
+        .filter_map(|e| e.path().strip_prefix(dir).ok().map(|p| p.to_string_lossy().replace('\\', "/")))
+        .filter(|relative| !is_declared(relative, rmake))
+        .collect()
+}
+
+/// 许可证相关的检查警告；二进制文件以最近一次构建的 .rmmp/build 为准（未构建时扫描项目目录）
+pub fn license_warnings(project_path: &Path, rmake: &RmakeConfig) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let declared = rmake.assets.iter().map(|(name, a)| (format!("[assets.{}]", name), &a.license))
+        .chain(rmake.third_party.iter().map(|(name, t)| (format!("[third_party.{}]", name), &t.license)));
+    for (section, info) in declared {
+        if info.license.is_none() {
+            warnings.push(format!("{} 未声明 license，分发时无法确认授权", section));
+        }
+        if let Some(file) = &info.license_file
+            && !project_path.join(file).is_file()
+        {
+            warnings.push(format!("{} 的 license_file 不存在: {}", section, file));
+        }
+    }
+    for (name, item) in &rmake.third_party {
+        for pattern in &item.paths {
+            let found = glob::glob(&project_path.join(pattern).to_string_lossy()).is_ok_and(|mut paths| paths.next().is_some());
+            if !found {
+                warnings.push(format!("[third_party.{}] 的路径没有匹配任何文件: {}", name, pattern));
+            }
+        }
+    }
+    let build_dir = project_path.join(".rmmp/build");
+    let scan_dir = if build_dir.is_dir() { build_dir } else { project_path.to_path_buf() };
+    for path in undeclared_binaries(&scan_dir, rmake) {
+        warnings.push(format!("{} 是二进制文件但没有许可证信息，第三方文件请在 [third_party] 中声明", path));
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_third_party_license_inventory() {
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        fs::create_dir_all(project.join("bin")).unwrap();
+        fs::create_dir_all(project.join("licenses")).unwrap();
+        fs::write(project.join("bin/curl"), b"\x7fELF\x02\x01curl").unwrap();
+        fs::write(project.join("bin/jq"), b"\x7fELF\x02\x01jq").unwrap();
+        fs::write(project.join("licenses/curl.txt"), "COPYRIGHT AND PERMISSION NOTICE\n").unwrap();
+        let config: RmakeConfig = toml::from_str(r#"
+[build]
+include = []
+exclude = []
+prebuild = []
+build = []
+postbuild = []
+
+[assets.busybox]
+url = "https://example.com/busybox"
+version = "1.36.1"
+dest = "bin/busybox"
+license = "GPL-2.0-only"
+
+[third_party.curl]
+paths = ["bin/curl*"]
+version = "8.5.0"
+license = "curl"
+license_file = "licenses/curl.txt"
+source = "https://github.com/curl/curl"
+
+[third_party.toybox]
+paths = ["bin/toybox"]
+"#).unwrap();
+        assert_eq!(config.assets["busybox"].license.license.as_deref(), Some("GPL-2.0-only"));
+
+        let entries = collect_licenses(project, &config).unwrap();
+        assert_eq!(entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["busybox", "curl", "toybox"]);
+        assert_eq!(entries[0].source.as_deref(), Some("https://example.com/busybox"));
+        let rendered = render_licenses("demo", &entries);
+        assert!(rendered.starts_with("Third-party software included in demo\n"));
+        assert!(rendered.contains("curl 8.5.0\nLicense: curl\nSource:  https://github.com/curl/curl\nFiles:   bin/curl*\n"));
+        assert!(rendered.contains("COPYRIGHT AND PERMISSION NOTICE"));
+        assert!(rendered.contains("toybox\nLicense: UNKNOWN\n"));
+
+        let warnings = license_warnings(project, &config);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings[0].contains("[third_party.toybox] 未声明 license"));
+        assert!(warnings[1].contains("没有匹配任何文件: bin/toybox"));
+        assert!(warnings[2].starts_with("bin/jq "));
+
+        fs::remove_file(project.join("licenses/curl.txt")).unwrap();
+        assert!(collect_licenses(project, &config).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::download::DownloadManager;
+use super::hashing::sha256_bytes;
+use super::licenses::LicenseInfo;
+use super::remote_cache::RemoteCache;
+use super::toml_doc::write_toml;
+
+/// 项目根目录下的锁文件，应提交到 Git
+pub const LOCK_FILE: &str = "rmm.lock";
+const LOCK_VERSION: u32 = 1;
+
+/// 锁定资源的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceKind {
+    /// 项目模板（git 仓库）
+    Template,
+    /// [assets] 中声明的远程文件（预编译二进制、busybox 等）
+    Asset,
+    /// project.script_dependencies 中被构建命令引用的脚本
+    Script,
+}
+
+/// 一个锁定的远程资源
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LockedResource {
+    pub kind: ResourceKind,
+    pub url: String,
+    /// 版本号或 git 提交
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub sha256: String,
+    pub fetched_at: String,
+}
+
+/// rmm.lock：记录获取过的远程内容，之后的获取必须与记录一致
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LockFile {
+    pub version: u32,
+    #[serde(default, rename = "resource")]
+    pub resources: BTreeMap<String, LockedResource>,
+}
+
+impl Default for LockFile {
+    fn default() -> Self {
+        Self { version: LOCK_VERSION, resources: BTreeMap::new() }
+    }
+}
+
+/// Rmake.toml 中的 [assets.<name>]：构建时下载并放入模块，哈希记录在 rmm.lock
+///
+/// ```toml
+/// [assets.busybox]
+/// url = "https://example.com/busybox-arm64"
+/// version = "1.36.1"
+/// dest = "bin/busybox"
+/// license = "GPL-2.0-only"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct AssetConfig {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// 模块内的目标路径
+    pub dest: String,
+    /// 预期的 sha256（可选，优先于 rmm.lock）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// 许可证信息，写入 LICENSES-THIRD-PARTY
+    #[serde(flatten)]
+    pub license: LicenseInfo,
+}
+
+/// 校验结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockStatus {
+    /// 与记录一致
+    Verified,
+    /// 首次获取或来源变化，已记录
+    Recorded,
+    /// rmm lock update 刷新了记录
+    Updated,
+}
+
+impl LockFile {
+    pub fn path(project_path: &Path) -> PathBuf {
+        project_path.join(LOCK_FILE)
+    }
+
+    /// 读取项目的 rmm.lock，不存在时返回空锁
+    pub fn load(project_path: &Path) -> Result<Self> {
+        let path = Self::path(project_path);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let lock: Self = toml::from_str(&fs::read_to_string(&path)?)
+            .with_context(|| format!("{} 格式无效", path.display()))?;
+        if lock.version > LOCK_VERSION {
+            anyhow::bail!("{} 由更新版本的 rmm 生成 (version {})，请升级 rmm", LOCK_FILE, lock.version);
+        }
+        Ok(lock)
+    }
+
+    pub fn save(&self, project_path: &Path) -> Result<()> {
+        write_toml(&Self::path(project_path), self)
+    }
+
+    /// 校验获取到的内容：来源与版本未变时哈希必须一致；update 时直接刷新记录
+    pub fn verify(&mut self, name: &str, kind: ResourceKind, url: &str, version: Option<&str>, sha256: &str, update: bool) -> Result<LockStatus> {
+        let existing = self.resources.get(name);
+        let same_source = existing.is_some_and(|r| r.url == url && r.version.as_deref() == version);
+        if same_source && existing.is_some_and(|r| r.sha256 == sha256) {
+            return Ok(LockStatus::Verified);
+        }
+        if same_source && !update {
+            anyhow::bail!(
+                "{} 的内容与 {} 中的记录不一致\n  来源: {}\n  记录: {}\n  实际: {}\n确认来源可信后运行 rmm lock update 刷新",
+                name, LOCK_FILE, url, existing.map(|r| r.sha256.as_str()).unwrap_or_default(), sha256
+            );
+        }
+        let status = if existing.is_some() && update { LockStatus::Updated } else { LockStatus::Recorded };
+        self.resources.insert(name.to_string(), LockedResource {
+            kind,
+            url: url.to_string(),
+            version: version.map(str::to_string),
+            sha256: sha256.to_string(),
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+        });
+        Ok(status)
+    }
+
+    /// 已锁定的资源（来源与版本一致时）
+    pub fn locked(&self, name: &str, url: &str, version: Option<&str>) -> Option<&LockedResource> {
+        self.resources.get(name).filter(|r| r.url == url && r.version.as_deref() == version)
+    }
+}
+
+/// 资源在锁文件中的名称
+pub fn asset_key(name: &str) -> String {
+    format!("asset:{}", name)
+}
+
+pub fn template_key(spec: &str) -> String {
+    format!("template:{}", spec)
+}
+
+/// 下载远程文件：与 network.mirrors 中的镜像竞速；expected_sha256 给出时跳过内容不符的来源
+///
+/// 配置了 cache.remote 时先按哈希查找远程缓存，下载后上传供其他机器使用；远程缓存出错只警告
+pub fn download(url: &str, expected_sha256: Option<&str>) -> Result<Vec<u8>> {
+    let remote = RemoteCache::from_config().unwrap_or_else(|e| {
+        eprintln!("⚠️  远程缓存配置无效，已忽略: {}", e);
+        None
+    });
+    if let (Some(remote), Some(expected)) = (&remote, expected_sha256) {
+        match remote.get_object(expected) {
+            Ok(Some(bytes)) => return Ok(bytes),
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠️  无法读取远程缓存: {}", e),
+        }
+    }
+    let bytes = DownloadManager::from_config().fetch(url, expected_sha256)?;
+    if let Some(remote) = remote.filter(|remote| remote.push)
+        && let Err(e) = remote.put_object(&bytes)
+    {
+        eprintln!("⚠️  无法上传到远程缓存: {}", e);
+    }
+    Ok(bytes)
+}
+
+/// 获取 [assets] 中的文件：缓存命中且与锁一致时不再下载，否则下载并校验；返回内容与校验结果
+pub fn fetch_asset(lock: &mut LockFile, name: &str, asset: &AssetConfig, cache_dir: &Path, update: bool) -> Result<(Vec<u8>, LockStatus)> {
+    let key = asset_key(name);
+    let cache_path = cache_dir.join("assets").join(&sha256_bytes(asset.url.as_bytes())[..16]);
+    let locked_hash = asset.sha256.clone()
+        .or_else(|| lock.locked(&key, &asset.url, asset.version.as_deref()).map(|r| r.sha256.clone()));
+
+    let cached = fs::read(&cache_path).ok()
+        .filter(|bytes| !update && locked_hash.as_deref() == Some(sha256_bytes(bytes).as_str()));
+    let bytes = match cached {
+        Some(bytes) => bytes,
+        None => {
+            // rmm lock update 时内容允许变化，只按 Rmake.toml 中声明的哈希筛选来源
+            let expected = if update { asset.sha256.as_deref() } else { locked_hash.as_deref() };
+            let bytes = download(&asset.url, expected)?;
+            if let Some(parent) = cache_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&cache_path, &bytes)?;
+            bytes
+        }
+    };
+
+    let hash = sha256_bytes(&bytes);
+    if let Some(expected) = &asset.sha256
+        && !expected.eq_ignore_ascii_case(&hash)
+    {
+        anyhow::bail!("[assets.{}] sha256 不匹配\n  预期: {}\n  实际: {}", name, expected, hash);
+    }
+    let status = lock.verify(&key, ResourceKind::Asset, &asset.url, asset.version.as_deref(), &hash, update)?;
+    Ok((bytes, status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lockfile_records_and_verifies_hashes() {
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        let mut lock = LockFile::load(project).unwrap();
+        let url = "https://example.com/busybox";
+        assert_eq!(lock.verify("asset:busybox", ResourceKind::Asset, url, Some("1.36"), "aaa", false).unwrap(), LockStatus::Recorded);
+        assert_eq!(lock.verify("asset:busybox", ResourceKind::Asset, url, Some("1.36"), "aaa", false).unwrap(), LockStatus::Verified);
+        let err = lock.verify("asset:busybox", ResourceKind::Asset, url, Some("1.36"), "bbb", false).unwrap_err();
+        assert!(err.to_string().contains("rmm lock update"));
+        // 版本变化视为新来源，update 时刷新记录
+        assert_eq!(lock.verify("asset:busybox", ResourceKind::Asset, url, Some("1.37"), "bbb", false).unwrap(), LockStatus::Recorded);
+        assert_eq!(lock.verify("asset:busybox", ResourceKind::Asset, url, Some("1.37"), "ccc", true).unwrap(), LockStatus::Updated);
+
+        lock.save(project).unwrap();
+        let loaded = LockFile::load(project).unwrap();
+        assert_eq!(loaded, lock);
+        assert!(fs::read_to_string(project.join("rmm.lock")).unwrap().contains("[resource.\"asset:busybox\"]"));
+
+        // 缓存内容与记录一致时不需要下载
+        let cache = project.join("cache");
+        let asset = AssetConfig { url: "https://invalid.example/tool".to_string(), version: None, dest: "bin/tool".to_string(), sha256: None, license: Default::default() };
+        let bytes = b"tool-binary".to_vec();
+        let cache_file = cache.join("assets").join(&sha256_bytes(asset.url.as_bytes())[..16]);
+        fs::create_dir_all(cache_file.parent().unwrap()).unwrap();
+        fs::write(&cache_file, &bytes).unwrap();
+        let mut lock = LockFile::default();
+        lock.verify("asset:tool", ResourceKind::Asset, &asset.url, None, &sha256_bytes(&bytes), false).unwrap();
+        let (fetched, status) = fetch_asset(&mut lock, "tool", &asset, &cache, false).unwrap();
+        assert_eq!((fetched, status), (bytes, LockStatus::Verified));
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::adb::shell_quote;
+
+/// 管理器安装模块时执行的脚本
+pub const CUSTOMIZE_SCRIPT: &str = "customize.sh";
+/// 迁移脚本在模块中的目录
+pub const MIGRATIONS_DIR: &str = "migrations";
+
+/// rmmproject.toml 中的 [[migrations]]：覆盖安装旧版本时由 customize.sh 执行的数据迁移脚本
+///
+/// ```toml
+/// [[migrations]]
+/// script = "migrations/migrate-from-1.x.sh"
+/// below = 200        # 设备上已安装的 versionCode 小于 200 时执行
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct MigrationConfig {
+    /// 项目中的脚本路径，需位于 migrations/ 下
+    pub script: String,
+    /// 已安装的 versionCode 小于该值时执行
+    pub below: u64,
+    /// 已安装的 versionCode 不小于该值时才执行（可选）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct MigrationsOnly {
+    #[serde(default)]
+    migrations: Vec<MigrationConfig>,
+}
+
+/// 读取 rmmproject.toml 中的 [[migrations]]，按 below 升序（旧版本的迁移先执行）
+pub fn load_migrations(project_path: &Path) -> Result<Vec<MigrationConfig>> {
+    let Ok(content) = fs::read_to_string(project_path.join("rmmproject.toml")) else {
+        return Ok(Vec::new());
+    };
+    let mut migrations = toml::from_str::<MigrationsOnly>(&content)
+        .context("rmmproject.toml [[migrations]] 格式无效")?
+        .migrations;
+    migrations.sort_by_key(|m| m.below);
+    Ok(migrations)
+}
+
+/// 检查迁移声明，返回错误信息
+pub fn validate_migrations(project_path: &Path, migrations: &[MigrationConfig]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for migration in migrations {
+        let script = migration.script.trim_start_matches("./");
+        if !script.starts_with(&format!("{}/", MIGRATIONS_DIR)) || script.split('/').any(|part| part == "..") {
+            errors.push(format!("迁移脚本必须位于 {}/ 下: {}", MIGRATIONS_DIR, migration.script));
+        } else if !project_path.join(script).is_file() {
+            errors.push(format!("迁移脚本不存在: {}", migration.script));
+        }
+        if migration.since.is_some_and(|since| since >= migration.below) {
+            errors.push(format!("{} 的 since 必须小于 below", migration.script));
+        }
+    }
+    errors
+}
+
+/// 生成 customize.sh 中的迁移片段：读取设备上已安装模块的 versionCode，按范围执行迁移脚本
+pub fn generate_migration_block(migrations: &[MigrationConfig]) -> String {
+    let mut block = String::from(r#"
+# 由 rmm 根据 rmmproject.toml [[migrations]] 生成
+RMM_PREV_PROP="/data/adb/modules/$MODID/module.prop"
+RMM_PREV_VERSION_CODE=""
+RMM_PREV_VERSION=""
+if [ -f "$RMM_PREV_PROP" ]; then
+    RMM_PREV_VERSION_CODE=$(grep -m1 '^versionCode=' "$RMM_PREV_PROP" | cut -d= -f2 | tr -dc '0-9')
+    RMM_PREV_VERSION=$(grep -m1 '^version=' "$RMM_PREV_PROP" | cut -d= -f2-)
+fi
+export RMM_PREV_VERSION_CODE RMM_PREV_VERSION
+if [ -n "$RMM_PREV_VERSION_CODE" ]; then
+    ui_print "- 已安装版本: $RMM_PREV_VERSION ($RMM_PREV_VERSION_CODE)"
+"#);
+    for migration in migrations {
+        let script = migration.script.trim_start_matches("./");
+        let condition = match migration.since {
+            Some(since) => format!("[ \"$RMM_PREV_VERSION_CODE\" -ge {} ] && [ \"$RMM_PREV_VERSION_CODE\" -lt {} ]", since, migration.below),
+            None => format!("[ \"$RMM_PREV_VERSION_CODE\" -lt {} ]", migration.below),
+        };
+        block.push_str(&format!(
+            "    if {condition}; then\n        ui_print \"- 数据迁移: {script}\"\n        ( . \"$MODPATH\"/{quoted} ) || abort \"! 数据迁移失败: {script}\"\n    fi\n",
+            quoted = shell_quote(script),
+        ));
+    }
+    block.push_str("fi\n");
+    block
+}
+
+/// 将迁移片段追加到 customize.sh（没有时创建）
+pub fn append_to_customize(existing: Option<&str>, migrations: &[MigrationConfig]) -> String {
+    let base = match existing {
+        Some(existing) => format!("{}\n", existing.trim_end()),
+        None => String::from("#!/system/bin/sh\n"),
+    };
+    format!("{}{}", base, generate_migration_block(migrations))
+}
+
+/// rmm migration new 生成的迁移脚本模板
+pub fn migration_boilerplate(below: u64) -> String {
+    format!(r#"#!/system/bin/sh
+# 覆盖安装 versionCode < {below} 的旧版本时由 customize.sh 执行（在子 shell 中 source）
+# 可用变量：
+#   $MODPATH                 新版本的安装目录
+#   $RMM_PREV_VERSION_CODE   设备上已安装的 versionCode
+#   $RMM_PREV_VERSION        设备上已安装的 version
+# 以非 0 状态退出会中止安装
+
+OLD_MODDIR="/data/adb/modules/$MODID"
+
+ui_print "  从 $RMM_PREV_VERSION ($RMM_PREV_VERSION_CODE) 迁移数据"
+
+# 示例：迁移旧版本的配置文件
+# if [ -f "$OLD_MODDIR/config.conf" ]; then
+#     cp -f "$OLD_MODDIR/config.conf" "$MODPATH/config.conf"
+# fi
+"#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migration_scripts() {
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        fs::create_dir_all(project.join("migrations")).unwrap();
+        fs::write(project.join("migrations/migrate-from-1.x.sh"), migration_boilerplate(200)).unwrap();
+        fs::write(project.join("rmmproject.toml"), r#"
+[project]
+id = "demo"
+
+[[migrations]]
+script = "migrations/migrate-from-2.x.sh"
+below = 300
+since = 200
+
+[[migrations]]
+script = "migrations/migrate-from-1.x.sh"
+below = 200
+
+[[migrations]]
+script = "scripts/fix.sh"
+below = 100
+since = 100
+"#).unwrap();
+
+        let migrations = load_migrations(project).unwrap();
+        assert_eq!(migrations.iter().map(|m| m.below).collect::<Vec<_>>(), vec![100, 200, 300]);
+        let errors = validate_migrations(project, &migrations);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].contains("必须位于 migrations/ 下"));
+        assert!(errors[1].contains("since 必须小于 below"));
+        assert!(errors[2].contains("迁移脚本不存在: migrations/migrate-from-2.x.sh"));
+
+        let customize = append_to_customize(Some("#!/system/bin/sh\nui_print hello\n\n"), &migrations[1..]);
+        assert!(customize.starts_with("#!/system/bin/sh\nui_print hello\n\n# 由 rmm"));
+        assert!(customize.contains("RMM_PREV_VERSION_CODE=$(grep -m1 '^versionCode='"));
+        assert!(customize.contains("if [ \"$RMM_PREV_VERSION_CODE\" -lt 200 ]; then"));
+        assert!(customize.contains("-ge 200 ] && [ \"$RMM_PREV_VERSION_CODE\" -lt 300 ]"));
+        assert!(customize.contains("( . \"$MODPATH\"/'migrations/migrate-from-1.x.sh' ) || abort"));
+        assert!(append_to_customize(None, &migrations).starts_with("#!/system/bin/sh\n\n# 由 rmm"));
+    }
+}
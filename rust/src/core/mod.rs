@@ -1,5 +1,65 @@
 pub mod rmm_core;
 pub mod python_bindings;
+pub mod errors;
+pub mod storage;
+pub mod update_json;
+pub mod hashing;
+pub mod adb;
+pub mod zip_inspect;
+pub mod gitignore;
+pub mod action;
+pub mod env_file;
+pub mod i18n;
+pub mod size_history;
+pub mod shell_script;
+pub mod template;
+pub mod events;
+pub mod exclude_presets;
+pub mod verify;
+pub mod process;
+pub mod notify;
+pub mod symbols;
+pub mod generate;
+pub mod guard;
+pub mod release_notes;
+pub mod sandbox;
+pub mod deps;
+pub mod settings;
+pub mod shellcheck_wiki;
+pub mod provenance;
+pub mod workspace;
+pub mod recovery;
+pub mod privacy;
+pub mod toml_doc;
+pub mod known_issues;
+pub mod uninstall;
+pub mod layout;
+pub mod control_files;
+pub mod lockfile;
+pub mod rmm_version;
+pub mod licenses;
+pub mod migrations;
+pub mod check_report;
+pub mod bootloop;
+pub mod checksums;
+pub mod script_deps;
+pub mod upstream;
+pub mod encoding;
+pub mod mount_strategy;
+pub mod webhooks;
+pub mod vcs;
+pub mod signing;
+pub mod project_id;
+pub mod config_refs;
+pub mod download;
+pub mod console;
+pub mod publish_pipeline;
+pub mod remote_cache;
+pub mod preflight;
+pub mod module_props;
+pub mod github;
+#[cfg(unix)]
+pub mod fake_device;
 
 #[cfg(test)]
 mod rmm_core_tests;
@@ -0,0 +1,249 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::adb::shell_quote;
+use super::bootloop::insert_after_shebang;
+use super::zip_inspect::parse_module_prop;
+
+/// 管理器在 post-fs-data 阶段加载的属性文件
+pub const SYSTEM_PROP: &str = "system.prop";
+
+/// 非 ro. 属性值的最大长度（PROP_VALUE_MAX - 1）
+pub const PROP_VALUE_MAX: usize = 91;
+
+/// 修改后可能导致 Play Integrity / 设备认证失败的属性；* 开头表示匹配后缀
+const INTEGRITY_PROPS: &[(&str, &str)] = &[
+    ("ro.debuggable", "可调试构建会被识别为非生产设备"),
+    ("ro.secure", "关闭 secure 会被识别为非生产设备"),
+    ("ro.adb.secure", "ADB 免授权会被识别为调试环境"),
+    ("service.adb.root", "adbd 以 root 运行会被识别为调试环境"),
+    ("*.build.type", "userdebug / eng 构建无法通过认证"),
+    ("*.build.tags", "test-keys 构建无法通过认证"),
+    ("*.build.fingerprint", "指纹与设备认证信息不一致时认证失败"),
+    ("ro.boot.verifiedbootstate", "与 bootloader 上报的启动状态不一致"),
+    ("ro.boot.flash.locked", "与 bootloader 上报的锁定状态不一致"),
+    ("ro.boot.vbmeta.device_state", "与 bootloader 上报的锁定状态不一致"),
+    ("ro.boot.veritymode", "dm-verity 状态与认证信息不一致"),
+    ("ro.boot.warranty_bit", "与 bootloader 上报的保修位不一致"),
+    ("ro.warranty_bit", "与 bootloader 上报的保修位不一致"),
+    ("ro.build.selinux", "SELinux 状态与认证信息不一致"),
+];
+
+/// 属性的写入方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+pub enum PropMethod {
+    /// 写入模块的 system.prop，由管理器在 post-fs-data 阶段加载
+    #[default]
+    #[serde(rename = "system.prop")]
+    SystemProp,
+    /// 在 post-fs-data.sh 中调用 resetprop，可删除属性
+    #[serde(rename = "resetprop")]
+    Resetprop,
+}
+
+/// [module.props] 中的一项：直接写值，或用表指定写入方式与删除
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PropValue {
+    Text(String),
+    Integer(i64),
+    Bool(bool),
+    Detailed(PropSpec),
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PropSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// 覆盖 [module] props_method
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<PropMethod>,
+    /// 删除属性（只能通过 resetprop）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub delete: bool,
+}
+
+/// Rmake.toml 中的 [module] 配置：声明模块在运行时设置的系统属性
+///
+/// ```toml
+/// [module]
+/// props_method = "system.prop"   # system.prop / resetprop
+///
+/// [module.props]
+/// "persist.sys.usb.config" = "mtp,adb"
+/// "ro.config.vc_call_vol_steps" = 15
+/// "debug.hwui.renderer" = { value = "skiavk", method = "resetprop" }
+/// "ro.boot.hwc" = { delete = true }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct ModuleConfig {
+    /// 属性的默认写入方式
+    #[serde(default)]
+    pub props_method: PropMethod,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub props: BTreeMap<String, PropValue>,
+}
+
+/// 解析后的一个属性；value 为 None 表示删除
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleProp {
+    pub name: String,
+    pub value: Option<String>,
+    pub method: PropMethod,
+}
+
+impl ModuleConfig {
+    /// 按声明解析所有属性（不校验）
+    pub fn resolve_props(&self) -> Vec<ModuleProp> {
+        self.props.iter().map(|(name, value)| {
+            let (value, method) = match value {
+                PropValue::Text(text) => (Some(text.clone()), self.props_method),
+                PropValue::Integer(number) => (Some(number.to_string()), self.props_method),
+                PropValue::Bool(flag) => (Some(flag.to_string()), self.props_method),
+                PropValue::Detailed(spec) if spec.delete => (None, PropMethod::Resetprop),
+                PropValue::Detailed(spec) => (spec.value.clone(), spec.method.unwrap_or(self.props_method)),
+            };
+            ModuleProp { name: name.clone(), value, method }
+        }).collect()
+    }
+}
+
+/// 属性名是否合法：以 . 分隔的非空段，每段只含字母、数字与 _ - @ :
+pub fn is_valid_prop_name(name: &str) -> bool {
+    !name.is_empty() && name.split('.').all(|segment| {
+        !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '@' | ':'))
+    })
+}
+
+/// 校验 [module.props]，返回错误信息
+pub fn validate_props(config: &ModuleConfig) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (name, value) in &config.props {
+        if !is_valid_prop_name(name) {
+            errors.push(format!("[module.props] 属性名 '{}' 无效：只能包含字母、数字、_ - @ : 与分隔的 .", name));
+        }
+        if let PropValue::Detailed(spec) = value {
+            if spec.delete && spec.value.is_some() {
+                errors.push(format!("[module.props] {} 同时设置了 value 与 delete", name));
+            } else if !spec.delete && spec.value.is_none() {
+                errors.push(format!("[module.props] {} 缺少 value", name));
+            }
+            if spec.delete && spec.method == Some(PropMethod::SystemProp) {
+                errors.push(format!("[module.props] {} 的 delete 只能通过 resetprop 实现", name));
+            }
+        }
+    }
+    for prop in config.resolve_props() {
+        let Some(value) = &prop.value else { continue };
+        if value.contains(['\n', '\r']) {
+            errors.push(format!("[module.props] {} 的值不能包含换行", prop.name));
+        } else if !prop.name.starts_with("ro.") && value.len() > PROP_VALUE_MAX {
+            errors.push(format!("[module.props] {} 的值长度为 {}，非 ro. 属性最多 {} 字节", prop.name, value.len(), PROP_VALUE_MAX));
+        }
+    }
+    errors
+}
+
+/// 修改会影响设备认证的属性及原因
+pub fn integrity_risk(name: &str) -> Option<&'static str> {
+    INTEGRITY_PROPS.iter()
+        .find(|(pattern, _)| match pattern.strip_prefix('*') {
+            Some(suffix) => name.ends_with(suffix),
+            None => name == *pattern,
+        })
+        .map(|(_, reason)| *reason)
+}
+
+/// 对会影响设备认证的属性给出警告
+pub fn integrity_warnings<'a>(names: impl IntoIterator<Item = &'a str>, source: &str) -> Vec<String> {
+    names.into_iter()
+        .filter_map(|name| integrity_risk(name).map(|reason| format!("{} 修改了 {}：{}，可能导致 Play Integrity / 设备认证失败", source, name, reason)))
+        .collect()
+}
+
+/// 在项目的 system.prop 后追加 system.prop 方式的属性；同名属性已在 system.prop 中定义时报错
+pub fn render_system_prop(existing: Option<&str>, props: &[ModuleProp]) -> Result<String> {
+    let defined = existing.map(parse_module_prop).unwrap_or_default();
+    let mut content = existing.unwrap_or_default().to_string();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("# 由 rmm 根据 Rmake.toml [module.props] 生成\n");
+    for prop in props {
+        let Some(value) = &prop.value else { continue };
+        if defined.contains_key(&prop.name) {
+            anyhow::bail!("属性 {} 同时在 {} 与 [module.props] 中定义，请只保留一处", prop.name, SYSTEM_PROP);
+        }
+        content.push_str(&format!("{}={}\n", prop.name, value));
+    }
+    Ok(content)
+}
+
+/// post-fs-data.sh：用 resetprop 设置或删除属性（-n 与 system.prop 一致，不触发 init 的属性触发器）
+pub fn resetprop_post_fs_data(props: &[ModuleProp], existing: Option<&str>) -> String {
+    let commands = props.iter().map(|prop| match &prop.value {
+        Some(value) => format!("    resetprop -n {} {}", shell_quote(&prop.name), shell_quote(value)),
+        None => format!("    resetprop -d {}", shell_quote(&prop.name)),
+    }).collect::<Vec<_>>().join("\n");
+    let snippet = format!(r#"# 由 rmm 根据 Rmake.toml [module.props] 生成
+if command -v resetprop >/dev/null 2>&1; then
+{commands}
+fi
+
+"#);
+    insert_after_shebang(existing, &snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_props() {
+        let config: ModuleConfig = toml::from_str(r#"
+props_method = "system.prop"
+
+[props]
+"persist.sys.usb.config" = "mtp,adb"
+"ro.config.vc_call_vol_steps" = 15
+"debug.hwui.renderer" = { value = "skiavk", method = "resetprop" }
+"ro.boot.hwc" = { delete = true }
+"#).unwrap();
+        assert!(validate_props(&config).is_empty(), "{:?}", validate_props(&config));
+        let props = config.resolve_props();
+        assert_eq!(props.len(), 4);
+        assert!(props.contains(&ModuleProp { name: "ro.config.vc_call_vol_steps".to_string(), value: Some("15".to_string()), method: PropMethod::SystemProp }));
+        assert!(props.contains(&ModuleProp { name: "ro.boot.hwc".to_string(), value: None, method: PropMethod::Resetprop }));
+
+        assert!(is_valid_prop_name("vendor.audio@2.0:offload"));
+        for name in ["", ".ro.foo", "ro..foo", "ro.foo.", "ro.foo bar", "ro.foo=1"] {
+            assert!(!is_valid_prop_name(name), "{}", name);
+        }
+        let invalid: ModuleConfig = toml::from_str(r#"
+[props]
+"bad name" = "1"
+"persist.long" = "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"
+"ro.empty" = { method = "resetprop" }
+"ro.delete" = { delete = true, method = "system.prop" }
+"#).unwrap();
+        assert_eq!(validate_props(&invalid).len(), 4, "{:?}", validate_props(&invalid));
+
+        assert!(integrity_risk("ro.debuggable").is_some());
+        assert!(integrity_risk("ro.vendor.build.fingerprint").is_some());
+        assert!(integrity_risk("persist.sys.usb.config").is_none());
+
+        let (system, reset): (Vec<_>, Vec<_>) = props.into_iter().partition(|p| p.method == PropMethod::SystemProp);
+        let content = render_system_prop(Some("ro.existing=1"), &system).unwrap();
+        assert!(content.starts_with("ro.existing=1\n# "));
+        assert!(content.contains("persist.sys.usb.config=mtp,adb\n"));
+        assert!(render_system_prop(Some("persist.sys.usb.config=none\n"), &system).is_err());
+
+        let script = resetprop_post_fs_data(&reset, Some("#!/system/bin/sh\necho hi\n"));
+        assert!(script.starts_with("#!/system/bin/sh\n# "));
+        assert!(script.contains("resetprop -n 'debug.hwui.renderer' 'skiavk'"));
+        assert!(script.contains("resetprop -d 'ro.boot.hwc'"));
+        assert!(script.ends_with("echo hi\n"));
+    }
+}
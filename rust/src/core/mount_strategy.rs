@@ -0,0 +1,174 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use walkdir::WalkDir;
+
+use super::adb::shell_quote;
+use super::bootloop::insert_after_shebang;
+use super::workspace::PARTITIONS;
+
+/// 模块文件的挂载方式，对应不同 Root 方案的实现
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MountStrategy {
+    /// Magisk 的 magic mount：只挂载 system/，vendor 等分区需放在 system/vendor/ 下
+    Magic,
+    /// KernelSU / APatch 的 OverlayFS：可直接使用 system/、vendor/、product/ 等分区目录
+    Overlayfs,
+    /// 不依赖管理器挂载：写入 skip_mount，由 post-fs-data.sh 逐个 bind mount 设备上已存在的文件
+    Bind,
+}
+
+/// 可选的挂载方式
+pub const MOUNT_STRATEGIES: [&str; 3] = ["magic", "overlayfs", "bind"];
+
+impl MountStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MountStrategy::Magic => "magic",
+            MountStrategy::Overlayfs => "overlayfs",
+            MountStrategy::Bind => "bind",
+        }
+    }
+
+    /// 模块根目录下会生效的分区目录
+    pub fn partitions(&self) -> &'static [&'static str] {
+        match self {
+            MountStrategy::Magic => &["system"],
+            MountStrategy::Overlayfs | MountStrategy::Bind => PARTITIONS,
+        }
+    }
+}
+
+impl FromStr for MountStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "magic" => Ok(MountStrategy::Magic),
+            "overlayfs" => Ok(MountStrategy::Overlayfs),
+            "bind" => Ok(MountStrategy::Bind),
+            _ => anyhow::bail!("未知的挂载方式: '{}'，可选: {}", s, MOUNT_STRATEGIES.join(", ")),
+        }
+    }
+}
+
+/// 分区目录下的条目（相对模块根目录，使用 / 分隔），不跟随符号链接
+fn partition_entries<'a>(dir: &'a Path, partitions: &'a [&'a str]) -> impl Iterator<Item = (String, walkdir::DirEntry)> + 'a {
+    partitions.iter()
+        .flat_map(move |partition| WalkDir::new(dir.join(partition)).min_depth(1).sort_by_file_name().into_iter().filter_map(|e| e.ok()))
+        .map(move |entry| {
+            let relative = entry.path().strip_prefix(dir).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+            (relative, entry)
+        })
+}
+
+/// bind 方式下需要挂载的文件：分区目录中的普通文件
+pub fn bind_targets(build_dir: &Path) -> Vec<String> {
+    partition_entries(build_dir, PARTITIONS)
+        .filter(|(_, entry)| entry.file_type().is_file() && entry.file_name() != ".replace")
+        .map(|(relative, _)| relative)
+        .collect()
+}
+
+/// post-fs-data.sh：逐个 bind mount 模块中的文件（设备上不存在的目标会跳过）
+pub fn bind_post_fs_data(files: &[String], existing: Option<&str>) -> String {
+    let list = files.iter().map(|file| shell_quote(file)).collect::<Vec<_>>().join(" \\\n    ");
+    let snippet = format!(r#"# 由 rmm 根据 Rmake.toml [control] mount = "bind" 生成：bind mount 只能替换已存在的文件
+RMM_MOUNT_DIR=${{0%/*}}
+for RMM_MOUNT_FILE in \
+    {list}
+do
+    [ -f "/$RMM_MOUNT_FILE" ] && mount -o bind "$RMM_MOUNT_DIR/$RMM_MOUNT_FILE" "/$RMM_MOUNT_FILE"
+done
+unset RMM_MOUNT_DIR RMM_MOUNT_FILE
+
+"#);
+    insert_after_shebang(existing, &snippet)
+}
+
+/// 在选定挂载方式下不会生效的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountIssue {
+    /// 相对项目根目录的路径
+    pub file: String,
+    pub message: String,
+}
+
+/// 检查项目中在该挂载方式下不会生效的文件
+pub fn mount_issues(project_path: &Path, strategy: MountStrategy) -> Vec<MountIssue> {
+    let mut issues = Vec::new();
+    for partition in PARTITIONS.iter().filter(|p| !strategy.partitions().contains(p)) {
+        if project_path.join(partition).is_dir() {
+            issues.push(MountIssue {
+                file: format!("{}/", partition),
+                message: format!("{} 方式只挂载 system/，{}/ 不会生效，请移到 system/{}/", strategy.as_str(), partition, partition),
+            });
+        }
+    }
+    if strategy != MountStrategy::Bind {
+        return issues;
+    }
+    for (relative, entry) in partition_entries(project_path, PARTITIONS) {
+        let file_type = entry.file_type();
+        let message = if entry.file_name() == ".replace" {
+            "bind mount 无法替换整个目录，.replace 不会生效"
+        } else if file_type.is_symlink() {
+            "bind mount 不能挂载符号链接"
+        } else if !file_type.is_file() && !file_type.is_dir() {
+            "bind mount 无法删除文件，whiteout 节点不会生效"
+        } else {
+            continue;
+        };
+        issues.push(MountIssue { file: relative, message: message.to_string() });
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_mount_strategy_rules() {
+        use crate::core::control_files::{write_control_files, ControlConfig};
+
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        fs::create_dir_all(project.join("system/etc")).unwrap();
+        fs::create_dir_all(project.join("system/app/Bloat")).unwrap();
+        fs::create_dir_all(project.join("vendor/etc")).unwrap();
+        fs::write(project.join("system/etc/hosts"), "127.0.0.1 localhost\n").unwrap();
+        fs::write(project.join("system/app/Bloat/.replace"), "").unwrap();
+        fs::write(project.join("vendor/etc/audio.conf"), "x\n").unwrap();
+
+        assert!("unionfs".parse::<MountStrategy>().is_err());
+        assert_eq!("bind".parse::<MountStrategy>().unwrap(), MountStrategy::Bind);
+
+        let magic = mount_issues(project, MountStrategy::Magic);
+        assert_eq!(magic.len(), 1);
+        assert_eq!(magic[0].file, "vendor/");
+        assert!(magic[0].message.contains("system/vendor/"));
+        assert!(mount_issues(project, MountStrategy::Overlayfs).is_empty());
+        let bind = mount_issues(project, MountStrategy::Bind);
+        assert_eq!(bind.iter().map(|i| i.file.as_str()).collect::<Vec<_>>(), vec!["system/app/Bloat/.replace"]);
+
+        let targets = bind_targets(project);
+        assert_eq!(targets, vec!["system/etc/hosts", "vendor/etc/audio.conf"]);
+        let script = bind_post_fs_data(&targets, Some("#!/system/bin/sh\necho user\n"));
+        assert!(script.starts_with("#!/system/bin/sh\n# 由 rmm"));
+        assert!(script.contains("'system/etc/hosts' \\\n    'vendor/etc/audio.conf'\ndo"));
+        assert!(script.ends_with("echo user\n"));
+        if let Ok(output) = std::process::Command::new("sh").arg("-n").arg("-c").arg(&script).output() {
+            assert!(output.status.success());
+        }
+
+        // bind 方式总是写入 skip_mount，避免管理器再挂载一次
+        let config = ControlConfig { mount: Some(MountStrategy::Bind), ..Default::default() };
+        assert_eq!(write_control_files(project, &config).unwrap(), vec!["skip_mount"]);
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// 事件级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Debug,
+    Info,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+        }
+    }
+}
+
+/// RmmCore 发出的事件，由订阅者决定如何展示
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoreEvent {
+    /// meta.toml 中的项目未通过有效性检查
+    ProjectInvalid { name: String, reason: String },
+    /// 扫描时跳过的路径
+    ScanSkipped { path: PathBuf, reason: String },
+    /// 扫描到的候选项目
+    ScanCandidate { name: String, path: PathBuf },
+    /// 候选项目通过名称校验
+    ScanAccepted { name: String },
+    /// 开始执行 Rmake.toml 脚本
+    ScriptStarted { name: String, command: String },
+    /// 脚本输出
+    ScriptOutput { stdout: String, stderr: String },
+    /// 脚本执行成功
+    ScriptFinished { name: String },
+}
+
+impl CoreEvent {
+    pub fn level(&self) -> Level {
+        match self {
+            CoreEvent::ProjectInvalid { .. }
+            | CoreEvent::ScanSkipped { .. }
+            | CoreEvent::ScanCandidate { .. }
+            | CoreEvent::ScanAccepted { .. } => Level::Debug,
+            CoreEvent::ScriptStarted { .. }
+            | CoreEvent::ScriptOutput { .. }
+            | CoreEvent::ScriptFinished { .. } => Level::Info,
+        }
+    }
+
+    /// 面向用户的单行描述
+    pub fn message(&self) -> String {
+        match self {
+            CoreEvent::ProjectInvalid { name, reason } => format!("项目 '{}' 无效: {}", name, reason),
+            CoreEvent::ScanSkipped { path, reason } => format!("跳过 {}: {}", path.display(), reason),
+            CoreEvent::ScanCandidate { name, path } => format!("正在验证项目名称: '{}' 在路径: {}", name, path.display()),
+            CoreEvent::ScanAccepted { name } => format!("项目名称 '{}' 验证通过", name),
+            CoreEvent::ScriptStarted { name, command } => format!("执行脚本: {} ({})", name, command),
+            CoreEvent::ScriptOutput { stdout, stderr } => format!("{}{}", stdout, stderr),
+            CoreEvent::ScriptFinished { name } => format!("脚本 '{}' 执行成功", name),
+        }
+    }
+}
+
+/// 事件订阅者
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: &CoreEvent);
+}
+
+/// 任意闭包都可以作为订阅者；GUI 等场景可在闭包中把事件转发到 mpsc channel
+impl<F> EventSink for F
+where
+    F: Fn(&CoreEvent) + Send + Sync,
+{
+    fn emit(&self, event: &CoreEvent) {
+        self(event)
+    }
+}
+
+/// 打印到终端（CLI 使用）；verbose 为 false 时不打印调试事件
+#[derive(Debug, Clone)]
+pub struct ConsoleSink {
+    pub verbose: bool,
+}
+
+impl Default for ConsoleSink {
+    fn default() -> Self {
+        Self { verbose: cfg!(debug_assertions) }
+    }
+}
+
+impl EventSink for ConsoleSink {
+    fn emit(&self, event: &CoreEvent) {
+        match event {
+            _ if event.level() == Level::Debug && !self.verbose => {}
+            CoreEvent::ScriptStarted { name, command } => {
+                println!("🚀 执行脚本: {}", name);
+                println!("📋 命令: {}", command);
+            }
+            CoreEvent::ScriptOutput { stdout, stderr } => {
+                print!("{}", stdout);
+                eprint!("{}", stderr);
+            }
+            CoreEvent::ScriptFinished { .. } => println!("✅ {}", event.message()),
+            _ => eprintln!("[{}] {}", event.level().as_str(), event.message()),
+        }
+    }
+}
+
+/// RmmCore 持有的事件发送端；未订阅时丢弃所有事件，库调用保持安静
+#[derive(Clone, Default)]
+pub struct EventEmitter {
+    sink: Option<Arc<dyn EventSink>>,
+}
+
+impl EventEmitter {
+    pub fn new(sink: Arc<dyn EventSink>) -> Self {
+        Self { sink: Some(sink) }
+    }
+
+    pub fn emit(&self, event: CoreEvent) {
+        if let Some(sink) = &self.sink {
+            sink.emit(&event);
+        }
+    }
+}
+
+impl fmt::Debug for EventEmitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventEmitter")
+            .field("subscribed", &self.sink.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::RmmCore;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_core_events_are_forwarded_to_sink() {
+        use std::sync::mpsc::channel;
+
+        let temp = tempdir().unwrap();
+        for name in ["good_module", "build"] {
+            let dir = temp.path().join(name);
+            fs::create_dir_all(dir.join(".rmmp")).unwrap();
+            fs::write(dir.join("rmmproject.toml"), "").unwrap();
+            fs::write(dir.join(".rmmp/Rmake.toml"), "").unwrap();
+        }
+
+        let (sender, receiver) = channel();
+        let core = RmmCore::new().with_event_sink(Arc::new(move |event: &CoreEvent| {
+            let _ = sender.send(event.clone());
+        }));
+        let results = core.scan_projects(temp.path(), Some(2)).unwrap();
+        assert_eq!(results.len(), 1);
+
+        let events: Vec<CoreEvent> = receiver.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(e, CoreEvent::ScanAccepted { name } if name == "good_module")));
+        let skipped = events.iter()
+            .find(|e| matches!(e, CoreEvent::ScanSkipped { reason, .. } if reason.contains("黑名单")))
+            .expect("blacklisted project reported");
+        assert_eq!(skipped.level(), Level::Debug);
+    }
+}
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::size_history::format_size;
+
+/// Windows 未启用长路径时的路径长度上限（MAX_PATH）
+pub const WINDOWS_MAX_PATH: usize = 260;
+/// 其他系统的路径长度上限（PATH_MAX）
+pub const UNIX_MAX_PATH: usize = 4096;
+/// 设备安装所需空间相对 zip 大小的倍数：推送到临时目录的副本、管理器解压后的模块，以及替换前保留的旧模块
+pub const DEVICE_SPACE_FACTOR: u64 = 3;
+
+/// 当前平台的路径长度上限
+pub fn max_path_len() -> usize {
+    if cfg!(windows) { WINDOWS_MAX_PATH } else { UNIX_MAX_PATH }
+}
+
+/// 文件系统状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FsStatus {
+    /// 当前用户可用的字节数
+    pub available: u64,
+    pub read_only: bool,
+}
+
+/// 查询 path 所在的文件系统；path 不存在时使用最近的已存在上级目录，不支持的平台返回 None
+#[allow(clippy::useless_conversion)] // statvfs 字段的宽度随平台不同
+pub fn fs_status(path: &Path) -> Option<FsStatus> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        let c_path = std::ffi::CString::new(existing.as_os_str().as_bytes()).ok()?;
+        // SAFETY: c_path 以 NUL 结尾，stat 是有效的输出缓冲区
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        Some(FsStatus {
+            available: u64::from(stat.f_bavail).saturating_mul(u64::from(stat.f_frsize)),
+            read_only: stat.f_flag & libc::ST_RDONLY != 0,
+        })
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = existing;
+        None
+    }
+}
+
+/// 目录中文件的总大小（不跟随符号链接），不存在时为 0
+pub fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// 估算构建所需空间：源文件总大小 × 系数（构建目录中的副本、模块 zip 与源码包）
+pub fn estimate_build_space(source_bytes: u64, factor: f64) -> u64 {
+    (source_bytes as f64 * factor.max(0.0)).ceil() as u64
+}
+
+/// 预检发现的问题
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreflightIssue {
+    /// 可用空间不足
+    Space { path: PathBuf, required: u64, available: u64 },
+    /// 目标文件系统只读
+    ReadOnly { path: PathBuf },
+    /// 目标路径超过系统的路径长度上限
+    PathTooLong { path: PathBuf, length: usize, limit: usize },
+}
+
+impl PreflightIssue {
+    pub fn describe(&self) -> String {
+        match self {
+            PreflightIssue::Space { path, required, available } => format!(
+                "{} 所在磁盘可用 {}，预计需要 {}（还差 {}）",
+                path.display(), format_size(*available), format_size(*required), format_size(required.saturating_sub(*available))
+            ),
+            PreflightIssue::ReadOnly { path } => format!("{} 所在文件系统为只读", path.display()),
+            PreflightIssue::PathTooLong { path, length, limit } => format!(
+                "{} 长度为 {}，超过上限 {}", path.display(), length, limit
+            ),
+        }
+    }
+}
+
+/// 检查 path 所在文件系统是否可写且有 required 字节可用；reclaimable 为写入前会被清理的占用（如旧的构建目录）
+pub fn check_space(path: &Path, required: u64, reclaimable: u64) -> Option<PreflightIssue> {
+    let status = fs_status(path)?;
+    if status.read_only {
+        return Some(PreflightIssue::ReadOnly { path: path.to_path_buf() });
+    }
+    let available = status.available.saturating_add(reclaimable);
+    (available < required).then(|| PreflightIssue::Space { path: path.to_path_buf(), required, available })
+}
+
+/// 检查 base 下各相对路径拼接后的长度
+pub fn check_path_lengths<'a>(base: &Path, relatives: impl IntoIterator<Item = &'a Path>, limit: usize) -> Vec<PreflightIssue> {
+    relatives.into_iter()
+        .map(|relative| base.join(relative))
+        .filter_map(|path| {
+            let length = path.as_os_str().len();
+            (length > limit).then_some(PreflightIssue::PathTooLong { path, length, limit })
+        })
+        .collect()
+}
+
+/// 解析 df -Pk 输出中最后一行的可用空间（字节）
+pub fn parse_df_available(output: &str) -> Option<u64> {
+    let line = output.lines().rev().find(|l| !l.trim().is_empty())?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 4 {
+        return None;
+    }
+    fields[fields.len() - 3].parse::<u64>().ok().map(|kb| kb.saturating_mul(1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_preflight_checks() {
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("build/system/bin")).unwrap();
+        fs::write(temp_dir.path().join("build/system/bin/tool"), vec![0u8; 1000]).unwrap();
+        fs::write(temp_dir.path().join("build/module.prop"), "id=test\n").unwrap();
+        assert_eq!(dir_size(&temp_dir.path().join("build")), 1008);
+        assert_eq!(dir_size(&temp_dir.path().join("missing")), 0);
+        assert_eq!(estimate_build_space(1000, 2.5), 2500);
+
+        // 不存在的目录按最近的上级目录检查
+        #[cfg(unix)]
+        {
+            let target = temp_dir.path().join("missing/.rmmp");
+            assert_eq!(check_space(&target, 0, 0), None);
+            let issue = check_space(&target, u64::MAX, 0).unwrap();
+            assert!(matches!(issue, PreflightIssue::Space { required: u64::MAX, .. }), "{:?}", issue);
+            assert!(issue.describe().contains("预计需要"));
+        }
+
+        let base = Path::new("/project/.rmmp/build");
+        let long = "a".repeat(100);
+        let relatives = [PathBuf::from("system/bin/tool"), PathBuf::from(format!("{}/{}", long, long))];
+        let issues = check_path_lengths(base, relatives.iter().map(|p| p.as_path()), 200);
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(&issues[0], PreflightIssue::PathTooLong { length, limit: 200, .. } if *length > 200));
+
+        let df = "Filesystem     1024-blocks    Used Available Capacity Mounted on\n/dev/block/dm-5   115249236 80145672  35103564      70% /data\n";
+        assert_eq!(parse_df_available(df), Some(35103564 * 1024));
+        assert_eq!(parse_df_available("Filesystem 1024-blocks Used Available Capacity Mounted on\n"), None);
+        assert_eq!(parse_df_available(""), None);
+    }
+}
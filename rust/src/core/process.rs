@@ -0,0 +1,318 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::settings::ConfigResolver;
+
+/// 取消后等待子进程自行退出的时间
+const CANCEL_GRACE: Duration = Duration::from_secs(2);
+/// 轮询子进程状态的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 外部命令类别，每类可单独配置超时
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandKind {
+    Adb,
+    Shellcheck,
+    Git,
+    /// prebuild / postbuild / scripts 等项目命令
+    Script,
+    /// rmm check --dry-run-service 在主机上试运行 service.sh
+    Service,
+}
+
+impl CommandKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandKind::Adb => "adb",
+            CommandKind::Shellcheck => "shellcheck",
+            CommandKind::Git => "git",
+            CommandKind::Script => "script",
+            CommandKind::Service => "service",
+        }
+    }
+
+    /// 默认超时（秒），0 表示不限制
+    fn default_secs(&self) -> u64 {
+        match self {
+            CommandKind::Adb => 300,
+            CommandKind::Shellcheck => 60,
+            CommandKind::Git => 300,
+            CommandKind::Script => 0,
+            CommandKind::Service => 30,
+        }
+    }
+
+    /// 覆盖超时的环境变量，如 RMM_TIMEOUT_ADB
+    pub fn env_var(&self) -> String {
+        format!("RMM_TIMEOUT_{}", self.as_str().to_uppercase())
+    }
+}
+
+/// Rmake.toml 中的 [timeouts] 配置（秒，0 表示不限制）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct TimeoutConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shellcheck: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<u64>,
+}
+
+impl TimeoutConfig {
+    fn get(&self, kind: CommandKind) -> Option<u64> {
+        match kind {
+            CommandKind::Adb => self.adb,
+            CommandKind::Shellcheck => self.shellcheck,
+            CommandKind::Git => self.git,
+            CommandKind::Script => self.script,
+            CommandKind::Service => self.service,
+        }
+    }
+}
+
+static TIMEOUTS: Mutex<Option<TimeoutConfig>> = Mutex::new(None);
+static CANCELLED: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+/// 设置当前项目的超时配置
+pub fn set_timeouts(config: Option<TimeoutConfig>) {
+    *TIMEOUTS.lock().unwrap() = config;
+}
+
+/// 按分层配置解析项目的超时（环境变量 > .rmmp/Rmake.toml [timeouts] > 默认值）
+pub fn configure_from_project(project_path: &Path) {
+    set_timeouts(Some(ConfigResolver::new(Some(project_path)).timeouts()));
+}
+
+/// 某类命令的超时：环境变量 > Rmake.toml [timeouts] > 默认值；None 表示不限制
+pub fn timeout_for(kind: CommandKind) -> Option<Duration> {
+    let secs = std::env::var(kind.env_var())
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .or_else(|| TIMEOUTS.lock().unwrap().as_ref().and_then(|c| c.get(kind)))
+        .unwrap_or_else(|| kind.default_secs());
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// 注册 Ctrl-C / SIGTERM 处理：第一次只标记取消，让正在运行的命令清理后退出；
+/// 再次按下 Ctrl-C 立即退出
+pub fn install_interrupt_handler() {
+    CANCELLED.get_or_init(|| {
+        let flag = Arc::new(AtomicBool::new(false));
+        for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+            let _ = signal_hook::flag::register_conditional_shutdown(signal, 130, Arc::clone(&flag));
+            let _ = signal_hook::flag::register(signal, Arc::clone(&flag));
+        }
+        flag
+    });
+}
+
+/// 是否已收到取消信号
+pub fn is_cancelled() -> bool {
+    CANCELLED.get().is_some_and(|flag| flag.load(Ordering::SeqCst))
+}
+
+/// 标记取消，与收到 Ctrl-C 相同（常驻进程转发客户端的 Ctrl-C）
+pub fn cancel() {
+    if let Some(flag) = CANCELLED.get() {
+        flag.store(true, Ordering::SeqCst);
+    }
+}
+
+/// 清除取消标记（常驻进程处理完被客户端取消的请求后继续运行）
+pub fn reset_cancelled() {
+    if let Some(flag) = CANCELLED.get() {
+        flag.store(false, Ordering::SeqCst);
+    }
+}
+
+/// 已收到取消信号时返回错误，用于在阶段之间尽早退出
+pub fn check_cancelled() -> Result<()> {
+    if is_cancelled() {
+        anyhow::bail!("操作已取消 (Ctrl-C)");
+    }
+    Ok(())
+}
+
+/// 带超时与取消处理的命令执行
+pub trait CommandExt {
+    /// 等价于 `output()`，超时或取消时终止子进程并返回错误
+    fn output_with_timeout(&mut self, kind: CommandKind) -> Result<Output>;
+    /// 等价于 `status()`（继承标准输入输出），超时或取消时终止子进程并返回错误
+    fn status_with_timeout(&mut self, kind: CommandKind) -> Result<ExitStatus>;
+}
+
+impl CommandExt for Command {
+    fn output_with_timeout(&mut self, kind: CommandKind) -> Result<Output> {
+        let program = self.get_program().to_string_lossy().to_string();
+        self.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        // 放入独立进程组，超时或取消时连同 sh -c 派生的子进程一起终止
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(self, 0);
+        let mut child = self.spawn()
+            .with_context(|| format!("无法执行 {}，请确认已安装并位于 PATH 中", program))?;
+
+        let stdout = child.stdout.take().map(spawn_reader);
+        let stderr = child.stderr.take().map(spawn_reader);
+        // 出错时不等待读取线程：残留的孙进程可能仍持有管道
+        let status = wait_child(&mut child, kind, &program, true)?;
+        let stdout = stdout.map(|h| h.join().unwrap_or_default()).unwrap_or_default();
+        let stderr = stderr.map(|h| h.join().unwrap_or_default()).unwrap_or_default();
+
+        Ok(Output { status, stdout, stderr })
+    }
+
+    fn status_with_timeout(&mut self, kind: CommandKind) -> Result<ExitStatus> {
+        let program = self.get_program().to_string_lossy().to_string();
+        let mut child = self.spawn()
+            .with_context(|| format!("无法执行 {}，请确认已安装并位于 PATH 中", program))?;
+        wait_child(&mut child, kind, &program, false)
+    }
+}
+
+/// 在后台线程读取管道，避免输出过多时子进程阻塞
+fn spawn_reader<R: Read + Send + 'static>(mut pipe: R) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// 向子进程所在的进程组发送信号（仅 Unix，且子进程已放入独立进程组）
+#[cfg(unix)]
+fn signal_group(child: &Child, signal: &str) {
+    let _ = Command::new("kill")
+        .args([format!("-{}", signal), "--".to_string(), format!("-{}", child.id())])
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(not(unix))]
+fn signal_group(_child: &Child, _signal: &str) {}
+
+/// 强制终止子进程（及其进程组）并回收
+fn terminate(child: &mut Child, group: bool) {
+    if group {
+        signal_group(child, "KILL");
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// 等待子进程结束；超时或取消时终止子进程
+fn wait_child(child: &mut Child, kind: CommandKind, program: &str, group: bool) -> Result<ExitStatus> {
+    let timeout = timeout_for(kind);
+    let start = Instant::now();
+    let mut cancelled_at: Option<Instant> = None;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if cancelled_at.is_some() {
+                anyhow::bail!("{} 已取消 (Ctrl-C)", program);
+            }
+            return Ok(status);
+        }
+
+        if let Some(timeout) = timeout.filter(|t| start.elapsed() >= *t) {
+            terminate(child, group);
+            anyhow::bail!(
+                "{} 超时（{} 秒），可通过 Rmake.toml [timeouts] {} 或环境变量 {} 调整",
+                program, timeout.as_secs(), kind.as_str(), kind.env_var()
+            );
+        }
+
+        // 收到 Ctrl-C：先把 SIGINT 转发给独立进程组，给子进程一点时间自行清理，超过则强制终止
+        if is_cancelled() {
+            let since = *cancelled_at.get_or_insert_with(|| {
+                if group {
+                    signal_group(child, "INT");
+                }
+                Instant::now()
+            });
+            if since.elapsed() >= CANCEL_GRACE {
+                terminate(child, group);
+                anyhow::bail!("{} 已取消 (Ctrl-C)", program);
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// 临时文件守卫：离开作用域（包括出错或取消）时删除，调用 keep() 后保留
+#[derive(Debug)]
+pub struct TempGuard {
+    path: Option<PathBuf>,
+}
+
+impl TempGuard {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: Some(path.into()) }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_deref().unwrap_or(Path::new(""))
+    }
+
+    /// 保留文件（操作成功完成）
+    pub fn keep(mut self) -> PathBuf {
+        self.path.take().unwrap_or_default()
+    }
+}
+
+impl Drop for TempGuard {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            if path.is_dir() {
+                let _ = std::fs::remove_dir_all(&path);
+            } else {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_timeout_and_temp_guard() {
+        let output = Command::new("sh").args(["-c", "echo ok"]).output_with_timeout(CommandKind::Script).unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+
+        set_timeouts(Some(TimeoutConfig { script: Some(1), ..Default::default() }));
+        let start = Instant::now();
+        let err = Command::new("sh").args(["-c", "sleep 30"]).output_with_timeout(CommandKind::Script).unwrap_err();
+        set_timeouts(None);
+        assert!(err.to_string().contains("超时"));
+        assert!(start.elapsed().as_secs() < 10);
+
+        let temp = tempdir().unwrap();
+        let dropped = temp.path().join("dropped.tmp");
+        fs::write(&dropped, "x").unwrap();
+        drop(TempGuard::new(&dropped));
+        assert!(!dropped.exists());
+
+        let kept = temp.path().join("kept.tmp");
+        fs::write(&kept, "x").unwrap();
+        assert_eq!(TempGuard::new(&kept).keep(), kept);
+        assert!(kept.exists());
+    }
+}
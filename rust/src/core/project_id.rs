@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use toml_edit::{value, DocumentMut};
+
+/// 同一进程内连续生成时区分种子
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 生成随机的项目 UUID（RFC 4122 第 4 版格式）
+pub fn generate_project_uuid() -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(chrono::Local::now().timestamp_nanos_opt().unwrap_or_default().to_le_bytes());
+    hasher.update(std::process::id().to_le_bytes());
+    hasher.update(COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    hasher.update(format!("{:p}", &hasher).as_bytes());
+    let mut bytes: [u8; 16] = hasher.finalize()[..16].try_into().unwrap();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// 是否为 8-4-4-4-12 格式的十六进制 UUID
+pub fn is_valid_project_uuid(uuid: &str) -> bool {
+    let groups: Vec<&str> = uuid.split('-').collect();
+    groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12])
+        && groups.iter().all(|g| g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// 读取 rmmproject.toml 中的 [project] uuid，缺失或无效时返回 None
+pub fn read_project_uuid(project_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(project_path.join("rmmproject.toml")).ok()?;
+    let document: toml::Value = toml::from_str(&content).ok()?;
+    let uuid = document.get("project")?.get("uuid")?.as_str()?;
+    is_valid_project_uuid(uuid).then(|| uuid.to_lowercase())
+}
+
+/// 写入 [project] uuid，保留文件中的注释与格式
+pub fn write_project_uuid(project_path: &Path, uuid: &str) -> Result<()> {
+    let path = project_path.join("rmmproject.toml");
+    let content = fs::read_to_string(&path).with_context(|| format!("无法读取 {}", path.display()))?;
+    let mut document: DocumentMut = content.parse().with_context(|| format!("{} 不是有效的 TOML", path.display()))?;
+    let project = document.get_mut("project")
+        .and_then(|item| item.as_table_like_mut())
+        .with_context(|| format!("{} 缺少 [project]", path.display()))?;
+    project.insert("uuid", value(uuid));
+    fs::write(&path, document.to_string()).with_context(|| format!("无法写入 {}", path.display()))
+}
+
+/// 返回项目的 UUID，缺失时生成并写入 rmmproject.toml
+pub fn ensure_project_uuid(project_path: &Path) -> Result<String> {
+    if let Some(uuid) = read_project_uuid(project_path) {
+        return Ok(uuid);
+    }
+    let uuid = generate_project_uuid();
+    write_project_uuid(project_path, &uuid)?;
+    Ok(uuid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_project_uuid_file() {
+        let (a, b) = (generate_project_uuid(), generate_project_uuid());
+        assert!(is_valid_project_uuid(&a), "{}", a);
+        assert_ne!(a, b);
+        assert_eq!(&a[14..15], "4");
+        assert!(!is_valid_project_uuid("not-a-uuid"));
+
+        let temp_dir = tempdir().unwrap();
+        let file = temp_dir.path().join("rmmproject.toml");
+        fs::write(&file, "# 注释\n[project]\nid = \"demo\"\n\n[urls]\ngithub = \"x\"\n").unwrap();
+        assert_eq!(read_project_uuid(temp_dir.path()), None);
+
+        // 写入后保留注释与其他表，再次调用不变
+        let uuid = ensure_project_uuid(temp_dir.path()).unwrap();
+        assert_eq!(read_project_uuid(temp_dir.path()).as_deref(), Some(uuid.as_str()));
+        assert_eq!(ensure_project_uuid(temp_dir.path()).unwrap(), uuid);
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content.starts_with(&format!("# 注释\n[project]\nid = \"demo\"\nuuid = \"{}\"\n", uuid)), "{}", content);
+        assert!(content.contains("[urls]"));
+
+        fs::write(&file, "[urls]\n").unwrap();
+        assert!(ensure_project_uuid(temp_dir.path()).is_err());
+    }
+}
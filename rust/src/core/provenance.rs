@@ -0,0 +1,283 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::hashing::{sha256_bytes, sha256_file};
+use super::privacy::strip_url_credentials;
+use super::vcs::analyze_vcs_info;
+use super::zip_inspect::{parse_module_prop, read_prop_text};
+
+/// 溯源文件名（位于 .rmmp/dist，可作为 Release 附件上传）
+pub const PROVENANCE_FILE: &str = "provenance.json";
+const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+const PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+const BUILD_TYPE: &str = "https://github.com/LIghtJUNction/RootManage-Module-Model/build/v1";
+/// 计算哈希的项目配置文件
+const CONFIG_FILES: [&str; 4] = [".rmmp/Rmake.toml", "rmmproject.toml", "module.prop", "update.json"];
+
+/// in-toto 声明：subject 为产物，predicate 为 SLSA 溯源
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProvenanceStatement {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<ResourceDescriptor>,
+    #[serde(rename = "predicateType")]
+    pub predicate_type: String,
+    pub predicate: Provenance,
+}
+
+/// 带摘要的资源（产物、依赖或输入）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResourceDescriptor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub digest: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    pub build_definition: BuildDefinition,
+    pub run_details: RunDetails,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDefinition {
+    pub build_type: String,
+    /// 模块 ID、版本与构建选项
+    pub external_parameters: BTreeMap<String, serde_json::Value>,
+    /// 配置文件哈希与环境摘要
+    pub internal_parameters: BTreeMap<String, serde_json::Value>,
+    /// 源码仓库提交
+    pub resolved_dependencies: Vec<ResourceDescriptor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunDetails {
+    pub builder: Builder,
+    pub metadata: BuildMetadata,
+    /// 输入文件摘要树根
+    pub byproducts: Vec<ResourceDescriptor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Builder {
+    pub id: String,
+    pub version: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildMetadata {
+    pub started_on: String,
+    pub finished_on: String,
+}
+
+/// 构建方提供的溯源输入
+#[derive(Debug, Clone, Default)]
+pub struct ProvenanceInput {
+    /// 输入文件摘要树根（构建指纹摘要）
+    pub inputs_digest: String,
+    pub input_files: usize,
+    pub started_on: String,
+    /// 构建选项，如 debug、sandbox
+    pub options: BTreeMap<String, serde_json::Value>,
+}
+
+fn digest(sha256: String) -> BTreeMap<String, String> {
+    BTreeMap::from([("sha256".to_string(), sha256)])
+}
+
+/// 本次构建的产物：dist 中以 <id>-<versionCode> 开头的 zip / tar.gz
+pub fn build_artifacts(project_path: &Path) -> Result<Vec<PathBuf>> {
+    let prop = parse_module_prop(&read_prop_text(&project_path.join("module.prop"))?);
+    let prefix = format!(
+        "{}-{}",
+        prop.get("id").map(String::as_str).unwrap_or_default(),
+        prop.get("versionCode").map(String::as_str).unwrap_or_default()
+    );
+    let mut artifacts: Vec<PathBuf> = fs::read_dir(project_path.join(".rmmp/dist"))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            name.starts_with(&prefix) && (name.ends_with(".zip") || name.ends_with(".tar.gz"))
+        })
+        .collect();
+    artifacts.sort();
+    Ok(artifacts)
+}
+
+/// 环境摘要：不包含用户名、主机名等个人信息
+pub fn environment_summary() -> BTreeMap<String, String> {
+    let mut env = BTreeMap::from([
+        ("os".to_string(), std::env::consts::OS.to_string()),
+        ("arch".to_string(), std::env::consts::ARCH.to_string()),
+    ]);
+    let ci = if std::env::var_os("GITHUB_ACTIONS").is_some() {
+        Some("github-actions")
+    } else if std::env::var_os("GITLAB_CI").is_some() {
+        Some("gitlab-ci")
+    } else if std::env::var_os("CI").is_some() {
+        Some("ci")
+    } else {
+        None
+    };
+    env.insert("ci".to_string(), ci.unwrap_or("local").to_string());
+    for (key, var) in [("runId", "GITHUB_RUN_ID"), ("workflow", "GITHUB_WORKFLOW_REF"), ("runner", "RUNNER_OS")] {
+        if let Ok(value) = std::env::var(var) {
+            env.insert(key.to_string(), value);
+        }
+    }
+    env
+}
+
+/// 生成溯源声明
+pub fn generate_provenance(project_path: &Path, input: &ProvenanceInput) -> Result<ProvenanceStatement> {
+    let subject = build_artifacts(project_path)?
+        .iter()
+        .map(|path| Ok(ResourceDescriptor {
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()),
+            uri: None,
+            digest: digest(sha256_file(path)?),
+            annotations: BTreeMap::new(),
+        }))
+        .collect::<Result<Vec<_>>>()?;
+    if subject.is_empty() {
+        anyhow::bail!("没有找到本次构建的产物，无法生成溯源");
+    }
+
+    let prop = parse_module_prop(&read_prop_text(&project_path.join("module.prop"))?);
+    let mut external = input.options.clone();
+    for key in ["id", "version", "versionCode"] {
+        if let Some(value) = prop.get(key) {
+            external.insert(key.to_string(), serde_json::Value::from(value.as_str()));
+        }
+    }
+
+    let config_hashes: BTreeMap<String, String> = CONFIG_FILES.iter()
+        .filter_map(|file| fs::read(project_path.join(file)).ok().map(|bytes| (file.to_string(), sha256_bytes(&bytes))))
+        .collect();
+    let internal = BTreeMap::from([
+        ("configHashes".to_string(), serde_json::to_value(&config_hashes)?),
+        ("environment".to_string(), serde_json::to_value(environment_summary())?),
+    ]);
+
+    let resolved_dependencies = analyze_vcs_info(project_path)
+        .ok()
+        .flatten()
+        .and_then(|git| {
+            let commit = git.last_commit_hash?;
+            // 不记录本地仓库路径与远程地址中的凭据
+            let uri = git.remote_url.map(|remote| match git.vcs {
+                "none" => strip_url_credentials(&remote),
+                vcs => format!("{}+{}@{}", vcs, strip_url_credentials(&remote), git.branch),
+            });
+            let mut annotations = BTreeMap::from([
+                ("dirty".to_string(), serde_json::Value::from(git.has_uncommitted_changes)),
+                ("path".to_string(), serde_json::Value::from(git.relative_path.to_string_lossy().replace('\\', "/"))),
+            ]);
+            // 没有版本控制时 [vcs] revision 不是内容哈希，只作为注解记录
+            let digest = match git.vcs {
+                "hg" => BTreeMap::from([("hgChangeset".to_string(), commit)]),
+                "none" => {
+                    annotations.insert("revision".to_string(), serde_json::Value::from(commit));
+                    BTreeMap::new()
+                }
+                _ => BTreeMap::from([("gitCommit".to_string(), commit)]),
+            };
+            Some(ResourceDescriptor { name: None, uri, digest, annotations })
+        })
+        .into_iter()
+        .collect();
+
+    Ok(ProvenanceStatement {
+        statement_type: STATEMENT_TYPE.to_string(),
+        subject,
+        predicate_type: PREDICATE_TYPE.to_string(),
+        predicate: Provenance {
+            build_definition: BuildDefinition {
+                build_type: BUILD_TYPE.to_string(),
+                external_parameters: external,
+                internal_parameters: internal,
+                resolved_dependencies,
+            },
+            run_details: RunDetails {
+                builder: Builder {
+                    id: "rmm".to_string(),
+                    version: BTreeMap::from([("rmm".to_string(), env!("CARGO_PKG_VERSION").to_string())]),
+                },
+                metadata: BuildMetadata {
+                    started_on: input.started_on.clone(),
+                    finished_on: chrono::Utc::now().to_rfc3339(),
+                },
+                byproducts: vec![ResourceDescriptor {
+                    name: Some("inputs".to_string()),
+                    uri: None,
+                    digest: digest(input.inputs_digest.clone()),
+                    annotations: BTreeMap::from([("files".to_string(), serde_json::Value::from(input.input_files))]),
+                }],
+            },
+        },
+    })
+}
+
+/// 生成溯源并写入 .rmmp/dist/provenance.json
+pub fn write_provenance(project_path: &Path, input: &ProvenanceInput) -> Result<(ProvenanceStatement, PathBuf)> {
+    let statement = generate_provenance(project_path, input)?;
+    let path = project_path.join(".rmmp/dist").join(PROVENANCE_FILE);
+    fs::write(&path, serde_json::to_string_pretty(&statement)?)?;
+    Ok((statement, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_build_provenance_statement() {
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        fs::create_dir_all(project.join(".rmmp/dist")).unwrap();
+        fs::write(project.join("module.prop"), "id=demo\nversion=v1.2\nversionCode=12\n").unwrap();
+        fs::write(project.join(".rmmp/Rmake.toml"), "[build]\n").unwrap();
+        fs::write(project.join(".rmmp/dist/demo-12.zip"), "zip").unwrap();
+        fs::write(project.join(".rmmp/dist/demo-12-source.tar.gz"), "src").unwrap();
+        fs::write(project.join(".rmmp/dist/demo-11.zip"), "old").unwrap();
+
+        let input = ProvenanceInput {
+            inputs_digest: "abc".to_string(),
+            input_files: 3,
+            started_on: "2026-01-01T00:00:00+00:00".to_string(),
+            ..Default::default()
+        };
+        let (statement, path) = write_provenance(project, &input).unwrap();
+        assert_eq!(path, project.join(".rmmp/dist").join(PROVENANCE_FILE));
+        let names: Vec<&str> = statement.subject.iter().filter_map(|s| s.name.as_deref()).collect();
+        assert_eq!(names, vec!["demo-12-source.tar.gz", "demo-12.zip"]);
+        assert_eq!(statement.subject[1].digest["sha256"], sha256_bytes(b"zip"));
+
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["_type"], "https://in-toto.io/Statement/v1");
+        assert_eq!(json["predicateType"], "https://slsa.dev/provenance/v1");
+        let definition = &json["predicate"]["buildDefinition"];
+        assert_eq!(definition["externalParameters"]["versionCode"], "12");
+        assert_eq!(definition["internalParameters"]["configHashes"][".rmmp/Rmake.toml"], sha256_bytes(b"[build]\n"));
+        assert!(definition["internalParameters"]["environment"]["os"].is_string());
+        assert_eq!(json["predicate"]["runDetails"]["byproducts"][0]["digest"]["sha256"], "abc");
+        assert_eq!(json["predicate"]["runDetails"]["builder"]["version"]["rmm"], env!("CARGO_PKG_VERSION"));
+
+        fs::remove_file(project.join(".rmmp/dist/demo-12.zip")).unwrap();
+        fs::remove_file(project.join(".rmmp/dist/demo-12-source.tar.gz")).unwrap();
+        assert!(write_provenance(project, &input).is_err());
+    }
+}
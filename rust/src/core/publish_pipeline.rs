@@ -0,0 +1,300 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// 发布流水线的进度文件（位于 .rmmp 下），中断后重新运行 rmm publish 从未完成的步骤继续
+const STATE_FILE: &str = "publish-state.json";
+
+/// 发布步骤的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum StepKind {
+    /// rmm build
+    Build,
+    /// rmm check
+    Check,
+    /// 按 module.prop 的 version 创建并推送 git 标签
+    Tag,
+    /// 创建或更新 GitHub Release 并上传构建产物
+    GithubRelease,
+    /// 向 [publish] upstream_repo 提交模块更新的 PR
+    UpstreamPr,
+    /// 按 [[publish.webhooks]] 发送通知
+    Webhook,
+    /// 执行 command 中的命令
+    Command,
+}
+
+impl StepKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepKind::Build => "build",
+            StepKind::Check => "check",
+            StepKind::Tag => "tag",
+            StepKind::GithubRelease => "github-release",
+            StepKind::UpstreamPr => "upstream-pr",
+            StepKind::Webhook => "webhook",
+            StepKind::Command => "command",
+        }
+    }
+}
+
+/// [[publish.steps]]：按声明顺序执行的发布步骤
+///
+/// ```toml
+/// [[publish.steps]]
+/// run = "build"
+///
+/// [[publish.steps]]
+/// run = "upstream-pr"
+/// channels = ["stable"]
+/// version_code_increased = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PublishStep {
+    pub run: StepKind,
+    /// 进度记录与输出中使用的名称，默认为 run；同类步骤出现多次时需要区分
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// run = "command" 时执行的命令，在项目根目录下通过 shell 运行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// 只在发布到这些通道时执行，为空表示所有通道
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<String>,
+    /// 只在 versionCode 大于上次成功发布的版本时执行
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub version_code_increased: bool,
+}
+
+impl PublishStep {
+    pub fn new(run: StepKind) -> Self {
+        Self { run, name: None, command: None, channels: Vec::new(), version_code_increased: false }
+    }
+
+    pub fn id(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.run.as_str().to_string())
+    }
+}
+
+/// 未配置 [[publish.steps]] 时的流程，与之前固定的发布顺序一致
+pub fn default_steps() -> Vec<PublishStep> {
+    vec![PublishStep::new(StepKind::GithubRelease), PublishStep::new(StepKind::Webhook)]
+}
+
+/// 检查步骤配置：名称不能重复，command 步骤必须提供命令
+pub fn validate_steps(steps: &[PublishStep]) -> Result<()> {
+    let mut seen = std::collections::BTreeSet::new();
+    for step in steps {
+        let id = step.id();
+        if !seen.insert(id.clone()) {
+            anyhow::bail!("[[publish.steps]] 中的步骤 {} 重复，请用 name 区分", id);
+        }
+        if step.run == StepKind::Command && step.command.as_deref().is_none_or(|c| c.trim().is_empty()) {
+            anyhow::bail!("[[publish.steps]] 中的步骤 {} 缺少 command", id);
+        }
+    }
+    Ok(())
+}
+
+/// 一次发布的进度：同一 versionCode 与通道的发布视为同一次
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishRun {
+    pub version_code: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    pub started_at: String,
+    /// 已完成的步骤
+    #[serde(default)]
+    pub completed: Vec<String>,
+    /// 已完成步骤的输出，如 Release 的 tag 与 url，供恢复后的步骤使用
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outputs: BTreeMap<String, String>,
+}
+
+/// .rmmp/publish-state.json
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PublishState {
+    /// 上次完整发布的 versionCode，用于 version_code_increased 条件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_published_version_code: Option<u64>,
+    /// 未完成的发布
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<PublishRun>,
+}
+
+impl PublishState {
+    pub fn load(project_path: &Path) -> Result<Self> {
+        let path = project_path.join(".rmmp").join(STATE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, project_path: &Path) -> Result<()> {
+        let dir = project_path.join(".rmmp");
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(STATE_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 开始或继续一次发布；versionCode 或通道不同、或指定 restart 时丢弃之前的进度。返回是否为继续
+    pub fn begin(&mut self, version_code: u64, channel: Option<&str>, restart: bool) -> bool {
+        let resumed = !restart && self.current.as_ref()
+            .is_some_and(|run| run.version_code == version_code && run.channel.as_deref() == channel);
+        if !resumed {
+            self.current = Some(PublishRun {
+                version_code,
+                channel: channel.map(str::to_string),
+                started_at: chrono::Utc::now().to_rfc3339(),
+                completed: Vec::new(),
+                outputs: BTreeMap::new(),
+            });
+        }
+        resumed
+    }
+
+    /// 记录完成的步骤及其输出
+    pub fn complete(&mut self, step: &str, outputs: BTreeMap<String, String>) {
+        if let Some(run) = self.current.as_mut() {
+            if !run.completed.iter().any(|s| s == step) {
+                run.completed.push(step.to_string());
+            }
+            run.outputs.extend(outputs);
+        }
+    }
+
+    /// 全部步骤完成：记录本次 versionCode 并清除进度
+    pub fn finish(&mut self) {
+        if let Some(run) = self.current.take() {
+            self.last_published_version_code = Some(run.version_code);
+        }
+    }
+
+    fn is_completed(&self, step: &str) -> bool {
+        self.current.as_ref().is_some_and(|run| run.completed.iter().any(|s| s == step))
+    }
+}
+
+/// 步骤在本次发布中的状态
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepStatus {
+    Pending,
+    /// 中断前已完成
+    Done,
+    /// 条件不满足，附原因
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedStep {
+    pub id: String,
+    pub step: PublishStep,
+    pub status: StepStatus,
+}
+
+/// 按条件与进度决定每个步骤是否执行；未指定通道的发布视为 default
+pub fn plan_steps(steps: &[PublishStep], state: &PublishState, version_code: u64, channel: Option<&str>) -> Vec<PlannedStep> {
+    let channel_name = channel.unwrap_or("default");
+    steps.iter().map(|step| {
+        let id = step.id();
+        let status = if state.is_completed(&id) {
+            StepStatus::Done
+        } else if !step.channels.is_empty() && !step.channels.iter().any(|c| c == channel_name) {
+            StepStatus::Skipped(format!("仅在通道 {} 执行，本次为 {}", step.channels.join(", "), channel_name))
+        } else if step.version_code_increased
+            && let Some(last) = state.last_published_version_code.filter(|last| version_code <= *last)
+        {
+            StepStatus::Skipped(format!("versionCode {} 未超过上次发布的 {}", version_code, last))
+        } else {
+            StepStatus::Pending
+        };
+        PlannedStep { id, step: step.clone(), status }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_publish_pipeline() {
+        use crate::core::rmm_core::RmakeConfig;
+
+        let config: RmakeConfig = toml::from_str(r#"
+[build]
+include = []
+exclude = []
+prebuild = []
+build = []
+postbuild = []
+
+[[publish.steps]]
+run = "build"
+
+[[publish.steps]]
+run = "github-release"
+
+[[publish.steps]]
+run = "upstream-pr"
+channels = ["stable"]
+version_code_increased = true
+
+[[publish.steps]]
+run = "command"
+name = "announce"
+command = "./announce.sh"
+"#).unwrap();
+        let steps = config.publish.unwrap().steps;
+        assert_eq!(steps[2].run, StepKind::UpstreamPr);
+        assert!(validate_steps(&steps).is_ok());
+        let status = |plan: &[PlannedStep]| -> Vec<(String, bool, bool)> {
+            plan.iter().map(|p| (p.id.clone(), p.status == StepStatus::Done, p.status == StepStatus::Pending)).collect()
+        };
+
+        // 首次发布：条件均满足
+        let dir = tempdir().unwrap();
+        let mut state = PublishState::load(dir.path()).unwrap();
+        assert!(!state.begin(100, Some("stable"), false));
+        assert!(plan_steps(&steps, &state, 100, Some("stable")).iter().all(|p| p.status == StepStatus::Pending));
+        // beta 通道跳过 upstream-pr
+        let beta = plan_steps(&steps, &state, 100, Some("beta"));
+        assert!(matches!(&beta[2].status, StepStatus::Skipped(reason) if reason.contains("stable")));
+
+        // 中断后继续：已完成的步骤及其输出保留
+        state.complete("build", Default::default());
+        state.complete("github-release", [("url".to_string(), "https://example.com/r".to_string())].into());
+        state.save(dir.path()).unwrap();
+        let mut state = PublishState::load(dir.path()).unwrap();
+        assert!(state.begin(100, Some("stable"), false));
+        assert_eq!(state.current.as_ref().unwrap().outputs["url"], "https://example.com/r");
+        assert_eq!(status(&plan_steps(&steps, &state, 100, Some("stable"))), vec![
+            ("build".to_string(), true, false),
+            ("github-release".to_string(), true, false),
+            ("upstream-pr".to_string(), false, true),
+            ("announce".to_string(), false, true),
+        ]);
+
+        // 完成后 versionCode 未增加的发布跳过 upstream-pr；restart 丢弃进度
+        state.finish();
+        assert_eq!(state.last_published_version_code, Some(100));
+        assert!(!state.begin(100, Some("stable"), false));
+        let again = plan_steps(&steps, &state, 100, Some("stable"));
+        assert!(matches!(&again[2].status, StepStatus::Skipped(reason) if reason.contains("100")));
+        assert_eq!(again[0].status, StepStatus::Pending);
+        state.complete("build", Default::default());
+        assert!(!state.begin(100, Some("stable"), true));
+        assert!(state.current.as_ref().unwrap().completed.is_empty());
+        assert_eq!(plan_steps(&steps, &state, 101, Some("stable"))[2].status, StepStatus::Pending);
+
+        // 重复的步骤与缺少命令的 command 步骤
+        let duplicate = vec![PublishStep::new(StepKind::Build), PublishStep::new(StepKind::Build)];
+        assert!(validate_steps(&duplicate).is_err());
+        assert!(validate_steps(&[PublishStep::new(StepKind::Command)]).is_err());
+    }
+}
@@ -2,7 +2,33 @@ use crate::core::rmm_core::*;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList, PyString, PyModule};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::core::errors::{project_file_err, to_py_err, ErrorKind, RmmError};
+use crate::core::notify::{CoreEvent, EventSink};
+
+/// 扫描结果转换为 Python 字典，git 为 None 或包含仓库信息的字典
+fn scan_result_to_dict<'py>(py: Python<'py>, result: &ProjectScanResult) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", &result.name)?;
+    dict.set_item("path", result.path.to_string_lossy().to_string())?;
+    dict.set_item("is_valid", result.is_valid)?;
+    match &result.git_info {
+        Some(git) => {
+            let git_dict = PyDict::new(py);
+            git_dict.set_item("repo_root", git.repo_root.to_string_lossy().to_string())?;
+            git_dict.set_item("relative_path", git.relative_path.to_string_lossy().to_string())?;
+            git_dict.set_item("branch", &git.branch)?;
+            git_dict.set_item("remote_url", &git.remote_url)?;
+            git_dict.set_item("has_uncommitted_changes", git.has_uncommitted_changes)?;
+            git_dict.set_item("last_commit_hash", &git.last_commit_hash)?;
+            dict.set_item("git", git_dict)?;
+        }
+        None => dict.set_item("git", py.None())?,
+    }
+    Ok(dict)
+}
 
 /// Python 包装器 - RmmCore
 #[pyclass(name = "RmmCore")]
@@ -41,9 +67,7 @@ impl PyRmmCore {
                 
                 Ok(dict.into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(to_py_err(&e, ErrorKind::Config, e.to_string())),
         }
     }
 
@@ -55,15 +79,23 @@ impl PyRmmCore {
         version: String,
         projects: HashMap<String, String>,
     ) -> PyResult<()> {
+        // 保留已有的信任记录、设备记录与标签
+        let existing = self.inner.get_meta_config().unwrap_or_default();
         let meta = MetaConfig {
             email,
             username,
             version,
             projects,
+            trusted: existing.trusted,
+            devices: existing.devices,
+            tags: existing.tags,
+            cache: existing.cache,
+            uuids: existing.uuids,
+            network: existing.network,
         };
         
         self.inner.update_meta_config(&meta).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
+            to_py_err(&e, ErrorKind::Config, e.to_string())
         })
     }
 
@@ -72,9 +104,7 @@ impl PyRmmCore {
         match self.inner.get_project_path(project_name) {
             Ok(Some(path)) => Ok(Some(path.to_string_lossy().to_string())),
             Ok(None) => Ok(None),
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(to_py_err(&e, ErrorKind::General, e.to_string())),
         }
     }
 
@@ -88,9 +118,7 @@ impl PyRmmCore {
                 }
                 Ok(dict.into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(to_py_err(&e, ErrorKind::General, e.to_string())),
         }
     }
 
@@ -114,12 +142,48 @@ impl PyRmmCore {
                 }
                 Ok(list.into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(to_py_err(&e, ErrorKind::General, e.to_string())),
         }
     }
 
+    /// 扫描项目（库 API）：多路径、深度与有效性过滤，progress(result, found) 报告进度
+    #[pyo3(signature = (paths, max_depth=None, only_valid=false, progress=None))]
+    fn scan(
+        &self,
+        py: Python,
+        paths: Vec<String>,
+        max_depth: Option<usize>,
+        only_valid: bool,
+        progress: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        let options = ScanOptions {
+            paths: paths.iter().map(PathBuf::from).collect(),
+            max_depth,
+            only_valid,
+        };
+        // 保留回调抛出的 Python 异常，扫描中止后原样抛出
+        let mut callback_error: Option<PyErr> = None;
+        let scanned = self.inner.scan_projects_with(&options, &mut |result, found| {
+            let Some(ref progress) = progress else { return Ok(()) };
+            let outcome = scan_result_to_dict(py, result)
+                .and_then(|dict| progress.call1(py, (dict, found)).map(|_| ()));
+            outcome.map_err(|e| {
+                callback_error = Some(e);
+                anyhow::anyhow!("progress 回调出错，扫描已中止")
+            })
+        });
+        if let Some(e) = callback_error {
+            return Err(e);
+        }
+        let results = scanned
+            .map_err(|e| to_py_err(&e, ErrorKind::General, e.to_string()))?;
+        let list = PyList::empty(py);
+        for result in &results {
+            list.append(scan_result_to_dict(py, result)?)?;
+        }
+        Ok(list.into())
+    }
+
     /// 同步项目
     fn sync_projects(
         &self,
@@ -128,7 +192,7 @@ impl PyRmmCore {
     ) -> PyResult<()> {
         let paths: Vec<&Path> = scan_paths.iter().map(|p| Path::new(p)).collect();
         self.inner.sync_projects(&paths, max_depth).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
+            to_py_err(&e, ErrorKind::General, e.to_string())
         })
     }
 
@@ -154,9 +218,7 @@ impl PyRmmCore {
 
                 Ok(dict.into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(project_file_err(&e)),
         }
     }
 
@@ -175,9 +237,7 @@ impl PyRmmCore {
                 dict.set_item("updateJson", prop.update_json)?;
                 Ok(dict.into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(project_file_err(&e)),
         }
     }
 
@@ -205,7 +265,7 @@ impl PyRmmCore {
         };
         
         self.inner.update_module_prop(path, &prop).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
+            to_py_err(&e, ErrorKind::General, e.to_string())
         })
     }
 
@@ -224,16 +284,14 @@ impl PyRmmCore {
                 dict.set_item("last_commit_message", git_info.last_commit_message)?;
                 Ok(dict.into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(to_py_err(&e, ErrorKind::General, e.to_string())),
         }
     }
 
     /// 移除项目
     fn remove_project_from_meta(&self, project_name: String) -> PyResult<bool> {
         self.inner.remove_project_from_meta(&project_name).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
+            to_py_err(&e, ErrorKind::General, e.to_string())
         })
     }    /// 移除多个项目
     fn remove_projects_from_meta(&self, py: Python, project_names: Vec<String>) -> PyResult<PyObject> {
@@ -243,9 +301,7 @@ impl PyRmmCore {
                 let json_str = serde_json::to_string(&removed).unwrap_or_else(|_| "[]".to_string());
                 Ok(PyString::new(py, &json_str).into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(to_py_err(&e, ErrorKind::General, e.to_string())),
         }
     }    /// 移除无效项目
     fn remove_invalid_projects(&self, py: Python) -> PyResult<PyObject> {
@@ -254,10 +310,277 @@ impl PyRmmCore {
                 let json_str = serde_json::to_string(&removed).unwrap_or_else(|_| "[]".to_string());
                 Ok(PyString::new(py, &json_str).into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(to_py_err(&e, ErrorKind::General, e.to_string())),
+        }
+    }
+
+    /// 追加项目事件到 .rmmp/events.jsonl
+    #[pyo3(signature = (project_path, kind, details=None))]
+    fn record_event(&self, project_path: String, kind: String, details: Option<HashMap<String, String>>) -> PyResult<()> {
+        let details: Vec<(&str, String)> = details
+            .iter()
+            .flatten()
+            .map(|(k, v)| (k.as_str(), v.clone()))
+            .collect();
+        crate::core::events::record_event(Path::new(&project_path), &kind, &details)
+            .map(|_| ())
+            .map_err(|e| to_py_err(&e, ErrorKind::General, e.to_string()))
+    }
+
+    /// 按 [publish.notes] 渲染发布说明并写入 .rmmp/dist/RELEASE_NOTES.md，返回 (内容, 文件路径)
+    #[pyo3(signature = (project_path, repo, tag, assets, mirrors=None, channel=None))]
+    fn render_release_notes(
+        &self,
+        project_path: String,
+        repo: String,
+        tag: String,
+        assets: Vec<String>,
+        mirrors: Option<Vec<String>>,
+        channel: Option<String>,
+    ) -> PyResult<(String, String)> {
+        let path = Path::new(&project_path);
+        let release = crate::core::release_notes::ReleaseContext {
+            repo,
+            tag,
+            assets: assets.iter().map(PathBuf::from).collect(),
+            mirrors: mirrors.unwrap_or_default(),
+            channel,
+        };
+        let result = self.inner.get_rmake_config(path).and_then(|config| {
+            let notes = config.publish.and_then(|p| p.notes);
+            crate::core::release_notes::write_release_notes(path, notes.as_ref(), &release)
+        });
+        result
+            .map(|(notes, file)| (notes, file.to_string_lossy().to_string()))
+            .map_err(|e| project_file_err(&e))
+    }
+
+    /// 发布到上游前检查模块 id 的命名空间归属，返回 {id, login, status, owner}
+    #[pyo3(signature = (project_path, reserve=true))]
+    fn verify_upstream(&self, py: Python, project_path: String, reserve: bool) -> PyResult<PyObject> {
+        let verification = crate::cmds::upstream::verify_project(Path::new(&project_path), reserve)
+            .map_err(|e| to_py_err(&e, ErrorKind::General, e.to_string()))?;
+        let (status, owner) = match verification.status {
+            crate::core::upstream::OwnershipStatus::Owned(owner) => ("owned", Some(owner)),
+            crate::core::upstream::OwnershipStatus::Reserved(owner) => ("reserved", Some(owner)),
+            crate::core::upstream::OwnershipStatus::Unclaimed => ("unclaimed", None),
+        };
+        let dict = PyDict::new(py);
+        dict.set_item("id", verification.id)?;
+        dict.set_item("login", verification.identity.login)?;
+        dict.set_item("status", status)?;
+        dict.set_item("owner", owner)?;
+        Ok(dict.into())
+    }
+
+    /// 发布完成后按 [[publish.webhooks]] 发送通知，返回 [{name, ok, attempts, error}]
+    #[pyo3(signature = (project_path, tag, url, repo, channel=None))]
+    fn send_webhooks(&self, py: Python, project_path: String, tag: String, url: String, repo: String, channel: Option<String>) -> PyResult<PyObject> {
+        let path = Path::new(&project_path);
+        let outcomes = self.inner.get_rmake_config(path)
+            .and_then(|config| {
+                let hooks = config.publish.map(|p| p.webhooks).unwrap_or_default();
+                let vars = crate::core::webhooks::release_vars(path, &tag, &url, &repo, channel.as_deref())?;
+                crate::core::webhooks::send_webhooks(&hooks, &vars, channel.as_deref())
+            })
+            .map_err(|e| to_py_err(&e, ErrorKind::General, e.to_string()))?;
+        let list = PyList::empty(py);
+        for outcome in outcomes {
+            let dict = PyDict::new(py);
+            dict.set_item("name", outcome.name)?;
+            dict.set_item("ok", outcome.ok)?;
+            dict.set_item("attempts", outcome.attempts)?;
+            dict.set_item("error", outcome.error)?;
+            list.append(dict)?;
+        }
+        Ok(list.into())
+    }
+
+    /// 按 [[publish.steps]] 与 .rmmp/publish-state.json 规划本次发布，返回 {resumed, outputs, steps: [{id, run, command, status, reason}]}
+    #[pyo3(signature = (project_path, version_code, channel=None, restart=false))]
+    fn publish_plan(&self, py: Python, project_path: String, version_code: u64, channel: Option<String>, restart: bool) -> PyResult<PyObject> {
+        use crate::core::publish_pipeline::{default_steps, plan_steps, validate_steps, PublishState, StepStatus};
+        let path = Path::new(&project_path);
+        let (plan, state, resumed) = self.inner.get_rmake_config(path)
+            .and_then(|config| {
+                let steps = config.publish.map(|p| p.steps).filter(|s| !s.is_empty()).unwrap_or_else(default_steps);
+                validate_steps(&steps)?;
+                let mut state = PublishState::load(path)?;
+                let resumed = state.begin(version_code, channel.as_deref(), restart);
+                state.save(path)?;
+                Ok((plan_steps(&steps, &state, version_code, channel.as_deref()), state, resumed))
+            })
+            .map_err(|e| to_py_err(&e, ErrorKind::Config, e.to_string()))?;
+        let list = PyList::empty(py);
+        for planned in plan {
+            let dict = PyDict::new(py);
+            dict.set_item("id", &planned.id)?;
+            dict.set_item("run", planned.step.run.as_str())?;
+            dict.set_item("command", planned.step.command)?;
+            let (status, reason) = match planned.status {
+                StepStatus::Pending => ("pending", None),
+                StepStatus::Done => ("done", None),
+                StepStatus::Skipped(reason) => ("skipped", Some(reason)),
+            };
+            dict.set_item("status", status)?;
+            dict.set_item("reason", reason)?;
+            list.append(dict)?;
+        }
+        let dict = PyDict::new(py);
+        dict.set_item("resumed", resumed)?;
+        dict.set_item("outputs", state.current.map(|run| run.outputs).unwrap_or_default())?;
+        dict.set_item("steps", list)?;
+        Ok(dict.into())
+    }
+
+    /// 记录发布步骤已完成，outputs 供中断后恢复的步骤使用
+    #[pyo3(signature = (project_path, step, outputs=None))]
+    fn complete_publish_step(&self, project_path: String, step: String, outputs: Option<HashMap<String, String>>) -> PyResult<()> {
+        let path = Path::new(&project_path);
+        crate::core::publish_pipeline::PublishState::load(path)
+            .and_then(|mut state| {
+                state.complete(&step, outputs.unwrap_or_default().into_iter().collect());
+                state.save(path)
+            })
+            .map_err(|e| to_py_err(&e, ErrorKind::Config, e.to_string()))
+    }
+
+    /// 全部发布步骤完成：记录本次 versionCode 并清除进度
+    fn finish_publish(&self, project_path: String) -> PyResult<()> {
+        let path = Path::new(&project_path);
+        crate::core::publish_pipeline::PublishState::load(path)
+            .and_then(|mut state| {
+                state.finish();
+                state.save(path)
+            })
+            .map_err(|e| to_py_err(&e, ErrorKind::Config, e.to_string()))
+    }
+
+    /// 按分层配置（命令行 > 环境变量 > 项目 > 全局 > 默认）解析配置项，返回 {key, value, layer, source}
+    #[pyo3(signature = (key, project_path=None, cli_value=None))]
+    fn resolve_config(&self, py: Python, key: String, project_path: Option<String>, cli_value: Option<String>) -> PyResult<PyObject> {
+        let resolved = crate::core::settings::ConfigResolver::new(project_path.as_deref().map(Path::new))
+            .with_cli(&key, cli_value)
+            .resolve(&key)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyKeyError, _>(e.to_string()))?;
+        let dict = PyDict::new(py);
+        dict.set_item("key", &resolved.key)?;
+        dict.set_item("value", &resolved.value)?;
+        dict.set_item("layer", resolved.source.layer())?;
+        dict.set_item("source", resolved.source.describe())?;
+        Ok(dict.into())
+    }
+
+    /// 创建完整配置的模块项目（选项与 rmm init 相同），返回项目路径
+    #[pyo3(signature = (project_path, project_id, author=None, email=None, include_email=false, template=None, license=None, channels=Vec::new(), git_init=false, version=None, boot_scripts=Vec::new(), force=false, mount=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn create_project(
+        &self,
+        project_path: String,
+        project_id: String,
+        author: Option<String>,
+        email: Option<String>,
+        include_email: bool,
+        template: Option<String>,
+        license: Option<String>,
+        channels: Vec<String>,
+        git_init: bool,
+        version: Option<String>,
+        boot_scripts: Vec<String>,
+        force: bool,
+        mount: Option<String>,
+    ) -> PyResult<String> {
+        let config = crate::core::settings::ConfigResolver::new(None);
+        let options = crate::cmds::init::ProjectOptions {
+            id: project_id,
+            author: author.or_else(|| config.get("author.name")).unwrap_or_default(),
+            email: email.or_else(|| config.get("author.email")).unwrap_or_default(),
+            include_email,
+            template,
+            license,
+            channels,
+            git_init,
+            version,
+            boot_scripts,
+            force,
+            mount,
+        };
+        options.validate()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        let path = PathBuf::from(&project_path);
+        std::fs::create_dir_all(&path)
+            .map_err(|e| RmmError::new_err(format!("无法创建项目目录: {}", e)))?;
+        crate::cmds::init::create_project(&path, &options)
+            .map_err(|e| to_py_err(&e, ErrorKind::General, e.to_string()))?;
+        Ok(path.canonicalize().unwrap_or(path).to_string_lossy().to_string())
+    }
+
+    /// 订阅核心事件：callback(level, message)，传入 None 取消订阅
+    #[pyo3(signature = (callback=None))]
+    fn set_event_callback(&mut self, callback: Option<PyObject>) {
+        let sink: Arc<dyn EventSink> = match callback {
+            Some(callback) => Arc::new(move |event: &CoreEvent| {
+                Python::with_gil(|py| {
+                    if let Err(e) = callback.call1(py, (event.level().as_str(), event.message())) {
+                        e.print(py);
+                    }
+                });
+            }),
+            None => Arc::new(|_: &CoreEvent| {}),
+        };
+        self.inner.set_event_sink(sink);
+    }
+
+    /// 列出 zip 中的条目（含 SHA-256），无需解压
+    fn list_zip_entries(&self, py: Python, zip_path: String) -> PyResult<PyObject> {
+        let entries = crate::core::zip_inspect::list_zip_entries(Path::new(&zip_path)).map_err(|e| {
+            to_py_err(&e, ErrorKind::General, e.to_string())
+        })?;
+        let list = PyList::empty(py);
+        for entry in entries {
+            let dict = PyDict::new(py);
+            dict.set_item("name", entry.name)?;
+            dict.set_item("size", entry.size)?;
+            dict.set_item("compressed_size", entry.compressed_size)?;
+            dict.set_item("is_dir", entry.is_dir)?;
+            dict.set_item("sha256", entry.sha256)?;
+            dict.set_item("unix_mode", entry.unix_mode)?;
+            list.append(dict)?;
+        }
+        Ok(list.into())
+    }
+
+    /// 读取 zip 中指定成员的内容
+    fn read_zip_member(&self, py: Python, zip_path: String, member: String) -> PyResult<PyObject> {
+        match crate::core::zip_inspect::read_zip_member(Path::new(&zip_path), &member) {
+            Ok(content) => Ok(pyo3::types::PyBytes::new(py, &content).into()),
+            Err(e) => Err(to_py_err(&e, ErrorKind::General, e.to_string())),
+        }
+    }
+
+    /// 检查模块 zip：module.prop、安装器入口与文件哈希
+    fn inspect_zip(&self, py: Python, zip_path: String) -> PyResult<PyObject> {
+        let inspection = crate::core::zip_inspect::inspect_zip(Path::new(&zip_path)).map_err(|e| {
+            to_py_err(&e, ErrorKind::General, e.to_string())
+        })?;
+        let dict = PyDict::new(py);
+        dict.set_item("path", inspection.path)?;
+        dict.set_item("size", inspection.size)?;
+        dict.set_item("sha256", inspection.sha256)?;
+        dict.set_item("has_update_binary", inspection.has_update_binary)?;
+        dict.set_item("has_updater_script", inspection.has_updater_script)?;
+        match inspection.module_prop {
+            Some(prop) => {
+                let prop_dict = PyDict::new(py);
+                for (key, value) in prop {
+                    prop_dict.set_item(key, value)?;
+                }
+                dict.set_item("module_prop", prop_dict)?;
+            }
+            None => dict.set_item("module_prop", py.None())?,
         }
+        dict.set_item("entries", self.list_zip_entries(py, zip_path)?)?;
+        Ok(dict.into())
     }
 
     /// 获取缓存统计
@@ -269,6 +592,22 @@ impl PyRmmCore {
         Ok(dict.into())
     }
 
+    /// 各缓存的命中 / 未命中次数、条目数与有效期
+    fn get_cache_statistics(&self, py: Python) -> PyResult<PyObject> {
+        let list = PyList::empty(py);
+        for stat in self.inner.cache_statistics() {
+            let dict = PyDict::new(py);
+            dict.set_item("cache", stat.cache)?;
+            dict.set_item("hits", stat.hits)?;
+            dict.set_item("misses", stat.misses)?;
+            dict.set_item("entries", stat.entries)?;
+            dict.set_item("expired", stat.expired)?;
+            dict.set_item("ttl_secs", stat.ttl_secs)?;
+            list.append(dict)?;
+        }
+        Ok(list.into())
+    }
+
     /// 清理所有缓存
     fn clear_all_cache(&self) -> PyResult<()> {
         self.inner.clear_all_cache();
@@ -286,6 +625,12 @@ impl PyRmmCore {
             username,
             version,
             projects: HashMap::new(),
+            trusted: HashMap::new(),
+            devices: Default::default(),
+            tags: Default::default(),
+            cache: None,
+            uuids: Default::default(),
+            network: None,
         };
         
         let dict = PyDict::new(py);
@@ -307,7 +652,10 @@ impl PyRmmCore {
                 changelog: "CHANGELOG.md".to_string(),
                 license: "LICENSE".to_string(),
                 dependencies: Vec::new(),
+                script_dependencies: Vec::new(),
+                keywords: Vec::new(),
                 scripts: None,
+                uuid: Some(crate::core::project_id::generate_project_uuid()),
             },
             authors: vec![Author {
                 name: username,
@@ -316,6 +664,8 @@ impl PyRmmCore {
             urls: None,
             build_system: None,
             tool: None,
+            verify: None,
+            migrations: Vec::new(),
         };
         
         let dict = PyDict::new(py);
@@ -374,6 +724,14 @@ impl PyRmmCore {
             build: BuildConfig {
                 include: vec!["rmm".to_string()],
                 exclude: vec![".git".to_string(), ".rmmp".to_string(), "*.tmp".to_string()],
+                exclude_presets: Vec::new(),
+                allow: Vec::new(),
+                generate: Vec::new(),
+                recovery: false,
+                permissions: Default::default(),
+                checksums: None,
+                line_endings: Default::default(),
+                symbols: None,
                 prebuild: vec!["echo 'Starting build'".to_string()],
                 build: vec!["rmm".to_string()],
                 postbuild: vec!["echo 'Build completed'".to_string()],
@@ -383,6 +741,19 @@ impl PyRmmCore {
                 }),
                 scripts: Some(HashMap::new()),
             },
+            update: None,
+            action: None,
+            check: None,
+            timeouts: None,
+            publish: None,
+            uninstall: None,
+            bootloop_guard: None,
+            control: None,
+            assets: Default::default(),
+            third_party: Default::default(),
+            vcs: None,
+            module: None,
+            sign: None,
         };
         
         let dict = PyDict::new(py);
@@ -391,6 +762,7 @@ impl PyRmmCore {
         let build_dict = PyDict::new(py);
         build_dict.set_item("include", config.build.include)?;
         build_dict.set_item("exclude", config.build.exclude)?;
+        build_dict.set_item("exclude_presets", config.build.exclude_presets)?;
         build_dict.set_item("prebuild", config.build.prebuild)?;
         build_dict.set_item("build", config.build.build)?;
         build_dict.set_item("postbuild", config.build.postbuild)?;
@@ -425,6 +797,7 @@ impl PyRmmCore {
                 let build_dict = PyDict::new(py);
                 build_dict.set_item("include", config.build.include)?;
                 build_dict.set_item("exclude", config.build.exclude)?;
+                build_dict.set_item("exclude_presets", config.build.exclude_presets)?;
                 build_dict.set_item("prebuild", config.build.prebuild)?;
                 build_dict.set_item("build", config.build.build)?;
                 build_dict.set_item("postbuild", config.build.postbuild)?;
@@ -437,6 +810,12 @@ impl PyRmmCore {
                 }
                 build_dict.set_item("src", src_dict)?;
                 
+                // Publish config
+                let publish_dict = PyDict::new(py);
+                publish_dict.set_item("provenance", config.publish.as_ref().is_some_and(|p| p.provenance))?;
+                publish_dict.set_item("upstream_repo", config.publish.as_ref().and_then(|p| p.upstream_repo.clone()))?;
+                dict.set_item("publish", publish_dict)?;
+                
                 // Scripts
                 let scripts_dict = PyDict::new(py);
                 if let Some(scripts) = config.build.scripts {
@@ -450,9 +829,7 @@ impl PyRmmCore {
                 
                 Ok(dict.into())
             }
-            Err(e) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
-                e.to_string(),
-            )),
+            Err(e) => Err(project_file_err(&e)),
         }
     }
 
@@ -472,15 +849,23 @@ impl PyRmmCore {
             projects.insert(project_name, project_path);
         }
         
+        // 保留已有的信任记录、设备记录与标签
+        let existing = self.inner.get_meta_config().unwrap_or_default();
         let meta = MetaConfig {
             email,
             username,
             version,
             projects,
+            trusted: existing.trusted,
+            devices: existing.devices,
+            tags: existing.tags,
+            cache: existing.cache,
+            uuids: existing.uuids,
+            network: existing.network,
         };
         
         self.inner.update_meta_config(&meta).map_err(|e| {
-            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
+            to_py_err(&e, ErrorKind::Config, e.to_string())
         })
     }
 
@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use super::adb::shell_quote;
+use super::zip_inspect::{list_zip_entries, UPDATER_SCRIPT, UPDATE_BINARY};
+
+/// 卡刷包文件名后缀，如 demo-100-recovery.zip
+pub const RECOVERY_SUFFIX: &str = "-recovery";
+/// 安装器目录下的文件不属于模块内容，比较两个变体时忽略
+const INSTALLER_DIR: &str = "META-INF/";
+/// edify 占位脚本：Recovery 直接执行 update-binary（sh），不解析此文件
+const UPDATER_SCRIPT_STUB: &str = "#MAGISK\n";
+
+/// 生成 Recovery 中使用的 sh 安装器：解压到 modules_update 并标记更新，重启后由 Root 管理器加载
+/// Recovery 中不会执行 customize.sh
+pub fn recovery_update_binary(module_id: &str) -> String {
+    format!(r#"#!/sbin/sh
+# 由 RMM 生成的 Recovery 安装器
+OUTFD=$2
+ZIPFILE=$3
+MODID={id}
+
+ui_print() {{
+  echo "ui_print $1" > /proc/self/fd/$OUTFD
+  echo "ui_print" > /proc/self/fd/$OUTFD
+}}
+abort() {{
+  ui_print "! $1"
+  exit 1
+}}
+
+ui_print "- Installing $MODID"
+mount /data 2>/dev/null
+[ -d /data/adb ] || abort "/data/adb not found (root manager not installed or /data not decrypted)"
+
+MODPATH=/data/adb/modules_update/$MODID
+rm -rf "$MODPATH"
+mkdir -p "$MODPATH" || abort "Cannot create $MODPATH"
+unzip -o "$ZIPFILE" -x 'META-INF/*' -d "$MODPATH" >&2 || abort "Failed to extract module files"
+[ -f "$MODPATH/module.prop" ] || abort "module.prop not found in zip"
+
+find "$MODPATH" -type d -exec chmod 755 {{}} \;
+find "$MODPATH" -type f -exec chmod 644 {{}} \;
+for script in post-fs-data.sh post-mount.sh service.sh boot-completed.sh uninstall.sh action.sh; do
+  [ -f "$MODPATH/$script" ] && chmod 755 "$MODPATH/$script"
+done
+for dir in system/bin system/xbin bin; do
+  [ -d "$MODPATH/$dir" ] && chmod -R 755 "$MODPATH/$dir"
+done
+[ -d "$MODPATH/system" ] && chcon -R u:object_r:system_file:s0 "$MODPATH/system" 2>/dev/null
+
+mkdir -p "/data/adb/modules/$MODID"
+touch "/data/adb/modules/$MODID/update"
+cp -f "$MODPATH/module.prop" "/data/adb/modules/$MODID/module.prop"
+
+ui_print "- Done, reboot to activate $MODID"
+exit 0
+"#, id = shell_quote(module_id))
+}
+
+/// 基于管理器安装包生成卡刷包：模块内容原样复制，替换 META-INF 中的安装器
+pub fn create_recovery_variant(manager_zip: &Path, output: &Path, module_id: &str) -> Result<()> {
+    let file = fs::File::open(manager_zip)
+        .with_context(|| format!("Failed to open {}", manager_zip.display()))?;
+    let mut source = zip::ZipArchive::new(file)?;
+    let mut writer = zip::ZipWriter::new(fs::File::create(output)?);
+
+    for i in 0..source.len() {
+        let entry = source.by_index_raw(i)?;
+        if entry.name() == UPDATE_BINARY || entry.name() == UPDATER_SCRIPT {
+            continue;
+        }
+        writer.raw_copy_file(entry)?;
+    }
+    let executable = zip::write::SimpleFileOptions::default().unix_permissions(0o755);
+    writer.start_file(UPDATE_BINARY, executable)?;
+    writer.write_all(recovery_update_binary(module_id).as_bytes())?;
+    writer.start_file(UPDATER_SCRIPT, zip::write::SimpleFileOptions::default())?;
+    writer.write_all(UPDATER_SCRIPT_STUB.as_bytes())?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// 模块内容（META-INF 以外的文件）：路径 → SHA-256
+pub fn payload_digests(zip_path: &Path) -> Result<BTreeMap<String, String>> {
+    Ok(list_zip_entries(zip_path)?
+        .into_iter()
+        .filter(|entry| !entry.is_dir && !entry.name.starts_with(INSTALLER_DIR))
+        .filter_map(|entry| entry.sha256.map(|sha| (entry.name, sha)))
+        .collect())
+}
+
+/// 校验两个安装包的模块内容完全一致
+pub fn verify_identical_payloads(left: &Path, right: &Path) -> Result<()> {
+    let left_digests = payload_digests(left)?;
+    let right_digests = payload_digests(right)?;
+    let mut differences: Vec<&str> = left_digests.keys()
+        .chain(right_digests.keys())
+        .filter(|name| left_digests.get(*name) != right_digests.get(*name))
+        .map(String::as_str)
+        .collect();
+    differences.sort_unstable();
+    differences.dedup();
+    if !differences.is_empty() {
+        anyhow::bail!(
+            "{} 与 {} 的模块内容不一致: {}",
+            left.display(),
+            right.display(),
+            differences.join(", ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_recovery_variant_keeps_payload() {
+        use crate::core::zip_inspect::read_zip_member;
+
+        let temp = tempdir().unwrap();
+        let write_zip = |path: &std::path::Path, service: &[u8]| {
+            let mut writer = zip::ZipWriter::new(fs::File::create(path).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            writer.start_file(UPDATE_BINARY, options).unwrap();
+            writer.write_all(b"#!/sbin/sh\n# magisk installer\n").unwrap();
+            writer.start_file("module.prop", options).unwrap();
+            writer.write_all(b"id=demo\nversionCode=1\n").unwrap();
+            writer.add_directory("system/bin/", options).unwrap();
+            writer.start_file("service.sh", options).unwrap();
+            writer.write_all(service).unwrap();
+            writer.finish().unwrap();
+        };
+        let manager = temp.path().join("demo-1.zip");
+        write_zip(&manager, b"echo hi\n");
+
+        let recovery = temp.path().join("demo-1-recovery.zip");
+        create_recovery_variant(&manager, &recovery, "demo").unwrap();
+        verify_identical_payloads(&manager, &recovery).unwrap();
+        assert_eq!(payload_digests(&recovery).unwrap().keys().collect::<Vec<_>>(), vec!["module.prop", "service.sh"]);
+        let binary = String::from_utf8(read_zip_member(&recovery, UPDATE_BINARY).unwrap()).unwrap();
+        assert!(binary.contains("MODID='demo'"));
+        assert!(binary.contains("/data/adb/modules_update/$MODID"));
+        assert!(!read_zip_member(&recovery, UPDATER_SCRIPT).unwrap().is_empty());
+
+        let changed = temp.path().join("changed.zip");
+        write_zip(&changed, b"echo changed\n");
+        let error = verify_identical_payloads(&manager, &changed).unwrap_err().to_string();
+        assert!(error.contains("service.sh") && !error.contains("module.prop"));
+    }
+}
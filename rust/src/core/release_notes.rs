@@ -0,0 +1,275 @@
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::generate::{generation_vars, render_template};
+use super::hashing::sha256_file;
+use super::publish_pipeline::PublishStep;
+use super::webhooks::WebhookConfig;
+
+/// 发布说明文件名（同时作为 Release 附件上传）
+pub const RELEASE_NOTES_FILE: &str = "RELEASE_NOTES.md";
+
+/// 未配置 [publish.notes] 时使用的模板
+pub const DEFAULT_NOTES_TEMPLATE: &str = "## {{name}} {{version}}
+
+{{changelog}}
+
+[完整更新日志]({{changelog_url}})
+
+### 下载
+{{downloads}}
+
+### SHA-256
+{{checksums}}
+";
+
+/// Rmake.toml 中的 [publish] 配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct PublishConfig {
+    /// 发布说明模板，如 [publish.notes]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<NotesConfig>,
+    /// 上传 Release 时附带构建溯源 provenance.json
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub provenance: bool,
+    /// 上游母仓库的模块索引 URL，发布前据此检查模块 id 归属
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_index: Option<String>,
+    /// upstream-pr 步骤提交 PR 的母仓库，如 "owner/modules"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_repo: Option<String>,
+    /// 发布完成后的通知，如 [[publish.webhooks]]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+    /// 发布流水线，如 [[publish.steps]]；未配置时为 github-release → webhook
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<PublishStep>,
+}
+
+/// [publish.notes]：默认模板与按通道覆盖的模板
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct NotesConfig {
+    #[serde(flatten)]
+    pub default: NotesTemplate,
+    /// 下载链接的镜像前缀，如 "https://ghproxy.net/"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    /// 按通道覆盖的模板，如 [publish.notes.channels.beta]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub channels: BTreeMap<String, NotesTemplate>,
+}
+
+/// 模板来源：内联 template 或项目内的 file（template 优先）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct NotesTemplate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// 相对项目根目录的 Markdown 模板文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl NotesTemplate {
+    fn load(&self, project_path: &Path) -> Result<Option<String>> {
+        if let Some(ref template) = self.template {
+            return Ok(Some(template.clone()));
+        }
+        match self.file {
+            Some(ref file) => {
+                let path = project_path.join(file);
+                fs::read_to_string(&path)
+                    .map(Some)
+                    .with_context(|| format!("无法读取发布说明模板: {}", path.display()))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// 一次发布的上下文
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseContext {
+    /// GitHub 仓库 owner/repo
+    pub repo: String,
+    pub tag: String,
+    /// 要上传的文件
+    pub assets: Vec<PathBuf>,
+    /// 额外的镜像前缀（与配置中的 mirrors 合并）
+    pub mirrors: Vec<String>,
+    /// 发布通道，None 表示默认
+    pub channel: Option<String>,
+}
+
+/// 从 CHANGELOG 中截取指定版本的段落；找不到时返回第一个版本段落
+pub fn changelog_excerpt(changelog: &str, version: &str) -> Option<String> {
+    let wanted = version.trim().trim_start_matches('v');
+    let lines: Vec<&str> = changelog.lines().collect();
+    let headings: Vec<(usize, usize)> = lines.iter()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let level = line.chars().take_while(|c| *c == '#').count();
+            (level > 0 && line[level..].starts_with(' ')).then_some((i, level))
+        })
+        .collect();
+
+    let mentions_version = |line: &str| {
+        !wanted.is_empty() && line
+            .split(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '(' | ')'))
+            .any(|word| word.trim_start_matches('v') == wanted)
+    };
+    let (start, level) = headings.iter()
+        .find(|(i, _)| mentions_version(lines[*i]))
+        .or_else(|| headings.iter().find(|(i, _)| lines[*i].chars().any(|c| c.is_ascii_digit())))
+        .copied()?;
+    let end = headings.iter()
+        .find(|(i, l)| *i > start && *l <= level)
+        .map(|(i, _)| *i)
+        .unwrap_or(lines.len());
+    let body = lines[start + 1..end].join("\n").trim().to_string();
+    (!body.is_empty()).then_some(body)
+}
+
+/// 完整 CHANGELOG.md 的链接：优先使用项目 update.json 中的 changelog，否则指向该 tag 下的文件
+/// （update.json 发布时只指向当前版本的摘录，完整历史由发布说明链接）
+fn full_changelog_url(project_path: &Path, release: &ReleaseContext) -> String {
+    fs::read_to_string(project_path.join("update.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|value| value.get("changelog")?.as_str().map(str::to_string))
+        .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+        .unwrap_or_else(|| format!("https://github.com/{}/blob/{}/CHANGELOG.md", release.repo, release.tag))
+}
+
+/// 镜像链接：镜像前缀 + 原始链接
+fn mirror_url(mirror: &str, url: &str) -> String {
+    format!("{}/{}", mirror.trim_end_matches('/'), url)
+}
+
+/// 渲染发布说明
+pub fn render_release_notes(project_path: &Path, config: Option<&NotesConfig>, release: &ReleaseContext) -> Result<String> {
+    let default_config = NotesConfig::default();
+    let config = config.unwrap_or(&default_config);
+    let channel_template = match release.channel {
+        Some(ref channel) => match config.channels.get(channel) {
+            Some(template) => template.load(project_path)?,
+            None => None,
+        },
+        None => None,
+    };
+    let template = match channel_template {
+        Some(template) => template,
+        None => config.default.load(project_path)?.unwrap_or_else(|| DEFAULT_NOTES_TEMPLATE.to_string()),
+    };
+
+    let mut vars = generation_vars(project_path)?;
+    let changelog = fs::read_to_string(project_path.join("CHANGELOG.md"))
+        .ok()
+        .and_then(|content| changelog_excerpt(&content, &vars["version"]))
+        .unwrap_or_default();
+    let mirrors: Vec<&String> = config.mirrors.iter().chain(release.mirrors.iter()).collect();
+
+    let mut downloads = Vec::new();
+    let mut checksums = Vec::new();
+    for asset in &release.assets {
+        let name = asset.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow::anyhow!("无效的附件路径: {}", asset.display()))?;
+        let url = format!("https://github.com/{}/releases/download/{}/{}", release.repo, release.tag, name);
+        let links: Vec<String> = std::iter::once(format!("[GitHub]({})", url))
+            .chain(mirrors.iter().map(|m| {
+                let host = m.trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/');
+                format!("[{}]({})", host, mirror_url(m, &url))
+            }))
+            .collect();
+        downloads.push(format!("- `{}`: {}", name, links.join(" · ")));
+        checksums.push(format!("- `{}`: `{}`", name, sha256_file(asset)?));
+    }
+
+    vars.insert("tag".to_string(), release.tag.clone());
+    vars.insert("repo".to_string(), release.repo.clone());
+    vars.insert("channel".to_string(), release.channel.clone().unwrap_or_else(|| "default".to_string()));
+    vars.insert("changelog".to_string(), changelog);
+    vars.insert("changelog_url".to_string(), full_changelog_url(project_path, release));
+    vars.insert("downloads".to_string(), downloads.join("\n"));
+    vars.insert("checksums".to_string(), checksums.join("\n"));
+    render_template(&template, &vars)
+}
+
+/// 渲染发布说明并写入 .rmmp/dist/RELEASE_NOTES.md，返回 (内容, 文件路径)
+pub fn write_release_notes(project_path: &Path, config: Option<&NotesConfig>, release: &ReleaseContext) -> Result<(String, PathBuf)> {
+    let notes = render_release_notes(project_path, config, release)?;
+    let dist_dir = project_path.join(".rmmp").join("dist");
+    fs::create_dir_all(&dist_dir)?;
+    let path = dist_dir.join(RELEASE_NOTES_FILE);
+    fs::write(&path, &notes)?;
+    Ok((notes, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_release_notes_rendering() {
+        use crate::core::rmm_core::RmakeConfig;
+
+        let changelog = "# Changelog\n\n## v1.2 (2026-10-01)\n+ 新功能\n### 修复\n+ bug\n\n## v1.1\n+ 旧版本\n";
+        assert_eq!(changelog_excerpt(changelog, "v1.2").unwrap(), "+ 新功能\n### 修复\n+ bug");
+        assert_eq!(changelog_excerpt(changelog, "1.1").unwrap(), "+ 旧版本");
+        // 找不到版本时取第一个版本段落
+        assert!(changelog_excerpt(changelog, "v9").unwrap().starts_with("+ 新功能"));
+
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("module.prop"), "id=demo\nname=Demo\nversion=v1.2\nversionCode=120\n").unwrap();
+        fs::write(temp.path().join("CHANGELOG.md"), changelog).unwrap();
+        fs::write(temp.path().join("beta.md"), "beta {{tag}} ({{channel}})\n{{checksums}}").unwrap();
+        let asset = temp.path().join("demo-120.zip");
+        fs::write(&asset, "zip").unwrap();
+
+        let config: RmakeConfig = toml::from_str(r##"
+[build]
+include = []
+exclude = []
+prebuild = []
+build = []
+postbuild = []
+
+[publish.notes]
+template = "# {{name}} {{version}}\n{{changelog}}\n{{downloads}}"
+mirrors = ["https://ghproxy.net/"]
+
+[publish.notes.channels.beta]
+file = "beta.md"
+"##).unwrap();
+        let notes = config.publish.unwrap().notes.unwrap();
+        let mut release = ReleaseContext {
+            repo: "alice/demo".to_string(),
+            tag: "v1.2".to_string(),
+            assets: vec![asset],
+            mirrors: vec!["https://mirror.example".to_string()],
+            channel: None,
+        };
+
+        let (body, file) = write_release_notes(temp.path(), Some(&notes), &release).unwrap();
+        assert!(body.starts_with("# Demo v1.2\n+ 新功能"));
+        assert!(body.contains("[GitHub](https://github.com/alice/demo/releases/download/v1.2/demo-120.zip)"));
+        assert!(body.contains("[ghproxy.net](https://ghproxy.net/https://github.com/alice/demo/releases/download/v1.2/demo-120.zip)"));
+        assert!(body.contains("[mirror.example]("));
+        assert_eq!(file, temp.path().join(".rmmp/dist").join(RELEASE_NOTES_FILE));
+        assert_eq!(fs::read_to_string(&file).unwrap(), body);
+
+        release.channel = Some("beta".to_string());
+        let (body, _) = write_release_notes(temp.path(), Some(&notes), &release).unwrap();
+        let zip_hash = crate::core::hashing::sha256_bytes(b"zip");
+        assert_eq!(body, format!("beta v1.2 (beta)\n- `demo-120.zip`: `{}`", zip_hash));
+
+        // 未配置时使用默认模板
+        let (body, _) = write_release_notes(temp.path(), None, &release).unwrap();
+        assert!(body.contains("### SHA-256") && body.contains(&zip_hash));
+    }
+}
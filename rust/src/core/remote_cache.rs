@@ -0,0 +1,369 @@
+use anyhow::{Context, Result};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::hashing::{hmac_sha256, sha256_bytes, to_hex};
+use super::settings::ConfigResolver;
+
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(300);
+/// 按内容哈希存放的文件：objects/<sha256>
+const OBJECTS_DIR: &str = "objects";
+/// 按构建输入摘要存放的构建记录：builds/<digest>.json
+const BUILDS_DIR: &str = "builds";
+
+/// 产物名只能是单层文件名：不含路径分隔符与 ..，不是绝对路径或盘符
+fn is_safe_artifact_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', '\\', ':']) && !name.contains("..") && !Path::new(name).is_absolute()
+}
+
+/// 远程缓存地址：共享目录、WebDAV（http/https）或 S3（s3://bucket/prefix）
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteBackend {
+    Directory(PathBuf),
+    WebDav(String),
+    S3 { bucket: String, prefix: String },
+}
+
+impl RemoteBackend {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if let Some(rest) = spec.strip_prefix("s3://") {
+            let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+            if bucket.is_empty() {
+                anyhow::bail!("远程缓存地址缺少 bucket: {}", spec);
+            }
+            return Ok(Self::S3 { bucket: bucket.to_string(), prefix: prefix.trim_matches('/').to_string() });
+        }
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            return Ok(Self::WebDav(spec.trim_end_matches('/').to_string()));
+        }
+        if spec.contains("://") && !spec.starts_with("file://") {
+            anyhow::bail!("不支持的远程缓存地址: {}（支持目录、http(s):// WebDAV 与 s3://）", spec);
+        }
+        Ok(Self::Directory(PathBuf::from(spec.strip_prefix("file://").unwrap_or(spec))))
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            RemoteBackend::Directory(path) => format!("目录 {}", path.display()),
+            RemoteBackend::WebDav(url) => format!("WebDAV {}", url),
+            RemoteBackend::S3 { bucket, prefix } => format!("S3 s3://{}/{}", bucket, prefix),
+        }
+    }
+}
+
+/// 远程缓存中的构建记录：构建输入摘要对应的 .rmmp/dist 产物
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub artifacts: Vec<CachedArtifact>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedArtifact {
+    pub name: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// 多台机器共享的构建缓存与下载缓存，内容按哈希寻址
+#[derive(Debug, Clone)]
+pub struct RemoteCache {
+    pub backend: RemoteBackend,
+    /// 构建成功后上传产物、下载资源后上传内容；只读的凭据可关闭
+    pub push: bool,
+    /// WebDAV 用户名或 S3 access key
+    user: Option<String>,
+    /// WebDAV 密码或 S3 secret key
+    password: Option<String>,
+    session_token: Option<String>,
+    region: String,
+    /// S3 兼容服务（MinIO 等）的地址，设置后使用路径风格
+    endpoint: Option<String>,
+}
+
+impl RemoteCache {
+    pub fn new(backend: RemoteBackend) -> Self {
+        Self {
+            backend,
+            push: true,
+            user: None,
+            password: None,
+            session_token: None,
+            region: "us-east-1".to_string(),
+            endpoint: None,
+        }
+    }
+
+    /// 按 cache.remote 等配置创建；未配置时返回 None
+    pub fn from_config() -> Result<Option<Self>> {
+        let resolver = ConfigResolver::new(None);
+        let Some(spec) = resolver.get("cache.remote") else { return Ok(None) };
+        let mut cache = Self::new(RemoteBackend::parse(&spec)?);
+        cache.push = resolver.get_bool("cache.remote_push");
+        cache.user = resolver.get("cache.remote_user");
+        cache.password = resolver.get("cache.remote_password");
+        cache.session_token = std::env::var("AWS_SESSION_TOKEN").ok().filter(|token| !token.is_empty());
+        if let Some(region) = resolver.get("cache.remote_region") {
+            cache.region = region;
+        }
+        cache.endpoint = resolver.get("cache.remote_endpoint");
+        Ok(Some(cache))
+    }
+
+    /// 读取内容哈希对应的文件；内容与哈希不符时视为未命中
+    pub fn get_object(&self, sha256: &str) -> Result<Option<Vec<u8>>> {
+        let sha256 = sha256.to_ascii_lowercase();
+        Ok(self.get(&format!("{}/{}", OBJECTS_DIR, sha256))?.filter(|bytes| sha256_bytes(bytes) == sha256))
+    }
+
+    /// 上传文件，远程已有相同内容时跳过；返回是否实际上传
+    pub fn put_object(&self, bytes: &[u8]) -> Result<bool> {
+        let key = format!("{}/{}", OBJECTS_DIR, sha256_bytes(bytes));
+        if self.exists(&key)? {
+            return Ok(false);
+        }
+        self.put(&key, bytes)?;
+        Ok(true)
+    }
+
+    pub fn get_build(&self, digest: &str) -> Result<Option<BuildManifest>> {
+        match self.get(&format!("{}/{}.json", BUILDS_DIR, digest))? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes).context("远程构建记录无法解析")?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 按构建输入摘要取回产物并写入 dist_dir；远程没有记录或缺少文件时返回 None
+    pub fn pull_build(&self, digest: &str, dist_dir: &Path) -> Result<Option<Vec<String>>> {
+        let Some(manifest) = self.get_build(digest)? else { return Ok(None) };
+        let mut files = Vec::new();
+        for artifact in &manifest.artifacts {
+            // 清单来自共享存储，不可信：名称只能是 dist 中的文件名，哈希只能是对象键
+            if !is_safe_artifact_name(&artifact.name) {
+                anyhow::bail!("远程缓存清单中的产物名 '{}' 无效", artifact.name);
+            }
+            if artifact.sha256.len() != 64 || !artifact.sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+                anyhow::bail!("远程缓存清单中 {} 的 sha256 无效", artifact.name);
+            }
+            let Some(bytes) = self.get_object(&artifact.sha256)? else { return Ok(None) };
+            files.push((artifact.name.clone(), bytes));
+        }
+        // 全部取回后再写入，避免留下不完整的产物
+        fs::create_dir_all(dist_dir)?;
+        for (name, bytes) in &files {
+            fs::write(dist_dir.join(name), bytes)?;
+        }
+        Ok(Some(files.into_iter().map(|(name, _)| name).collect()))
+    }
+
+    /// 上传 dist_dir 中的产物并记录构建输入摘要，返回实际上传的文件数
+    pub fn push_build(&self, digest: &str, dist_dir: &Path, artifacts: &[String]) -> Result<usize> {
+        let mut uploaded = 0;
+        let mut entries = Vec::new();
+        for name in artifacts {
+            let bytes = fs::read(dist_dir.join(name)).with_context(|| format!("无法读取产物 {}", name))?;
+            if self.put_object(&bytes)? {
+                uploaded += 1;
+            }
+            entries.push(CachedArtifact { name: name.clone(), sha256: sha256_bytes(&bytes), size: bytes.len() as u64 });
+        }
+        let manifest = BuildManifest { artifacts: entries, created_at: chrono::Utc::now().to_rfc3339() };
+        self.put(&format!("{}/{}.json", BUILDS_DIR, digest), &serde_json::to_vec_pretty(&manifest)?)?;
+        Ok(uploaded)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match &self.backend {
+            RemoteBackend::Directory(root) => match fs::read(root.join(key)) {
+                Ok(bytes) => Ok(Some(bytes)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e).with_context(|| format!("无法读取远程缓存 {}", root.join(key).display())),
+            },
+            _ => self.request(Method::GET, key, None),
+        }
+    }
+
+    fn exists(&self, key: &str) -> Result<bool> {
+        match &self.backend {
+            RemoteBackend::Directory(root) => Ok(root.join(key).is_file()),
+            _ => Ok(self.request(Method::HEAD, key, None)?.is_some()),
+        }
+    }
+
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        match &self.backend {
+            RemoteBackend::Directory(root) => {
+                // 先写临时文件再改名，多台机器同时写入时读到的总是完整文件
+                let path = root.join(key);
+                let parent = path.parent().unwrap_or(root);
+                fs::create_dir_all(parent)?;
+                let temp = parent.join(format!(".{}.{}.tmp", path.file_name().unwrap_or_default().to_string_lossy(), std::process::id()));
+                fs::write(&temp, bytes)?;
+                fs::rename(&temp, &path).with_context(|| format!("无法写入远程缓存 {}", path.display()))
+            }
+            RemoteBackend::WebDav(_) => {
+                // WebDAV 需要先创建目录；已存在时服务端返回 405，忽略
+                if let Some((dir, _)) = key.rsplit_once('/') {
+                    let _ = self.request(Method::from_bytes(b"MKCOL")?, &format!("{}/", dir), None);
+                }
+                self.request(Method::PUT, key, Some(bytes)).map(|_| ())
+            }
+            RemoteBackend::S3 { .. } => self.request(Method::PUT, key, Some(bytes)).map(|_| ()),
+        }
+    }
+
+    /// WebDAV 或 S3 请求；404 返回 None
+    fn request(&self, method: Method, key: &str, body: Option<&[u8]>) -> Result<Option<Vec<u8>>> {
+        let (url, headers) = self.target(method.as_str(), key, body)?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        runtime.block_on(async {
+            let client = reqwest::Client::builder()
+                .timeout(REMOTE_TIMEOUT)
+                .user_agent(concat!("rmm/", env!("CARGO_PKG_VERSION")))
+                .build()?;
+            let mut request = client.request(method.clone(), &url);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            if let (RemoteBackend::WebDav(_), Some(user)) = (&self.backend, &self.user) {
+                request = request.basic_auth(user, self.password.as_ref());
+            }
+            if let Some(body) = body {
+                request = request.body(body.to_vec());
+            }
+            let response = request.send().await.with_context(|| format!("{} {} 失败", method, url))?;
+            match response.status() {
+                StatusCode::NOT_FOUND => Ok(None),
+                status if status.is_success() => Ok(Some(response.bytes().await?.to_vec())),
+                status => anyhow::bail!("{} {} 失败: HTTP {}", method, url, status),
+            }
+        })
+    }
+
+    /// 请求地址与附加的请求头（S3 签名）
+    fn target(&self, method: &str, key: &str, body: Option<&[u8]>) -> Result<(String, Vec<(String, String)>)> {
+        match &self.backend {
+            RemoteBackend::WebDav(base) => Ok((format!("{}/{}", base, key), Vec::new())),
+            RemoteBackend::S3 { bucket, prefix } => {
+                let (Some(access_key), Some(secret_key)) = (&self.user, &self.password) else {
+                    anyhow::bail!("S3 远程缓存需要 AWS_ACCESS_KEY_ID 与 AWS_SECRET_ACCESS_KEY（或 RMM_REMOTE_CACHE_USER / RMM_REMOTE_CACHE_PASSWORD）");
+                };
+                let object = if prefix.is_empty() { key.to_string() } else { format!("{}/{}", prefix, key) };
+                let (base, path) = match &self.endpoint {
+                    Some(endpoint) => (endpoint.trim_end_matches('/').to_string(), format!("/{}/{}", bucket, uri_encode_path(&object))),
+                    None => (format!("https://{}.s3.{}.amazonaws.com", bucket, self.region), format!("/{}", uri_encode_path(&object))),
+                };
+                let host = base.split_once("://").map_or(base.as_str(), |(_, rest)| rest).to_string();
+                let request = S3Request {
+                    method,
+                    host: &host,
+                    path: &path,
+                    payload_hash: &sha256_bytes(body.unwrap_or_default()),
+                    region: &self.region,
+                };
+                let headers = request.sign(&chrono::Utc::now(), access_key, secret_key, self.session_token.as_deref());
+                Ok((format!("{}{}", base, path), headers))
+            }
+            RemoteBackend::Directory(_) => unreachable!("目录缓存不发送请求"),
+        }
+    }
+}
+
+/// 按 AWS 的规则编码对象路径，保留 /
+fn uri_encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// 需要 Signature Version 4 签名的 S3 请求
+pub(crate) struct S3Request<'a> {
+    pub method: &'a str,
+    pub host: &'a str,
+    pub path: &'a str,
+    pub payload_hash: &'a str,
+    pub region: &'a str,
+}
+
+impl S3Request<'_> {
+    /// 返回需要附加的请求头（不含 host，由 HTTP 客户端设置）
+    pub fn sign(&self, now: &chrono::DateTime<chrono::Utc>, access_key: &str, secret_key: &str, session_token: Option<&str>) -> Vec<(String, String)> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = &amz_date[..8];
+        let mut headers = vec![
+            ("host".to_string(), self.host.to_string()),
+            ("x-amz-content-sha256".to_string(), self.payload_hash.to_string()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        if let Some(token) = session_token {
+            headers.push(("x-amz-security-token".to_string(), token.to_string()));
+        }
+        let signed_headers = headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+        let canonical_request = format!(
+            "{}\n{}\n\n{}\n{}\n{}",
+            self.method, self.path, canonical_headers, signed_headers, self.payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, sha256_bytes(canonical_request.as_bytes()));
+        let mut key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+        for part in [self.region, "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = to_hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+        headers.push((
+            "authorization".to_string(),
+            format!("AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}", access_key, scope, signed_headers, signature),
+        ));
+        headers.retain(|(name, _)| name != "host");
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_remote_cache_backend() {
+        // RFC 4231 测试用例 2
+        assert_eq!(
+            to_hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        assert_eq!(RemoteBackend::parse("s3://bucket/rmm/cache/").unwrap(), RemoteBackend::S3 { bucket: "bucket".to_string(), prefix: "rmm/cache".to_string() });
+        assert_eq!(RemoteBackend::parse("https://dav.example.com/rmm/").unwrap(), RemoteBackend::WebDav("https://dav.example.com/rmm".to_string()));
+        assert_eq!(RemoteBackend::parse("file:///srv/cache").unwrap(), RemoteBackend::Directory(PathBuf::from("/srv/cache")));
+        assert!(RemoteBackend::parse("s3://").is_err());
+        assert!(RemoteBackend::parse("ftp://example.com").is_err());
+
+        // 签名请求头：不含 host，签名随内容变化
+        let now = chrono::DateTime::parse_from_rfc3339("2013-05-24T00:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let request = S3Request { method: "GET", host: "bucket.s3.us-east-1.amazonaws.com", path: "/objects/abc", payload_hash: &sha256_bytes(b""), region: "us-east-1" };
+        let headers = request.sign(&now, "AKID", "SECRET", None);
+        let authorization = &headers.iter().find(|(name, _)| name == "authorization").unwrap().1;
+        assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKID/20130524/us-east-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="));
+        assert!(headers.iter().all(|(name, _)| name != "host"));
+        assert!(headers.contains(&("x-amz-date".to_string(), "20130524T000000Z".to_string())));
+        let other = S3Request { path: "/objects/def", ..request };
+        assert_ne!(other.sign(&now, "AKID", "SECRET", None), headers);
+
+        // 目录后端：按内容哈希存取，重复上传跳过
+        let dir = tempdir().unwrap();
+        let cache = RemoteCache::new(RemoteBackend::Directory(dir.path().to_path_buf()));
+        assert!(cache.put_object(b"busybox").unwrap());
+        assert!(!cache.put_object(b"busybox").unwrap());
+        assert_eq!(cache.get_object(&sha256_bytes(b"busybox")).unwrap().unwrap(), b"busybox");
+        assert_eq!(cache.get_object(&sha256_bytes(b"missing")).unwrap(), None);
+        assert_eq!(cache.get_build("0000").unwrap(), None);
+    }
+}
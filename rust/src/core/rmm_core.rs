@@ -1,15 +1,43 @@
 use anyhow::{Context, Result};
 use git2::{Repository, StatusOptions};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::env;
+use schemars::JsonSchema;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
 use toml;
 use walkdir::WalkDir;
 
+use super::settings::ConfigResolver;
+use super::storage::{canonicalize_or_absolute, RmmStorage};
+use super::project_id::{ensure_project_uuid, generate_project_uuid, read_project_uuid, write_project_uuid};
+use super::action::ActionConfig;
+use super::bootloop::BootloopGuardConfig;
+use super::encoding::LineEnding;
+use super::hashing::HashAlgorithm;
+use super::adb::KnownDevice;
+use super::i18n::CheckConfig;
+use super::notify::{CoreEvent, EventEmitter, EventSink};
+use super::release_notes::PublishConfig;
+use super::process::{CommandExt, CommandKind, TimeoutConfig};
+use super::generate::GenerateRule;
+use super::symbols::SymbolsConfig;
+use super::toml_doc::write_toml;
+use super::uninstall::UninstallConfig;
+use super::control_files::ControlConfig;
+use super::module_props::ModuleConfig;
+use super::signing::SignConfig;
+use super::lockfile::AssetConfig;
+use super::licenses::ThirdPartyConfig;
+use super::migrations::MigrationConfig;
+use super::update_json::UpdateConfig;
+use super::vcs::{analyze_vcs_info, VcsConfig};
+use super::verify::VerifyConfig;
+use super::zip_inspect::read_prop_text;
+
 /// 缓存项结构
 #[derive(Debug, Clone)]
 struct CacheItem<T> {
@@ -29,21 +57,236 @@ impl<T> CacheItem<T> {
     }
 
     fn is_expired(&self) -> bool {
-        Instant::now() > self.expires_at
+        Instant::now() >= self.expires_at
+    }
+}
+
+/// 缓存默认有效期（秒）
+const DEFAULT_CACHE_TTL: u64 = 60;
+
+/// meta.toml 中的 [cache]：RmmCore 各缓存的有效期（秒），0 表示不缓存
+///
+/// 有效期内不会重新读取文件；项目位于网络文件系统、可能被其他机器修改时，可调低 project
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct CacheTtlConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<u64>,
+}
+
+/// RmmCore 的缓存
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    /// meta.toml
+    Meta,
+    /// 各项目的 rmmproject.toml
+    Project,
+    /// 各路径的仓库信息
+    Git,
+}
+
+impl CacheKind {
+    pub const ALL: [CacheKind; 3] = [CacheKind::Meta, CacheKind::Project, CacheKind::Git];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheKind::Meta => "meta",
+            CacheKind::Project => "project",
+            CacheKind::Git => "git",
+        }
+    }
+
+    /// 对应的配置项（rmm env --config 中的 cache.<name>_ttl）
+    pub fn setting(&self) -> &'static str {
+        match self {
+            CacheKind::Meta => "cache.meta_ttl",
+            CacheKind::Project => "cache.project_ttl",
+            CacheKind::Git => "cache.git_ttl",
+        }
+    }
+}
+
+/// 各缓存的有效期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtls {
+    pub meta: Duration,
+    pub project: Duration,
+    pub git: Duration,
+}
+
+impl Default for CacheTtls {
+    fn default() -> Self {
+        let ttl = Duration::from_secs(DEFAULT_CACHE_TTL);
+        Self { meta: ttl, project: ttl, git: ttl }
+    }
+}
+
+impl CacheTtls {
+    /// 按分层配置（环境变量 → meta.toml [cache]）解析，无效值使用默认的 60 秒
+    pub fn resolve() -> Self {
+        Self::from_config(&ConfigResolver::new(None))
+    }
+
+    pub fn from_config(config: &ConfigResolver) -> Self {
+        let ttl = |kind: CacheKind| {
+            let secs = config.get(kind.setting()).and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_CACHE_TTL);
+            Duration::from_secs(secs)
+        };
+        Self { meta: ttl(CacheKind::Meta), project: ttl(CacheKind::Project), git: ttl(CacheKind::Git) }
+    }
+
+    pub fn get(&self, kind: CacheKind) -> Duration {
+        match kind {
+            CacheKind::Meta => self.meta,
+            CacheKind::Project => self.project,
+            CacheKind::Git => self.git,
+        }
+    }
+}
+
+/// 单个缓存的命中计数
+#[derive(Debug, Default)]
+struct CacheCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounter {
+    fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 各缓存的命中计数（与缓存一起在常驻进程中共享）
+#[derive(Debug, Default)]
+struct CacheCounters {
+    meta: CacheCounter,
+    project: CacheCounter,
+    git: CacheCounter,
+}
+
+impl CacheCounters {
+    fn get(&self, kind: CacheKind) -> &CacheCounter {
+        match kind {
+            CacheKind::Meta => &self.meta,
+            CacheKind::Project => &self.project,
+            CacheKind::Git => &self.git,
+        }
     }
 }
 
+/// 单个缓存的统计（rmm cache stats）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CacheStats {
+    pub cache: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+    /// 当前条目数（含已过期但尚未清理的）
+    pub entries: usize,
+    pub expired: usize,
+    pub ttl_secs: u64,
+}
+
 /// Meta.toml 文件结构
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct MetaConfig {
     pub email: String,
     pub username: String,
     pub version: String,
     pub projects: HashMap<String, String>,
+    /// 已信任的项目：项目路径 → 构建命令摘要（命令变更后需重新信任）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub trusted: HashMap<String, String>,
+    /// 无线调试设备：主机地址 → 设备信息（rmm device connect 时记录）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub devices: BTreeMap<String, KnownDevice>,
+    /// 项目标签：项目名 → 标签（如 work、experimental、zygisk），用于批量命令筛选
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, BTreeSet<String>>,
+    /// 缓存有效期，如 [cache] project = 5
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheTtlConfig>,
+    /// 项目 UUID → 最后已知的名称与路径，项目移动后 sync 据此找回
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub uuids: BTreeMap<String, TrackedProject>,
+    /// 网络设置，如 [network] mirrors = ["https://ghproxy.net"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkConfig>,
+}
+
+/// meta.toml 中的 [network]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct NetworkConfig {
+    /// 下载镜像前缀，与原始地址竞速
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+}
+
+/// meta.toml [uuids] 中记录的项目位置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct TrackedProject {
+    pub name: String,
+    pub path: String,
+}
+
+/// 按 UUID 登记扫描到的项目的结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectTracking {
+    /// 已登记且位置未变
+    Unchanged,
+    /// 首次登记该 UUID
+    New,
+    /// 项目从 from 移动到当前路径，name 为原登记名称
+    Moved { name: String, from: String },
+    /// 与 original 处的项目 UUID 相同（复制出的项目），已为当前项目生成新的 UUID
+    Copied { original: String },
+}
+
+impl MetaConfig {
+    /// 带有任一指定标签的已注册项目（项目名 → 路径），按名称排序
+    pub fn projects_with_tags(&self, tags: &[String]) -> BTreeMap<String, String> {
+        self.projects.iter()
+            .filter(|(name, _)| self.tags.get(*name).is_some_and(|own| tags.iter().any(|t| own.contains(t))))
+            .map(|(name, path)| (name.clone(), path.clone()))
+            .collect()
+    }
+
+    /// 路径对应的已注册项目的标签
+    pub fn tags_for_path(&self, path: &Path) -> BTreeSet<String> {
+        let path = canonicalize_or_absolute(path);
+        self.projects.iter()
+            .filter(|(_, registered)| canonicalize_or_absolute(Path::new(registered)) == path)
+            .filter_map(|(name, _)| self.tags.get(name))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// 把原登记的项目迁到新路径：名称、标签与信任记录随之移动，不留下重复项
+    pub fn move_project(&mut self, previous: &TrackedProject, name: &str, path: &str) {
+        let registered_at_old_path = self.projects.get(&previous.name)
+            .is_some_and(|registered| canonicalize_or_absolute(Path::new(registered)) == canonicalize_or_absolute(Path::new(&previous.path)));
+        if registered_at_old_path {
+            self.projects.remove(&previous.name);
+        }
+        self.projects.insert(name.to_string(), path.to_string());
+        if previous.name != name
+            && let Some(tags) = self.tags.remove(&previous.name)
+        {
+            self.tags.entry(name.to_string()).or_default().extend(tags);
+        }
+        if let Some(digest) = self.trusted.remove(&previous.path) {
+            self.trusted.insert(path.to_string(), digest);
+        }
+    }
 }
 
 /// RmmProject.toml 文件结构
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct RmmProject {
     pub project: ProjectInfo,
     pub authors: Vec<Author>,
@@ -51,32 +294,49 @@ pub struct RmmProject {
     #[serde(rename = "build-system")]
     pub build_system: Option<BuildSystem>,
     #[serde(rename = "tool")]
+    #[schemars(with = "Option<HashMap<String, serde_json::Value>>")]
     pub tool: Option<HashMap<String, toml::Value>>,
+    /// 安装后验证条件，如 [verify]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<VerifyConfig>,
+    /// 覆盖安装旧版本时执行的数据迁移脚本，如 [[migrations]]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub migrations: Vec<MigrationConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ProjectInfo {
     pub id: String,
     pub description: String,
     pub readme: String,
     pub changelog: String,
     pub license: String,
+    /// 模块依赖，如 "other_mod>=1.2"、"lib @ ../lib"
     pub dependencies: Vec<String>,
+    /// 脚本/二进制依赖，如 "busybox==1.36 @ https://…"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub script_dependencies: Vec<String>,
+    /// 关键词（初始化时取自 GitHub 仓库主题）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
     pub scripts: Option<HashMap<String, String>>,
+    /// 项目的稳定标识，文件夹移动或改名后 sync 据此找回登记
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct Author {
     pub name: String,
     pub email: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct UrlsInfo {
     pub github: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct BuildSystem {
     pub requires: Vec<String>,
     #[serde(rename = "build-backend")]
@@ -98,25 +358,87 @@ pub struct ModuleProp {
 }
 
 /// Rmake.toml 文件结构
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct RmakeConfig {
     pub build: BuildConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update: Option<UpdateConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<ActionConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check: Option<CheckConfig>,
+    /// 外部命令超时（秒），如 [timeouts] adb = 600
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeouts: Option<TimeoutConfig>,
+    /// 发布配置，如 [publish.notes]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish: Option<PublishConfig>,
+    /// 卸载清理，如 [uninstall] paths = ["/data/adb/demo"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uninstall: Option<UninstallConfig>,
+    /// 启动失败守护，如 [bootloop_guard] max_attempts = 3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootloop_guard: Option<BootloopGuardConfig>,
+    /// 模块控制文件，如 [control] skip_mount = "auto"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control: Option<ControlConfig>,
+    /// 构建时下载的远程文件，如 [assets.busybox]，哈希记录在 rmm.lock
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub assets: BTreeMap<String, AssetConfig>,
+    /// 项目中直接提交的第三方文件及其许可证，如 [third_party.curl]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub third_party: BTreeMap<String, ThirdPartyConfig>,
+    /// 版本控制后端，如 [vcs] backend = "none"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vcs: Option<VcsConfig>,
+    /// 模块运行时设置的系统属性，如 [module.props]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<ModuleConfig>,
+    /// 产物签名，如 [sign] key = "release"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sign: Option<SignConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct BuildConfig {
     pub include: Vec<String>,
+    /// 排除规则（gitignore 风格）：不含 / 的模式匹配任一层的名称，含 / 的模式从项目根目录匹配，
+    /// ** 匹配任意层目录，/ 结尾只匹配目录，! 开头重新包含之前排除的路径
     pub exclude: Vec<String>,
+    /// 内置排除预设，如 ["node", "python"]，同时作用于模块与源代码打包
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_presets: Vec<String>,
+    /// 默认排除规则（VCS 目录、系统垃圾文件、编辑器目录等）的例外，匹配的路径照常打包
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
     pub prebuild: Vec<String>,
     pub build: Vec<String>,
     pub postbuild: Vec<String>,
     pub src: Option<SrcConfig>,
     pub scripts: Option<HashMap<String, String>>,
+    /// 拆分调试符号包，如 [build.symbols]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbols: Option<SymbolsConfig>,    /// 每次构建时生成的文件，如 [[build.generate]]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generate: Vec<GenerateRule>,
+    /// 同时生成可在第三方 Recovery 中刷入的卡刷包（<id>-<versionCode>-recovery.zip）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub recovery: bool,
+    /// zip 条目权限覆盖：glob → 权限，如 "system/bin/*" = 0o755（默认沿用源文件权限）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub permissions: BTreeMap<String, u32>,
+    /// 打包前在模块根目录生成 rmm-checksums.json，值为哈希算法（blake3 / sha256）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksums: Option<HashAlgorithm>,
+    /// 行尾策略覆盖：glob → lf / crlf / keep，如 "*.ini" = "crlf"（默认文本文件转为 LF）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub line_endings: BTreeMap<String, LineEnding>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct SrcConfig {
     pub include: Vec<String>,
+    /// 源代码包的排除规则，语义与 [build] exclude 相同
     pub exclude: Vec<String>,
 }
 
@@ -129,7 +451,18 @@ pub struct ProjectScanResult {
     pub git_info: Option<GitInfo>,
 }
 
-/// Git 仓库信息
+/// 库 API 的扫描选项
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// 要扫描的根路径
+    pub paths: Vec<PathBuf>,
+    /// 最大扫描深度，None 表示无限制
+    pub max_depth: Option<usize>,
+    /// 只返回有效项目（存在 .rmmp/Rmake.toml）
+    pub only_valid: bool,
+}
+
+/// 仓库信息（Git，或 vcs 后端提供的 hg / 普通目录信息）
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct GitInfo {
     pub repo_root: PathBuf,
@@ -139,6 +472,8 @@ pub struct GitInfo {
     pub has_uncommitted_changes: bool,
     pub last_commit_hash: Option<String>,
     pub last_commit_message: Option<String>,
+    /// 信息来源的版本控制后端：git / hg / none
+    pub vcs: &'static str,
 }
 
 /// Git 分析器
@@ -146,43 +481,65 @@ pub struct GitAnalyzer;
 
 impl GitAnalyzer {
     /// 分析给定路径的 Git 信息
+    /// 支持 .git 为 gitdir 指向文件的布局（worktree、子模块）、分离 HEAD、尚无提交的仓库与浅克隆
     pub fn analyze_git_info(path: &Path) -> Result<Option<GitInfo>> {
-        let git_root = Self::find_git_root(path)?;
-        
-        if let Some(repo_root) = git_root {
-            let repo = Repository::open(&repo_root)
-                .with_context(|| format!("Failed to open Git repository at {}", repo_root.display()))?;
-            
-            let relative_path = path.strip_prefix(&repo_root)
-                .unwrap_or(Path::new(""))
-                .to_path_buf();
-            
-            let branch = Self::get_current_branch(&repo)?;
-            let remote_url = Self::get_remote_url(&repo)?;
-            let has_uncommitted_changes = Self::has_uncommitted_changes(&repo)?;
-            let (last_commit_hash, last_commit_message) = Self::get_last_commit_info(&repo)?;
-            
-            Ok(Some(GitInfo {
-                repo_root,
-                relative_path,
-                branch,
-                remote_url,
-                has_uncommitted_changes,
-                last_commit_hash,
-                last_commit_message,
-            }))
+        let path = canonicalize_or_absolute(path);
+        let Some(marker_root) = Self::find_git_root(&path)? else {
+            return Ok(None);
+        };
+
+        let repo = Repository::discover(&path)
+            .with_context(|| format!("Failed to open Git repository at {}", marker_root.display()))?;
+        // 裸仓库没有工作目录
+        let Some(workdir) = repo.workdir() else {
+            return Ok(None);
+        };
+        // 与 git 一致：.git 为指向文件时，工作目录就是该文件所在目录（libgit2 可能推断为 gitdir 的上级）
+        let repo_root = if marker_root.join(".git").is_file() {
+            if canonicalize_or_absolute(workdir) != marker_root {
+                repo.set_workdir(&marker_root, false)?;
+            }
+            marker_root
         } else {
-            Ok(None)
-        }
+            canonicalize_or_absolute(workdir)
+        };
+
+        let relative_path = path.strip_prefix(&repo_root)
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+
+        let branch = Self::get_current_branch(&repo)?;
+        let remote_url = Self::get_remote_url(&repo)?;
+        let has_uncommitted_changes = Self::has_uncommitted_changes(&repo)?;
+        let (last_commit_hash, last_commit_message) = Self::get_last_commit_info(&repo)?;
+
+        Ok(Some(GitInfo {
+            repo_root,
+            relative_path,
+            branch,
+            remote_url,
+            has_uncommitted_changes,
+            last_commit_hash,
+            last_commit_message,
+            vcs: "git",
+        }))
     }
     
-    /// 查找 Git 根目录
+    /// 查找 Git 根目录（包含 .git 目录或 gitdir 指向文件的最近一级目录）
     pub fn find_git_root(path: &Path) -> Result<Option<PathBuf>> {
         let mut current = path.to_path_buf();
         
         loop {
             let git_dir = current.join(".git");
-            if git_dir.exists() {
+            if git_dir.is_dir() {
+                return Ok(Some(current));
+            }
+            if git_dir.is_file() {
+                // worktree / 子模块：.git 文件内容为 "gitdir: <路径>"
+                let target = Self::read_gitdir_file(&git_dir)?;
+                if !target.exists() {
+                    anyhow::bail!("{} 指向的 Git 目录不存在: {}", git_dir.display(), target.display());
+                }
                 return Ok(Some(current));
             }
             
@@ -195,25 +552,82 @@ impl GitAnalyzer {
         
         Ok(None)
     }
+
+    /// 解析 .git 文件中的 gitdir 指向（相对路径相对于 .git 文件所在目录）
+    fn read_gitdir_file(git_file: &Path) -> Result<PathBuf> {
+        let content = fs::read_to_string(git_file)
+            .with_context(|| format!("Failed to read {}", git_file.display()))?;
+        let target = content.lines()
+            .find_map(|line| line.trim().strip_prefix("gitdir:"))
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("{} 不是有效的 gitdir 文件", git_file.display()))?;
+        let base = git_file.parent().unwrap_or(Path::new(""));
+        Ok(base.join(target))
+    }
     
     /// 获取当前分支名
     fn get_current_branch(repo: &Repository) -> Result<String> {
-        let head = repo.head()
-            .with_context(|| "Failed to get HEAD reference")?;
-        
-        if let Some(branch_name) = head.shorthand() {
-            Ok(branch_name.to_string())
-        } else {
-            Ok("HEAD".to_string())
+        let head = match repo.head() {
+            Ok(head) => head,
+            // 尚无提交：HEAD 指向未诞生的分支
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch || e.code() == git2::ErrorCode::NotFound => {
+                let head = repo.find_reference("HEAD")
+                    .with_context(|| "Failed to get HEAD reference")?;
+                let name = head.symbolic_target()
+                    .and_then(|target| target.strip_prefix("refs/heads/"))
+                    .unwrap_or("HEAD");
+                return Ok(name.to_string());
+            }
+            Err(e) => return Err(e).with_context(|| "Failed to get HEAD reference"),
+        };
+
+        if head.is_branch() {
+            return Ok(head.shorthand().unwrap_or("HEAD").to_string());
+        }
+        // 分离 HEAD（CI 检出、标签检出）：查找指向同一提交的分支，再参考 CI 环境变量
+        Ok(head.target()
+            .and_then(|oid| Self::branch_at(repo, oid))
+            .or_else(Self::ci_branch)
+            .unwrap_or_else(|| "HEAD".to_string()))
+    }
+
+    /// 查找指向给定提交的分支，本地分支优先，远程分支去掉远程名前缀
+    fn branch_at(repo: &Repository, oid: git2::Oid) -> Option<String> {
+        let mut remote_match = None;
+        for (branch, kind) in repo.branches(None).ok()?.flatten() {
+            if branch.get().target() != Some(oid) {
+                continue;
+            }
+            let Ok(Some(name)) = branch.name() else { continue };
+            match kind {
+                git2::BranchType::Local => return Some(name.to_string()),
+                git2::BranchType::Remote if !name.ends_with("/HEAD") && remote_match.is_none() => {
+                    remote_match = Some(name.split_once('/').map(|(_, b)| b).unwrap_or(name).to_string());
+                }
+                git2::BranchType::Remote => {}
+            }
         }
+        remote_match
+    }
+
+    /// CI 环境提供的分支名（GitHub Actions、GitLab CI）
+    fn ci_branch() -> Option<String> {
+        ["GITHUB_HEAD_REF", "GITHUB_REF_NAME", "CI_COMMIT_REF_NAME"].iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.trim().is_empty())
     }
     
-    /// 获取远程仓库 URL
+    /// 获取远程仓库 URL，优先 origin
     fn get_remote_url(repo: &Repository) -> Result<Option<String>> {
         let remotes = repo.remotes()
             .with_context(|| "Failed to get remotes")?;
         
-        if let Some(remote_name) = remotes.get(0) {
+        let remote_name = remotes.iter()
+            .flatten()
+            .find(|name| *name == "origin")
+            .or_else(|| remotes.iter().flatten().next());
+        if let Some(remote_name) = remote_name {
             let remote = repo.find_remote(remote_name)
                 .with_context(|| format!("Failed to find remote: {}", remote_name))?;
             
@@ -225,34 +639,24 @@ impl GitAnalyzer {
         Ok(None)
     }
     
-    /// 检查是否有未提交的更改
+    /// 检查是否有未提交的更改；无法读取状态（如浅克隆缺少对象）时视为没有更改
     fn has_uncommitted_changes(repo: &Repository) -> Result<bool> {
         let mut opts = StatusOptions::new();
         opts.include_ignored(false);
         opts.include_untracked(true);
         
-        let statuses = repo.statuses(Some(&mut opts))
-            .with_context(|| "Failed to get repository status")?;
-        
-        Ok(!statuses.is_empty())
+        Ok(repo.statuses(Some(&mut opts)).is_ok_and(|statuses| !statuses.is_empty()))
     }
     
-    /// 获取最后一次提交信息
+    /// 获取最后一次提交信息；尚无提交时返回 None，提交对象缺失（浅克隆、部分克隆）时只返回哈希
     fn get_last_commit_info(repo: &Repository) -> Result<(Option<String>, Option<String>)> {
-        let head = repo.head()
-            .with_context(|| "Failed to get HEAD reference")?;
-        
-        if let Some(oid) = head.target() {
-            let commit = repo.find_commit(oid)
-                .with_context(|| "Failed to find commit")?;
-            
-            let hash = oid.to_string();
-            let message = commit.message().unwrap_or("").to_string();
-            
-            Ok((Some(hash), Some(message)))
-        } else {
-            Ok((None, None))
-        }
+        let Some(oid) = repo.head().ok().and_then(|head| head.target()) else {
+            return Ok((None, None));
+        };
+        let message = repo.find_commit(oid)
+            .ok()
+            .map(|commit| commit.message().unwrap_or("").to_string());
+        Ok((Some(oid.to_string()), message))
     }
 }
 
@@ -262,41 +666,72 @@ pub struct RmmCore {
     rmm_root: PathBuf,
     meta_cache: Arc<Mutex<Option<CacheItem<MetaConfig>>>>,
     project_cache: Arc<Mutex<HashMap<String, CacheItem<RmmProject>>>>,
-    cache_ttl: Duration,
+    cache_ttls: CacheTtls,
+    cache_counters: Arc<CacheCounters>,
     /// Git 信息缓存
     git_cache: Arc<Mutex<HashMap<PathBuf, (GitInfo, Instant)>>>,
-
+    /// 事件发送端（扫描警告、脚本输出等），默认不订阅
+    events: EventEmitter,
 }
 
-impl RmmCore {    /// 创建新的 RmmCore 实例
+/// 常驻进程（rmm daemon）中所有 RmmCore 实例共享的缓存
+static SHARED_CACHES: OnceLock<RmmCore> = OnceLock::new();
+
+impl RmmCore {    /// 创建新的 RmmCore 实例；启用共享缓存后复用常驻进程中的缓存
     pub fn new() -> Self {
+        match SHARED_CACHES.get() {
+            Some(shared) => Self {
+                rmm_root: shared.rmm_root.clone(),
+                meta_cache: Arc::clone(&shared.meta_cache),
+                project_cache: Arc::clone(&shared.project_cache),
+                cache_ttls: shared.cache_ttls,
+                cache_counters: Arc::clone(&shared.cache_counters),
+                git_cache: Arc::clone(&shared.git_cache),
+                events: EventEmitter::default(),
+            },
+            None => Self::with_fresh_caches(),
+        }
+    }
+
+    /// 让此后创建的 RmmCore 共享同一份缓存（meta.toml、项目信息、Git 信息），供 rmm daemon 使用
+    pub fn enable_shared_caches() {
+        SHARED_CACHES.get_or_init(Self::with_fresh_caches);
+    }
+
+    /// 当前进程是否为共享缓存的常驻进程
+    pub fn shared_caches_enabled() -> bool {
+        SHARED_CACHES.get().is_some()
+    }
+
+    fn with_fresh_caches() -> Self {
         Self {
             rmm_root: Self::get_rmm_root_path(),
             meta_cache: Arc::new(Mutex::new(None)),
             project_cache: Arc::new(Mutex::new(HashMap::new())),
-            cache_ttl: Duration::from_secs(60), // 60秒缓存
+            cache_ttls: CacheTtls::resolve(),
+            cache_counters: Arc::new(CacheCounters::default()),
             git_cache: Arc::new(Mutex::new(HashMap::new())),
+            events: EventEmitter::default(),
         }
     }
 
+    /// 订阅 RmmCore 发出的事件；CLI 使用 ConsoleSink 打印
+    pub fn with_event_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.events = EventEmitter::new(sink);
+        self
+    }
+
+    /// 更换事件订阅者
+    pub fn set_event_sink(&mut self, sink: Arc<dyn EventSink>) {
+        self.events = EventEmitter::new(sink);
+    }
+
     /// 功能一：获取 RMM_ROOT 路径
-    /// 尝试读取环境变量 RMM_ROOT，如果没有返回默认值：~/data/adb/.rmm/
+    /// 优先读取环境变量 RMM_ROOT，其次为 Termux 前缀、XDG 数据目录或 %APPDATA%（见 RmmStorage）
     pub fn get_rmm_root(&self) -> PathBuf {
         self.rmm_root.clone()
     }    fn get_rmm_root_path() -> PathBuf {
-        if let Ok(root) = env::var("RMM_ROOT") {
-            PathBuf::from(root)
-        } else {
-            let home = env::var("HOME")
-                .or_else(|_| env::var("USERPROFILE"))
-                .unwrap_or_else(|_| String::from("."));
-            // 确保路径构建的正确性，强制重新构建路径字符串
-            let mut path = PathBuf::from(home);
-            path.push("data");
-            path.push("adb");
-            path.push(".rmm");
-            path
-        }
+        RmmStorage::resolve().root().to_path_buf()
     }
 
     /// 获取 meta.toml 文件路径
@@ -309,10 +744,12 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
             let cache = self.meta_cache.lock().unwrap();
             if let Some(cached) = cache.as_ref() {
                 if !cached.is_expired() {
+                    self.cache_counters.meta.record(true);
                     return Ok(cached.data.clone());
                 }
             }
         }
+        self.cache_counters.meta.record(false);
 
         // 读取并解析文件
         let meta_path = self.get_meta_path();
@@ -325,7 +762,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
         // 更新缓存
         {
             let mut cache = self.meta_cache.lock().unwrap();
-            *cache = Some(CacheItem::new(meta.clone(), self.cache_ttl));
+            *cache = Some(CacheItem::new(meta.clone(), self.cache_ttls.meta));
         }
 
         Ok(meta)
@@ -341,15 +778,14 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
                 .with_context(|| format!("Failed to create directory {}", parent.display()))?;
         }
 
-        let content = toml::to_string_pretty(meta)
-            .with_context(|| "Failed to serialize meta config")?;
-            fs::write(&meta_path, content)
+        // 保留用户在 meta.toml 中的注释与键顺序
+        write_toml(&meta_path, meta)
             .with_context(|| format!("Failed to write meta.toml to {}", meta_path.display()))?;
 
         // 更新缓存
         {
             let mut cache = self.meta_cache.lock().unwrap();
-            *cache = Some(CacheItem::new(meta.clone(), self.cache_ttl));
+            *cache = Some(CacheItem::new(meta.clone(), self.cache_ttls.meta));
         }
 
         Ok(())
@@ -383,8 +819,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
             // 1. 检查项目名称是否符合规范
             let name_valid = is_valid_project_name(name);
             if !name_valid {
-                #[cfg(debug_assertions)]
-                eprintln!("❌ 项目名称 '{}' 不符合规范", name);
+                self.events.emit(CoreEvent::ProjectInvalid { name: name.clone(), reason: "名称不符合规范".to_string() });
                 results.insert(name.clone(), false);
                 continue;
             }
@@ -396,8 +831,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
                 ".rmmp", "out", "bin", "obj", ".next", "coverage"
             ];
             if blacklisted_names.contains(&name.as_str()) {
-                #[cfg(debug_assertions)]
-                eprintln!("🚫 项目名称 '{}' 在黑名单中", name);
+                self.events.emit(CoreEvent::ProjectInvalid { name: name.clone(), reason: "名称在黑名单中".to_string() });
                 results.insert(name.clone(), false);
                 continue;
             }
@@ -406,8 +840,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
             if project_path.ancestors().any(|ancestor| {
                 ancestor.file_name().map_or(false, |name| name == ".rmmp")
             }) {
-                #[cfg(debug_assertions)]
-                eprintln!("🚫 项目路径 '{}' 位于 .rmmp 构建目录下", path);
+                self.events.emit(CoreEvent::ProjectInvalid { name: name.clone(), reason: format!("路径 '{}' 位于 .rmmp 构建目录下", path) });
                 results.insert(name.clone(), false);
                 continue;
             }
@@ -420,8 +853,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
                            project_path.join(".rmmp").join("Rmake.toml").exists();
             
             if !path_valid {
-                #[cfg(debug_assertions)]
-                eprintln!("❌ 项目路径 '{}' 无效或缺少必要文件", path);
+                self.events.emit(CoreEvent::ProjectInvalid { name: name.clone(), reason: format!("路径 '{}' 无效或缺少必要文件", path) });
                 results.insert(name.clone(), false);
                 continue;
             }
@@ -429,8 +861,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
             // 5. 检查路径重复（使用 canonicalize 解析真实路径）
             if let Ok(canonical_path) = project_path.canonicalize() {
                 if canonical_paths.contains(&canonical_path) {
-                    #[cfg(debug_assertions)]
-                    eprintln!("🚫 项目 '{}' 路径重复: {}", name, canonical_path.display());
+                    self.events.emit(CoreEvent::ProjectInvalid { name: name.clone(), reason: format!("路径重复: {}", canonical_path.display()) });
                     results.insert(name.clone(), false);
                     continue;
                 }
@@ -446,6 +877,45 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
     pub fn scan_projects(&self, scan_path: &Path, max_depth: Option<usize>) -> Result<Vec<ProjectScanResult>> {
         let mut results = Vec::new();
         let mut canonical_paths = std::collections::HashSet::new(); // 防止重复路径
+        self.scan_path_into(scan_path, max_depth, &mut canonical_paths, &mut |result| {
+            results.push(result);
+            Ok(())
+        })?;
+        Ok(results)
+    }
+
+    /// 按选项扫描多个路径（跨路径去重），每找到一个项目调用 progress(结果, 已找到数量)；
+    /// progress 返回错误时中止扫描
+    pub fn scan_projects_with(
+        &self,
+        options: &ScanOptions,
+        progress: &mut dyn FnMut(&ProjectScanResult, usize) -> Result<()>,
+    ) -> Result<Vec<ProjectScanResult>> {
+        let mut results = Vec::new();
+        let mut canonical_paths = std::collections::HashSet::new();
+        for scan_path in &options.paths {
+            if !scan_path.exists() {
+                anyhow::bail!("扫描路径不存在: {}", scan_path.display());
+            }
+            self.scan_path_into(scan_path, options.max_depth, &mut canonical_paths, &mut |result| {
+                if options.only_valid && !result.is_valid {
+                    return Ok(());
+                }
+                progress(&result, results.len() + 1)?;
+                results.push(result);
+                Ok(())
+            })?;
+        }
+        Ok(results)
+    }
+
+    fn scan_path_into(
+        &self,
+        scan_path: &Path,
+        max_depth: Option<usize>,
+        canonical_paths: &mut std::collections::HashSet<PathBuf>,
+        on_found: &mut dyn FnMut(ProjectScanResult) -> Result<()>,
+    ) -> Result<()> {
         
         let walker = if let Some(depth) = max_depth {
             WalkDir::new(scan_path).max_depth(depth)
@@ -460,8 +930,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
             if path.ancestors().any(|ancestor| {
                 ancestor.file_name().map_or(false, |name| name == ".rmmp")
             }) {
-                #[cfg(debug_assertions)]
-                eprintln!("⏭️  跳过 .rmmp 目录下的路径: {}", path.display());
+                self.events.emit(CoreEvent::ScanSkipped { path: path.to_path_buf(), reason: "位于 .rmmp 目录下".to_string() });
                 continue;
             }
             
@@ -472,16 +941,20 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
                 let canonical_path = match path.canonicalize() {
                     Ok(p) => p,
                     Err(_) => {
-                        #[cfg(debug_assertions)]
-                        eprintln!("⚠️  无法解析路径: {}", path.display());
+                        self.events.emit(CoreEvent::ScanSkipped { path: path.to_path_buf(), reason: "无法解析路径".to_string() });
                         continue;
                     }
                 };
                 
                 // 检查路径是否已存在
                 if canonical_paths.contains(&canonical_path) {
-                    #[cfg(debug_assertions)]
-                    eprintln!("⏭️  跳过重复路径: {}", canonical_path.display());
+                    self.events.emit(CoreEvent::ScanSkipped { path: canonical_path, reason: "重复路径".to_string() });
+                    continue;
+                }
+
+                // meta.toml 只能保存 UTF-8 路径，非 UTF-8 路径登记后会被替换字符破坏
+                if canonical_path.to_str().is_none() {
+                    self.events.emit(CoreEvent::ScanSkipped { path: canonical_path, reason: "路径不是有效的 UTF-8".to_string() });
                     continue;
                 }
                 
@@ -490,9 +963,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
                     .unwrap_or("unknown")
                     .to_string();
                 
-                // 调试信息：打印正在验证的项目名称
-                #[cfg(debug_assertions)]
-                eprintln!("🔍 正在验证项目名称: '{}' 在路径: {} (canonical: {})", name, path.display(), canonical_path.display());
+                self.events.emit(CoreEvent::ScanCandidate { name: name.clone(), path: canonical_path.clone() });
                 
                 // 黑名单检查 - 排除构建相关目录
                 let blacklisted_names = [
@@ -501,20 +972,17 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
                     ".rmmp", "out", "bin", "obj", ".next", "coverage"
                 ];
                 if blacklisted_names.contains(&name.as_str()) {
-                    #[cfg(debug_assertions)]
-                    eprintln!("🚫 项目名称 '{}' 在黑名单中，跳过", name);
+                    self.events.emit(CoreEvent::ScanSkipped { path: canonical_path, reason: format!("项目名称 '{}' 在黑名单中", name) });
                     continue;
                 }
                 
                 // 验证项目名称格式：必须符合 ^[a-zA-Z][a-zA-Z0-9._-]+$
                 if !is_valid_project_name(&name) {
-                    #[cfg(debug_assertions)]
-                    eprintln!("❌ 项目名称 '{}' 不符合命名规则，跳过", name);
+                    self.events.emit(CoreEvent::ScanSkipped { path: canonical_path, reason: format!("项目名称 '{}' 不符合命名规则", name) });
                     continue; // 跳过不符合命名规则的项目
                 }
                 
-                #[cfg(debug_assertions)]
-                eprintln!("✅ 项目名称 '{}' 验证通过", name);
+                self.events.emit(CoreEvent::ScanAccepted { name: name.clone() });
                 
                 // 检查是否是完整的 RMM 项目
                 let rmmp_dir = path.join(".rmmp");
@@ -522,45 +990,50 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
                 let is_valid = rmmp_dir.exists() && rmake_file.exists();
                 
                 // 获取 Git 信息
-                let git_info = GitAnalyzer::analyze_git_info(path).ok().flatten();
+                let git_info = analyze_vcs_info(path).ok().flatten();
                 
                 // 记录这个路径以防重复
                 canonical_paths.insert(canonical_path.clone());
                 
-                results.push(ProjectScanResult {
+                on_found(ProjectScanResult {
                     name,
                     path: canonical_path, // 使用标准化的路径
                     is_valid,
                     git_info,
-                });
+                })?;
             }
         }
 
-        Ok(results)
+        Ok(())
     }
 
     /// 功能八：双向更新项目列表（将扫描结果同步到 meta.toml）
     pub fn sync_projects(&self, scan_paths: &[&Path], max_depth: Option<usize>) -> Result<()> {
-        let mut all_projects = HashMap::new();
-        
-        // 扫描所有路径
-        for &scan_path in scan_paths {
-            let scanned = self.scan_projects(scan_path, max_depth)?;
-            for project in scanned {
-                all_projects.insert(project.name, project.path.to_string_lossy().to_string());
-            }
-        }
-
         // 获取当前配置
         let mut meta = self.get_meta_config().unwrap_or_else(|_| MetaConfig {
             email: String::new(),
             username: String::new(),
             version: String::new(),
             projects: HashMap::new(),
+            trusted: HashMap::new(),
+            devices: BTreeMap::new(),
+            tags: BTreeMap::new(),
+            cache: None,
+            uuids: BTreeMap::new(),
+            network: None,
         });
 
-        // 更新项目列表
-        meta.projects.extend(all_projects);
+        // 扫描所有路径，按 UUID 找回移动过的项目后更新项目列表
+        for &scan_path in scan_paths {
+            let scanned = self.scan_projects(scan_path, max_depth)?;
+            for project in scanned {
+                let path = project.path.to_string_lossy().to_string();
+                if let Err(e) = self.track_project(&mut meta, &project.name, &path) {
+                    self.events.emit(CoreEvent::ScanSkipped { path: project.path.clone(), reason: format!("无法记录项目 UUID: {}", e) });
+                }
+                meta.projects.insert(project.name, path);
+            }
+        }
 
         // 保存更新
         self.update_meta_config(&meta)?;
@@ -568,6 +1041,31 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
         Ok(())
     }
 
+    /// 按 UUID 登记项目（缺少 UUID 时写入 rmmproject.toml）；
+    /// 同一 UUID 曾登记在别处时，原位置已不是该项目则视为移动，否则视为复制并为当前项目换新 UUID
+    pub fn track_project(&self, meta: &mut MetaConfig, name: &str, path: &str) -> Result<ProjectTracking> {
+        let project_path = Path::new(path);
+        let mut uuid = ensure_project_uuid(project_path)?;
+        let tracking = match meta.uuids.get(&uuid).cloned() {
+            None => ProjectTracking::New,
+            Some(previous) if canonicalize_or_absolute(Path::new(&previous.path)) == canonicalize_or_absolute(project_path) => {
+                ProjectTracking::Unchanged
+            }
+            Some(previous) if read_project_uuid(Path::new(&previous.path)).as_deref() == Some(uuid.as_str()) => {
+                uuid = generate_project_uuid();
+                write_project_uuid(project_path, &uuid)?;
+                ProjectTracking::Copied { original: previous.path }
+            }
+            Some(previous) => {
+                meta.move_project(&previous, name, path);
+                ProjectTracking::Moved { name: previous.name, from: previous.path }
+            }
+        };
+        self.project_cache.lock().unwrap().remove(path);
+        meta.uuids.insert(uuid, TrackedProject { name: name.to_string(), path: path.to_string() });
+        Ok(tracking)
+    }
+
     /// 功能九：读取项目的 rmmproject.toml
     pub fn get_project_config(&self, project_path: &Path) -> Result<RmmProject> {
         let project_key = project_path.to_string_lossy().to_string();
@@ -577,10 +1075,12 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
             let cache = self.project_cache.lock().unwrap();
             if let Some(cached) = cache.get(&project_key) {
                 if !cached.is_expired() {
+                    self.cache_counters.project.record(true);
                     return Ok(cached.data.clone());
                 }
             }
         }
+        self.cache_counters.project.record(false);
 
         let project_file = project_path.join("rmmproject.toml");
         let content = fs::read_to_string(&project_file)
@@ -592,7 +1092,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
         // 更新缓存
         {
             let mut cache = self.project_cache.lock().unwrap();
-            cache.insert(project_key, CacheItem::new(project.clone(), self.cache_ttl));
+            cache.insert(project_key, CacheItem::new(project.clone(), self.cache_ttls.project));
         }
 
         Ok(project)
@@ -602,17 +1102,14 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
     pub fn update_project_config(&self, project_path: &Path, project: &RmmProject) -> Result<()> {
         let project_file = project_path.join("rmmproject.toml");
         
-        let content = toml::to_string_pretty(project)
-            .with_context(|| "Failed to serialize project config")?;
-        
-        fs::write(&project_file, content)
+        write_toml(&project_file, project)
             .with_context(|| format!("Failed to write rmmproject.toml to {}", project_file.display()))?;
 
         // 更新缓存
         let project_key = project_path.to_string_lossy().to_string();
         {
             let mut cache = self.project_cache.lock().unwrap();
-            cache.insert(project_key, CacheItem::new(project.clone(), self.cache_ttl));
+            cache.insert(project_key, CacheItem::new(project.clone(), self.cache_ttls.project));
         }
 
         Ok(())
@@ -621,7 +1118,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
     /// 功能十：读取项目目录下的 module.prop（以 TOML 格式）
     pub fn get_module_prop(&self, project_path: &Path) -> Result<ModuleProp> {
         let prop_file = project_path.join("module.prop");
-        let content = fs::read_to_string(&prop_file)
+        let content = read_prop_text(&prop_file)
             .with_context(|| format!("Failed to read module.prop from {}", prop_file.display()))?;
         
         let prop: ModuleProp = toml::from_str(&content)
@@ -634,10 +1131,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
     pub fn update_module_prop(&self, project_path: &Path, prop: &ModuleProp) -> Result<()> {
         let prop_file = project_path.join("module.prop");
         
-        let content = toml::to_string_pretty(prop)
-            .with_context(|| "Failed to serialize module prop")?;
-        
-        fs::write(&prop_file, content)
+        write_toml(&prop_file, prop)
             .with_context(|| format!("Failed to write module.prop to {}", prop_file.display()))?;
 
         Ok(())
@@ -664,10 +1158,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
         fs::create_dir_all(&rmmp_dir)
             .with_context(|| format!("Failed to create .rmmp directory at {}", rmmp_dir.display()))?;
         
-        let content = toml::to_string_pretty(rmake)
-            .with_context(|| "Failed to serialize Rmake config")?;
-        
-        fs::write(&rmake_file, content)
+        write_toml(&rmake_file, rmake)
             .with_context(|| format!("Failed to write Rmake.toml to {}", rmake_file.display()))?;
 
         Ok(())
@@ -687,7 +1178,8 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
         let script_command = scripts.get(script_name)
             .ok_or_else(|| anyhow::anyhow!("脚本 '{}' 未找到", script_name))?;
         
-        println!("🚀 执行脚本: {}", script_name);        println!("📋 命令: {}", script_command);        
+        self.events.emit(CoreEvent::ScriptStarted { name: script_name.to_string(), command: script_command.clone() });
+
         // 执行命令 - 使用系统默认终端避免UNC路径问题
         let mut cmd = if cfg!(target_os = "windows") {
             // Windows: 使用PowerShell避免UNC路径问题
@@ -703,16 +1195,15 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
             cmd
         };
         
-        let output = cmd.output()
+        let output = cmd.output_with_timeout(CommandKind::Script)
             .with_context(|| format!("执行脚本 '{}' 失败", script_name))?;
         
         // 输出结果
-        if !output.stdout.is_empty() {
-            print!("{}", String::from_utf8_lossy(&output.stdout));
-        }
-        
-        if !output.stderr.is_empty() {
-            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        if !output.stdout.is_empty() || !output.stderr.is_empty() {
+            self.events.emit(CoreEvent::ScriptOutput {
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
         }
         
         // 检查执行结果
@@ -724,7 +1215,7 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
             ));
         }
         
-        println!("✅ 脚本 '{}' 执行成功", script_name);
+        self.events.emit(CoreEvent::ScriptFinished { name: script_name.to_string() });
         Ok(())
     }
     
@@ -771,6 +1262,42 @@ impl RmmCore {    /// 创建新的 RmmCore 实例
 
         (meta_cached, project_count)
     }
+
+    /// 各缓存的命中 / 未命中次数、条目数与有效期
+    pub fn cache_statistics(&self) -> Vec<CacheStats> {
+        CacheKind::ALL.iter().map(|&kind| {
+            let (entries, expired) = match kind {
+                CacheKind::Meta => {
+                    let cache = self.meta_cache.lock().unwrap();
+                    (cache.iter().count(), cache.iter().filter(|c| c.is_expired()).count())
+                }
+                CacheKind::Project => {
+                    let cache = self.project_cache.lock().unwrap();
+                    (cache.len(), cache.values().filter(|c| c.is_expired()).count())
+                }
+                CacheKind::Git => {
+                    let cache = self.git_cache.lock().unwrap();
+                    (cache.len(), cache.values().filter(|(_, time)| time.elapsed() >= self.cache_ttls.git).count())
+                }
+            };
+            let counter = self.cache_counters.get(kind);
+            CacheStats {
+                cache: kind.as_str(),
+                hits: counter.hits.load(Ordering::Relaxed),
+                misses: counter.misses.load(Ordering::Relaxed),
+                entries,
+                expired,
+                ttl_secs: self.cache_ttls.get(kind).as_secs(),
+            }
+        }).collect()
+    }
+
+    /// 调整缓存有效期（已缓存的 meta / 项目条目保持写入时的有效期），运行时的有效期来自 [cache] 配置
+    #[cfg(test)]
+    pub fn with_cache_ttls(mut self, ttls: CacheTtls) -> Self {
+        self.cache_ttls = ttls;
+        self
+    }
 }
 
 impl Default for RmmCore {
@@ -788,6 +1315,12 @@ impl RmmCore {
             username: username.to_string(),
             version: version.to_string(),
             projects: HashMap::new(),
+            trusted: HashMap::new(),
+            devices: BTreeMap::new(),
+            tags: BTreeMap::new(),
+            cache: None,
+            uuids: BTreeMap::new(),
+            network: None,
         }
     }
 
@@ -801,11 +1334,14 @@ impl RmmCore {
                 changelog: "CHANGELOG.MD".to_string(),
                 license: "LICENSE".to_string(),
                 dependencies: vec![],
+                script_dependencies: Vec::new(),
+                keywords: vec![],
                 scripts: Some({
                     let mut scripts = HashMap::new();
                     scripts.insert("hello".to_string(), "echo 'hello world!'".to_string());
                     scripts
                 }),
+                uuid: Some(generate_project_uuid()),
             },
             authors: vec![Author {
                 name: username.to_string(),
@@ -819,6 +1355,8 @@ impl RmmCore {
                 build_backend: "rmm".to_string(),
             }),
             tool: None,
+            verify: None,
+            migrations: Vec::new(),
         }
     }
 
@@ -856,6 +1394,14 @@ impl RmmCore {
                     "*.tmp".to_string(), 
                     "*.log".to_string()
                 ],
+                exclude_presets: Vec::new(),
+                allow: Vec::new(),
+                generate: Vec::new(),
+                recovery: false,
+                permissions: Default::default(),
+                checksums: None,
+                line_endings: Default::default(),
+                symbols: None,
                 prebuild: vec!["echo 'Starting build'".to_string()],
                 build: vec!["rmm".to_string()],
                 postbuild: vec!["echo 'Build completed'".to_string()],
@@ -870,24 +1416,38 @@ impl RmmCore {
                 }),
                 scripts: Some(default_scripts),
             },
+            update: None,
+            action: None,
+            check: None,
+            timeouts: None,
+            publish: None,
+            uninstall: None,
+            bootloop_guard: None,
+            control: None,
+            assets: Default::default(),
+            third_party: Default::default(),
+            vcs: None,
+            module: None,
+            sign: None,
         }
     }
 }
 
 impl RmmCore {/// 检测给定路径是否在 Git 仓库中，并返回详细信息
     pub fn get_git_info(&self, path: &Path) -> Result<GitInfo> {
-        let canonical_path = path.canonicalize()
-            .map_err(|e| anyhow::anyhow!("无法获取路径的绝对路径: {}", e))?;
+        let canonical_path = canonicalize_or_absolute(path);
         
         // 检查缓存
         {
             let cache = self.git_cache.lock().unwrap();
             if let Some((git_info, cached_time)) = cache.get(&canonical_path) {
-                if cached_time.elapsed() < self.cache_ttl {
+                if cached_time.elapsed() < self.cache_ttls.git {
+                    self.cache_counters.git.record(true);
                     return Ok(git_info.clone());
                 }
             }
         }
+        self.cache_counters.git.record(false);
         
         let git_info = self.analyze_git_info(&canonical_path)?;
         
@@ -900,122 +1460,9 @@ impl RmmCore {/// 检测给定路径是否在 Git 仓库中，并返回详细信
         Ok(git_info)
     }
     
-    /// 分析路径的 Git 信息
+    /// 分析路径的仓库信息（按 [vcs] 配置选择后端），不在仓库中时返回默认值
     fn analyze_git_info(&self, path: &Path) -> Result<GitInfo> {
-        let mut current_path = path.to_path_buf();
-        let original_path = path.to_path_buf();
-        
-        // 向上遍历寻找 .git 文件夹
-        loop {
-            let git_path = current_path.join(".git");
-            if git_path.exists() {
-                let relative_path = original_path.strip_prefix(&current_path)
-                    .unwrap_or(Path::new(""))
-                    .to_path_buf();
-                
-                let mut git_info = GitInfo {
-                    repo_root: current_path.clone(),
-                    relative_path,
-                    branch: String::new(),
-                    remote_url: None,
-                    has_uncommitted_changes: false,
-                    last_commit_hash: None,
-                    last_commit_message: None,
-                };
-                
-                // 读取更多 Git 信息
-                self.read_git_details(&current_path, &mut git_info)?;
-                
-                return Ok(git_info);
-            }
-            
-            match current_path.parent() {
-                Some(parent) => current_path = parent.to_path_buf(),
-                None => break,
-            }
-        }
-        
-        // 没有找到 Git 仓库
-        Ok(GitInfo::default())
-    }
-    
-    /// 读取 Git 仓库的详细信息
-    fn read_git_details(&self, git_root: &Path, git_info: &mut GitInfo) -> Result<()> {
-        let git_path = git_root.join(".git");
-        
-        // 读取当前分支
-        if let Ok(head_content) = fs::read_to_string(git_path.join("HEAD")) {
-            if let Some(branch) = head_content.strip_prefix("ref: refs/heads/") {
-                git_info.branch = branch.trim().to_string();
-            }
-        }
-        
-        // 读取远程仓库 URL
-        if let Ok(config_content) = fs::read_to_string(git_path.join("config")) {
-            git_info.remote_url = self.parse_git_remote_url(&config_content);
-        }
-        
-        // 检查是否有未提交的更改（简单检查）
-        git_info.has_uncommitted_changes = self.check_git_status(git_root)?;
-        
-        // 获取最后一次提交信息
-        let (last_commit_hash, last_commit_message) = self.get_last_commit_info(git_root)?;
-        git_info.last_commit_hash = last_commit_hash;
-        git_info.last_commit_message = last_commit_message;
-        
-        Ok(())
-    }
-    
-    /// 解析 Git 配置中的远程 URL
-    fn parse_git_remote_url(&self, config_content: &str) -> Option<String> {
-        for line in config_content.lines() {
-            let line = line.trim();
-            if line.starts_with("url = ") {
-                return Some(line.strip_prefix("url = ")?.to_string());
-            }
-        }
-        None
-    }
-    
-    /// 检查 Git 仓库状态（简化版）
-    fn check_git_status(&self, git_root: &Path) -> Result<bool> {
-        let git_path = git_root.join(".git");
-        
-        // 检查 index 文件是否存在且最近被修改
-        let index_path = git_path.join("index");
-        if let Ok(metadata) = fs::metadata(&index_path) {
-            if let Ok(modified) = metadata.modified() {
-                if let Ok(elapsed) = modified.elapsed() {
-                    // 如果 index 文件在最近 1 小时内被修改，可能有未提交的更改
-                    return Ok(elapsed < Duration::from_secs(3600));
-                }
-            }
-        }
-        
-        // 检查工作目录中是否有新文件或修改的文件
-        // 这里做简化处理，只检查一些常见的指示器
-        Ok(false)
-    }
-    
-    /// 获取最后一次提交信息
-    fn get_last_commit_info(&self, git_root: &Path) -> Result<(Option<String>, Option<String>)> {
-        let repo = Repository::open(git_root)
-            .with_context(|| format!("Failed to open Git repository at {}", git_root.display()))?;
-        
-        let head = repo.head()
-            .with_context(|| "Failed to get HEAD reference")?;
-        
-        if let Some(oid) = head.target() {
-            let commit = repo.find_commit(oid)
-                .with_context(|| "Failed to find commit")?;
-            
-            let hash = oid.to_string();
-            let message = commit.message().unwrap_or("").to_string();
-            
-            Ok((Some(hash), Some(message)))
-        } else {
-            Ok((None, None))
-        }
+        Ok(analyze_vcs_info(path)?.unwrap_or_default())
     }
     
     /// 获取项目的 Git 信息
@@ -1067,7 +1514,7 @@ impl RmmCore {/// 检测给定路径是否在 Git 仓库中，并返回详细信
     pub fn cleanup_expired_git_cache(&self) {
         let mut cache = self.git_cache.lock().unwrap();
         let now = Instant::now();
-        cache.retain(|_, (_, cached_time)| now.duration_since(*cached_time) < self.cache_ttl);
+        cache.retain(|_, (_, cached_time)| now.duration_since(*cached_time) < self.cache_ttls.git);
     }
 }
 
@@ -1,8 +1,9 @@
 #[cfg(test)]
 mod tests {
     use crate::core::RmmCore;
+    use crate::core::storage::canonicalize_or_absolute;
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use tempfile::tempdir;    fn setup_test_env() -> (tempfile::TempDir, RmmCore) {
         let temp_dir = tempdir().unwrap();
         
@@ -509,4 +510,203 @@ build-backend = "rmm"
         println!("✅ 缓存清理测试通过");
         Ok(())
     }
+
+    #[test]
+    fn test_cache_statistics_and_ttls() -> anyhow::Result<()> {
+        use crate::core::rmm_core::CacheTtls;
+        use crate::core::settings::ConfigResolver;
+        use std::collections::BTreeMap;
+        use std::time::Duration;
+
+        let (temp_dir, core) = setup_test_env();
+        let meta = core.create_default_meta("test@example.com", "testuser", "1.0.0");
+        core.update_meta_config(&meta)?;
+        core.get_meta_config()?;
+        core.get_meta_config()?;
+        core.clear_cache();
+        core.get_meta_config()?;
+
+        let stats = core.cache_statistics();
+        let names: Vec<&str> = stats.iter().map(|s| s.cache).collect();
+        assert_eq!(names, ["meta", "project", "git"]);
+        assert_eq!((stats[0].hits, stats[0].misses, stats[0].entries), (2, 1, 1));
+
+        // 有效期为 0 时每次都重新读取
+        let core = RmmCore::new().with_cache_ttls(CacheTtls { meta: Duration::ZERO, ..CacheTtls::default() });
+        core.update_meta_config(&meta)?;
+        core.get_meta_config()?;
+        core.get_meta_config()?;
+        let stats = core.cache_statistics();
+        assert_eq!((stats[0].hits, stats[0].misses, stats[0].expired, stats[0].ttl_secs), (0, 2, 1, 0));
+
+        // meta.toml [cache] 与环境变量，无效值回退到默认
+        let meta_path = temp_dir.path().join("meta.toml");
+        fs::write(&meta_path, "[cache]\nproject = 5\ngit = \"soon\"\n")?;
+        let env = BTreeMap::from([("RMM_CACHE_META_TTL".to_string(), "0".to_string())]);
+        let ttls = CacheTtls::from_config(&ConfigResolver::with_sources(None, env, &meta_path));
+        assert_eq!(ttls.meta, Duration::ZERO);
+        assert_eq!(ttls.project, Duration::from_secs(5));
+        assert_eq!(ttls.git, Duration::from_secs(60));
+        Ok(())
+    }
+
+    #[test]
+    fn test_track_project_moves_and_copies() {
+        use crate::core::project_id::read_project_uuid;
+        use crate::core::rmm_core::ProjectTracking;
+
+        let (temp_dir, core) = setup_test_env();
+        let make_project = |name: &str| {
+            let path = temp_dir.path().join(name);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("rmmproject.toml"), format!("[project]\nid = \"{}\"\n", name)).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let old_path = make_project("demo");
+        let mut meta = core.create_default_meta("test@example.com", "testuser", "1.0.0");
+        meta.projects.insert("demo".to_string(), old_path.clone());
+        meta.tags.insert("demo".to_string(), ["work".to_string()].into());
+        meta.trusted.insert(old_path.clone(), "digest".to_string());
+
+        assert_eq!(core.track_project(&mut meta, "demo", &old_path).unwrap(), ProjectTracking::New);
+        assert_eq!(core.track_project(&mut meta, "demo", &old_path).unwrap(), ProjectTracking::Unchanged);
+        let uuid = read_project_uuid(Path::new(&old_path)).unwrap();
+
+        // 移动并改名：原名称、标签与信任记录迁到新位置，不留下重复项
+        let new_path = temp_dir.path().join("renamed").to_string_lossy().to_string();
+        fs::rename(&old_path, &new_path).unwrap();
+        let tracking = core.track_project(&mut meta, "renamed", &new_path).unwrap();
+        assert_eq!(tracking, ProjectTracking::Moved { name: "demo".to_string(), from: old_path.clone() });
+        assert_eq!(meta.projects.len(), 1);
+        assert_eq!(meta.projects["renamed"], new_path);
+        assert!(meta.tags["renamed"].contains("work"));
+        assert!(!meta.tags.contains_key("demo"));
+        assert_eq!(meta.trusted.get(&new_path).map(String::as_str), Some("digest"));
+        assert_eq!(meta.uuids[&uuid].path, new_path);
+
+        // 复制出的项目换新 UUID，原项目保持登记
+        let copy_path = make_project("copy");
+        fs::copy(Path::new(&new_path).join("rmmproject.toml"), Path::new(&copy_path).join("rmmproject.toml")).unwrap();
+        let tracking = core.track_project(&mut meta, "copy", &copy_path).unwrap();
+        assert_eq!(tracking, ProjectTracking::Copied { original: new_path.clone() });
+        let copy_uuid = read_project_uuid(Path::new(&copy_path)).unwrap();
+        assert_ne!(copy_uuid, uuid);
+        assert_eq!(meta.uuids[&uuid].path, new_path);
+        assert_eq!(meta.uuids[&copy_uuid].name, "copy");
+    }
+
+    fn git_commit(repo: &git2::Repository, message: &str) -> git2::Oid {
+        let signature = git2::Signature::now("rmm", "rmm@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents).unwrap()
+    }
+
+    #[test]
+    fn test_git_analyzer_layouts() {
+        use crate::core::rmm_core::GitAnalyzer;
+
+        let temp = tempdir().unwrap();
+        let root = canonicalize_or_absolute(temp.path());
+
+        // 尚无提交的仓库：分支来自 HEAD 的符号引用
+        let main_dir = root.join("main");
+        let repo = git2::Repository::init(&main_dir).unwrap();
+        repo.set_head("refs/heads/trunk").unwrap();
+        repo.remote("upstream", "https://example.com/up.git").unwrap();
+        repo.remote("origin", "https://github.com/user/repo.git").unwrap();
+        fs::create_dir_all(main_dir.join("module")).unwrap();
+        let info = GitAnalyzer::analyze_git_info(&main_dir.join("module")).unwrap().unwrap();
+        assert_eq!(info.repo_root, main_dir);
+        assert_eq!(info.relative_path, PathBuf::from("module"));
+        assert_eq!(info.branch, "trunk");
+        assert_eq!(info.remote_url.as_deref(), Some("https://github.com/user/repo.git"));
+        assert!(info.last_commit_hash.is_none());
+
+        // 分离 HEAD：找到指向同一提交的分支
+        let first = git_commit(&repo, "first");
+        repo.set_head_detached(first).unwrap();
+        let info = GitAnalyzer::analyze_git_info(&main_dir).unwrap().unwrap();
+        assert_eq!(info.branch, "trunk");
+        assert_eq!(info.last_commit_hash, Some(first.to_string()));
+        assert_eq!(info.last_commit_message.as_deref(), Some("first"));
+        repo.set_head("refs/heads/trunk").unwrap();
+
+        // worktree：.git 是指向主仓库 worktrees/ 的文件
+        let wt_dir = root.join("wt");
+        repo.worktree("feature", &wt_dir, None).unwrap();
+        let info = GitAnalyzer::analyze_git_info(&wt_dir).unwrap().unwrap();
+        assert_eq!(info.repo_root, wt_dir);
+        assert_eq!(info.branch, "feature");
+        assert_eq!(info.last_commit_hash, Some(first.to_string()));
+
+        // 子模块式布局：嵌套目录的 .git 文件使用相对 gitdir 指向
+        let sub_dir = main_dir.join("sub");
+        let sub_git = root.join("modules/sub.git");
+        let sub_repo = git2::Repository::init(&sub_dir).unwrap();
+        let sub_commit = git_commit(&sub_repo, "sub");
+        drop(sub_repo);
+        fs::create_dir_all(sub_git.parent().unwrap()).unwrap();
+        fs::rename(sub_dir.join(".git"), &sub_git).unwrap();
+        fs::write(sub_dir.join(".git"), "gitdir: ../../modules/sub.git\n").unwrap();
+        let info = GitAnalyzer::analyze_git_info(&sub_dir).unwrap().unwrap();
+        assert_eq!(info.repo_root, sub_dir);
+        assert_eq!(info.last_commit_hash, Some(sub_commit.to_string()));
+
+        // gitdir 指向不存在时报错而不是静默回退到上层仓库
+        fs::write(sub_dir.join(".git"), "gitdir: ../missing.git\n").unwrap();
+        assert!(GitAnalyzer::analyze_git_info(&sub_dir).is_err());
+        fs::remove_file(sub_dir.join(".git")).unwrap();
+
+        // 浅克隆：shallow 文件标记的提交缺少父提交
+        let second = git_commit(&repo, "second");
+        fs::write(main_dir.join(".git/shallow"), format!("{}\n", second)).unwrap();
+        let repo = git2::Repository::open(&main_dir).unwrap();
+        assert!(repo.is_shallow());
+        let info = GitAnalyzer::analyze_git_info(&main_dir).unwrap().unwrap();
+        assert_eq!(info.branch, "trunk");
+        assert_eq!(info.last_commit_hash, Some(second.to_string()));
+    }
+
+    #[test]
+    fn test_scan_projects_with_options() {
+        use crate::core::rmm_core::ScanOptions;
+
+        let (_env, core) = setup_test_env();
+        let first = tempdir().unwrap();
+        let second = tempdir().unwrap();
+        fs::create_dir_all(first.path().join("valid_mod/.rmmp")).unwrap();
+        fs::write(first.path().join("valid_mod/rmmproject.toml"), "").unwrap();
+        fs::write(first.path().join("valid_mod/.rmmp/Rmake.toml"), "").unwrap();
+        fs::create_dir_all(second.path().join("draft_mod")).unwrap();
+        fs::write(second.path().join("draft_mod/rmmproject.toml"), "").unwrap();
+
+        let mut options = ScanOptions {
+            // 重复路径只返回一次
+            paths: vec![first.path().to_path_buf(), second.path().to_path_buf(), first.path().to_path_buf()],
+            max_depth: Some(2),
+            only_valid: false,
+        };
+        let mut seen = Vec::new();
+        let results = core.scan_projects_with(&options, &mut |result, found| {
+            seen.push((result.name.clone(), found));
+            Ok(())
+        }).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(seen, vec![("valid_mod".to_string(), 1), ("draft_mod".to_string(), 2)]);
+
+        options.only_valid = true;
+        let results = core.scan_projects_with(&options, &mut |_, _| Ok(())).unwrap();
+        assert_eq!(results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), vec!["valid_mod"]);
+
+        // 回调出错时中止扫描
+        let err = core.scan_projects_with(&options, &mut |_, _| anyhow::bail!("stop")).unwrap_err();
+        assert_eq!(err.to_string(), "stop");
+
+        options.paths = vec![first.path().join("missing")];
+        assert!(core.scan_projects_with(&options, &mut |_, _| Ok(())).is_err());
+    }
+
 }
@@ -0,0 +1,184 @@
+use anyhow::Result;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+
+use super::deps::{compare_versions, satisfies};
+
+/// 正在运行的 RMM 版本
+pub const RMM_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// rmmproject.toml [build-system] requires 中 RMM 自身的包名
+const RMM_PACKAGE: &str = "rmm";
+
+/// 从 [build-system] requires 中取出 rmm 的版本范围（如 ">=0.3.0,<0.4"）；未声明或不限版本时返回 None
+pub fn rmm_requirement(requires: &[String]) -> Option<String> {
+    requires.iter().find_map(|entry| {
+        let entry = entry.trim();
+        let split = entry.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_')).unwrap_or(entry.len());
+        let (name, range) = entry.split_at(split);
+        let range = range.replace(' ', "");
+        (name.eq_ignore_ascii_case(RMM_PACKAGE) && !range.is_empty()).then_some(range)
+    })
+}
+
+/// 版本号的前 n 段，不足补 0
+fn numeric_parts(version: &str) -> Vec<u64> {
+    version.trim_start_matches(['v', 'V'])
+        .split('.')
+        .map(|part| part.chars().take_while(char::is_ascii_digit).collect::<String>().parse().unwrap_or(0))
+        .collect()
+}
+
+fn join_parts(parts: &[u64]) -> String {
+    parts.iter().map(u64::to_string).collect::<Vec<_>>().join(".")
+}
+
+/// 把 ^ / ~ / ~= / .* 展开为基本比较
+fn expand_comparator(comparator: &str) -> Result<Vec<String>> {
+    let invalid = || anyhow::anyhow!("无效的版本范围: '{}'", comparator);
+    if let Some(version) = comparator.strip_prefix("~=") {
+        // PEP 440 兼容版本：~=1.4.5 → >=1.4.5,<1.5；至少需要两段
+        let parts = numeric_parts(version);
+        if parts.len() < 2 {
+            return Err(invalid());
+        }
+        let mut upper = parts[..parts.len() - 1].to_vec();
+        *upper.last_mut().ok_or_else(invalid)? += 1;
+        return Ok(vec![format!(">={}", version), format!("<{}", join_parts(&upper))]);
+    }
+    if let Some(version) = comparator.strip_prefix('^') {
+        // 语义化版本：^1.2.3 → <2.0.0，^0.3.1 → <0.4.0
+        let parts = numeric_parts(version);
+        let index = parts.iter().position(|&p| p != 0).unwrap_or(parts.len().saturating_sub(1));
+        let mut upper = parts[..=index].to_vec();
+        upper[index] += 1;
+        return Ok(vec![format!(">={}", version), format!("<{}", join_parts(&upper))]);
+    }
+    if let Some(version) = comparator.strip_prefix('~') {
+        // ~1.2.3 → <1.3.0，~1 → <2
+        let parts = numeric_parts(version);
+        let mut upper = parts[..parts.len().min(2)].to_vec();
+        if upper.len() == 2 { upper[1] += 1 } else { upper[0] += 1 }
+        return Ok(vec![format!(">={}", version), format!("<{}", join_parts(&upper))]);
+    }
+    if let Some(prefix) = comparator.strip_prefix("==").and_then(|v| v.strip_suffix(".*")) {
+        // ==0.3.* → >=0.3,<0.4
+        let mut upper = numeric_parts(prefix);
+        *upper.last_mut().ok_or_else(invalid)? += 1;
+        return Ok(vec![format!(">={}", prefix), format!("<{}", join_parts(&upper))]);
+    }
+    let version = [">=", "<=", "==", "!=", ">", "<", "="].iter()
+        .find_map(|op| comparator.strip_prefix(op))
+        .ok_or_else(invalid)?;
+    if version.is_empty() || !version.trim_start_matches(['v', 'V']).starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    Ok(vec![comparator.to_string()])
+}
+
+/// 版本是否满足范围（逗号分隔的多个条件需同时满足）
+pub fn version_satisfies(version: &str, range: &str) -> Result<bool> {
+    for comparator in range.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        for basic in expand_comparator(comparator)? {
+            if !satisfies(version, &basic) {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// 读取项目要求的 RMM 版本范围（rmmproject.toml 缺失或无法解析时不检查）
+///
+/// 只读取 [build-system]，其余字段不完整的旧项目同样会被检查
+pub fn project_requirement(project_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(project_path.join("rmmproject.toml")).ok()?;
+    let document: toml::Value = toml::from_str(&content).ok()?;
+    let requires: Vec<String> = document.get("build-system")?.get("requires")?.as_array()?
+        .iter()
+        .filter_map(|entry| entry.as_str().map(str::to_string))
+        .collect();
+    rmm_requirement(&requires)
+}
+
+/// 检查当前 RMM 是否满足项目的 [build-system] requires，不满足时给出升级或降级方法
+pub fn ensure_rmm_version(project_path: &Path) -> Result<()> {
+    let Some(range) = project_requirement(project_path) else {
+        return Ok(());
+    };
+    if version_satisfies(RMM_VERSION, &range)? {
+        return Ok(());
+    }
+    let newer_needed = range.split(',')
+        .filter_map(|c| expand_comparator(c.trim()).ok())
+        .flatten()
+        .filter_map(|c| c.strip_prefix(">=").or_else(|| c.strip_prefix('>')).map(str::to_string))
+        .any(|minimum| compare_versions(RMM_VERSION, &minimum) != Ordering::Greater);
+    anyhow::bail!(
+        "当前 rmm {} 不满足项目要求 rmm{}（rmmproject.toml [build-system] requires）\n  {}: pip install -U \"pyrmm{}\"\n  如需改用当前版本，运行 rmm self pin",
+        RMM_VERSION, range, if newer_needed { "升级" } else { "安装匹配版本" }, range
+    )
+}
+
+/// 生成固定到当前版本的要求：默认兼容同一次版本（~=），exact 时固定为 ==
+pub fn pinned_requirement(exact: bool) -> String {
+    if exact {
+        format!("{}=={}", RMM_PACKAGE, RMM_VERSION)
+    } else {
+        format!("{}~={}", RMM_PACKAGE, RMM_VERSION)
+    }
+}
+
+/// 在 requires 中替换（或加入）rmm 的要求，保留其他条目
+pub fn replace_requirement(requires: &mut Vec<String>, requirement: String) {
+    let position = requires.iter().position(|entry| {
+        let name: String = entry.trim().chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_').collect();
+        name.eq_ignore_ascii_case(RMM_PACKAGE)
+    });
+    match position {
+        Some(index) => requires[index] = requirement,
+        None => requires.insert(0, requirement),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rmm_version_requirement() {
+        let requires = vec!["setuptools>=61".to_string(), "rmm >= 0.3.0, <0.4".to_string()];
+        assert_eq!(rmm_requirement(&requires).as_deref(), Some(">=0.3.0,<0.4"));
+        assert_eq!(rmm_requirement(&["rmm".to_string()]), None);
+
+        assert!(version_satisfies("0.3.5", ">=0.3.0,<0.4").unwrap());
+        assert!(!version_satisfies("0.4.0", ">=0.3.0,<0.4").unwrap());
+        assert!(version_satisfies("0.3.9", "~=0.3.1").unwrap());
+        assert!(!version_satisfies("0.4.0", "~=0.3.1").unwrap());
+        assert!(version_satisfies("0.3.9", "^0.3.1").unwrap());
+        assert!(!version_satisfies("1.0.0", "^0.3.1").unwrap());
+        assert!(version_satisfies("1.9.0", "^1.2").unwrap());
+        assert!(!version_satisfies("1.3.0", "~1.2.0").unwrap());
+        assert!(version_satisfies("0.3.7", "==0.3.*").unwrap());
+        assert!(version_satisfies("0.3.7", "!=0.3.6").unwrap());
+        assert!(version_satisfies("0.3.7", "").unwrap());
+        assert!(version_satisfies("0.3.7", "latest").is_err());
+
+        let mut requires = vec!["foo".to_string(), "rmm>=0.1".to_string()];
+        replace_requirement(&mut requires, pinned_requirement(true));
+        assert_eq!(requires, vec!["foo".to_string(), format!("rmm=={}", RMM_VERSION)]);
+        assert!(version_satisfies(RMM_VERSION, rmm_requirement(&requires).unwrap().as_str()).unwrap());
+        let mut empty = Vec::new();
+        replace_requirement(&mut empty, pinned_requirement(false));
+        assert_eq!(empty, vec![format!("rmm~={}", RMM_VERSION)]);
+
+        let temp = tempfile::tempdir().unwrap();
+        let project = temp.path();
+        fs::write(project.join("rmmproject.toml"), "[project]\nid = \"demo\"\n\n[build-system]\nrequires = [\"rmm>=999.0\"]\nbuild-backend = \"rmm\"\n").unwrap();
+        let err = ensure_rmm_version(project).unwrap_err().to_string();
+        assert!(err.contains("pip install -U \"pyrmm>=999.0\""), "{}", err);
+        assert!(err.contains("rmm self pin"));
+        fs::write(project.join("rmmproject.toml"), "[project]\nid = \"demo\"\n").unwrap();
+        assert!(ensure_rmm_version(project).is_ok());
+    }
+}
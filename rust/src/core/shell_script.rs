@@ -0,0 +1,173 @@
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 嗅探文件类型时读取的字节数
+const SNIFF_LEN: usize = 8192;
+
+/// shellcheck 支持的解释器
+const SHELL_INTERPRETERS: [&str; 6] = ["sh", "bash", "ash", "dash", "ksh", "mksh"];
+
+/// [check.scripts] 配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct ScriptCheckConfig {
+    /// 额外作为 shell 脚本检查的文件（相对构建目录的通配符），如 "system/bin/*"
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// 不做检查的文件
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// 单个文件的识别结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    /// 需要检查的 shell 脚本
+    Shell,
+    /// 看起来是脚本（扩展名或规则匹配）但内容是二进制，不能检查或修复
+    Binary,
+    /// 其他文件
+    Other,
+}
+
+/// 脚本扫描结果
+#[derive(Debug, Clone, Default)]
+pub struct ScriptScan {
+    pub scripts: Vec<PathBuf>,
+    /// 被跳过的二进制文件
+    pub skipped_binary: Vec<PathBuf>,
+}
+
+/// 判断内容是否为二进制：ELF 头或包含 NUL 字节
+pub fn is_binary(head: &[u8]) -> bool {
+    head.starts_with(b"\x7fELF") || head.contains(&0)
+}
+
+/// 从 shebang 判断是否为 shell 脚本，支持 `#!/usr/bin/env bash` 形式
+pub fn has_shell_shebang(head: &[u8]) -> bool {
+    let Some(line) = head.strip_prefix(b"#!") else {
+        return false;
+    };
+    let line = line.split(|b| *b == b'\n').next().unwrap_or_default();
+    let line = String::from_utf8_lossy(line);
+    let mut parts = line.split_whitespace();
+    let Some(program) = parts.next() else {
+        return false;
+    };
+    let mut name = program.rsplit('/').next().unwrap_or(program);
+    if name == "env" {
+        name = parts.find(|p| !p.starts_with('-')).unwrap_or_default();
+    }
+    SHELL_INTERPRETERS.contains(&name)
+}
+
+/// 把通配符转换为正则：`*` 匹配任意字符（含 /）
+fn compile_glob(pattern: &str) -> Option<Regex> {
+    let trimmed = pattern.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    Regex::new(&format!("^{}$", regex::escape(trimmed).replace(r"\*", ".*"))).ok()
+}
+
+/// 根据扩展名、shebang、内容与 [check.scripts] 规则识别 shell 脚本
+pub struct ScriptMatcher {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl ScriptMatcher {
+    pub fn new(config: Option<&ScriptCheckConfig>) -> Self {
+        let compile = |patterns: &[String]| patterns.iter().filter_map(|p| compile_glob(p)).collect();
+        match config {
+            Some(config) => Self { include: compile(&config.include), exclude: compile(&config.exclude) },
+            None => Self { include: Vec::new(), exclude: Vec::new() },
+        }
+    }
+
+    /// 识别文件，`relative` 为用于匹配规则的相对路径
+    pub fn classify(&self, path: &Path, relative: &str) -> Result<ScriptKind> {
+        if self.exclude.iter().any(|re| re.is_match(relative)) {
+            return Ok(ScriptKind::Other);
+        }
+
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+        File::open(path)?.take(SNIFF_LEN as u64).read_to_end(&mut head)?;
+
+        let by_name = path.extension().is_some_and(|ext| ext == "sh")
+            || self.include.iter().any(|re| re.is_match(relative));
+        if is_binary(&head) {
+            return Ok(if by_name { ScriptKind::Binary } else { ScriptKind::Other });
+        }
+        if by_name || has_shell_shebang(&head) {
+            return Ok(ScriptKind::Shell);
+        }
+        Ok(ScriptKind::Other)
+    }
+
+    /// 递归扫描目录中的 shell 脚本
+    pub fn scan(&self, dir: &Path) -> Result<ScriptScan> {
+        let mut scan = ScriptScan::default();
+        if !dir.exists() {
+            return Ok(scan);
+        }
+
+        for entry in WalkDir::new(dir).sort_by_file_name().into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(dir)?
+                .to_string_lossy()
+                .replace('\\', "/");
+            match self.classify(entry.path(), &relative)? {
+                ScriptKind::Shell => scan.scripts.push(entry.into_path()),
+                ScriptKind::Binary => scan.skipped_binary.push(entry.into_path()),
+                ScriptKind::Other => {}
+            }
+        }
+        Ok(scan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_shell_script_detection_skips_binaries() {
+        assert!(has_shell_shebang(b"#!/system/bin/sh\n"));
+        assert!(has_shell_shebang(b"#!/usr/bin/env -S bash -e\n"));
+        assert!(!has_shell_shebang(b"#!/usr/bin/python3\n"));
+
+        let temp_dir = tempdir().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("system/bin")).unwrap();
+        fs::write(dir.join("service.sh"), "echo service\n").unwrap();
+        fs::write(dir.join("post-fs-data"), "#!/system/bin/sh\necho boot\n").unwrap();
+        fs::write(dir.join("busybox.sh"), b"\x7fELF\x02\x01\x01\0\0").unwrap();
+        fs::write(dir.join("system/bin/tool"), "echo tool\n").unwrap();
+        fs::write(dir.join("system/bin/skip"), "#!/bin/sh\n").unwrap();
+        fs::write(dir.join("module.prop"), "id=demo\n").unwrap();
+
+        let scan = ScriptMatcher::new(None).scan(dir).unwrap();
+        let names = |paths: &[PathBuf]| -> Vec<String> {
+            paths.iter().map(|p| p.strip_prefix(dir).unwrap().to_string_lossy().replace('\\', "/")).collect()
+        };
+        assert_eq!(names(&scan.scripts), vec!["post-fs-data", "service.sh", "system/bin/skip"]);
+        assert_eq!(names(&scan.skipped_binary), vec!["busybox.sh"]);
+
+        let config = ScriptCheckConfig {
+            include: vec!["system/bin/*".to_string()],
+            exclude: vec!["system/bin/skip".to_string()],
+        };
+        let scan = ScriptMatcher::new(Some(&config)).scan(dir).unwrap();
+        assert_eq!(names(&scan.scripts), vec!["post-fs-data", "service.sh", "system/bin/tool"]);
+    }
+}
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::settings::ConfigResolver;
+
+/// wiki 原始 Markdown 的下载地址
+const WIKI_RAW_URL: &str = "https://raw.githubusercontent.com/wiki/koalaman/shellcheck";
+/// 在线 wiki 页面（仅作为参考链接）
+const WIKI_PAGE_URL: &str = "https://www.shellcheck.net/wiki";
+/// 下载单个页面的超时时间
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(5);
+/// 摘要最大字符数
+const EXCERPT_MAX_CHARS: usize = 800;
+
+/// 内置的常见 SC 规则说明，无法访问网络且没有缓存时使用
+const BUNDLED: &[(u32, &str)] = &[
+    (1090, "ShellCheck can't follow a non-constant source. Add `# shellcheck source=path/to/file` above the line, or `# shellcheck source=/dev/null` to skip it."),
+    (1091, "The sourced file was not found or not given as input. Pass it with `-x` or point to it with a `# shellcheck source=` directive."),
+    (2006, "Use `$(...)` instead of legacy backticks: it nests cleanly and handles escapes predictably."),
+    (2016, "Expressions don't expand in single quotes. Use double quotes if the variable should be expanded."),
+    (2034, "The variable is assigned but never used. Remove it, export it if another program needs it, or check the name for typos."),
+    (2046, "Quote the command substitution to prevent word splitting, or use a loop / `xargs` if splitting is intended."),
+    (2068, "Quote `\"$@\"` to keep arguments with spaces intact instead of re-splitting them."),
+    (2086, "Double quote variable expansions (`\"$var\"`) to prevent word splitting and glob expansion of their values."),
+    (2115, "Use `\"${var:?}\"` so that `rm -rf \"$var/\"` fails instead of deleting `/` when the variable is empty."),
+    (2148, "Add a shebang such as `#!/system/bin/sh` so ShellCheck (and the system) know which shell runs the script."),
+    (2154, "The variable is referenced but never assigned. Assign it, or silence the warning if it comes from the environment (e.g. MODPATH in installer scripts)."),
+    (2155, "Declare and assign separately (`local x; x=$(cmd)`), otherwise the exit status of the command is masked."),
+    (2162, "`read` without `-r` mangles backslashes. Use `read -r` unless backslash escapes are really wanted."),
+    (2164, "Use `cd ... || exit` in case the directory does not exist, otherwise later commands run in the wrong place."),
+    (2181, "Check the exit code directly (`if cmd; then`) instead of indirectly through `$?`."),
+];
+
+/// 摘要来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExcerptSource {
+    /// RMM_ROOT/cache/shellcheck-wiki 中已缓存的页面
+    Cache,
+    /// 本次下载并写入缓存
+    Downloaded,
+    /// 内置说明
+    Bundled,
+}
+
+/// 嵌入报告的规则说明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WikiExcerpt {
+    pub code: u32,
+    pub excerpt: String,
+    pub source: ExcerptSource,
+    pub url: String,
+}
+
+/// 从 wiki 页面提取标题与 Rationale 段落
+pub fn extract_excerpt(markdown: &str) -> Option<String> {
+    let title = markdown.lines()
+        .map(str::trim)
+        .find(|line| line.starts_with('#'))
+        .map(|line| line.trim_start_matches('#').trim().to_string());
+    let rationale = markdown.lines()
+        .skip_while(|line| !line.trim_start_matches('#').trim().to_lowercase().starts_with("rationale"))
+        .skip(1)
+        .skip_while(|line| line.trim().is_empty())
+        .take_while(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let text = match (title, rationale.trim()) {
+        (Some(title), "") => title,
+        (Some(title), rationale) => format!("{}. {}", title.trim_end_matches('.'), rationale),
+        (None, "") => return None,
+        (None, rationale) => rationale.to_string(),
+    };
+    Some(match text.char_indices().nth(EXCERPT_MAX_CHARS) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text,
+    })
+}
+
+/// shellcheck wiki 离线缓存
+pub struct WikiCache {
+    dir: PathBuf,
+    /// 是否允许联网下载缺失页面
+    download: bool,
+}
+
+impl WikiCache {
+    pub fn new(dir: &Path, download: bool) -> Self {
+        Self { dir: dir.to_path_buf(), download }
+    }
+
+    /// 使用 RMM_ROOT/cache/shellcheck-wiki；设置 RMM_OFFLINE 时不下载
+    pub fn from_config(config: &ConfigResolver) -> Self {
+        let dir = super::storage::RmmStorage::resolve().cache_dir().join("shellcheck-wiki");
+        Self::new(&dir, !config.get_bool("network.offline"))
+    }
+
+    fn page_path(&self, code: u32) -> PathBuf {
+        self.dir.join(format!("SC{}.md", code))
+    }
+
+    fn download_pages(&self, codes: &[u32]) -> BTreeMap<u32, String> {
+        let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() else {
+            return BTreeMap::new();
+        };
+        runtime.block_on(async {
+            let Ok(client) = reqwest::Client::builder()
+                .timeout(DOWNLOAD_TIMEOUT)
+                .user_agent(concat!("rmm/", env!("CARGO_PKG_VERSION")))
+                .build()
+            else {
+                return BTreeMap::new();
+            };
+            let mut pages = BTreeMap::new();
+            for &code in codes {
+                let response = match client.get(format!("{}/SC{}.md", WIKI_RAW_URL, code)).send().await {
+                    Ok(response) if response.status().is_success() => response,
+                    Ok(_) => continue,
+                    // 网络不可用时不再尝试其余页面
+                    Err(_) => break,
+                };
+                if let Ok(body) = response.text().await {
+                    pages.insert(code, body);
+                }
+            }
+            pages
+        })
+    }
+
+    /// 获取一组规则的说明：缓存 > 下载（写入缓存）> 内置
+    pub fn excerpts(&self, codes: &BTreeSet<u32>) -> BTreeMap<u32, WikiExcerpt> {
+        let mut pages: BTreeMap<u32, (String, ExcerptSource)> = codes.iter()
+            .filter_map(|&code| fs::read_to_string(self.page_path(code)).ok().map(|page| (code, (page, ExcerptSource::Cache))))
+            .collect();
+
+        let missing: Vec<u32> = codes.iter().copied().filter(|code| !pages.contains_key(code)).collect();
+        if self.download && !missing.is_empty() {
+            let downloaded = self.download_pages(&missing);
+            if !downloaded.is_empty() {
+                let _ = fs::create_dir_all(&self.dir);
+            }
+            for (code, page) in downloaded {
+                let _ = fs::write(self.page_path(code), &page);
+                pages.insert(code, (page, ExcerptSource::Downloaded));
+            }
+        }
+
+        codes.iter()
+            .filter_map(|&code| {
+                let (excerpt, source) = pages.get(&code)
+                    .and_then(|(page, source)| extract_excerpt(page).map(|excerpt| (excerpt, *source)))
+                    .or_else(|| bundled_excerpt(code).map(|excerpt| (excerpt.to_string(), ExcerptSource::Bundled)))?;
+                Some((code, WikiExcerpt { code, excerpt, source, url: format!("{}/SC{}", WIKI_PAGE_URL, code) }))
+            })
+            .collect()
+    }
+}
+
+fn bundled_excerpt(code: u32) -> Option<&'static str> {
+    BUNDLED.iter().find(|(c, _)| *c == code).map(|(_, text)| *text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_shellcheck_wiki_offline_cache() {
+        let page = "## Double quote to prevent globbing and word splitting.\n\n### Problematic code:\n\n```sh\necho $1\n```\n\n### Rationale\n\nQuoting variables prevents\nsplitting on spaces.\n\nMore text.\n";
+        assert_eq!(
+            extract_excerpt(page).as_deref(),
+            Some("Double quote to prevent globbing and word splitting. Quoting variables prevents splitting on spaces.")
+        );
+        assert_eq!(extract_excerpt("no headings here"), None);
+
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("SC2086.md"), page).unwrap();
+        let wiki = WikiCache::new(temp.path(), false).excerpts(&BTreeSet::from([2086, 2164, 9999]));
+        assert_eq!(wiki[&2086].source, ExcerptSource::Cache);
+        assert!(wiki[&2086].excerpt.contains("Quoting variables"));
+        assert_eq!(wiki[&2164].source, ExcerptSource::Bundled);
+        assert_eq!(wiki[&2164].url, "https://www.shellcheck.net/wiki/SC2164");
+        assert!(!wiki.contains_key(&9999));
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fs;
+use std::path::Path;
+
+/// 产物大小历史文件（位于 .rmmp 下）
+const HISTORY_FILE: &str = "history.json";
+
+/// [check.size] 配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct SizeCheckConfig {
+    /// 相比上一版本允许增长的最大百分比，超过时构建失败
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_growth_percent: Option<f64>,
+}
+
+/// 一次构建产物的大小记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SizeRecord {
+    pub version: String,
+    pub version_code: String,
+    pub artifact: String,
+    pub size: u64,
+    pub timestamp: String,
+}
+
+/// 产物大小历史
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SizeHistory {
+    pub records: Vec<SizeRecord>,
+}
+
+impl SizeHistory {
+    pub fn load(project_path: &Path) -> Result<Self> {
+        let path = project_path.join(".rmmp").join(HISTORY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, project_path: &Path) -> Result<()> {
+        fs::write(
+            project_path.join(".rmmp").join(HISTORY_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// 记录产物大小；同一 versionCode 重复构建时覆盖旧记录
+    pub fn record(&mut self, record: SizeRecord) {
+        self.records.retain(|r| r.version_code != record.version_code);
+        self.records.push(record);
+    }
+
+    /// 指定 versionCode 之前最近一个版本的记录
+    pub fn previous(&self, version_code: &str) -> Option<&SizeRecord> {
+        self.records.iter().rev().find(|r| r.version_code != version_code)
+    }
+}
+
+/// 计算相对上一版本的增长百分比
+pub fn growth_percent(previous: u64, current: u64) -> Option<f64> {
+    if previous == 0 {
+        return None;
+    }
+    Some((current as f64 - previous as f64) / previous as f64 * 100.0)
+}
+
+/// 检查增长是否超过阈值，超过时返回错误信息
+pub fn check_growth(history: &SizeHistory, current: &SizeRecord, config: &SizeCheckConfig) -> Option<String> {
+    let limit = config.max_growth_percent?;
+    let previous = history.previous(&current.version_code)?;
+    let growth = growth_percent(previous.size, current.size)?;
+    if growth > limit {
+        Some(format!(
+            "产物大小增长 {:.1}%（{} → {} 字节，相对 {}），超过上限 {:.1}%",
+            growth, previous.size, current.size, previous.version, limit
+        ))
+    } else {
+        None
+    }
+}
+
+/// 将字节数格式化为易读的大小
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_size_history_growth_check() {
+        let record = |code: &str, size: u64| SizeRecord {
+            version: format!("v{}", code),
+            version_code: code.to_string(),
+            artifact: format!("demo-{}.zip", code),
+            size,
+            timestamp: String::new(),
+        };
+
+        let temp_dir = tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join(".rmmp")).unwrap();
+        let mut history = SizeHistory::load(temp_dir.path()).unwrap();
+        history.record(record("1", 1000));
+        history.record(record("2", 1050));
+        history.record(record("2", 1100));
+        history.save(temp_dir.path()).unwrap();
+
+        let history = SizeHistory::load(temp_dir.path()).unwrap();
+        assert_eq!(history.records.len(), 2);
+        assert_eq!(history.previous("3").unwrap().size, 1100);
+
+        let config = SizeCheckConfig { max_growth_percent: Some(10.0) };
+        assert!(check_growth(&history, &record("3", 1200), &config).is_none());
+        assert!(check_growth(&history, &record("3", 1300), &config).is_some());
+        assert!(check_growth(&history, &record("3", 5000), &SizeCheckConfig::default()).is_none());
+
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+    }
+}
@@ -0,0 +1,234 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// RMM_ROOT 的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootSource {
+    /// 环境变量 RMM_ROOT
+    Env,
+    /// Termux 前缀（Android 设备上运行）
+    Termux,
+    /// XDG 数据目录（Linux / macOS）
+    Xdg,
+    /// %APPDATA%（Windows）
+    AppData,
+    /// 旧版默认位置 ~/data/adb/.rmm
+    Legacy,
+}
+
+impl RootSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RootSource::Env => "RMM_ROOT",
+            RootSource::Termux => "termux",
+            RootSource::Xdg => "xdg",
+            RootSource::AppData => "appdata",
+            RootSource::Legacy => "legacy",
+        }
+    }
+}
+
+/// RMM 存储位置抽象
+#[derive(Debug, Clone)]
+pub struct RmmStorage {
+    root: PathBuf,
+    source: RootSource,
+}
+
+impl RmmStorage {
+    /// 按优先级解析存储根目录：RMM_ROOT > Termux > 平台数据目录；
+    /// 旧版位置有数据而当前位置还没有时继续使用旧版位置，直到运行 rmm env --migrate
+    pub fn resolve() -> Self {
+        let preferred = Self::preferred();
+        if preferred.legacy_pending() {
+            return Self { root: Self::legacy_root(), source: RootSource::Legacy };
+        }
+        preferred
+    }
+
+    /// 不考虑旧版数据时应使用的存储根目录
+    fn preferred() -> Self {
+        if let Some(root) = env::var_os("RMM_ROOT").filter(|v| !v.is_empty()) {
+            return Self { root: PathBuf::from(root), source: RootSource::Env };
+        }
+
+        if let Some(prefix) = termux_prefix() {
+            return Self {
+                root: prefix.join("var").join("lib").join("rmm"),
+                source: RootSource::Termux,
+            };
+        }
+
+        if cfg!(target_os = "windows") {
+            if let Some(appdata) = env::var_os("APPDATA").filter(|v| !v.is_empty()) {
+                return Self { root: PathBuf::from(appdata).join("rmm"), source: RootSource::AppData };
+            }
+        } else {
+            let data_home = env::var_os("XDG_DATA_HOME")
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| home_dir().map(|h| h.join(".local").join("share")));
+            if let Some(data_home) = data_home {
+                return Self { root: data_home.join("rmm"), source: RootSource::Xdg };
+            }
+        }
+
+        Self { root: Self::legacy_root(), source: RootSource::Legacy }
+    }
+
+    /// 存储根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 根目录来源
+    pub fn source(&self) -> RootSource {
+        self.source
+    }
+
+    /// meta.toml 路径
+    pub fn meta_path(&self) -> PathBuf {
+        self.root.join("meta.toml")
+    }
+
+    /// 缓存目录
+    pub fn cache_dir(&self) -> PathBuf {
+        self.root.join("cache")
+    }
+
+    /// 设备模块快照目录
+    pub fn snapshots_dir(&self) -> PathBuf {
+        self.root.join("snapshots")
+    }
+
+    /// 回收站目录（保存最近一次破坏性操作删除的内容）
+    pub fn trash_dir(&self) -> PathBuf {
+        self.root.join("trash")
+    }
+
+    /// 模块模板目录
+    pub fn templates_dir(&self) -> PathBuf {
+        self.root.join("templates")
+    }
+
+    /// 签名密钥目录（<name>.pub 为受信任的公钥）
+    pub fn keys_dir(&self) -> PathBuf {
+        self.root.join("keys")
+    }
+
+    /// 旧版默认位置：~/data/adb/.rmm
+    pub fn legacy_root() -> PathBuf {
+        let mut path = home_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("data");
+        path.push("adb");
+        path.push(".rmm");
+        path
+    }
+
+    /// 旧版位置有 meta.toml 而当前根目录还没有（RMM_ROOT 显式指定时不考虑旧版位置）
+    fn legacy_pending(&self) -> bool {
+        self.source != RootSource::Env
+            && self.source != RootSource::Legacy
+            && Self::legacy_root().join("meta.toml").exists()
+            && !self.meta_path().exists()
+    }
+
+    /// 旧版数据等待迁移时返回迁移目标
+    pub fn pending_migration() -> Option<Self> {
+        Some(Self::preferred()).filter(Self::legacy_pending)
+    }
+
+    /// 将旧版位置的数据迁移到当前根目录（rmm env --migrate）
+    /// 仅在当前根目录尚无 meta.toml 且旧目录存在时迁移，返回迁移后的存储位置
+    pub fn migrate_legacy() -> Result<Option<Self>> {
+        let Some(target) = Self::pending_migration() else {
+            return Ok(None);
+        };
+        let legacy = Self::legacy_root();
+
+        if let Some(parent) = target.root.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        }
+
+        // 优先直接重命名，跨设备时回退为复制
+        if fs::rename(&legacy, &target.root).is_err() {
+            copy_dir_all(&legacy, &target.root)
+                .with_context(|| format!("Failed to migrate {} to {}", legacy.display(), target.root.display()))?;
+        }
+
+        Ok(Some(target))
+    }
+}
+
+/// 规范化路径，canonicalize 失败时（部分 Android/Termux 环境）回退为绝对路径
+pub fn canonicalize_or_absolute(path: &Path) -> PathBuf {
+    match path.canonicalize() {
+        Ok(p) => p,
+        Err(_) if path.is_absolute() => path.to_path_buf(),
+        Err(_) => env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+/// 检测 Termux 环境，返回 $PREFIX
+fn termux_prefix() -> Option<PathBuf> {
+    let prefix = env::var_os("PREFIX").map(PathBuf::from)?;
+    let is_termux = env::var_os("TERMUX_VERSION").is_some()
+        || prefix.to_string_lossy().contains("com.termux");
+    if is_termux { Some(prefix) } else { None }
+}
+
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+pub(crate) fn copy_dir_all(src: &Path, dest: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_storage_resolve_from_env() {
+        let temp_dir = tempdir().unwrap();
+        unsafe {
+            env::set_var("RMM_ROOT", temp_dir.path());
+        }
+        let storage = RmmStorage::resolve();
+        assert_eq!(storage.source(), RootSource::Env);
+        assert_eq!(storage.meta_path(), temp_dir.path().join("meta.toml"));
+
+        // 非旧版位置且来源为环境变量时不进行迁移
+        assert!(RmmStorage::pending_migration().is_none());
+        assert!(RmmStorage::migrate_legacy().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_canonicalize_or_absolute_fallback() {
+        let missing = PathBuf::from("/absolutely/nonexistent/rmm/path");
+        assert_eq!(canonicalize_or_absolute(&missing), missing);
+
+        let relative = canonicalize_or_absolute(Path::new("nonexistent_relative_dir"));
+        assert!(relative.is_absolute());
+    }
+}
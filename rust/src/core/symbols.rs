@@ -0,0 +1,230 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+use super::hashing::sha256_file;
+use super::process::{CommandExt, CommandKind};
+
+/// 符号包中的清单文件名
+pub const SYMBOLS_MANIFEST: &str = "symbols.json";
+
+/// Rmake.toml 中的 [build.symbols] 配置：剥离原生二进制的调试符号并单独打包
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct SymbolsConfig {
+    /// 是否拆分符号包，默认 true
+    #[serde(default = "default_split")]
+    pub split: bool,
+    /// strip 工具路径，默认依次尝试 RMM_STRIP、NDK 的 llvm-strip、llvm-strip、strip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip: Option<String>,
+    /// 只处理匹配这些模式的 ELF 文件（语义同 exclude），为空时处理全部
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+}
+
+fn default_split() -> bool {
+    true
+}
+
+/// 符号包清单中的一个二进制
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SymbolEntry {
+    /// 模块内路径，如 system/lib64/libfoo.so
+    pub path: String,
+    /// ELF GNU build-id（十六进制），用于匹配崩溃日志
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<String>,
+    /// 安装包中剥离后文件的 SHA-256
+    pub stripped_sha256: String,
+    pub original_size: u64,
+    pub stripped_size: u64,
+}
+
+/// 符号包清单
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SymbolsManifest {
+    pub module_id: String,
+    pub version_code: String,
+    pub entries: Vec<SymbolEntry>,
+}
+
+/// 文件是否为 ELF
+pub fn is_elf(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    fs::File::open(path)
+        .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut magic))
+        .is_ok_and(|_| magic == *b"\x7fELF")
+}
+
+/// 从 ELF 的 PT_NOTE 段读取 GNU build-id
+pub fn read_build_id(data: &[u8]) -> Option<String> {
+    if data.len() < 0x34 || &data[..4] != b"\x7fELF" {
+        return None;
+    }
+    let is_64 = data[4] == 2;
+    let little = data[5] == 1;
+    let read = |offset: usize, size: usize| -> Option<u64> {
+        let bytes = data.get(offset..offset + size)?;
+        let mut value = 0u64;
+        for i in 0..size {
+            let byte = if little { bytes[size - 1 - i] } else { bytes[i] };
+            value = (value << 8) | byte as u64;
+        }
+        Some(value)
+    };
+
+    let (phoff, phentsize, phnum) = if is_64 {
+        (read(0x20, 8)?, read(0x36, 2)?, read(0x38, 2)?)
+    } else {
+        (read(0x1c, 4)?, read(0x2a, 2)?, read(0x2c, 2)?)
+    };
+    for i in 0..phnum {
+        let ph = (phoff + i * phentsize) as usize;
+        if read(ph, 4)? != 4 {
+            continue; // 仅处理 PT_NOTE
+        }
+        let (offset, size) = if is_64 {
+            (read(ph + 0x08, 8)?, read(ph + 0x20, 8)?)
+        } else {
+            (read(ph + 0x04, 4)?, read(ph + 0x10, 4)?)
+        };
+        let (mut cursor, end) = (offset as usize, (offset + size) as usize);
+        while cursor + 12 <= end {
+            let namesz = read(cursor, 4)? as usize;
+            let descsz = read(cursor + 4, 4)? as usize;
+            let note_type = read(cursor + 8, 4)?;
+            let name_start = cursor + 12;
+            let desc_start = name_start + namesz.div_ceil(4) * 4;
+            if note_type == 3 && data.get(name_start..name_start + namesz)? == b"GNU\0" {
+                let desc = data.get(desc_start..desc_start + descsz)?;
+                return Some(desc.iter().map(|b| format!("{:02x}", b)).collect());
+            }
+            cursor = desc_start + descsz.div_ceil(4) * 4;
+        }
+    }
+    None
+}
+
+/// 查找可用的 strip 工具
+pub fn find_strip_tool(config: &SymbolsConfig) -> Option<String> {
+    let mut candidates: Vec<String> = Vec::new();
+    candidates.extend(config.strip.clone());
+    candidates.extend(std::env::var("RMM_STRIP").ok().filter(|s| !s.is_empty()));
+    for var in ["ANDROID_NDK_HOME", "ANDROID_NDK_ROOT", "NDK_HOME"] {
+        let Ok(ndk) = std::env::var(var) else { continue };
+        let prebuilt = Path::new(&ndk).join("toolchains/llvm/prebuilt");
+        for host in fs::read_dir(prebuilt).into_iter().flatten().filter_map(|e| e.ok()) {
+            let exe = if cfg!(windows) { "llvm-strip.exe" } else { "llvm-strip" };
+            candidates.push(host.path().join("bin").join(exe).to_string_lossy().to_string());
+        }
+    }
+    candidates.push("llvm-strip".to_string());
+    candidates.push("strip".to_string());
+
+    candidates.into_iter().find(|tool| {
+        Command::new(tool)
+            .arg("--version")
+            .output_with_timeout(CommandKind::Script)
+            .is_ok_and(|o| o.status.success())
+    })
+}
+
+/// 剥离构建目录中的 ELF 文件：原始文件保存到 symbols_dir，构建目录中的文件被 strip
+pub fn split_symbols(build_dir: &Path, symbols_dir: &Path, config: &SymbolsConfig, strip_tool: &str) -> Result<Vec<SymbolEntry>> {
+    if symbols_dir.exists() {
+        fs::remove_dir_all(symbols_dir)?;
+    }
+    let patterns: Vec<String> = config.include.iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty() && !p.starts_with('#'))
+        .map(|p| regex::escape(p).replace(r"\*", ".*"))
+        .collect();
+    let include = regex::RegexSet::new(&patterns)?;
+    let matches_include = |relative: &str| patterns.is_empty() || include.is_match(relative);
+
+    let binaries: Vec<(PathBuf, String)> = WalkDir::new(build_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_elf(e.path()))
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(build_dir).ok()?.to_string_lossy().replace('\\', "/");
+            matches_include(&relative).then(|| (e.path().to_path_buf(), relative))
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    for (path, relative) in binaries {
+        let original = fs::read(&path)?;
+        let saved = symbols_dir.join(&relative);
+        if let Some(parent) = saved.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&saved, &original)?;
+
+        let output = Command::new(strip_tool)
+            .arg("--strip-unneeded")
+            .arg(&path)
+            .output_with_timeout(CommandKind::Script)?;
+        if !output.status.success() {
+            anyhow::bail!("strip {} 失败: {}", relative, String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        entries.push(SymbolEntry {
+            path: relative,
+            build_id: read_build_id(&original),
+            stripped_sha256: sha256_file(&path)?,
+            original_size: original.len() as u64,
+            stripped_size: fs::metadata(&path)?.len(),
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_split_symbols_and_build_id() {
+        // 最小 ELF64：一个 PT_NOTE 段，包含 GNU build-id deadbeef
+        let mut elf = vec![0u8; 64];
+        elf[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        elf[0x20..0x28].copy_from_slice(&64u64.to_le_bytes()); // e_phoff
+        elf[0x36..0x38].copy_from_slice(&56u16.to_le_bytes()); // e_phentsize
+        elf[0x38..0x3a].copy_from_slice(&1u16.to_le_bytes()); // e_phnum
+        let mut phdr = vec![0u8; 56];
+        phdr[..4].copy_from_slice(&4u32.to_le_bytes()); // PT_NOTE
+        phdr[0x08..0x10].copy_from_slice(&120u64.to_le_bytes());
+        phdr[0x20..0x28].copy_from_slice(&20u64.to_le_bytes());
+        elf.extend(phdr);
+        for value in [4u32, 4, 3] {
+            elf.extend(value.to_le_bytes());
+        }
+        elf.extend(b"GNU\0");
+        elf.extend([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(read_build_id(&elf).as_deref(), Some("deadbeef"));
+        assert_eq!(read_build_id(b"not an elf"), None);
+
+        let temp = tempdir().unwrap();
+        let build_dir = temp.path().join("build");
+        fs::create_dir_all(build_dir.join("system/lib64")).unwrap();
+        fs::write(build_dir.join("system/lib64/libdemo.so"), &elf).unwrap();
+        fs::write(build_dir.join("module.prop"), "id=demo\n").unwrap();
+
+        let config: SymbolsConfig = toml::from_str("include = [\"*.so\"]").unwrap();
+        assert!(config.split);
+        // 使用 true 代替 strip：只验证拆分流程
+        let entries = split_symbols(&build_dir, &temp.path().join("symbols"), &config, "true").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "system/lib64/libdemo.so");
+        assert_eq!(entries[0].build_id.as_deref(), Some("deadbeef"));
+        assert!(temp.path().join("symbols/system/lib64/libdemo.so").exists());
+        assert!(!temp.path().join("symbols/module.prop").exists());
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::hashing::{sha256_bytes, sha256_file};
+use super::toml_doc::write_toml;
+
+/// 模板清单文件名（位于模板根目录）
+pub const TEMPLATE_MANIFEST: &str = "template.toml";
+/// 已安装模板的来源记录（位于 RMM_ROOT/templates 下）
+const REGISTRY_FILE: &str = "registry.toml";
+
+/// template.toml
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+/// 模板来源
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateOrigin {
+    /// 安装时使用的来源，如 github:user/repo#webui
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// 模板内容摘要
+    pub digest: String,
+    pub installed_at: String,
+}
+
+/// 已安装模板的来源登记表
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TemplateRegistry {
+    #[serde(default)]
+    pub templates: BTreeMap<String, TemplateOrigin>,
+}
+
+impl TemplateRegistry {
+    pub fn load(templates_dir: &Path) -> Result<Self> {
+        let path = templates_dir.join(REGISTRY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, templates_dir: &Path) -> Result<()> {
+        fs::create_dir_all(templates_dir)?;
+        write_toml(&templates_dir.join(REGISTRY_FILE), self)?;
+        Ok(())
+    }
+}
+
+/// 模板来源说明，如 github:user/repo#webui
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateSpec {
+    /// git 仓库地址或本地路径
+    pub url: String,
+    /// 仓库中的模板目录
+    pub subdir: Option<String>,
+}
+
+/// 解析模板来源：github:/gitlab: 简写、git 地址或本地路径，`#` 后为仓库内目录
+pub fn parse_spec(spec: &str) -> Result<TemplateSpec> {
+    let (source, subdir) = match spec.split_once('#') {
+        Some((source, subdir)) => (source, Some(subdir.trim_matches('/').to_string()).filter(|s| !s.is_empty())),
+        None => (spec, None),
+    };
+    if source.is_empty() {
+        anyhow::bail!("无效的模板来源: '{}'", spec);
+    }
+
+    let url = if let Some(repo) = source.strip_prefix("github:") {
+        format!("https://github.com/{}.git", repo.trim_end_matches(".git"))
+    } else if let Some(repo) = source.strip_prefix("gitlab:") {
+        format!("https://gitlab.com/{}.git", repo.trim_end_matches(".git"))
+    } else {
+        source.to_string()
+    };
+    Ok(TemplateSpec { url, subdir })
+}
+
+/// 读取并校验模板清单
+pub fn load_manifest(template_dir: &Path) -> Result<TemplateManifest> {
+    let path = template_dir.join(TEMPLATE_MANIFEST);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("模板缺少 {}: {}", TEMPLATE_MANIFEST, template_dir.display()))?;
+    let manifest: TemplateManifest = toml::from_str(&content)
+        .with_context(|| format!("无法解析 {}", path.display()))?;
+
+    let name_regex = regex::Regex::new(r"^[a-zA-Z0-9][a-zA-Z0-9._-]*$").unwrap();
+    if !name_regex.is_match(&manifest.name) {
+        anyhow::bail!("模板名称无效: '{}'", manifest.name);
+    }
+    if manifest.version.trim().is_empty() {
+        anyhow::bail!("模板 '{}' 缺少 version", manifest.name);
+    }
+    Ok(manifest)
+}
+
+/// 模板文件列表（相对路径，已排序，排除 .git）；模板中不允许出现符号链接
+fn template_files(template_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let walker = WalkDir::new(template_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git");
+    for entry in walker {
+        let entry = entry?;
+        if entry.path_is_symlink() {
+            anyhow::bail!("模板中不允许符号链接: {}", entry.path().display());
+        }
+        if entry.file_type().is_file() {
+            files.push(entry.path().strip_prefix(template_dir)?.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+/// 模板内容摘要：相对路径与文件哈希的组合
+pub fn template_digest(template_dir: &Path) -> Result<String> {
+    let mut content = String::new();
+    for relative in template_files(template_dir)? {
+        content.push_str(&relative.to_string_lossy().replace('\\', "/"));
+        content.push('\0');
+        content.push_str(&sha256_file(&template_dir.join(&relative))?);
+        content.push('\n');
+    }
+    Ok(sha256_bytes(content.as_bytes()))
+}
+
+/// 复制模板目录（排除 .git）
+fn copy_template(source: &Path, target: &Path) -> Result<()> {
+    for relative in template_files(source)? {
+        let dest = target.join(&relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source.join(&relative), dest)?;
+    }
+    Ok(())
+}
+
+/// 从目录安装模板到 templates_dir，并登记来源
+pub fn install_from_dir(
+    source_dir: &Path,
+    templates_dir: &Path,
+    source: &str,
+    commit: Option<String>,
+    force: bool,
+) -> Result<TemplateManifest> {
+    let manifest = load_manifest(source_dir)?;
+    let digest = template_digest(source_dir)?;
+
+    let target = templates_dir.join(&manifest.name);
+    if target.exists() {
+        if !force {
+            anyhow::bail!("模板 '{}' 已安装，使用 --force 覆盖", manifest.name);
+        }
+        fs::remove_dir_all(&target)?;
+    }
+    copy_template(source_dir, &target)?;
+
+    // 复制后重新校验，确保内容与来源一致
+    if template_digest(&target)? != digest {
+        fs::remove_dir_all(&target)?;
+        anyhow::bail!("模板 '{}' 校验失败：安装内容与来源不一致", manifest.name);
+    }
+
+    let mut registry = TemplateRegistry::load(templates_dir)?;
+    registry.templates.insert(manifest.name.clone(), TemplateOrigin {
+        source: source.to_string(),
+        commit,
+        digest,
+        installed_at: chrono::Utc::now().to_rfc3339(),
+    });
+    registry.save(templates_dir)?;
+    Ok(manifest)
+}
+
+/// 已安装的模板
+#[derive(Debug, Clone)]
+pub struct TemplateInfo {
+    pub manifest: TemplateManifest,
+    pub path: PathBuf,
+    /// 手动放入 templates 目录的模板没有来源记录
+    pub origin: Option<TemplateOrigin>,
+}
+
+/// 列出 templates_dir 中的模板
+pub fn list_templates(templates_dir: &Path) -> Result<Vec<TemplateInfo>> {
+    if !templates_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let registry = TemplateRegistry::load(templates_dir)?;
+    let mut templates: Vec<TemplateInfo> = fs::read_dir(templates_dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.join(TEMPLATE_MANIFEST).is_file())
+        .filter_map(|path| {
+            let manifest = load_manifest(&path).ok()?;
+            let origin = registry.templates.get(&manifest.name).cloned();
+            Some(TemplateInfo { manifest, path, origin })
+        })
+        .collect();
+    templates.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    Ok(templates)
+}
+
+/// 将模板应用到项目目录，文本文件中的 {{key}} 会被替换；返回写入的文件
+pub fn apply_template(template_dir: &Path, project_path: &Path, vars: &BTreeMap<&str, String>) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for relative in template_files(template_dir)? {
+        if relative == Path::new(TEMPLATE_MANIFEST) {
+            continue;
+        }
+        let dest = project_path.join(&relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let bytes = fs::read(template_dir.join(&relative))?;
+        match String::from_utf8(bytes) {
+            Ok(mut text) => {
+                for (key, value) in vars {
+                    text = text.replace(&format!("{{{{{}}}}}", key), value);
+                }
+                fs::write(&dest, text)?;
+            }
+            Err(e) => fs::write(&dest, e.into_bytes())?,
+        }
+        written.push(relative);
+    }
+    Ok(written)
+}
@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, TableLike, Value};
+
+/// 将 value 合并进已有的 TOML 文本：未变化的值保持原样，注释、空行与键顺序不变
+/// value 中不存在的键会被删除，新键追加到所在表的末尾
+pub fn merge_into_document<T: Serialize>(existing: &str, value: &T) -> Result<String> {
+    let mut document: DocumentMut = existing.parse().context("现有文件不是有效的 TOML")?;
+    let source: DocumentMut = toml::to_string_pretty(value)?.parse()?;
+    merge_table_like(document.as_table_mut(), source.as_table());
+    Ok(document.to_string())
+}
+
+/// 写入 TOML 文件；文件已存在且可解析时保留其中的注释与格式
+pub fn write_toml<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let existing = fs::read_to_string(path).ok();
+    let content = match existing.as_deref().map(|text| merge_into_document(text, value)) {
+        Some(Ok(merged)) => merged,
+        // 文件不存在或已损坏：直接序列化
+        _ => toml::to_string_pretty(value)?,
+    };
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn merge_table_like(target: &mut dyn TableLike, source: &dyn TableLike) {
+    let removed: Vec<String> = target.iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !source.contains_key(key))
+        .collect();
+    for key in removed {
+        target.remove(&key);
+    }
+    for (key, item) in source.iter() {
+        match target.get_mut(key) {
+            Some(existing) => merge_item(existing, item),
+            None => {
+                target.insert(key, detached(item));
+            }
+        }
+    }
+}
+
+fn merge_item(target: &mut Item, source: &Item) {
+    if let (Some(target_table), Some(source_table)) = (target.as_table_like_mut(), source.as_table_like()) {
+        merge_table_like(target_table, source_table);
+        return;
+    }
+    match (target, source) {
+        (Item::ArrayOfTables(target_array), Item::ArrayOfTables(source_array)) if target_array.len() == source_array.len() => {
+            for (target_table, source_table) in target_array.iter_mut().zip(source_array.iter()) {
+                merge_table_like(target_table, source_table);
+            }
+        }
+        (Item::Value(target_value), Item::Value(source_value)) => {
+            if !same_value(target_value, source_value) {
+                // 保留原值两侧的空白与行尾注释
+                let decor = target_value.decor().clone();
+                *target_value = source_value.clone();
+                *target_value.decor_mut() = decor;
+            }
+        }
+        (target, source) => *target = detached(source),
+    }
+}
+
+/// 语义比较两个值（忽略格式、注释与多行排版）
+fn same_value(left: &Value, right: &Value) -> bool {
+    let parse = |value: &Value| format!("v = {}", value).parse::<toml::Table>().ok();
+    parse(left) == parse(right)
+}
+
+/// 复制来自另一文档的条目并清除其表位置，使其按插入位置输出
+fn detached(item: &Item) -> Item {
+    match item {
+        Item::Table(table) => Item::Table(detached_table(table)),
+        Item::ArrayOfTables(array) => {
+            let mut copy = ArrayOfTables::new();
+            for table in array.iter() {
+                copy.push(detached_table(table));
+            }
+            Item::ArrayOfTables(copy)
+        }
+        other => other.clone(),
+    }
+}
+
+fn detached_table(table: &Table) -> Table {
+    let mut copy = Table::new();
+    copy.set_implicit(table.is_implicit());
+    for (key, item) in table.iter() {
+        copy.insert(key, detached(item));
+    }
+    copy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_toml_writer_preserves_comments_and_order() {
+        use std::collections::BTreeMap;
+
+        #[derive(serde::Serialize)]
+        struct Build {
+            exclude: Vec<String>,
+            scripts: BTreeMap<String, String>,
+        }
+        #[derive(serde::Serialize)]
+        struct Config {
+            version: String,
+            name: String,
+            build: Build,
+        }
+
+        let existing = r#"# 用户注释
+name = "demo"   # 行尾注释
+
+version = "1.0"
+
+[build]
+# 排除列表
+exclude = [
+    "*.log", # 日志
+]
+removed = true
+
+[build.scripts]
+test = "echo test" # 测试
+"#;
+        let config = Config {
+            version: "1.1".to_string(),
+            name: "demo".to_string(),
+            build: Build {
+                exclude: vec!["*.log".to_string()],
+                scripts: BTreeMap::from([("test".to_string(), "echo test".to_string()), ("lint".to_string(), "shellcheck".to_string())]),
+            },
+        };
+        let merged = merge_into_document(existing, &config).unwrap();
+        assert_eq!(merged, r#"# 用户注释
+name = "demo"   # 行尾注释
+
+version = "1.1"
+
+[build]
+# 排除列表
+exclude = [
+    "*.log", # 日志
+]
+
+[build.scripts]
+test = "echo test" # 测试
+lint = "shellcheck"
+"#);
+
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("config.toml");
+        write_toml(&path, &config).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().starts_with("version = \"1.1\""));
+        fs::write(&path, existing).unwrap();
+        write_toml(&path, &config).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), merged);
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::Result;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use super::adb::shell_quote;
+
+/// 模块卸载时由管理器执行的脚本
+pub const UNINSTALL_SCRIPT: &str = "uninstall.sh";
+/// 运行时会写入文件的脚本（检查其中写入的路径是否会在卸载时清理）
+pub const RUNTIME_SCRIPTS: [&str; 5] = ["post-fs-data.sh", "post-mount.sh", "service.sh", "boot-completed.sh", "action.sh"];
+/// 卸载后仍会保留的持久化位置，写到其他位置（/dev、/proc 等）的文件重启后即消失
+const PERSISTENT_ROOTS: [&str; 4] = ["/data/", "/sdcard/", "/storage/", "/cache/"];
+/// 不允许整体删除的目录
+const PROTECTED_PATHS: [&str; 12] = [
+    "/data", "/data/adb", "/data/adb/modules", "/data/local", "/data/local/tmp", "/data/data", "/data/media",
+    "/sdcard", "/sdcard/Android", "/storage", "/storage/emulated/0", "/cache",
+];
+
+/// Rmake.toml 中的 [uninstall] 配置：生成 uninstall.sh 清理模块运行时留下的文件
+///
+/// ```toml
+/// [uninstall]
+/// paths = ["/data/adb/demo", "/sdcard/Android/demo"]   # 卸载时删除
+/// waive = ["/data/local/tmp/demo.pid"]                 # 无需清理（支持 glob）
+/// commands = ["settings delete global demo_flag"]       # 额外执行的命令
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct UninstallConfig {
+    /// 卸载时删除的文件或目录（目录递归删除）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// 确认无需清理的路径，rmm check 不再报告
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waive: Vec<String>,
+    /// 删除文件后执行的命令
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+}
+
+/// 脚本在运行时写入的持久化路径
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuntimeWrite {
+    pub script: String,
+    pub line: usize,
+    pub path: String,
+}
+
+/// 清理路径必须是绝对路径，且不能是系统目录本身
+fn validate_cleanup_path(path: &str) -> Result<()> {
+    let trimmed = path.trim_end_matches('/');
+    if !path.starts_with('/') {
+        anyhow::bail!("[uninstall] 中的路径必须是绝对路径: {}", path);
+    }
+    if trimmed.is_empty() || PROTECTED_PATHS.contains(&trimmed) || trimmed.split('/').any(|part| part == "..") {
+        anyhow::bail!("[uninstall] 拒绝删除系统目录: {}", path);
+    }
+    Ok(())
+}
+
+/// 根据 [uninstall] 生成清理片段；existing 为项目自带的 uninstall.sh，生成内容追加在其后
+pub fn generate_uninstall_script(config: &UninstallConfig, existing: Option<&str>) -> Result<String> {
+    for path in &config.paths {
+        validate_cleanup_path(path)?;
+    }
+    let mut script = match existing {
+        Some(existing) => format!("{}\n", existing.trim_end()),
+        None => String::from("#!/system/bin/sh\nMODDIR=${0%/*}\n"),
+    };
+    script.push_str("\n# 由 rmm 根据 Rmake.toml [uninstall] 生成\n");
+    for path in &config.paths {
+        script.push_str(&format!("rm -rf {}\n", shell_quote(path)));
+    }
+    for command in &config.commands {
+        script.push_str(&format!("{}\n", command));
+    }
+    Ok(script)
+}
+
+/// 写入文件的常见形式：重定向、mkdir、touch、tee、cp/mv 的目标
+static WRITE_PATTERNS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r#">>?\s*"?(/[^\s"'|;&)]+)"#,
+        r#"\b(?:mkdir|touch)\s+(?:-\w+\s+)*"?(/[^\s"'|;&)]+)"#,
+        r#"\btee\s+(?:-a\s+)?"?(/[^\s"'|;&)]+)"#,
+        r#"\b(?:cp|mv|ln)\s+(?:-\w+\s+)*\S+\s+"?(/[^\s"'|;&)]+)"#,
+    ]
+    .iter()
+    .map(|pattern| Regex::new(pattern).expect("invalid write pattern"))
+    .collect()
+});
+
+/// 启发式找出脚本写入的持久化路径（跳过注释与 $MODDIR 下的文件）
+pub fn detect_runtime_writes(script: &str, content: &str) -> Vec<RuntimeWrite> {
+    let mut writes = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        for pattern in WRITE_PATTERNS.iter() {
+            for captures in pattern.captures_iter(line) {
+                let path = captures[1].to_string();
+                let persistent = PERSISTENT_ROOTS.iter().any(|root| path.starts_with(root));
+                if persistent && !writes.iter().any(|w: &RuntimeWrite| w.line == index + 1 && w.path == path) {
+                    writes.push(RuntimeWrite { script: script.to_string(), line: index + 1, path });
+                }
+            }
+        }
+    }
+    writes
+}
+
+/// 路径是否已被清理：位于 [uninstall] paths 之下、匹配 waive，或出现在项目自带的 uninstall.sh 中
+fn is_covered(path: &str, config: Option<&UninstallConfig>, uninstall_script: &str) -> bool {
+    let under = |root: &str| {
+        let root = root.trim_end_matches('/');
+        path == root || path.strip_prefix(root).is_some_and(|rest| rest.starts_with('/'))
+    };
+    if let Some(config) = config {
+        if config.paths.iter().any(|p| under(p)) {
+            return true;
+        }
+        if config.waive.iter().any(|p| under(p) || glob::Pattern::new(p).is_ok_and(|g| g.matches(path))) {
+            return true;
+        }
+    }
+    // 手写的 uninstall.sh 删除了该路径或其上级目录
+    let mut candidate = Some(path);
+    while let Some(current) = candidate.filter(|c| !c.is_empty() && !PROTECTED_PATHS.contains(c)) {
+        if uninstall_script.contains(current) {
+            return true;
+        }
+        candidate = current.rsplit_once('/').map(|(parent, _)| parent);
+    }
+    false
+}
+
+/// 检查项目运行时脚本写入的路径是否都会在卸载时清理，返回未覆盖的写入
+pub fn uncovered_runtime_writes(project_path: &Path, config: Option<&UninstallConfig>) -> Vec<RuntimeWrite> {
+    let uninstall_script = fs::read_to_string(project_path.join(UNINSTALL_SCRIPT)).unwrap_or_default();
+    RUNTIME_SCRIPTS.iter()
+        .filter_map(|script| fs::read_to_string(project_path.join(script)).ok().map(|content| (script, content)))
+        .flat_map(|(script, content)| detect_runtime_writes(script, &content))
+        .filter(|write| !is_covered(&write.path, config, &uninstall_script))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_uninstall_script_covers_runtime_writes() {
+        let service = r#"#!/system/bin/sh
+MODDIR=${0%/*}
+# echo 1 > /data/commented
+mkdir -p /data/adb/demo/cache
+echo "$(date)" >> /data/adb/demo/log.txt
+echo 1 > /proc/sys/vm/swappiness
+cp "$MODDIR/config.json" /sdcard/Android/demo.json
+touch $MODDIR/flag 2>/dev/null
+settings get global x | tee -a /data/local/tmp/demo.state
+"#;
+        let paths: Vec<(usize, String)> = detect_runtime_writes("service.sh", service).into_iter().map(|w| (w.line, w.path)).collect();
+        assert_eq!(paths, vec![
+            (4, "/data/adb/demo/cache".to_string()),
+            (5, "/data/adb/demo/log.txt".to_string()),
+            (7, "/sdcard/Android/demo.json".to_string()),
+            (9, "/data/local/tmp/demo.state".to_string()),
+        ]);
+
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        fs::write(project.join("service.sh"), service).unwrap();
+        let config = UninstallConfig {
+            paths: vec!["/data/adb/demo/".to_string()],
+            waive: vec!["/data/local/tmp/*.state".to_string()],
+            commands: Vec::new(),
+        };
+        let uncovered = uncovered_runtime_writes(project, Some(&config));
+        assert_eq!(uncovered.iter().map(|w| w.path.as_str()).collect::<Vec<_>>(), vec!["/sdcard/Android/demo.json"]);
+        fs::write(project.join("uninstall.sh"), "rm -f /sdcard/Android/demo.json\n").unwrap();
+        assert!(uncovered_runtime_writes(project, Some(&config)).is_empty());
+        assert_eq!(uncovered_runtime_writes(project, None).len(), 3);
+
+        let script = generate_uninstall_script(&config, Some("#!/system/bin/sh\nrm -f /sdcard/x\n")).unwrap();
+        assert!(script.starts_with("#!/system/bin/sh\nrm -f /sdcard/x\n\n# "));
+        assert!(script.ends_with("rm -rf '/data/adb/demo/'\n"));
+        for dangerous in ["/data/adb", "/sdcard/", "data/demo", "/data/adb/demo/../.."] {
+            let config = UninstallConfig { paths: vec![dangerous.to_string()], ..Default::default() };
+            assert!(generate_uninstall_script(&config, None).is_err(), "{}", dangerous);
+        }
+    }
+}
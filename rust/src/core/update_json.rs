@@ -0,0 +1,448 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::release_notes::changelog_excerpt;
+
+/// Rmake.toml 中的 [update] 配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct UpdateConfig {
+    /// update.json 托管方式
+    #[serde(default)]
+    pub backend: UpdateBackend,
+    /// 每个发布通道的附加元数据，如 [update.channels.beta]
+    #[serde(default)]
+    pub channels: BTreeMap<String, ChannelConfig>,
+    /// GitHub Pages 托管配置，如 [update.pages]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pages: Option<PagesConfig>,
+    /// update.json 中 changelog 指向的内容
+    #[serde(default)]
+    pub changelog: ChangelogMode,
+}
+
+/// update.json 的 changelog 字段
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangelogMode {
+    /// 只包含当前版本段落的 changelog-<versionCode>.md，与 zip 一同上传（默认）
+    #[default]
+    Excerpt,
+    /// 保持 update.json 中原有的链接（通常是完整的 CHANGELOG.md）
+    Full,
+}
+
+/// update.json 托管方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateBackend {
+    /// 作为 Release 附件发布（默认）
+    #[default]
+    Release,
+    /// 发布到 GitHub Pages（gh-pages 分支或 docs/ 目录）
+    Pages,
+}
+
+/// [update.pages] 配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct PagesConfig {
+    /// 发布分支，默认 gh-pages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// 发布到当前分支的目录（如 docs），设置后忽略 branch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+}
+
+/// 单个发布通道的附加元数据
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct ChannelConfig {
+    /// 最低 Magisk 版本号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_magisk: Option<u32>,
+    /// 最低 KernelSU 版本号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_ksu: Option<u32>,
+    /// 灰度发布百分比 (0-100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rollout_percent: Option<u8>,
+    /// 发布说明链接
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_notes_url: Option<String>,
+}
+
+/// update.json 基础字段（所有管理器都能理解）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UpdateManifest {
+    pub version: String,
+    #[serde(rename = "versionCode")]
+    pub version_code: i64,
+    #[serde(rename = "zipUrl")]
+    pub zip_url: String,
+    pub changelog: String,
+}
+
+/// update.json 可选扩展字段
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct RolloutFields {
+    #[serde(rename = "minMagisk", skip_serializing_if = "Option::is_none")]
+    pub min_magisk: Option<u32>,
+    #[serde(rename = "minKsu", skip_serializing_if = "Option::is_none")]
+    pub min_ksu: Option<u32>,
+    #[serde(rename = "rolloutPercent", skip_serializing_if = "Option::is_none")]
+    pub rollout_percent: Option<u8>,
+    #[serde(rename = "releaseNotesUrl", skip_serializing_if = "Option::is_none")]
+    pub release_notes_url: Option<String>,
+}
+
+impl From<&ChannelConfig> for RolloutFields {
+    fn from(channel: &ChannelConfig) -> Self {
+        Self {
+            min_magisk: channel.min_magisk,
+            min_ksu: channel.min_ksu,
+            rollout_percent: channel.rollout_percent,
+            release_notes_url: channel.release_notes_url.clone(),
+        }
+    }
+}
+
+/// 带扩展字段的完整 update.json
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChannelManifest {
+    #[serde(flatten)]
+    pub base: UpdateManifest,
+    #[serde(flatten)]
+    pub rollout: RolloutFields,
+}
+
+/// 从 JSON 文本解析并校验 update.json
+pub fn parse_manifest(content: &str) -> Result<ChannelManifest> {
+    let value: serde_json::Value = serde_json::from_str(content)
+        .with_context(|| "Failed to parse update.json")?;
+    verify_manifest(&value)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// 校验 update.json：基础字段必须完整且类型正确，扩展字段可选但必须合法
+/// 去掉所有扩展字段后剩余部分必须仍是一个有效的基础清单
+pub fn verify_manifest(value: &serde_json::Value) -> Result<()> {
+    let obj = value.as_object()
+        .ok_or_else(|| anyhow::anyhow!("update.json 必须是 JSON 对象"))?;
+
+    for key in ["version", "zipUrl", "changelog"] {
+        match obj.get(key) {
+            Some(serde_json::Value::String(s)) if !s.trim().is_empty() => {}
+            Some(_) => anyhow::bail!("update.json 字段 '{}' 必须是非空字符串", key),
+            None => anyhow::bail!("update.json 缺少必需字段 '{}'", key),
+        }
+    }
+    match obj.get("versionCode") {
+        Some(v) if v.is_i64() => {}
+        Some(_) => anyhow::bail!("update.json 字段 'versionCode' 必须是整数"),
+        None => anyhow::bail!("update.json 缺少必需字段 'versionCode'"),
+    }
+
+    for key in ["minMagisk", "minKsu"] {
+        if obj.get(key).is_some_and(|v| !v.is_u64()) {
+            anyhow::bail!("update.json 字段 '{}' 必须是非负整数", key);
+        }
+    }
+    if let Some(v) = obj.get("rolloutPercent") {
+        match v.as_u64() {
+            Some(p) if p <= 100 => {}
+            _ => anyhow::bail!("update.json 字段 'rolloutPercent' 必须在 0-100 之间"),
+        }
+    }
+    if obj.get("releaseNotesUrl").is_some_and(|v| !v.is_string()) {
+        anyhow::bail!("update.json 字段 'releaseNotesUrl' 必须是字符串");
+    }
+
+    Ok(())
+}
+
+/// 校验通道配置
+pub fn verify_channel(name: &str, channel: &ChannelConfig) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!("无效的通道名称: '{}'", name);
+    }
+    if channel.rollout_percent.is_some_and(|p| p > 100) {
+        anyhow::bail!("通道 '{}' 的 rollout_percent 必须在 0-100 之间", name);
+    }
+    Ok(())
+}
+
+/// 当前版本的更新日志摘录文件名，带 versionCode 以便随产物一同发布
+pub fn changelog_excerpt_name(version_code: i64) -> String {
+    format!("changelog-{}.md", version_code)
+}
+
+/// 与 zipUrl 同目录的下载地址（Release 附件彼此相邻）
+pub fn sibling_url(zip_url: &str, name: &str) -> Option<String> {
+    let path = zip_url.split(['?', '#']).next()?;
+    let (dir, file) = path.rsplit_once('/')?;
+    let (_, host) = dir.split_once("://")?;
+    (!host.is_empty() && !file.is_empty()).then(|| format!("{}/{}", dir, name))
+}
+
+/// 从项目的 CHANGELOG.md 截取当前版本段落写入分发目录，并将 changelog 指向它；
+/// 没有 CHANGELOG.md、找不到段落或 zipUrl 不是网址时保持原样
+fn write_changelog_excerpt(project_path: &Path, dist_dir: &Path, manifest: &mut UpdateManifest) -> Result<Option<PathBuf>> {
+    let Ok(changelog) = fs::read_to_string(project_path.join("CHANGELOG.md")) else {
+        return Ok(None);
+    };
+    let Some(excerpt) = changelog_excerpt(&changelog, &manifest.version) else {
+        return Ok(None);
+    };
+    let name = changelog_excerpt_name(manifest.version_code);
+    let Some(url) = sibling_url(&manifest.zip_url, &name) else {
+        return Ok(None);
+    };
+    let path = dist_dir.join(&name);
+    fs::write(&path, format!("## {}\n\n{}\n", manifest.version, excerpt))?;
+    manifest.changelog = url;
+    Ok(Some(path))
+}
+
+/// 构建时由项目根目录 update.json 生成的副本所在目录
+pub const UPDATE_JSON_COPY_DIRS: [&str; 3] = [".rmmp/dist", ".rmmp/build", ".rmmp/source-build"];
+
+/// update.json 或 update-<channel>.json
+fn is_manifest_name(name: &str) -> bool {
+    name == "update.json" || name.strip_prefix("update-").is_some_and(|rest| rest.ends_with(".json"))
+}
+
+/// .rmmp 各输出目录中的 update.json 副本（含通道清单）
+pub fn update_json_copies(project_path: &Path) -> Vec<PathBuf> {
+    let mut copies: Vec<PathBuf> = UPDATE_JSON_COPY_DIRS.iter()
+        .filter_map(|dir| fs::read_dir(project_path.join(dir)).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| entry.file_name().to_str().is_some_and(is_manifest_name))
+        .map(|entry| entry.path())
+        .collect();
+    copies.sort();
+    copies
+}
+
+/// 与项目根目录 update.json 不一致的副本及原因；根目录的 update.json 是唯一来源，
+/// 副本只比较 version、versionCode、zipUrl（分发目录中的 changelog 会被改写为摘录）
+pub fn divergent_update_json(project_path: &Path) -> Vec<(PathBuf, String)> {
+    let source = fs::read_to_string(project_path.join("update.json")).ok()
+        .and_then(|content| parse_manifest(&content).ok());
+    update_json_copies(project_path)
+        .into_iter()
+        .filter_map(|copy| {
+            let Some(source) = &source else {
+                return Some((copy, "项目根目录没有有效的 update.json，该副本已失去来源".to_string()));
+            };
+            let manifest = match fs::read_to_string(&copy).map_err(anyhow::Error::from).and_then(|c| parse_manifest(&c)) {
+                Ok(manifest) => manifest.base,
+                Err(e) => return Some((copy, format!("副本无效: {}", e))),
+            };
+            let differences: Vec<String> = [
+                ("version", manifest.version != source.base.version, manifest.version.clone()),
+                ("versionCode", manifest.version_code != source.base.version_code, manifest.version_code.to_string()),
+                ("zipUrl", manifest.zip_url != source.base.zip_url, manifest.zip_url.clone()),
+            ]
+            .into_iter()
+            .filter(|(_, differs, _)| *differs)
+            .map(|(key, _, value)| format!("{} = {}", key, value))
+            .collect();
+            (!differences.is_empty()).then(|| (copy, format!("与项目根目录的 update.json 不一致（{}）", differences.join(", "))))
+        })
+        .collect()
+}
+
+/// 删除分发目录中本次构建不会重新生成的清单：根目录没有 update.json 时全部删除，
+/// 否则删除已从 [update.channels] 移除的通道清单；返回删除的文件
+pub fn remove_stale_manifests(project_path: &Path, config: Option<&UpdateConfig>) -> Result<Vec<PathBuf>> {
+    let dist_dir = project_path.join(".rmmp/dist");
+    let has_source = project_path.join("update.json").is_file();
+    let mut removed = Vec::new();
+    for copy in update_json_copies(project_path).into_iter().filter(|copy| copy.starts_with(&dist_dir)) {
+        let name = copy.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let channel = name.strip_prefix("update-").and_then(|rest| rest.strip_suffix(".json"));
+        let regenerated = has_source && match channel {
+            None => true,
+            Some(channel) => config.is_some_and(|c| c.channels.contains_key(channel)),
+        };
+        if !regenerated {
+            fs::remove_file(&copy)?;
+            removed.push(copy);
+        }
+    }
+    Ok(removed)
+}
+
+/// 生成分发目录中的 update.json 以及每个通道的 update-<channel>.json；
+/// 默认同时生成当前版本的更新日志摘录（见 [update] changelog）
+/// 返回写入的文件列表，摘录文件（如有）位于最后
+pub fn write_channel_manifests(
+    source: &Path,
+    dist_dir: &Path,
+    config: Option<&UpdateConfig>,
+) -> Result<Vec<PathBuf>> {
+    let content = fs::read_to_string(source)
+        .with_context(|| format!("Failed to read {}", source.display()))?;
+    let mut manifest = parse_manifest(&content)?;
+
+    let excerpt = match config.map(|c| c.changelog).unwrap_or_default() {
+        ChangelogMode::Excerpt => {
+            let project_path = source.parent().unwrap_or(Path::new("."));
+            write_changelog_excerpt(project_path, dist_dir, &mut manifest.base)?
+        }
+        ChangelogMode::Full => None,
+    };
+
+    let mut written = Vec::new();
+
+    // 基础清单：只保留所有管理器都能理解的字段
+    let base_path = dist_dir.join("update.json");
+    fs::write(&base_path, serde_json::to_string_pretty(&manifest.base)?)?;
+    written.push(base_path);
+
+    if let Some(config) = config {
+        for (name, channel) in &config.channels {
+            verify_channel(name, channel)?;
+            let channel_manifest = ChannelManifest {
+                base: manifest.base.clone(),
+                rollout: RolloutFields::from(channel),
+            };
+            let value = serde_json::to_value(&channel_manifest)?;
+            verify_manifest(&value)?;
+
+            let path = dist_dir.join(format!("update-{}.json", name));
+            fs::write(&path, serde_json::to_string_pretty(&value)?)?;
+            written.push(path);
+        }
+    }
+
+    written.extend(excerpt);
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_update_json_channel_manifests() {
+        let temp_dir = tempdir().unwrap();
+        let source = temp_dir.path().join("update.json");
+        fs::write(&source, r#"{
+  "changelog": "https://example.com/CHANGELOG.md",
+  "version": "v0.1.0",
+  "versionCode": 2025061301,
+  "zipUrl": "https://example.com/module.zip",
+  "rolloutPercent": 10
+}"#).unwrap();
+
+        let mut config = UpdateConfig::default();
+        config.channels.insert("beta".to_string(), ChannelConfig {
+            min_ksu: Some(11000),
+            rollout_percent: Some(50),
+            ..Default::default()
+        });
+
+        let written = write_channel_manifests(&source, temp_dir.path(), Some(&config)).unwrap();
+        assert_eq!(written.len(), 2);
+
+        // 基础清单不包含扩展字段
+        let base: serde_json::Value = serde_json::from_str(&fs::read_to_string(&written[0]).unwrap()).unwrap();
+        assert!(base.get("rolloutPercent").is_none());
+        assert!(verify_manifest(&base).is_ok());
+
+        let beta: serde_json::Value = serde_json::from_str(&fs::read_to_string(&written[1]).unwrap()).unwrap();
+        assert_eq!(beta["minKsu"], 11000);
+        assert_eq!(beta["rolloutPercent"], 50);
+        assert!(beta.get("minMagisk").is_none());
+    }
+
+    #[test]
+    fn test_update_json_verifier_rejects_invalid() {
+        let missing = serde_json::json!({ "version": "v1", "versionCode": 1, "zipUrl": "https://x" });
+        assert!(verify_manifest(&missing).is_err());
+
+        let bad_rollout = serde_json::json!({
+            "version": "v1", "versionCode": 1, "zipUrl": "https://x", "changelog": "https://y",
+            "rolloutPercent": 150
+        });
+        assert!(verify_manifest(&bad_rollout).is_err());
+
+        let string_code = serde_json::json!({
+            "version": "v1", "versionCode": "1", "zipUrl": "https://x", "changelog": "https://y"
+        });
+        assert!(verify_manifest(&string_code).is_err());
+    }
+
+    #[test]
+    fn test_update_json_changelog_excerpt() {
+        assert_eq!(sibling_url("https://github.com/a/b/releases/latest/download/demo-2.zip?x=1", "changelog-2.md").as_deref(),
+            Some("https://github.com/a/b/releases/latest/download/changelog-2.md"));
+        assert!(sibling_url("demo.zip", "changelog-2.md").is_none());
+        assert!(sibling_url("https://", "changelog-2.md").is_none());
+
+        let temp = tempdir().unwrap();
+        let project = temp.path();
+        let dist = project.join(".rmmp/dist");
+        fs::create_dir_all(&dist).unwrap();
+        fs::write(project.join("CHANGELOG.md"), "# 更新日志\n\n## v1.2\n+ 新功能\n\n## v1.1\n+ 旧版本\n").unwrap();
+        fs::write(project.join("update.json"), r#"{
+  "changelog": "https://github.com/a/b/raw/main/CHANGELOG.md",
+  "version": "v1.2",
+  "versionCode": 120,
+  "zipUrl": "https://github.com/a/b/releases/latest/download/demo-120.zip"
+}"#).unwrap();
+
+        let written = write_channel_manifests(&project.join("update.json"), &dist, None).unwrap();
+        assert_eq!(written.last().unwrap(), &dist.join("changelog-120.md"));
+        assert_eq!(fs::read_to_string(dist.join("changelog-120.md")).unwrap(), "## v1.2\n\n+ 新功能\n");
+        let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(&written[0]).unwrap()).unwrap();
+        assert_eq!(manifest["changelog"], "https://github.com/a/b/releases/latest/download/changelog-120.md");
+
+        let config = UpdateConfig { changelog: ChangelogMode::Full, ..Default::default() };
+        fs::remove_file(dist.join("changelog-120.md")).unwrap();
+        let written = write_channel_manifests(&project.join("update.json"), &dist, Some(&config)).unwrap();
+        assert_eq!(written.len(), 1);
+        let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(&written[0]).unwrap()).unwrap();
+        assert_eq!(manifest["changelog"], "https://github.com/a/b/raw/main/CHANGELOG.md");
+        assert!(!dist.join("changelog-120.md").exists());
+    }
+
+    #[test]
+    fn test_update_json_copies_follow_project_root() {
+        let temp_dir = tempdir().unwrap();
+        let project = temp_dir.path();
+        let manifest = |version: &str, code: i64| format!(
+            r#"{{"version":"{}","versionCode":{},"zipUrl":"https://x/m.zip","changelog":"https://x/CHANGELOG.md"}}"#, version, code);
+        fs::write(project.join("update.json"), manifest("v1.1", 11)).unwrap();
+        for dir in [".rmmp/dist", ".rmmp/build", ".rmmp/source-build"] {
+            fs::create_dir_all(project.join(dir)).unwrap();
+            fs::write(project.join(dir).join("update.json"), manifest("v1.1", 11)).unwrap();
+        }
+        assert!(divergent_update_json(project).is_empty());
+
+        // 清理构建后留下的旧副本与已删除通道的清单
+        fs::write(project.join(".rmmp/source-build/update.json"), manifest("v1.0", 10)).unwrap();
+        fs::write(project.join(".rmmp/dist/update-beta.json"), manifest("v1.1", 11)).unwrap();
+        let divergent = divergent_update_json(project);
+        assert_eq!(divergent.len(), 1);
+        assert!(divergent[0].0.ends_with(".rmmp/source-build/update.json"));
+        assert!(divergent[0].1.contains("version = v1.0") && divergent[0].1.contains("versionCode = 10"));
+
+        let mut config = UpdateConfig::default();
+        config.channels.insert("stable".to_string(), Default::default());
+        let removed = remove_stale_manifests(project, Some(&config)).unwrap();
+        assert_eq!(removed, vec![project.join(".rmmp/dist/update-beta.json")]);
+
+        // 根目录的 update.json 被删除后，所有副本都失去来源
+        fs::remove_file(project.join("update.json")).unwrap();
+        assert_eq!(divergent_update_json(project).len(), 3);
+        assert_eq!(remove_stale_manifests(project, Some(&config)).unwrap(), vec![project.join(".rmmp/dist/update.json")]);
+    }
+}
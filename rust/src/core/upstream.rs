@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const API_TIMEOUT: Duration = Duration::from_secs(30);
+const GITHUB_API: &str = "https://api.github.com";
+
+/// 上游索引（母仓库发布的 index.json）中与所有权相关的部分
+///
+/// ```json
+/// {
+///   "namespaces": { "acme": ["acme-org"] },
+///   "modules": { "acme_tweaks": { "owner": "acme-org", "repo": "acme-org/tweaks" } },
+///   "reserve_url": "https://example.com/api/reserve"
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpstreamIndex {
+    /// 命名空间（id 中第一个 _ 之前的部分）→ 允许使用它的用户或组织
+    #[serde(default)]
+    pub namespaces: BTreeMap<String, Vec<String>>,
+    /// 已发布或已预留的模块
+    #[serde(default)]
+    pub modules: BTreeMap<String, IndexedModule>,
+    /// 预留新 id 的接口
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserve_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexedModule {
+    pub owner: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+/// 令牌对应的 GitHub 身份
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UpstreamIdentity {
+    pub login: String,
+    pub orgs: Vec<String>,
+}
+
+impl UpstreamIdentity {
+    /// 用户本人或其所在组织
+    fn owns(&self, owner: &str) -> bool {
+        self.login.eq_ignore_ascii_case(owner) || self.orgs.iter().any(|org| org.eq_ignore_ascii_case(owner))
+    }
+
+    fn describe(&self) -> String {
+        if self.orgs.is_empty() {
+            self.login.clone()
+        } else {
+            format!("{}（组织: {}）", self.login, self.orgs.join(", "))
+        }
+    }
+}
+
+/// 发布者信息：随预留请求提交，上游据此把 id 绑定到发布它的仓库与工作流
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PublisherMetadata {
+    pub owner: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// GitHub Actions 中的 GITHUB_WORKFLOW_REF
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+}
+
+impl PublisherMetadata {
+    /// 从 GitHub Actions 环境变量收集（本地运行时只有 owner）
+    pub fn from_env(owner: &str) -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Self {
+            owner: owner.to_string(),
+            repository: var("GITHUB_REPOSITORY"),
+            workflow: var("GITHUB_WORKFLOW_REF"),
+            commit: var("GITHUB_SHA"),
+            run_id: var("GITHUB_RUN_ID"),
+        }
+    }
+}
+
+/// 所有权检查结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "owner", rename_all = "lowercase")]
+pub enum OwnershipStatus {
+    /// 索引中已登记且属于当前身份
+    Owned(String),
+    /// 尚未登记，需要预留
+    Unclaimed,
+    /// 本次已预留
+    Reserved(String),
+}
+
+/// id 的命名空间：第一个 _ 之前的部分
+pub fn namespace_of(id: &str) -> &str {
+    id.split('_').next().unwrap_or(id)
+}
+
+/// 按上游索引规则检查 id 是否可以由该身份发布
+pub fn check_ownership(index: &UpstreamIndex, id: &str, identity: &UpstreamIdentity) -> Result<OwnershipStatus> {
+    if let Some(module) = index.modules.get(id) {
+        if identity.owns(&module.owner) {
+            return Ok(OwnershipStatus::Owned(module.owner.clone()));
+        }
+        anyhow::bail!(
+            "模块 id {} 在上游索引中属于 {}，当前 UPSTREAM_ACCESS_TOKEN 属于 {}\n请改用其他 id，或请 {} 将你加入其组织后再发布",
+            id, module.owner, identity.describe(), module.owner
+        );
+    }
+    let namespace = namespace_of(id);
+    if let Some(owners) = index.namespaces.get(namespace)
+        && !owners.iter().any(|owner| identity.owns(owner))
+    {
+        anyhow::bail!(
+            "命名空间 {}_ 在上游索引中保留给 {}，当前 UPSTREAM_ACCESS_TOKEN 属于 {}\n请改用以自己用户名或组织为前缀的 id，如 {}_{}",
+            namespace, owners.join(", "), identity.describe(),
+            identity.login.to_lowercase(), id.strip_prefix(&format!("{}_", namespace)).unwrap_or(id)
+        );
+    }
+    Ok(OwnershipStatus::Unclaimed)
+}
+
+fn runtime() -> Result<tokio::runtime::Runtime> {
+    Ok(tokio::runtime::Builder::new_current_thread().enable_all().build()?)
+}
+
+fn client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(API_TIMEOUT)
+        .user_agent(concat!("rmm/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+/// 下载并解析上游索引
+pub fn fetch_index(url: &str) -> Result<UpstreamIndex> {
+    runtime()?.block_on(async {
+        let response = client()?.get(url).send().await?.error_for_status()?;
+        Ok::<_, anyhow::Error>(response.json::<UpstreamIndex>().await?)
+    }).with_context(|| format!("无法读取上游索引: {}", url))
+}
+
+/// 查询令牌对应的用户及其组织
+pub fn fetch_identity(token: &str) -> Result<UpstreamIdentity> {
+    #[derive(Deserialize)]
+    struct Account {
+        login: String,
+    }
+    runtime()?.block_on(async {
+        let client = client()?;
+        let get = |path: &str| client.get(format!("{}{}", GITHUB_API, path)).bearer_auth(token).send();
+        let response = get("/user").await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            anyhow::bail!("UPSTREAM_ACCESS_TOKEN 无效或已过期，请重新生成并更新仓库 secrets");
+        }
+        let user: Account = response.error_for_status()?.json().await?;
+        // 令牌没有 read:org 权限时组织列表为空，只按用户本人判断
+        let orgs: Vec<Account> = match get("/user/orgs").await.and_then(|r| r.error_for_status()) {
+            Ok(response) => response.json().await.unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        Ok(UpstreamIdentity { login: user.login, orgs: orgs.into_iter().map(|o| o.login).collect() })
+    }).context("无法通过 UPSTREAM_ACCESS_TOKEN 查询 GitHub 身份")
+}
+
+/// 在上游预留新的 id
+pub fn reserve_id(url: &str, token: &str, id: &str, publisher: &PublisherMetadata) -> Result<()> {
+    let body = serde_json::json!({ "id": id, "publisher": publisher });
+    runtime()?.block_on(async {
+        let response = client()?.post(url).bearer_auth(token).json(&body).send().await?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            reqwest::StatusCode::CONFLICT => anyhow::bail!("模块 id {} 刚被其他发布者预留，请换一个 id", id),
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                anyhow::bail!("上游拒绝了预留请求（{}），请确认 UPSTREAM_ACCESS_TOKEN 有发布权限", response.status())
+            }
+            status => anyhow::bail!("预留 id 失败（{}）: {}", status, response.text().await.unwrap_or_default()),
+        }
+    }).with_context(|| format!("预留上游模块 id 失败: {}", url))
+}
+
+/// 完整的发布前检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamVerification {
+    pub id: String,
+    pub identity: UpstreamIdentity,
+    pub status: OwnershipStatus,
+    pub publisher: PublisherMetadata,
+}
+
+/// 发布到上游前检查 id 所有权；reserve 时为未登记的 id 预留
+pub fn verify_upstream(index_url: &str, token: &str, id: &str, reserve: bool) -> Result<UpstreamVerification> {
+    let index = fetch_index(index_url)?;
+    let identity = fetch_identity(token)?;
+    let mut status = check_ownership(&index, id, &identity)?;
+    let publisher = PublisherMetadata::from_env(&identity.login);
+    if status == OwnershipStatus::Unclaimed && reserve {
+        let url = index.reserve_url.as_deref()
+            .ok_or_else(|| anyhow::anyhow!("上游索引没有提供 reserve_url，无法预留 id {}", id))?;
+        reserve_id(url, token, id, &publisher)?;
+        status = OwnershipStatus::Reserved(identity.login.clone());
+    }
+    Ok(UpstreamVerification { id: id.to_string(), identity, status, publisher })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upstream_namespace_ownership() {
+        let index: UpstreamIndex = serde_json::from_str(r#"{
+            "namespaces": { "acme": ["acme-org"] },
+            "modules": { "tweaks": { "owner": "alice" }, "acme_boost": { "owner": "acme-org" } }
+        }"#).unwrap();
+        let alice = UpstreamIdentity { login: "Alice".to_string(), orgs: Vec::new() };
+        let member = UpstreamIdentity { login: "bob".to_string(), orgs: vec!["ACME-org".to_string()] };
+
+        assert_eq!(namespace_of("acme_boost"), "acme");
+        assert_eq!(namespace_of("tweaks"), "tweaks");
+        assert_eq!(check_ownership(&index, "tweaks", &alice).unwrap(), OwnershipStatus::Owned("alice".to_string()));
+        assert_eq!(check_ownership(&index, "acme_boost", &member).unwrap(), OwnershipStatus::Owned("acme-org".to_string()));
+        assert_eq!(check_ownership(&index, "acme_new", &member).unwrap(), OwnershipStatus::Unclaimed);
+        assert_eq!(check_ownership(&index, "fresh_id", &alice).unwrap(), OwnershipStatus::Unclaimed);
+
+        let err = check_ownership(&index, "tweaks", &member).unwrap_err().to_string();
+        assert!(err.contains("属于 alice") && err.contains("bob（组织: ACME-org）"), "{}", err);
+        let err = check_ownership(&index, "acme_new", &alice).unwrap_err().to_string();
+        assert!(err.contains("保留给 acme-org") && err.contains("alice_new"), "{}", err);
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use super::process::{CommandExt, CommandKind};
+use super::rmm_core::{GitAnalyzer, GitInfo};
+use super::settings::ConfigResolver;
+use super::storage::canonicalize_or_absolute;
+
+/// 版本控制后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VcsKind {
+    /// 按最近一级的 .git / .hg 检测，都没有时使用 none
+    #[default]
+    Auto,
+    Git,
+    /// Mercurial，需要 PATH 中有 hg
+    Hg,
+    /// 普通目录：提交、分支与远程地址取自 [vcs] 配置
+    None,
+}
+
+/// 可选的版本控制后端
+pub const VCS_KINDS: [&str; 4] = ["auto", "git", "hg", "none"];
+
+impl FromStr for VcsKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(VcsKind::Auto),
+            "git" => Ok(VcsKind::Git),
+            "hg" => Ok(VcsKind::Hg),
+            "none" => Ok(VcsKind::None),
+            _ => anyhow::bail!("未知的版本控制后端: '{}'，可选: {}", s, VCS_KINDS.join(", ")),
+        }
+    }
+}
+
+/// Rmake.toml 中的 [vcs]：不使用 Git 的项目在这里给出版本号、分支与仓库地址
+///
+/// ```toml
+/// [vcs]
+/// backend = "none"
+/// revision = "r1024"
+/// branch = "main"
+/// remote = "https://github.com/acme/tweaks"
+/// ```
+///
+/// 也可通过 RMM_VCS、RMM_VCS_REVISION、RMM_VCS_BRANCH、RMM_VCS_REMOTE 设置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct VcsConfig {
+    #[serde(default)]
+    pub backend: VcsKind,
+    /// 当前版本标识，代替提交哈希用于版本号与构建变量
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// 仓库地址，用于生成 update.json 与发布链接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+}
+
+impl VcsConfig {
+    /// 按分层配置（环境变量 → Rmake.toml [vcs]）解析
+    pub fn resolve(project_path: &Path) -> Result<Self> {
+        let config = ConfigResolver::new(Some(project_path));
+        Ok(Self {
+            backend: config.get("vcs.backend").as_deref().unwrap_or("auto").parse()?,
+            revision: config.get("vcs.revision"),
+            branch: config.get("vcs.branch"),
+            remote: config.get("vcs.remote"),
+        })
+    }
+
+    /// 实际使用的后端；auto 时按路径向上查找最近的 .git 或 .hg，都没有时为 none
+    pub fn kind_for(&self, path: &Path) -> VcsKind {
+        match self.backend {
+            VcsKind::Auto => detect_kind(path),
+            kind => kind,
+        }
+    }
+
+    pub fn backend_for(&self, path: &Path) -> Box<dyn VcsBackend> {
+        match self.kind_for(path) {
+            VcsKind::Git => Box::new(GitBackend),
+            VcsKind::Hg => Box::new(HgBackend),
+            VcsKind::Auto | VcsKind::None => Box::new(NoVcs(self.clone())),
+        }
+    }
+}
+
+/// 最近一级目录中的 .git（目录或 gitdir 文件）或 .hg 决定后端
+fn detect_kind(path: &Path) -> VcsKind {
+    let path = canonicalize_or_absolute(path);
+    path.ancestors()
+        .find_map(|dir| {
+            if dir.join(".git").exists() {
+                Some(VcsKind::Git)
+            } else if dir.join(".hg").is_dir() {
+                Some(VcsKind::Hg)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(VcsKind::None)
+}
+
+/// 版本控制查询；结果沿用 GitInfo 结构，vcs 字段标明来源
+pub trait VcsBackend {
+    /// 路径所在仓库的信息，不在仓库中时返回 None
+    fn info(&self, path: &Path) -> Result<Option<GitInfo>>;
+    /// 提交者的名字与邮箱
+    fn user(&self, path: &Path) -> Option<(String, String)>;
+}
+
+pub struct GitBackend;
+
+impl VcsBackend for GitBackend {
+    fn info(&self, path: &Path) -> Result<Option<GitInfo>> {
+        GitAnalyzer::analyze_git_info(path)
+    }
+
+    fn user(&self, path: &Path) -> Option<(String, String)> {
+        let config = match git2::Repository::discover(path) {
+            Ok(repo) => repo.config().ok()?,
+            Err(_) => git2::Config::open_default().ok()?,
+        };
+        Some((config.get_string("user.name").unwrap_or_default(), config.get_string("user.email").unwrap_or_default()))
+    }
+}
+
+pub struct HgBackend;
+
+impl HgBackend {
+    fn hg(path: &Path, args: &[&str]) -> Result<String> {
+        let output = Command::new("hg")
+            .args(args)
+            .current_dir(path)
+            .env("HGPLAIN", "1")
+            .output_with_timeout(CommandKind::Git)
+            .context("无法运行 hg，请确认已安装 Mercurial")?;
+        if !output.status.success() {
+            anyhow::bail!("hg {} 失败: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+    }
+}
+
+impl VcsBackend for HgBackend {
+    fn info(&self, path: &Path) -> Result<Option<GitInfo>> {
+        let path = canonicalize_or_absolute(path);
+        let Ok(root) = Self::hg(&path, &["root"]) else {
+            return Ok(None);
+        };
+        let repo_root = canonicalize_or_absolute(Path::new(&root));
+        let relative_path = path.strip_prefix(&repo_root).unwrap_or(Path::new("")).to_path_buf();
+        let log = Self::hg(&path, &["log", "-r", ".", "--template", "{node}\\n{desc}"])?;
+        let (node, message) = log.split_once('\n').unwrap_or((&log, ""));
+        // 尚无提交时工作目录的父提交是空节点
+        let last_commit_hash = Some(node.to_string()).filter(|node| !node.is_empty() && node.chars().any(|c| c != '0'));
+        Ok(Some(GitInfo {
+            repo_root,
+            relative_path,
+            branch: Self::hg(&path, &["branch"])?,
+            remote_url: Self::hg(&path, &["paths", "default"]).ok().filter(|url| !url.is_empty()),
+            has_uncommitted_changes: !Self::hg(&path, &["status"])?.is_empty(),
+            last_commit_message: last_commit_hash.as_ref().map(|_| message.to_string()),
+            last_commit_hash,
+            vcs: "hg",
+        }))
+    }
+
+    fn user(&self, path: &Path) -> Option<(String, String)> {
+        let username = Self::hg(path, &["config", "ui.username"]).ok()?;
+        Some(parse_hg_username(&username))
+    }
+}
+
+/// Mercurial 的 ui.username："Name <email>"
+fn parse_hg_username(username: &str) -> (String, String) {
+    match username.split_once('<') {
+        Some((name, email)) => (name.trim().to_string(), email.trim_end_matches('>').trim().to_string()),
+        None => (username.trim().to_string(), String::new()),
+    }
+}
+
+/// 普通目录：没有可查询的仓库，只返回 [vcs] 中显式配置的值；作者信息由 author.name / author.email 提供
+pub struct NoVcs(pub VcsConfig);
+
+impl VcsBackend for NoVcs {
+    fn info(&self, path: &Path) -> Result<Option<GitInfo>> {
+        let config = &self.0;
+        if config.revision.is_none() && config.branch.is_none() && config.remote.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(GitInfo {
+            repo_root: canonicalize_or_absolute(path),
+            relative_path: PathBuf::new(),
+            branch: config.branch.clone().unwrap_or_default(),
+            remote_url: config.remote.clone(),
+            has_uncommitted_changes: false,
+            last_commit_hash: config.revision.clone(),
+            last_commit_message: None,
+            vcs: "none",
+        }))
+    }
+
+    fn user(&self, _path: &Path) -> Option<(String, String)> {
+        None
+    }
+}
+
+/// 按项目的 [vcs] 配置选择后端并查询仓库信息
+pub fn analyze_vcs_info(path: &Path) -> Result<Option<GitInfo>> {
+    VcsConfig::resolve(path)?.backend_for(path).info(path)
+}
+
+/// 按项目的 [vcs] 配置查询提交者信息
+pub fn vcs_user(path: &Path) -> Option<(String, String)> {
+    VcsConfig::resolve(path).ok()?.backend_for(path).user(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_vcs_backends_without_git() {
+        use crate::core::generate::generation_vars;
+        use crate::core::rmm_core::RmakeConfig;
+
+        let temp_dir = tempdir().unwrap();
+        let plain = temp_dir.path().join("plain");
+        fs::create_dir_all(plain.join(".rmmp")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("hg/module")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("hg/.hg")).unwrap();
+
+        // auto：按最近的 .hg / .git 选择后端，普通目录没有配置时不返回信息
+        assert_eq!(VcsConfig::default().kind_for(&temp_dir.path().join("hg/module")), VcsKind::Hg);
+        assert_eq!(VcsConfig::default().kind_for(&plain), VcsKind::None);
+        assert!(VcsConfig::default().backend_for(&plain).info(&plain).unwrap().is_none());
+        assert_eq!("svn".parse::<VcsKind>().unwrap_err().to_string(), "未知的版本控制后端: 'svn'，可选: auto, git, hg, none");
+
+        // Rmake.toml [vcs] 提供版本标识、分支与仓库地址
+        fs::write(plain.join(".rmmp/Rmake.toml"), r#"
+[build]
+include = []
+exclude = []
+prebuild = []
+build = []
+postbuild = []
+
+[vcs]
+backend = "none"
+revision = "r1024"
+remote = "https://github.com/acme/tweaks"
+"#).unwrap();
+        fs::write(plain.join("module.prop"), "id=tweaks\nversion=v1.0\nversionCode=1\n").unwrap();
+        let rmake: RmakeConfig = toml::from_str(&fs::read_to_string(plain.join(".rmmp/Rmake.toml")).unwrap()).unwrap();
+        let vcs = rmake.vcs.unwrap();
+        assert_eq!(vcs.backend, VcsKind::None);
+        let info = vcs.backend_for(&plain).info(&plain).unwrap().unwrap();
+        assert_eq!(info.vcs, "none");
+        assert_eq!(info.remote_url.as_deref(), Some("https://github.com/acme/tweaks"));
+        assert_eq!(info.last_commit_hash.as_deref(), Some("r1024"));
+        assert_eq!(VcsConfig::resolve(&plain).unwrap(), vcs);
+        assert_eq!(generation_vars(&plain).unwrap()["git_hash"], "r1024");
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use super::adb::shell_quote;
+use super::rmm_core::RmmProject;
+
+/// 安装后验证脚本名（随调试构建打包到模块根目录）
+pub const VERIFY_SCRIPT: &str = "verify.sh";
+
+/// rmmproject.toml 中的 [verify] 配置：安装并重启后设备上应满足的条件
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct VerifyConfig {
+    /// 应存在的文件，如 /system/bin/foo（通过 overlay 挂载）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// 应设置的属性及其值
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub props: BTreeMap<String, String>,
+    /// 应在运行的进程名
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<String>,
+}
+
+impl VerifyConfig {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.props.is_empty() && self.services.is_empty()
+    }
+}
+
+/// 单条断言的结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VerifyResult {
+    /// file / prop / service
+    pub kind: String,
+    pub target: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// 读取 rmmproject.toml 中的 [verify] 配置
+pub fn load_verify_config(project_path: &Path) -> Result<Option<VerifyConfig>> {
+    let path = project_path.join("rmmproject.toml");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let project: RmmProject = toml::from_str(&fs::read_to_string(path)?)?;
+    Ok(project.verify.filter(|v| !v.is_empty()))
+}
+
+/// 生成 verify.sh：每条断言输出一行 `PASS|FAIL<TAB>类型<TAB>目标<TAB>详情`
+pub fn generate_verify_script(config: &VerifyConfig) -> String {
+    let mut script = String::from(r#"#!/system/bin/sh
+# 由 rmm 根据 rmmproject.toml [verify] 生成，请勿手动修改
+# 输出格式: PASS|FAIL<TAB>类型<TAB>目标<TAB>详情
+
+FAILED=0
+pass() { printf 'PASS\t%s\t%s\t%s\n' "$1" "$2" "$3"; }
+fail() { printf 'FAIL\t%s\t%s\t%s\n' "$1" "$2" "$3"; FAILED=$((FAILED + 1)); }
+
+check_file() {
+    if [ -e "$1" ]; then pass file "$1" ""; else fail file "$1" "not found"; fi
+}
+
+check_prop() {
+    actual="$(getprop "$1")"
+    if [ "$actual" = "$2" ]; then pass prop "$1" "$actual"; else fail prop "$1" "expected '$2', got '$actual'"; fi
+}
+
+check_service() {
+    if pidof "$1" >/dev/null 2>&1 || pgrep -f "$1" >/dev/null 2>&1; then
+        pass service "$1" ""
+    else
+        fail service "$1" "not running"
+    fi
+}
+
+"#);
+    for file in &config.files {
+        script.push_str(&format!("check_file {}\n", shell_quote(file)));
+    }
+    for (prop, value) in &config.props {
+        script.push_str(&format!("check_prop {} {}\n", shell_quote(prop), shell_quote(value)));
+    }
+    for service in &config.services {
+        script.push_str(&format!("check_service {}\n", shell_quote(service)));
+    }
+    script.push_str("\n[ \"$FAILED\" -eq 0 ]\n");
+    script
+}
+
+/// 解析 verify.sh 的输出，忽略其他行
+pub fn parse_verify_output(output: &str) -> Vec<VerifyResult> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.trim_end_matches('\r').splitn(4, '\t');
+            let passed = match parts.next()? {
+                "PASS" => true,
+                "FAIL" => false,
+                _ => return None,
+            };
+            Some(VerifyResult {
+                kind: parts.next()?.to_string(),
+                target: parts.next()?.to_string(),
+                passed,
+                detail: parts.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_verify_script_generation_and_parsing() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("rmmproject.toml"), r#"
+[project]
+id = "demo"
+description = "demo"
+readme = "README.MD"
+changelog = "CHANGELOG.MD"
+license = "LICENSE"
+dependencies = []
+
+[[authors]]
+name = "a"
+email = "a@b.c"
+
+[urls]
+github = "https://github.com/a/demo"
+
+[build-system]
+requires = []
+build-backend = "rmm"
+
+[verify]
+files = ["/system/bin/demo"]
+services = ["demod"]
+
+[verify.props]
+"ro.demo.enabled" = "1"
+"#).unwrap();
+
+        let config = load_verify_config(temp.path()).unwrap().expect("verify config");
+        assert_eq!(config.files, vec!["/system/bin/demo".to_string()]);
+        let script = generate_verify_script(&config);
+        assert!(script.starts_with("#!/system/bin/sh"));
+        assert!(script.contains("check_file '/system/bin/demo'"));
+        assert!(script.contains("check_prop 'ro.demo.enabled' '1'"));
+        assert!(script.contains("check_service 'demod'"));
+
+        let results = parse_verify_output("noise\nPASS\tfile\t/system/bin/demo\t\nFAIL\tprop\tro.demo.enabled\texpected '1', got ''\r\n");
+        assert_eq!(results.len(), 2);
+        assert!(results[0].passed);
+        assert!(!results[1].passed);
+        assert_eq!(results[1].detail, "expected '1', got ''");
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use super::generate::{generation_vars, render_template};
+
+/// 未设置 message 时的通知内容
+pub const DEFAULT_WEBHOOK_MESSAGE: &str = "{{name}} {{version}} 已发布\n{{url}}";
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+/// 默认重试次数
+const DEFAULT_RETRIES: u32 = 3;
+/// 首次重试前的等待时间，之后每次翻倍
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+/// 服务端要求的 Retry-After 超过该值时按该值等待
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// 通知目标的请求格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    /// {"event": "publish", "message": ..., 以及全部发布变量}
+    #[default]
+    Generic,
+    /// Discord 频道 webhook：{"content": ...}
+    Discord,
+    /// Telegram Bot API sendMessage：{"chat_id": ..., "text": ...}
+    Telegram,
+}
+
+/// Rmake.toml 中的 [[publish.webhooks]]：发布完成后通知聊天频道
+///
+/// ```toml
+/// [[publish.webhooks]]
+/// kind = "telegram"
+/// url = "https://api.telegram.org/bot{{env.TELEGRAM_BOT_TOKEN}}/sendMessage"
+/// chat_id = "@my_channel"
+/// message = "{{name}} {{version}} 已发布：{{url}}"
+/// channels = ["stable"]
+/// ```
+///
+/// url、message、body 中可使用发布变量（{{version}}、{{tag}}、{{url}} 等），密钥通过 {{env.NAME}} 从环境变量读取
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+pub struct WebhookConfig {
+    /// 输出中显示的名称（URL 可能包含密钥，不会被打印）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub url: String,
+    #[serde(default)]
+    pub kind: WebhookKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Telegram 的频道或群组 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+    /// 完整的 JSON 请求体模板，设置后忽略 kind 的默认格式；变量值按 JSON 字符串转义
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// 只在发布到这些通道时通知，为空表示所有通道
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<String>,
+    /// 失败（网络错误、429 或 5xx）后的重试次数，默认 3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+}
+
+impl WebhookConfig {
+    pub fn display_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| format!("{:?}", self.kind).to_lowercase())
+    }
+
+    /// 该通道的发布是否需要通知；未指定通道的发布视为 default
+    pub fn applies_to(&self, channel: Option<&str>) -> bool {
+        self.channels.is_empty() || self.channels.iter().any(|c| c == channel.unwrap_or("default"))
+    }
+}
+
+/// 发布变量：module.prop 字段、Git 信息，以及本次发布的 tag、repo、url、channel
+pub fn release_vars(project_path: &Path, tag: &str, url: &str, repo: &str, channel: Option<&str>) -> Result<BTreeMap<String, String>> {
+    let mut vars = generation_vars(project_path)?;
+    vars.insert("tag".to_string(), tag.to_string());
+    vars.insert("url".to_string(), url.to_string());
+    vars.insert("repo".to_string(), repo.to_string());
+    vars.insert("channel".to_string(), channel.unwrap_or("default").to_string());
+    Ok(vars)
+}
+
+/// 渲染请求的 URL 与 JSON 请求体
+pub fn render_webhook(hook: &WebhookConfig, vars: &BTreeMap<String, String>) -> Result<(String, serde_json::Value)> {
+    let url = render_template(&hook.url, vars)?;
+    if let Some(body) = &hook.body {
+        let escaped = vars.iter()
+            .map(|(key, value)| {
+                let quoted = serde_json::to_string(value).unwrap_or_default();
+                (key.clone(), quoted[1..quoted.len() - 1].to_string())
+            })
+            .collect();
+        let rendered = render_template(body, &escaped)?;
+        let body = serde_json::from_str(&rendered)
+            .map_err(|e| anyhow::anyhow!("body 渲染后不是有效的 JSON: {}", e))?;
+        return Ok((url, body));
+    }
+    let message = render_template(hook.message.as_deref().unwrap_or(DEFAULT_WEBHOOK_MESSAGE), vars)?;
+    let body = match hook.kind {
+        WebhookKind::Discord => serde_json::json!({ "content": message }),
+        WebhookKind::Telegram => {
+            let chat_id = hook.chat_id.as_deref()
+                .ok_or_else(|| anyhow::anyhow!("kind = \"telegram\" 需要设置 chat_id"))?;
+            serde_json::json!({ "chat_id": render_template(chat_id, vars)?, "text": message })
+        }
+        WebhookKind::Generic => {
+            let mut body = serde_json::json!({ "event": "publish", "message": message });
+            for (key, value) in vars {
+                body[key] = serde_json::Value::String(value.clone());
+            }
+            body
+        }
+    };
+    Ok((url, body))
+}
+
+/// 一次通知的结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WebhookOutcome {
+    pub name: String,
+    pub ok: bool,
+    /// 实际发送的次数
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 发送一次通知；网络错误、429 与 5xx 按指数退避重试（429 优先使用 Retry-After）
+async fn deliver(client: &reqwest::Client, url: &str, body: &serde_json::Value, retries: u32) -> (u32, Result<()>) {
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let (retryable, error, retry_after) = match client.post(url).json(body).send().await {
+            Ok(response) if response.status().is_success() => return (attempts, Ok(())),
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = response.headers().get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse::<u64>().ok())
+                    .map(|secs| Duration::from_secs(secs).min(MAX_RETRY_AFTER));
+                let text = response.text().await.unwrap_or_default();
+                let text: String = text.chars().take(200).collect();
+                let retryable = status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                (retryable, anyhow::anyhow!("HTTP {}: {}", status, text.trim()), retry_after)
+            }
+            // 错误信息中的 URL 可能包含 token，不输出
+            Err(e) => (true, anyhow::anyhow!("请求失败: {}", e.without_url()), None),
+        };
+        if !retryable || attempts > retries {
+            return (attempts, Err(error));
+        }
+        tokio::time::sleep(retry_after.unwrap_or(RETRY_DELAY * 2u32.pow(attempts - 1))).await;
+    }
+}
+
+/// 发送适用于该通道的全部通知；单个通知失败不影响其他通知
+pub fn send_webhooks(hooks: &[WebhookConfig], vars: &BTreeMap<String, String>, channel: Option<&str>) -> Result<Vec<WebhookOutcome>> {
+    let hooks: Vec<&WebhookConfig> = hooks.iter().filter(|hook| hook.applies_to(channel)).collect();
+    if hooks.is_empty() {
+        return Ok(Vec::new());
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .user_agent(concat!("rmm/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    Ok(hooks.iter()
+        .map(|hook| {
+            let name = hook.display_name();
+            let (attempts, result) = match render_webhook(hook, vars) {
+                Ok((url, body)) => runtime.block_on(deliver(&client, &url, &body, hook.retries.unwrap_or(DEFAULT_RETRIES))),
+                Err(e) => (0, Err(e)),
+            };
+            WebhookOutcome { name, ok: result.is_ok(), attempts, error: result.err().map(|e| e.to_string()) }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_webhooks() {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        let vars: BTreeMap<String, String> = [("name", "Demo \"Mod\""), ("version", "v1.0"), ("url", "https://example.com/r"), ("tag", "v1.0")]
+            .into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let telegram = WebhookConfig {
+            kind: WebhookKind::Telegram,
+            url: "https://api.telegram.org/bot{{env.RMM_TEST_WEBHOOK_TOKEN}}/sendMessage".to_string(),
+            chat_id: Some("@demo".to_string()),
+            ..Default::default()
+        };
+        // 密钥未设置时不发送
+        assert!(render_webhook(&telegram, &vars).unwrap_err().to_string().contains("env.RMM_TEST_WEBHOOK_TOKEN"));
+        unsafe { std::env::set_var("RMM_TEST_WEBHOOK_TOKEN", "123:abc") };
+        let (url, body) = render_webhook(&telegram, &vars).unwrap();
+        assert_eq!(url, "https://api.telegram.org/bot123:abc/sendMessage");
+        assert_eq!(body, serde_json::json!({ "chat_id": "@demo", "text": "Demo \"Mod\" v1.0 已发布\nhttps://example.com/r" }));
+
+        let custom = WebhookConfig { url: "x".to_string(), body: Some(r#"{"text": "{{name}} {{tag}}"}"#.to_string()), ..Default::default() };
+        assert_eq!(render_webhook(&custom, &vars).unwrap().1, serde_json::json!({ "text": "Demo \"Mod\" v1.0" }));
+
+        // 第一次返回 429，按 Retry-After 重试后成功；stable 通道的 webhook 不参与 beta 发布
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for (index, stream) in listener.incoming().take(2).enumerate() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                bodies.push(String::from_utf8(body).unwrap());
+                let response = if index == 0 {
+                    "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                } else {
+                    "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n"
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            bodies
+        });
+        let hooks = vec![
+            WebhookConfig { name: Some("discord".to_string()), kind: WebhookKind::Discord, url: format!("http://127.0.0.1:{}/hook", port), ..Default::default() },
+            WebhookConfig { url: "http://127.0.0.1:1/never".to_string(), channels: vec!["stable".to_string()], ..Default::default() },
+        ];
+        let outcomes = send_webhooks(&hooks, &vars, Some("beta")).unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].ok, "{:?}", outcomes[0].error);
+        assert_eq!(outcomes[0].attempts, 2);
+        let bodies = server.join().unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&bodies[1]).unwrap()["content"], "Demo \"Mod\" v1.0 已发布\nhttps://example.com/r");
+    }
+}
@@ -0,0 +1,322 @@
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::deps::{module_identity, parse_dep_spec, read_project, DepKind, DepSource};
+
+/// 工作区配置文件（位于工作区根目录）
+pub const WORKSPACE_FILE: &str = "rmmworkspace.toml";
+
+/// rmmworkspace.toml 文件结构
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct WorkspaceFile {
+    pub workspace: WorkspaceConfig,
+}
+
+/// [workspace]：成员目录，支持末段通配，如 "modules/*"
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct WorkspaceConfig {
+    pub members: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// 多个成员提供同一系统文件时的处理方式
+    #[serde(default)]
+    pub conflicts: ConflictPolicy,
+    /// 允许多个成员同时提供的设备路径（glob，如 "/system/etc/permissions/*"）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_conflicts: Vec<String>,
+}
+
+/// 成员间文件冲突的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// 打印警告
+    #[default]
+    Warn,
+    /// 构建失败
+    Error,
+    /// 不检查
+    Ignore,
+}
+
+/// 会被挂载到设备分区的目录（Magisk 中 vendor 等分区位于 system/ 下）
+pub(crate) const PARTITIONS: &[&str] = &["system", "vendor", "product", "system_ext", "odm"];
+
+/// 多个成员提供的同一设备路径
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FileConflict {
+    /// 设备上的路径，如 /system/etc/hosts
+    pub path: String,
+    /// 提供该文件的成员 id
+    pub members: Vec<String>,
+}
+
+/// zip 条目对应的设备路径；system/vendor/x 与 vendor/x 都映射到 /vendor/x，非分区文件返回 None
+pub fn device_path(entry: &str) -> Option<String> {
+    if entry.ends_with('/') {
+        return None;
+    }
+    let (partition, rest) = entry.split_once('/')?;
+    if !PARTITIONS.contains(&partition) || rest.is_empty() {
+        return None;
+    }
+    if partition == "system"
+        && let Some((nested, nested_rest)) = rest.split_once('/')
+        && PARTITIONS[1..].contains(&nested)
+    {
+        return Some(format!("/{}/{}", nested, nested_rest));
+    }
+    Some(format!("/{}", entry))
+}
+
+/// 模块 zip 中会被挂载的全部设备路径
+pub fn mounted_paths(zip_path: &Path) -> Result<BTreeSet<String>> {
+    let file = fs::File::open(zip_path).with_context(|| format!("Failed to open {}", zip_path.display()))?;
+    let archive = zip::ZipArchive::new(file)?;
+    Ok(archive.file_names().filter_map(device_path).collect())
+}
+
+/// 工作区成员
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct WorkspaceMember {
+    /// module.prop 中的 id
+    pub id: String,
+    pub path: PathBuf,
+    /// 依赖的其他成员 id
+    pub depends_on: Vec<String>,
+}
+
+/// 已加载的工作区
+#[derive(Debug, Clone, Serialize)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub members: Vec<WorkspaceMember>,
+    #[serde(skip)]
+    pub conflicts: ConflictPolicy,
+    #[serde(skip)]
+    pub allow_conflicts: Vec<String>,
+}
+
+/// 从 start 向上查找包含 rmmworkspace.toml 的目录
+pub fn find_workspace_root(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find(|dir| dir.join(WORKSPACE_FILE).is_file()).map(Path::to_path_buf)
+}
+
+/// 展开成员模式：末段为 * 时匹配该目录下所有含 rmmproject.toml 的子目录
+fn expand_member(root: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let pattern = pattern.trim_end_matches('/');
+    match pattern.strip_suffix("/*").or_else(|| (pattern == "*").then_some("")) {
+        Some(parent) => {
+            let dir = root.join(parent);
+            let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+                .with_context(|| format!("无法读取工作区成员目录: {}", dir.display()))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.join("rmmproject.toml").is_file())
+                .collect();
+            paths.sort();
+            Ok(paths)
+        }
+        None if pattern.contains('*') => anyhow::bail!("成员模式只支持末段通配（如 modules/*）: {}", pattern),
+        None => Ok(vec![root.join(pattern)]),
+    }
+}
+
+impl Workspace {
+    /// 加载工作区并解析成员之间的依赖
+    pub fn load(root: &Path) -> Result<Self> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let file = root.join(WORKSPACE_FILE);
+        let content = fs::read_to_string(&file).with_context(|| format!("无法读取 {}", file.display()))?;
+        let config: WorkspaceFile = toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("无法解析 {}: {}", file.display(), e))?;
+
+        let excluded: BTreeSet<PathBuf> = config.workspace.exclude.iter()
+            .flat_map(|pattern| expand_member(&root, pattern).unwrap_or_default())
+            .map(|path| path.canonicalize().unwrap_or(path))
+            .collect();
+        let mut paths = Vec::new();
+        for pattern in &config.workspace.members {
+            for path in expand_member(&root, pattern)? {
+                if !path.join("rmmproject.toml").is_file() {
+                    anyhow::bail!("工作区成员 {} 不是 RMM 项目（缺少 rmmproject.toml）", path.display());
+                }
+                let path = path.canonicalize().unwrap_or(path);
+                if !excluded.contains(&path) && !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        let mut ids: BTreeMap<String, PathBuf> = BTreeMap::new();
+        for path in &paths {
+            let (id, _) = module_identity(path);
+            if let Some(other) = ids.insert(id.clone(), path.clone()) {
+                anyhow::bail!("工作区成员 id 重复: {}（{} 与 {}）", id, other.display(), path.display());
+            }
+        }
+
+        let mut members = Vec::new();
+        for path in &paths {
+            let (id, _) = module_identity(path);
+            let mut depends_on = Vec::new();
+            if let Some(project) = read_project(path)? {
+                for spec in &project.project.dependencies {
+                    let spec = parse_dep_spec(spec, DepKind::Module)?;
+                    let target = match spec.source {
+                        DepSource::Path(ref relative) => {
+                            let target = path.join(relative);
+                            let target = target.canonicalize().unwrap_or(target);
+                            ids.iter().find(|(_, p)| **p == target).map(|(id, _)| id.clone())
+                        }
+                        DepSource::Registry => ids.contains_key(&spec.id).then(|| spec.id.clone()),
+                        DepSource::Url(_) => None,
+                    };
+                    if let Some(target) = target.filter(|t| *t != id && !depends_on.contains(t)) {
+                        depends_on.push(target);
+                    }
+                }
+            }
+            members.push(WorkspaceMember { id, path: path.clone(), depends_on });
+        }
+        Ok(Self {
+            root,
+            members,
+            conflicts: config.workspace.conflicts,
+            allow_conflicts: config.workspace.allow_conflicts,
+        })
+    }
+
+    /// 比较成员产物中的设备路径，返回被多个成员提供且不在 allow_conflicts 中的文件
+    /// paths 为 成员 id → 该成员产物的设备路径
+    pub fn find_conflicts(&self, paths: &BTreeMap<String, BTreeSet<String>>) -> Result<Vec<FileConflict>> {
+        let allowed = self.allow_conflicts.iter()
+            .map(|p| glob::Pattern::new(p).with_context(|| format!("allow_conflicts 中的模式无效: {}", p)))
+            .collect::<Result<Vec<_>>>()?;
+        let mut owners: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (id, member_paths) in paths {
+            for path in member_paths {
+                owners.entry(path).or_default().push(id.clone());
+            }
+        }
+        Ok(owners.into_iter()
+            .filter(|(path, members)| members.len() > 1 && !allowed.iter().any(|p| p.matches(path)))
+            .map(|(path, members)| FileConflict { path: path.to_string(), members })
+            .collect())
+    }
+
+    pub fn member(&self, id: &str) -> Option<&WorkspaceMember> {
+        self.members.iter().find(|m| m.id == id)
+    }
+
+    /// 拓扑排序：依赖先于依赖者；同层按配置顺序；存在循环时报错
+    pub fn build_order(&self) -> Result<Vec<&WorkspaceMember>> {
+        let mut order: Vec<&WorkspaceMember> = Vec::new();
+        let mut done: BTreeSet<&str> = BTreeSet::new();
+        while order.len() < self.members.len() {
+            let ready: Vec<&WorkspaceMember> = self.members.iter()
+                .filter(|m| !done.contains(m.id.as_str()))
+                .filter(|m| m.depends_on.iter().all(|d| done.contains(d.as_str())))
+                .collect();
+            if ready.is_empty() {
+                let cycle: Vec<&str> = self.members.iter()
+                    .filter(|m| !done.contains(m.id.as_str()))
+                    .map(|m| m.id.as_str())
+                    .collect();
+                anyhow::bail!("工作区成员之间存在循环依赖: {}", cycle.join(", "));
+            }
+            for member in ready {
+                done.insert(&member.id);
+                order.push(member);
+            }
+        }
+        Ok(order)
+    }
+
+    /// 直接或间接依赖 ids 中任一成员的成员（含 ids 本身）
+    pub fn with_dependents(&self, ids: &BTreeSet<String>) -> BTreeSet<String> {
+        let mut affected = ids.clone();
+        loop {
+            let before = affected.len();
+            for member in &self.members {
+                if member.depends_on.iter().any(|d| affected.contains(d)) {
+                    affected.insert(member.id.clone());
+                }
+            }
+            if affected.len() == before {
+                return affected;
+            }
+        }
+    }
+
+    /// 自 git_ref 以来（含未提交与未跟踪的改动）文件有变化的成员及其依赖者
+    pub fn affected_since(&self, git_ref: &str) -> Result<BTreeSet<String>> {
+        let repo = git2::Repository::discover(&self.root)
+            .with_context(|| format!("{} 不在 Git 仓库中", self.root.display()))?;
+        let workdir = repo.workdir()
+            .ok_or_else(|| anyhow::anyhow!("不支持裸仓库"))?;
+        let workdir = workdir.canonicalize().unwrap_or_else(|_| workdir.to_path_buf());
+        let tree = repo.revparse_single(git_ref)
+            .and_then(|object| object.peel_to_tree())
+            .with_context(|| format!("无法解析 Git 引用: {}", git_ref))?;
+        let mut options = git2::DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+
+        let changed: Vec<PathBuf> = diff.deltas()
+            .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+            .flatten()
+            .map(|path| workdir.join(path))
+            .collect();
+        let touched: BTreeSet<String> = self.members.iter()
+            .filter(|member| changed.iter().any(|path| path.starts_with(&member.path)))
+            .map(|member| member.id.clone())
+            .collect();
+        Ok(self.with_dependents(&touched))
+    }
+}
+
+/// 传给依赖者构建命令的环境变量名，如 RMM_DEP_CORE_LIB_DIST
+pub fn dependency_env_var(id: &str) -> String {
+    let name: String = id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("RMM_DEP_{}_DIST", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_workspace_build_order_and_cycles() {
+        let member = |id: &str, deps: &[&str]| WorkspaceMember {
+            id: id.to_string(),
+            path: PathBuf::from(id),
+            depends_on: deps.iter().map(|d| d.to_string()).collect(),
+        };
+        let mut workspace = Workspace {
+            root: PathBuf::from("."),
+            members: vec![member("app", &["net", "lib"]), member("net", &["lib"]), member("lib", &[]), member("solo", &[])],
+            conflicts: Default::default(),
+            allow_conflicts: Vec::new(),
+        };
+        let order: Vec<&str> = workspace.build_order().unwrap().iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(order, vec!["lib", "solo", "net", "app"]);
+        let dependents = workspace.with_dependents(&std::collections::BTreeSet::from(["net".to_string()]));
+        assert_eq!(dependents.into_iter().collect::<Vec<_>>(), vec!["app".to_string(), "net".to_string()]);
+        assert_eq!(dependency_env_var("core-lib.v2"), "RMM_DEP_CORE_LIB_V2_DIST");
+
+        workspace.members[2].depends_on.push("app".to_string());
+        let error = workspace.build_order().unwrap_err().to_string();
+        assert!(error.contains("循环依赖") && !error.contains("solo"));
+
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join(WORKSPACE_FILE), "[workspace]\nmembers = [\"missing\"]\n").unwrap();
+        assert!(Workspace::load(temp.path()).is_err());
+    }
+}
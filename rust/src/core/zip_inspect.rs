@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use super::hashing::{sha256_file, sha256_reader};
+
+/// Magisk 安装器入口
+pub const UPDATE_BINARY: &str = "META-INF/com/google/android/update-binary";
+/// Magisk 安装器脚本
+pub const UPDATER_SCRIPT: &str = "META-INF/com/google/android/updater-script";
+
+/// zip 中单个条目的信息
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ZipEntryInfo {
+    pub name: String,
+    pub size: u64,
+    pub compressed_size: u64,
+    pub is_dir: bool,
+    /// 文件内容的 SHA-256（目录为 None）
+    pub sha256: Option<String>,
+    /// Unix 权限位（如有）
+    pub unix_mode: Option<u32>,
+}
+
+/// 模块 zip 的检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ZipInspection {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    pub entries: Vec<ZipEntryInfo>,
+    /// 解析后的 module.prop（缺失时为 None）
+    pub module_prop: Option<BTreeMap<String, String>>,
+    pub has_update_binary: bool,
+    pub has_updater_script: bool,
+}
+
+fn open_archive(zip_path: &Path) -> Result<zip::ZipArchive<fs::File>> {
+    let file = fs::File::open(zip_path)
+        .with_context(|| format!("Failed to open {}", zip_path.display()))?;
+    zip::ZipArchive::new(file)
+        .with_context(|| format!("{} 不是有效的 zip 文件", zip_path.display()))
+}
+
+/// 列出 zip 中的全部条目（含每个文件的 SHA-256），无需解压
+pub fn list_zip_entries(zip_path: &Path) -> Result<Vec<ZipEntryInfo>> {
+    let mut archive = open_archive(zip_path)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        let is_dir = entry.is_dir();
+        let size = entry.size();
+        let compressed_size = entry.compressed_size();
+        let unix_mode = entry.unix_mode();
+        let sha256 = if is_dir {
+            None
+        } else {
+            Some(sha256_reader(entry).with_context(|| format!("Failed to read {}", name))?)
+        };
+        entries.push(ZipEntryInfo { name, size, compressed_size, is_dir, sha256, unix_mode });
+    }
+    Ok(entries)
+}
+
+/// 读取 zip 中指定成员的内容，无需解压
+pub fn read_zip_member(zip_path: &Path, member: &str) -> Result<Vec<u8>> {
+    let mut archive = open_archive(zip_path)?;
+    let mut entry = archive.by_name(member)
+        .with_context(|| format!("{} 中不存在 {}", zip_path.display(), member))?;
+    let mut content = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+/// 解析 module.prop 文本
+pub fn parse_module_prop(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect()
+}
+
+/// 读取 prop 文本：去掉 UTF-8 BOM，非 UTF-8 字节替换为 U+FFFD，其他工具生成的文件不会导致读取失败
+pub fn read_prop_text(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).with_context(|| format!("无法读取 {}", path.display()))?;
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+    Ok(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// 替换 prop 内容中已有键的值；其他行按原始字节保留，不会破坏非 UTF-8 内容
+pub fn replace_prop_values(content: &[u8], changes: &[(&str, &str)]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(content.len());
+    for line in content.split_inclusive(|&b| b == b'\n') {
+        let replacement = changes.iter().find(|(key, _)| {
+            line.strip_prefix(key.as_bytes()).is_some_and(|rest| rest.first() == Some(&b'='))
+        });
+        match replacement {
+            Some((key, value)) => {
+                output.extend_from_slice(format!("{}={}", key, value).as_bytes());
+                if line.ends_with(b"\n") {
+                    output.push(b'\n');
+                }
+            }
+            None => output.extend_from_slice(line),
+        }
+    }
+    output
+}
+
+/// 检查模块 zip：条目与哈希、module.prop、安装器入口
+pub fn inspect_zip(zip_path: &Path) -> Result<ZipInspection> {
+    let entries = list_zip_entries(zip_path)?;
+    let has_member = |name: &str| entries.iter().any(|e| e.name == name);
+
+    let module_prop = if has_member("module.prop") {
+        let content = read_zip_member(zip_path, "module.prop")?;
+        Some(parse_module_prop(&String::from_utf8_lossy(&content)))
+    } else {
+        None
+    };
+
+    Ok(ZipInspection {
+        path: zip_path.to_string_lossy().to_string(),
+        size: fs::metadata(zip_path)?.len(),
+        sha256: sha256_file(zip_path)?,
+        has_update_binary: has_member(UPDATE_BINARY),
+        has_updater_script: has_member(UPDATER_SCRIPT),
+        module_prop,
+        entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_zip_inspect_reads_members_without_extracting() {
+        use crate::core::hashing::sha256_bytes;
+        use std::io::Write;
+
+        let temp_dir = tempdir().unwrap();
+        let zip_path = temp_dir.path().join("demo-100.zip");
+        {
+            let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            writer.add_directory("META-INF/", options).unwrap();
+            writer.start_file(UPDATE_BINARY, options).unwrap();
+            writer.write_all(b"#!/sbin/sh\n").unwrap();
+            writer.start_file("module.prop", options).unwrap();
+            writer.write_all(b"id=demo\nversionCode=100\n").unwrap();
+            writer.finish().unwrap();
+        }
+
+        let inspection = inspect_zip(&zip_path).unwrap();
+        assert!(inspection.has_update_binary);
+        assert!(!inspection.has_updater_script);
+        let prop = inspection.module_prop.unwrap();
+        assert_eq!(prop.get("id").map(String::as_str), Some("demo"));
+        assert_eq!(prop.get("versionCode").map(String::as_str), Some("100"));
+
+        let entry = inspection.entries.iter().find(|e| e.name == "module.prop").unwrap();
+        assert_eq!(entry.sha256.as_deref(), Some(sha256_bytes(b"id=demo\nversionCode=100\n").as_str()));
+        assert!(inspection.entries.iter().any(|e| e.is_dir && e.sha256.is_none()));
+
+        assert_eq!(read_zip_member(&zip_path, UPDATE_BINARY).unwrap(), b"#!/sbin/sh\n");
+        assert!(read_zip_member(&zip_path, "missing.txt").is_err());
+    }
+
+    #[test]
+    fn test_prop_with_bom_and_non_utf8_bytes() {
+        let temp = tempdir().unwrap();
+        let prop_path = temp.path().join("module.prop");
+        let content = b"\xEF\xBB\xBFid=demo\r\nversion=v1.0\r\ndescription=Caf\xe9 fonts\r\nversionCode=100\n";
+        fs::write(&prop_path, content).unwrap();
+
+        let prop = parse_module_prop(&read_prop_text(&prop_path).unwrap());
+        assert_eq!(prop["id"], "demo");
+        assert_eq!(prop["versionCode"], "100");
+        assert_eq!(prop["description"], "Caf\u{FFFD} fonts");
+
+        let updated = replace_prop_values(content, &[("version", "v1.1"), ("versionCode", "101")]);
+        assert_eq!(
+            updated,
+            b"\xEF\xBB\xBFid=demo\r\nversion=v1.1\ndescription=Caf\xe9 fonts\r\nversionCode=101\n".to_vec()
+        );
+    }
+}
@@ -1,13 +1,14 @@
 #![recursion_limit = "256"]
 
 use pyo3::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 mod cmds;
 mod core;
 
-use cmds::{Commands, RmmBox};
+use cmds::{CacheCommands, CiCommands, Commands, DaemonCommands, DepsCommands, LockCommands, DeviceCommands, PropCommands, MigrationCommands, RegistryCommands, RmmBox, SelfCommands, SignCommands, UpstreamCommands, TemplateCommands, WorkspaceCommands};
 use core::python_bindings::PyRmmCore;
+use core::errors::{to_py_err, BuildError, ErrorKind, RmmError};
 use pyo3::Python;
 
 use clap::{Parser, CommandFactory};
@@ -25,7 +26,20 @@ use colored::*;
 
 {all-args}{after-help}
 ")]
+#[command(after_help = "旧版命令名与选项的对照见 rmm help --deprecated")]
 struct Cli {
+    /// 打印每个配置值及其来源（命令行 > 环境变量 > 项目 > 全局 > 默认）
+    #[arg(long, global = true, default_value = "false")]
+    trace_config: bool,
+
+    /// 纯文本输出：表情换成 [OK]/[WARN]/[ERR] 等标签并关闭颜色（也可设置 RMM_NO_EMOJI）
+    #[arg(long, global = true, default_value = "false")]
+    plain: bool,
+
+    /// GitHub 令牌（优先于 GITHUB_ACCESS_TOKEN、GITHUB_TOKEN、GH_TOKEN）
+    #[arg(long, global = true, value_name = "TOKEN")]
+    token: Option<String>,
+
     #[command(subcommand)]
     /// 命令
     cmd: Option<Commands>,
@@ -33,12 +47,58 @@ struct Cli {
 /// CLI 入口函数
 #[pyfunction]
 fn cli() -> PyResult<()> {
-    let args = Cli::parse_from(std::env::args().skip(1));
+    let raw_args: Vec<String> = std::env::args().skip(1).collect();
+    // 纯文本模式在解析参数前生效，帮助与错误信息同样经过转换
+    let plain_output = core::console::PlainOutput::install(raw_args.iter().any(|arg| arg == "--plain"));
+    let (program, rest) = raw_args.split_first().map(|(p, r)| (vec![p.clone()], r)).unwrap_or_default();
+    if cmds::alias::is_deprecated_help(rest) {
+        cmds::alias::print_deprecated();
+        return Ok(());
+    }
+    // 旧命令名与旧选项改写为当前写法，并提示迁移
+    let (rest, deprecations) = cmds::alias::rewrite_args(rest);
+    deprecations.iter().for_each(|d| d.print());
+    let raw_args: Vec<String> = program.into_iter().chain(rest).collect();
+    let args = match Cli::try_parse_from(&raw_args) {
+        Ok(args) => args,
+        Err(e) => {
+            let _ = e.print();
+            // process::exit 不会运行析构，先把转换中的输出写完
+            drop(plain_output);
+            std::process::exit(e.exit_code());
+        }
+    };
+
+    // rmm daemon 运行时由常驻进程执行，复用其中已加载的缓存
+    if let Some(cmd) = &args.cmd
+        && let Some(result) = cmds::daemon::delegate(cmd, &raw_args)
+    {
+        return result.map_err(RmmError::new_err);
+    }
+    execute(args)
+}
+
+/// 常驻进程中执行一条命令（args 含程序名）
+fn run_args(args: Vec<String>) -> Result<(), String> {
+    let (rest, deprecations) = cmds::alias::rewrite_args(args.get(1..).unwrap_or_default());
+    deprecations.iter().for_each(|d| d.print());
+    let args = Cli::try_parse_from(args.into_iter().take(1).chain(rest)).map_err(|e| e.to_string())?;
+    execute(args).map_err(|e| e.to_string())
+}
+
+/// 执行解析后的命令
+fn execute(args: Cli) -> PyResult<()> {
+    core::settings::set_trace(args.trace_config);
+    let token = args.token;
+
+    // Ctrl-C 时终止外部命令并清理临时文件，而不是直接杀死进程
+    core::process::install_interrupt_handler();
+
     match args.cmd {        // 初始化命令
-        Some(Commands::Init { project_id }) => {
+        Some(Commands::Init { project_id, template, license, channel, with_email, git_init, initial_version, boot_script, force, mount }) => {
             // 获取当前目录
             let current_dir = std::env::current_dir().map_err(|e| 
-                pyo3::exceptions::PyRuntimeError::new_err(format!("无法获取当前目录: {}", e))
+                RmmError::new_err(format!("无法获取当前目录: {}", e))
             )?;
             
             // 处理项目ID和路径
@@ -46,14 +106,14 @@ fn cli() -> PyResult<()> {
                 // 如果是 "."，使用当前目录名作为项目ID，在当前目录初始化
                 let dir_name = current_dir.file_name()
                     .and_then(|n| n.to_str())
-                    .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("无法获取当前目录名"))?;
+                    .ok_or_else(|| RmmError::new_err("无法获取当前目录名"))?;
                 (dir_name.to_string(), current_dir)
             } else {
                 // 解析路径，可能是相对路径如 ./XXX/YYY
                 let target_path = if project_id.starts_with('.') {
                     // 相对路径：./XXX/YYY 或 ../XXX
                     current_dir.join(&project_id).canonicalize()
-                        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("无法解析路径 '{}': {}", project_id, e)))?
+                        .map_err(|e| RmmError::new_err(format!("无法解析路径 '{}': {}", project_id, e)))?
                 } else {
                     // 直接名称：在当前目录下创建
                     current_dir.join(&project_id)
@@ -62,47 +122,52 @@ fn cli() -> PyResult<()> {
                 // 从最终路径提取项目ID（目录名）
                 let dir_name = target_path.file_name()
                     .and_then(|n| n.to_str())
-                    .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("无法获取目标目录名"))?;
+                    .ok_or_else(|| RmmError::new_err("无法获取目标目录名"))?;
                 
                 // 如果不是相对路径，需要创建目录
                 if !project_id.starts_with('.') {
                     if let Err(e) = std::fs::create_dir_all(&target_path) {
-                        return Err(pyo3::exceptions::PyRuntimeError::new_err(format!("无法创建项目目录: {}", e)));
+                        return Err(RmmError::new_err(format!("无法创建项目目录: {}", e)));
                     }
                 }
                 
                 (dir_name.to_string(), target_path)
-            };// 从 meta 配置读取作者信息，如果没有则使用默认值
-            let core = core::rmm_core::RmmCore::new();
-            let (author_name, author_email) = match core.get_meta_config() {
-                Ok(meta) => {
-                    (meta.username, meta.email)
-                }
-                Err(_) => {
-                    ("unknown".to_string(), "unknown@example.com".to_string())
-                }
+            };// 作者信息按分层配置解析：环境变量 > meta 配置，均未设置时由 init 从 Git 配置获取
+            let config = core::settings::ConfigResolver::new(None)
+                .with_flag("privacy.include_email", with_email);
+            let author_name = config.get("author.name").unwrap_or_default();
+            let author_email = config.get("author.email").unwrap_or_default();
+            let options = cmds::init::ProjectOptions {
+                id: actual_project_id,
+                author: author_name,
+                email: author_email,
+                include_email: config.get_bool("privacy.include_email"),
+                template,
+                license,
+                channels: channel,
+                git_init,
+                version: initial_version,
+                boot_scripts: boot_script,
+                force,
+                mount,
             };
-              match cmds::init::init_project(&project_path, &actual_project_id, &author_name, &author_email) {
+            match cmds::init::create_project(&project_path, &options) {
                 Ok(()) => {
-                    // 更新 meta 配置中的 projects (ID = PATH)
-                    if let Err(e) = update_meta_projects(&core, &actual_project_id, &project_path) {
-                        eprintln!("⚠️ 警告: 无法更新 meta 配置: {}", e);
-                    }
                     println!("{} 项目初始化成功！", "✅".green().bold());
                 }
                 Err(e) => {                    eprintln!("❌ 初始化失败: {}", e);
-                    return Err(pyo3::exceptions::PyRuntimeError::new_err(format!("初始化失败: {}", e)));
+                    return Err(to_py_err(&e, ErrorKind::General, format!("初始化失败: {}", e)));
                 }
             }
         },
           // 构建命令
-        Some(Commands::Build { project_path, no_auto_fix, script }) => {
+        Some(Commands::Build { project_path, no_auto_fix, explain, force, resume, debug, recovery, trust, sandbox, sign, allow_path, allow_network, workspace, tags, script }) => {
             // 确定项目路径
             let target_path = if let Some(path) = project_path {
                 PathBuf::from(path)
             } else {
                 std::env::current_dir().map_err(|e| 
-                    pyo3::exceptions::PyRuntimeError::new_err(format!("无法获取当前目录: {}", e))
+                    RmmError::new_err(format!("无法获取当前目录: {}", e))
                 )?
             };
             
@@ -110,7 +175,8 @@ fn cli() -> PyResult<()> {
             let project_path = target_path.canonicalize().unwrap_or(target_path);
               // 如果指定了脚本，运行脚本；否则运行构建
             if let Some(script_name) = script {
-                let core = core::rmm_core::RmmCore::new();
+                let core = core::rmm_core::RmmCore::new()
+                    .with_event_sink(std::sync::Arc::new(core::notify::ConsoleSink::default()));
                 match core.run_rmake_script(&project_path, &script_name) {
                     Ok(()) => {
                         println!("{} 脚本执行成功！", "✅".green().bold());
@@ -137,18 +203,41 @@ fn cli() -> PyResult<()> {
                         } else {
                             eprintln!("❌ 脚本执行失败: {}", e);
                         }
-                        return Err(pyo3::exceptions::PyRuntimeError::new_err(format!("脚本执行失败: {}", e)));
+                        return Err(to_py_err(&e, ErrorKind::Build, format!("脚本执行失败: {}", e)));
                     }
                 }
             } else {
                 // 执行构建，传递自动修复参数
-                let auto_fix = !no_auto_fix;  // 默认启用自动修复，除非用户明确禁用
-                match cmds::build::build_project_with_options(&project_path, auto_fix) {
+                let options = cmds::build::BuildOptions {
+                    auto_fix: !no_auto_fix,  // 默认启用自动修复，除非用户明确禁用
+                    explain,
+                    force,
+                    resume,
+                    debug,
+                    recovery,
+                    trust,
+                    sandbox: core::settings::ConfigResolver::new(Some(&project_path))
+                        .with_flag("build.sandbox", sandbox)
+                        .get_bool("build.sandbox")
+                        .then(|| core::sandbox::SandboxOptions {
+                        allow_paths: allow_path.iter().map(PathBuf::from).collect(),
+                        allow_network,
+                    }),
+                    env: Default::default(),
+                    incremental: None,
+                    sign,
+                };
+                let result = if workspace {
+                    cmds::workspace::build_workspace(&project_path, &options, None, &tags)
+                } else {
+                    cmds::build::build_project_with_options(&project_path, &options)
+                };
+                match result {
                     Ok(()) => {
                         println!("{} 构建成功！", "✅".green().bold());
                     }                    Err(e) => {
                         eprintln!("❌ 构建失败: {}", e);
-                        return Err(pyo3::exceptions::PyRuntimeError::new_err(format!("构建失败: {}", e)));
+                        return Err(to_py_err(&e, ErrorKind::Build, format!("构建失败: {}", e)));
                     }
                 }
             }        },
@@ -160,7 +249,7 @@ fn cli() -> PyResult<()> {
                 PathBuf::from(path)
             } else {
                 std::env::current_dir().map_err(|e| 
-                    pyo3::exceptions::PyRuntimeError::new_err(format!("无法获取当前目录: {}", e))
+                    RmmError::new_err(format!("无法获取当前目录: {}", e))
                 )?
             };
             
@@ -175,44 +264,607 @@ fn cli() -> PyResult<()> {
                     }
                 }                Err(e) => {
                     eprintln!("❌ 执行失败: {}", e);
-                    return Err(pyo3::exceptions::PyRuntimeError::new_err(format!("执行失败: {}", e)));
+                    return Err(to_py_err(&e, ErrorKind::Build, format!("执行失败: {}", e)));
+                }
+            }
+        },
+        
+        // 发布到 GitHub Release
+        Some(Commands::Publish { project_path, channel, restart, no_notify }) => {
+            let project_path = resolve_project_path(project_path)?;
+            let options = cmds::publish::PublishOptions { channel, restart, no_notify, token };
+            if let Err(e) = cmds::publish::publish_project(&project_path, &options) {
+                eprintln!("❌ 发布失败: {:#}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("发布失败: {:#}", e)));
+            }
+        },
+        
+        // 监听文件变化并增量构建
+        Some(Commands::Watch { project_path, no_auto_fix, debug, trust }) => {
+            let project_path = resolve_project_path(project_path)?;
+            let options = cmds::build::BuildOptions {
+                auto_fix: !no_auto_fix,
+                debug,
+                trust,
+                ..Default::default()
+            };
+            if let Err(e) = cmds::watch::watch_project(&project_path, &options) {
+                eprintln!("❌ 监听失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::Build, format!("监听构建失败: {}", e)));
+            }
+        },
+        
+        // 产物签名与密钥管理
+        Some(Commands::Sign { command }) => {
+            let result = match command {
+                SignCommands::Artifacts { files, project_path, key, update_json } => {
+                    resolve_project_path(project_path).map_err(anyhow::Error::from).and_then(|path| {
+                        let files: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+                        cmds::sign::sign_artifacts(&path, &files, key.as_deref(), update_json).map(|_| ())
+                    })
                 }
+                SignCommands::Keygen { name, force } => cmds::sign::keygen(&name, force),
+                SignCommands::List => cmds::sign::list_keys(),
+                SignCommands::Verify { zip, strict } => cmds::sign::verify(Path::new(&zip), strict),
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 签名操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("签名操作失败: {}", e)));
             }
         },
         
         // 同步项目元数据命令
-        Some(Commands::Sync { project_name, projects_only, search_paths, max_depth }) => {
+        Some(Commands::Sync { project_name, projects_only, search_paths, max_depth, tags }) => {
             // 转换 search_paths 为 &str 类型
             let search_paths_refs = search_paths.as_ref().map(|paths| {
                 paths.iter().map(|s| s.as_str()).collect::<Vec<&str>>()
             });
             
+            let max_depth = core::settings::ConfigResolver::new(None)
+                .with_cli("sync.max_depth", max_depth)
+                .get_parsed::<usize>("sync.max_depth")
+                .map_err(|e| to_py_err(&e, ErrorKind::Config, format!("项目同步失败: {}", e)))?;
+
             // 同步项目
             match cmds::sync::sync_projects(
                 project_name.as_deref(),
                 projects_only,
                 search_paths_refs,
                 max_depth,
+                &tags,
             ) {
                 Ok(()) => {
                     println!("{} 项目同步成功！", "✅".green().bold());
                 }
                 Err(e) => {
                     eprintln!("❌ 同步失败: {}", e);
-                    return Err(pyo3::exceptions::PyRuntimeError::new_err(format!("同步失败: {}", e)));
+                    return Err(to_py_err(&e, ErrorKind::General, format!("同步失败: {}", e)));
                 }
             }        },
         
+        // 显示运行环境
+        Some(Commands::Env { paths, config, migrate, project_path }) => {
+            let result = if migrate {
+                cmds::env::migrate()
+            } else if config {
+                resolve_project_path(project_path).map(|path| cmds::env::show_config(&path))?
+            } else {
+                cmds::env::show_env(paths)
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 获取环境信息失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("获取环境信息失败: {}", e)));
+            }
+        },
+
+        // CI 配置命令
+        Some(Commands::Ci { command: CiCommands::Init { github, project_path, force } }) => {
+            if !github {
+                eprintln!("❌ 请指定 CI 平台，目前支持: --github");
+                return Err(RmmError::new_err("未指定 CI 平台"));
+            }
+            let project_path = resolve_project_path(project_path)?;
+            
+            match cmds::ci::init_github(&project_path, &Cli::command(), force) {
+                Ok(_) => {
+                    println!("{} CI 工作流生成成功！", "✅".green().bold());
+                }
+                Err(e) => {
+                    eprintln!("❌ 生成 CI 工作流失败: {}", e);
+                    return Err(to_py_err(&e, ErrorKind::General, format!("生成 CI 工作流失败: {}", e)));
+                }
+            }
+        },
+
+        // 上游母仓库发布前检查
+        Some(Commands::Upstream { command }) => {
+            let result = match command {
+                UpstreamCommands::Verify { project_path, reserve, json } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::upstream::verify_project(&project_path, reserve).and_then(|verification| {
+                        if json {
+                            println!("{}", serde_json::to_string_pretty(&verification)?);
+                        } else {
+                            cmds::upstream::print_verification(&verification);
+                        }
+                        Ok(())
+                    })
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 上游检查失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("上游检查失败: {}", e)));
+            }
+        },
+
+        // 设备模块快照与回滚
+        Some(Commands::Device { command }) => {
+            let result = match command {
+                DeviceCommands::Install { zip, project_path, serial: _, qr: true, abi, port, .. } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::device::handoff::install_via_qr(&project_path, zip.map(PathBuf::from), &abi, port).map(|_| ())
+                }
+                DeviceCommands::Install { zip, project_path, serial, verify, strict, no_deps, yes, reboot, no_reboot, .. } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    let policy = if strict {
+                        core::signing::VerifyPolicy::Strict
+                    } else if verify {
+                        core::signing::VerifyPolicy::Verify
+                    } else {
+                        core::signing::VerifyPolicy::Off
+                    };
+                    cmds::device::connect(&project_path, serial).and_then(|adb| {
+                        if !no_deps {
+                            cmds::device::deps::install_dependencies(&adb, &project_path, yes)?;
+                        }
+                        cmds::device::install_module(&adb, &project_path, zip.map(PathBuf::from), policy)?;
+                        cmds::device::offer_reboot(&adb, reboot, no_reboot)
+                    })
+                }
+                DeviceCommands::InstallKey { key, project_path, serial } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    core::signing::resolve_public_key(key.as_deref()).and_then(|key| {
+                        cmds::device::install_public_key(&cmds::device::connect(&project_path, serial)?, &key).map(|_| ())
+                    })
+                }
+                DeviceCommands::Snapshot { module_id, project_path, serial } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::device::resolve_module_id(module_id, &project_path).and_then(|id| {
+                        cmds::device::snapshot_module(&cmds::device::connect(&project_path, serial)?, &id).map(|_| ())
+                    })
+                }
+                DeviceCommands::Rollback { module_id, project_path, serial, snapshot, reboot } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::device::connect(&project_path, serial).and_then(|adb| {
+                        let id = cmds::device::resolve_module_id(module_id, &project_path)?;
+                        cmds::device::rollback_module(&adb, &id, snapshot.as_deref())?;
+                        if reboot { adb.reboot() } else { Ok(()) }
+                    })
+                }
+                DeviceCommands::Action { module_id, project_path, serial } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::device::resolve_module_id(module_id, &project_path).and_then(|id| {
+                        cmds::device::run_action(&cmds::device::connect(&project_path, serial)?, &id)
+                    })
+                }
+                DeviceCommands::Flag { flag, module_id, on, off, project_path, serial } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    let enable = if on { Some(true) } else if off { Some(false) } else { None };
+                    cmds::device::resolve_module_id(module_id, &project_path).and_then(|id| {
+                        cmds::device::set_control_flag(&cmds::device::connect(&project_path, serial)?, &id, &flag, enable).map(|_| ())
+                    })
+                }
+                DeviceCommands::PushProp { module_id, project_path, serial, set, edit, restart } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    let changes: anyhow::Result<Vec<(String, String)>> = set.iter()
+                        .map(|s| cmds::device::parse_prop_assignment(s))
+                        .collect();
+                    changes.and_then(|changes| {
+                        let id = cmds::device::resolve_module_id(module_id, &project_path)?;
+                        cmds::device::push_prop(&cmds::device::connect(&project_path, serial)?, &id, &changes, edit, restart)
+                    })
+                }
+                DeviceCommands::Perf { module_id, project_path, serial, runs, boot_timeout, json } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::device::resolve_module_id(module_id, &project_path).and_then(|id| {
+                        let adb = cmds::device::connect(&project_path, serial)?;
+                        let report = cmds::device::perf::measure_boot_impact(&adb, &id, runs, boot_timeout)?;
+                        if json {
+                            println!("{}", serde_json::to_string_pretty(&report)?);
+                        } else {
+                            cmds::device::perf::print_perf_report(&report);
+                        }
+                        Ok(())
+                    })
+                }
+                DeviceCommands::Snapshots { module_id, project_path } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::device::resolve_module_id(module_id, &project_path)
+                        .and_then(|id| cmds::device::print_snapshots(&id))
+                }
+                DeviceCommands::Pair { address, code } => {
+                    cmds::device::wireless::pair_device(&cmds::device::server_adb(), &address, code)
+                }
+                DeviceCommands::Connect { address } => {
+                    cmds::device::wireless::connect_device(&cmds::device::server_adb(), address.as_deref())
+                }
+                DeviceCommands::Disconnect { address } => {
+                    cmds::device::wireless::disconnect_device(&cmds::device::server_adb(), address.as_deref())
+                }
+                DeviceCommands::List => cmds::device::wireless::list_devices(&cmds::device::server_adb()),
+                DeviceCommands::Forget { address } => {
+                    cmds::device::wireless::forget_device(&cmds::device::server_adb(), &address)
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 设备操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::Device, format!("设备操作失败: {}", e)));
+            }
+        },
+
+        // 设备测试
+        Some(Commands::Test { project_path, host: true, .. }) => {
+            let project_path = resolve_project_path(project_path)?;
+            match cmds::test::host::run_host_tests(&project_path) {
+                Ok(report) => {
+                    cmds::test::host::print_host_report(&report);
+                    if report.failed > 0 {
+                        eprintln!("❌ 主机测试失败: {} 个用例未通过", report.failed);
+                        return Err(BuildError::new_err(format!("主机测试失败: {} 个用例未通过", report.failed)));
+                    }
+                    println!("{} 主机测试通过！", "✅".green().bold());
+                }
+                Err(e) => {
+                    eprintln!("❌ 主机测试失败: {}", e);
+                    return Err(to_py_err(&e, ErrorKind::Build, format!("主机测试失败: {}", e)));
+                }
+            }
+        },
+
+        Some(Commands::Test { project_path, serial, keep, no_reboot, boot_timeout, verify, screenshot, screenrecord, .. }) => {
+            let project_path = resolve_project_path(project_path)?;
+            let options = cmds::test::TestOptions { serial, keep, no_reboot, boot_timeout, verify, screenshot, screenrecord };
+            match cmds::test::run_device_test(&project_path, &options) {
+                Ok(()) => {
+                    println!("{} 设备测试通过！", "✅".green().bold());
+                }
+                Err(e) => {
+                    eprintln!("❌ 设备测试失败: {}", e);
+                    return Err(to_py_err(&e, ErrorKind::Device, format!("设备测试失败: {}", e)));
+                }
+            }
+        },
+
+        // 跨项目查询配置
+        Some(Commands::Query { key, file, json }) => {
+            if let Err(e) = cmds::query::query_projects(&key, file.as_deref(), json) {
+                eprintln!("❌ 查询失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("查询失败: {}", e)));
+            }
+        },
+
+        // 跨项目搜索配置
+        Some(Commands::Grep { pattern, ignore_case }) => {
+            if let Err(e) = cmds::query::grep_projects(&pattern, ignore_case) {
+                eprintln!("❌ 搜索失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("搜索失败: {}", e)));
+            }
+        },
+
+        // 项目事件日志
+        Some(Commands::Log { project_path, kind, limit, json }) => {
+            let project_path = resolve_project_path(project_path)?;
+            if let Err(e) = cmds::log::show_log(&project_path, kind.as_deref(), limit, json) {
+                eprintln!("❌ 读取事件日志失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("读取事件日志失败: {}", e)));
+            }
+        },
+
+        // 模板管理
+        Some(Commands::Template { command }) => {
+            let storage = core::storage::RmmStorage::resolve();
+            let templates_dir = storage.templates_dir();
+            let result = match command {
+                TemplateCommands::Install { source, force } => {
+                    let project_path = resolve_project_path(None)?;
+                    cmds::lock::with_project_lock(&project_path, |lock| {
+                        cmds::template::install_template(&source, &templates_dir, &storage.cache_dir(), force, lock, false)
+                    }).map(|manifest| {
+                        println!("{} 模板 {} {} 安装成功！", "✅".green().bold(), manifest.name.green(), manifest.version.yellow());
+                    })
+                }
+                TemplateCommands::List => cmds::template::print_templates(&templates_dir),
+                TemplateCommands::Publish { name, repo, version } => {
+                    cmds::template::publish_template(&templates_dir, &name, &PathBuf::from(repo), version.as_deref()).map(|_| ())
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 模板操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("模板操作失败: {}", e)));
+            }
+        },
+
+        // 项目注册表导入/导出
+        Some(Commands::Registry { command }) => {
+            let result = match command {
+                RegistryCommands::Export { output, map } => {
+                    cmds::registry::parse_mappings(&map).and_then(|mappings| {
+                        cmds::registry::export_registry(output.as_deref().map(Path::new), &mappings)
+                    })
+                }
+                RegistryCommands::Import { file, map, overwrite } => {
+                    cmds::registry::parse_mappings(&map).and_then(|mappings| {
+                        cmds::registry::import_registry(Path::new(&file), &mappings, overwrite)
+                    }).map(|summary| {
+                        println!("{} 导入完成：新增 {}，更新 {}，冲突 {}", "✅".green().bold(),
+                            summary.added.len(), summary.updated.len(), summary.conflicts.len());
+                    })
+                }
+                RegistryCommands::Tag { project, tags } => cmds::registry::tag_project(&project, &tags, false),
+                RegistryCommands::Untag { project, tags } => cmds::registry::tag_project(&project, &tags, true),
+                RegistryCommands::List { tags, json } => cmds::registry::list_projects(&tags, json),
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 注册表操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("注册表操作失败: {}", e)));
+            }
+        },
+
+        Some(Commands::Deps { command: DepsCommands::Tree { project_path, json } }) => {
+            let project_path = resolve_project_path(project_path)?;
+            if let Err(e) = cmds::deps::show_tree(&project_path, json) {
+                eprintln!("❌ 依赖检查失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("依赖检查失败: {}", e)));
+            }
+        },
+
+        // 查看或修改 module.prop
+        Some(Commands::Prop { command }) => {
+            let result = match command {
+                PropCommands::Get { key, project_path } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::prop::get_prop(&project_path, key.as_deref())
+                }
+                PropCommands::Set { key, value, project_path, force } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::prop::set_prop(&project_path, &key, &value, force)
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 属性操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("属性操作失败: {}", e)));
+            }
+        },
+
+        // 工作区
+        Some(Commands::Workspace { command }) => {
+            let result = match command {
+                WorkspaceCommands::Graph { project_path, json } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::workspace::show_graph(&project_path, json)
+                }
+                WorkspaceCommands::Build { project_path, affected_since, tags, force, trust, sandbox } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    let options = cmds::build::BuildOptions {
+                        force,
+                        trust,
+                        sandbox: core::settings::ConfigResolver::new(Some(&project_path))
+                            .with_flag("build.sandbox", sandbox)
+                            .get_bool("build.sandbox")
+                            .then(core::sandbox::SandboxOptions::default),
+                        ..Default::default()
+                    };
+                    cmds::workspace::build_workspace(&project_path, &options, affected_since.as_deref(), &tags)
+                }
+                WorkspaceCommands::Conflicts { project_path, json } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::workspace::show_conflicts(&project_path, json)
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 工作区操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("工作区操作失败: {}", e)));
+            }
+        },
+
+        // 发布到 GitHub Pages
+        Some(Commands::Pages { project_path, no_push }) => {
+            let project_path = resolve_project_path(project_path)?;
+            match cmds::pages::publish_pages(&project_path, !no_push) {
+                Ok(_) => {
+                    println!("{} GitHub Pages 发布成功！", "✅".green().bold());
+                }
+                Err(e) => {
+                    eprintln!("❌ GitHub Pages 发布失败: {}", e);
+                    return Err(to_py_err(&e, ErrorKind::General, format!("GitHub Pages 发布失败: {}", e)));
+                }
+            }
+        },
+
+        Some(Commands::Serve { project_path, host, port, advertise }) => {
+            let project_path = resolve_project_path(project_path)?;
+            let options = cmds::serve::ServeOptions { bind: host, port, advertise };
+            if let Err(e) = cmds::serve::serve(&project_path, &options) {
+                eprintln!("❌ 本地服务失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("本地服务失败: {}", e)));
+            }
+        },
+
+        // 产物大小历史
+        Some(Commands::Clean { project_path, all, force, undo, layout, yes }) => {
+            let project_path = resolve_project_path(project_path)?;
+            if let Err(e) = cmds::clean::run_clean(&project_path, all, force, undo, layout, yes) {
+                eprintln!("❌ 清理失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("清理失败: {}", e)));
+            }
+        },
+
+        Some(Commands::Size { project_path, json }) => {
+            let project_path = resolve_project_path(project_path)?;
+            if let Err(e) = cmds::size::show_size_history(&project_path, json) {
+                eprintln!("❌ 读取大小历史失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("读取大小历史失败: {}", e)));
+            }
+        },
+
+        // 检查项目配置
+        Some(Commands::Check { project_path, update_db, dry_run_service }) => {
+            let project_path = resolve_project_path(project_path)?;
+            match cmds::check::check_project(&project_path, update_db, dry_run_service) {
+                Ok(()) => {
+                    println!("{} 检查通过！", "✅".green().bold());
+                }
+                Err(e) => {
+                    eprintln!("❌ 检查失败: {}", e);
+                    return Err(to_py_err(&e, ErrorKind::Build, format!("检查失败: {}", e)));
+                }
+            }
+        },
+
+        // 检查模块 zip
+        Some(Commands::Inspect { zip_path, json, member }) => {
+            if let Err(e) = cmds::inspect::inspect(std::path::Path::new(&zip_path), json, member.as_deref()) {
+                eprintln!("❌ 检查失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("检查失败: {}", e)));
+            }
+        },
+
+        // 解释路径的打包决定
+        Some(Commands::WhyExcluded { path, project_path, json }) => {
+            let project_path = resolve_project_path(project_path)?;
+            if let Err(e) = cmds::build::why::why_excluded(&project_path, &path, json) {
+                eprintln!("❌ 解释失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("解释失败: {}", e)));
+            }
+        },
+
+        // 输出配置 JSON Schema
+        Some(Commands::Schema { target, output }) => {
+            if let Err(e) = cmds::schema::print_schema(&target, output.as_deref().map(Path::new)) {
+                eprintln!("❌ 生成 Schema 失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("生成 Schema 失败: {}", e)));
+            }
+        },
+
+        // 打包流程基准测试
+        Some(Commands::Bench { files, size_mb, iterations, baseline, save_baseline, max_regression, json }) => {
+            let options = cmds::build::bench::BenchOptions {
+                files,
+                size_mb,
+                iterations,
+                baseline: baseline.map(PathBuf::from),
+                save_baseline,
+                max_regression,
+                json,
+            };
+            if let Err(e) = cmds::build::bench::run_bench(&options) {
+                eprintln!("❌ 基准测试失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::Build, format!("基准测试失败: {}", e)));
+            }
+        },
+
         // 显示版本信息
+        Some(Commands::Lock { command }) => {
+            let result = match command {
+                LockCommands::Update { names, project_path } => {
+                    resolve_project_path(project_path).map_err(anyhow::Error::from)
+                        .and_then(|path| cmds::lock::update_lock(&path, &names))
+                }
+                LockCommands::List { project_path, json } => {
+                    resolve_project_path(project_path).map_err(anyhow::Error::from)
+                        .and_then(|path| cmds::lock::list_lock(&path, json))
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 锁文件操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("锁文件操作失败: {}", e)));
+            }
+        },
+
+        Some(Commands::Licenses { project_path, json, output }) => {
+            let project_path = resolve_project_path(project_path)?;
+            if let Err(e) = cmds::licenses::list_licenses(&project_path, json, output.as_deref().map(Path::new)) {
+                eprintln!("❌ 许可证清单生成失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("许可证清单生成失败: {}", e)));
+            }
+        },
+
+        Some(Commands::Migration { command }) => {
+            let result = match command {
+                MigrationCommands::New { below, name, project_path } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::migration::new_migration(&project_path, below, name.as_deref()).map(|_| ())
+                }
+                MigrationCommands::List { project_path } => {
+                    cmds::migration::list_migrations(&resolve_project_path(project_path)?)
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 迁移脚本操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("迁移脚本操作失败: {}", e)));
+            }
+        },
+
+        Some(Commands::RmmSelf { command }) => {
+            let result = match command {
+                SelfCommands::Pin { project_path, exact } => {
+                    let project_path = resolve_project_path(project_path)?;
+                    cmds::rmm_self::pin_version(&project_path, exact).map(|_| ())
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 版本固定失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("版本固定失败: {}", e)));
+            }
+        },
+
+        Some(Commands::Daemon { command }) => {
+            let result = match command {
+                DaemonCommands::Start { foreground } => cmds::daemon::start(foreground),
+                DaemonCommands::Stop => cmds::daemon::stop(),
+                DaemonCommands::Status { json } => cmds::daemon::status(json),
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 常驻进程操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("常驻进程操作失败: {}", e)));
+            }
+        },
+
+        Some(Commands::Cache { command }) => {
+            let result = match command {
+                CacheCommands::Stats { json } => cmds::cache::show_stats(json),
+                CacheCommands::Mirrors { json, reset } => cmds::cache::show_mirrors(json, reset),
+                CacheCommands::Remote { project_path, push } => {
+                    resolve_project_path(project_path).map_err(anyhow::Error::from)
+                        .and_then(|path| cmds::cache::show_remote(&path, push))
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("❌ 缓存操作失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("缓存操作失败: {}", e)));
+            }
+        },
+
         Some(Commands::Version) => {
             RmmBox::rmm_version();
         },
 
+        Some(Commands::Completions { shell }) => {
+            if let Err(e) = cmds::completions::print_completions(shell, &mut Cli::command()) {
+                eprintln!("❌ 生成补全脚本失败: {}", e);
+                return Err(to_py_err(&e, ErrorKind::General, format!("生成补全脚本失败: {}", e)));
+            }
+        },
+
+        // 补全时不输出错误，shell 回退到静态补全
+        Some(Commands::Complete { index, words }) => {
+            let _ = cmds::completions::complete(&Cli::command(), index, &words);
+        },
+
         // 匹配外部命令
         Some(Commands::External(cmd)) => {
             println!("🤗查询拓展命令: {}", cmd.join(" ").bright_magenta().bold());
-            let command_name = cmd.get(0).cloned();
-            let module_name = command_name;
+            let module_name = cmd.first().cloned();
               // 尝试导入 Python 模块并执行
             let result = Python::with_gil(|py| {
                 if let Some(name) = &module_name {
@@ -269,18 +921,18 @@ fn cli() -> PyResult<()> {
 
 
 
-///库函数
-/// 更新 meta 配置中的项目列表
-fn update_meta_projects(core: &core::rmm_core::RmmCore, project_id: &str, project_path: &std::path::Path) -> anyhow::Result<()> {
-    let mut meta = core.get_meta_config()?;
-    meta.projects.insert(project_id.to_string(), project_path.to_string_lossy().to_string());
-    
-    // 保存更新后的配置
-    let meta_path = core.get_rmm_root().join("meta.toml");
-    let meta_content = toml::to_string_pretty(&meta)?;
-    std::fs::write(meta_path, meta_content)?;
-    
-    Ok(())
+/// 解析命令行给出的项目路径（默认为当前目录）
+fn resolve_project_path(project_path: Option<String>) -> PyResult<PathBuf> {
+    let target_path = if let Some(path) = project_path {
+        PathBuf::from(path)
+    } else {
+        std::env::current_dir().map_err(|e| 
+            RmmError::new_err(format!("无法获取当前目录: {}", e))
+        )?
+    };
+    let target_path = target_path.canonicalize().unwrap_or(target_path);
+    core::process::configure_from_project(&target_path);
+    Ok(target_path)
 }
 
 /// 获取 clap 样式配置
@@ -304,5 +956,8 @@ fn rmmcore(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // 添加 RmmCore 类
     m.add_class::<PyRmmCore>()?;
     
+    // 异常类型：RmmError 及 ProjectNotFound / ConfigError / BuildError / DeviceError / NetworkError
+    core::errors::register(m)?;
+    
     Ok(())
 }
\ No newline at end of file
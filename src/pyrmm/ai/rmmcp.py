@@ -28,7 +28,12 @@ class RmMcp(FastMCP):
     @property
     def ROOT(self) -> Path:
         """Get the root directory for RMM configuration."""
-        return Path(os.getenv("RMM_ROOT", Path().home() / "data" / "adb" / ".rmm"))
+        try:
+            from pyrmm.cli.rmmcore import RmmCore
+            return Path(RmmCore().get_rmm_root())
+        except ImportError:
+            from pyrmm.utils.storage import rmm_root
+            return rmm_root()
 
     @property
     def META_FILE(self) -> Path:
@@ -4,7 +4,31 @@ RmmCore 类型存根文件
 """
 
 from __future__ import annotations
-from typing import Any
+from typing import Any, Callable
+
+
+class RmmError(RuntimeError):
+    """RMM 异常的基类（继承 RuntimeError，兼容 except RuntimeError）"""
+
+
+class ProjectNotFound(RmmError):
+    """路径不是有效的 RMM 项目，或项目文件不存在"""
+
+
+class ConfigError(RmmError):
+    """配置文件缺失、无法解析或取值无效"""
+
+
+class BuildError(RmmError):
+    """构建、脚本、检查或测试失败"""
+
+
+class DeviceError(RmmError):
+    """adb 与设备操作失败"""
+
+
+class NetworkError(RmmError):
+    """网络请求失败"""
 
 
 class RmmCore:
@@ -35,7 +59,7 @@ class RmmCore:
             包含邮箱、用户名、版本和项目列表的字典
             
         Raises:
-            RuntimeError: 当配置文件不存在或解析失败时
+            ConfigError: 当配置文件解析失败时
         """
         ...    
     def update_meta_config(self, email: str, username: str, version: str, projects: dict[str, str]) -> None:
@@ -49,7 +73,7 @@ class RmmCore:
             projects: 项目名到路径的映射字典
             
         Raises:
-            RuntimeError: 当写入失败时
+            ConfigError: 当写入失败时
         """
         ...
     
@@ -61,7 +85,7 @@ class RmmCore:
             config: 包含配置信息的字典，需包含 email, username, version, projects 字段
             
         Raises:
-            RuntimeError: 当写入失败时
+            ConfigError: 当写入失败时
         """
         ...
     
@@ -111,6 +135,29 @@ class RmmCore:
         """
         ...
     
+    def scan(
+        self,
+        paths: list[str],
+        max_depth: int | None = None,
+        only_valid: bool = False,
+        progress: Callable[[dict[str, Any], int], Any] | None = None,
+    ) -> list[dict[str, Any]]:
+        """
+        扫描多个路径下的项目（跨路径去重），适合在程序中索引模块
+        
+        Args:
+            paths: 要扫描的路径列表
+            max_depth: 最大扫描深度，None 表示无限制
+            only_valid: 只返回有效项目（存在 .rmmp/Rmake.toml）
+            progress: 每找到一个项目调用 progress(result, found_count)；抛出异常会中止扫描
+            
+        Returns:
+            字典列表，每个字典包含 name, path, is_valid, git
+            （git 为 None 或包含 repo_root, relative_path, branch, remote_url,
+            has_uncommitted_changes, last_commit_hash）
+        """
+        ...
+    
     def sync_projects(self, scan_paths: list[str], max_depth: int | None = None) -> None:
         """
         同步项目列表到 meta.toml
@@ -120,7 +167,7 @@ class RmmCore:
             max_depth: 最大扫描深度
             
         Raises:
-            RuntimeError: 当同步失败时
+            RmmError: 当同步失败时
         """
         ...
     
@@ -135,7 +182,7 @@ class RmmCore:
             项目配置字典
             
         Raises:
-            RuntimeError: 当配置文件不存在或解析失败时
+            ProjectNotFound: 当 rmmproject.toml 不存在时；ConfigError: 解析失败时
         """
         ...
     
@@ -148,7 +195,7 @@ class RmmCore:
             config: 项目配置字典
             
         Raises:
-            RuntimeError: 当写入失败时
+            RmmError: 当写入失败时
         """
         ...
     
@@ -163,7 +210,7 @@ class RmmCore:
             模块属性字典
             
         Raises:
-            RuntimeError: 当文件不存在或解析失败时
+            ProjectNotFound: 当 module.prop 不存在时；ConfigError: 解析失败时
         """
         ...
     
@@ -176,7 +223,7 @@ class RmmCore:
             prop: 模块属性字典
             
         Raises:
-            RuntimeError: 当写入失败时
+            RmmError: 当写入失败时
         """
         ...
     
@@ -191,7 +238,7 @@ class RmmCore:
             构建配置字典
             
         Raises:
-            RuntimeError: 当配置文件不存在或解析失败时
+            ProjectNotFound: 当 Rmake.toml 不存在时；ConfigError: 解析失败时
         """
         ...
     
@@ -204,7 +251,7 @@ class RmmCore:
             config: 构建配置字典
             
         Raises:
-            RuntimeError: 当写入失败时
+            RmmError: 当写入失败时
         """
         ...
     
@@ -226,7 +273,7 @@ class RmmCore:
             - last_commit_message: 最后一次提交的消息
             
         Raises:
-            RuntimeError: 当路径不在 Git 仓库中时
+            RmmError: 当路径不在 Git 仓库中时
         """
         ...
     
@@ -263,6 +310,242 @@ class RmmCore:
         """
         ...
 
+    def record_event(self, project_path: str, kind: str, details: dict[str, str] | None = None) -> None:
+        """
+        追加项目事件到 .rmmp/events.jsonl（可通过 rmm log 查看）
+        
+        Args:
+            project_path: 项目路径
+            kind: 事件类型，如 publish
+            details: 事件详情
+        """
+        ...
+
+    def render_release_notes(
+        self,
+        project_path: str,
+        repo: str,
+        tag: str,
+        assets: list[str],
+        mirrors: list[str] | None = None,
+        channel: str | None = None,
+    ) -> tuple[str, str]:
+        """
+        按 Rmake.toml 的 [publish.notes] 渲染发布说明，并写入 .rmmp/dist/RELEASE_NOTES.md
+        
+        Args:
+            project_path: 项目路径
+            repo: GitHub 仓库 owner/repo
+            tag: Release 标签
+            assets: 要上传的文件，用于生成下载链接与 SHA-256
+            mirrors: 额外的镜像前缀，与配置中的 mirrors 合并
+            channel: 发布通道，存在 [publish.notes.channels.<name>] 时使用该模板
+            
+        Returns:
+            (发布说明内容, RELEASE_NOTES.md 路径)
+        """
+        ...
+
+    def verify_upstream(self, project_path: str, reserve: bool = True) -> dict[str, Any]:
+        """
+        发布到上游母仓库前，按上游索引检查模块 id 是否属于 UPSTREAM_ACCESS_TOKEN 对应的用户或组织
+        
+        Args:
+            project_path: 项目路径
+            reserve: id 尚未登记时通过上游接口预留
+            
+        Returns:
+            {id, login, status, owner}，status 为 owned / reserved / unclaimed
+            
+        Raises:
+            RmmError: 未配置上游索引或令牌、id 属于他人、预留失败；NetworkError: 请求失败
+        """
+        ...
+
+    def send_webhooks(self, project_path: str, tag: str, url: str, repo: str, channel: str | None = None) -> list[dict[str, Any]]:
+        """
+        发布完成后按 Rmake.toml [[publish.webhooks]] 通知聊天频道（Telegram、Discord 或通用 JSON）
+        
+        Args:
+            project_path: 项目路径
+            tag: 发布的 tag
+            url: Release 页面链接
+            repo: GitHub 仓库（owner/name）
+            channel: 发布通道，只通知 channels 为空或包含该通道的 webhook
+            
+        Returns:
+            每个 webhook 的结果 {name, ok, attempts, error}；单个失败不会抛出异常
+            
+        Raises:
+            RmmError: 无法读取 Rmake.toml 或 module.prop（解析失败时为 ConfigError）
+        """
+        ...
+
+    def publish_plan(self, project_path: str, version_code: int, channel: str | None = None, restart: bool = False) -> dict[str, Any]:
+        """
+        按 Rmake.toml [[publish.steps]] 规划本次发布（未配置时为 github-release → webhook）
+
+        同一 versionCode 与通道的上一次发布中断时，已完成的步骤标记为 done
+
+        Args:
+            project_path: 项目路径
+            version_code: module.prop 中的 versionCode
+            channel: 发布通道，用于步骤的 channels 条件
+            restart: 丢弃 .rmmp/publish-state.json 中的进度，从头执行
+
+        Returns:
+            {resumed, outputs, steps}；steps 中每项为 {id, run, command, status, reason}，
+            status 为 pending / done / skipped
+
+        Raises:
+            ConfigError: 无法读取 Rmake.toml、步骤重复或 command 步骤缺少命令
+        """
+        ...
+
+    def complete_publish_step(self, project_path: str, step: str, outputs: dict[str, str] | None = None) -> None:
+        """
+        记录发布步骤已完成
+
+        Args:
+            project_path: 项目路径
+            step: 步骤 id
+            outputs: 步骤输出（如 tag、url），恢复发布时由 publish_plan 返回
+        """
+        ...
+
+    def finish_publish(self, project_path: str) -> None:
+        """
+        全部发布步骤完成：记录本次 versionCode（用于 version_code_increased 条件）并清除进度
+        """
+        ...
+
+    def create_project(
+        self,
+        project_path: str,
+        project_id: str,
+        author: str | None = None,
+        email: str | None = None,
+        include_email: bool = False,
+        template: str | None = None,
+        license: str | None = None,
+        channels: list[str] = [],
+        git_init: bool = False,
+        version: str | None = None,
+        boot_scripts: list[str] = [],
+        force: bool = False,
+        mount: str | None = None,
+    ) -> str:
+        """
+        创建完整配置的模块项目（与 rmm init 相同），并注册到 meta.toml
+        
+        Args:
+            project_path: 项目目录，不存在时创建
+            project_id: 模块 ID
+            author: 作者，None 时依次使用 RMM_AUTHOR_NAME、meta.toml、Git 配置
+            email: 邮箱，None 时依次使用 RMM_AUTHOR_EMAIL、meta.toml、Git 配置
+            include_email: 将邮箱写入 rmmproject.toml（默认不写入）
+            template: 应用的已安装模板
+            license: 许可证 SPDX 标识，None 时默认 MIT
+            channels: 发布通道，写入 Rmake.toml [update.channels]
+            git_init: 目录不在 Git 仓库中时执行 git init
+            version: 初始版本，如 v0.1.0
+            boot_scripts: 生成的启动脚本，如 service.sh、post-fs-data.sh
+            force: 允许在主目录、根目录等位置创建
+            mount: 挂载方式（magic / overlayfs / bind），写入 Rmake.toml [control] mount
+            
+        Returns:
+            项目的绝对路径
+            
+        Raises:
+            ValueError: 选项无效（ID 格式、未知的启动脚本、挂载方式等）
+        """
+        ...
+
+    def resolve_config(
+        self,
+        key: str,
+        project_path: str | None = None,
+        cli_value: str | None = None,
+    ) -> dict[str, str | None]:
+        """
+        按分层配置解析配置项，优先级：命令行 > 环境变量 > 项目 > 全局 meta.toml > 默认值
+        
+        Args:
+            key: 配置项，如 github.token、device.serial、timeouts.adb
+            project_path: 项目路径，None 时跳过项目层
+            cli_value: 命令行传入的值
+            
+        Returns:
+            {"key", "value", "layer", "source"}，value 未设置时为 None
+            
+        Raises:
+            KeyError: 未知的配置项
+        """
+        ...
+    
+    def set_event_callback(self, callback: Callable[[str, str], None] | None = None) -> None:
+        """
+        订阅核心事件（扫描跳过的路径、脚本输出等），默认不输出任何内容
+
+        Args:
+            callback: 回调函数 callback(level, message)，level 为 debug / info；传入 None 取消订阅
+        """
+        ...
+
+    def list_zip_entries(self, zip_path: str) -> list[dict[str, Any]]:
+        """
+        列出模块 zip 中的条目，无需解压
+        
+        Args:
+            zip_path: zip 文件路径
+            
+        Returns:
+            条目字典列表，包含以下字段：
+            - name: 条目名称
+            - size: 解压后大小
+            - compressed_size: 压缩后大小
+            - is_dir: 是否为目录
+            - sha256: 文件内容的 SHA-256（目录为 None）
+            - unix_mode: Unix 权限位（如有）
+            
+        Raises:
+            RmmError: 当文件不存在或不是有效的 zip 时
+        """
+        ...
+    
+    def read_zip_member(self, zip_path: str, member: str) -> bytes:
+        """
+        读取模块 zip 中指定成员的内容，无需解压
+        
+        Args:
+            zip_path: zip 文件路径
+            member: 成员名称，如 module.prop
+            
+        Returns:
+            成员的原始内容
+            
+        Raises:
+            RmmError: 当成员不存在时
+        """
+        ...
+    
+    def inspect_zip(self, zip_path: str) -> dict[str, Any]:
+        """
+        检查模块 zip
+        
+        Args:
+            zip_path: zip 文件路径
+            
+        Returns:
+            检查结果字典，包含以下字段：
+            - path / size / sha256: zip 文件本身的信息
+            - module_prop: 解析后的 module.prop（缺失时为 None）
+            - has_update_binary: 是否包含 update-binary
+            - has_updater_script: 是否包含 updater-script
+            - entries: 同 list_zip_entries
+        """
+        ...
+
     def get_cache_stats(self) -> dict[str, bool | int]:
         """
         获取缓存统计信息
@@ -274,6 +557,19 @@ class RmmCore:
         """
         ...
     
+    def get_cache_statistics(self) -> list[dict[str, str | int]]:
+        """
+        获取各缓存（meta / project / git）的统计
+        
+        Returns:
+            每个缓存一项：
+            - cache: 缓存名称
+            - hits / misses: 命中与未命中次数
+            - entries / expired: 条目数与其中已过期的数量
+            - ttl_secs: 有效期（秒），可在 meta.toml [cache] 中调整
+        """
+        ...
+    
     def clear_all_cache(self) -> None:
         """清理所有缓存"""
         ...
@@ -1,5 +1,4 @@
 import json
-import os
 import requests
 from pathlib import Path
 from datetime import datetime, timedelta
@@ -15,7 +14,12 @@ class ProxyManagerMeta(type):
         """
         Returns the root directory of the proxy manager.
         """
-        return Path(os.getenv("RMM_ROOT", Path.home() / "data" / "adb" / ".rmm"))
+        try:
+            from pyrmm.cli.rmmcore import RmmCore
+            return Path(RmmCore().get_rmm_root())
+        except ImportError:
+            from pyrmm.utils.storage import rmm_root
+            return rmm_root()
 
     @property
     def CACHE(cls):
@@ -0,0 +1,37 @@
+"""
+RMM_ROOT 解析（未安装 Rust 扩展时的回退实现，与 rust/src/core/storage.rs 保持一致）
+"""
+import os
+import sys
+from pathlib import Path
+
+
+def legacy_root() -> Path:
+    """旧版默认位置 ~/data/adb/.rmm"""
+    return Path.home() / "data" / "adb" / ".rmm"
+
+
+def preferred_root() -> Path:
+    """不考虑旧版数据时的存储位置：Termux 前缀 > %APPDATA% / XDG 数据目录"""
+    prefix = os.getenv("PREFIX", "")
+    if prefix and (os.getenv("TERMUX_VERSION") or "com.termux" in prefix):
+        return Path(prefix) / "var" / "lib" / "rmm"
+    if sys.platform == "win32":
+        appdata = os.getenv("APPDATA")
+        if appdata:
+            return Path(appdata) / "rmm"
+        return legacy_root()
+    data_home = os.getenv("XDG_DATA_HOME") or str(Path.home() / ".local" / "share")
+    return Path(data_home) / "rmm"
+
+
+def rmm_root() -> Path:
+    """RMM_ROOT > 平台位置；旧版位置有数据而平台位置还没有时继续使用旧版位置（直到 rmm env --migrate）"""
+    env_root = os.getenv("RMM_ROOT")
+    if env_root:
+        return Path(env_root)
+    preferred = preferred_root()
+    legacy = legacy_root()
+    if (legacy / "meta.toml").exists() and not (preferred / "meta.toml").exists():
+        return legacy
+    return preferred